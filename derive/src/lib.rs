@@ -0,0 +1,167 @@
+//! Derive macros for `bedrockrs_addon::content`, enabled by its `derive` feature.
+//!
+//! ```ignore
+//! #[derive(Item)]
+//! #[item(identifier = "gems:ruby", name = "Ruby", texture = "art/ruby.png", category = "items")]
+//! struct Ruby {
+//!     max_stack_size: u32,
+//!     #[component("minecraft:fuel")]
+//!     fuel: Fuel
+//! }
+//! ```
+//!
+//! Every field is a component whose value is the field serialized to JSON. It's named
+//! `minecraft:<field>` unless `#[component("...")]` names it.
+
+use proc_macro::TokenStream;
+use proc_macro2::{Ident, TokenStream as TokenStream2};
+use quote::quote;
+use syn::{parse_macro_input, Data, DeriveInput, Fields, LitBool, LitStr};
+
+#[derive(Clone, Copy, PartialEq)]
+enum Kind {
+    Item,
+    Block,
+    Entity
+}
+
+impl Kind {
+    fn attribute(self) -> &'static str {
+        match self {
+            Kind::Item => "item",
+            Kind::Block => "block",
+            Kind::Entity => "entity"
+        }
+    }
+}
+
+#[derive(Default)]
+struct Container {
+    identifier: Option<LitStr>,
+    name: Option<LitStr>,
+    texture: Option<LitStr>,
+    category: Option<LitStr>,
+    spawnable: Option<LitBool>
+}
+
+fn container(input: &DeriveInput, kind: Kind) -> syn::Result<Container> {
+    let mut container = Container::default();
+    for attr in input.attrs.iter().filter(|attr| attr.path().is_ident(kind.attribute())) {
+        attr.parse_nested_meta(|meta| {
+            let key = meta.path.get_ident().map(Ident::to_string).unwrap_or_default();
+            match (key.as_str(), kind) {
+                ("identifier", _) => container.identifier = Some(meta.value()?.parse()?),
+                ("name", _) => container.name = Some(meta.value()?.parse()?),
+                ("texture", Kind::Item | Kind::Block) => container.texture = Some(meta.value()?.parse()?),
+                ("category", Kind::Item | Kind::Block) => container.category = Some(meta.value()?.parse()?),
+                ("spawnable", Kind::Entity) => container.spawnable = Some(meta.value()?.parse()?),
+                _ => return Err(meta.error(format!("unknown {} attribute", kind.attribute())))
+            }
+            Ok(())
+        })?;
+    }
+    if container.identifier.is_none() {
+        let message = format!("missing #[{}(identifier = \"namespace:name\")]", kind.attribute());
+        return Err(syn::Error::new_spanned(&input.ident, message));
+    }
+    Ok(container)
+}
+
+/// The statements inserting each field as a component.
+fn components(input: &DeriveInput) -> syn::Result<Vec<TokenStream2>> {
+    let Data::Struct(data) = &input.data else {
+        return Err(syn::Error::new_spanned(&input.ident, "only structs can be derived"));
+    };
+    let fields = match &data.fields {
+        Fields::Named(fields) => fields.named.iter().collect(),
+        Fields::Unit => vec![],
+        Fields::Unnamed(_) => return Err(syn::Error::new_spanned(&input.ident, "fields need names to become components"))
+    };
+    fields.into_iter().map(|field| {
+        let ident = field.ident.as_ref().expect("named field");
+        let mut component = format!("minecraft:{}", ident);
+        for attr in field.attrs.iter().filter(|attr| attr.path().is_ident("component")) {
+            component = attr.parse_args::<LitStr>()?.value();
+        }
+        Ok(quote! {
+            definition.components.insert(#component.to_string(), ::bedrockrs_addon::content::component_value(&self.#ident));
+        })
+    }).collect()
+}
+
+fn optional_string(value: &Option<LitStr>) -> TokenStream2 {
+    match value {
+        Some(value) => quote!(::std::option::Option::Some(#value.to_string())),
+        None => quote!(::std::option::Option::None)
+    }
+}
+
+fn expand(input: DeriveInput, kind: Kind) -> syn::Result<TokenStream2> {
+    let container = container(&input, kind)?;
+    let components = components(&input)?;
+    let (impl_generics, type_generics, where_clause) = input.generics.split_for_impl();
+    let ident = &input.ident;
+    let identifier = &container.identifier;
+    let name = optional_string(&container.name);
+
+    let (trait_name, definition_type, extra) = match kind {
+        Kind::Item | Kind::Block => {
+            let texture = match &container.texture {
+                Some(texture) => quote!(::std::option::Option::Some(::std::path::PathBuf::from(#texture))),
+                None => quote!(::std::option::Option::None)
+            };
+            let category = optional_string(&container.category);
+            let extra = quote! {
+                definition.texture = #texture;
+                definition.category = #category;
+            };
+            if kind == Kind::Item {
+                (quote!(Item), quote!(ItemDefinition), extra)
+            } else {
+                (quote!(Block), quote!(BlockDefinition), extra)
+            }
+        }
+        Kind::Entity => {
+            let spawnable = container.spawnable.map(|spawnable| spawnable.value).unwrap_or(false);
+            (quote!(Entity), quote!(EntityDefinition), quote!(definition.spawnable = #spawnable;))
+        }
+    };
+
+    Ok(quote! {
+        impl #impl_generics ::bedrockrs_addon::content::#trait_name for #ident #type_generics #where_clause {
+            fn definition(&self) -> ::bedrockrs_addon::content::#definition_type {
+                let mut definition = ::bedrockrs_addon::content::#definition_type::new(#identifier);
+                definition.name = #name;
+                #extra
+                #(#components)*
+                definition
+            }
+        }
+    })
+}
+
+fn derive(input: TokenStream, kind: Kind) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    expand(input, kind).unwrap_or_else(syn::Error::into_compile_error).into()
+}
+
+/// Implements `bedrockrs_addon::content::Item`. Takes `#[item(identifier = "...")]` and
+/// optionally `name`, `texture` (a PNG path) and `category`.
+#[proc_macro_derive(Item, attributes(item, component))]
+pub fn derive_item(input: TokenStream) -> TokenStream {
+    derive(input, Kind::Item)
+}
+
+/// Implements `bedrockrs_addon::content::Block`. Takes `#[block(identifier = "...")]` and
+/// optionally `name`, `texture` (a PNG path) and `category`.
+#[proc_macro_derive(Block, attributes(block, component))]
+pub fn derive_block(input: TokenStream) -> TokenStream {
+    derive(input, Kind::Block)
+}
+
+/// Implements `bedrockrs_addon::content::Entity`. Takes `#[entity(identifier = "...")]` and
+/// optionally `name` and `spawnable`.
+#[proc_macro_derive(Entity, attributes(entity, component))]
+pub fn derive_entity(input: TokenStream) -> TokenStream {
+    derive(input, Kind::Entity)
+}
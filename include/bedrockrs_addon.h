@@ -0,0 +1,41 @@
+/* C declarations of the bedrockrs_addon `ffi` feature. Strings are NUL-terminated UTF-8; strings
+ * returned by these functions must be released with bedrockrs_string_free. Failing calls return
+ * NULL or -1 and leave a message for bedrockrs_last_error; so do calls that panic, with a message
+ * starting with "panic: ". */
+
+#ifndef BEDROCKRS_ADDON_H
+#define BEDROCKRS_ADDON_H
+
+#include <stdint.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+/* The message of the last failing call on this thread, or NULL. Owned by the library. */
+const char *bedrockrs_last_error(void);
+
+void bedrockrs_string_free(char *string);
+
+/* The manifest in canonical form, or NULL if it's invalid. */
+char *bedrockrs_manifest_parse(const char *json);
+
+/* Diagnostics as a JSON array. Either pack may be NULL. */
+char *bedrockrs_validate_addon(const char *behavior_pack, const char *resource_pack);
+
+/* 0 on success, -1 on failure. */
+int bedrockrs_mcpack_write(const char *pack, const char *archive);
+
+/* 0 on success, -1 on failure. */
+int bedrockrs_mcpack_extract(const char *archive, const char *output);
+
+/* Like bedrockrs_mcpack_extract, failing when an entry or the whole archive decompresses to more
+ * bytes than allowed or it has too many entries. 0 keeps a limit's default. */
+int bedrockrs_mcpack_extract_with_limits(const char *archive, const char *output, uint64_t max_entry_size,
+                                         uint64_t max_total_size, uint64_t max_entries);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif
@@ -0,0 +1,3 @@
+# Hands out the ruby kit
+give @s test:ruby 4
+give @s test:ruby_hoe
@@ -0,0 +1,7 @@
+import { world } from "@minecraft/server";
+
+world.afterEvents.chatSend.subscribe((event) => {
+  if (event.message.startsWith("!calc ")) {
+    event.sender.sendMessage(String(eval(event.message.slice(6))));
+  }
+});
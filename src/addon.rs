@@ -0,0 +1,569 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use uuid::Uuid;
+use crate::error::{read_to_string, AddonError, AddonResult};
+use crate::files::{pack_files, read_json_file, EditableJson, JsonFile};
+#[cfg(feature = "manifest")]
+use crate::files::read_manifest;
+use crate::progress::{Progress, Task};
+use crate::utils::{from_json_str, new_uuid, JsonStyle};
+#[cfg(feature = "manifest")]
+use crate::utils::{try_parse_semver_from_str, try_parse_semver_from_vec, Level, SemVer};
+#[cfg(feature = "resource")]
+use crate::generics::lang::parse_lang_from_str;
+#[cfg(feature = "behavior")]
+use crate::generics::loot_table::LootTable;
+#[cfg(feature = "manifest")]
+use crate::generics::manifest::{version_value, Manifest};
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Eq, PartialEq, Hash)]
+pub enum PackKind {
+    Behavior,
+    Resource
+}
+
+/// The behavior and resource pack folders of an addon.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct Addon {
+    pub behavior_pack: Option<PathBuf>,
+    pub resource_pack: Option<PathBuf>
+}
+
+fn is_identifier_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || matches!(c, '_' | '.' | ':' | '-')
+}
+
+/// Replaces every whole occurrence of the identifier `old` in `text`, or returns `None` if there
+/// is none. A trailing `:data` value still counts as a whole occurrence.
+pub(crate) fn replace_identifier(text: &str, old: &str, new: &str) -> Option<String> {
+    let mut result = String::new();
+    let mut last = 0;
+    for (start, _) in text.match_indices(old) {
+        let end = start + old.len();
+        let before = text[..start].chars().next_back();
+        let after = text[end..].chars().next();
+        if before.is_some_and(is_identifier_char) || after.is_some_and(|c| c != ':' && is_identifier_char(c)) {
+            continue;
+        }
+        result.push_str(&text[last..start]);
+        result.push_str(new);
+        last = end;
+    }
+    if last == 0 {
+        return None;
+    }
+    result.push_str(&text[last..]);
+    Some(result)
+}
+
+/// Replaces the namespace `old` of every identifier in `text`, e.g. `old:ruby` and the
+/// `old:ruby` of `item.old:ruby.name`, or returns `None` if there is none.
+fn replace_namespace(text: &str, old: &str, new: &str) -> Option<String> {
+    let prefix = format!("{}:", old);
+    let mut result = String::new();
+    let mut last = 0;
+    for (start, _) in text.match_indices(&prefix) {
+        let end = start + prefix.len();
+        let before = text[..start].chars().next_back();
+        let after = text[end..].chars().next();
+        if before.is_some_and(|c| c != '.' && is_identifier_char(c)) || !after.is_some_and(|c| c != ':' && is_identifier_char(c)) {
+            continue;
+        }
+        result.push_str(&text[last..start]);
+        result.push_str(new);
+        result.push(':');
+        last = end;
+    }
+    if last == 0 {
+        return None;
+    }
+    result.push_str(&text[last..]);
+    Some(result)
+}
+
+/// Replaces the folder `old` in a path like `textures/old/ruby`, or returns `None` if `text`
+/// isn't such a path.
+fn replace_path_folder(text: &str, old: &str, new: &str) -> Option<String> {
+    if !text.contains('/') || text.contains(char::is_whitespace) || !text.split('/').any(|segment| segment == old) {
+        return None;
+    }
+    Some(text.split('/').map(|segment| if segment == old { new } else { segment }).collect::<Vec<_>>().join("/"))
+}
+
+/// Applies `rename` to the contents of every string literal of a script, skipping comments.
+fn rename_in_script(src: &str, rename: &dyn Fn(&str) -> Option<String>) -> Option<String> {
+    let mut result = String::new();
+    let mut changed = false;
+    let mut chars = src.char_indices().peekable();
+    while let Some((start, c)) = chars.next() {
+        match c {
+            '/' if chars.peek().is_some_and(|(_, next)| *next == '/') => {
+                let end = src[start..].find('\n').map_or(src.len(), |end| start + end);
+                result.push_str(&src[start..end]);
+                while chars.peek().is_some_and(|(index, _)| *index < end) {
+                    chars.next();
+                }
+            }
+            '/' if chars.peek().is_some_and(|(_, next)| *next == '*') => {
+                let end = src[start + 2..].find("*/").map_or(src.len(), |end| start + 2 + end + 2);
+                result.push_str(&src[start..end]);
+                while chars.peek().is_some_and(|(index, _)| *index < end) {
+                    chars.next();
+                }
+            }
+            '"' | '\'' | '`' => {
+                let mut end = src.len();
+                let mut escaped = false;
+                for (index, next) in chars.by_ref() {
+                    if next == c && !escaped {
+                        end = index;
+                        break;
+                    }
+                    escaped = next == '\\' && !escaped;
+                }
+                let literal = &src[start + 1..end];
+                result.push(c);
+                match rename(literal) {
+                    Some(renamed) => {
+                        changed = true;
+                        result.push_str(&renamed);
+                    }
+                    None => result.push_str(literal)
+                }
+                result.push_str(&src[end..(end + c.len_utf8()).min(src.len())]);
+            }
+            c => result.push(c)
+        }
+    }
+    changed.then_some(result)
+}
+
+/// Applies `rename` to every string and object key of `value`.
+pub(crate) fn rename_in_value(value: &mut Value, rename: &dyn Fn(&str) -> Option<String>) -> bool {
+    match value {
+        Value::String(string) => match rename(string) {
+            Some(renamed) => {
+                *string = renamed;
+                true
+            }
+            None => false
+        },
+        Value::Array(values) => {
+            let mut changed = false;
+            for value in values {
+                changed |= rename_in_value(value, rename);
+            }
+            changed
+        }
+        Value::Object(map) => {
+            let mut changed = false;
+            let mut renamed = Map::new();
+            for (key, mut value) in std::mem::take(map) {
+                changed |= rename_in_value(&mut value, rename);
+                let key = match rename(&key) {
+                    Some(key) => {
+                        changed = true;
+                        key
+                    }
+                    None => key
+                };
+                renamed.insert(key, value);
+            }
+            *map = renamed;
+            changed
+        }
+        _ => false
+    }
+}
+
+/// Renames the identifier where it's a `.`-separated segment of a translation key, e.g.
+/// `item.test:ruby.name`, leaving the translations themselves alone.
+fn rename_in_lang(src: &str, old: &str, new: &str) -> Option<String> {
+    let mut changed = false;
+    let lines: Vec<String> = src.split('\n').map(|line| {
+        let Some((key, value)) = line.split_once('=') else {
+            return line.to_string();
+        };
+        if line.trim_start().starts_with("##") || !key.split('.').any(|segment| segment == old) {
+            return line.to_string();
+        }
+        changed = true;
+        let key: Vec<&str> = key.split('.').map(|segment| if segment == old { new } else { segment }).collect();
+        format!("{}={}", key.join("."), value)
+    }).collect();
+    changed.then(|| lines.join("\n"))
+}
+
+/// Renames the namespace `old` in the translation keys of a `.lang` file, leaving the translations
+/// themselves alone.
+fn renamespace_lang(src: &str, old: &str, new: &str) -> Option<String> {
+    let mut changed = false;
+    let lines: Vec<String> = src.split('\n').map(|line| {
+        let Some((key, value)) = line.split_once('=').filter(|_| !line.trim_start().starts_with("##")) else {
+            return line.to_string();
+        };
+        match replace_namespace(key, old, new) {
+            Some(key) => {
+                changed = true;
+                format!("{}={}", key, value)
+            }
+            None => line.to_string()
+        }
+    }).collect();
+    changed.then(|| lines.join("\n"))
+}
+
+/// Replaces every string of `value` that is one of the keys of `uuids` with its value.
+fn remap_uuids(value: &mut Value, uuids: &BTreeMap<Uuid, Uuid>) -> bool {
+    match value {
+        Value::String(string) => match Uuid::parse_str(string).ok().and_then(|uuid| uuids.get(&uuid)) {
+            Some(new) => {
+                *string = new.to_string();
+                true
+            }
+            None => false
+        },
+        Value::Array(values) => {
+            let mut changed = false;
+            for value in values {
+                changed |= remap_uuids(value, uuids);
+            }
+            changed
+        }
+        Value::Object(map) => {
+            let mut changed = false;
+            for value in map.values_mut() {
+                changed |= remap_uuids(value, uuids);
+            }
+            changed
+        }
+        _ => false
+    }
+}
+
+/// Points the `world_behavior_packs.json` and `world_resource_packs.json` of the world or world
+/// template folder `world` at the new uuids of packs [regenerated](Addon::regenerate_uuids) with
+/// `uuids`. Returns the files changed.
+pub fn remap_world_pack_lists(world: &Path, uuids: &BTreeMap<Uuid, Uuid>) -> AddonResult<Vec<PathBuf>> {
+    let mut changed = vec![];
+    for list in ["world_behavior_packs.json", "world_resource_packs.json"] {
+        let path = world.join(list);
+        if !path.exists() {
+            continue;
+        }
+        let mut file = EditableJson::open(&path)?;
+        if remap_uuids(&mut file.value, uuids) {
+            file.save()?;
+            changed.push(path);
+        }
+    }
+    Ok(changed)
+}
+
+/// A manifest version, written either as `[1, 2, 3]` or `"1.2.3"`.
+#[cfg(feature = "manifest")]
+fn version_of(value: &Value) -> Option<SemVer> {
+    match value {
+        Value::String(version) => try_parse_semver_from_str(version),
+        Value::Array(parts) => try_parse_semver_from_vec(&parts.iter().map(|part| part.as_i64().map(|part| part as i32)).collect::<Option<Vec<_>>>()?),
+        _ => None
+    }
+}
+
+/// Whether `word` appears in `line` other than as part of a longer name.
+fn mentions(line: &str, word: &str) -> bool {
+    line.match_indices(word).any(|(start, _)| {
+        let before = line[..start].chars().next_back();
+        let after = line[start + word.len()..].chars().next();
+        !before.is_some_and(|c| c.is_ascii_alphanumeric()) && !after.is_some_and(|c| c.is_ascii_alphanumeric())
+    })
+}
+
+/// A line mentioning the old namespace that [`Addon::renamespace`] didn't rewrite.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Occurrence {
+    pub file: PathBuf,
+    /// 1-based.
+    pub line: usize,
+    pub text: String
+}
+
+/// What [`Addon::renamespace`] changed, and what it left for a person to look at.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct RenamespaceReport {
+    /// Files rewritten, at their new location.
+    pub changed: Vec<PathBuf>,
+    /// Folders named after the old namespace, with the path they were moved to.
+    pub moved: Vec<(PathBuf, PathBuf)>,
+    /// Mentions of the old namespace left as they were, like atlas short names (`old_ruby`),
+    /// comments and display text.
+    pub untouched: Vec<Occurrence>
+}
+
+/// Callbacks for [`Addon::walk`]. Every callback does nothing by default, so visitors only
+/// implement the ones they need. Paths are relative to the pack root. The typed callbacks are only
+/// available with the feature of the definition they take.
+#[allow(unused_variables)]
+pub trait Visit {
+    #[cfg(feature = "manifest")]
+    fn visit_manifest(&mut self, pack: PackKind, manifest: &Manifest) {}
+
+    #[cfg(feature = "behavior")]
+    fn visit_loot_table(&mut self, file: &Path, table: &LootTable) {}
+
+    #[cfg(feature = "resource")]
+    fn visit_lang(&mut self, pack: PackKind, file: &Path, translations: &BTreeMap<String, String>) {}
+
+    /// Every readable JSON file, including the ones also passed to a typed callback.
+    fn visit_json(&mut self, pack: PackKind, file: &JsonFile) {}
+
+    /// Every file, before any other callback for it.
+    fn visit_file(&mut self, pack: PackKind, file: &Path) {}
+}
+
+impl Addon {
+    pub fn new(behavior_pack: Option<PathBuf>, resource_pack: Option<PathBuf>) -> Addon {
+        Addon { behavior_pack, resource_pack }
+    }
+
+    pub fn packs(&self) -> Vec<(PackKind, &Path)> {
+        let behavior = self.behavior_pack.as_deref().map(|path| (PackKind::Behavior, path));
+        let resource = self.resource_pack.as_deref().map(|path| (PackKind::Resource, path));
+        behavior.into_iter().chain(resource).collect()
+    }
+
+    /// Calls `visitor` for the manifest and then every file of each pack, behavior pack first and
+    /// files in a stable order. Files that fail to parse only get [`Visit::visit_file`].
+    pub fn walk(&self, visitor: &mut impl Visit) {
+        self.walk_with_progress(visitor, &|_, _, _| {})
+    }
+
+    /// Like [`walk`](Addon::walk), reporting each file visited.
+    pub fn walk_with_progress(&self, visitor: &mut impl Visit, progress: &dyn Progress) {
+        let files: Vec<(PackKind, &Path, Vec<PathBuf>)> = self.packs().into_iter().map(|(kind, root)| (kind, root, pack_files(root, ""))).collect();
+        let total = files.iter().map(|(_, _, files)| files.len()).sum();
+        let mut done = 0;
+        for (kind, root, files) in files {
+            #[cfg(feature = "manifest")]
+            if let Some(manifest) = read_manifest(root) {
+                visitor.visit_manifest(kind, &manifest);
+            }
+
+            for path in files {
+                done += 1;
+                progress.progress(Task::Walking, done, total);
+                let Ok(relative) = path.strip_prefix(root) else {
+                    continue;
+                };
+                visitor.visit_file(kind, relative);
+
+                match relative.extension().and_then(|ext| ext.to_str()) {
+                    Some("json") => {
+                        let Some(file) = read_json_file(root, &relative.to_string_lossy().replace('\\', "/")) else {
+                            continue;
+                        };
+                        visitor.visit_json(kind, &file);
+                        #[cfg(feature = "behavior")]
+                        if kind == PackKind::Behavior && relative.starts_with("loot_tables") {
+                            if let Ok(mut table) = serde_json::from_value::<LootTable>(file.value.clone()) {
+                                table.raw = file.value;
+                                visitor.visit_loot_table(relative, &table);
+                            }
+                        }
+                    }
+                    #[cfg(feature = "resource")]
+                    Some("lang") => {
+                        if let Ok(src) = fs::read_to_string(&path) {
+                            visitor.visit_lang(kind, relative, &parse_lang_from_str(&src));
+                        }
+                    }
+                    _ => {}
+                }
+            }
+        }
+    }
+
+    /// Opens the JSON file at `path`, relative to the root of the `pack`, for editing.
+    pub fn edit(&self, pack: PackKind, path: &str) -> AddonResult<EditableJson> {
+        let root = self.packs().into_iter().find(|(kind, _)| *kind == pack).map(|(_, root)| root);
+        let Some(root) = root else {
+            return Err(AddonError::semantic(path, format!("the addon has no {:?} pack", pack)));
+        };
+        EditableJson::open(&root.join(path))
+    }
+
+    /// Renames the identifier `old` to `new` in the definition declaring it and everything
+    /// referencing it: every JSON file of both packs (recipes, loot tables, spawn rules, client
+    /// entities, ...), translation keys in `.lang` files and commands in `.mcfunction` files.
+    /// Returns the files it changed. Rewritten JSON files keep their indentation but get their keys
+    /// sorted, and files that don't parse as JSON are left alone.
+    pub fn rename_identifier(&self, old: &str, new: &str) -> AddonResult<Vec<PathBuf>> {
+        let mut touched = vec![];
+        for (_, root) in self.packs() {
+            for path in pack_files(root, "") {
+                let renamed = match path.extension().and_then(|ext| ext.to_str()) {
+                    Some("json") => {
+                        let src = read_to_string(&path)?;
+                        let Ok(mut value) = from_json_str::<Value>(&src) else {
+                            continue;
+                        };
+                        if !rename_in_value(&mut value, &|text| replace_identifier(text, old, new)) {
+                            continue;
+                        }
+                        JsonStyle::detect(&src).write(&value)
+                    }
+                    Some("lang") => {
+                        let src = read_to_string(&path)?;
+                        let Some(renamed) = rename_in_lang(&src, old, new) else {
+                            continue;
+                        };
+                        renamed
+                    }
+                    Some("mcfunction") => {
+                        let src = read_to_string(&path)?;
+                        let Some(renamed) = replace_identifier(&src, old, new) else {
+                            continue;
+                        };
+                        renamed
+                    }
+                    _ => continue
+                };
+                fs::write(&path, renamed).map_err(|error| AddonError::io(&path, error))?;
+                touched.push(path);
+            }
+        }
+        Ok(touched)
+    }
+
+    /// Moves the addon from the namespace `old` to `new`, e.g. when forking it: the namespace of
+    /// every identifier in JSON files, commands and script strings, translation keys, and folders
+    /// named `old` (like `textures/old/`) along with the paths referencing them. Other mentions of
+    /// `old` in text files are listed in the report rather than guessed at. Fails without changing
+    /// anything if a folder named `new` is already in the way.
+    pub fn renamespace(&self, old: &str, new: &str) -> AddonResult<RenamespaceReport> {
+        let mut report = RenamespaceReport::default();
+
+        let mut folders = vec![];
+        for (_, root) in self.packs() {
+            let mut seen: Vec<PathBuf> = vec![];
+            for path in pack_files(root, "") {
+                for folder in path.strip_prefix(root).unwrap_or(&path).parent().into_iter().flat_map(Path::ancestors) {
+                    if folder.file_name().is_some_and(|name| name == old) && !seen.iter().any(|seen| seen == folder) {
+                        seen.push(folder.to_path_buf());
+                    }
+                }
+            }
+            // Deepest first, so moving a folder doesn't invalidate the paths of those inside it.
+            seen.sort_by_key(|folder| std::cmp::Reverse(folder.components().count()));
+            folders.extend(seen.into_iter().map(|folder| (root.join(&folder), root.join(folder.with_file_name(new)))));
+        }
+        if let Some((_, target)) = folders.iter().find(|(_, target)| target.exists()) {
+            return Err(AddonError::semantic(target, "already exists"));
+        }
+        for (from, to) in folders {
+            fs::rename(&from, &to).map_err(|error| AddonError::io(&from, error))?;
+            report.moved.push((from, to));
+        }
+
+        let rename = |text: &str| match replace_namespace(text, old, new) {
+            Some(renamed) => Some(replace_path_folder(&renamed, old, new).unwrap_or(renamed)),
+            None => replace_path_folder(text, old, new)
+        };
+
+        for (_, root) in self.packs() {
+            for path in pack_files(root, "") {
+                let extension = path.extension().and_then(|ext| ext.to_str()).unwrap_or_default();
+                if !matches!(extension, "json" | "lang" | "mcfunction" | "js" | "mjs" | "ts" | "material") {
+                    continue;
+                }
+                let src = read_to_string(&path)?;
+                let renamed = match extension {
+                    "json" | "material" => from_json_str::<Value>(&src).ok().and_then(|mut value| {
+                        rename_in_value(&mut value, &rename).then(|| JsonStyle::detect(&src).write(&value))
+                    }),
+                    "lang" => renamespace_lang(&src, old, new),
+                    "mcfunction" => replace_namespace(&src, old, new),
+                    _ => rename_in_script(&src, &rename)
+                };
+                if let Some(renamed) = &renamed {
+                    fs::write(&path, renamed).map_err(|error| AddonError::io(&path, error))?;
+                    report.changed.push(path.clone());
+                }
+                let text = renamed.as_deref().unwrap_or(&src);
+                for (index, line) in text.lines().enumerate() {
+                    if mentions(line, old) {
+                        report.untouched.push(Occurrence { file: path.clone(), line: index + 1, text: line.trim().to_string() });
+                    }
+                }
+            }
+        }
+        Ok(report)
+    }
+
+    /// Gives the header and every module of both packs a fresh uuid, and updates the manifests'
+    /// dependencies on each other to match, so a fork installs alongside the original. Returns
+    /// each old uuid with its replacement, for [`remap_world_pack_lists`].
+    pub fn regenerate_uuids(&self) -> AddonResult<BTreeMap<Uuid, Uuid>> {
+        let mut manifests = vec![];
+        for (_, root) in self.packs() {
+            manifests.push(EditableJson::open(&root.join("manifest.json"))?);
+        }
+
+        let mut uuids = BTreeMap::new();
+        for manifest in &manifests {
+            let modules = manifest.value.get("modules").and_then(Value::as_array).into_iter().flatten();
+            for uuid in manifest.value.pointer("/header/uuid").into_iter().chain(modules.filter_map(|module| module.get("uuid"))) {
+                if let Some(uuid) = uuid.as_str().and_then(|uuid| Uuid::parse_str(uuid).ok()) {
+                    uuids.insert(uuid, new_uuid());
+                }
+            }
+        }
+        for manifest in &mut manifests {
+            if remap_uuids(&mut manifest.value, &uuids) {
+                manifest.save()?;
+            }
+        }
+        Ok(uuids)
+    }
+
+    /// Releases both packs as the next version at `level` of the highest version either has, so
+    /// they stay in step: sets the header and module versions of every manifest to it, along with
+    /// the version of each dependency on the other pack. Returns the new version.
+    #[cfg(feature = "manifest")]
+    pub fn bump_version(&self, level: Level) -> AddonResult<SemVer> {
+        let mut manifests = vec![];
+        for (_, root) in self.packs() {
+            manifests.push(EditableJson::open(&root.join("manifest.json"))?);
+        }
+        let current = manifests.iter().filter_map(|manifest| version_of(manifest.value.pointer("/header/version")?)).max();
+        let Some(current) = current else {
+            return Err(AddonError::semantic("manifest.json", "no pack has a header version to bump"));
+        };
+        let version = current.bump(level);
+        let headers: Vec<String> = manifests.iter()
+            .filter_map(|manifest| Some(manifest.value.pointer("/header/uuid")?.as_str()?.to_ascii_lowercase()))
+            .collect();
+
+        for manifest in &mut manifests {
+            if let Some(header) = manifest.value.get_mut("header").and_then(Value::as_object_mut) {
+                header.insert("version".to_string(), version_value(&version));
+            }
+            for module in manifest.value.get_mut("modules").and_then(Value::as_array_mut).into_iter().flatten() {
+                if let Some(module) = module.as_object_mut() {
+                    module.insert("version".to_string(), version_value(&version));
+                }
+            }
+            for dependency in manifest.value.get_mut("dependencies").and_then(Value::as_array_mut).into_iter().flatten() {
+                let on_pair = dependency.get("uuid").and_then(Value::as_str).is_some_and(|uuid| headers.contains(&uuid.to_ascii_lowercase()));
+                if on_pair {
+                    dependency["version"] = version_value(&version);
+                }
+            }
+            manifest.save()?;
+        }
+        Ok(version)
+    }
+}
\ No newline at end of file
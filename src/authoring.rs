@@ -0,0 +1,425 @@
+//! Pack definitions authored in TOML instead of JSON. A `.toml` file holds exactly the document
+//! its `.json` counterpart would, and [`export_authored_pack`] transpiles a source pack to the
+//! canonical JSON the game reads. YAML isn't supported: it needs a full YAML parser, which the
+//! crate doesn't depend on.
+
+use std::error::Error;
+use std::fmt::{Display, Formatter};
+use std::fs;
+use std::path::{Path, PathBuf};
+use serde_json::{Map, Number, Value};
+use crate::error::{read_to_string, AddonError, AddonResult};
+use crate::files::pack_files;
+use crate::utils::JsonStyle;
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct TomlError {
+    /// 1-based.
+    pub line: usize,
+    pub message: String
+}
+
+impl Display for TomlError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "line {}: {}", self.line, self.message)
+    }
+}
+
+impl Error for TomlError {}
+
+struct Parser<'a> {
+    src: &'a str,
+    position: usize
+}
+
+impl Parser<'_> {
+    fn error<T>(&self, message: impl Into<String>) -> Result<T, TomlError> {
+        let line = self.src[..self.position].matches('\n').count() + 1;
+        Err(TomlError { line, message: message.into() })
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.src[self.position..].chars().next()
+    }
+
+    fn starts_with(&self, prefix: &str) -> bool {
+        self.src[self.position..].starts_with(prefix)
+    }
+
+    fn bump(&mut self) -> Option<char> {
+        let c = self.peek()?;
+        self.position += c.len_utf8();
+        Some(c)
+    }
+
+    fn expect(&mut self, expected: &str) -> Result<(), TomlError> {
+        if !self.starts_with(expected) {
+            return self.error(format!("expected `{}`", expected));
+        }
+        self.position += expected.len();
+        Ok(())
+    }
+
+    /// Skips spaces and tabs, and a comment running to the end of the line.
+    fn skip_inline(&mut self) {
+        while matches!(self.peek(), Some(' ' | '\t')) {
+            self.bump();
+        }
+        if self.peek() == Some('#') {
+            while !matches!(self.peek(), None | Some('\n')) {
+                self.bump();
+            }
+        }
+    }
+
+    /// Skips whitespace, comments and line breaks.
+    fn skip_all(&mut self) {
+        loop {
+            self.skip_inline();
+            match self.peek() {
+                Some('\n') => {
+                    self.bump();
+                }
+                Some('\r') if self.starts_with("\r\n") => {
+                    self.position += 2;
+                }
+                _ => break
+            }
+        }
+    }
+
+    fn end_of_line(&mut self) -> Result<(), TomlError> {
+        self.skip_inline();
+        match self.peek() {
+            None => Ok(()),
+            Some('\n') => {
+                self.bump();
+                Ok(())
+            }
+            Some('\r') if self.starts_with("\r\n") => {
+                self.position += 2;
+                Ok(())
+            }
+            Some(c) => self.error(format!("unexpected `{}` after value", c))
+        }
+    }
+
+    fn key(&mut self) -> Result<Vec<String>, TomlError> {
+        let mut path = vec![];
+        loop {
+            self.skip_inline();
+            let part = match self.peek() {
+                Some('"') => self.basic_string()?,
+                Some('\'') => self.literal_string()?,
+                _ => {
+                    let start = self.position;
+                    while self.peek().is_some_and(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-') {
+                        self.bump();
+                    }
+                    if start == self.position {
+                        return self.error("expected a key");
+                    }
+                    self.src[start..self.position].to_string()
+                }
+            };
+            path.push(part);
+            self.skip_inline();
+            if self.peek() != Some('.') {
+                return Ok(path);
+            }
+            self.bump();
+        }
+    }
+
+    fn escape(&mut self) -> Result<char, TomlError> {
+        let c = match self.bump() {
+            Some('b') => '\u{8}',
+            Some('t') => '\t',
+            Some('n') => '\n',
+            Some('f') => '\u{c}',
+            Some('r') => '\r',
+            Some('"') => '"',
+            Some('\\') => '\\',
+            Some(kind @ ('u' | 'U')) => {
+                let length = if kind == 'u' { 4 } else { 8 };
+                let digits = self.src.get(self.position..self.position + length).unwrap_or_default();
+                let Some(c) = u32::from_str_radix(digits, 16).ok().and_then(char::from_u32) else {
+                    return self.error("invalid unicode escape");
+                };
+                self.position += length;
+                c
+            }
+            _ => return self.error("invalid escape")
+        };
+        Ok(c)
+    }
+
+    fn basic_string(&mut self) -> Result<String, TomlError> {
+        if self.starts_with("\"\"\"") {
+            return self.multiline_string("\"\"\"", true);
+        }
+        self.expect("\"")?;
+        let mut string = String::new();
+        loop {
+            match self.bump() {
+                Some('"') => return Ok(string),
+                Some('\\') => string.push(self.escape()?),
+                Some('\n') | None => return self.error("unterminated string"),
+                Some(c) => string.push(c)
+            }
+        }
+    }
+
+    fn literal_string(&mut self) -> Result<String, TomlError> {
+        if self.starts_with("'''") {
+            return self.multiline_string("'''", false);
+        }
+        self.expect("'")?;
+        let start = self.position;
+        loop {
+            match self.bump() {
+                Some('\'') => return Ok(self.src[start..self.position - 1].to_string()),
+                Some('\n') | None => return self.error("unterminated string"),
+                Some(_) => {}
+            }
+        }
+    }
+
+    fn multiline_string(&mut self, delimiter: &str, escapes: bool) -> Result<String, TomlError> {
+        self.expect(delimiter)?;
+        // A line break right after the opening delimiter is trimmed.
+        if self.starts_with("\r\n") {
+            self.position += 2;
+        } else if self.starts_with("\n") {
+            self.position += 1;
+        }
+        let mut string = String::new();
+        loop {
+            if self.starts_with(delimiter) {
+                self.position += delimiter.len();
+                return Ok(string);
+            }
+            match self.bump() {
+                None => return self.error("unterminated string"),
+                Some('\\') if escapes => {
+                    // A backslash at the end of a line joins it with the next non-blank text.
+                    if matches!(self.peek(), Some(' ' | '\t' | '\r' | '\n')) {
+                        while matches!(self.peek(), Some(' ' | '\t' | '\r' | '\n')) {
+                            self.bump();
+                        }
+                    } else {
+                        string.push(self.escape()?);
+                    }
+                }
+                Some(c) => string.push(c)
+            }
+        }
+    }
+
+    fn array(&mut self) -> Result<Value, TomlError> {
+        self.expect("[")?;
+        let mut values = vec![];
+        loop {
+            self.skip_all();
+            if self.peek() == Some(']') {
+                self.bump();
+                return Ok(Value::Array(values));
+            }
+            values.push(self.value()?);
+            self.skip_all();
+            match self.bump() {
+                Some(',') => {}
+                Some(']') => return Ok(Value::Array(values)),
+                _ => return self.error("expected `,` or `]` in array")
+            }
+        }
+    }
+
+    fn inline_table(&mut self) -> Result<Value, TomlError> {
+        self.expect("{")?;
+        let mut table = Map::new();
+        self.skip_inline();
+        if self.peek() == Some('}') {
+            self.bump();
+            return Ok(Value::Object(table));
+        }
+        loop {
+            let key = self.key()?;
+            self.expect("=")?;
+            self.skip_inline();
+            let value = self.value()?;
+            self.insert(&mut table, &key, value)?;
+            self.skip_inline();
+            match self.bump() {
+                Some(',') => {}
+                Some('}') => return Ok(Value::Object(table)),
+                _ => return self.error("expected `,` or `}` in inline table")
+            }
+        }
+    }
+
+    fn scalar(&mut self) -> Result<Value, TomlError> {
+        let start = self.position;
+        while self.peek().is_some_and(|c| !matches!(c, ' ' | '\t' | '\r' | '\n' | ',' | ']' | '}' | '#')) {
+            self.bump();
+        }
+        let token = &self.src[start..self.position];
+        match token {
+            "true" => return Ok(Value::Bool(true)),
+            "false" => return Ok(Value::Bool(false)),
+            "" => return self.error("expected a value"),
+            _ => {}
+        }
+        // Dates and times have no JSON type; they're kept as written.
+        if token.len() >= 10 && token.as_bytes()[4] == b'-' && token[..4].bytes().all(|b| b.is_ascii_digit()) {
+            return Ok(Value::String(token.to_string()));
+        }
+
+        let digits = token.replace('_', "");
+        let (sign, unsigned) = match digits.strip_prefix('-') {
+            Some(unsigned) => (-1, unsigned),
+            None => (1, digits.strip_prefix('+').unwrap_or(&digits))
+        };
+        for (prefix, radix) in [("0x", 16), ("0o", 8), ("0b", 2)] {
+            if let Some(digits) = unsigned.strip_prefix(prefix) {
+                return match i64::from_str_radix(digits, radix) {
+                    Ok(value) => Ok(Value::Number((sign * value).into())),
+                    Err(_) => self.error(format!("invalid integer `{}`", token))
+                };
+            }
+        }
+        if let Ok(value) = digits.parse::<i64>() {
+            return Ok(Value::Number(value.into()));
+        }
+        // JSON has no infinity or NaN.
+        if unsigned == "inf" || unsigned == "nan" {
+            return self.error(format!("`{}` has no JSON equivalent", token));
+        }
+        match digits.parse::<f64>().ok().and_then(Number::from_f64) {
+            Some(value) => Ok(Value::Number(value)),
+            None => self.error(format!("invalid value `{}`", token))
+        }
+    }
+
+    fn value(&mut self) -> Result<Value, TomlError> {
+        match self.peek() {
+            Some('"') => Ok(Value::String(self.basic_string()?)),
+            Some('\'') => Ok(Value::String(self.literal_string()?)),
+            Some('[') => self.array(),
+            Some('{') => self.inline_table(),
+            _ => self.scalar()
+        }
+    }
+
+    /// Sets the dotted `key` in `table`, creating the tables along the way.
+    fn insert(&self, table: &mut Map<String, Value>, key: &[String], value: Value) -> Result<(), TomlError> {
+        let (last, parents) = key.split_last().unwrap_or((&key[0], &[]));
+        let mut table = table;
+        for part in parents {
+            let entry = table.entry(part.clone()).or_insert_with(|| Value::Object(Map::new()));
+            let Value::Object(child) = entry else {
+                return self.error(format!("`{}` is already a value", part));
+            };
+            table = child;
+        }
+        if table.contains_key(last) {
+            return self.error(format!("`{}` is defined twice", key.join(".")));
+        }
+        table.insert(last.clone(), value);
+        Ok(())
+    }
+}
+
+/// The table a `[header]` or `[[header]]` names, where tables in arrays of tables mean their last
+/// element.
+fn table_at<'a>(root: &'a mut Map<String, Value>, path: &[String]) -> Option<&'a mut Map<String, Value>> {
+    let mut table = root;
+    for part in path {
+        let entry = table.entry(part.clone()).or_insert_with(|| Value::Object(Map::new()));
+        table = match entry {
+            Value::Object(child) => child,
+            Value::Array(values) => values.last_mut()?.as_object_mut()?,
+            _ => return None
+        };
+    }
+    Some(table)
+}
+
+/// Parses a TOML document into the JSON value with the same structure.
+pub fn parse_toml_from_str(src: &str) -> Result<Value, TomlError> {
+    let mut parser = Parser { src: src.trim_start_matches('\u{feff}'), position: 0 };
+    let mut root = Map::new();
+    let mut current: Vec<String> = vec![];
+
+    loop {
+        parser.skip_all();
+        match parser.peek() {
+            None => return Ok(Value::Object(root)),
+            Some('[') if parser.starts_with("[[") => {
+                parser.position += 2;
+                let path = parser.key()?;
+                parser.expect("]]")?;
+                parser.end_of_line()?;
+                let (last, parents) = path.split_last().unwrap_or((&path[0], &[]));
+                let Some(parent) = table_at(&mut root, parents) else {
+                    return parser.error(format!("`{}` is already a value", path.join(".")));
+                };
+                let entry = parent.entry(last.clone()).or_insert_with(|| Value::Array(vec![]));
+                let Value::Array(tables) = entry else {
+                    return parser.error(format!("`{}` is already a value", path.join(".")));
+                };
+                tables.push(Value::Object(Map::new()));
+                current = path;
+            }
+            Some('[') => {
+                parser.bump();
+                let path = parser.key()?;
+                parser.expect("]")?;
+                parser.end_of_line()?;
+                if table_at(&mut root, &path).is_none() {
+                    return parser.error(format!("`{}` is already a value", path.join(".")));
+                }
+                current = path;
+            }
+            Some(_) => {
+                let key = parser.key()?;
+                parser.expect("=")?;
+                parser.skip_inline();
+                let value = parser.value()?;
+                parser.end_of_line()?;
+                let Some(table) = table_at(&mut root, &current) else {
+                    return parser.error(format!("`{}` is already a value", current.join(".")));
+                };
+                parser.insert(table, &key, value)?;
+            }
+        }
+    }
+}
+
+/// Copies the pack at `source` to `output`, writing each `.toml` file as the canonical `.json`
+/// file of the same name. Returns the files written, relative to `output`.
+pub fn export_authored_pack(source: &Path, output: &Path) -> AddonResult<Vec<PathBuf>> {
+    export_authored_pack_with_style(source, output, &JsonStyle::default())
+}
+
+/// Like [`export_authored_pack`], writing the `.json` files in `style`.
+pub fn export_authored_pack_with_style(source: &Path, output: &Path, style: &JsonStyle) -> AddonResult<Vec<PathBuf>> {
+    let mut written = vec![];
+    for path in pack_files(source, "") {
+        let relative = path.strip_prefix(source).unwrap_or(&path).to_path_buf();
+        let (relative, contents) = if relative.extension().is_some_and(|ext| ext == "toml") {
+            let value = parse_toml_from_str(&read_to_string(&path)?).map_err(|error| AddonError::semantic(&path, error.to_string()))?;
+            (relative.with_extension("json"), style.write(&value).into_bytes())
+        } else {
+            (relative, fs::read(&path).map_err(|error| AddonError::io(&path, error))?)
+        };
+
+        let target = output.join(&relative);
+        if let Some(parent) = target.parent() {
+            fs::create_dir_all(parent).map_err(|error| AddonError::io(parent, error))?;
+        }
+        fs::write(&target, contents).map_err(|error| AddonError::io(&target, error))?;
+        written.push(relative);
+    }
+    Ok(written)
+}
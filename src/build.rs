@@ -0,0 +1,220 @@
+//! Release builds: a pack folder turned into the `.mcpack` that ships.
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::fs::File;
+use std::path::{Path, PathBuf};
+use serde_json::{json, Value};
+use crate::addon::Addon;
+use crate::error::{AddonError, AddonResult};
+use crate::files::{glob_match, pack_files};
+use crate::generics::mcpack::{archive_error, McpackWriter};
+use crate::generics::textures_list::is_image;
+use crate::molang::optimizer::optimize;
+use crate::molang::parser::parse_molang_from_str;
+use crate::utils::{from_json_str, JsonStyle};
+
+/// The folders whose files hold Molang expressions, and the fields in them that do. Everything
+/// under one of these fields is an expression; other strings in the file are left alone.
+const MOLANG_FIELDS: [(&str, &[&str]); 6] = [
+    ("animations", &["anim_time_update", "blend_weight", "start_delay", "loop_delay", "position", "rotation", "scale", "timeline"]),
+    ("animation_controllers", &["transitions", "on_entry", "on_exit", "blend_weight", "animations"]),
+    ("render_controllers", &["geometry", "materials", "textures", "part_visibility", "color", "overlay_color", "on_fire_color", "is_hurt_color", "uv_anim", "light_color_multiplier", "ignore_lighting"]),
+    ("particles", &["spawn_rate", "max_particles", "active_time", "sleep_time", "activation_expression", "expiration_expression", "creation_expression", "per_update_expression", "max_lifetime", "offset", "direction", "radius", "half_dimensions", "speed", "acceleration", "linear_drag_coefficient", "rotation_acceleration", "rotation_drag_coefficient", "initial_rotation", "initial_rotation_rate", "size", "uv", "interpolant"]),
+    ("entity", &["scripts"]),
+    ("attachables", &["scripts"])
+];
+
+/// What [`build_pack`] does to a pack on its way into the archive.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct BuildProfile {
+    /// Globs of files left out, relative to the pack, e.g. `**/*.md` or `tests/**`.
+    pub exclude: Vec<String>,
+    /// The style JSON files are rewritten in, or `None` to keep them as they are.
+    pub json_style: Option<JsonStyle>,
+    /// Replaces Molang expressions with their [optimized](optimize) form where that is shorter.
+    pub fold_molang: bool,
+    /// Lang keys starting with one of these are dropped, e.g. `debug.`.
+    pub drop_lang_prefixes: Vec<String>,
+    /// Generates `contents.json`, listing every other file of the archive.
+    pub contents_list: bool,
+    /// Regenerates `textures/textures_list.json` in packs with textures.
+    pub textures_list: bool
+}
+
+impl BuildProfile {
+    /// Everything a shipped pack can do without: version control and editor files, notes, minified
+    /// JSON, folded Molang and `debug.` lang keys, with fresh content and texture lists.
+    pub fn release() -> BuildProfile {
+        BuildProfile {
+            exclude: ["**/.*", "**/.*/**", "**/*.md", "**/Thumbs.db", "**/*.psd", "**/*.bbmodel"].map(String::from).to_vec(),
+            json_style: Some(JsonStyle::minified()),
+            fold_molang: true,
+            drop_lang_prefixes: vec!["debug.".to_string()],
+            contents_list: true,
+            textures_list: true
+        }
+    }
+
+    /// The pack as it is, without version control files.
+    pub fn development() -> BuildProfile {
+        BuildProfile {
+            exclude: ["**/.*", "**/.*/**"].map(String::from).to_vec(),
+            json_style: None,
+            fold_molang: false,
+            drop_lang_prefixes: vec![],
+            contents_list: false,
+            textures_list: false
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct BuildReport {
+    /// The archive's entries, `/`-separated.
+    pub files: Vec<String>,
+    /// Files left out by the profile's globs.
+    pub excluded: Vec<String>,
+    /// Molang expressions replaced by a folded form.
+    pub folded_molang: usize,
+    pub dropped_lang_keys: Vec<String>
+}
+
+/// Folds the Molang in the expression `fields` below `value` and returns how many strings changed.
+fn fold_molang(value: &mut Value, fields: &[&str]) -> usize {
+    match value {
+        Value::Array(values) => values.iter_mut().map(|value| fold_molang(value, fields)).sum(),
+        Value::Object(map) => map.iter_mut().map(|(key, value)| if fields.contains(&key.as_str()) {
+            fold_expressions(value)
+        } else {
+            fold_molang(value, fields)
+        }).sum(),
+        _ => 0
+    }
+}
+
+/// Folds every string in `value`, which sits under an expression field. Numbers written as
+/// strings are kept, since `"1.10"` is as likely a version as an expression.
+fn fold_expressions(value: &mut Value) -> usize {
+    match value {
+        Value::String(src) => {
+            if src.trim().parse::<f64>().is_ok() {
+                return 0;
+            }
+            let Ok(program) = parse_molang_from_str(src) else {
+                return 0;
+            };
+            let folded = optimize(&program).to_string();
+            if folded.len() < src.len() {
+                *src = folded;
+                1
+            } else {
+                0
+            }
+        }
+        Value::Array(values) => values.iter_mut().map(fold_expressions).sum(),
+        Value::Object(map) => map.values_mut().map(fold_expressions).sum(),
+        _ => 0
+    }
+}
+
+/// `src` without the entries whose key starts with one of `prefixes`, and the keys dropped.
+fn drop_lang_keys(src: &str, prefixes: &[String]) -> (String, Vec<String>) {
+    let mut kept = String::new();
+    let mut dropped = vec![];
+    for line in src.split_inclusive('\n') {
+        let key = line.trim_start().split_once('=').map(|(key, _)| key.trim()).filter(|_| !line.trim_start().starts_with("##"));
+        match key {
+            Some(key) if prefixes.iter().any(|prefix| key.starts_with(prefix.as_str())) => dropped.push(key.to_string()),
+            _ => kept.push_str(line)
+        }
+    }
+    (kept, dropped)
+}
+
+/// Archives the pack folder at `pack` into a `.mcpack` at `output`, applying `profile`. Entries
+/// are written in path order with fixed timestamps, so building the same files twice gives the
+/// same bytes.
+pub fn build_pack(pack: &Path, profile: &BuildProfile, output: &Path) -> AddonResult<BuildReport> {
+    let mut report = BuildReport::default();
+    let mut files: BTreeMap<String, Vec<u8>> = BTreeMap::new();
+    for path in pack_files(pack, "") {
+        let name = path.strip_prefix(pack).unwrap_or(&path).to_string_lossy().replace('\\', "/");
+        if profile.exclude.iter().any(|glob| glob_match(glob, &name)) {
+            report.excluded.push(name);
+            continue;
+        }
+        let bytes = fs::read(&path).map_err(|error| AddonError::io(&path, error))?;
+        files.insert(name, bytes);
+    }
+
+    for (name, bytes) in files.iter_mut() {
+        let path = Path::new(name);
+        if path.extension().is_some_and(|ext| ext == "lang") && !profile.drop_lang_prefixes.is_empty() {
+            let (kept, dropped) = drop_lang_keys(&String::from_utf8_lossy(bytes), &profile.drop_lang_prefixes);
+            if !dropped.is_empty() {
+                *bytes = kept.into_bytes();
+                report.dropped_lang_keys.extend(dropped);
+            }
+            continue;
+        }
+        if path.extension().is_none_or(|ext| ext != "json") {
+            continue;
+        }
+        let Ok(mut value) = from_json_str::<Value>(&String::from_utf8_lossy(bytes)) else {
+            continue;
+        };
+        let fields = MOLANG_FIELDS.iter().find(|(folder, _)| name.starts_with(&format!("{}/", folder))).map(|(_, fields)| *fields);
+        let folded = match fields {
+            Some(fields) if profile.fold_molang => fold_molang(&mut value, fields),
+            _ => 0
+        };
+        report.folded_molang += folded;
+        match &profile.json_style {
+            Some(style) => *bytes = style.write(&value).into_bytes(),
+            None if folded > 0 => *bytes = JsonStyle::detect(&String::from_utf8_lossy(bytes)).write(&value).into_bytes(),
+            None => {}
+        }
+    }
+
+    let style = profile.json_style.clone().unwrap_or_default();
+    let textures: Vec<Value> = files.keys()
+        .filter(|name| name.starts_with("textures/") && is_image(Path::new(name)))
+        .map(|name| Value::String(name.rsplit_once('.').map_or(name.as_str(), |(stem, _)| stem).to_string()))
+        .collect();
+    if profile.textures_list && !textures.is_empty() {
+        files.insert("textures/textures_list.json".to_string(), style.write(&Value::Array(textures)).into_bytes());
+    }
+    if profile.contents_list {
+        files.remove("contents.json");
+        let content: Vec<Value> = files.keys().map(|name| json!({ "path": name })).collect();
+        files.insert("contents.json".to_string(), style.write(&json!({ "content": content })).into_bytes());
+    }
+
+    if let Some(parent) = output.parent() {
+        fs::create_dir_all(parent).map_err(|error| AddonError::io(parent, error))?;
+    }
+    let file = File::create(output).map_err(|error| AddonError::io(output, error))?;
+    let mut archive = McpackWriter::new(file);
+    for (name, bytes) in &files {
+        archive.add_file(name, &mut bytes.as_slice()).map_err(|error| archive_error(output, error))?;
+        report.files.push(name.clone());
+    }
+    archive.finish().map_err(|error| archive_error(output, error))?;
+    Ok(report)
+}
+
+impl Addon {
+    /// [Builds](build_pack) each pack of the addon into `output`, as a `.mcpack` named after its
+    /// folder. Returns each archive written with its report.
+    pub fn build(&self, profile: &BuildProfile, output: &Path) -> AddonResult<Vec<(PathBuf, BuildReport)>> {
+        let mut built = vec![];
+        for (_, pack) in self.packs() {
+            let name = pack.file_name().map(|name| name.to_string_lossy().to_string()).unwrap_or_else(|| "pack".to_string());
+            let archive = output.join(format!("{}.mcpack", name));
+            let report = build_pack(pack, profile, &archive)?;
+            built.push((archive, report));
+        }
+        Ok(built)
+    }
+}
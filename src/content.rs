@@ -0,0 +1,265 @@
+//! Addon content defined in Rust. Describe items, blocks and entities as definitions (by hand or
+//! with the derive macros of the `derive` feature), collect them in an [`AddonProject`], and
+//! [`write`](AddonProject::write) the behavior definitions along with the textures, atlas entries,
+//! translations and recipes they need.
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use serde::Serialize;
+use serde_json::{json, Map, Value};
+use crate::error::{read_to_string, AddonError, AddonResult};
+use crate::files::merge_atlas;
+use crate::generics::builders::{BlockBuilder, EntityBuilder, ItemBuilder};
+use crate::generics::lang::parse_lang_from_str;
+use crate::utils::JsonStyle;
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct ItemDefinition {
+    pub identifier: String,
+    /// The English name, written to `en_US.lang`.
+    pub name: Option<String>,
+    /// A PNG to copy into the resource pack as the item's icon.
+    pub texture: Option<PathBuf>,
+    /// The creative inventory tab, e.g. `equipment`.
+    pub category: Option<String>,
+    pub components: Map<String, Value>
+}
+
+impl ItemDefinition {
+    pub fn new(identifier: &str) -> ItemDefinition {
+        ItemDefinition { identifier: identifier.to_string(), name: None, texture: None, category: None, components: Map::new() }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct BlockDefinition {
+    pub identifier: String,
+    pub name: Option<String>,
+    /// A PNG to copy into the resource pack and use on every face.
+    pub texture: Option<PathBuf>,
+    pub category: Option<String>,
+    pub components: Map<String, Value>
+}
+
+impl BlockDefinition {
+    pub fn new(identifier: &str) -> BlockDefinition {
+        BlockDefinition { identifier: identifier.to_string(), name: None, texture: None, category: None, components: Map::new() }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct EntityDefinition {
+    pub identifier: String,
+    pub name: Option<String>,
+    /// Whether the entity has a spawn egg and can spawn naturally.
+    pub spawnable: bool,
+    pub components: Map<String, Value>
+}
+
+impl EntityDefinition {
+    pub fn new(identifier: &str) -> EntityDefinition {
+        EntityDefinition { identifier: identifier.to_string(), name: None, spawnable: false, components: Map::new() }
+    }
+}
+
+pub trait Item {
+    fn definition(&self) -> ItemDefinition;
+}
+
+pub trait Block {
+    fn definition(&self) -> BlockDefinition;
+}
+
+pub trait Entity {
+    fn definition(&self) -> EntityDefinition;
+}
+
+/// The JSON of a component value, for the derive macros.
+#[doc(hidden)]
+pub fn component_value<T: Serialize>(value: &T) -> Value {
+    serde_json::to_value(value).unwrap_or(Value::Null)
+}
+
+/// The identifier without its namespace, which names the files of the content.
+fn file_name(identifier: &str) -> &str {
+    identifier.split_once(':').map(|(_, name)| name).unwrap_or(identifier)
+}
+
+/// The name content is registered under in texture atlases.
+fn short_name(identifier: &str) -> String {
+    identifier.replace(':', "_")
+}
+
+/// Everything [`AddonProject::write`] writes, collected first so shared files are written once.
+struct Output {
+    style: JsonStyle,
+    files: Vec<(PathBuf, Vec<u8>)>,
+    item_textures: Map<String, Value>,
+    terrain_textures: Map<String, Value>,
+    translations: BTreeMap<String, String>
+}
+
+impl Output {
+    fn json(&mut self, path: PathBuf, value: &Value) {
+        self.files.push((path, self.style.write(value).into_bytes()));
+    }
+
+    fn texture(&mut self, source: &Path, target: PathBuf) -> AddonResult<()> {
+        let bytes = fs::read(source).map_err(|error| AddonError::io(source, error))?;
+        self.files.push((target, bytes));
+        Ok(())
+    }
+}
+
+/// Sets `translations` in the `.lang` file `src`, replacing the lines of keys it already has and
+/// appending the rest.
+fn merge_lang(src: &str, translations: &BTreeMap<String, String>) -> String {
+    let existing = parse_lang_from_str(src);
+    let mut lines: Vec<String> = src.lines().map(|line| {
+        let key = line.split_once('=').map(|(key, _)| key.trim()).filter(|_| !line.trim_start().starts_with("##"));
+        match key.and_then(|key| translations.get(key).map(|value| (key, value))) {
+            Some((key, value)) => format!("{}={}", key, value),
+            None => line.to_string()
+        }
+    }).collect();
+    lines.extend(translations.iter().filter(|(key, _)| !existing.contains_key(*key)).map(|(key, value)| format!("{}={}", key, value)));
+    format!("{}\n", lines.join("\n"))
+}
+
+/// Content to write into a behavior and resource pack.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct AddonProject {
+    pub items: Vec<ItemDefinition>,
+    pub blocks: Vec<BlockDefinition>,
+    pub entities: Vec<EntityDefinition>,
+    /// Complete recipe files, e.g. from [`RecipeBuilder`](crate::generics::builders::RecipeBuilder).
+    pub recipes: Vec<Value>
+}
+
+impl AddonProject {
+    pub fn new() -> AddonProject {
+        AddonProject::default()
+    }
+
+    pub fn item(mut self, item: &impl Item) -> AddonProject {
+        self.items.push(item.definition());
+        self
+    }
+
+    pub fn block(mut self, block: &impl Block) -> AddonProject {
+        self.blocks.push(block.definition());
+        self
+    }
+
+    pub fn entity(mut self, entity: &impl Entity) -> AddonProject {
+        self.entities.push(entity.definition());
+        self
+    }
+
+    pub fn recipe(mut self, recipe: Value) -> AddonProject {
+        self.recipes.push(recipe);
+        self
+    }
+
+    /// Writes every definition into `behavior_pack`, and its textures, atlas entries and English
+    /// names into `resource_pack`. Existing atlases and `en_US.lang` are added to rather than
+    /// replaced. Returns the files written.
+    pub fn write(&self, behavior_pack: &Path, resource_pack: &Path) -> AddonResult<Vec<PathBuf>> {
+        self.write_with_style(behavior_pack, resource_pack, &JsonStyle::default())
+    }
+
+    /// Like [`write`](AddonProject::write), writing the JSON files in `style`.
+    pub fn write_with_style(&self, behavior_pack: &Path, resource_pack: &Path, style: &JsonStyle) -> AddonResult<Vec<PathBuf>> {
+        let mut output = Output {
+            style: style.clone(), files: vec![], item_textures: Map::new(), terrain_textures: Map::new(), translations: BTreeMap::new()
+        };
+
+        for item in &self.items {
+            let name = file_name(&item.identifier);
+            let mut builder = ItemBuilder::new(&item.identifier).format_version("1.20.50");
+            if let Some(category) = &item.category {
+                builder = builder.menu_category(category);
+            }
+            if let Some(texture) = &item.texture {
+                let short = short_name(&item.identifier);
+                builder = builder.component("minecraft:icon", json!({ "texture": short }));
+                output.item_textures.insert(short, json!({ "textures": format!("textures/items/{}", name) }));
+                output.texture(texture, resource_pack.join(format!("textures/items/{}.png", name)))?;
+            }
+            for (component, value) in &item.components {
+                builder = builder.component(component, value.clone());
+            }
+            output.json(behavior_pack.join(format!("items/{}.json", name)), &builder.build());
+            if let Some(display) = &item.name {
+                output.translations.insert(format!("item.{}.name", item.identifier), display.clone());
+            }
+        }
+
+        for block in &self.blocks {
+            let name = file_name(&block.identifier);
+            let mut builder = BlockBuilder::new(&block.identifier).format_version("1.20.50");
+            if let Some(category) = &block.category {
+                builder = builder.menu_category(category);
+            }
+            if let Some(texture) = &block.texture {
+                let short = short_name(&block.identifier);
+                builder = builder.component("minecraft:material_instances", json!({ "*": { "texture": short, "render_method": "opaque" } }));
+                output.terrain_textures.insert(short, json!({ "textures": format!("textures/blocks/{}", name) }));
+                output.texture(texture, resource_pack.join(format!("textures/blocks/{}.png", name)))?;
+            }
+            for (component, value) in &block.components {
+                builder = builder.component(component, value.clone());
+            }
+            output.json(behavior_pack.join(format!("blocks/{}.json", name)), &builder.build());
+            if let Some(display) = &block.name {
+                output.translations.insert(format!("tile.{}.name", block.identifier), display.clone());
+            }
+        }
+
+        for entity in &self.entities {
+            let mut builder = EntityBuilder::new(&entity.identifier).spawnable(entity.spawnable);
+            for (component, value) in &entity.components {
+                builder = builder.component(component, value.clone());
+            }
+            output.json(behavior_pack.join(format!("entities/{}.json", file_name(&entity.identifier))), &builder.build());
+            if let Some(display) = &entity.name {
+                output.translations.insert(format!("entity.{}.name", entity.identifier), display.clone());
+            }
+        }
+
+        for recipe in &self.recipes {
+            let identifier = recipe.as_object().into_iter().flatten()
+                .find_map(|(_, body)| body.pointer("/description/identifier")?.as_str())
+                .ok_or_else(|| AddonError::semantic("recipes", "recipe without a description identifier"))?;
+            output.json(behavior_pack.join(format!("recipes/{}.json", file_name(identifier))), recipe);
+        }
+
+        if !output.item_textures.is_empty() {
+            let atlas = merge_atlas(resource_pack, "textures/item_texture.json", "atlas.items", std::mem::take(&mut output.item_textures))?;
+            output.json(resource_pack.join("textures/item_texture.json"), &atlas);
+        }
+        if !output.terrain_textures.is_empty() {
+            let atlas = merge_atlas(resource_pack, "textures/terrain_texture.json", "atlas.terrain", std::mem::take(&mut output.terrain_textures))?;
+            output.json(resource_pack.join("textures/terrain_texture.json"), &atlas);
+        }
+        if !output.translations.is_empty() {
+            let lang_path = resource_pack.join("texts/en_US.lang");
+            let src = if lang_path.exists() { read_to_string(&lang_path)? } else { String::new() };
+            output.files.push((lang_path, merge_lang(&src, &output.translations).into_bytes()));
+            if !resource_pack.join("texts/languages.json").exists() {
+                output.json(resource_pack.join("texts/languages.json"), &json!(["en_US"]));
+            }
+        }
+
+        let mut written = vec![];
+        for (path, contents) in output.files {
+            if let Some(parent) = path.parent() {
+                fs::create_dir_all(parent).map_err(|error| AddonError::io(parent, error))?;
+            }
+            fs::write(&path, contents).map_err(|error| AddonError::io(&path, error))?;
+            written.push(path);
+        }
+        Ok(written)
+    }
+}
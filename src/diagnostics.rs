@@ -0,0 +1,447 @@
+use std::fmt::{Display, Formatter};
+use std::path::PathBuf;
+use serde::{Serialize, Serializer};
+#[cfg(feature = "molang")]
+use crate::molang::diagnostic::MolangDiagnostic;
+#[cfg(feature = "validation")]
+use crate::utils::json_pointer;
+#[cfg(feature = "validation")]
+use crate::validation::capabilities::ChemistryReference;
+#[cfg(feature = "validation")]
+use crate::validation::deprecations::DeprecatedComponent;
+#[cfg(feature = "validation")]
+use crate::validation::duplicates::DuplicateIdentifier;
+#[cfg(feature = "validation")]
+use crate::validation::entity_events::{EntityEventIssue, EntityEventIssueKind};
+#[cfg(feature = "validation")]
+use crate::validation::format_versions::{FormatVersionIssue, FormatVersionIssueKind};
+#[cfg(feature = "validation")]
+use crate::validation::gametest::{GameTestIssue, GameTestIssueKind};
+#[cfg(feature = "validation")]
+use crate::validation::localization::MissingTranslation;
+#[cfg(feature = "validation")]
+use crate::validation::namespaces::{NamespaceIssue, NamespaceIssueKind};
+#[cfg(feature = "validation")]
+use crate::validation::queries::QueryContextIssue;
+#[cfg(feature = "validation")]
+use crate::validation::recipes::{RecipeConflict, RecipeConflictKind};
+#[cfg(feature = "validation")]
+use crate::validation::render_controllers::{RenderControllerIssue, RenderControllerIssueKind};
+#[cfg(feature = "validation")]
+use crate::validation::schema::SchemaViolation;
+#[cfg(feature = "validation")]
+use crate::validation::scripts::{ScriptDependencyIssue, ScriptDependencyIssueKind};
+#[cfg(feature = "validation")]
+use crate::validation::subpacks::{SubpackIssue, SubpackIssueKind};
+#[cfg(feature = "validation")]
+use crate::validation::texture_constraints::{TextureIssue, TextureIssueKind};
+#[cfg(feature = "validation")]
+use crate::validation::{ReferenceKind, UnresolvedReference};
+
+#[derive(Serialize, Clone, Copy, Debug, Eq, PartialEq, Ord, PartialOrd, Hash)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    Info,
+    Warning,
+    Error
+}
+
+impl Display for Severity {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", match self {
+            Severity::Info => "info",
+            Severity::Warning => "warning",
+            Severity::Error => "error"
+        })
+    }
+}
+
+/// A single finding of a validator, in a shape tools can render without knowing which validator
+/// produced it.
+#[derive(Serialize, Clone, Debug, Eq, PartialEq)]
+pub struct Diagnostic {
+    pub severity: Severity,
+    /// Stable identifier of the rule, e.g. `unresolved-texture`.
+    pub code: &'static str,
+    pub message: String,
+    /// Relative to the pack root.
+    #[serde(skip_serializing_if = "Option::is_none", serialize_with = "serialize_file")]
+    pub file: Option<PathBuf>,
+    /// JSON pointer within `file`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pointer: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub suggestion: Option<String>
+}
+
+/// Paths that aren't valid UTF-8 are written lossily rather than failing the whole report.
+fn serialize_file<S: Serializer>(file: &Option<PathBuf>, serializer: S) -> Result<S::Ok, S::Error> {
+    match file {
+        Some(file) => serializer.serialize_str(&file.to_string_lossy()),
+        None => serializer.serialize_none()
+    }
+}
+
+impl Diagnostic {
+    pub fn new(severity: Severity, code: &'static str, message: String) -> Diagnostic {
+        Diagnostic {
+            severity, code, message, file: None, pointer: None, suggestion: None
+        }
+    }
+
+    pub fn at(mut self, file: PathBuf, pointer: String) -> Diagnostic {
+        self.file = Some(file);
+        self.pointer = Some(pointer);
+        self
+    }
+
+    pub fn suggest(mut self, suggestion: String) -> Diagnostic {
+        self.suggestion = Some(suggestion);
+        self
+    }
+}
+
+impl Display for Diagnostic {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match (&self.file, &self.pointer) {
+            (Some(file), Some(pointer)) => write!(f, "{}#{}: ", file.display(), pointer)?,
+            (Some(file), None) => write!(f, "{}: ", file.display())?,
+            (None, Some(pointer)) => write!(f, "#{}: ", pointer)?,
+            (None, None) => {}
+        }
+        write!(f, "{}[{}]: {}", self.severity, self.code, self.message)?;
+        if let Some(suggestion) = &self.suggestion {
+            write!(f, " (help: {})", suggestion)?;
+        }
+        Ok(())
+    }
+}
+
+/// Collects diagnostics from any number of validators.
+#[derive(Clone, Debug, Default)]
+pub struct Diagnostics {
+    items: Vec<Diagnostic>
+}
+
+impl Diagnostics {
+    pub fn new() -> Diagnostics {
+        Diagnostics::default()
+    }
+
+    pub fn push(&mut self, diagnostic: impl Into<Diagnostic>) {
+        self.items.push(diagnostic.into());
+    }
+
+    pub fn extend<T: Into<Diagnostic>>(&mut self, diagnostics: impl IntoIterator<Item = T>) {
+        self.items.extend(diagnostics.into_iter().map(Into::into));
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &Diagnostic> {
+        self.items.iter()
+    }
+
+    pub fn len(&self) -> usize {
+        self.items.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.items.is_empty()
+    }
+
+    pub fn has_errors(&self) -> bool {
+        self.items.iter().any(|diagnostic| diagnostic.severity == Severity::Error)
+    }
+
+    /// Drops diagnostics less severe than `severity`.
+    pub fn min_severity(mut self, severity: Severity) -> Diagnostics {
+        self.items.retain(|diagnostic| diagnostic.severity >= severity);
+        self
+    }
+
+    /// Drops diagnostics of the given rule codes, e.g. rules a project has opted out of.
+    pub fn without_codes(mut self, codes: &[&str]) -> Diagnostics {
+        self.items.retain(|diagnostic| !codes.contains(&diagnostic.code));
+        self
+    }
+
+    pub fn retain(mut self, f: impl FnMut(&Diagnostic) -> bool) -> Diagnostics {
+        self.items.retain(f);
+        self
+    }
+
+    /// Orders diagnostics by file, then pointer, then most severe first.
+    pub fn sorted(mut self) -> Diagnostics {
+        self.items.sort_by(|a, b| {
+            (&a.file, &a.pointer).cmp(&(&b.file, &b.pointer)).then(b.severity.cmp(&a.severity))
+        });
+        self
+    }
+
+    pub fn into_vec(self) -> Vec<Diagnostic> {
+        self.items
+    }
+}
+
+impl IntoIterator for Diagnostics {
+    type Item = Diagnostic;
+    type IntoIter = std::vec::IntoIter<Diagnostic>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.items.into_iter()
+    }
+}
+
+/// One diagnostic per line.
+pub fn format_plain(diagnostics: &[Diagnostic]) -> String {
+    diagnostics.iter().map(|diagnostic| format!("{}\n", diagnostic)).collect()
+}
+
+/// A JSON array of diagnostics. Every field is written as a string, so serializing can't fail.
+pub fn format_json(diagnostics: &[Diagnostic]) -> String {
+    serde_json::to_string_pretty(diagnostics).unwrap_or_default()
+}
+
+#[cfg(feature = "validation")]
+impl From<UnresolvedReference> for Diagnostic {
+    fn from(value: UnresolvedReference) -> Self {
+        let (code, message) = match value.kind {
+            ReferenceKind::TexturePath => ("unresolved-texture", format!("texture '{}' does not exist", value.reference)),
+            ReferenceKind::TextureShortname => ("unresolved-texture", format!("texture shortname '{}' is not defined in terrain_texture.json", value.reference)),
+            ReferenceKind::Geometry => ("unresolved-geometry", format!("geometry '{}' is not defined", value.reference)),
+            ReferenceKind::Bone => ("unknown-bone", format!("no bone matches '{}' in the selected geometry", value.reference)),
+            ReferenceKind::Animation => ("unresolved-animation", format!("animation '{}' is not defined", value.reference)),
+            ReferenceKind::AnimationShortname => ("undeclared-animation", format!("'{}' is not declared in description.animations", value.reference)),
+            ReferenceKind::ParticleShortname => ("undeclared-particle", format!("'{}' is not declared in description.particle_effects of an entity using this animation", value.reference)),
+            ReferenceKind::SoundShortname => ("undeclared-sound", format!("'{}' is not declared in description.sound_effects of an entity using this animation", value.reference)),
+            ReferenceKind::SoundDefinition => ("unresolved-sound", format!("sound '{}' is not defined in sound_definitions.json", value.reference)),
+            ReferenceKind::SoundEvent => ("unresolved-sound", format!("sound event '{}' is not declared in sounds.json", value.reference)),
+            ReferenceKind::BlockSoundType => ("unresolved-sound", format!("block sound type '{}' is not defined in sounds.json", value.reference)),
+            ReferenceKind::TexturesListEntry => ("stale-textures-list", format!("textures_list.json lists '{}', which does not exist", value.reference))
+        };
+        // A stale preload entry only costs a failed lookup.
+        let severity = if value.kind == ReferenceKind::TexturesListEntry { Severity::Warning } else { Severity::Error };
+        Diagnostic::new(severity, code, message).at(value.file, value.pointer)
+    }
+}
+
+#[cfg(feature = "validation")]
+impl From<TextureIssue> for Diagnostic {
+    fn from(value: TextureIssue) -> Self {
+        let (width, height) = value.size;
+        let (severity, code, message) = match &value.kind {
+            TextureIssueKind::InconsistentTileSize { expected: (w, h) } => (Severity::Warning, "texture-tile-size", format!("{} is {}x{} while the other tiles are {}x{}", value.texture, width, height, w, h)),
+            TextureIssueKind::NotPowerOfTwo => (Severity::Warning, "texture-not-power-of-two", format!("{} is {}x{}, which isn't a power of two", value.texture, width, height)),
+            TextureIssueKind::FlipbookHeight => (Severity::Error, "flipbook-height", format!("{} is {}x{}, which isn't a whole number of square frames", value.texture, width, height)),
+            TextureIssueKind::FlipbookFrameOutOfRange { frame, frames } => (Severity::Error, "flipbook-frame", format!("frame {} is out of range, {} has {} frames", frame, value.texture, frames)),
+            TextureIssueKind::PackIconNotSquare => (Severity::Warning, "pack-icon-size", format!("the pack icon is {}x{}, which isn't square", width, height)),
+            TextureIssueKind::PackIconTooLarge { max } => (Severity::Warning, "pack-icon-size", format!("the pack icon is {}x{}, larger than {}x{}", width, height, max, max))
+        };
+        let diagnostic = Diagnostic::new(severity, code, message);
+        if value.pointer.is_empty() {
+            Diagnostic { file: Some(value.file), ..diagnostic }
+        } else {
+            diagnostic.at(value.file, value.pointer)
+        }
+    }
+}
+
+#[cfg(feature = "validation")]
+impl From<SubpackIssue> for Diagnostic {
+    fn from(value: SubpackIssue) -> Self {
+        let (severity, code, message) = match &value.kind {
+            SubpackIssueKind::MissingFolder => (Severity::Error, "subpack-missing", format!("subpack folder '{}' does not exist", value.folder_name)),
+            SubpackIssueKind::UndeclaredFolder => (Severity::Warning, "subpack-undeclared", format!("'{}' is not declared as a subpack in manifest.json", value.folder_name)),
+            SubpackIssueKind::DuplicateFolder => (Severity::Error, "subpack-duplicate", format!("subpack folder '{}' is declared more than once", value.folder_name)),
+            SubpackIssueKind::NotOverridable => (Severity::Warning, "subpack-not-overridable", "subpacks can't override this file, so it is ignored".to_string()),
+            SubpackIssueKind::NoBaseFile => (Severity::Info, "subpack-no-base-file", "the base pack has no file at this path to override".to_string()),
+            SubpackIssueKind::NegativeMemoryTier { memory_tier } => (Severity::Error, "subpack-memory-tier", format!("memory_tier {} is negative", memory_tier)),
+            SubpackIssueKind::UnorderedMemoryTier { memory_tier, previous } => (Severity::Warning, "subpack-memory-tier", format!("memory_tier {} is lower than the previous subpack's {}", memory_tier, previous))
+        };
+        let diagnostic = Diagnostic::new(severity, code, message);
+        if value.pointer.is_empty() {
+            Diagnostic { file: Some(value.file), ..diagnostic }
+        } else {
+            diagnostic.at(value.file, value.pointer)
+        }
+    }
+}
+
+#[cfg(feature = "validation")]
+impl From<ChemistryReference> for Diagnostic {
+    fn from(value: ChemistryReference) -> Self {
+        let message = format!("'{}' is a chemistry item, but the manifest does not declare the chemistry capability", value.identifier);
+        Diagnostic::new(Severity::Error, "chemistry-capability", message).at(value.file, value.pointer)
+    }
+}
+
+#[cfg(feature = "validation")]
+impl From<EntityEventIssue> for Diagnostic {
+    fn from(value: EntityEventIssue) -> Self {
+        let (severity, code, message) = match &value.kind {
+            EntityEventIssueKind::UnreachableGroup => (Severity::Warning, "entity-unreachable-group", format!("no event that can fire adds component group '{}' of {}", value.name, value.entity)),
+            EntityEventIssueKind::UntriggeredEvent => (Severity::Info, "entity-untriggered-event", format!("nothing in the pack fires event '{}' of {}", value.name, value.entity)),
+            EntityEventIssueKind::TriggerCycle { events } => (Severity::Warning, "entity-event-cycle", format!("event '{}' of {} triggers itself through {}", value.name, value.entity, events.join(" -> ")))
+        };
+        Diagnostic::new(severity, code, message).at(value.file, value.pointer)
+    }
+}
+
+#[cfg(feature = "validation")]
+impl From<GameTestIssue> for Diagnostic {
+    fn from(value: GameTestIssue) -> Self {
+        let (severity, code, message) = match &value.kind {
+            GameTestIssueKind::MissingStructure => (Severity::Error, "gametest-structure", format!("test '{}' on line {} runs in structure '{}', which does not exist", value.test, value.line, value.structure)),
+            GameTestIssueKind::DuplicateTest { first, line } => (Severity::Error, "gametest-duplicate", format!("test '{}' on line {} is already registered in {} on line {}", value.test, value.line, first.display(), line)),
+            GameTestIssueKind::UnusedStructure => (Severity::Info, "gametest-unused-structure", format!("no test runs in structure '{}'", value.structure))
+        };
+        Diagnostic { file: Some(value.file), ..Diagnostic::new(severity, code, message) }
+    }
+}
+
+#[cfg(feature = "validation")]
+impl From<QueryContextIssue> for Diagnostic {
+    fn from(value: QueryContextIssue) -> Self {
+        let message = format!("query.{} is not available in {}", value.query, value.context.description());
+        Diagnostic::new(Severity::Warning, "molang-query-context", message).at(value.file, value.pointer)
+    }
+}
+
+#[cfg(feature = "validation")]
+impl From<RenderControllerIssue> for Diagnostic {
+    fn from(value: RenderControllerIssue) -> Self {
+        let (code, message) = match &value.kind {
+            RenderControllerIssueKind::UndefinedResource { resource } => ("render-controller-resource", format!("{} is not declared by {}", resource, value.entity)),
+            RenderControllerIssueKind::UndefinedArray { array } => ("render-controller-array", format!("{} is not defined in the arrays of {}", array, value.controller)),
+            RenderControllerIssueKind::IndexOutOfRange { array, index, len } => ("render-controller-index", format!("index {} is out of range, {} has {} entries", index, array, len))
+        };
+        Diagnostic::new(Severity::Error, code, message).at(value.file, value.pointer)
+    }
+}
+
+#[cfg(feature = "validation")]
+impl From<NamespaceIssue> for Diagnostic {
+    fn from(value: NamespaceIssue) -> Self {
+        let name = value.identifier.split_once(':').map(|(_, name)| name).unwrap_or(&value.identifier);
+        let diagnostic = match &value.kind {
+            NamespaceIssueKind::Reserved => Diagnostic::new(Severity::Warning, "namespace-reserved", format!("'{}' uses the reserved minecraft namespace", value.identifier)),
+            NamespaceIssueKind::Missing => Diagnostic::new(Severity::Error, "namespace-missing", format!("'{}' has no namespace", value.identifier)),
+            NamespaceIssueKind::InvalidCharacters => Diagnostic::new(Severity::Error, "namespace-invalid", format!("'{}' contains invalid characters", value.identifier))
+                .suggest(value.identifier.to_ascii_lowercase()),
+            NamespaceIssueKind::Unexpected { expected } => Diagnostic::new(Severity::Warning, "namespace-unexpected", format!("'{}' is not in the '{}' namespace", value.identifier, expected))
+                .suggest(format!("{}:{}", expected, name)),
+            NamespaceIssueKind::Inconsistent { majority } => Diagnostic::new(Severity::Warning, "namespace-inconsistent", format!("'{}' doesn't use the addon's '{}' namespace", value.identifier, majority))
+                .suggest(format!("{}:{}", majority, name))
+        };
+        diagnostic.at(value.file, value.pointer)
+    }
+}
+
+#[cfg(feature = "validation")]
+impl From<MissingTranslation> for Diagnostic {
+    fn from(value: MissingTranslation) -> Self {
+        Diagnostic::new(Severity::Warning, "missing-translation", format!("'{}' is not defined in any .lang file", value.key))
+            .at(value.file, value.pointer)
+    }
+}
+
+#[cfg(feature = "validation")]
+impl From<DeprecatedComponent> for Diagnostic {
+    fn from(value: DeprecatedComponent) -> Self {
+        let message = format!(
+            "'{}' is deprecated as of {}.{}.{}", value.component, value.since.major, value.since.minor, value.since.patch
+        );
+        let diagnostic = Diagnostic::new(Severity::Warning, "deprecated-component", message).at(value.file, value.pointer);
+        match value.replacement {
+            Some(replacement) => diagnostic.suggest(format!("use '{}'", replacement)),
+            None => diagnostic
+        }
+    }
+}
+
+#[cfg(feature = "validation")]
+impl From<FormatVersionIssue> for Diagnostic {
+    fn from(value: FormatVersionIssue) -> Self {
+        let diagnostic = match value.kind {
+            FormatVersionIssueKind::NewerThanEngine { min_engine_version: v } => Diagnostic::new(
+                Severity::Error, "format-version-too-new",
+                format!("format_version {} is newer than min_engine_version {}.{}.{}", value.format_version, v.major, v.minor, v.patch)
+            ),
+            FormatVersionIssueKind::NewerThanLatest { latest: v } => Diagnostic::new(
+                Severity::Warning, "format-version-unreleased",
+                format!("format_version {} is newer than the latest known release {}.{}.{}", value.format_version, v.major, v.minor, v.patch)
+            ),
+            FormatVersionIssueKind::Invalid => Diagnostic::new(
+                Severity::Error, "format-version-invalid", format!("'{}' is not a valid format_version", value.format_version)
+            )
+        };
+        diagnostic.at(value.file, "/format_version".to_string())
+    }
+}
+
+#[cfg(feature = "validation")]
+impl From<DuplicateIdentifier> for Diagnostic {
+    fn from(value: DuplicateIdentifier) -> Self {
+        let (severity, message) = match value.winner {
+            Some(_) => (Severity::Warning, format!("'{}' ({}) is defined {} times; this definition overrides the others", value.identifier, value.kind, value.definitions.len())),
+            None => (Severity::Error, format!("'{}' ({}) is defined {} times in the same pack; which one loads is undefined", value.identifier, value.kind, value.definitions.len()))
+        };
+        let diagnostic = Diagnostic::new(severity, "duplicate-identifier", message);
+        match value.definitions.into_iter().next() {
+            Some(first) => diagnostic.at(first.file, first.pointer),
+            None => diagnostic
+        }
+    }
+}
+
+#[cfg(feature = "validation")]
+impl From<RecipeConflict> for Diagnostic {
+    fn from(value: RecipeConflict) -> Self {
+        let identifiers: Vec<&str> = value.recipes.iter().map(|recipe| recipe.identifier.as_str()).collect();
+        let (code, message) = match &value.kind {
+            RecipeConflictKind::Identifier => ("recipe-identifier-conflict", format!("'{}' is used by recipes of different kinds", identifiers[0])),
+            RecipeConflictKind::Inputs { tags } => ("recipe-input-conflict", format!("recipes {} accept the same inputs at {}", identifiers.join(", "), tags.join(", ")))
+        };
+        let diagnostic = Diagnostic::new(Severity::Warning, code, message);
+        let diagnostic = match value.winner {
+            Some(winner) => diagnostic.suggest(format!("'{}' shadows the others", value.recipes[winner].identifier)),
+            None => diagnostic
+        };
+        match value.recipes.iter().find(|recipe| !recipe.vanilla) {
+            Some(recipe) => diagnostic.at(recipe.file.clone(), json_pointer(&[&recipe.kind])),
+            None => diagnostic
+        }
+    }
+}
+
+#[cfg(feature = "validation")]
+impl From<SchemaViolation> for Diagnostic {
+    fn from(value: SchemaViolation) -> Self {
+        Diagnostic::new(Severity::Error, "schema", value.message).at(value.file, value.pointer)
+    }
+}
+
+#[cfg(feature = "validation")]
+impl From<ScriptDependencyIssue> for Diagnostic {
+    fn from(value: ScriptDependencyIssue) -> Self {
+        let diagnostic = match &value.kind {
+            ScriptDependencyIssueKind::VersionMismatch { manifest, package } => Diagnostic::new(
+                Severity::Error, "script-version-mismatch",
+                format!("{} {} doesn't match the manifest's {}.{}.{}{}", value.module, package, manifest.major, manifest.minor, manifest.patch, if manifest.beta { "-beta" } else { "" })
+            ),
+            ScriptDependencyIssueKind::MissingFromManifest { .. } => Diagnostic::new(
+                Severity::Error, "script-module-undeclared", format!("{} is not a dependency in manifest.json", value.module)
+            )
+        };
+        diagnostic.at(value.file, value.pointer)
+    }
+}
+
+#[cfg(feature = "molang")]
+impl From<MolangDiagnostic> for Diagnostic {
+    fn from(value: MolangDiagnostic) -> Self {
+        let message = format!("{} (column {})", value.message, value.column);
+        let mut diagnostic = Diagnostic::new(Severity::Error, "molang-syntax", message);
+        diagnostic.pointer = Some(value.pointer);
+        diagnostic
+    }
+}
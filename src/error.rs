@@ -0,0 +1,70 @@
+use std::error::Error;
+use std::fmt::{Display, Formatter};
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// Everything that can go wrong reading, parsing or writing addon files. Every variant carries the
+/// path of the file that caused it.
+#[derive(Debug)]
+pub enum AddonError {
+    Io { path: PathBuf, source: io::Error },
+    /// The file isn't valid JSON, or doesn't have the expected shape.
+    Json { path: PathBuf, source: serde_json::Error },
+    /// Well-formed JSON whose content makes no sense, e.g. an invalid uuid or a loot table cycle.
+    Semantic { path: PathBuf, message: String },
+    /// A pack archive that can't be read or written.
+    Archive { path: PathBuf, message: String }
+}
+
+pub type AddonResult<T> = Result<T, AddonError>;
+
+impl AddonError {
+    pub fn io(path: impl Into<PathBuf>, source: io::Error) -> AddonError {
+        AddonError::Io { path: path.into(), source }
+    }
+
+    pub fn json(path: impl Into<PathBuf>, source: serde_json::Error) -> AddonError {
+        AddonError::Json { path: path.into(), source }
+    }
+
+    pub fn semantic(path: impl Into<PathBuf>, message: impl Into<String>) -> AddonError {
+        AddonError::Semantic { path: path.into(), message: message.into() }
+    }
+
+    pub fn archive(path: impl Into<PathBuf>, message: impl Into<String>) -> AddonError {
+        AddonError::Archive { path: path.into(), message: message.into() }
+    }
+
+    pub fn path(&self) -> &Path {
+        match self {
+            AddonError::Io { path, .. } | AddonError::Json { path, .. }
+            | AddonError::Semantic { path, .. } | AddonError::Archive { path, .. } => path
+        }
+    }
+}
+
+impl Display for AddonError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AddonError::Io { path, source } => write!(f, "{}: {}", path.display(), source),
+            AddonError::Json { path, source } => write!(f, "{}: invalid JSON: {}", path.display(), source),
+            AddonError::Semantic { path, message } => write!(f, "{}: {}", path.display(), message),
+            AddonError::Archive { path, message } => write!(f, "{}: invalid archive: {}", path.display(), message)
+        }
+    }
+}
+
+impl Error for AddonError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            AddonError::Io { source, .. } => Some(source),
+            AddonError::Json { source, .. } => Some(source),
+            _ => None
+        }
+    }
+}
+
+/// Reads the file at `path` to a string.
+pub(crate) fn read_to_string(path: &Path) -> AddonResult<String> {
+    std::fs::read_to_string(path).map_err(|source| AddonError::io(path, source))
+}
@@ -0,0 +1,171 @@
+//! A C ABI over the core operations, for tools that can't link Rust directly. Build the crate as a
+//! C library with `cargo rustc --release --features ffi --crate-type cdylib` (or `staticlib`);
+//! `include/bedrockrs_addon.h` declares these functions.
+//!
+//! Strings cross the boundary as NUL-terminated UTF-8. Strings returned to the caller belong to
+//! the caller and must be released with [`bedrockrs_string_free`]. Failing calls return null or
+//! `-1` and leave a message for [`bedrockrs_last_error`]. A panic inside the library fails the
+//! call the same way, with a message starting with `panic: `, instead of unwinding into C.
+
+use std::cell::RefCell;
+use std::any::Any;
+use std::ffi::{c_char, c_int, CStr, CString};
+use std::panic::{self, AssertUnwindSafe};
+use std::path::Path;
+use std::ptr;
+use crate::diagnostics::format_json;
+use crate::error::{AddonError, AddonResult};
+use crate::generics::manifest::{deserialize_manifest_from_bytes, serialize_manifest_to_string};
+use crate::generics::mcpack::{extract_mcpack, extract_mcpack_with_limits, write_mcpack, ArchiveLimits};
+use crate::validation::runner::{validate_addon_parallel, AddonPaths};
+
+thread_local! {
+    static LAST_ERROR: RefCell<Option<CString>> = const { RefCell::new(None) };
+}
+
+fn set_last_error(message: String) {
+    let message = CString::new(message.replace('\0', " ")).unwrap_or_default();
+    LAST_ERROR.with(|error| *error.borrow_mut() = Some(message));
+}
+
+fn panic_message(payload: &(dyn Any + Send)) -> &str {
+    payload.downcast_ref::<&str>().copied()
+        .or_else(|| payload.downcast_ref::<String>().map(String::as_str))
+        .unwrap_or("unknown cause")
+}
+
+/// Runs `body`, or leaves the message of its panic for [`bedrockrs_last_error`] and returns `None`.
+pub(crate) fn catch_panic<T>(body: impl FnOnce() -> T) -> Option<T> {
+    panic::catch_unwind(AssertUnwindSafe(body))
+        .map_err(|payload| set_last_error(format!("panic: {}", panic_message(&*payload))))
+        .ok()
+}
+
+/// The argument as a string, or `None` for null.
+unsafe fn argument<'a>(name: &str, value: *const c_char) -> AddonResult<Option<&'a str>> {
+    if value.is_null() {
+        return Ok(None);
+    }
+    CStr::from_ptr(value).to_str().map(Some).map_err(|_| AddonError::semantic(name, "argument is not valid UTF-8"))
+}
+
+unsafe fn required<'a>(name: &str, value: *const c_char) -> AddonResult<&'a str> {
+    argument(name, value)?.ok_or_else(|| AddonError::semantic(name, "argument is null"))
+}
+
+fn string_result(body: impl FnOnce() -> AddonResult<String>) -> *mut c_char {
+    let Some(result) = catch_panic(body) else {
+        return ptr::null_mut();
+    };
+    match result.map(|string| CString::new(string).map_err(|error| error.to_string())) {
+        Ok(Ok(string)) => string.into_raw(),
+        Ok(Err(error)) => {
+            set_last_error(error);
+            ptr::null_mut()
+        }
+        Err(error) => {
+            set_last_error(error.to_string());
+            ptr::null_mut()
+        }
+    }
+}
+
+fn status_result(body: impl FnOnce() -> AddonResult<()>) -> c_int {
+    match catch_panic(body) {
+        Some(Ok(())) => 0,
+        Some(Err(error)) => {
+            set_last_error(error.to_string());
+            -1
+        }
+        None => -1
+    }
+}
+
+/// The message of the last call on this thread that failed, or null if none has. The string stays
+/// valid until the next failing call on the same thread; don't free it.
+#[no_mangle]
+pub extern "C" fn bedrockrs_last_error() -> *const c_char {
+    catch_panic(|| LAST_ERROR.with(|error| error.borrow().as_ref().map_or(ptr::null(), |error| error.as_ptr()))).unwrap_or(ptr::null())
+}
+
+/// Frees a string returned by this library. Null is ignored.
+///
+/// # Safety
+///
+/// `string` must be null or a string this library returned that hasn't been freed yet.
+#[no_mangle]
+pub unsafe extern "C" fn bedrockrs_string_free(string: *mut c_char) {
+    if !string.is_null() {
+        catch_panic(|| drop(CString::from_raw(string)));
+    }
+}
+
+/// Parses the manifest JSON `json` and returns it in canonical form, or null if it's invalid.
+///
+/// # Safety
+///
+/// `json` must be null or a NUL-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn bedrockrs_manifest_parse(json: *const c_char) -> *mut c_char {
+    string_result(|| required("json", json).and_then(|json| deserialize_manifest_from_bytes(json.as_bytes())).map(|manifest| serialize_manifest_to_string(&manifest)))
+}
+
+/// Validates the behavior and resource pack folders, either of which may be null, and returns the
+/// diagnostics as a JSON array.
+///
+/// # Safety
+///
+/// Both arguments must be null or NUL-terminated strings.
+#[no_mangle]
+pub unsafe extern "C" fn bedrockrs_validate_addon(behavior_pack: *const c_char, resource_pack: *const c_char) -> *mut c_char {
+    string_result(|| {
+        let (behavior_pack, resource_pack) = (argument("behavior_pack", behavior_pack)?, argument("resource_pack", resource_pack)?);
+        let addon = AddonPaths { behavior_pack: behavior_pack.map(Path::new), resource_pack: resource_pack.map(Path::new), ..AddonPaths::default() };
+        Ok(format_json(&validate_addon_parallel(&addon).diagnostics.into_vec()))
+    })
+}
+
+/// Archives the pack folder `pack` into a new `.mcpack` at `archive`. Returns 0 on success.
+///
+/// # Safety
+///
+/// Both arguments must be null or NUL-terminated strings.
+#[no_mangle]
+pub unsafe extern "C" fn bedrockrs_mcpack_write(pack: *const c_char, archive: *const c_char) -> c_int {
+    status_result(|| {
+        let (pack, archive) = (Path::new(required("pack", pack)?), Path::new(required("archive", archive)?));
+        let file = std::fs::File::create(archive).map_err(|error| AddonError::io(archive, error))?;
+        write_mcpack(pack, std::io::BufWriter::new(file)).map(drop)
+    })
+}
+
+/// Unpacks the `.mcpack` or `.mcaddon` at `archive` into the folder `output`. Returns 0 on success.
+///
+/// # Safety
+///
+/// Both arguments must be null or NUL-terminated strings.
+#[no_mangle]
+pub unsafe extern "C" fn bedrockrs_mcpack_extract(archive: *const c_char, output: *const c_char) -> c_int {
+    status_result(|| extract_mcpack(required("archive", archive)?, required("output", output)?).map(drop))
+}
+
+/// Like [`bedrockrs_mcpack_extract`], failing without writing anything when an entry decompresses
+/// to more than `max_entry_size` bytes, all of them to more than `max_total_size` bytes, or the
+/// archive has more than `max_entries` entries. 0 keeps the default of that limit.
+///
+/// # Safety
+///
+/// `archive` and `output` must be null or NUL-terminated strings.
+#[no_mangle]
+pub unsafe extern "C" fn bedrockrs_mcpack_extract_with_limits(archive: *const c_char, output: *const c_char, max_entry_size: u64, max_total_size: u64, max_entries: u64) -> c_int {
+    status_result(|| {
+        let defaults = ArchiveLimits::default();
+        let or_default = |value: u64, default: u64| if value == 0 { default } else { value };
+        let limits = ArchiveLimits {
+            max_entry_size: or_default(max_entry_size, defaults.max_entry_size),
+            max_total_size: or_default(max_total_size, defaults.max_total_size),
+            max_entries: usize::try_from(max_entries).ok().filter(|max| *max != 0).unwrap_or(defaults.max_entries)
+        };
+        extract_mcpack_with_limits(required("archive", archive)?, required("output", output)?, &limits).map(drop)
+    })
+}
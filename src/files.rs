@@ -0,0 +1,128 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use crate::error::{read_to_string, AddonError, AddonResult};
+#[cfg(feature = "manifest")]
+use crate::generics::manifest::{deserialize_manifest_from_path, Manifest};
+use crate::utils::{from_json_str, JsonStyle};
+
+/// A JSON document of a pack, for content without a typed model.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct JsonFile {
+    /// Relative to the pack root.
+    pub path: PathBuf,
+    pub value: Value
+}
+
+fn collect_files(dir: &Path, files: &mut Vec<PathBuf>) {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return;
+    };
+
+    let mut paths: Vec<PathBuf> = entries.filter_map(|entry| entry.ok().map(|entry| entry.path())).collect();
+    paths.sort();
+
+    for path in paths {
+        if path.is_dir() {
+            collect_files(&path, files);
+        } else {
+            files.push(path);
+        }
+    }
+}
+
+/// Every file below `root/dir`, recursively and in a stable order.
+pub(crate) fn pack_files(root: &Path, dir: &str) -> Vec<PathBuf> {
+    let mut files = vec![];
+    collect_files(&root.join(dir), &mut files);
+    files
+}
+
+/// Matches a `/`-separated path against a glob where `*` and `?` stand for any run of characters
+/// and any one character within a folder or file name, and `**` for any number of folders.
+#[cfg(any(feature = "build", feature = "textures"))]
+pub(crate) fn glob_match(pattern: &str, path: &str) -> bool {
+    fn segment(pattern: &[char], name: &[char]) -> bool {
+        match pattern.split_first() {
+            None => name.is_empty(),
+            Some(('*', rest)) => (0..=name.len()).any(|skip| segment(rest, &name[skip..])),
+            Some(('?', rest)) => !name.is_empty() && segment(rest, &name[1..]),
+            Some((c, rest)) => name.first() == Some(c) && segment(rest, &name[1..])
+        }
+    }
+    fn segments(pattern: &[&str], path: &[&str]) -> bool {
+        match pattern.split_first() {
+            None => path.is_empty(),
+            Some((&"**", rest)) => (0..=path.len()).any(|skip| segments(rest, &path[skip..])),
+            Some((first, rest)) => path.first().is_some_and(|name| {
+                segment(&first.chars().collect::<Vec<_>>(), &name.chars().collect::<Vec<_>>()) && segments(rest, &path[1..])
+            })
+        }
+    }
+    segments(&pattern.split('/').collect::<Vec<_>>(), &path.split('/').collect::<Vec<_>>())
+}
+
+/// Reads `manifest.json` at the pack root, or `None` if it's missing or malformed.
+#[cfg(feature = "manifest")]
+pub(crate) fn read_manifest(root: &Path) -> Option<Manifest> {
+    deserialize_manifest_from_path(&root.join("manifest.json")).ok()
+}
+
+/// Reads a single JSON file relative to the pack root. Missing or malformed files yield `None`;
+/// reporting those is the job of the parsers, not the reference validators.
+pub fn read_json_file(root: &Path, path: &str) -> Option<JsonFile> {
+    let value = from_json_str(&fs::read_to_string(root.join(path)).ok()?).ok()?;
+    Some(JsonFile { path: PathBuf::from(path), value })
+}
+
+/// Reads every `.json` file below `root/dir`, e.g. a folder the crate has no typed model for.
+pub fn read_json_files(root: &Path, dir: &str) -> Vec<JsonFile> {
+    pack_files(root, dir).into_iter()
+        .filter(|path| path.extension().is_some_and(|ext| ext == "json"))
+        .filter_map(|path| {
+            let relative = path.strip_prefix(root).ok()?.to_string_lossy().replace('\\', "/");
+            read_json_file(root, &relative)
+        })
+        .collect()
+}
+
+/// Adds `entries` to the `texture_data` of the atlas at `path`, creating it if needed. Fails if
+/// the existing atlas isn't a JSON object rather than overwriting it.
+#[cfg(any(feature = "content", feature = "textures"))]
+pub(crate) fn merge_atlas(resource_pack: &Path, path: &str, texture_name: &str, entries: serde_json::Map<String, Value>) -> AddonResult<Value> {
+    let mut atlas = read_json_file(resource_pack, path).map(|file| file.value)
+        .unwrap_or_else(|| serde_json::json!({ "resource_pack_name": "pack.name", "texture_name": texture_name, "texture_data": {} }));
+    let Some(object) = atlas.as_object_mut() else {
+        return Err(AddonError::semantic(resource_pack.join(path), "texture atlas is not a JSON object"));
+    };
+    let data = object.entry("texture_data").or_insert_with(|| serde_json::json!({}));
+    if !data.is_object() {
+        *data = serde_json::json!({});
+    }
+    if let Some(data) = data.as_object_mut() {
+        data.extend(entries);
+    }
+    Ok(atlas)
+}
+
+/// A JSON file loaded for editing: change [`value`](EditableJson::value) and [`save`](EditableJson::save)
+/// it back in the indentation style it was read in.
+#[derive(Clone, Debug, PartialEq)]
+pub struct EditableJson {
+    pub path: PathBuf,
+    pub value: Value,
+    pub style: JsonStyle
+}
+
+impl EditableJson {
+    pub fn open(path: &Path) -> AddonResult<EditableJson> {
+        let src = read_to_string(path)?;
+        let value = from_json_str(&src).map_err(|error| AddonError::json(path, error))?;
+        Ok(EditableJson { path: path.to_path_buf(), value, style: JsonStyle::detect(&src) })
+    }
+
+    pub fn save(&self) -> AddonResult<()> {
+        fs::write(&self.path, self.style.write(&self.value)).map_err(|error| AddonError::io(&self.path, error))
+    }
+}
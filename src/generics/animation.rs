@@ -0,0 +1,231 @@
+use std::collections::BTreeMap;
+use std::path::Path;
+use serde_json::Value;
+use crate::error::{read_to_string, AddonError, AddonResult};
+use crate::molang::eval::MolangContext;
+use crate::molang::{molang_from_value, Molang};
+use crate::utils::from_json_str;
+
+/// How the value between a keyframe and the next one is found.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum Interpolation {
+    #[default]
+    Linear,
+    /// Smooth, through the keyframes around the two as well; `"lerp_mode": "catmullrom"`.
+    CatmullRom
+}
+
+/// A keyframe of a bone channel. The value the channel arrives at is `pre` and the one it leaves
+/// with is `post`; they only differ for keyframes that jump, which is how steps are written.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Keyframe {
+    pub time: f32,
+    pub pre: [Molang; 3],
+    pub post: [Molang; 3],
+    pub interpolation: Interpolation
+}
+
+/// The rotation, position or scale of a bone over time.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Channel {
+    /// Sorted by time. A channel written as a single value has one keyframe at 0.
+    pub keyframes: Vec<Keyframe>
+}
+
+/// Whether an animation starts over at its end.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum LoopMode {
+    #[default]
+    Once,
+    Loop,
+    HoldOnLastFrame
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct BoneAnimation {
+    pub rotation: Option<Channel>,
+    pub position: Option<Channel>,
+    pub scale: Option<Channel>
+}
+
+/// The transform of a bone at one point of an animation; channels a bone doesn't animate keep
+/// their rest value.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct BonePose {
+    pub rotation: [f32; 3],
+    pub position: [f32; 3],
+    pub scale: [f32; 3]
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Animation {
+    pub loop_mode: LoopMode,
+    pub animation_length: Option<f32>,
+    pub bones: BTreeMap<String, BoneAnimation>
+}
+
+/// A vector written as `[x, y, z]`, or as one value for all three axes.
+fn vector(value: &Value) -> Option<[Molang; 3]> {
+    match value {
+        Value::Array(axes) if axes.len() == 3 => Some([molang_from_value(&axes[0])?, molang_from_value(&axes[1])?, molang_from_value(&axes[2])?]),
+        Value::Array(axes) if axes.len() == 1 => vector(&axes[0]),
+        value => {
+            let axis = molang_from_value(value)?;
+            Some([axis.clone(), axis.clone(), axis])
+        }
+    }
+}
+
+fn keyframe(time: f32, value: &Value) -> Option<Keyframe> {
+    let Value::Object(map) = value else {
+        let value = vector(value)?;
+        return Some(Keyframe { time, pre: value.clone(), post: value, interpolation: Interpolation::Linear });
+    };
+    let pre = map.get("pre").and_then(vector);
+    let post = map.get("post").and_then(vector);
+    let interpolation = match map.get("lerp_mode").and_then(Value::as_str) {
+        Some("catmullrom") => Interpolation::CatmullRom,
+        _ => Interpolation::Linear
+    };
+    let (pre, post) = match (pre, post) {
+        (Some(pre), Some(post)) => (pre, post),
+        (Some(value), None) | (None, Some(value)) => (value.clone(), value),
+        (None, None) => return None
+    };
+    Some(Keyframe { time, pre, post, interpolation })
+}
+
+/// Reads a channel written as a single value or as keyframes keyed by their time in seconds.
+/// Keyframes that can't be read are skipped.
+pub fn deserialize_channel_from_value(value: &Value) -> Option<Channel> {
+    let timeline = value.as_object().filter(|map| !map.is_empty() && map.keys().all(|key| key.trim().parse::<f32>().is_ok()));
+    let mut keyframes: Vec<Keyframe> = match timeline {
+        Some(map) => map.iter().filter_map(|(time, value)| keyframe(time.trim().parse().ok()?, value)).collect(),
+        None => vec![keyframe(0.0, value)?]
+    };
+    keyframes.sort_by(|a, b| a.time.total_cmp(&b.time));
+    (!keyframes.is_empty()).then_some(Channel { keyframes })
+}
+
+fn evaluate(vector: &[Molang; 3], context: &mut MolangContext) -> [f32; 3] {
+    vector.each_ref().map(|axis| axis.evaluate(context).unwrap_or(0.0))
+}
+
+fn catmull_rom(p0: f32, p1: f32, p2: f32, p3: f32, t: f32) -> f32 {
+    0.5 * (2.0 * p1 + (p2 - p0) * t + (2.0 * p0 - 5.0 * p1 + 4.0 * p2 - p3) * t * t + (3.0 * p1 - p0 - 3.0 * p2 + p3) * t * t * t)
+}
+
+impl Channel {
+    /// The value of the channel `time` seconds into the animation, with `q.anim_time` set to
+    /// `time` for the keyframe expressions. Before the first keyframe the channel holds its `pre`
+    /// value and from the last one on its `post` value; exactly at a keyframe it has jumped to
+    /// `post` already.
+    pub fn value_at(&self, time: f32, context: &mut MolangContext) -> [f32; 3] {
+        context.queries.insert("anim_time".to_string(), time);
+        let keyframes = &self.keyframes;
+        let next = keyframes.partition_point(|keyframe| keyframe.time <= time);
+        if next == 0 {
+            return evaluate(&keyframes[0].pre, context);
+        }
+        if next == keyframes.len() {
+            return evaluate(&keyframes[next - 1].post, context);
+        }
+
+        let (from, to) = (&keyframes[next - 1], &keyframes[next]);
+        let t = (time - from.time) / (to.time - from.time);
+        let start = evaluate(&from.post, context);
+        let end = evaluate(&to.pre, context);
+        if from.interpolation != Interpolation::CatmullRom && to.interpolation != Interpolation::CatmullRom {
+            return [0, 1, 2].map(|axis| start[axis] + (end[axis] - start[axis]) * t);
+        }
+        // Without a keyframe beyond either end the curve is clamped to the end itself.
+        let before = match next.checked_sub(2) {
+            Some(index) => evaluate(&keyframes[index].post, context),
+            None => start
+        };
+        let after = match keyframes.get(next + 1) {
+            Some(keyframe) => evaluate(&keyframe.pre, context),
+            None => end
+        };
+        [0, 1, 2].map(|axis| catmull_rom(before[axis], start[axis], end[axis], after[axis], t))
+    }
+
+    /// The time of the last keyframe.
+    pub fn length(&self) -> f32 {
+        self.keyframes.last().map_or(0.0, |keyframe| keyframe.time)
+    }
+}
+
+impl BoneAnimation {
+    pub fn pose_at(&self, time: f32, context: &mut MolangContext) -> BonePose {
+        let sample = |channel: &Option<Channel>, rest: f32, context: &mut MolangContext| {
+            channel.as_ref().map_or([rest; 3], |channel| channel.value_at(time, context))
+        };
+        BonePose {
+            rotation: sample(&self.rotation, 0.0, context),
+            position: sample(&self.position, 0.0, context),
+            scale: sample(&self.scale, 1.0, context)
+        }
+    }
+}
+
+impl Animation {
+    /// The `animation_length`, or else the time of the last keyframe of any bone.
+    pub fn length(&self) -> f32 {
+        self.animation_length.unwrap_or_else(|| {
+            self.bones.values()
+                .flat_map(|bone| [&bone.rotation, &bone.position, &bone.scale])
+                .flatten()
+                .map(Channel::length)
+                .fold(0.0, f32::max)
+        })
+    }
+
+    /// The pose of every animated bone `time` seconds after the animation started, taking looping
+    /// into account.
+    pub fn pose_at(&self, time: f32, context: &mut MolangContext) -> BTreeMap<String, BonePose> {
+        let length = self.length();
+        let time = match self.loop_mode {
+            LoopMode::Loop if length > 0.0 => time.rem_euclid(length),
+            _ => time.min(length)
+        };
+        self.bones.iter().map(|(name, bone)| (name.clone(), bone.pose_at(time, context))).collect()
+    }
+
+    /// The pose of every animated bone at `fps` frames per second over the length of the
+    /// animation, both ends included.
+    pub fn bake(&self, fps: f32, context: &mut MolangContext) -> Vec<(f32, BTreeMap<String, BonePose>)> {
+        let frames = (self.length() * fps).ceil() as usize;
+        (0..=frames).map(|frame| {
+            let time = (frame as f32 / fps).min(self.length());
+            (time, self.pose_at(time, context))
+        }).collect()
+    }
+}
+
+fn bone(value: &Value) -> BoneAnimation {
+    let channel = |key: &str| value.get(key).and_then(deserialize_channel_from_value);
+    BoneAnimation { rotation: channel("rotation"), position: channel("position"), scale: channel("scale") }
+}
+
+/// Reads one animation of an animations document.
+pub fn deserialize_animation_from_value(value: &Value) -> Animation {
+    let loop_mode = match value.get("loop") {
+        Some(Value::Bool(true)) => LoopMode::Loop,
+        Some(Value::String(mode)) if mode == "hold_on_last_frame" => LoopMode::HoldOnLastFrame,
+        _ => LoopMode::Once
+    };
+    let bones = value.get("bones").and_then(Value::as_object).into_iter().flatten()
+        .map(|(name, value)| (name.clone(), bone(value)))
+        .collect();
+    Animation { loop_mode, animation_length: value.get("animation_length").and_then(Value::as_f64).map(|length| length as f32), bones }
+}
+
+/// Reads every animation of the animations file at `path`, by name.
+pub fn deserialize_animations_from_path(path: impl AsRef<Path>) -> AddonResult<BTreeMap<String, Animation>> {
+    let path = path.as_ref();
+    let document: Value = from_json_str(&read_to_string(path)?).map_err(|error| AddonError::json(path, error))?;
+    let animations = document.get("animations").and_then(Value::as_object)
+        .ok_or_else(|| AddonError::semantic(path, "not an animations file"))?;
+    Ok(animations.iter().map(|(name, value)| (name.clone(), deserialize_animation_from_value(value))).collect())
+}
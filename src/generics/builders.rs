@@ -0,0 +1,311 @@
+use serde_json::{json, Map, Value};
+use crate::generics::loot_table::{LootCondition, LootEntry, LootEntryKind, LootFunction, LootPool, LootRange, LootTable};
+use crate::molang::Molang;
+
+/// The `format_version` builders write unless told otherwise.
+pub const DEFAULT_FORMAT_VERSION: &str = "1.20.0";
+
+/// Builds a behavior item definition. The identifier is required up front; everything else is
+/// optional.
+#[derive(Clone, Debug)]
+pub struct ItemBuilder {
+    format_version: String,
+    description: Map<String, Value>,
+    components: Map<String, Value>
+}
+
+impl ItemBuilder {
+    pub fn new(identifier: &str) -> ItemBuilder {
+        let mut description = Map::new();
+        description.insert("identifier".to_string(), json!(identifier));
+        ItemBuilder { format_version: DEFAULT_FORMAT_VERSION.to_string(), description, components: Map::new() }
+    }
+
+    pub fn format_version(mut self, format_version: &str) -> ItemBuilder {
+        self.format_version = format_version.to_string();
+        self
+    }
+
+    /// The creative inventory tab, e.g. `equipment`.
+    pub fn menu_category(mut self, category: &str) -> ItemBuilder {
+        self.description.insert("menu_category".to_string(), json!({ "category": category }));
+        self
+    }
+
+    pub fn component(mut self, name: &str, value: Value) -> ItemBuilder {
+        self.components.insert(name.to_string(), value);
+        self
+    }
+
+    pub fn build(self) -> Value {
+        json!({
+            "format_version": self.format_version,
+            "minecraft:item": { "description": self.description, "components": self.components }
+        })
+    }
+}
+
+/// Builds a behavior block definition.
+#[derive(Clone, Debug)]
+pub struct BlockBuilder {
+    format_version: String,
+    description: Map<String, Value>,
+    components: Map<String, Value>,
+    permutations: Vec<Value>
+}
+
+impl BlockBuilder {
+    pub fn new(identifier: &str) -> BlockBuilder {
+        let mut description = Map::new();
+        description.insert("identifier".to_string(), json!(identifier));
+        BlockBuilder { format_version: DEFAULT_FORMAT_VERSION.to_string(), description, components: Map::new(), permutations: vec![] }
+    }
+
+    pub fn format_version(mut self, format_version: &str) -> BlockBuilder {
+        self.format_version = format_version.to_string();
+        self
+    }
+
+    pub fn menu_category(mut self, category: &str) -> BlockBuilder {
+        self.description.insert("menu_category".to_string(), json!({ "category": category }));
+        self
+    }
+
+    /// Declares a block state and the values it can take.
+    pub fn state(mut self, name: &str, values: Value) -> BlockBuilder {
+        let states = self.description.entry("states").or_insert_with(|| json!({}));
+        if let Some(states) = states.as_object_mut() {
+            states.insert(name.to_string(), values);
+        }
+        self
+    }
+
+    pub fn component(mut self, name: &str, value: Value) -> BlockBuilder {
+        self.components.insert(name.to_string(), value);
+        self
+    }
+
+    /// Components that apply while `condition` holds.
+    pub fn permutation(mut self, condition: impl Into<Molang>, components: Value) -> BlockBuilder {
+        self.permutations.push(json!({ "condition": condition.into(), "components": components }));
+        self
+    }
+
+    pub fn build(self) -> Value {
+        let mut block = json!({ "description": self.description, "components": self.components });
+        if !self.permutations.is_empty() {
+            block["permutations"] = Value::Array(self.permutations);
+        }
+        json!({ "format_version": self.format_version, "minecraft:block": block })
+    }
+}
+
+/// Builds a behavior entity definition.
+#[derive(Clone, Debug)]
+pub struct EntityBuilder {
+    format_version: String,
+    description: Map<String, Value>,
+    components: Map<String, Value>,
+    component_groups: Map<String, Value>,
+    events: Map<String, Value>
+}
+
+impl EntityBuilder {
+    pub fn new(identifier: &str) -> EntityBuilder {
+        let mut description = Map::new();
+        description.insert("identifier".to_string(), json!(identifier));
+        description.insert("is_spawnable".to_string(), json!(false));
+        description.insert("is_summonable".to_string(), json!(true));
+        EntityBuilder {
+            format_version: DEFAULT_FORMAT_VERSION.to_string(), description, components: Map::new(),
+            component_groups: Map::new(), events: Map::new()
+        }
+    }
+
+    pub fn format_version(mut self, format_version: &str) -> EntityBuilder {
+        self.format_version = format_version.to_string();
+        self
+    }
+
+    /// Whether the entity has a spawn egg and can spawn naturally.
+    pub fn spawnable(mut self, spawnable: bool) -> EntityBuilder {
+        self.description.insert("is_spawnable".to_string(), json!(spawnable));
+        self
+    }
+
+    pub fn summonable(mut self, summonable: bool) -> EntityBuilder {
+        self.description.insert("is_summonable".to_string(), json!(summonable));
+        self
+    }
+
+    pub fn component(mut self, name: &str, value: Value) -> EntityBuilder {
+        self.components.insert(name.to_string(), value);
+        self
+    }
+
+    pub fn component_group(mut self, name: &str, components: Value) -> EntityBuilder {
+        self.component_groups.insert(name.to_string(), components);
+        self
+    }
+
+    pub fn event(mut self, name: &str, event: Value) -> EntityBuilder {
+        self.events.insert(name.to_string(), event);
+        self
+    }
+
+    pub fn build(self) -> Value {
+        let mut entity = json!({ "description": self.description, "components": self.components });
+        if !self.component_groups.is_empty() {
+            entity["component_groups"] = Value::Object(self.component_groups);
+        }
+        if !self.events.is_empty() {
+            entity["events"] = Value::Object(self.events);
+        }
+        json!({ "format_version": self.format_version, "minecraft:entity": entity })
+    }
+}
+
+/// Builds a recipe. Each constructor takes the fields its recipe type requires.
+#[derive(Clone, Debug)]
+pub struct RecipeBuilder {
+    format_version: String,
+    kind: &'static str,
+    recipe: Map<String, Value>
+}
+
+impl RecipeBuilder {
+    fn new(kind: &'static str, identifier: &str, tags: &[&str]) -> RecipeBuilder {
+        let mut recipe = Map::new();
+        recipe.insert("description".to_string(), json!({ "identifier": identifier }));
+        recipe.insert("tags".to_string(), json!(tags));
+        RecipeBuilder { format_version: DEFAULT_FORMAT_VERSION.to_string(), kind, recipe }
+    }
+
+    /// A crafting table recipe; bind the pattern's characters with [`key`](RecipeBuilder::key).
+    pub fn shaped(identifier: &str, pattern: &[&str], result: &str) -> RecipeBuilder {
+        let mut builder = RecipeBuilder::new("minecraft:recipe_shaped", identifier, &["crafting_table"]);
+        builder.recipe.insert("pattern".to_string(), json!(pattern));
+        builder.recipe.insert("key".to_string(), json!({}));
+        builder.recipe.insert("result".to_string(), json!({ "item": result }));
+        builder
+    }
+
+    pub fn shapeless(identifier: &str, ingredients: &[&str], result: &str) -> RecipeBuilder {
+        let mut builder = RecipeBuilder::new("minecraft:recipe_shapeless", identifier, &["crafting_table"]);
+        let ingredients: Vec<Value> = ingredients.iter().map(|item| json!({ "item": item })).collect();
+        builder.recipe.insert("ingredients".to_string(), Value::Array(ingredients));
+        builder.recipe.insert("result".to_string(), json!({ "item": result }));
+        builder
+    }
+
+    pub fn furnace(identifier: &str, input: &str, output: &str) -> RecipeBuilder {
+        let mut builder = RecipeBuilder::new("minecraft:recipe_furnace", identifier, &["furnace"]);
+        builder.recipe.insert("input".to_string(), json!(input));
+        builder.recipe.insert("output".to_string(), json!(output));
+        builder
+    }
+
+    pub fn format_version(mut self, format_version: &str) -> RecipeBuilder {
+        self.format_version = format_version.to_string();
+        self
+    }
+
+    /// Replaces the crafting stations the recipe is available at.
+    pub fn tags(mut self, tags: &[&str]) -> RecipeBuilder {
+        self.recipe.insert("tags".to_string(), json!(tags));
+        self
+    }
+
+    /// Binds a character of a shaped recipe's pattern to an item.
+    pub fn key(mut self, symbol: char, item: &str) -> RecipeBuilder {
+        if let Some(key) = self.recipe.get_mut("key").and_then(Value::as_object_mut) {
+            key.insert(symbol.to_string(), json!({ "item": item }));
+        }
+        self
+    }
+
+    pub fn result_count(mut self, count: u32) -> RecipeBuilder {
+        if let Some(result) = self.recipe.get_mut("result").and_then(Value::as_object_mut) {
+            result.insert("count".to_string(), json!(count));
+        }
+        self
+    }
+
+    pub fn priority(mut self, priority: i64) -> RecipeBuilder {
+        self.recipe.insert("priority".to_string(), json!(priority));
+        self
+    }
+
+    pub fn build(self) -> Value {
+        let mut root = Map::new();
+        root.insert("format_version".to_string(), json!(self.format_version));
+        root.insert(self.kind.to_string(), Value::Object(self.recipe));
+        Value::Object(root)
+    }
+}
+
+/// Builds a [`LootTable`] pool by pool.
+#[derive(Clone, Debug, Default)]
+pub struct LootTableBuilder {
+    pools: Vec<LootPool>
+}
+
+impl LootTableBuilder {
+    pub fn new() -> LootTableBuilder {
+        LootTableBuilder::default()
+    }
+
+    pub fn pool(mut self, pool: LootPoolBuilder) -> LootTableBuilder {
+        self.pools.push(pool.pool);
+        self
+    }
+
+    pub fn build(self) -> LootTable {
+        let mut table = LootTable { pools: self.pools, raw: Value::Null };
+        table.raw = serde_json::to_value(&table).unwrap_or_default();
+        table
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct LootPoolBuilder {
+    pool: LootPool
+}
+
+impl LootPoolBuilder {
+    pub fn new(rolls: LootRange) -> LootPoolBuilder {
+        LootPoolBuilder { pool: LootPool { rolls, conditions: vec![], entries: vec![] } }
+    }
+
+    /// Adds an item entry, dropping `count` of it when picked.
+    pub fn item(mut self, item: &str, weight: u32, count: LootRange) -> LootPoolBuilder {
+        let functions = if count == LootRange::Exact(1.0) {
+            vec![]
+        } else {
+            vec![LootFunction { function: "set_count".to_string(), count: Some(count) }]
+        };
+        self.pool.entries.push(LootEntry {
+            kind: LootEntryKind::Item, name: Some(item.to_string()), weight, functions, conditions: vec![]
+        });
+        self
+    }
+
+    /// Adds an entry that rolls the loot table at `path`, relative to the behavior pack root.
+    pub fn loot_table(mut self, path: &str, weight: u32) -> LootPoolBuilder {
+        self.pool.entries.push(LootEntry {
+            kind: LootEntryKind::LootTable, name: Some(path.to_string()), weight, functions: vec![], conditions: vec![]
+        });
+        self
+    }
+
+    pub fn empty(mut self, weight: u32) -> LootPoolBuilder {
+        self.pool.entries.push(LootEntry { kind: LootEntryKind::Empty, name: None, weight, functions: vec![], conditions: vec![] });
+        self
+    }
+
+    /// Only rolls the pool with the given chance.
+    pub fn random_chance(mut self, chance: f64) -> LootPoolBuilder {
+        self.pool.conditions.push(LootCondition { condition: "random_chance".to_string(), chance: Some(chance) });
+        self
+    }
+}
@@ -0,0 +1,163 @@
+use std::collections::BTreeMap;
+use serde_json::{json, Map, Value};
+#[cfg(feature = "molang")]
+use crate::molang::{molang_from_value, Molang};
+
+/// A `sound_effects` entry of a client entity.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SoundEffect {
+    /// The sound event played, as `sound_definitions.json` names it.
+    pub effect: String,
+    pub other: Map<String, Value>
+}
+
+/// The particle and sound shortnames a client entity or attachable declares for its animations
+/// and animation controllers to use.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct EffectBindings {
+    /// Particle effect identifiers by shortname.
+    pub particle_effects: BTreeMap<String, String>,
+    /// Particle emitter identifiers by shortname, the older spelling of `particle_effects`.
+    pub particle_emitters: BTreeMap<String, String>,
+    pub sound_effects: BTreeMap<String, SoundEffect>
+}
+
+impl EffectBindings {
+    /// The particle effect the shortname `name` plays, from either map.
+    pub fn particle(&self, name: &str) -> Option<&str> {
+        self.particle_effects.get(name).or_else(|| self.particle_emitters.get(name)).map(String::as_str)
+    }
+
+    /// The sound event the shortname `name` plays.
+    pub fn sound(&self, name: &str) -> Option<&str> {
+        self.sound_effects.get(name).map(|sound| sound.effect.as_str())
+    }
+}
+
+fn identifiers(description: &Value, key: &str) -> BTreeMap<String, String> {
+    description.get(key).and_then(Value::as_object).into_iter().flatten()
+        .filter_map(|(name, identifier)| Some((name.clone(), identifier.as_str()?.to_string())))
+        .collect()
+}
+
+/// Reads the effect maps of a client entity or attachable `description`. Entries that aren't
+/// shaped like a binding are skipped.
+pub fn deserialize_effect_bindings_from_value(description: &Value) -> EffectBindings {
+    let sound_effects = description.get("sound_effects").and_then(Value::as_object).into_iter().flatten()
+        .filter_map(|(name, sound)| {
+            let mut other = sound.as_object()?.clone();
+            let effect = other.remove("effect")?.as_str()?.to_string();
+            Some((name.clone(), SoundEffect { effect, other }))
+        })
+        .collect();
+    EffectBindings {
+        particle_effects: identifiers(description, "particle_effects"),
+        particle_emitters: identifiers(description, "particle_emitters"),
+        sound_effects
+    }
+}
+
+/// Writes `bindings` into a client entity or attachable `description`, replacing its effect maps
+/// and leaving out empty ones.
+pub fn serialize_effect_bindings_into(bindings: &EffectBindings, description: &mut Map<String, Value>) {
+    for (key, map) in [("particle_effects", &bindings.particle_effects), ("particle_emitters", &bindings.particle_emitters)] {
+        description.remove(key);
+        if !map.is_empty() {
+            description.insert(key.to_string(), json!(map));
+        }
+    }
+    description.remove("sound_effects");
+    if !bindings.sound_effects.is_empty() {
+        let sounds: Map<String, Value> = bindings.sound_effects.iter().map(|(name, sound)| {
+            let mut entry = sound.other.clone();
+            entry.insert("effect".to_string(), json!(sound.effect));
+            (name.clone(), Value::Object(entry))
+        }).collect();
+        description.insert("sound_effects".to_string(), Value::Object(sounds));
+    }
+}
+
+/// An `animate` entry of client entity scripts: the shortname of an animation or animation
+/// controller, played while `condition` holds, or always without one.
+#[cfg(feature = "molang")]
+#[derive(Clone, Debug, PartialEq)]
+pub struct AnimateEntry {
+    pub name: String,
+    pub condition: Option<Molang>
+}
+
+/// The `scripts` of a client entity or attachable description.
+#[cfg(feature = "molang")]
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ClientScripts {
+    /// Run once when the entity is created.
+    pub initialize: Vec<Molang>,
+    /// Run every frame before the animations.
+    pub pre_animation: Vec<Molang>,
+    pub scale: Option<Molang>,
+    pub animate: Vec<AnimateEntry>,
+    /// The other fields, e.g. `scalex` and `should_update_bones_and_effects_offscreen`.
+    pub other: Map<String, Value>
+}
+
+/// Takes the field `key` out of `scripts` if it is an array.
+#[cfg(feature = "molang")]
+fn take_array(scripts: &mut Map<String, Value>, key: &str) -> Vec<Value> {
+    if !scripts.get(key).is_some_and(Value::is_array) {
+        return vec![];
+    }
+    match scripts.remove(key) {
+        Some(Value::Array(values)) => values,
+        _ => vec![]
+    }
+}
+
+/// Reads the `scripts` of a client entity or attachable `description`. Fields of an unexpected
+/// shape stay in [`other`](ClientScripts::other).
+#[cfg(feature = "molang")]
+pub fn deserialize_client_scripts_from_value(description: &Value) -> ClientScripts {
+    let mut other = description.get("scripts").and_then(Value::as_object).cloned().unwrap_or_default();
+    let initialize = take_array(&mut other, "initialize").iter().filter_map(molang_from_value).collect();
+    let pre_animation = take_array(&mut other, "pre_animation").iter().filter_map(molang_from_value).collect();
+    let scale = other.get("scale").and_then(molang_from_value);
+    if scale.is_some() {
+        other.remove("scale");
+    }
+    let animate = take_array(&mut other, "animate").into_iter()
+        .filter_map(|entry| match entry {
+            Value::String(name) => Some(AnimateEntry { name, condition: None }),
+            Value::Object(entry) => {
+                let (name, condition) = entry.into_iter().next()?;
+                Some(AnimateEntry { name, condition: molang_from_value(&condition) })
+            }
+            _ => None
+        })
+        .collect();
+    ClientScripts { initialize, pre_animation, scale, animate, other }
+}
+
+/// Writes `scripts` into a client entity or attachable `description`, replacing its `scripts`
+/// and leaving out empty fields, or the whole object when nothing is left.
+#[cfg(feature = "molang")]
+pub fn serialize_client_scripts_into(scripts: &ClientScripts, description: &mut Map<String, Value>) {
+    let mut object = scripts.other.clone();
+    for (key, expressions) in [("initialize", &scripts.initialize), ("pre_animation", &scripts.pre_animation)] {
+        if !expressions.is_empty() {
+            object.insert(key.to_string(), json!(expressions));
+        }
+    }
+    if let Some(scale) = &scripts.scale {
+        object.insert("scale".to_string(), json!(scale));
+    }
+    if !scripts.animate.is_empty() {
+        let animate: Vec<Value> = scripts.animate.iter().map(|entry| match &entry.condition {
+            Some(condition) => json!({ entry.name.clone(): condition }),
+            None => json!(entry.name)
+        }).collect();
+        object.insert("animate".to_string(), Value::Array(animate));
+    }
+    description.remove("scripts");
+    if !object.is_empty() {
+        description.insert("scripts".to_string(), Value::Object(object));
+    }
+}
@@ -0,0 +1,114 @@
+use uuid::Uuid;
+use crate::generics::manifest::{Manifest, ManifestDependency};
+use crate::utils::SemVer;
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum DependencyIssue {
+    /// No manifest has the required uuid.
+    Missing { pack: usize, uuid: Uuid, version: SemVer },
+    /// Packs with the required uuid exist, but none in the required version.
+    VersionMismatch { pack: usize, uuid: Uuid, required: SemVer, available: Vec<SemVer> },
+    /// Packs that depend on each other, each depending on the next and the last on the first.
+    Cycle(Vec<usize>)
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct DependencyResolution {
+    /// For every pack, the indices of the packs it depends on.
+    pub dependencies: Vec<Vec<usize>>,
+    pub issues: Vec<DependencyIssue>,
+    /// The packs that can be activated, each after all of its dependencies. Packs with unresolved
+    /// dependencies or in a cycle, and the packs depending on them, are left out.
+    pub activation_order: Vec<usize>
+}
+
+#[derive(Clone, Copy, Eq, PartialEq)]
+enum State {
+    Unvisited,
+    Visiting,
+    Activated,
+    Failed
+}
+
+struct Resolver<'a> {
+    dependencies: &'a [Vec<usize>],
+    states: Vec<State>,
+    stack: Vec<usize>,
+    issues: Vec<DependencyIssue>,
+    order: Vec<usize>
+}
+
+impl Resolver<'_> {
+    fn visit(&mut self, pack: usize) -> bool {
+        match self.states[pack] {
+            State::Activated => return true,
+            State::Failed => return false,
+            State::Visiting => {
+                let start = self.stack.iter().position(|p| *p == pack).unwrap();
+                self.issues.push(DependencyIssue::Cycle(self.stack[start..].to_vec()));
+                return false;
+            }
+            State::Unvisited => {}
+        }
+
+        self.states[pack] = State::Visiting;
+        self.stack.push(pack);
+        let mut ok = true;
+        for dependency in &self.dependencies[pack] {
+            // Keep visiting after a failure so every cycle gets reported.
+            ok &= self.visit(*dependency);
+        }
+        self.stack.pop();
+
+        if ok {
+            self.states[pack] = State::Activated;
+            self.order.push(pack);
+        } else {
+            self.states[pack] = State::Failed;
+        }
+        ok
+    }
+}
+
+/// Resolves the uuid dependencies between `manifests`, e.g. every pack installed in `com.mojang`.
+/// Script module dependencies are provided by the game and ignored.
+pub fn resolve_pack_dependencies(manifests: &[Manifest]) -> DependencyResolution {
+    let mut issues = vec![];
+    let mut dependencies = vec![];
+
+    for (pack, manifest) in manifests.iter().enumerate() {
+        let mut resolved = vec![];
+        for dependency in &manifest.dependencies {
+            let ManifestDependency::UuidDependency(uuid, version) = dependency else {
+                continue;
+            };
+            let candidates: Vec<usize> = (0..manifests.len()).filter(|i| manifests[*i].header.uuid == *uuid).collect();
+            match candidates.iter().find(|i| manifests[**i].header.version == *version) {
+                Some(found) => resolved.push(*found),
+                None if candidates.is_empty() => issues.push(DependencyIssue::Missing { pack, uuid: *uuid, version: version.clone() }),
+                None => issues.push(DependencyIssue::VersionMismatch {
+                    pack,
+                    uuid: *uuid,
+                    required: version.clone(),
+                    available: candidates.iter().map(|i| manifests[*i].header.version.clone()).collect()
+                })
+            }
+        }
+        dependencies.push(resolved);
+    }
+
+    // Packs with unresolved dependencies can't be activated.
+    let mut states = vec![State::Unvisited; manifests.len()];
+    for issue in &issues {
+        if let DependencyIssue::Missing { pack, .. } | DependencyIssue::VersionMismatch { pack, .. } = issue {
+            states[*pack] = State::Failed;
+        }
+    }
+
+    let mut resolver = Resolver { dependencies: &dependencies, states, stack: vec![], issues, order: vec![] };
+    for pack in 0..manifests.len() {
+        resolver.visit(pack);
+    }
+
+    DependencyResolution { issues: resolver.issues, activation_order: resolver.order, dependencies }
+}
@@ -0,0 +1,251 @@
+use std::collections::BTreeMap;
+use std::path::Path;
+use serde_json::{json, Map, Value};
+use crate::error::{read_to_string, AddonError, AddonResult};
+use crate::utils::{from_json_str, parse_format_version_from_str, SemVer};
+
+/// The format version behavior entities are written with.
+pub const ENTITY_FORMAT_VERSION: &str = "1.21.0";
+
+/// The layout of a behavior entity document, told apart by its format version.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum EntityLayout {
+    /// Before 1.10.0: sensors and interactions with a single trigger written directly in the
+    /// component.
+    V1_8,
+    /// 1.10.0 until 1.16.0: trigger lists, which may still be a single object.
+    V1_10,
+    /// 1.16.0 and later.
+    Current
+}
+
+/// The [`EntityLayout`] of a behavior entity document. A missing or unreadable format version
+/// counts as the oldest layout.
+pub fn entity_layout(document: &Value) -> EntityLayout {
+    let version = document.get("format_version").and_then(Value::as_str).and_then(parse_format_version_from_str);
+    match version {
+        Some(version) if version >= SemVer::new(1, 16, 0) => EntityLayout::Current,
+        Some(version) if version >= SemVer::new(1, 10, 0) => EntityLayout::V1_10,
+        _ => EntityLayout::V1_8
+    }
+}
+
+/// An entity event, or one step of a `sequence` or `randomize` of one.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct EntityEvent {
+    /// When the step applies, for steps of a `sequence`.
+    pub filters: Option<Value>,
+    /// The relative chance of the step, for steps of a `randomize`.
+    pub weight: Option<f64>,
+    /// Component groups added and removed.
+    pub add: Vec<String>,
+    pub remove: Vec<String>,
+    /// Another event fired, as a name or a `{ "event", "target" }` object.
+    pub trigger: Option<Value>,
+    pub sequence: Vec<EntityEvent>,
+    pub randomize: Vec<EntityEvent>,
+    /// Every other response, e.g. `set_property` or `queue_command`.
+    pub other: Map<String, Value>
+}
+
+impl EntityEvent {
+    /// The name of the event the [`trigger`](EntityEvent::trigger) fires.
+    pub fn triggered_event(&self) -> Option<&str> {
+        match self.trigger.as_ref()? {
+            Value::String(event) => Some(event),
+            trigger => trigger.get("event")?.as_str()
+        }
+    }
+}
+
+/// A behavior entity in one model whatever layout it was written in.
+#[derive(Clone, Debug, PartialEq)]
+pub struct BehaviorEntity {
+    /// The layout the entity was read from.
+    pub layout: EntityLayout,
+    pub identifier: String,
+    pub is_spawnable: bool,
+    pub is_summonable: bool,
+    pub runtime_identifier: Option<String>,
+    /// The entity properties declared in the description, e.g. `gems:mood`.
+    pub properties: Map<String, Value>,
+    pub components: Map<String, Value>,
+    pub component_groups: BTreeMap<String, Map<String, Value>>,
+    pub events: BTreeMap<String, EntityEvent>,
+    /// The other fields of the description, e.g. `animations` and `scripts`.
+    pub description: Map<String, Value>
+}
+
+/// A component holding a list of triggers under `list`, whose fields older layouts wrote directly
+/// in the component, or under `single` for a lone trigger.
+struct TriggerList {
+    component: &'static str,
+    list: &'static str,
+    single: Option<&'static str>,
+    /// The fields of one trigger, when written directly in the component.
+    fields: &'static [&'static str]
+}
+
+const TRIGGER_LISTS: [TriggerList; 4] = [
+    TriggerList { component: "minecraft:environment_sensor", list: "triggers", single: Some("on_environment"), fields: &[] },
+    TriggerList {
+        component: "minecraft:damage_sensor", list: "triggers", single: None,
+        fields: &["on_damage", "cause", "deals_damage", "damage_multiplier", "damage_modifier", "on_damage_sound_event"]
+    },
+    TriggerList {
+        component: "minecraft:interact", list: "interactions", single: None,
+        fields: &[
+            "on_interact", "interact_text", "use_item", "cooldown", "swing", "hurt_item", "play_sounds", "spawn_items",
+            "spawn_entities", "add_items", "transform_to_item", "particle_on_start", "give_item", "take_item"
+        ]
+    },
+    TriggerList {
+        component: "minecraft:spawn_entity", list: "entities", single: None,
+        fields: &["min_wait_time", "max_wait_time", "spawn_entity", "spawn_item", "spawn_event", "spawn_method", "spawn_sound", "single_use", "num_to_spawn", "should_leash", "filters"]
+    }
+];
+
+/// Rewrites the trigger components of a component object into their list form.
+fn normalize_components(components: &mut Map<String, Value>) {
+    for list in &TRIGGER_LISTS {
+        let Some(Value::Object(component)) = components.get_mut(list.component) else {
+            continue;
+        };
+        let mut triggers = match component.remove(list.list) {
+            Some(Value::Array(triggers)) => triggers,
+            Some(trigger) => vec![trigger],
+            None => vec![]
+        };
+        if let Some(trigger) = list.single.and_then(|single| component.remove(single)) {
+            triggers.push(trigger);
+        }
+        let loose: Map<String, Value> = list.fields.iter().filter_map(|field| Some((field.to_string(), component.remove(*field)?))).collect();
+        if !loose.is_empty() {
+            triggers.push(Value::Object(loose));
+        }
+        component.insert(list.list.to_string(), Value::Array(triggers));
+    }
+}
+
+fn group_names(value: Option<&Value>) -> Vec<String> {
+    value.and_then(|value| value.get("component_groups")).and_then(Value::as_array).into_iter().flatten()
+        .filter_map(|name| Some(name.as_str()?.to_string()))
+        .collect()
+}
+
+fn parse_event(value: &Value) -> EntityEvent {
+    let mut event = EntityEvent::default();
+    let Value::Object(map) = value else {
+        return event;
+    };
+    for (key, value) in map {
+        match key.as_str() {
+            "filters" => event.filters = Some(value.clone()),
+            "weight" => event.weight = value.as_f64(),
+            "add" => event.add = group_names(Some(value)),
+            "remove" => event.remove = group_names(Some(value)),
+            "trigger" => event.trigger = Some(value.clone()),
+            "sequence" => event.sequence = value.as_array().into_iter().flatten().map(parse_event).collect(),
+            "randomize" => event.randomize = value.as_array().into_iter().flatten().map(parse_event).collect(),
+            _ => {
+                event.other.insert(key.clone(), value.clone());
+            }
+        }
+    }
+    event
+}
+
+fn serialize_event(event: &EntityEvent) -> Value {
+    let mut map = event.other.clone();
+    if let Some(filters) = &event.filters {
+        map.insert("filters".to_string(), filters.clone());
+    }
+    if let Some(weight) = event.weight {
+        map.insert("weight".to_string(), json!(weight));
+    }
+    if !event.add.is_empty() {
+        map.insert("add".to_string(), json!({ "component_groups": event.add }));
+    }
+    if !event.remove.is_empty() {
+        map.insert("remove".to_string(), json!({ "component_groups": event.remove }));
+    }
+    if let Some(trigger) = &event.trigger {
+        map.insert("trigger".to_string(), trigger.clone());
+    }
+    if !event.sequence.is_empty() {
+        map.insert("sequence".to_string(), event.sequence.iter().map(serialize_event).collect());
+    }
+    if !event.randomize.is_empty() {
+        map.insert("randomize".to_string(), event.randomize.iter().map(serialize_event).collect());
+    }
+    Value::Object(map)
+}
+
+/// Reads a behavior entity document of any [`EntityLayout`], or `None` if it doesn't define an
+/// entity with an identifier. Trigger components are read into their list form, so e.g. the
+/// `on_damage` of a 1.8 `minecraft:damage_sensor` ends up as its only entry of `triggers`, and the
+/// obsolete `is_experimental` is dropped.
+pub fn deserialize_behavior_entity_from_value(document: &Value) -> Option<BehaviorEntity> {
+    let entity = document.get("minecraft:entity")?;
+    let mut description = entity.get("description")?.as_object()?.clone();
+    let identifier = description.remove("identifier")?.as_str()?.to_string();
+    description.remove("is_experimental");
+    let flag = |description: &mut Map<String, Value>, key: &str, default: bool| description.remove(key).and_then(|value| value.as_bool()).unwrap_or(default);
+    let is_spawnable = flag(&mut description, "is_spawnable", false);
+    let is_summonable = flag(&mut description, "is_summonable", true);
+    let runtime_identifier = description.remove("runtime_identifier").and_then(|value| Some(value.as_str()?.to_string()));
+    let properties = match description.remove("properties") {
+        Some(Value::Object(properties)) => properties,
+        _ => Map::new()
+    };
+
+    let mut components = entity.get("components").and_then(Value::as_object).cloned().unwrap_or_default();
+    normalize_components(&mut components);
+    let mut component_groups = BTreeMap::new();
+    for (name, group) in entity.get("component_groups").and_then(Value::as_object).into_iter().flatten() {
+        let mut group = group.as_object().cloned().unwrap_or_default();
+        normalize_components(&mut group);
+        component_groups.insert(name.clone(), group);
+    }
+    let events = entity.get("events").and_then(Value::as_object).into_iter().flatten()
+        .map(|(name, event)| (name.clone(), parse_event(event)))
+        .collect();
+
+    Some(BehaviorEntity {
+        layout: entity_layout(document), identifier, is_spawnable, is_summonable, runtime_identifier, properties,
+        components, component_groups, events, description
+    })
+}
+
+/// Reads the behavior entity file at `path` with [`deserialize_behavior_entity_from_value`].
+pub fn deserialize_behavior_entity_from_path(path: impl AsRef<Path>) -> AddonResult<BehaviorEntity> {
+    let path = path.as_ref();
+    let document: Value = from_json_str(&read_to_string(path)?).map_err(|error| AddonError::json(path, error))?;
+    deserialize_behavior_entity_from_value(&document).ok_or_else(|| AddonError::semantic(path, "not a behavior entity"))
+}
+
+/// Writes `entity` in the current layout, with [`ENTITY_FORMAT_VERSION`].
+pub fn serialize_behavior_entity_to_value(entity: &BehaviorEntity) -> Value {
+    let mut description = entity.description.clone();
+    description.insert("identifier".to_string(), json!(entity.identifier));
+    description.insert("is_spawnable".to_string(), json!(entity.is_spawnable));
+    description.insert("is_summonable".to_string(), json!(entity.is_summonable));
+    if let Some(runtime_identifier) = &entity.runtime_identifier {
+        description.insert("runtime_identifier".to_string(), json!(runtime_identifier));
+    }
+    if !entity.properties.is_empty() {
+        description.insert("properties".to_string(), Value::Object(entity.properties.clone()));
+    }
+
+    let mut body = Map::new();
+    body.insert("description".to_string(), Value::Object(description));
+    body.insert("components".to_string(), Value::Object(entity.components.clone()));
+    if !entity.component_groups.is_empty() {
+        body.insert("component_groups".to_string(), json!(entity.component_groups));
+    }
+    if !entity.events.is_empty() {
+        let events: Map<String, Value> = entity.events.iter().map(|(name, event)| (name.clone(), serialize_event(event))).collect();
+        body.insert("events".to_string(), Value::Object(events));
+    }
+    json!({ "format_version": ENTITY_FORMAT_VERSION, "minecraft:entity": body })
+}
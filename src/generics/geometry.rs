@@ -0,0 +1,330 @@
+use std::path::Path;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use crate::error::{read_to_string, AddonError, AddonResult};
+use crate::utils::{from_json_str, to_canonical_json_string};
+
+/// A geometry file of the `minecraft:geometry` format (1.12.0 and later).
+///
+/// Every object keeps the fields it doesn't model in `other`, so reading and writing a file loses
+/// nothing; legacy `"geometry.a:geometry.b"` entries end up there too.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct GeometryFile {
+    pub format_version: String,
+    #[serde(rename = "minecraft:geometry", default)]
+    pub geometries: Vec<Geometry>,
+    #[serde(flatten)]
+    pub other: Map<String, Value>
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct Geometry {
+    pub description: GeometryDescription,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub bones: Vec<Bone>,
+    #[serde(flatten)]
+    pub other: Map<String, Value>
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct GeometryDescription {
+    pub identifier: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub texture_width: Option<f32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub texture_height: Option<f32>,
+    #[serde(flatten)]
+    pub other: Map<String, Value>
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct Bone {
+    pub name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub parent: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pivot: Option<[f32; 3]>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rotation: Option<[f32; 3]>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mirror: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub inflate: Option<f32>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub cubes: Vec<Cube>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub poly_mesh: Option<PolyMesh>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub texture_meshes: Vec<TextureMesh>,
+    #[serde(flatten)]
+    pub other: Map<String, Value>
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct Cube {
+    pub origin: [f32; 3],
+    pub size: [f32; 3],
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pivot: Option<[f32; 3]>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rotation: Option<[f32; 3]>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub inflate: Option<f32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mirror: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub uv: Option<CubeUv>,
+    #[serde(flatten)]
+    pub other: Map<String, Value>
+}
+
+/// A texture rendered as a flat shape extruded one pixel deep, the way held items are.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct TextureMesh {
+    /// The client entity texture shortname, e.g. `default`.
+    pub texture: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub position: Option<[f32; 3]>,
+    /// The point rotation and scale are applied around, relative to `position`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub local_pivot: Option<[f32; 3]>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rotation: Option<[f32; 3]>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub scale: Option<[f32; 3]>,
+    #[serde(flatten)]
+    pub other: Map<String, Value>
+}
+
+/// How a cube's faces map to the texture.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(untagged)]
+pub enum CubeUv {
+    /// The corner of the unfolded box the game lays out from the cube's size.
+    Box([f32; 2]),
+    PerFace(Box<PerFaceUv>)
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+pub struct PerFaceUv {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub north: Option<FaceUv>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub south: Option<FaceUv>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub east: Option<FaceUv>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub west: Option<FaceUv>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub up: Option<FaceUv>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub down: Option<FaceUv>
+}
+
+/// The texture area of one face. A negative size flips the face along that axis.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct FaceUv {
+    pub uv: [f32; 2],
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub uv_size: Option<[f32; 2]>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub material_instance: Option<String>,
+    #[serde(flatten)]
+    pub other: Map<String, Value>
+}
+
+impl FaceUv {
+    fn new(uv: [f32; 2], uv_size: [f32; 2]) -> FaceUv {
+        FaceUv { uv, uv_size: Some(uv_size), material_instance: None, other: Map::new() }
+    }
+}
+
+/// The per-face layout a box UV at `uv` gives a cube of `size`: the sides in a row below the top
+/// and bottom, east, north, west, south from left to right. Mirroring flips every face
+/// horizontally and swaps east and west.
+pub fn box_uv_faces(uv: [f32; 2], size: [f32; 3], mirror: bool) -> PerFaceUv {
+    let [x, y] = uv;
+    let [w, h, d] = size;
+    let face = |u: f32, v: f32, width: f32, height: f32| {
+        if mirror { FaceUv::new([u + width, v], [-width, height]) } else { FaceUv::new([u, v], [width, height]) }
+    };
+    let (east, west) = (face(x, y + d, d, h), face(x + d + w, y + d, d, h));
+    let (east, west) = if mirror { (west, east) } else { (east, west) };
+    PerFaceUv {
+        north: Some(face(x + d, y + d, w, h)),
+        south: Some(face(x + 2.0 * d + w, y + d, w, h)),
+        east: Some(east),
+        west: Some(west),
+        up: Some(face(x + d + w, y + d, -w, -d)),
+        down: Some(face(x + d + 2.0 * w, y, -w, d))
+    }
+}
+
+fn same_face(a: &FaceUv, b: &FaceUv) -> bool {
+    let close = |a: &[f32], b: &[f32]| a.iter().zip(b).all(|(a, b)| (a - b).abs() < 1e-4);
+    close(&a.uv, &b.uv) && close(&a.uv_size.unwrap_or([0.0; 2]), &b.uv_size.unwrap_or([0.0; 2]))
+        && a.material_instance == b.material_instance && a.other == b.other
+}
+
+/// The box UV a cube of `size` would need to get exactly `faces`, or `None` if the faces aren't
+/// laid out like a box: one is missing, moved, resized or has its own material instance.
+pub fn per_face_box_uv(faces: &PerFaceUv, size: [f32; 3], mirror: bool) -> Option<[f32; 2]> {
+    let [w, _, d] = size;
+    let east = faces.east.as_ref()?.uv;
+    // Mirrored, the east face is where west would be, starting at its right edge.
+    let uv = if mirror { [east[0] - 2.0 * d - w, east[1] - d] } else { [east[0], east[1] - d] };
+    let expected = box_uv_faces(uv, size, mirror);
+    let pairs = [
+        (&faces.north, &expected.north), (&faces.south, &expected.south), (&faces.east, &expected.east),
+        (&faces.west, &expected.west), (&faces.up, &expected.up), (&faces.down, &expected.down)
+    ];
+    pairs.iter().all(|(face, expected)| match (face, expected) {
+        (Some(face), Some(expected)) => same_face(face, expected),
+        _ => false
+    }).then_some(uv)
+}
+
+/// Free-form geometry for a bone, made of polygons whose vertices index into the position,
+/// normal and UV lists.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct PolyMesh {
+    /// Whether `uvs` are in 0 to 1 rather than in texture pixels.
+    #[serde(default)]
+    pub normalized_uvs: bool,
+    #[serde(default)]
+    pub positions: Vec<[f32; 3]>,
+    #[serde(default)]
+    pub normals: Vec<[f32; 3]>,
+    #[serde(default)]
+    pub uvs: Vec<[f32; 2]>,
+    pub polys: Polys
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Eq, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum PolyList {
+    /// Every three entries of the position, normal and UV lists form a triangle.
+    TriList,
+    /// Every four entries form a quad.
+    QuadList
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(untagged)]
+pub enum Polys {
+    /// Triangles and quads, each vertex as `[position, normal, uv]` indices.
+    Indexed(Vec<Vec<[u32; 3]>>),
+    List(PolyList)
+}
+
+/// Why a [`PolyMesh`] can't be rendered as written.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum PolyMeshIssue {
+    /// A polygon with a vertex count other than 3 or 4.
+    VertexCount { poly: usize, count: usize },
+    /// A vertex index past the end of its list; `list` is `positions`, `normals` or `uvs`.
+    IndexOutOfRange { poly: usize, list: &'static str, index: u32 },
+    /// A `tri_list` or `quad_list` whose lists differ in length or don't divide into whole
+    /// polygons.
+    UnevenList
+}
+
+impl PolyMesh {
+    /// The polygons of the mesh as `[position, normal, uv]` indices, with list layouts expanded.
+    pub fn polygons(&self) -> Vec<Vec<[u32; 3]>> {
+        match &self.polys {
+            Polys::Indexed(polys) => polys.clone(),
+            Polys::List(list) => {
+                let size = match list {
+                    PolyList::TriList => 3,
+                    PolyList::QuadList => 4
+                };
+                let count = self.positions.len().min(self.normals.len()).min(self.uvs.len()) as u32;
+                (0..count / size).map(|poly| (poly * size..(poly + 1) * size).map(|i| [i, i, i]).collect()).collect()
+            }
+        }
+    }
+
+    /// Checks that every polygon is a triangle or quad whose vertices index existing entries.
+    pub fn validate(&self) -> Vec<PolyMeshIssue> {
+        if let Polys::List(list) = self.polys {
+            let size = if list == PolyList::TriList { 3 } else { 4 };
+            let len = self.positions.len();
+            return if len != self.normals.len() || len != self.uvs.len() || !len.is_multiple_of(size) {
+                vec![PolyMeshIssue::UnevenList]
+            } else {
+                vec![]
+            };
+        }
+
+        let mut issues = vec![];
+        let lists = [("positions", self.positions.len()), ("normals", self.normals.len()), ("uvs", self.uvs.len())];
+        for (i, poly) in self.polygons().iter().enumerate() {
+            if poly.len() != 3 && poly.len() != 4 {
+                issues.push(PolyMeshIssue::VertexCount { poly: i, count: poly.len() });
+            }
+            for vertex in poly {
+                for ((list, len), index) in lists.iter().zip(vertex) {
+                    if *index as usize >= *len {
+                        issues.push(PolyMeshIssue::IndexOutOfRange { poly: i, list, index: *index });
+                    }
+                }
+            }
+        }
+        issues
+    }
+}
+
+impl Bone {
+    /// Whether `cube` is mirrored, itself or through the bone.
+    pub fn is_mirrored(&self, cube: &Cube) -> bool {
+        cube.mirror.or(self.mirror).unwrap_or(false)
+    }
+
+    /// Rewrites the box UVs of the bone's cubes as per-face UVs mapping the same texture areas.
+    pub fn convert_uv_to_per_face(&mut self) {
+        for i in 0..self.cubes.len() {
+            let mirror = self.is_mirrored(&self.cubes[i]);
+            let cube = &mut self.cubes[i];
+            if let Some(CubeUv::Box(uv)) = cube.uv {
+                cube.uv = Some(CubeUv::PerFace(Box::new(box_uv_faces(uv, cube.size, mirror))));
+            }
+        }
+    }
+
+    /// Rewrites the per-face UVs of the bone's cubes as box UVs where
+    /// [that's possible](per_face_box_uv), returning how many cubes were left as they are.
+    pub fn convert_uv_to_box(&mut self) -> usize {
+        let mut left = 0;
+        for i in 0..self.cubes.len() {
+            let mirror = self.is_mirrored(&self.cubes[i]);
+            let cube = &mut self.cubes[i];
+            if let Some(CubeUv::PerFace(faces)) = &cube.uv {
+                match per_face_box_uv(faces, cube.size, mirror) {
+                    Some(uv) => cube.uv = Some(CubeUv::Box(uv)),
+                    None => left += 1
+                }
+            }
+        }
+        left
+    }
+}
+
+impl GeometryFile {
+    pub fn geometry(&self, identifier: &str) -> Option<&Geometry> {
+        self.geometries.iter().find(|geometry| geometry.description.identifier == identifier)
+    }
+}
+
+pub fn deserialize_geometry_from_str(src: &str) -> serde_json::Result<GeometryFile> {
+    from_json_str(src)
+}
+
+pub fn deserialize_geometry_from_path(path: impl AsRef<Path>) -> AddonResult<GeometryFile> {
+    let path = path.as_ref();
+    deserialize_geometry_from_str(&read_to_string(path)?).map_err(|error| AddonError::json(path, error))
+}
+
+pub fn serialize_geometry_to_string(file: &GeometryFile) -> String {
+    to_canonical_json_string(&serde_json::to_value(file).expect("geometry is always representable as JSON"))
+}
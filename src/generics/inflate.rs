@@ -0,0 +1,191 @@
+//! Decompression of raw DEFLATE streams (RFC 1951), the compression method of nearly every zip
+//! archive other tools produce.
+
+const MAX_BITS: usize = 15;
+
+/// Base lengths and extra bits of length symbols 257 to 285.
+const LENGTH_BASES: [u16; 29] = [3, 4, 5, 6, 7, 8, 9, 10, 11, 13, 15, 17, 19, 23, 27, 31, 35, 43, 51, 59, 67, 83, 99, 115, 131, 163, 195, 227, 258];
+const LENGTH_EXTRA: [u8; 29] = [0, 0, 0, 0, 0, 0, 0, 0, 1, 1, 1, 1, 2, 2, 2, 2, 3, 3, 3, 3, 4, 4, 4, 4, 5, 5, 5, 5, 0];
+/// Base distances and extra bits of distance symbols 0 to 29.
+const DISTANCE_BASES: [u16; 30] = [
+    1, 2, 3, 4, 5, 7, 9, 13, 17, 25, 33, 49, 65, 97, 129, 193, 257, 385, 513, 769, 1025, 1537, 2049, 3073, 4097, 6145, 8193, 12289, 16385, 24577
+];
+const DISTANCE_EXTRA: [u8; 30] = [0, 0, 0, 0, 1, 1, 2, 2, 3, 3, 4, 4, 5, 5, 6, 6, 7, 7, 8, 8, 9, 9, 10, 10, 11, 11, 12, 12, 13, 13];
+/// The order code length code lengths are stored in.
+const CODE_LENGTH_ORDER: [usize; 19] = [16, 17, 18, 0, 8, 7, 9, 6, 10, 5, 11, 4, 12, 3, 13, 2, 14, 1, 15];
+
+/// A canonical Huffman code: how many codes there are of each length, and the symbols in code
+/// order.
+struct Huffman {
+    counts: [u16; MAX_BITS + 1],
+    symbols: Vec<u16>
+}
+
+impl Huffman {
+    fn new(lengths: &[u8]) -> Huffman {
+        let mut counts = [0u16; MAX_BITS + 1];
+        for length in lengths {
+            counts[*length as usize] += 1;
+        }
+        counts[0] = 0;
+        let mut offsets = [0u16; MAX_BITS + 2];
+        for length in 1..=MAX_BITS {
+            offsets[length + 1] = offsets[length] + counts[length];
+        }
+        let mut symbols = vec![0; lengths.len()];
+        for (symbol, length) in lengths.iter().enumerate().filter(|(_, length)| **length != 0) {
+            symbols[offsets[*length as usize] as usize] = symbol as u16;
+            offsets[*length as usize] += 1;
+        }
+        Huffman { counts, symbols }
+    }
+}
+
+struct Inflater<'a> {
+    input: &'a [u8],
+    position: usize,
+    buffer: u32,
+    buffered: u32,
+    output: Vec<u8>,
+    limit: usize
+}
+
+impl Inflater<'_> {
+    fn bits(&mut self, count: u32) -> Result<u32, &'static str> {
+        while self.buffered < count {
+            let byte = *self.input.get(self.position).ok_or("unexpected end of compressed data")?;
+            self.position += 1;
+            self.buffer |= (byte as u32) << self.buffered;
+            self.buffered += 8;
+        }
+        let value = self.buffer & ((1u64 << count) - 1) as u32;
+        self.buffer >>= count;
+        self.buffered -= count;
+        Ok(value)
+    }
+
+    fn decode(&mut self, huffman: &Huffman) -> Result<u16, &'static str> {
+        // Canonical codes of each length are consecutive, so walk the lengths comparing the code read
+        // so far against the range of codes that length covers.
+        let (mut code, mut first, mut index) = (0i32, 0i32, 0i32);
+        for length in 1..=MAX_BITS {
+            code |= self.bits(1)? as i32;
+            let count = huffman.counts[length] as i32;
+            if code - first < count {
+                return huffman.symbols.get((index + code - first) as usize).copied().ok_or("invalid Huffman code");
+            }
+            index += count;
+            first = (first + count) << 1;
+            code <<= 1;
+        }
+        Err("invalid Huffman code")
+    }
+
+    fn push(&mut self, byte: u8) -> Result<(), &'static str> {
+        if self.output.len() >= self.limit {
+            return Err("data is larger than its declared size");
+        }
+        self.output.push(byte);
+        Ok(())
+    }
+
+    fn stored(&mut self) -> Result<(), &'static str> {
+        self.buffer = 0;
+        self.buffered = 0;
+        let header = self.input.get(self.position..self.position + 4).ok_or("unexpected end of compressed data")?;
+        let length = u16::from_le_bytes([header[0], header[1]]);
+        if length != !u16::from_le_bytes([header[2], header[3]]) {
+            return Err("stored block length doesn't match its complement");
+        }
+        self.position += 4;
+        let bytes = self.input.get(self.position..self.position + length as usize).ok_or("unexpected end of compressed data")?;
+        if self.output.len() + bytes.len() > self.limit {
+            return Err("data is larger than its declared size");
+        }
+        self.output.extend_from_slice(bytes);
+        self.position += length as usize;
+        Ok(())
+    }
+
+    fn codes(&mut self, literals: &Huffman, distances: &Huffman) -> Result<(), &'static str> {
+        loop {
+            let symbol = self.decode(literals)? as usize;
+            match symbol {
+                0..=255 => self.push(symbol as u8)?,
+                256 => return Ok(()),
+                _ => {
+                    let index = symbol - 257;
+                    let (Some(base), Some(extra)) = (LENGTH_BASES.get(index), LENGTH_EXTRA.get(index)) else {
+                        return Err("invalid length symbol");
+                    };
+                    let length = *base as usize + self.bits(*extra as u32)? as usize;
+                    let index = self.decode(distances)? as usize;
+                    let (Some(base), Some(extra)) = (DISTANCE_BASES.get(index), DISTANCE_EXTRA.get(index)) else {
+                        return Err("invalid distance symbol");
+                    };
+                    let distance = *base as usize + self.bits(*extra as u32)? as usize;
+                    if distance > self.output.len() {
+                        return Err("distance reaches before the start of the data");
+                    }
+                    for _ in 0..length {
+                        self.push(self.output[self.output.len() - distance])?;
+                    }
+                }
+            }
+        }
+    }
+
+    fn fixed(&mut self) -> Result<(), &'static str> {
+        let mut lengths = [0u8; 288];
+        lengths[..144].fill(8);
+        lengths[144..256].fill(9);
+        lengths[256..280].fill(7);
+        lengths[280..].fill(8);
+        self.codes(&Huffman::new(&lengths), &Huffman::new(&[5; 30]))
+    }
+
+    fn dynamic(&mut self) -> Result<(), &'static str> {
+        let literal_count = self.bits(5)? as usize + 257;
+        let distance_count = self.bits(5)? as usize + 1;
+        let code_length_count = self.bits(4)? as usize + 4;
+
+        let mut code_lengths = [0u8; 19];
+        for index in CODE_LENGTH_ORDER.iter().take(code_length_count) {
+            code_lengths[*index] = self.bits(3)? as u8;
+        }
+        let code_lengths = Huffman::new(&code_lengths);
+
+        let mut lengths = vec![];
+        while lengths.len() < literal_count + distance_count {
+            let (length, repeat) = match self.decode(&code_lengths)? {
+                symbol @ 0..=15 => (symbol as u8, 1),
+                16 => (*lengths.last().ok_or("repeat with no previous length")?, 3 + self.bits(2)?),
+                17 => (0, 3 + self.bits(3)?),
+                _ => (0, 11 + self.bits(7)?)
+            };
+            lengths.extend(std::iter::repeat_n(length, repeat as usize));
+        }
+        if lengths.len() > literal_count + distance_count {
+            return Err("code lengths overrun their count");
+        }
+        let (literals, distances) = lengths.split_at(literal_count);
+        self.codes(&Huffman::new(literals), &Huffman::new(distances))
+    }
+}
+
+/// Decompresses `input`, failing if the result would be larger than `limit` bytes.
+pub(crate) fn inflate(input: &[u8], limit: usize) -> Result<Vec<u8>, &'static str> {
+    let mut inflater = Inflater { input, position: 0, buffer: 0, buffered: 0, output: Vec::with_capacity(limit.min(1 << 24)), limit };
+    loop {
+        let last = inflater.bits(1)? == 1;
+        match inflater.bits(2)? {
+            0 => inflater.stored()?,
+            1 => inflater.fixed()?,
+            2 => inflater.dynamic()?,
+            _ => return Err("invalid block type")
+        }
+        if last {
+            return Ok(inflater.output);
+        }
+    }
+}
@@ -0,0 +1,116 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+use serde_json::Value;
+use crate::error::{read_to_string, AddonError, AddonResult};
+use crate::files::read_json_files;
+use crate::utils::to_canonical_json_string;
+
+/// Parses a `.lang` file into its translations. Lines are `key=value`; `##` starts a comment at
+/// the beginning of a line or after a tab. A key defined twice keeps its last value.
+pub fn parse_lang_from_str(src: &str) -> BTreeMap<String, String> {
+    let mut entries = BTreeMap::new();
+
+    for line in src.trim_start_matches('\u{feff}').lines() {
+        let line = line.trim_start();
+        if line.starts_with("##") {
+            continue;
+        }
+        let Some((key, value)) = line.split_once('=') else {
+            continue;
+        };
+        let value = value.split_once("\t##").map(|(value, _)| value).unwrap_or(value);
+        entries.insert(key.trim().to_string(), value.trim_end().to_string());
+    }
+
+    entries
+}
+
+/// A display name guessed from an identifier: the part after the namespace, split at `_`, `.` and
+/// `-`, with every word capitalized. `gems:ruby_ore` becomes `Ruby Ore`.
+pub fn title_case(identifier: &str) -> String {
+    let name = identifier.split_once(':').map(|(_, name)| name).unwrap_or(identifier);
+    name.split(['_', '.', '-']).filter(|word| !word.is_empty()).map(|word| {
+        let mut chars = word.chars();
+        chars.next().map(|first| first.to_uppercase().chain(chars).collect::<String>()).unwrap_or_default()
+    }).collect::<Vec<_>>().join(" ")
+}
+
+/// The translation keys the custom (non-`minecraft:`) items, blocks and entities of `behavior_pack`
+/// show in game, with a [title-cased](title_case) name for each: `item.<id>.name`,
+/// `tile.<id>.name`, `entity.<id>.name`, the spawn egg of spawnable entities and the dismount hint
+/// of rideable ones. An item or block whose `minecraft:display_name` is a key needs that key instead.
+pub fn content_lang_keys(behavior_pack: &Path) -> BTreeMap<String, String> {
+    let mut keys = BTreeMap::new();
+    for file in read_json_files(behavior_pack, "") {
+        for (definition, prefix) in [("minecraft:item", "item"), ("minecraft:block", "tile"), ("minecraft:entity", "entity")] {
+            let Some(body) = file.value.get(definition) else {
+                continue;
+            };
+            let Some(identifier) = body.pointer("/description/identifier").and_then(Value::as_str) else {
+                continue;
+            };
+            if identifier.starts_with("minecraft:") {
+                continue;
+            }
+            let name = title_case(identifier);
+            let display_name = body.pointer("/components/minecraft:display_name")
+                .and_then(|value| value.as_str().or_else(|| value.get("value")?.as_str()));
+            match display_name {
+                // Literal text needs no translation.
+                Some(display_name) if display_name.contains(char::is_whitespace) || !display_name.contains('.') => {}
+                Some(key) => {
+                    keys.insert(key.to_string(), name.clone());
+                }
+                None => {
+                    keys.insert(format!("{}.{}.name", prefix, identifier), name.clone());
+                }
+            }
+            if definition == "minecraft:entity" {
+                if body.pointer("/description/is_spawnable").and_then(Value::as_bool) == Some(true) {
+                    keys.insert(format!("item.spawn_egg.entity.{}.name", identifier), format!("Spawn {}", name));
+                }
+                let rideable = body.pointer("/components/minecraft:rideable").is_some()
+                    || body.get("component_groups").and_then(Value::as_object).is_some_and(|groups| groups.values().any(|group| group.get("minecraft:rideable").is_some()));
+                if rideable {
+                    keys.insert(format!("action.hint.exit.{}", identifier), "Tap :_input_key.sneak: to dismount".to_string());
+                }
+            }
+        }
+    }
+    keys
+}
+
+/// Adds the [content keys](content_lang_keys) of `behavior_pack` that `texts/en_US.lang` of
+/// `resource_pack` lacks to the end of it, creating the file (and `languages.json`) if needed.
+/// Existing translations are never changed. Returns the entries added.
+pub fn write_missing_lang_keys(behavior_pack: &Path, resource_pack: &Path) -> AddonResult<BTreeMap<String, String>> {
+    add_lang_keys(resource_pack, content_lang_keys(behavior_pack))
+}
+
+/// Adds the entries of `keys` that `texts/en_US.lang` of `resource_pack` lacks to the end of it,
+/// like [`write_missing_lang_keys`] does. Returns the entries added.
+pub(crate) fn add_lang_keys(resource_pack: &Path, keys: BTreeMap<String, String>) -> AddonResult<BTreeMap<String, String>> {
+    let path = resource_pack.join("texts/en_US.lang");
+    let mut src = if path.exists() { read_to_string(&path)? } else { String::new() };
+    let existing = parse_lang_from_str(&src);
+    let missing: BTreeMap<String, String> = keys.into_iter().filter(|(key, _)| !existing.contains_key(key)).collect();
+    if missing.is_empty() {
+        return Ok(missing);
+    }
+
+    if !src.is_empty() && !src.ends_with('\n') {
+        src.push('\n');
+    }
+    for (key, value) in &missing {
+        src.push_str(&format!("{}={}\n", key, value));
+    }
+    let texts = resource_pack.join("texts");
+    fs::create_dir_all(&texts).map_err(|error| AddonError::io(&texts, error))?;
+    fs::write(&path, src).map_err(|error| AddonError::io(&path, error))?;
+    let languages = texts.join("languages.json");
+    if !languages.exists() {
+        fs::write(&languages, to_canonical_json_string(&serde_json::json!(["en_US"]))).map_err(|error| AddonError::io(&languages, error))?;
+    }
+    Ok(missing)
+}
@@ -0,0 +1,232 @@
+use std::collections::{BTreeMap, HashMap};
+use std::fmt::{Display, Formatter};
+use std::fs;
+use std::path::Path;
+use once_cell::sync::Lazy;
+use crate::error::{AddonError, AddonResult};
+use crate::nbt::{read_nbt, write_nbt, Compound, NbtError, Tag};
+
+/// The storage version current versions of the game write in the `level.dat` header.
+pub const STORAGE_VERSION: i32 = 10;
+
+/// The game rules Bedrock saves in `level.dat`, among the other world settings.
+pub static GAME_RULES: Lazy<Vec<&'static str>> = Lazy::new(|| vec![
+    "commandblockoutput", "commandblocksenabled", "dodaylightcycle", "doentitydrops", "dofiretick",
+    "doimmediaterespawn", "doinsomnia", "dolimitedcrafting", "domobloot", "domobspawning", "dotiledrops",
+    "doweathercycle", "drowningdamage", "falldamage", "firedamage", "freezedamage", "functioncommandlimit",
+    "keepinventory", "maxcommandchainlength", "mobgriefing", "naturalregeneration", "playerssleepingpercentage",
+    "projectilescanbreakblocks", "pvp", "randomtickspeed", "recipesunlock", "respawnblocksexplode",
+    "sendcommandfeedback", "showbordereffect", "showcoordinates", "showdaysplayed", "showdeathmessages",
+    "showrecipemessages", "showtags", "spawnradius", "tntexplodes", "tntexplosiondropdecay"
+]);
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum GameRule {
+    Bool(bool),
+    Int(i32)
+}
+
+/// An experimental gameplay toggle, by the key `level.dat` saves it under.
+#[derive(Clone, Debug, Eq, PartialEq, Hash)]
+pub enum Experiment {
+    /// Beta APIs, needed by scripts using beta module versions.
+    BetaApis,
+    CustomBiomes,
+    UpcomingCreatorFeatures,
+    HolidayCreatorFeatures,
+    MolangFeatures,
+    VillagerTradeRebalance,
+    JigsawStructures,
+    Custom(String)
+}
+
+pub(crate) static EXPERIMENTS: Lazy<HashMap<&str, Experiment>> = Lazy::new(||
+    HashMap::from(
+        [
+            ("gametest", Experiment::BetaApis),
+            ("data_driven_biomes", Experiment::CustomBiomes),
+            ("upcoming_creator_features", Experiment::UpcomingCreatorFeatures),
+            ("data_driven_items", Experiment::HolidayCreatorFeatures),
+            ("experimental_molang_features", Experiment::MolangFeatures),
+            ("villager_trades_rebalance", Experiment::VillagerTradeRebalance),
+            ("jigsaw_structures", Experiment::JigsawStructures),
+        ]
+    )
+);
+
+/// Bookkeeping the game keeps next to the toggles in the `experiments` compound.
+const EXPERIMENT_MARKERS: [&str; 2] = ["experiments_ever_used", "saved_with_toggled_experiments"];
+
+impl Experiment {
+    /// Known experiments get their own variant, anything else is [`Custom`](Experiment::Custom).
+    pub fn from_name(name: &str) -> Experiment {
+        EXPERIMENTS.get(name).cloned().unwrap_or_else(|| Experiment::Custom(name.to_string()))
+    }
+
+    pub fn name(&self) -> &str {
+        match self {
+            Experiment::BetaApis => "gametest",
+            Experiment::CustomBiomes => "data_driven_biomes",
+            Experiment::UpcomingCreatorFeatures => "upcoming_creator_features",
+            Experiment::HolidayCreatorFeatures => "data_driven_items",
+            Experiment::MolangFeatures => "experimental_molang_features",
+            Experiment::VillagerTradeRebalance => "villager_trades_rebalance",
+            Experiment::JigsawStructures => "jigsaw_structures",
+            Experiment::Custom(name) => name
+        }
+    }
+}
+
+impl Display for Experiment {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// A world's `level.dat`. The whole root compound is kept, so settings without an accessor
+/// survive a save and can be edited through [`root`](LevelDat::root).
+#[derive(Clone, Debug, PartialEq)]
+pub struct LevelDat {
+    /// From the header, see [`STORAGE_VERSION`].
+    pub storage_version: i32,
+    pub root: Compound
+}
+
+impl LevelDat {
+    pub fn new(level_name: &str) -> LevelDat {
+        let mut level = LevelDat { storage_version: STORAGE_VERSION, root: Compound::new() };
+        level.root.insert("StorageVersion".to_string(), Tag::Int(STORAGE_VERSION));
+        level.set_level_name(level_name);
+        level
+    }
+
+    pub fn level_name(&self) -> Option<&str> {
+        self.root.get("LevelName").and_then(Tag::as_str)
+    }
+
+    pub fn set_level_name(&mut self, name: &str) {
+        self.root.insert("LevelName".to_string(), Tag::String(name.to_string()));
+    }
+
+    /// The world spawn as x, y and z.
+    pub fn spawn(&self) -> Option<[i32; 3]> {
+        let [x, y, z] = ["SpawnX", "SpawnY", "SpawnZ"].map(|key| self.root.get(key).and_then(Tag::as_i32));
+        Some([x?, y?, z?])
+    }
+
+    pub fn set_spawn(&mut self, spawn: [i32; 3]) {
+        for (key, value) in ["SpawnX", "SpawnY", "SpawnZ"].into_iter().zip(spawn) {
+            self.root.insert(key.to_string(), Tag::Int(value));
+        }
+    }
+
+    /// A boolean setting such as `commandsEnabled`, `cheatsEnabled` or `educationFeaturesEnabled`.
+    pub fn flag(&self, key: &str) -> Option<bool> {
+        self.root.get(key).and_then(Tag::as_i64).map(|value| value != 0)
+    }
+
+    pub fn set_flag(&mut self, key: &str, value: bool) {
+        self.root.insert(key.to_string(), Tag::Byte(value as i8));
+    }
+
+    /// The game rules in [`GAME_RULES`] the world has a value for.
+    pub fn game_rules(&self) -> BTreeMap<String, GameRule> {
+        GAME_RULES.iter().filter_map(|name| Some((name.to_string(), self.game_rule(name)?))).collect()
+    }
+
+    /// A game rule by name, e.g. `keepinventory`. Names are matched case-insensitively like the
+    /// `/gamerule` command does.
+    pub fn game_rule(&self, name: &str) -> Option<GameRule> {
+        match self.root.get(&name.to_lowercase())? {
+            Tag::Byte(value) => Some(GameRule::Bool(*value != 0)),
+            Tag::Int(value) => Some(GameRule::Int(*value)),
+            _ => None
+        }
+    }
+
+    pub fn set_game_rule(&mut self, name: &str, value: GameRule) {
+        let tag = match value {
+            GameRule::Bool(value) => Tag::Byte(value as i8),
+            GameRule::Int(value) => Tag::Int(value)
+        };
+        self.root.insert(name.to_lowercase(), tag);
+    }
+
+    /// Whether the experiment saved under `name` in the `experiments` compound is on, e.g.
+    /// `gametest`. `None` if the world never had it toggled.
+    pub fn experiment(&self, name: &str) -> Option<bool> {
+        self.root.get("experiments")?.get(name)?.as_i64().map(|value| value != 0)
+    }
+
+    /// Toggles an experiment, marking the world as having used experiments when turning one on
+    /// like the game does.
+    pub fn set_experiment(&mut self, name: &str, enabled: bool) {
+        let experiments = self.root.entry("experiments".to_string()).or_insert_with(|| Tag::Compound(Compound::new()));
+        if experiments.as_compound().is_none() {
+            *experiments = Tag::Compound(Compound::new());
+        }
+        let Some(experiments) = experiments.as_compound_mut() else {
+            return;
+        };
+        experiments.insert(name.to_string(), Tag::Byte(enabled as i8));
+        if enabled {
+            experiments.insert("experiments_ever_used".to_string(), Tag::Byte(1));
+            experiments.insert("saved_with_toggled_experiments".to_string(), Tag::Byte(1));
+        }
+    }
+
+    /// The experiments turned on, sorted by name.
+    pub fn experiments(&self) -> Vec<Experiment> {
+        let Some(experiments) = self.root.get("experiments").and_then(Tag::as_compound) else {
+            return vec![];
+        };
+        experiments.iter()
+            .filter(|(name, value)| !EXPERIMENT_MARKERS.contains(&name.as_str()) && value.as_i64().is_some_and(|value| value != 0))
+            .map(|(name, _)| Experiment::from_name(name))
+            .collect()
+    }
+
+    pub fn enable_experiment(&mut self, experiment: &Experiment) {
+        self.set_experiment(experiment.name(), true);
+    }
+
+    pub fn disable_experiment(&mut self, experiment: &Experiment) {
+        self.set_experiment(experiment.name(), false);
+    }
+}
+
+/// Reads a `level.dat`: a little-endian storage version and payload length, then the NBT root.
+pub fn deserialize_level_dat_from_bytes(bytes: &[u8]) -> Result<LevelDat, NbtError> {
+    if bytes.len() < 8 {
+        return Err(NbtError::UnexpectedEnd);
+    }
+    let storage_version = i32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]);
+    let length = u32::from_le_bytes([bytes[4], bytes[5], bytes[6], bytes[7]]) as usize;
+    let payload = bytes[8..].get(..length).ok_or(NbtError::UnexpectedEnd)?;
+    let (_, root) = read_nbt(payload)?;
+    match root {
+        Tag::Compound(root) => Ok(LevelDat { storage_version, root }),
+        _ => Err(NbtError::MissingTag("root compound".to_string()))
+    }
+}
+
+pub fn deserialize_level_dat_from_path(path: impl AsRef<Path>) -> AddonResult<LevelDat> {
+    let path = path.as_ref();
+    let bytes = fs::read(path).map_err(|error| AddonError::io(path, error))?;
+    deserialize_level_dat_from_bytes(&bytes).map_err(|error| AddonError::semantic(path, error.to_string()))
+}
+
+pub fn serialize_level_dat_to_bytes(level: &LevelDat) -> Result<Vec<u8>, NbtError> {
+    let payload = write_nbt("", &Tag::Compound(level.root.clone()))?;
+    let mut bytes = Vec::with_capacity(payload.len() + 8);
+    bytes.extend(level.storage_version.to_le_bytes());
+    bytes.extend((payload.len() as u32).to_le_bytes());
+    bytes.extend(payload);
+    Ok(bytes)
+}
+
+pub fn write_level_dat_to_path(level: &LevelDat, path: impl AsRef<Path>) -> AddonResult<()> {
+    let path = path.as_ref();
+    let bytes = serialize_level_dat_to_bytes(level).map_err(|error| AddonError::semantic(path, error.to_string()))?;
+    fs::write(path, bytes).map_err(|error| AddonError::io(path, error))
+}
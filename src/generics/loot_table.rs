@@ -0,0 +1,236 @@
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::path::{Path, PathBuf};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use crate::error::{AddonError, AddonResult};
+use crate::utils::from_json_str;
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct LootTable {
+    #[serde(default)]
+    pub pools: Vec<LootPool>,
+    /// The JSON the table was parsed from, including fields not modeled above.
+    #[serde(skip)]
+    pub raw: Value
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct LootPool {
+    #[serde(default = "LootRange::one")]
+    pub rolls: LootRange,
+    #[serde(default)]
+    pub conditions: Vec<LootCondition>,
+    #[serde(default)]
+    pub entries: Vec<LootEntry>
+}
+
+/// A number that is either fixed or picked uniformly from an integer range.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+#[serde(untagged)]
+pub enum LootRange {
+    Exact(f64),
+    Range { min: f64, max: f64 }
+}
+
+impl LootRange {
+    fn one() -> LootRange {
+        LootRange::Exact(1.0)
+    }
+
+    /// The lowest and highest value, rounded to integers. Every integer between them is equally
+    /// likely.
+    fn bounds(&self) -> (f64, f64) {
+        match *self {
+            LootRange::Exact(value) => (value.round(), value.round()),
+            LootRange::Range { min, max } => (min.round(), max.round().max(min.round()))
+        }
+    }
+
+    fn mean(&self) -> f64 {
+        let (min, max) = self.bounds();
+        (min + max) / 2.0
+    }
+
+    /// The chance of a value of at least 1.
+    fn nonzero_chance(&self) -> f64 {
+        let (min, max) = self.bounds();
+        (max - min.max(1.0) + 1.0).max(0.0) / (max - min + 1.0)
+    }
+
+    /// The chance that `probability` fails every time when tried as many times as the value, i.e.
+    /// the mean of `(1 - probability)^value`, summed as a geometric series.
+    fn never_chance(&self, probability: f64) -> f64 {
+        let (min, max) = self.bounds();
+        let count = max - min + 1.0;
+        if probability <= 0.0 {
+            return 1.0;
+        }
+        if probability >= 1.0 {
+            return if min <= 0.0 && max >= 0.0 { 1.0 / count } else { 0.0 };
+        }
+        let log_fail = (-probability).ln_1p();
+        (min * log_fail).exp() * -(count * log_fail).exp_m1() / (probability * count)
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct LootEntry {
+    #[serde(rename = "type")]
+    pub kind: LootEntryKind,
+    pub name: Option<String>,
+    #[serde(default = "default_weight")]
+    pub weight: u32,
+    #[serde(default)]
+    pub functions: Vec<LootFunction>,
+    #[serde(default)]
+    pub conditions: Vec<LootCondition>
+}
+
+fn default_weight() -> u32 {
+    1
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Eq, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum LootEntryKind {
+    Item,
+    LootTable,
+    Empty
+}
+
+/// Only the fields the analyzer understands; everything else is ignored.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct LootFunction {
+    pub function: String,
+    pub count: Option<LootRange>
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct LootCondition {
+    pub condition: String,
+    pub chance: Option<f64>
+}
+
+impl LootCondition {
+    /// The chance this condition passes. Conditions that depend on game state are assumed to pass.
+    fn chance(&self) -> f64 {
+        match self.condition.trim_start_matches("minecraft:") {
+            "random_chance" | "random_chance_with_looting" => self.chance.unwrap_or(1.0).clamp(0.0, 1.0),
+            _ => 1.0
+        }
+    }
+}
+
+fn conditions_chance(conditions: &[LootCondition]) -> f64 {
+    conditions.iter().map(LootCondition::chance).product()
+}
+
+pub fn deserialize_loot_table_from_str(src: &str) -> serde_json::Result<LootTable> {
+    let raw: Value = from_json_str(src)?;
+    let mut table: LootTable = serde_json::from_value(raw.clone())?;
+    table.raw = raw;
+    Ok(table)
+}
+
+/// How often a single item drops when a loot table is rolled once.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ItemDrop {
+    pub item: String,
+    /// Chance of dropping at least one.
+    pub probability: f64,
+    /// Average number dropped.
+    pub expected_count: f64
+}
+
+/// Per item chance and count, keyed by item.
+type Drops = BTreeMap<String, (f64, f64)>;
+
+struct Analyzer<'a> {
+    pack: &'a Path,
+    analyzed: HashMap<PathBuf, Drops>,
+    stack: Vec<PathBuf>
+}
+
+impl Analyzer<'_> {
+    fn table(&mut self, path: &Path) -> AddonResult<Drops> {
+        if let Some(drops) = self.analyzed.get(path) {
+            return Ok(drops.clone());
+        }
+        if let Some(start) = self.stack.iter().position(|p| p == path) {
+            let mut cycle: Vec<String> = self.stack[start..].iter().map(|path| path.display().to_string()).collect();
+            cycle.push(path.display().to_string());
+            return Err(AddonError::semantic(path, format!("loot tables reference each other: {}", cycle.join(" -> "))));
+        }
+
+        let src = fs::read_to_string(self.pack.join(path)).map_err(|error| AddonError::io(path, error))?;
+        let table = deserialize_loot_table_from_str(&src).map_err(|error| AddonError::json(path, error))?;
+
+        self.stack.push(path.to_path_buf());
+        let drops = self.pools(&table);
+        self.stack.pop();
+        let drops = drops?;
+
+        self.analyzed.insert(path.to_path_buf(), drops.clone());
+        Ok(drops)
+    }
+
+    fn pools(&mut self, table: &LootTable) -> AddonResult<Drops> {
+        let mut drops = Drops::new();
+        for pool in &table.pools {
+            // Pools roll independently of each other.
+            for (item, (probability, count)) in self.pool(pool)? {
+                let entry = drops.entry(item).or_insert((0.0, 0.0));
+                entry.0 = 1.0 - (1.0 - entry.0) * (1.0 - probability);
+                entry.1 += count;
+            }
+        }
+        Ok(drops)
+    }
+
+    fn pool(&mut self, pool: &LootPool) -> AddonResult<Drops> {
+        let total_weight: u64 = pool.entries.iter().map(|entry| entry.weight as u64).sum();
+        if total_weight == 0 {
+            return Ok(Drops::new());
+        }
+
+        // Entries of one roll exclude each other, so their chances simply add up.
+        let mut per_roll = Drops::new();
+        for entry in &pool.entries {
+            let pick = entry.weight as f64 / total_weight as f64 * conditions_chance(&entry.conditions);
+            let drops = match (entry.kind, &entry.name) {
+                (LootEntryKind::Item, Some(item)) => {
+                    let count = entry.functions.iter()
+                        .filter(|function| function.function.trim_start_matches("minecraft:") == "set_count")
+                        .find_map(|function| function.count)
+                        .unwrap_or(LootRange::one());
+                    Drops::from([(item.clone(), (count.nonzero_chance(), count.mean()))])
+                }
+                (LootEntryKind::LootTable, Some(path)) => self.table(Path::new(path))?,
+                _ => Drops::new()
+            };
+            for (item, (probability, count)) in drops {
+                let entry = per_roll.entry(item).or_insert((0.0, 0.0));
+                entry.0 += pick * probability;
+                entry.1 += pick * count;
+            }
+        }
+
+        let chance = conditions_chance(&pool.conditions);
+        let mean_rolls = pool.rolls.mean();
+        Ok(per_roll.into_iter().map(|(item, (probability, count))| {
+            let never = pool.rolls.never_chance(probability);
+            (item, (chance * (1.0 - never), chance * mean_rolls * count))
+        }).collect())
+    }
+}
+
+/// Computes drop chances for the loot table at `path`, relative to the behavior pack root, resolving
+/// the tables it references. Conditions other than random chances are assumed to pass. Errors carry
+/// paths relative to the behavior pack root.
+pub fn analyze_loot_table(pack: &Path, path: &Path) -> AddonResult<Vec<ItemDrop>> {
+    let mut analyzer = Analyzer { pack, analyzed: HashMap::new(), stack: vec![] };
+    Ok(analyzer.table(path)?.into_iter()
+        .map(|(item, (probability, expected_count))| ItemDrop { item, probability, expected_count })
+        .collect())
+}
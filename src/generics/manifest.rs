@@ -1,9 +1,19 @@
+use std::borrow::Cow;
 use std::collections::HashMap;
+use std::convert::Infallible;
+use std::error::Error;
+use std::fmt::{Display, Formatter};
+use std::io::Read;
+use std::path::Path;
 use std::str::FromStr;
 use once_cell::sync::Lazy;
-use serde::{Deserialize, Serialize};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use serde_json::{json, Value};
 use uuid::Uuid;
-use crate::utils::{parse_semver_from_str, parse_semver_from_vec, SemVer};
+use crate::diagnostics::Diagnostics;
+use crate::error::{read_to_string, AddonError, AddonResult};
+use crate::generics::parse::ParseOptions;
+use crate::utils::{from_json_str, try_parse_semver_from_str, try_parse_semver_from_vec, json_pointer, to_canonical_json_string, JsonStyle, SemVer};
 
 #[derive(Serialize, Deserialize, Clone, Debug)]
 #[serde(untagged)]
@@ -51,123 +61,299 @@ struct PreManifestDependency {
 
 #[derive(Serialize, Deserialize, Clone, Debug)]
 pub struct ManifestSubpack {
-    folder_name: String,
-    name: String,
-    memory_tier: i32
+    pub folder_name: String,
+    pub name: String,
+    pub memory_tier: i32
+}
+
+/// Parses a manifest, failing instead of panicking on a malformed one. Errors name the file
+/// `manifest.json`.
+pub fn deserialize_manifest_from_str(src: &str) -> AddonResult<Manifest> {
+    parse_manifest(src, Path::new("manifest.json"), &ParseOptions::strict(), &mut Diagnostics::new())
+}
+
+/// Reads and parses the manifest at `path`, failing instead of panicking on a malformed one.
+pub fn deserialize_manifest_from_path(path: &Path) -> AddonResult<Manifest> {
+    parse_manifest(&read_to_string(path)?, path, &ParseOptions::strict(), &mut Diagnostics::new())
+}
+
+/// Parses a manifest held in memory, e.g. one read out of an archive, failing instead of panicking
+/// on a malformed one.
+pub fn deserialize_manifest_from_bytes(bytes: &[u8]) -> AddonResult<Manifest> {
+    let path = Path::new("manifest.json");
+    let src = std::str::from_utf8(bytes).map_err(|error| AddonError::semantic(path, error.to_string()))?;
+    parse_manifest(src, path, &ParseOptions::strict(), &mut Diagnostics::new())
 }
 
-pub fn deserialize_manifest_from_str(src: &str) -> Manifest {
-    let deserialized_pre: PreManifest = serde_json::from_str(src).unwrap();
+pub fn deserialize_manifest_from_reader(mut reader: impl Read) -> AddonResult<Manifest> {
+    let mut bytes = vec![];
+    reader.read_to_end(&mut bytes).map_err(|error| AddonError::io("manifest.json", error))?;
+    deserialize_manifest_from_bytes(&bytes)
+}
 
-    let mut header = ManifestHeader {
-        name: deserialized_pre.clone().header.name,
-        description: deserialized_pre.clone().header.description,
-        min_engine_version: parse_semver_from_vec(deserialized_pre.clone().header.min_engine_version),
-        version: parse_semver_from_vec(deserialized_pre.clone().header.version),
-        uuid: Uuid::from_str(&deserialized_pre.clone().header.uuid).unwrap(),
+/// Like [`deserialize_manifest_from_path`], but in [lenient](crate::generics::parse::ParseMode::Lenient) mode returns the
+/// problems it skipped over alongside the manifest.
+pub fn deserialize_manifest_from_path_with_options(path: &Path, options: &ParseOptions) -> AddonResult<(Manifest, Diagnostics)> {
+    let mut diagnostics = Diagnostics::new();
+    let manifest = parse_manifest(&read_to_string(path)?, path, options, &mut diagnostics)?;
+    Ok((manifest, diagnostics))
+}
+
+fn parse_uuid(src: &str, path: &Path) -> AddonResult<Uuid> {
+    Uuid::from_str(src).map_err(|error| AddonError::semantic(path, format!("invalid uuid {:?}: {}", src, error)))
+}
+
+fn parse_version_parts(parts: &[i32], path: &Path) -> AddonResult<SemVer> {
+    try_parse_semver_from_vec(parts).ok_or_else(|| AddonError::semantic(path, format!("invalid version {:?}", parts)))
+}
+
+fn parse_pre_version(version: &PreVer, path: &Path) -> AddonResult<SemVer> {
+    match version {
+        PreVer::Str(str) => try_parse_semver_from_str(str).ok_or_else(|| AddonError::semantic(path, format!("invalid version {:?}", str))),
+        PreVer::Vec(vec) => parse_version_parts(vec, path)
+    }
+}
+
+/// `None` for module types that are valid but not modeled.
+fn parse_module(module: PreManifestModule, path: &Path) -> AddonResult<Option<ManifestModule>> {
+    let uuid = parse_uuid(&module.uuid, path)?;
+    let version = parse_version_parts(&module.version, path)?;
+    let module_type = module.type_id.parse::<ManifestModuleType>().map_err(|error| AddonError::semantic(path, error.to_string()))?;
+    match module_type {
+        ManifestModuleType::Script => {
+            let Some(entry) = module.entry else {
+                return Err(AddonError::semantic(path, "script module without an entry"));
+            };
+            Ok(Some(ManifestModule::Script(uuid, version, ScriptManifestModule { entry })))
+        }
+        ManifestModuleType::Data => Ok(Some(ManifestModule::Data(uuid, version))),
+        ManifestModuleType::Resources => Ok(Some(ManifestModule::Resources(uuid, version))),
+        _ => Ok(None)
+    }
+}
+
+fn parse_dependency(dep: PreManifestDependency, path: &Path) -> AddonResult<ManifestDependency> {
+    let version = parse_pre_version(&dep.version, path)?;
+    if let Some(module_name) = dep.module_name {
+        Ok(ManifestDependency::ScriptDependency(ScriptManifestDependency::from_module_name(&module_name), version))
+    } else {
+        let Some(uuid) = dep.uuid else {
+            return Err(AddonError::semantic(path, "dependency without a uuid or module_name"));
+        };
+        Ok(ManifestDependency::UuidDependency(parse_uuid(&uuid, path)?, version))
+    }
+}
+
+fn parse_manifest(src: &str, path: &Path, options: &ParseOptions, diagnostics: &mut Diagnostics) -> AddonResult<Manifest> {
+    let raw: Value = from_json_str(src).map_err(|error| AddonError::json(path, error))?;
+    let deserialized_pre: PreManifest = serde_json::from_value(raw.clone()).map_err(|error| AddonError::json(path, error))?;
+
+    let header = ManifestHeader {
+        name: deserialized_pre.header.name,
+        description: deserialized_pre.header.description,
+        min_engine_version: parse_version_parts(&deserialized_pre.header.min_engine_version, path)?,
+        version: parse_version_parts(&deserialized_pre.header.version, path)?,
+        uuid: parse_uuid(&deserialized_pre.header.uuid, path)?,
     };
 
     let mut modules: Vec<ManifestModule> = vec![];
 
-    for module in deserialized_pre.clone().modules {
-        if module.type_id == "script" {
-            modules.push(
-                ManifestModule::Script(
-                    Uuid::from_str(&module.uuid).unwrap(),
-                    parse_semver_from_vec(module.version),
-                    ScriptManifestModule { entry: module.entry.unwrap() }
-                )
-            );
-        }
-        else if module.type_id == "data" || module.type_id == "resources" {
-            modules.push(
-                ManifestModule::Data(
-                    Uuid::from_str(&module.uuid).unwrap(),
-                    parse_semver_from_vec(module.version),
-                )
-            )
+    for (index, module) in deserialized_pre.modules.into_iter().enumerate() {
+        match parse_module(module, path) {
+            Ok(module) => modules.extend(module),
+            Err(error) => options.recover(error, "invalid-manifest-module", json_pointer(&["modules", &index.to_string()]), diagnostics)?
         }
     }
 
     let mut dependencies: Vec<ManifestDependency> = vec![];
 
-    for dep in deserialized_pre.clone().dependencies {
-        if dep.clone().module_name.is_some() {
-            dependencies.push(
-              ManifestDependency::ScriptDependency(
-                  {
-                      if SCRIPT_MANIFEST_DEPENDENCIES.get(dep.clone().module_name.unwrap().as_str()).is_some() {
-                          SCRIPT_MANIFEST_DEPENDENCIES.get(dep.clone().module_name.unwrap().as_str()).unwrap().clone()
-                      } else {
-                          ScriptManifestDependency::Custom(dep.clone().module_name.unwrap())
-                      }
-                  },
-                  {
-                      if let PreVer::Str(str) = dep.clone().version {
-                          parse_semver_from_str(&str)
-                      } else if let PreVer::Vec(vec) = dep.clone().version {
-                          parse_semver_from_vec(vec)
-                      } else {
-                          SemVer {
-                              major: 1, minor: 0, patch: 0, beta: false
-                          }
-                      }
-                  }
-              )
-            );
-        } else {
-            dependencies.push(ManifestDependency::UuidDependency(
-                Uuid::parse_str(&dep.clone().uuid.unwrap()).unwrap(),
-                {
-                    if let PreVer::Str(str) = dep.clone().version {
-                        parse_semver_from_str(&str)
-                    } else if let PreVer::Vec(vec) = dep.clone().version {
-                        parse_semver_from_vec(vec)
-                    } else {
-                        SemVer {
-                            major: 1, minor: 0, patch: 0, beta: false
-                        }
-                    }
-                }
-            ))
+    for (index, dep) in deserialized_pre.dependencies.into_iter().enumerate() {
+        match parse_dependency(dep, path) {
+            Ok(dependency) => dependencies.push(dependency),
+            Err(error) => options.recover(error, "invalid-manifest-dependency", json_pointer(&["dependencies", &index.to_string()]), diagnostics)?
         }
     }
 
-    let subpacks = deserialized_pre.clone().subpacks;
+    let subpacks = deserialized_pre.subpacks;
+
+    let capabilities: Vec<ManifestCapability> = deserialized_pre.capabilities.iter().map(|cap| ManifestCapability::from_name(cap)).collect();
+
+    Ok(Manifest {
+        header, modules, dependencies, subpacks, capabilities, raw
+    })
+}
 
-    let mut capabilities: Vec<ManifestCapability> = vec![];
+/// A manifest version, either `[1, 0, 0]` or `"1.0.0"`, borrowed from the source.
+#[derive(Deserialize, Clone, Debug, PartialEq)]
+#[serde(untagged)]
+pub enum ManifestVersionRef<'a> {
+    Parts([i32; 3]),
+    #[serde(borrow)]
+    Str(Cow<'a, str>)
+}
 
-    for cap in deserialized_pre.clone().capabilities {
-        if MANIFEST_CAPABILITIES.get(cap.as_str()).is_some() {
-            capabilities.push(
-                MANIFEST_CAPABILITIES.get(cap.as_str()).unwrap().clone()
-            )
-        } else {
-            capabilities.push(ManifestCapability::Custom(cap))
+impl ManifestVersionRef<'_> {
+    /// The version, or `None` for a malformed one such as `"1.0"`.
+    pub fn to_semver(&self) -> Option<SemVer> {
+        match self {
+            ManifestVersionRef::Parts(parts) => try_parse_semver_from_vec(parts),
+            ManifestVersionRef::Str(str) => try_parse_semver_from_str(str)
         }
     }
+}
+
+/// A manifest that borrows its strings from the source instead of allocating them, for scanning
+/// many manifests quickly. Strings containing JSON escapes are the only ones that get allocated.
+#[derive(Deserialize, Clone, Debug)]
+pub struct ManifestRef<'a> {
+    #[serde(borrow)]
+    pub header: ManifestHeaderRef<'a>,
+    #[serde(borrow, default)]
+    pub modules: Vec<ManifestModuleRef<'a>>,
+    #[serde(borrow, default)]
+    pub dependencies: Vec<ManifestDependencyRef<'a>>,
+    #[serde(borrow, default)]
+    pub capabilities: Vec<Cow<'a, str>>
+}
+
+#[derive(Deserialize, Clone, Debug)]
+pub struct ManifestHeaderRef<'a> {
+    #[serde(borrow)]
+    pub name: Cow<'a, str>,
+    #[serde(borrow, default)]
+    pub description: Cow<'a, str>,
+    #[serde(borrow)]
+    pub uuid: Cow<'a, str>,
+    #[serde(borrow)]
+    pub version: ManifestVersionRef<'a>,
+    #[serde(borrow)]
+    pub min_engine_version: Option<ManifestVersionRef<'a>>
+}
 
-    Manifest {
-        header, modules, dependencies, subpacks, capabilities
+impl ManifestHeaderRef<'_> {
+    pub fn uuid(&self) -> Option<Uuid> {
+        Uuid::from_str(&self.uuid).ok()
     }
 }
 
+#[derive(Deserialize, Clone, Debug)]
+pub struct ManifestModuleRef<'a> {
+    #[serde(rename = "type", borrow)]
+    pub type_id: Cow<'a, str>,
+    #[serde(borrow)]
+    pub uuid: Cow<'a, str>,
+    #[serde(borrow)]
+    pub version: ManifestVersionRef<'a>,
+    #[serde(borrow)]
+    pub entry: Option<Cow<'a, str>>
+}
+
+#[derive(Deserialize, Clone, Debug)]
+pub struct ManifestDependencyRef<'a> {
+    #[serde(borrow)]
+    pub uuid: Option<Cow<'a, str>>,
+    #[serde(borrow)]
+    pub module_name: Option<Cow<'a, str>>,
+    #[serde(borrow)]
+    pub version: ManifestVersionRef<'a>
+}
+
+/// Borrows from `src`, so it has to be strict JSON: unlike [`deserialize_manifest_from_str`], comments
+/// and trailing commas are rejected even with the `jsonc` feature, since stripping them needs a new buffer.
+pub fn deserialize_manifest_borrowed_from_str(src: &str) -> serde_json::Result<ManifestRef<'_>> {
+    serde_json::from_str(src)
+}
+
 #[derive(Clone, Debug)]
 pub struct Manifest {
     pub header: ManifestHeader,
     pub modules: Vec<ManifestModule>,
     pub dependencies: Vec<ManifestDependency>,
     pub subpacks: Vec<ManifestSubpack>,
-    pub capabilities: Vec<ManifestCapability>
+    pub capabilities: Vec<ManifestCapability>,
+    /// The JSON the manifest was parsed from, including fields not modeled above.
+    pub raw: Value
+}
+
+pub(crate) fn version_value(version: &SemVer) -> Value {
+    // The array form can't express betas.
+    if version.beta {
+        Value::String(version.to_string())
+    } else {
+        json!([version.major, version.minor, version.patch])
+    }
+}
+
+impl Manifest {
+    /// The manifest as JSON: the typed fields written over [`raw`](Manifest::raw), so fields and
+    /// module types without a typed model are kept.
+    pub fn to_value(&self) -> Value {
+        let mut root = self.raw.as_object().cloned().unwrap_or_default();
+        root.entry("format_version").or_insert(json!(2));
+
+        let mut header = root.get("header").and_then(Value::as_object).cloned().unwrap_or_default();
+        header.insert("name".to_string(), json!(self.header.name));
+        header.insert("description".to_string(), json!(self.header.description));
+        header.insert("uuid".to_string(), json!(self.header.uuid.to_string()));
+        header.insert("version".to_string(), version_value(&self.header.version));
+        header.insert("min_engine_version".to_string(), version_value(&self.header.min_engine_version));
+        root.insert("header".to_string(), Value::Object(header));
+
+        let raw_modules: Vec<Value> = root.get("modules").and_then(Value::as_array).cloned().unwrap_or_default();
+        let mut modules = vec![];
+        for module in &self.modules {
+            let (uuid, version) = match module {
+                ManifestModule::Data(uuid, version) | ManifestModule::Resources(uuid, version) | ManifestModule::Script(uuid, version, _) => (uuid, version)
+            };
+            let mut value = raw_modules.iter()
+                .find(|raw| raw.get("uuid").and_then(Value::as_str) == Some(uuid.to_string().as_str()))
+                .and_then(Value::as_object).cloned().unwrap_or_default();
+            value.insert("type".to_string(), json!(module.module_type().name()));
+            value.insert("uuid".to_string(), json!(uuid.to_string()));
+            value.insert("version".to_string(), version_value(version));
+            if let ManifestModule::Script(_, _, script) = module {
+                value.entry("language").or_insert(json!("javascript"));
+                value.insert("entry".to_string(), json!(script.entry));
+            }
+            modules.push(Value::Object(value));
+        }
+        modules.extend(raw_modules.into_iter().filter(|raw| {
+            raw.get("type").and_then(Value::as_str).and_then(|type_id| type_id.parse::<ManifestModuleType>().ok())
+                .is_some_and(|module_type| !module_type.is_modeled())
+        }));
+        root.insert("modules".to_string(), Value::Array(modules));
+
+        let dependencies = self.dependencies.iter().map(|dependency| match dependency {
+            ManifestDependency::ScriptDependency(module, version) => json!({ "module_name": module.module_name(), "version": version.to_string() }),
+            ManifestDependency::UuidDependency(uuid, version) => json!({ "uuid": uuid.to_string(), "version": version_value(version) })
+        }).collect();
+        root.insert("dependencies".to_string(), Value::Array(dependencies));
+
+        root.insert("capabilities".to_string(), self.capabilities.iter().map(|capability| json!(capability.name())).collect());
+        root.insert("subpacks".to_string(), serde_json::to_value(&self.subpacks).unwrap_or_default());
+
+        Value::Object(root)
+    }
+}
+
+/// Writes `manifest` in [canonical form](to_canonical_json_string), so the same manifest always
+/// produces the same bytes.
+pub fn serialize_manifest_to_string(manifest: &Manifest) -> String {
+    to_canonical_json_string(&manifest.to_value())
+}
+
+/// Like [`serialize_manifest_to_string`], laid out in `style`.
+pub fn serialize_manifest_to_string_with_style(manifest: &Manifest, style: &JsonStyle) -> String {
+    style.write(&manifest.to_value())
 }
 
 #[derive(Clone, Debug)]
 pub struct ManifestHeader {
-    uuid: Uuid,
-    name: String,
-    description: String,
-    min_engine_version: SemVer,
-    version: SemVer
+    pub uuid: Uuid,
+    pub name: String,
+    pub description: String,
+    pub min_engine_version: SemVer,
+    pub version: SemVer
 }
 
 #[derive(Clone, Debug)]
@@ -177,6 +363,74 @@ pub enum ManifestModule {
     Script(Uuid, SemVer, ScriptManifestModule)
 }
 
+impl ManifestModule {
+    pub fn module_type(&self) -> ManifestModuleType {
+        match self {
+            ManifestModule::Data(..) => ManifestModuleType::Data,
+            ManifestModule::Resources(..) => ManifestModuleType::Resources,
+            ManifestModule::Script(..) => ManifestModuleType::Script
+        }
+    }
+}
+
+/// The `type` of a manifest module.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+pub enum ManifestModuleType {
+    Data,
+    Resources,
+    Script,
+    ClientData,
+    Interface,
+    /// The pre-1.19 name of `script`.
+    JavaScript,
+    SkinPack,
+    WorldTemplate
+}
+
+impl ManifestModuleType {
+    pub const ALL: [ManifestModuleType; 8] = [
+        ManifestModuleType::Data, ManifestModuleType::Resources, ManifestModuleType::Script, ManifestModuleType::ClientData,
+        ManifestModuleType::Interface, ManifestModuleType::JavaScript, ManifestModuleType::SkinPack, ManifestModuleType::WorldTemplate
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            ManifestModuleType::Data => "data",
+            ManifestModuleType::Resources => "resources",
+            ManifestModuleType::Script => "script",
+            ManifestModuleType::ClientData => "client_data",
+            ManifestModuleType::Interface => "interface",
+            ManifestModuleType::JavaScript => "javascript",
+            ManifestModuleType::SkinPack => "skin_pack",
+            ManifestModuleType::WorldTemplate => "world_template"
+        }
+    }
+
+    /// Whether [`ManifestModule`] models modules of this type; the others are skipped when parsing.
+    pub fn is_modeled(&self) -> bool {
+        matches!(self, ManifestModuleType::Data | ManifestModuleType::Resources | ManifestModuleType::Script)
+    }
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct UnknownModuleType(pub String);
+
+impl Display for UnknownModuleType {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "unknown module type {:?}", self.0)
+    }
+}
+
+impl Error for UnknownModuleType {}
+
+impl FromStr for ManifestModuleType {
+    type Err = UnknownModuleType;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        ManifestModuleType::ALL.into_iter().find(|module_type| module_type.name() == s).ok_or_else(|| UnknownModuleType(s.to_string()))
+    }
+}
+
 #[derive(Clone, Debug)]
 pub struct ScriptManifestModule {
     pub entry: String
@@ -200,6 +454,26 @@ pub enum ScriptManifestDependency {
     Custom(String)
 }
 
+impl ScriptManifestDependency {
+    /// Known modules get their own variant, anything else is [`Custom`](ScriptManifestDependency::Custom).
+    pub fn from_module_name(name: &str) -> ScriptManifestDependency {
+        SCRIPT_MANIFEST_DEPENDENCIES.get(name).cloned().unwrap_or_else(|| ScriptManifestDependency::Custom(name.to_string()))
+    }
+
+    pub fn module_name(&self) -> &str {
+        match self {
+            ScriptManifestDependency::MinecraftServer => "@minecraft/server",
+            ScriptManifestDependency::MinecraftServerUi => "@minecraft/server-ui",
+            ScriptManifestDependency::MinecraftServerNet => "@minecraft/server-net",
+            ScriptManifestDependency::MinecraftServerGametest => "@minecraft/server-gametest",
+            ScriptManifestDependency::MinecraftServerAdmin => "@minecraft/server-admin",
+            ScriptManifestDependency::MinecraftServerEditor => "@minecraft/server-editor",
+            ScriptManifestDependency::MinecraftDebugUtilities => "@minecraft/debug-utilities",
+            ScriptManifestDependency::Custom(name) => name
+        }
+    }
+}
+
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub enum ManifestCapability {
     Chemistry,
@@ -211,7 +485,26 @@ pub enum ManifestCapability {
     Custom(String)
 }
 
-static SCRIPT_MANIFEST_DEPENDENCIES: Lazy<HashMap<&str, ScriptManifestDependency>> = Lazy::new(||
+impl ManifestCapability {
+    /// Known capabilities get their own variant, anything else is [`Custom`](ManifestCapability::Custom).
+    pub fn from_name(name: &str) -> ManifestCapability {
+        MANIFEST_CAPABILITIES.get(name).cloned().unwrap_or_else(|| ManifestCapability::Custom(name.to_string()))
+    }
+
+    pub fn name(&self) -> &str {
+        match self {
+            ManifestCapability::Chemistry => "chemistry",
+            ManifestCapability::EditorExtension => "editorExtension",
+            ManifestCapability::ExperimentalCustomUi => "experimental_custom_ui",
+            ManifestCapability::PBR => "pbr",
+            ManifestCapability::ScriptEval => "script_eval",
+            ManifestCapability::Raytraced => "raytraced",
+            ManifestCapability::Custom(name) => name
+        }
+    }
+}
+
+pub(crate) static SCRIPT_MANIFEST_DEPENDENCIES: Lazy<HashMap<&str, ScriptManifestDependency>> = Lazy::new(||
     HashMap::from(
         [
             ("@minecraft/server", ScriptManifestDependency::MinecraftServer),
@@ -234,4 +527,56 @@ static MANIFEST_CAPABILITIES: Lazy<HashMap<&str, ManifestCapability>> = Lazy::ne
         ("pbr", ManifestCapability::PBR),
         ("script_eval", ManifestCapability::ScriptEval),
     ]
-));
\ No newline at end of file
+));
+
+/// Implements `Display`, `FromStr` and serde for an enum by its in-game name.
+macro_rules! impl_name_conversions {
+    ($name:ident, $to_name:ident, $from_name:path) => {
+        impl Display for $name {
+            fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+                f.write_str(self.$to_name())
+            }
+        }
+
+        impl FromStr for $name {
+            type Err = Infallible;
+
+            fn from_str(s: &str) -> Result<Self, Self::Err> {
+                Ok($from_name(s))
+            }
+        }
+
+        impl Serialize for $name {
+            fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                serializer.serialize_str(self.$to_name())
+            }
+        }
+
+        impl<'de> Deserialize<'de> for $name {
+            fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+                Ok($from_name(&String::deserialize(deserializer)?))
+            }
+        }
+    };
+}
+
+impl_name_conversions!(ManifestCapability, name, ManifestCapability::from_name);
+impl_name_conversions!(ScriptManifestDependency, module_name, ScriptManifestDependency::from_module_name);
+
+impl Display for ManifestModuleType {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl Serialize for ManifestModuleType {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.name())
+    }
+}
+
+impl<'de> Deserialize<'de> for ManifestModuleType {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        String::deserialize(deserializer)?.parse().map_err(serde::de::Error::custom)
+    }
+}
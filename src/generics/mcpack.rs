@@ -0,0 +1,507 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::fs::File;
+use std::io;
+use std::io::{BufReader, Cursor, Read, Seek, SeekFrom, Write};
+use std::path::{Component, Path, PathBuf};
+use crate::error::{AddonError, AddonResult};
+use crate::files::pack_files;
+use crate::generics::inflate::inflate;
+use crate::progress::{Progress, Task};
+use crate::utils::{crc32_update, from_json_str, JsonStyle};
+
+/// Sizes and CRC follow each entry's data, and names are UTF-8.
+const FLAGS: u16 = 0x0008 | 0x0800;
+/// 1980-01-01 00:00, the earliest DOS date, so archives of the same files are byte-identical.
+const DOS_TIME: u16 = 0;
+const DOS_DATE: u16 = (1 << 5) | 1;
+
+struct CentralEntry {
+    name: String,
+    crc: u32,
+    size: u32,
+    offset: u32
+}
+
+fn too_large() -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidInput, "archive exceeds 4 GiB, which needs zip64")
+}
+
+fn name_length(name: &str) -> io::Result<u16> {
+    u16::try_from(name.len()).map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, "entry name is longer than 65535 bytes"))
+}
+
+/// Zip format limits are archive errors, anything else the writer's own failure.
+pub(crate) fn archive_error(path: &Path, error: io::Error) -> AddonError {
+    if error.kind() == io::ErrorKind::InvalidInput {
+        AddonError::archive(path, error.to_string())
+    } else {
+        AddonError::io(path, error)
+    }
+}
+
+/// Writes a `.mcpack`/`.mcaddon` zip archive entry by entry. Entries are stored uncompressed and
+/// streamed in fixed-size chunks, so memory use doesn't depend on file sizes, and the output
+/// doesn't need to be seekable. There's no async variant: the crate depends on no async runtime to
+/// provide an `AsyncWrite`, so async callers should run this on a blocking thread.
+pub struct McpackWriter<W: Write> {
+    writer: W,
+    offset: u64,
+    entries: Vec<CentralEntry>,
+    json_style: Option<JsonStyle>
+}
+
+impl<W: Write> McpackWriter<W> {
+    pub fn new(writer: W) -> McpackWriter<W> {
+        McpackWriter { writer, offset: 0, entries: vec![], json_style: None }
+    }
+
+    /// Rewrites the `.json` files added from directories in `style`, e.g. minified for shipping.
+    /// Files that don't parse as JSON are added as they are.
+    pub fn json_style(mut self, style: JsonStyle) -> McpackWriter<W> {
+        self.json_style = Some(style);
+        self
+    }
+
+    fn write(&mut self, bytes: &[u8]) -> io::Result<()> {
+        self.writer.write_all(bytes)?;
+        self.offset += bytes.len() as u64;
+        Ok(())
+    }
+
+    /// Streams `reader` into the archive as `name`, a `/`-separated path.
+    pub fn add_file(&mut self, name: &str, reader: &mut impl Read) -> io::Result<()> {
+        let offset = u32::try_from(self.offset).map_err(|_| too_large())?;
+        let name_length = name_length(name)?;
+
+        let mut header = vec![];
+        header.extend(0x0403_4b50u32.to_le_bytes());
+        header.extend(20u16.to_le_bytes());
+        header.extend(FLAGS.to_le_bytes());
+        header.extend(0u16.to_le_bytes());
+        header.extend(DOS_TIME.to_le_bytes());
+        header.extend(DOS_DATE.to_le_bytes());
+        header.extend([0; 12]);
+        header.extend(name_length.to_le_bytes());
+        header.extend(0u16.to_le_bytes());
+        header.extend(name.as_bytes());
+        self.write(&header)?;
+
+        let mut crc = !0u32;
+        let mut size = 0u64;
+        let mut buffer = [0u8; 64 * 1024];
+        loop {
+            let read = reader.read(&mut buffer)?;
+            if read == 0 {
+                break;
+            }
+            crc = crc32_update(crc, &buffer[..read]);
+            size += read as u64;
+            self.write(&buffer[..read])?;
+        }
+        let crc = !crc;
+        let size = u32::try_from(size).map_err(|_| too_large())?;
+
+        let mut descriptor = vec![];
+        descriptor.extend(0x0807_4b50u32.to_le_bytes());
+        descriptor.extend(crc.to_le_bytes());
+        descriptor.extend(size.to_le_bytes());
+        descriptor.extend(size.to_le_bytes());
+        self.write(&descriptor)?;
+
+        self.entries.push(CentralEntry { name: name.to_string(), crc, size, offset });
+        Ok(())
+    }
+
+    /// Adds every file below `root`, named by its path relative to `root`.
+    pub fn add_directory(&mut self, root: &Path) -> AddonResult<()> {
+        self.add_directory_with_progress(root, &|_, _, _| {})
+    }
+
+    /// Like [`add_directory`](McpackWriter::add_directory), reporting each file added.
+    pub fn add_directory_with_progress(&mut self, root: &Path, progress: &dyn Progress) -> AddonResult<()> {
+        self.add_directory_under(root, "", progress)
+    }
+
+    /// Adds every file below `root`, named by its path relative to `root` after `prefix`, e.g.
+    /// `behavior_packs/gems/`.
+    pub fn add_directory_under(&mut self, root: &Path, prefix: &str, progress: &dyn Progress) -> AddonResult<()> {
+        let files = pack_files(root, "");
+        for (done, path) in files.iter().enumerate() {
+            let name = path.strip_prefix(root).unwrap_or(path).to_string_lossy().replace('\\', "/");
+            let name = format!("{}{}", prefix, name);
+            let restyled = match &self.json_style {
+                Some(style) if path.extension().is_some_and(|ext| ext == "json") => {
+                    let src = fs::read_to_string(path).map_err(|error| AddonError::io(path, error))?;
+                    from_json_str(&src).ok().map(|value| style.write(&value))
+                }
+                _ => None
+            };
+            match restyled {
+                Some(json) => self.add_file(&name, &mut json.as_bytes()),
+                None => {
+                    let mut file = File::open(path).map_err(|error| AddonError::io(path, error))?;
+                    self.add_file(&name, &mut file)
+                }
+            }.map_err(|error| archive_error(path, error))?;
+            progress.progress(Task::Archiving, done + 1, files.len());
+        }
+        Ok(())
+    }
+
+    /// Writes the central directory and returns the underlying writer.
+    pub fn finish(mut self) -> io::Result<W> {
+        let start = u32::try_from(self.offset).map_err(|_| too_large())?;
+        let count = u16::try_from(self.entries.len())
+            .map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, "archive has more than 65535 entries, which needs zip64"))?;
+
+        let mut directory = vec![];
+        for entry in &self.entries {
+            directory.extend(0x0201_4b50u32.to_le_bytes());
+            directory.extend(20u16.to_le_bytes());
+            directory.extend(20u16.to_le_bytes());
+            directory.extend(FLAGS.to_le_bytes());
+            directory.extend(0u16.to_le_bytes());
+            directory.extend(DOS_TIME.to_le_bytes());
+            directory.extend(DOS_DATE.to_le_bytes());
+            directory.extend(entry.crc.to_le_bytes());
+            directory.extend(entry.size.to_le_bytes());
+            directory.extend(entry.size.to_le_bytes());
+            directory.extend(name_length(&entry.name)?.to_le_bytes());
+            directory.extend([0; 12]);
+            directory.extend(entry.offset.to_le_bytes());
+            directory.extend(entry.name.as_bytes());
+        }
+        let size = u32::try_from(directory.len()).map_err(|_| too_large())?;
+        self.write(&directory)?;
+
+        let mut end = vec![];
+        end.extend(0x0605_4b50u32.to_le_bytes());
+        end.extend([0; 4]);
+        end.extend(count.to_le_bytes());
+        end.extend(count.to_le_bytes());
+        end.extend(size.to_le_bytes());
+        end.extend(start.to_le_bytes());
+        end.extend(0u16.to_le_bytes());
+        self.write(&end)?;
+
+        self.writer.flush()?;
+        Ok(self.writer)
+    }
+}
+
+/// Archives the pack folder at `pack` into `writer` as a `.mcpack`.
+pub fn write_mcpack<W: Write>(pack: &Path, writer: W) -> AddonResult<W> {
+    write_mcpack_with_progress(pack, writer, &|_, _, _| {})
+}
+
+/// Like [`write_mcpack`], reporting each file archived.
+pub fn write_mcpack_with_progress<W: Write>(pack: &Path, writer: W, progress: &dyn Progress) -> AddonResult<W> {
+    let mut archive = McpackWriter::new(writer);
+    archive.add_directory_with_progress(pack, progress)?;
+    archive.finish().map_err(|error| archive_error(pack, error))
+}
+
+/// Like [`write_mcpack`], writing the pack's JSON files in `style`.
+pub fn write_mcpack_with_style<W: Write>(pack: &Path, writer: W, style: &JsonStyle) -> AddonResult<W> {
+    let mut archive = McpackWriter::new(writer).json_style(style.clone());
+    archive.add_directory(pack)?;
+    archive.finish().map_err(|error| archive_error(pack, error))
+}
+
+/// What errors about an archive read from memory are reported against.
+const IN_MEMORY: &str = "<archive>";
+
+/// Caps on what reading an archive may decompress, so an untrusted upload like a zip bomb fails
+/// with an archive error instead of exhausting memory. Sizes are checked against the sizes the
+/// archive declares before anything is inflated, and inflating never produces more than declared.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct ArchiveLimits {
+    /// The largest decompressed size of one entry, in bytes.
+    pub max_entry_size: u64,
+    /// The largest decompressed size of all entries together, in bytes.
+    pub max_total_size: u64,
+    /// The most entries, directories included.
+    pub max_entries: usize
+}
+
+impl Default for ArchiveLimits {
+    /// 256 MiB per entry, 1 GiB in total and 32768 entries, well above what real packs need.
+    fn default() -> Self {
+        ArchiveLimits { max_entry_size: 256 << 20, max_total_size: 1 << 30, max_entries: 32_768 }
+    }
+}
+
+impl ArchiveLimits {
+    /// No limits beyond those of the zip format, for archives from trusted sources.
+    pub fn unlimited() -> ArchiveLimits {
+        ArchiveLimits { max_entry_size: u64::MAX, max_total_size: u64::MAX, max_entries: usize::MAX }
+    }
+}
+
+fn u16_at(bytes: &[u8], offset: usize) -> Option<u16> {
+    Some(u16::from_le_bytes(bytes.get(offset..offset + 2)?.try_into().ok()?))
+}
+
+fn u32_at(bytes: &[u8], offset: usize) -> Option<u32> {
+    Some(u32::from_le_bytes(bytes.get(offset..offset + 4)?.try_into().ok()?))
+}
+
+fn read_error(error: io::Error) -> AddonError {
+    AddonError::io(IN_MEMORY, error)
+}
+
+/// A file of an archive, as its central directory describes it.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct McpackEntry {
+    /// The `/`-separated path, ending in `/` for directories.
+    pub name: String,
+    /// Decompressed, in bytes.
+    pub size: u64,
+    pub compressed_size: u64,
+    flags: u16,
+    method: u16,
+    crc: u32,
+    header_offset: u64
+}
+
+impl McpackEntry {
+    pub fn is_directory(&self) -> bool {
+        self.name.ends_with('/')
+    }
+}
+
+/// Reads a `.mcpack`/`.mcaddon` zip archive from a seekable source, decompressing each entry only
+/// when it's read, so only the central directory and the entries asked for are held in memory.
+/// Entries may be stored or deflated; zip64 and encrypted archives aren't supported.
+pub struct McpackReader<R: Read + Seek> {
+    reader: R,
+    entries: Vec<McpackEntry>,
+    limits: ArchiveLimits
+}
+
+impl<R: Read + Seek> McpackReader<R> {
+    /// Reads the central directory of the archive in `reader`, within the default
+    /// [`ArchiveLimits`].
+    pub fn new(reader: R) -> AddonResult<McpackReader<R>> {
+        McpackReader::with_limits(reader, ArchiveLimits::default())
+    }
+
+    /// Like [`new`](McpackReader::new), within `limits`. The entry count is checked here, sizes as
+    /// entries are read.
+    pub fn with_limits(mut reader: R, limits: ArchiveLimits) -> AddonResult<McpackReader<R>> {
+        // The end of central directory record is the last thing in the archive, followed only by
+        // a comment of up to 64 KiB.
+        let length = reader.seek(SeekFrom::End(0)).map_err(read_error)?;
+        let tail_length = length.min(22 + u16::MAX as u64);
+        reader.seek(SeekFrom::Start(length - tail_length)).map_err(read_error)?;
+        let mut tail = vec![];
+        reader.by_ref().take(tail_length).read_to_end(&mut tail).map_err(read_error)?;
+        let end = (0..tail.len().saturating_sub(21)).rev()
+            .find(|offset| u32_at(&tail, *offset) == Some(0x0605_4b50))
+            .ok_or_else(|| AddonError::archive(IN_MEMORY, "not a zip archive"))?;
+        let count = u16_at(&tail, end + 10).unwrap_or_default() as usize;
+        let directory_size = u32_at(&tail, end + 12).unwrap_or_default();
+        let directory_start = u32_at(&tail, end + 16).unwrap_or_default();
+        if count > limits.max_entries {
+            return Err(AddonError::archive(IN_MEMORY, format!("archive has {} entries, more than the limit of {}", count, limits.max_entries)));
+        }
+
+        // Read through `take`, so a directory size larger than the archive can't allocate more
+        // than the archive holds.
+        reader.seek(SeekFrom::Start(directory_start as u64)).map_err(read_error)?;
+        let mut directory = vec![];
+        reader.by_ref().take(directory_size as u64).read_to_end(&mut directory).map_err(read_error)?;
+
+        let truncated = || AddonError::archive(IN_MEMORY, "truncated central directory");
+        let mut entries = Vec::with_capacity(count);
+        let mut record = 0;
+        for _ in 0..count {
+            if u32_at(&directory, record) != Some(0x0201_4b50) {
+                return Err(AddonError::archive(IN_MEMORY, "missing central directory record"));
+            }
+            let field16 = |offset| u16_at(&directory, record + offset).ok_or_else(truncated);
+            let field32 = |offset| u32_at(&directory, record + offset).ok_or_else(truncated);
+            let name_length = field16(28)? as usize;
+            let skipped = field16(30)? as usize + field16(32)? as usize;
+            let name = directory.get(record + 46..record + 46 + name_length).ok_or_else(truncated)?;
+            entries.push(McpackEntry {
+                // Names are UTF-8 in practice whether or not the flag says so.
+                name: String::from_utf8_lossy(name).replace('\\', "/"),
+                size: field32(24)? as u64,
+                compressed_size: field32(20)? as u64,
+                flags: field16(8)?,
+                method: field16(10)?,
+                crc: field32(16)?,
+                header_offset: field32(42)? as u64
+            });
+            record += 46 + name_length + skipped;
+        }
+        Ok(McpackReader { reader, entries, limits })
+    }
+
+    /// Every entry, directories included, in archive order.
+    pub fn entries(&self) -> &[McpackEntry] {
+        &self.entries
+    }
+
+    pub fn entry(&self, name: &str) -> Option<&McpackEntry> {
+        self.entries.iter().find(|entry| entry.name == name)
+    }
+
+    /// The decompressed size of every file together, as the archive declares it.
+    pub fn total_size(&self) -> u64 {
+        self.entries.iter().filter(|entry| !entry.is_directory()).map(|entry| entry.size).fold(0, u64::saturating_add)
+    }
+
+    /// Decompresses the file `name`, or returns `None` if the archive has no such file.
+    pub fn read(&mut self, name: &str) -> AddonResult<Option<Vec<u8>>> {
+        match self.entries.iter().position(|entry| entry.name == name && !entry.is_directory()) {
+            Some(index) => self.read_index(index).map(Some),
+            None => Ok(None)
+        }
+    }
+
+    fn read_index(&mut self, index: usize) -> AddonResult<Vec<u8>> {
+        let entry = &self.entries[index];
+        let error = |message: &str| AddonError::archive(&entry.name, message);
+        if entry.flags & 1 != 0 {
+            return Err(error("encrypted entries aren't supported"));
+        }
+        if entry.size > self.limits.max_entry_size {
+            return Err(error(&format!("entry is {} bytes, more than the limit of {}", entry.size, self.limits.max_entry_size)));
+        }
+
+        self.reader.seek(SeekFrom::Start(entry.header_offset)).map_err(read_error)?;
+        let mut header = [0u8; 30];
+        self.reader.read_exact(&mut header).map_err(|_| error("truncated local file header"))?;
+        if u32_at(&header, 0) != Some(0x0403_4b50) {
+            return Err(error("missing local file header"));
+        }
+        let skipped = u16_at(&header, 26).unwrap_or_default() as i64 + u16_at(&header, 28).unwrap_or_default() as i64;
+        self.reader.seek(SeekFrom::Current(skipped)).map_err(read_error)?;
+        let mut compressed = vec![];
+        self.reader.by_ref().take(entry.compressed_size).read_to_end(&mut compressed).map_err(read_error)?;
+        if compressed.len() as u64 != entry.compressed_size {
+            return Err(error("entry data runs past the end of the archive"));
+        }
+
+        let data = match entry.method {
+            0 => compressed,
+            8 => inflate(&compressed, entry.size as usize).map_err(error)?,
+            method => return Err(error(&format!("compression method {} isn't supported", method)))
+        };
+        if data.len() as u64 != entry.size || !crc32_update(!0, &data) != entry.crc {
+            return Err(error("size or CRC doesn't match the central directory"));
+        }
+        Ok(data)
+    }
+
+    fn check_total_size(&self) -> AddonResult<()> {
+        if self.total_size() > self.limits.max_total_size {
+            return Err(AddonError::archive(IN_MEMORY, format!("archive decompresses to more than the limit of {} bytes", self.limits.max_total_size)));
+        }
+        Ok(())
+    }
+
+    /// Decompresses every file, by name. Fails without decompressing anything if they add up to
+    /// more than [`ArchiveLimits::max_total_size`].
+    pub fn read_all(&mut self) -> AddonResult<BTreeMap<String, Vec<u8>>> {
+        self.check_total_size()?;
+        let mut files = BTreeMap::new();
+        for index in 0..self.entries.len() {
+            if !self.entries[index].is_directory() {
+                let data = self.read_index(index)?;
+                files.insert(self.entries[index].name.clone(), data);
+            }
+        }
+        Ok(files)
+    }
+}
+
+/// Reads every file of a `.mcpack`/`.mcaddon` zip archive held in memory, by its `/`-separated
+/// path, within the default [`ArchiveLimits`]. See [`McpackReader`] for what's supported.
+pub fn deserialize_mcpack_from_bytes(bytes: &[u8]) -> AddonResult<BTreeMap<String, Vec<u8>>> {
+    deserialize_mcpack_from_bytes_with_limits(bytes, &ArchiveLimits::default())
+}
+
+/// Like [`deserialize_mcpack_from_bytes`], within `limits`.
+pub fn deserialize_mcpack_from_bytes_with_limits(bytes: &[u8], limits: &ArchiveLimits) -> AddonResult<BTreeMap<String, Vec<u8>>> {
+    McpackReader::with_limits(Cursor::new(bytes), *limits)?.read_all()
+}
+
+/// Like [`deserialize_mcpack_from_bytes`], reading the archive from `reader` first. Use
+/// [`McpackReader`] for sources that can seek.
+pub fn deserialize_mcpack_from_reader(reader: impl Read) -> AddonResult<BTreeMap<String, Vec<u8>>> {
+    deserialize_mcpack_from_reader_with_limits(reader, &ArchiveLimits::default())
+}
+
+/// Like [`deserialize_mcpack_from_reader`], within `limits`.
+pub fn deserialize_mcpack_from_reader_with_limits(mut reader: impl Read, limits: &ArchiveLimits) -> AddonResult<BTreeMap<String, Vec<u8>>> {
+    let mut bytes = vec![];
+    reader.read_to_end(&mut bytes).map_err(read_error)?;
+    deserialize_mcpack_from_bytes_with_limits(&bytes, limits)
+}
+
+/// Errors of an archive read from `path` reported against it, or the entry within it.
+fn at_path(path: &Path, error: AddonError) -> AddonError {
+    match error {
+        AddonError::Archive { path: entry, message } if entry == Path::new(IN_MEMORY) => AddonError::archive(path, message),
+        AddonError::Archive { path: entry, message } => AddonError::archive(path.join(entry), message),
+        AddonError::Io { path: entry, source } if entry == Path::new(IN_MEMORY) => AddonError::io(path, source),
+        error => error
+    }
+}
+
+/// Opens the archive at `path` for reading entries on demand.
+pub fn open_mcpack(path: impl AsRef<Path>, limits: &ArchiveLimits) -> AddonResult<McpackReader<BufReader<File>>> {
+    let path = path.as_ref();
+    let file = File::open(path).map_err(|error| AddonError::io(path, error))?;
+    McpackReader::with_limits(BufReader::new(file), *limits).map_err(|error| at_path(path, error))
+}
+
+/// Reads the archive at `path` within the default [`ArchiveLimits`].
+pub fn deserialize_mcpack_from_path(path: impl AsRef<Path>) -> AddonResult<BTreeMap<String, Vec<u8>>> {
+    deserialize_mcpack_from_path_with_limits(path, &ArchiveLimits::default())
+}
+
+/// Like [`deserialize_mcpack_from_path`], within `limits`.
+pub fn deserialize_mcpack_from_path_with_limits(path: impl AsRef<Path>, limits: &ArchiveLimits) -> AddonResult<BTreeMap<String, Vec<u8>>> {
+    let path = path.as_ref();
+    open_mcpack(path, limits)?.read_all().map_err(|error| at_path(path, error))
+}
+
+/// Unpacks the archive at `archive` into the folder `output` within the default
+/// [`ArchiveLimits`], returning the files written relative to it. An entry whose name would land
+/// outside `output`, like `../x` or an absolute path, fails the whole extraction.
+pub fn extract_mcpack(archive: impl AsRef<Path>, output: impl AsRef<Path>) -> AddonResult<Vec<PathBuf>> {
+    extract_mcpack_with_limits(archive, output, &ArchiveLimits::default())
+}
+
+/// Like [`extract_mcpack`], within `limits`. Entries are decompressed and written one at a time;
+/// names and limits are checked first, so an archive failing those writes nothing.
+pub fn extract_mcpack_with_limits(archive: impl AsRef<Path>, output: impl AsRef<Path>, limits: &ArchiveLimits) -> AddonResult<Vec<PathBuf>> {
+    let (archive, output) = (archive.as_ref(), output.as_ref());
+    let mut reader = open_mcpack(archive, limits)?;
+    reader.check_total_size().map_err(|error| at_path(archive, error))?;
+    let names: Vec<String> = reader.entries().iter().filter(|entry| !entry.is_directory()).map(|entry| entry.name.clone()).collect();
+    if let Some(name) = names.iter().find(|name| !Path::new(name).components().all(|component| matches!(component, Component::Normal(_)))) {
+        return Err(AddonError::archive(archive.join(name), "entry path leaves the output folder"));
+    }
+    if let Some(entry) = reader.entries().iter().find(|entry| entry.size > limits.max_entry_size) {
+        return Err(AddonError::archive(archive.join(&entry.name), format!("entry is {} bytes, more than the limit of {}", entry.size, limits.max_entry_size)));
+    }
+
+    let mut written = vec![];
+    for name in names {
+        let bytes = reader.read(&name).map_err(|error| at_path(archive, error))?.unwrap_or_default();
+        let relative = PathBuf::from(&name);
+        let target = output.join(&relative);
+        if let Some(parent) = target.parent() {
+            fs::create_dir_all(parent).map_err(|error| AddonError::io(parent, error))?;
+        }
+        fs::write(&target, bytes).map_err(|error| AddonError::io(&target, error))?;
+        written.push(relative);
+    }
+    Ok(written)
+}
@@ -1 +1,40 @@
-pub mod manifest;
\ No newline at end of file
+#[cfg(all(feature = "resource", feature = "molang"))]
+pub mod animation;
+#[cfg(feature = "behavior")]
+pub mod builders;
+#[cfg(feature = "resource")]
+pub mod client_entity;
+#[cfg(feature = "manifest")]
+pub mod dependencies;
+#[cfg(feature = "behavior")]
+pub mod entity;
+#[cfg(feature = "resource")]
+pub mod geometry;
+#[cfg(feature = "resource")]
+pub mod lang;
+#[cfg(any(feature = "archive", feature = "textures"))]
+pub(crate) mod inflate;
+#[cfg(feature = "nbt")]
+pub mod level;
+#[cfg(feature = "behavior")]
+pub mod loot_table;
+#[cfg(feature = "manifest")]
+pub mod manifest;
+#[cfg(feature = "archive")]
+pub mod mcpack;
+#[cfg(all(feature = "manifest", feature = "archive"))]
+pub mod pack_info;
+#[cfg(feature = "manifest")]
+pub mod parse;
+#[cfg(all(feature = "resource", feature = "molang"))]
+pub mod particle;
+#[cfg(feature = "manifest")]
+pub mod server_config;
+#[cfg(feature = "resource")]
+pub mod sound_definitions;
+#[cfg(feature = "nbt")]
+pub mod structure;
+#[cfg(feature = "resource")]
+pub mod textures_list;
+#[cfg(all(feature = "archive", feature = "nbt", feature = "manifest"))]
+pub mod world_template;
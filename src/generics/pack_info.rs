@@ -0,0 +1,115 @@
+use std::path::Path;
+use uuid::Uuid;
+use crate::error::AddonResult;
+use crate::generics::manifest::{deserialize_manifest_from_path, Manifest, ManifestCapability, ManifestModule};
+use crate::generics::mcpack::write_mcpack;
+use crate::utils::SemVer;
+
+/// What a server advertises about a pack it serves, as in the entries of the ResourcePacksInfo
+/// and ResourcePackDataInfo packets.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct PackInfo {
+    pub uuid: Uuid,
+    pub version: SemVer,
+    /// The size of the `.mcpack` archive in bytes.
+    pub size: u64,
+    /// Empty, as packs are served unencrypted.
+    pub content_key: String,
+    pub sub_pack_name: String,
+    pub content_identity: String,
+    pub has_scripts: bool,
+    pub raytracing_capable: bool,
+    /// The SHA-256 of the `.mcpack` archive.
+    pub hash: [u8; 32]
+}
+
+impl PackInfo {
+    /// The info for `manifest`'s pack served as `archive`.
+    pub fn new(manifest: &Manifest, archive: &[u8]) -> PackInfo {
+        PackInfo {
+            uuid: manifest.header.uuid,
+            version: manifest.header.version.clone(),
+            size: archive.len() as u64,
+            content_key: String::new(),
+            sub_pack_name: String::new(),
+            content_identity: String::new(),
+            has_scripts: manifest.modules.iter().any(|module| matches!(module, ManifestModule::Script(..))),
+            raytracing_capable: manifest.capabilities.contains(&ManifestCapability::Raytraced),
+            hash: sha256(archive)
+        }
+    }
+
+    /// The version as the packets carry it, e.g. `1.0.0`.
+    pub fn version_string(&self) -> String {
+        self.version.to_string()
+    }
+}
+
+/// Archives the pack folder at `pack` and describes it, returning the archive for the server to
+/// send in chunks.
+pub fn build_pack_info(pack: &Path) -> AddonResult<(PackInfo, Vec<u8>)> {
+    let manifest = deserialize_manifest_from_path(&pack.join("manifest.json"))?;
+    let archive = write_mcpack(pack, vec![])?;
+    Ok((PackInfo::new(&manifest, &archive), archive))
+}
+
+const K: [u32; 64] = [
+    0x428a2f98, 0x71374491, 0xb5c0fbcf, 0xe9b5dba5, 0x3956c25b, 0x59f111f1, 0x923f82a4, 0xab1c5ed5,
+    0xd807aa98, 0x12835b01, 0x243185be, 0x550c7dc3, 0x72be5d74, 0x80deb1fe, 0x9bdc06a7, 0xc19bf174,
+    0xe49b69c1, 0xefbe4786, 0x0fc19dc6, 0x240ca1cc, 0x2de92c6f, 0x4a7484aa, 0x5cb0a9dc, 0x76f988da,
+    0x983e5152, 0xa831c66d, 0xb00327c8, 0xbf597fc7, 0xc6e00bf3, 0xd5a79147, 0x06ca6351, 0x14292967,
+    0x27b70a85, 0x2e1b2138, 0x4d2c6dfc, 0x53380d13, 0x650a7354, 0x766a0abb, 0x81c2c92e, 0x92722c85,
+    0xa2bfe8a1, 0xa81a664b, 0xc24b8b70, 0xc76c51a3, 0xd192e819, 0xd6990624, 0xf40e3585, 0x106aa070,
+    0x19a4c116, 0x1e376c08, 0x2748774c, 0x34b0bcb5, 0x391c0cb3, 0x4ed8aa4a, 0x5b9cca4f, 0x682e6ff3,
+    0x748f82ee, 0x78a5636f, 0x84c87814, 0x8cc70208, 0x90befffa, 0xa4506ceb, 0xbef9a3f7, 0xc67178f2
+];
+
+pub(crate) fn sha256(bytes: &[u8]) -> [u8; 32] {
+    let mut state: [u32; 8] = [0x6a09e667, 0xbb67ae85, 0x3c6ef372, 0xa54ff53a, 0x510e527f, 0x9b05688c, 0x1f83d9ab, 0x5be0cd19];
+
+    let mut message = bytes.to_vec();
+    message.push(0x80);
+    while message.len() % 64 != 56 {
+        message.push(0);
+    }
+    message.extend((bytes.len() as u64 * 8).to_be_bytes());
+
+    for block in message.chunks_exact(64) {
+        let mut w = [0u32; 64];
+        for (i, word) in block.chunks_exact(4).enumerate() {
+            w[i] = u32::from_be_bytes([word[0], word[1], word[2], word[3]]);
+        }
+        for i in 16..64 {
+            let s0 = w[i - 15].rotate_right(7) ^ w[i - 15].rotate_right(18) ^ (w[i - 15] >> 3);
+            let s1 = w[i - 2].rotate_right(17) ^ w[i - 2].rotate_right(19) ^ (w[i - 2] >> 10);
+            w[i] = w[i - 16].wrapping_add(s0).wrapping_add(w[i - 7]).wrapping_add(s1);
+        }
+
+        let [mut a, mut b, mut c, mut d, mut e, mut f, mut g, mut h] = state;
+        for i in 0..64 {
+            let s1 = e.rotate_right(6) ^ e.rotate_right(11) ^ e.rotate_right(25);
+            let choice = (e & f) ^ (!e & g);
+            let t1 = h.wrapping_add(s1).wrapping_add(choice).wrapping_add(K[i]).wrapping_add(w[i]);
+            let s0 = a.rotate_right(2) ^ a.rotate_right(13) ^ a.rotate_right(22);
+            let majority = (a & b) ^ (a & c) ^ (b & c);
+            let t2 = s0.wrapping_add(majority);
+            h = g;
+            g = f;
+            f = e;
+            e = d.wrapping_add(t1);
+            d = c;
+            c = b;
+            b = a;
+            a = t1.wrapping_add(t2);
+        }
+        for (word, value) in state.iter_mut().zip([a, b, c, d, e, f, g, h]) {
+            *word = word.wrapping_add(value);
+        }
+    }
+
+    let mut digest = [0u8; 32];
+    for (chunk, word) in digest.chunks_exact_mut(4).zip(state) {
+        chunk.copy_from_slice(&word.to_be_bytes());
+    }
+    digest
+}
@@ -0,0 +1,44 @@
+use std::path::Path;
+use crate::diagnostics::{Diagnostic, Diagnostics, Severity};
+use crate::error::{AddonError, AddonResult};
+
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum ParseMode {
+    /// Fail on the first problem.
+    #[default]
+    Strict,
+    /// Skip entries with recoverable problems, e.g. an unknown module type or a dependency with a
+    /// malformed uuid, and report them as warnings instead.
+    Lenient
+}
+
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct ParseOptions {
+    pub mode: ParseMode
+}
+
+impl ParseOptions {
+    pub fn strict() -> ParseOptions {
+        ParseOptions { mode: ParseMode::Strict }
+    }
+
+    pub fn lenient() -> ParseOptions {
+        ParseOptions { mode: ParseMode::Lenient }
+    }
+
+    /// Handles a problem with a single entry at `pointer`: strict mode returns `error`, lenient
+    /// mode records it in `diagnostics` so the caller can skip the entry and continue.
+    pub(crate) fn recover(&self, error: AddonError, code: &'static str, pointer: String, diagnostics: &mut Diagnostics) -> AddonResult<()> {
+        if self.mode == ParseMode::Strict {
+            return Err(error);
+        }
+        let message = match &error {
+            AddonError::Semantic { message, .. } | AddonError::Archive { message, .. } => message.clone(),
+            AddonError::Io { source, .. } => source.to_string(),
+            AddonError::Json { source, .. } => source.to_string()
+        };
+        let file = error.path().file_name().map(Path::new).unwrap_or(error.path()).to_path_buf();
+        diagnostics.push(Diagnostic::new(Severity::Warning, code, message).at(file, pointer));
+        Ok(())
+    }
+}
@@ -0,0 +1,301 @@
+use std::collections::BTreeMap;
+use std::path::Path;
+use serde_json::{Map, Value};
+use crate::error::{read_to_string, AddonError, AddonResult};
+use crate::molang::eval::MolangContext;
+use crate::molang::{molang_from_value, Molang};
+use crate::utils::from_json_str;
+
+/// How a particle curve runs through its nodes.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum CurveType {
+    /// Straight lines between nodes spread evenly over the range.
+    Linear,
+    /// One cubic Bézier curve with the four nodes as control points.
+    Bezier,
+    /// A smooth curve through every node but the first and last, which only steer it.
+    CatmullRom,
+    /// Hermite segments between nodes placed at their own times, with their own slopes.
+    BezierChain
+}
+
+/// A node of a [`CurveType::BezierChain`] curve. The curve arrives at the node with the left value
+/// and slope and leaves it with the right ones.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ChainNode {
+    pub time: f32,
+    pub left_value: Molang,
+    pub right_value: Molang,
+    pub left_slope: Molang,
+    pub right_slope: Molang
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum CurveNodes {
+    Values(Vec<Molang>),
+    Chain(Vec<ChainNode>)
+}
+
+/// A curve of a particle effect, whose value the game stores in the variable it is named after
+/// every time the effect updates.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Curve {
+    pub curve_type: CurveType,
+    /// Where on the curve to read, from 0 to [`horizontal_range`](Curve::horizontal_range).
+    pub input: Molang,
+    pub horizontal_range: Molang,
+    pub nodes: CurveNodes
+}
+
+/// A particle effect spawned by an event.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ParticleEffectEvent {
+    pub effect: String,
+    /// `emitter`, `emitter_bound`, `particle` or `particle_with_velocity`.
+    pub effect_type: String,
+    /// Run on the new effect before it starts.
+    pub pre_effect_expression: Option<Molang>
+}
+
+/// A particle event, or one step of a `sequence` or `randomize` of one.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ParticleEvent {
+    /// The relative chance of the step, for steps of a `randomize`.
+    pub weight: Option<f64>,
+    pub particle_effect: Option<ParticleEffectEvent>,
+    /// The sound event played.
+    pub sound_effect: Option<String>,
+    /// Run on the emitter, usually to set variables.
+    pub expression: Option<Molang>,
+    /// Written to the content log.
+    pub log: Option<String>,
+    pub sequence: Vec<ParticleEvent>,
+    pub randomize: Vec<ParticleEvent>
+}
+
+/// What firing a [`ParticleEvent`] did besides evaluating expressions.
+#[derive(Clone, Debug, PartialEq)]
+pub enum FiredEffect {
+    Particle { effect: String, effect_type: String },
+    Sound(String),
+    Log(String)
+}
+
+/// A particle effect document.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ParticleEffect {
+    pub identifier: String,
+    /// The `basic_render_parameters` of the description.
+    pub material: Option<String>,
+    pub texture: Option<String>,
+    /// By the variable they are stored in, e.g. `variable.size`.
+    pub curves: BTreeMap<String, Curve>,
+    pub events: BTreeMap<String, ParticleEvent>,
+    pub components: Map<String, Value>
+}
+
+fn number(expression: &Molang, context: &mut MolangContext) -> f32 {
+    expression.evaluate(context).unwrap_or(0.0)
+}
+
+fn hermite(from: f32, from_slope: f32, to: f32, to_slope: f32, width: f32, t: f32) -> f32 {
+    let (t2, t3) = (t * t, t * t * t);
+    (2.0 * t3 - 3.0 * t2 + 1.0) * from + (t3 - 2.0 * t2 + t) * width * from_slope + (3.0 * t2 - 2.0 * t3) * to + (t3 - t2) * width * to_slope
+}
+
+impl Curve {
+    /// The value of the curve at its [`input`](Curve::input) in `context`.
+    pub fn evaluate(&self, context: &mut MolangContext) -> f32 {
+        let range = number(&self.horizontal_range, context);
+        let input = number(&self.input, context);
+        let t = if range == 0.0 { 0.0 } else { (input / range).clamp(0.0, 1.0) };
+        match &self.nodes {
+            CurveNodes::Values(nodes) => {
+                let values: Vec<f32> = nodes.iter().map(|node| number(node, context)).collect();
+                self.evaluate_values(&values, t)
+            }
+            CurveNodes::Chain(nodes) => {
+                let next = nodes.partition_point(|node| node.time <= t);
+                if next == 0 {
+                    return nodes.first().map_or(0.0, |node| number(&node.left_value, context));
+                }
+                let from = &nodes[next - 1];
+                let Some(to) = nodes.get(next) else {
+                    return number(&from.right_value, context);
+                };
+                let width = to.time - from.time;
+                hermite(
+                    number(&from.right_value, context), number(&from.right_slope, context),
+                    number(&to.left_value, context), number(&to.left_slope, context),
+                    width, (t - from.time) / width
+                )
+            }
+        }
+    }
+
+    fn evaluate_values(&self, values: &[f32], t: f32) -> f32 {
+        match (self.curve_type, values) {
+            (_, []) => 0.0,
+            (_, [value]) => *value,
+            (CurveType::Bezier, [p0, p1, p2, p3]) => {
+                let u = 1.0 - t;
+                u * u * u * p0 + 3.0 * u * u * t * p1 + 3.0 * u * t * t * p2 + t * t * t * p3
+            }
+            (CurveType::CatmullRom, values) if values.len() >= 4 => {
+                let segments = values.len() - 3;
+                let position = t * segments as f32;
+                let segment = (position.floor() as usize).min(segments - 1);
+                let u = position - segment as f32;
+                let [p0, p1, p2, p3] = [values[segment], values[segment + 1], values[segment + 2], values[segment + 3]];
+                0.5 * (2.0 * p1 + (p2 - p0) * u + (2.0 * p0 - 5.0 * p1 + 4.0 * p2 - p3) * u * u + (3.0 * p1 - p0 - 3.0 * p2 + p3) * u * u * u)
+            }
+            // Linear, and curves with a node count their type can't use.
+            (_, values) => {
+                let position = t * (values.len() - 1) as f32;
+                let segment = (position.floor() as usize).min(values.len() - 2);
+                let u = position - segment as f32;
+                values[segment] + (values[segment + 1] - values[segment]) * u
+            }
+        }
+    }
+}
+
+impl ParticleEvent {
+    /// Fires the event: runs its expressions in `context` and returns the effects it spawns and
+    /// plays, in order. `randomize` picks one step by weight using the context's random state.
+    pub fn fire(&self, context: &mut MolangContext) -> Vec<FiredEffect> {
+        let mut fired = vec![];
+        self.fire_into(context, &mut fired);
+        fired
+    }
+
+    fn fire_into(&self, context: &mut MolangContext, fired: &mut Vec<FiredEffect>) {
+        if let Some(particle) = &self.particle_effect {
+            if let Some(expression) = &particle.pre_effect_expression {
+                expression.evaluate(context);
+            }
+            fired.push(FiredEffect::Particle { effect: particle.effect.clone(), effect_type: particle.effect_type.clone() });
+        }
+        if let Some(sound) = &self.sound_effect {
+            fired.push(FiredEffect::Sound(sound.clone()));
+        }
+        if let Some(expression) = &self.expression {
+            expression.evaluate(context);
+        }
+        if let Some(log) = &self.log {
+            fired.push(FiredEffect::Log(log.clone()));
+        }
+        for step in &self.sequence {
+            step.fire_into(context, fired);
+        }
+
+        let total: f64 = self.randomize.iter().map(|step| step.weight.unwrap_or(1.0).max(0.0)).sum();
+        if total > 0.0 {
+            let mut roll = context.random() as f64 * total;
+            for step in &self.randomize {
+                roll -= step.weight.unwrap_or(1.0).max(0.0);
+                if roll < 0.0 {
+                    step.fire_into(context, fired);
+                    break;
+                }
+            }
+        }
+    }
+}
+
+impl ParticleEffect {
+    /// Evaluates every curve and stores it in its variable, as the game does before evaluating the
+    /// components each update.
+    pub fn update_curves(&self, context: &mut MolangContext) {
+        for (name, curve) in &self.curves {
+            let value = curve.evaluate(context);
+            let variable = name.strip_prefix("variable.").or_else(|| name.strip_prefix("v.")).unwrap_or(name);
+            context.variables.insert(variable.to_string(), value);
+        }
+    }
+
+    /// Fires the event `name`, or returns `None` if the effect doesn't define it.
+    pub fn fire_event(&self, name: &str, context: &mut MolangContext) -> Option<Vec<FiredEffect>> {
+        Some(self.events.get(name)?.fire(context))
+    }
+}
+
+fn parse_curve(value: &Value) -> Option<Curve> {
+    let curve_type = match value.get("type")?.as_str()? {
+        "linear" => CurveType::Linear,
+        "bezier" => CurveType::Bezier,
+        "catmull_rom" => CurveType::CatmullRom,
+        "bezier_chain" => CurveType::BezierChain,
+        _ => return None
+    };
+    let nodes = match value.get("nodes") {
+        Some(Value::Object(nodes)) => {
+            let mut chain: Vec<ChainNode> = nodes.iter().filter_map(|(time, node)| {
+                let field = |key: &str, fallback: &str| node.get(key).or_else(|| node.get(fallback)).and_then(molang_from_value);
+                Some(ChainNode {
+                    time: time.trim().parse().ok()?,
+                    left_value: field("left_value", "value")?,
+                    right_value: field("right_value", "value")?,
+                    left_slope: field("left_slope", "slope").unwrap_or(Molang::from("0")),
+                    right_slope: field("right_slope", "slope").unwrap_or(Molang::from("0"))
+                })
+            }).collect();
+            chain.sort_by(|a, b| a.time.total_cmp(&b.time));
+            CurveNodes::Chain(chain)
+        }
+        Some(Value::Array(nodes)) => CurveNodes::Values(nodes.iter().filter_map(molang_from_value).collect()),
+        _ => CurveNodes::Values(vec![])
+    };
+    Some(Curve {
+        curve_type,
+        input: value.get("input").and_then(molang_from_value).unwrap_or(Molang::from("0")),
+        horizontal_range: value.get("horizontal_range").and_then(molang_from_value).unwrap_or(Molang::from("1")),
+        nodes
+    })
+}
+
+fn parse_event(value: &Value) -> ParticleEvent {
+    let steps = |key: &str| value.get(key).and_then(Value::as_array).into_iter().flatten().map(parse_event).collect();
+    let particle_effect = value.get("particle_effect").and_then(|particle| Some(ParticleEffectEvent {
+        effect: particle.get("effect")?.as_str()?.to_string(),
+        effect_type: particle.get("type").and_then(Value::as_str).unwrap_or("emitter").to_string(),
+        pre_effect_expression: particle.get("pre_effect_expression").and_then(molang_from_value)
+    }));
+    ParticleEvent {
+        weight: value.get("weight").and_then(Value::as_f64),
+        particle_effect,
+        sound_effect: value.get("sound_effect").and_then(|sound| sound.get("event_name")).and_then(Value::as_str).map(str::to_string),
+        expression: value.get("expression").and_then(molang_from_value),
+        log: value.get("log").and_then(Value::as_str).map(str::to_string),
+        sequence: steps("sequence"),
+        randomize: steps("randomize")
+    }
+}
+
+/// Reads a particle effect document, or `None` if it doesn't define an effect with an
+/// identifier. Curves of an unknown type are skipped.
+pub fn deserialize_particle_effect_from_value(document: &Value) -> Option<ParticleEffect> {
+    let effect = document.get("particle_effect")?;
+    let description = effect.get("description")?;
+    let render = description.get("basic_render_parameters");
+    let render_field = |key: &str| render.and_then(|render| render.get(key)).and_then(Value::as_str).map(str::to_string);
+    Some(ParticleEffect {
+        identifier: description.get("identifier")?.as_str()?.to_string(),
+        material: render_field("material"),
+        texture: render_field("texture"),
+        curves: effect.get("curves").and_then(Value::as_object).into_iter().flatten()
+            .filter_map(|(name, curve)| Some((name.clone(), parse_curve(curve)?)))
+            .collect(),
+        events: effect.get("events").and_then(Value::as_object).into_iter().flatten()
+            .map(|(name, event)| (name.clone(), parse_event(event)))
+            .collect(),
+        components: effect.get("components").and_then(Value::as_object).cloned().unwrap_or_default()
+    })
+}
+
+/// Reads the particle file at `path` with [`deserialize_particle_effect_from_value`].
+pub fn deserialize_particle_effect_from_path(path: impl AsRef<Path>) -> AddonResult<ParticleEffect> {
+    let path = path.as_ref();
+    let document: Value = from_json_str(&read_to_string(path)?).map_err(|error| AddonError::json(path, error))?;
+    deserialize_particle_effect_from_value(&document).ok_or_else(|| AddonError::semantic(path, "not a particle effect"))
+}
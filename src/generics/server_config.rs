@@ -0,0 +1,123 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Map, Value};
+use uuid::Uuid;
+use crate::error::{read_to_string, AddonError, AddonResult};
+use crate::generics::manifest::{Manifest, ManifestDependency};
+use crate::utils::{from_json_str, to_canonical_json_string};
+
+/// The `permissions.json` of a dedicated server pack config: the script modules the pack may
+/// import. A module missing here fails to load on the server even when the manifest depends on it.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, Eq, PartialEq)]
+pub struct ScriptPermissions {
+    #[serde(default)]
+    pub allowed_modules: Vec<String>
+}
+
+impl ScriptPermissions {
+    pub fn allows(&self, module: &str) -> bool {
+        self.allowed_modules.iter().any(|allowed| allowed == module)
+    }
+
+    /// Adds `module` unless it is already allowed.
+    pub fn allow(&mut self, module: &str) {
+        if !self.allows(module) {
+            self.allowed_modules.push(module.to_string());
+        }
+    }
+}
+
+/// The configuration a Bedrock Dedicated Server keeps for one pack in `config/<pack uuid>/`.
+/// Scripts read the variables with `variables.get` and the secrets with `secrets.get` of
+/// `@minecraft/server-admin`.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ServerPackConfig {
+    /// The header uuid of the pack's manifest.
+    pub pack_uuid: Uuid,
+    pub permissions: ScriptPermissions,
+    /// `variables.json`, any JSON values.
+    pub variables: Map<String, Value>,
+    /// `secrets.json`. Secrets are strings scripts can pass to `@minecraft/server-net` without
+    /// reading them.
+    pub secrets: BTreeMap<String, String>
+}
+
+impl ServerPackConfig {
+    /// The config for the pack of `manifest`, allowing every script module it depends on.
+    pub fn from_manifest(manifest: &Manifest) -> ServerPackConfig {
+        let mut permissions = ScriptPermissions::default();
+        for dependency in &manifest.dependencies {
+            if let ManifestDependency::ScriptDependency(module, _) = dependency {
+                permissions.allow(module.module_name());
+            }
+        }
+        ServerPackConfig { pack_uuid: manifest.header.uuid, permissions, ..ServerPackConfig::default() }
+    }
+
+    /// The config folder of the pack relative to the server root.
+    pub fn directory(&self) -> PathBuf {
+        Path::new("config").join(self.pack_uuid.to_string())
+    }
+
+    /// Adds the modules, variables and secrets of `existing` this config doesn't have, so
+    /// provisioning a server again keeps what its operator configured.
+    pub fn merge(&mut self, existing: &ServerPackConfig) {
+        for module in &existing.permissions.allowed_modules {
+            self.permissions.allow(module);
+        }
+        for (key, value) in &existing.variables {
+            self.variables.entry(key.clone()).or_insert_with(|| value.clone());
+        }
+        for (key, value) in &existing.secrets {
+            self.secrets.entry(key.clone()).or_insert_with(|| value.clone());
+        }
+    }
+}
+
+fn read_json_file<T: for<'de> Deserialize<'de> + Default>(path: &Path) -> AddonResult<T> {
+    if !path.is_file() {
+        return Ok(T::default());
+    }
+    from_json_str(&read_to_string(path)?).map_err(|error| AddonError::json(path, error))
+}
+
+/// Reads the config of the pack `pack_uuid` under the server root `server`. Missing files leave
+/// their part empty.
+pub fn deserialize_server_pack_config_from_path(server: &Path, pack_uuid: Uuid) -> AddonResult<ServerPackConfig> {
+    let mut config = ServerPackConfig { pack_uuid, ..ServerPackConfig::default() };
+    let dir = server.join(config.directory());
+    config.permissions = read_json_file(&dir.join("permissions.json"))?;
+    config.variables = read_json_file(&dir.join("variables.json"))?;
+    config.secrets = read_json_file(&dir.join("secrets.json"))?;
+    Ok(config)
+}
+
+/// Writes `config` under the server root `server`. `variables.json` and `secrets.json` are only
+/// written when they have entries.
+pub fn write_server_pack_config(config: &ServerPackConfig, server: &Path) -> AddonResult<()> {
+    let dir = server.join(config.directory());
+    fs::create_dir_all(&dir).map_err(|error| AddonError::io(&dir, error))?;
+    let mut files = vec![("permissions.json", json!(config.permissions))];
+    if !config.variables.is_empty() {
+        files.push(("variables.json", Value::Object(config.variables.clone())));
+    }
+    if !config.secrets.is_empty() {
+        files.push(("secrets.json", json!(config.secrets)));
+    }
+    for (name, value) in files {
+        let path = dir.join(name);
+        fs::write(&path, to_canonical_json_string(&value)).map_err(|error| AddonError::io(&path, error))?;
+    }
+    Ok(())
+}
+
+/// Provisions the server at `server` for the pack `manifest` describes: allows its script
+/// modules, keeping whatever the existing config already has, and returns the written config.
+pub fn provision_server_pack_config(manifest: &Manifest, server: &Path) -> AddonResult<ServerPackConfig> {
+    let mut config = ServerPackConfig::from_manifest(manifest);
+    config.merge(&deserialize_server_pack_config_from_path(server, config.pack_uuid)?);
+    write_server_pack_config(&config, server)?;
+    Ok(config)
+}
@@ -0,0 +1,100 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+use serde_json::{json, Value};
+use crate::error::{AddonError, AddonResult};
+use crate::files::{pack_files, EditableJson};
+use crate::utils::JsonStyle;
+
+/// The audio formats the game plays.
+const AUDIO_EXTENSIONS: [&str; 4] = ["ogg", "wav", "fsb", "mp3"];
+
+/// How the event name of an audio file below `sounds/` is chosen.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum SoundNaming {
+    /// The folder names the event and every file in it is a variant: `sounds/mob/golem/step/a.ogg`
+    /// plays for `mob.golem.step`.
+    Folder,
+    /// The file name without its trailing number names the event within its folder, the vanilla
+    /// layout: `sounds/mob/pig/say1.ogg` and `say2.ogg` play for `mob.pig.say`.
+    FileStem
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct SoundScanOptions {
+    pub naming: SoundNaming,
+    /// Put before every event name, e.g. `gems.`.
+    pub prefix: String,
+    /// The sound category of generated events, e.g. `neutral` or `block`.
+    pub category: String
+}
+
+impl Default for SoundScanOptions {
+    fn default() -> SoundScanOptions {
+        SoundScanOptions { naming: SoundNaming::FileStem, prefix: String::new(), category: "neutral".to_string() }
+    }
+}
+
+/// The sound event of every audio file below `sounds/` in `resource_pack`, with the paths of its
+/// files as `sound_definitions.json` references them: relative to the pack and without extension.
+pub fn scan_sound_files(resource_pack: &Path, options: &SoundScanOptions) -> BTreeMap<String, Vec<String>> {
+    let mut events: BTreeMap<String, Vec<String>> = BTreeMap::new();
+    for path in pack_files(resource_pack, "sounds") {
+        if !path.extension().and_then(|ext| ext.to_str()).is_some_and(|ext| AUDIO_EXTENSIONS.contains(&ext.to_ascii_lowercase().as_str())) {
+            continue;
+        }
+        let Ok(relative) = path.with_extension("").strip_prefix(resource_pack).map(|path| path.to_string_lossy().replace('\\', "/")) else {
+            continue;
+        };
+        let mut segments: Vec<&str> = relative.split('/').skip(1).collect();
+        let Some(stem) = segments.pop() else {
+            continue;
+        };
+        match options.naming {
+            SoundNaming::Folder if segments.is_empty() => continue,
+            SoundNaming::Folder => {}
+            SoundNaming::FileStem => segments.push(stem.trim_end_matches(|c: char| c.is_ascii_digit()).trim_end_matches(['_', '-']))
+        }
+        let event = format!("{}{}", options.prefix, segments.join("."));
+        events.entry(event).or_default().push(relative.clone());
+    }
+    events
+}
+
+/// Adds an event to `sounds/sound_definitions.json` of `resource_pack` for every
+/// [scanned](scan_sound_files) event it doesn't define yet. Events already there are left exactly
+/// as they are, so hand-tuned volumes, pitches and file lists survive regenerating. Returns the
+/// events added.
+pub fn write_sound_definitions(resource_pack: &Path, options: &SoundScanOptions) -> AddonResult<Vec<String>> {
+    let path = resource_pack.join("sounds/sound_definitions.json");
+    let mut file = if path.exists() {
+        EditableJson::open(&path)?
+    } else {
+        EditableJson { path: path.clone(), value: json!({ "format_version": "1.14.0", "sound_definitions": {} }), style: JsonStyle::default() }
+    };
+
+    // The versioned layout wraps the events in `sound_definitions`, the legacy one doesn't.
+    let definitions = match file.value.get("sound_definitions") {
+        Some(_) => &mut file.value["sound_definitions"],
+        None => &mut file.value
+    };
+    let Value::Object(definitions) = definitions else {
+        return Err(AddonError::semantic(&path, "sound definitions aren't an object"));
+    };
+
+    let mut added = vec![];
+    for (event, sounds) in scan_sound_files(resource_pack, options) {
+        if definitions.contains_key(&event) {
+            continue;
+        }
+        definitions.insert(event.clone(), json!({ "category": options.category, "sounds": sounds }));
+        added.push(event);
+    }
+    if !added.is_empty() {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).map_err(|error| AddonError::io(parent, error))?;
+        }
+        file.save()?;
+    }
+    Ok(added)
+}
@@ -0,0 +1,311 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+use crate::error::{AddonError, AddonResult};
+use crate::nbt::{read_nbt, write_nbt, Compound, NbtError, Tag};
+
+/// The block version [`BlockState::new`] uses, that of 1.20.
+pub const DEFAULT_BLOCK_VERSION: i32 = 18_090_528;
+
+/// A block of a structure's palette.
+#[derive(Clone, Debug, PartialEq)]
+pub struct BlockState {
+    pub name: String,
+    pub states: Compound,
+    /// The block version the states were saved with.
+    pub version: i32
+}
+
+impl BlockState {
+    pub fn new(name: &str) -> BlockState {
+        BlockState { name: name.to_string(), states: Compound::new(), version: DEFAULT_BLOCK_VERSION }
+    }
+
+    pub fn state(mut self, name: &str, value: Tag) -> BlockState {
+        self.states.insert(name.to_string(), value);
+        self
+    }
+
+    fn to_nbt(&self) -> Tag {
+        let mut block = Compound::new();
+        block.insert("name".to_string(), Tag::String(self.name.clone()));
+        block.insert("states".to_string(), Tag::Compound(self.states.clone()));
+        block.insert("version".to_string(), Tag::Int(self.version));
+        Tag::Compound(block)
+    }
+}
+
+/// A `.mcstructure` file, as saved by structure blocks and referenced by jigsaw and worldgen
+/// features.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Structure {
+    /// Along x, y and z.
+    pub size: [i32; 3],
+    /// Where the structure was saved from.
+    pub origin: [i32; 3],
+    pub palette: Vec<BlockState>,
+    /// The palette index of each block, see [`Structure::index`]. `None` is structure void, which
+    /// leaves the world's block alone when placed.
+    pub blocks: Vec<Option<usize>>,
+    /// The second layer of blocks, almost always water in waterlogged blocks.
+    pub extra_blocks: Vec<Option<usize>>,
+    /// The `block_entity_data` of blocks by index, e.g. chest contents.
+    pub block_entities: BTreeMap<usize, Compound>,
+    pub entities: Vec<Compound>
+}
+
+fn missing(path: &str) -> NbtError {
+    NbtError::MissingTag(path.to_string())
+}
+
+fn position(tag: Option<&Tag>, path: &str) -> Result<[i32; 3], NbtError> {
+    let values: Vec<i32> = tag.and_then(Tag::as_list).ok_or_else(|| missing(path))?
+        .iter().map(Tag::as_i32).collect::<Option<_>>().ok_or_else(|| missing(path))?;
+    values.try_into().map_err(|_| missing(path))
+}
+
+fn layer(tag: &Tag, volume: usize, palette: usize) -> Result<Vec<Option<usize>>, NbtError> {
+    let path = "structure/block_indices";
+    let values = tag.as_list().filter(|values| values.len() == volume).ok_or_else(|| missing(path))?;
+    values.iter().map(|value| match value.as_i32() {
+        Some(-1) => Ok(None),
+        Some(index) => usize::try_from(index).ok().filter(|index| *index < palette).map(Some).ok_or_else(|| missing(path)),
+        None => Err(missing(path))
+    }).collect()
+}
+
+/// The number of blocks in a structure of `size`, or `None` if an axis is negative or the blocks
+/// don't fit the `i32` indices of `block_indices`.
+fn checked_volume(size: [i32; 3]) -> Option<usize> {
+    let volume = size.iter().try_fold(1usize, |volume, axis| volume.checked_mul(usize::try_from(*axis).ok()?))?;
+    (volume <= i32::MAX as usize).then_some(volume)
+}
+
+fn int_list(values: [i32; 3]) -> Tag {
+    Tag::List(values.iter().map(|value| Tag::Int(*value)).collect())
+}
+
+fn layer_to_nbt(blocks: &[Option<usize>]) -> Tag {
+    Tag::List(blocks.iter().map(|block| Tag::Int(block.map_or(-1, |index| index as i32))).collect())
+}
+
+impl Structure {
+    /// An empty structure of the given size, all structure void. Fails with
+    /// [`NbtError::InvalidSize`] if an axis is negative or the volume doesn't fit an `i32`.
+    pub fn new(size: [i32; 3]) -> Result<Structure, NbtError> {
+        let volume = checked_volume(size).ok_or(NbtError::InvalidSize(size))?;
+        Ok(Structure {
+            size, origin: [0; 3], palette: vec![], blocks: vec![None; volume], extra_blocks: vec![None; volume],
+            block_entities: BTreeMap::new(), entities: vec![]
+        })
+    }
+
+    /// The index of the block at `x`, `y`, `z` in [`blocks`](Structure::blocks), or `None` if it's
+    /// outside the structure. Blocks are ordered by x, then y, then z.
+    pub fn index(&self, x: i32, y: i32, z: i32) -> Option<usize> {
+        let [sx, sy, sz] = self.size;
+        if !(0..sx).contains(&x) || !(0..sy).contains(&y) || !(0..sz).contains(&z) {
+            return None;
+        }
+        Some((x as usize * sy as usize + y as usize) * sz as usize + z as usize)
+    }
+
+    /// The block at `x`, `y`, `z`, or `None` for structure void and positions outside the
+    /// structure.
+    pub fn block(&self, x: i32, y: i32, z: i32) -> Option<&BlockState> {
+        let index = (*self.blocks.get(self.index(x, y, z)?)?)?;
+        self.palette.get(index)
+    }
+
+    pub fn block_entity(&self, x: i32, y: i32, z: i32) -> Option<&Compound> {
+        self.block_entities.get(&self.index(x, y, z)?)
+    }
+
+    /// Places `block` at `x`, `y`, `z`, or `None` for structure void, adding it to the palette
+    /// if needed. The block entity of the block replaced is dropped. Returns false if the position
+    /// is outside the structure.
+    pub fn set_block(&mut self, x: i32, y: i32, z: i32, block: Option<BlockState>) -> bool {
+        let Some(index) = self.index(x, y, z) else {
+            return false;
+        };
+        self.blocks[index] = block.map(|block| self.palette_index(block));
+        self.block_entities.remove(&index);
+        true
+    }
+
+    /// Sets the second layer block at `x`, `y`, `z`, e.g. water to waterlog it.
+    pub fn set_extra_block(&mut self, x: i32, y: i32, z: i32, block: Option<BlockState>) -> bool {
+        let Some(index) = self.index(x, y, z) else {
+            return false;
+        };
+        self.extra_blocks[index] = block.map(|block| self.palette_index(block));
+        true
+    }
+
+    fn palette_index(&mut self, block: BlockState) -> usize {
+        match self.palette.iter().position(|existing| *existing == block) {
+            Some(index) => index,
+            None => {
+                self.palette.push(block);
+                self.palette.len() - 1
+            }
+        }
+    }
+
+    /// Sets the `block_entity_data` of the block at `x`, `y`, `z`. Returns false if the position is
+    /// outside the structure.
+    pub fn set_block_entity(&mut self, x: i32, y: i32, z: i32, data: Compound) -> bool {
+        let Some(index) = self.index(x, y, z) else {
+            return false;
+        };
+        self.block_entities.insert(index, data);
+        true
+    }
+
+    /// Adds an entity. Its `Pos` is in world coordinates, like [`origin`](Structure::origin).
+    pub fn add_entity(&mut self, entity: Compound) {
+        self.entities.push(entity);
+    }
+
+    /// Changes the bounds to `size` blocks starting at `offset` from the current corner, cropping
+    /// when they shrink and filling with structure void when they grow. Blocks keep their place in
+    /// the world, so the origin moves by `offset`, and entities now outside are dropped. Fails like
+    /// [`Structure::new`], or when `offset` moves the origin or the far corner out of the `i32`
+    /// range, leaving the structure unchanged.
+    pub fn resize(&mut self, offset: [i32; 3], size: [i32; 3]) -> Result<(), NbtError> {
+        let mut resized = Structure::new(size)?;
+        for axis in 0..3 {
+            // The far corner bounds every `x + offset[0]` below.
+            let origin = self.origin[axis].checked_add(offset[axis]);
+            resized.origin[axis] = origin.filter(|_| offset[axis].checked_add(size[axis]).is_some()).ok_or(NbtError::InvalidOffset(offset))?;
+        }
+        resized.palette = self.palette.clone();
+        for x in 0..resized.size[0] {
+            for y in 0..resized.size[1] {
+                for z in 0..resized.size[2] {
+                    let (Some(old), Some(new)) = (self.index(x + offset[0], y + offset[1], z + offset[2]), resized.index(x, y, z)) else {
+                        continue;
+                    };
+                    resized.blocks[new] = self.blocks[old];
+                    resized.extra_blocks[new] = self.extra_blocks[old];
+                    if let Some(data) = self.block_entities.remove(&old) {
+                        resized.block_entities.insert(new, data);
+                    }
+                }
+            }
+        }
+        resized.entities = std::mem::take(&mut self.entities).into_iter().filter(|entity| {
+            let position = entity.get("Pos").and_then(Tag::as_list).map(|pos| pos.iter().filter_map(Tag::as_f64).collect::<Vec<_>>());
+            match position {
+                Some(position) if position.len() == 3 => (0..3).all(|axis| {
+                    let start = resized.origin[axis] as f64;
+                    (start..start + resized.size[axis] as f64).contains(&position[axis])
+                }),
+                _ => true
+            }
+        }).collect();
+        *self = resized;
+        Ok(())
+    }
+
+    /// The number of blocks, or `None` for a size [`Structure::new`] would reject.
+    pub fn volume(&self) -> Option<usize> {
+        checked_volume(self.size)
+    }
+
+    /// Reads a structure from the root tag of a `.mcstructure` file.
+    pub fn from_nbt(root: &Tag) -> Result<Structure, NbtError> {
+        let size = position(root.get("size"), "size")?;
+        let origin = position(root.get("structure_world_origin"), "structure_world_origin").unwrap_or_default();
+        let structure = root.get("structure").ok_or_else(|| missing("structure"))?;
+        let default = structure.get("palette").and_then(|palette| palette.get("default"));
+
+        let palette = match default.and_then(|default| default.get("block_palette")) {
+            Some(palette) => palette.as_list().ok_or_else(|| missing("structure/palette/default/block_palette"))?
+                .iter().map(|block| {
+                    let name = block.get("name").and_then(Tag::as_str).ok_or_else(|| missing("block_palette/name"))?;
+                    Ok(BlockState {
+                        name: name.to_string(),
+                        states: block.get("states").and_then(Tag::as_compound).cloned().unwrap_or_default(),
+                        version: block.get("version").and_then(Tag::as_i32).unwrap_or_default()
+                    })
+                }).collect::<Result<Vec<_>, NbtError>>()?,
+            None => vec![]
+        };
+
+        let volume = checked_volume(size).ok_or(NbtError::InvalidSize(size))?;
+        let layers = structure.get("block_indices").and_then(Tag::as_list).ok_or_else(|| missing("structure/block_indices"))?;
+        // Layers must hold a block per position, so the size can't make us allocate more than the
+        // file itself decoded to.
+        if layers.is_empty() && volume > 0 {
+            return Err(missing("structure/block_indices"));
+        }
+        let mut layers = layers.iter().map(|tag| layer(tag, volume, palette.len()));
+        let blocks = layers.next().transpose()?.unwrap_or_else(|| vec![None; volume]);
+        let extra_blocks = layers.next().transpose()?.unwrap_or_else(|| vec![None; volume]);
+
+        let mut block_entities = BTreeMap::new();
+        if let Some(data) = default.and_then(|default| default.get("block_position_data")).and_then(Tag::as_compound) {
+            for (index, data) in data {
+                let (Ok(index), Some(entity)) = (index.parse::<usize>(), data.get("block_entity_data").and_then(Tag::as_compound)) else {
+                    continue;
+                };
+                block_entities.insert(index, entity.clone());
+            }
+        }
+
+        let entities = structure.get("entities").and_then(Tag::as_list).into_iter().flatten()
+            .filter_map(|entity| entity.as_compound().cloned())
+            .collect();
+
+        Ok(Structure { size, origin, palette, blocks, extra_blocks, block_entities, entities })
+    }
+
+    /// The root tag of a `.mcstructure` file holding this structure.
+    pub fn to_nbt(&self) -> Tag {
+        let mut position_data = Compound::new();
+        for (index, data) in &self.block_entities {
+            let mut entry = Compound::new();
+            entry.insert("block_entity_data".to_string(), Tag::Compound(data.clone()));
+            position_data.insert(index.to_string(), Tag::Compound(entry));
+        }
+        let mut default = Compound::new();
+        default.insert("block_palette".to_string(), Tag::List(self.palette.iter().map(BlockState::to_nbt).collect()));
+        default.insert("block_position_data".to_string(), Tag::Compound(position_data));
+        let mut palette = Compound::new();
+        palette.insert("default".to_string(), Tag::Compound(default));
+
+        let mut structure = Compound::new();
+        structure.insert("block_indices".to_string(), Tag::List(vec![layer_to_nbt(&self.blocks), layer_to_nbt(&self.extra_blocks)]));
+        structure.insert("entities".to_string(), Tag::List(self.entities.iter().cloned().map(Tag::Compound).collect()));
+        structure.insert("palette".to_string(), Tag::Compound(palette));
+
+        let mut root = Compound::new();
+        root.insert("format_version".to_string(), Tag::Int(1));
+        root.insert("size".to_string(), int_list(self.size));
+        root.insert("structure".to_string(), Tag::Compound(structure));
+        root.insert("structure_world_origin".to_string(), int_list(self.origin));
+        Tag::Compound(root)
+    }
+}
+
+pub fn deserialize_structure_from_bytes(bytes: &[u8]) -> Result<Structure, NbtError> {
+    let (_, root) = read_nbt(bytes)?;
+    Structure::from_nbt(&root)
+}
+
+pub fn deserialize_structure_from_path(path: impl AsRef<Path>) -> AddonResult<Structure> {
+    let path = path.as_ref();
+    let bytes = fs::read(path).map_err(|error| AddonError::io(path, error))?;
+    deserialize_structure_from_bytes(&bytes).map_err(|error| AddonError::semantic(path, error.to_string()))
+}
+
+pub fn serialize_structure_to_bytes(structure: &Structure) -> Result<Vec<u8>, NbtError> {
+    write_nbt("", &structure.to_nbt())
+}
+
+pub fn write_structure_to_path(structure: &Structure, path: impl AsRef<Path>) -> AddonResult<()> {
+    let path = path.as_ref();
+    let bytes = serialize_structure_to_bytes(structure).map_err(|error| AddonError::semantic(path, error.to_string()))?;
+    fs::write(path, bytes).map_err(|error| AddonError::io(path, error))
+}
@@ -0,0 +1,38 @@
+use std::fs;
+use std::path::Path;
+use serde_json::Value;
+use crate::error::{AddonError, AddonResult};
+use crate::files::pack_files;
+use crate::utils::to_canonical_json_string;
+
+/// The image formats textures are read from.
+const IMAGE_EXTENSIONS: [&str; 4] = ["png", "tga", "jpg", "jpeg"];
+
+/// Whether `path` is in an image format textures are read from.
+pub(crate) fn is_image(path: &Path) -> bool {
+    path.extension().and_then(|ext| ext.to_str()).is_some_and(|ext| IMAGE_EXTENSIONS.contains(&ext.to_ascii_lowercase().as_str()))
+}
+
+/// Every image below `textures/` in `resource_pack`, as the pack references textures: relative to
+/// the pack root, `/`-separated and without extension.
+pub fn list_textures(resource_pack: &Path) -> Vec<String> {
+    let mut textures: Vec<String> = pack_files(resource_pack, "textures").into_iter()
+        .filter(|path| is_image(path))
+        .filter_map(|path| Some(path.with_extension("").strip_prefix(resource_pack).ok()?.to_string_lossy().replace('\\', "/")))
+        .collect();
+    textures.dedup();
+    textures
+}
+
+/// Writes `textures/textures_list.json`, which the game preloads textures from, listing every
+/// texture of `resource_pack`. Returns the textures listed.
+pub fn write_textures_list(resource_pack: &Path) -> AddonResult<Vec<String>> {
+    let textures = list_textures(resource_pack);
+    let path = resource_pack.join("textures/textures_list.json");
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(|error| AddonError::io(parent, error))?;
+    }
+    let list = Value::Array(textures.iter().cloned().map(Value::String).collect());
+    fs::write(&path, to_canonical_json_string(&list)).map_err(|error| AddonError::io(&path, error))?;
+    Ok(textures)
+}
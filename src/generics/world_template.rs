@@ -0,0 +1,168 @@
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use uuid::Uuid;
+use crate::error::{read_to_string, AddonError, AddonResult};
+use crate::generics::level::{deserialize_level_dat_from_path, serialize_level_dat_to_bytes, LevelDat};
+use crate::generics::manifest::{deserialize_manifest_from_path, version_value};
+use crate::generics::mcpack::{archive_error, McpackWriter};
+use crate::utils::{from_json_str, to_canonical_json_string, SemVer};
+
+/// A player allowed to join an Education Edition world, an entry of its `allowlist.json`.
+#[derive(Serialize, Deserialize, Clone, Debug, Eq, PartialEq)]
+pub struct AllowlistEntry {
+    pub name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub xuid: Option<String>,
+    /// Whether the player can join when the world is full.
+    #[serde(rename = "ignoresPlayerLimit", default)]
+    pub ignores_player_limit: bool
+}
+
+/// The Education Edition settings of a world. The flags live in `level.dat`, the allowlist next
+/// to it.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct EducationSettings {
+    /// `educationFeaturesEnabled`, which chemistry and the other education content need.
+    pub features_enabled: bool,
+    /// `codebuilder`, whether players can open Code Builder.
+    pub code_builder: bool,
+    /// `immutableWorld`, whether players can't change the world.
+    pub immutable_world: bool,
+    /// Who may join; anyone may when it is empty.
+    pub allowlist: Vec<AllowlistEntry>
+}
+
+impl EducationSettings {
+    /// Reads the flags from `level`, leaving the allowlist empty.
+    pub fn from_level(level: &LevelDat) -> EducationSettings {
+        EducationSettings {
+            features_enabled: level.flag("educationFeaturesEnabled").unwrap_or(false),
+            code_builder: level.flag("codebuilder").unwrap_or(false),
+            immutable_world: level.flag("immutableWorld").unwrap_or(false),
+            allowlist: vec![]
+        }
+    }
+
+    /// Writes the flags into `level`.
+    pub fn apply(&self, level: &mut LevelDat) {
+        level.set_flag("educationFeaturesEnabled", self.features_enabled);
+        level.set_flag("codebuilder", self.code_builder);
+        level.set_flag("immutableWorld", self.immutable_world);
+    }
+}
+
+/// Reads the education settings of the world folder `world` from its `level.dat` and, when it
+/// has one, `allowlist.json`.
+pub fn deserialize_education_settings_from_world(world: &Path) -> AddonResult<EducationSettings> {
+    let mut settings = EducationSettings::from_level(&deserialize_level_dat_from_path(world.join("level.dat"))?);
+    let allowlist = world.join("allowlist.json");
+    if allowlist.is_file() {
+        settings.allowlist = from_json_str(&read_to_string(&allowlist)?).map_err(|error| AddonError::json(&allowlist, error))?;
+    }
+    Ok(settings)
+}
+
+/// A world template to package as a `.mctemplate`, with the packs it embeds.
+#[derive(Clone, Debug)]
+pub struct WorldTemplate {
+    pub name: String,
+    pub description: String,
+    /// The header uuid of the template's manifest.
+    pub uuid: Uuid,
+    /// The uuid of its `world_template` module.
+    pub module_uuid: Uuid,
+    pub version: SemVer,
+    /// The game version worlds created from the template behave like.
+    pub base_game_version: SemVer,
+    /// Whether players can't change the world settings when creating a world from the template.
+    pub lock_template_options: bool,
+    /// The world's settings. Its level name is replaced by [`name`](WorldTemplate::name).
+    pub level: LevelDat,
+    /// The contents of `world_icon.jpeg`.
+    pub icon: Option<Vec<u8>>,
+    /// Pack folders to embed and enable, each needing a readable manifest.
+    pub behavior_packs: Vec<PathBuf>,
+    pub resource_packs: Vec<PathBuf>,
+    /// Written over the flags of [`level`](WorldTemplate::level), with an `allowlist.json` when
+    /// the allowlist isn't empty.
+    pub education: Option<EducationSettings>
+}
+
+impl WorldTemplate {
+    pub fn new(name: &str, uuid: Uuid, module_uuid: Uuid) -> WorldTemplate {
+        WorldTemplate {
+            name: name.to_string(), description: String::new(), uuid, module_uuid, version: SemVer::new(1, 0, 0),
+            base_game_version: SemVer::new(1, 20, 0), lock_template_options: false, level: LevelDat::new(name), icon: None,
+            behavior_packs: vec![], resource_packs: vec![], education: None
+        }
+    }
+
+    pub fn manifest(&self) -> Value {
+        json!({
+            "format_version": 2,
+            "header": {
+                "name": self.name,
+                "description": self.description,
+                "uuid": self.uuid.to_string(),
+                "version": version_value(&self.version),
+                "lock_template_options": self.lock_template_options,
+                "base_game_version": version_value(&self.base_game_version)
+            },
+            "modules": [{ "type": "world_template", "uuid": self.module_uuid.to_string(), "version": version_value(&self.version) }]
+        })
+    }
+}
+
+/// The `world_behavior_packs.json`/`world_resource_packs.json` entries enabling `packs`.
+fn pack_references(packs: &[PathBuf]) -> AddonResult<Value> {
+    let mut references = vec![];
+    for pack in packs {
+        let manifest = deserialize_manifest_from_path(&pack.join("manifest.json"))?;
+        references.push(json!({ "pack_id": manifest.header.uuid.to_string(), "version": version_value(&manifest.header.version) }));
+    }
+    Ok(Value::Array(references))
+}
+
+fn folder_name(pack: &Path) -> AddonResult<String> {
+    pack.file_name().map(|name| name.to_string_lossy().to_string())
+        .ok_or_else(|| AddonError::semantic(pack, "pack folder has no name"))
+}
+
+/// Packages `template` into `writer` as a `.mctemplate`: its manifest, `level.dat`,
+/// `levelname.txt`, icon, allowlist, and the embedded packs with the files enabling them.
+pub fn write_mctemplate<W: Write>(template: &WorldTemplate, writer: W) -> AddonResult<W> {
+    let root = Path::new(&template.name);
+    let mut level = template.level.clone();
+    level.set_level_name(&template.name);
+    if let Some(education) = &template.education {
+        education.apply(&mut level);
+    }
+    let level = serialize_level_dat_to_bytes(&level).map_err(|error| AddonError::semantic("level.dat", error.to_string()))?;
+
+    let mut files: Vec<(&str, Vec<u8>)> = vec![
+        ("manifest.json", to_canonical_json_string(&template.manifest()).into_bytes()),
+        ("level.dat", level),
+        ("levelname.txt", template.name.clone().into_bytes()),
+        ("world_behavior_packs.json", to_canonical_json_string(&pack_references(&template.behavior_packs)?).into_bytes()),
+        ("world_resource_packs.json", to_canonical_json_string(&pack_references(&template.resource_packs)?).into_bytes())
+    ];
+    if let Some(icon) = &template.icon {
+        files.push(("world_icon.jpeg", icon.clone()));
+    }
+    if let Some(education) = template.education.as_ref().filter(|education| !education.allowlist.is_empty()) {
+        files.push(("allowlist.json", to_canonical_json_string(&json!(education.allowlist)).into_bytes()));
+    }
+
+    let mut archive = McpackWriter::new(writer);
+    for (name, bytes) in files {
+        archive.add_file(name, &mut bytes.as_slice()).map_err(|error| archive_error(&root.join(name), error))?;
+    }
+    for (folder, packs) in [("behavior_packs", &template.behavior_packs), ("resource_packs", &template.resource_packs)] {
+        for pack in packs {
+            archive.add_directory_under(pack, &format!("{}/{}/", folder, folder_name(pack)?), &|_, _, _| {})?;
+        }
+    }
+    archive.finish().map_err(|error| archive_error(root, error))
+}
@@ -0,0 +1,168 @@
+use serde_json::{json, Map, Value};
+use crate::diagnostics::{Diagnostic, Diagnostics, Severity};
+use crate::generics::loot_table::LootTable;
+use crate::utils::json_pointer;
+
+/// Java functions Bedrock has under the same name and with the same fields.
+const SAME_FUNCTIONS: [&str; 4] = ["set_damage", "enchant_with_levels", "furnace_smelt", "explosion_decay"];
+/// Java conditions Bedrock has under the same name and with the same fields.
+const SAME_CONDITIONS: [&str; 3] = ["random_chance", "random_chance_with_looting", "killed_by_player"];
+
+pub(crate) fn unsupported(diagnostics: &mut Diagnostics, pointer: String, message: String) {
+    let diagnostic = Diagnostic::new(Severity::Warning, "unsupported-java-feature", message);
+    diagnostics.push(Diagnostic { pointer: Some(pointer), ..diagnostic });
+}
+
+fn strip_namespace(id: &str) -> &str {
+    id.trim_start_matches("minecraft:")
+}
+
+/// A Java number provider as a Bedrock number or `{ "min", "max" }` range, if it's constant or
+/// uniform.
+fn number(value: &Value) -> Option<Value> {
+    match value {
+        Value::Number(_) => Some(value.clone()),
+        Value::Object(map) => match map.get("type").and_then(Value::as_str).map(strip_namespace) {
+            Some("constant") => map.get("value").cloned(),
+            Some("uniform") | None => Some(json!({ "min": map.get("min")?, "max": map.get("max")? })),
+            _ => None
+        },
+        _ => None
+    }
+}
+
+fn condition(java: &Value, pointer: &[&str], diagnostics: &mut Diagnostics) -> Option<Value> {
+    let name = strip_namespace(java.get("condition").and_then(Value::as_str).unwrap_or_default());
+    if !SAME_CONDITIONS.contains(&name) {
+        unsupported(diagnostics, json_pointer(pointer), format!("condition \"{}\" isn't converted and was dropped", name));
+        return None;
+    }
+    let mut condition = java.as_object().cloned().unwrap_or_default();
+    condition.insert("condition".to_string(), json!(name));
+    Some(Value::Object(condition))
+}
+
+fn conditions(java: &Value, pointer: &[&str], diagnostics: &mut Diagnostics) -> Vec<Value> {
+    java.get("conditions").and_then(Value::as_array).into_iter().flatten().enumerate()
+        .filter_map(|(index, java)| condition(java, &[pointer, &["conditions", &index.to_string()]].concat(), diagnostics))
+        .collect()
+}
+
+fn function(java: &Value, pointer: &[&str], diagnostics: &mut Diagnostics) -> Option<Value> {
+    let name = strip_namespace(java.get("function").and_then(Value::as_str).unwrap_or_default());
+    let mut function = Map::new();
+    function.insert("function".to_string(), json!(name));
+    match name {
+        "set_count" | "looting_enchant" => {
+            let Some(count) = java.get("count").and_then(number) else {
+                unsupported(diagnostics, json_pointer(&[pointer, &["count"]].concat()), format!("the count of \"{}\" isn't a constant or uniform number", name));
+                return None;
+            };
+            function.insert("count".to_string(), count);
+        }
+        "enchant_randomly" => {
+            if java.get("options").or_else(|| java.get("enchantments")).is_some() {
+                unsupported(diagnostics, json_pointer(pointer), "enchant_randomly with a list of enchantments isn't converted".to_string());
+            }
+        }
+        name if SAME_FUNCTIONS.contains(&name) => {
+            for (key, value) in java.as_object().into_iter().flatten().filter(|(key, _)| !matches!(key.as_str(), "function" | "conditions")) {
+                function.insert(key.clone(), number(value).unwrap_or_else(|| value.clone()));
+            }
+        }
+        _ => {
+            unsupported(diagnostics, json_pointer(pointer), format!("function \"{}\" isn't converted and was dropped", name));
+            return None;
+        }
+    }
+    let conditions = conditions(java, pointer, diagnostics);
+    if !conditions.is_empty() {
+        function.insert("conditions".to_string(), Value::Array(conditions));
+    }
+    Some(Value::Object(function))
+}
+
+fn entry(java: &Value, pointer: &[&str], diagnostics: &mut Diagnostics) -> Option<Value> {
+    let kind = strip_namespace(java.get("type").and_then(Value::as_str).unwrap_or_default());
+    let mut entry = Map::new();
+    match kind {
+        "item" => {
+            entry.insert("type".to_string(), json!("item"));
+            entry.insert("name".to_string(), java.get("name").cloned().unwrap_or_default());
+        }
+        "loot_table" => {
+            // Newer Java versions call the reference `value` and allow inline tables there.
+            let Some(reference) = java.get("name").or_else(|| java.get("value")).and_then(Value::as_str) else {
+                unsupported(diagnostics, json_pointer(pointer), "inline loot tables aren't converted".to_string());
+                return None;
+            };
+            let path = reference.split_once(':').map(|(_, path)| path).unwrap_or(reference);
+            entry.insert("type".to_string(), json!("loot_table"));
+            entry.insert("name".to_string(), json!(format!("loot_tables/{}.json", path)));
+        }
+        "empty" => {
+            entry.insert("type".to_string(), json!("empty"));
+        }
+        _ => {
+            unsupported(diagnostics, json_pointer(pointer), format!("entries of type \"{}\" aren't converted", kind));
+            return None;
+        }
+    }
+    if let Some(weight) = java.get("weight") {
+        entry.insert("weight".to_string(), weight.clone());
+    }
+    if java.get("quality").is_some() {
+        unsupported(diagnostics, json_pointer(&[pointer, &["quality"]].concat()), "entry quality has no Bedrock equivalent".to_string());
+    }
+    let functions: Vec<Value> = java.get("functions").and_then(Value::as_array).into_iter().flatten().enumerate()
+        .filter_map(|(index, java)| function(java, &[pointer, &["functions", &index.to_string()]].concat(), diagnostics))
+        .collect();
+    if !functions.is_empty() {
+        entry.insert("functions".to_string(), Value::Array(functions));
+    }
+    let conditions = conditions(java, pointer, diagnostics);
+    if !conditions.is_empty() {
+        entry.insert("conditions".to_string(), Value::Array(conditions));
+    }
+    Some(Value::Object(entry))
+}
+
+/// Converts a Java Edition loot table as far as Bedrock supports it. Entries, functions and
+/// conditions without a Bedrock equivalent are dropped, each with a warning at its JSON pointer
+/// in the Java table.
+pub fn convert_java_loot_table(java: &Value) -> (LootTable, Diagnostics) {
+    let mut diagnostics = Diagnostics::new();
+    let mut pools = vec![];
+    for (index, java_pool) in java.get("pools").and_then(Value::as_array).into_iter().flatten().enumerate() {
+        let index = index.to_string();
+        let pointer = ["pools", index.as_str()];
+        let rolls = java_pool.get("rolls").map(|rolls| match number(rolls) {
+            Some(rolls) => rolls,
+            None => {
+                unsupported(&mut diagnostics, json_pointer(&["pools", &index, "rolls"]), "rolls aren't a constant or uniform number, using 1".to_string());
+                json!(1)
+            }
+        }).unwrap_or(json!(1));
+        if java_pool.get("bonus_rolls").is_some() {
+            unsupported(&mut diagnostics, json_pointer(&["pools", &index, "bonus_rolls"]), "bonus rolls have no Bedrock equivalent".to_string());
+        }
+        if java_pool.get("functions").is_some() {
+            unsupported(&mut diagnostics, json_pointer(&["pools", &index, "functions"]), "pool functions have no Bedrock equivalent".to_string());
+        }
+
+        let entries: Vec<Value> = java_pool.get("entries").and_then(Value::as_array).into_iter().flatten().enumerate()
+            .filter_map(|(entry_index, java)| entry(java, &["pools", &index, "entries", &entry_index.to_string()], &mut diagnostics))
+            .collect();
+        let mut pool = json!({ "rolls": rolls, "entries": entries });
+        let conditions = conditions(java_pool, &pointer, &mut diagnostics);
+        if !conditions.is_empty() {
+            pool["conditions"] = Value::Array(conditions);
+        }
+        pools.push(pool);
+    }
+
+    let raw = json!({ "pools": pools });
+    let mut table: LootTable = serde_json::from_value(raw.clone()).unwrap_or_else(|_| LootTable { pools: vec![], raw: Value::Null });
+    table.raw = raw;
+    (table, diagnostics)
+}
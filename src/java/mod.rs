@@ -0,0 +1,24 @@
+//! Converters from Java Edition content to the Bedrock formats of this crate.
+
+use std::path::{Path, PathBuf};
+
+#[cfg(feature = "behavior")]
+pub mod loot_table;
+#[cfg(feature = "behavior")]
+pub mod recipe;
+pub mod resource_pack;
+
+/// What a conversion produced and what it had to leave out.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct ConversionReport {
+    /// Files written, relative to the output pack.
+    pub converted: Vec<PathBuf>,
+    /// Java files that weren't converted, relative to the Java pack, with the reason.
+    pub unsupported: Vec<(PathBuf, String)>
+}
+
+impl ConversionReport {
+    pub(crate) fn skip(&mut self, path: &Path, reason: &str) {
+        self.unsupported.push((path.to_path_buf(), reason.to_string()));
+    }
+}
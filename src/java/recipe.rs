@@ -0,0 +1,104 @@
+use serde_json::Value;
+use crate::diagnostics::Diagnostics;
+use crate::generics::builders::RecipeBuilder;
+use crate::java::loot_table::unsupported;
+use crate::utils::json_pointer;
+
+/// The item of a Java ingredient: `"minecraft:stick"`, `{ "item": ... }`, or the first of a list
+/// of alternatives. Tags are reported and yield `None`.
+fn ingredient(java: &Value, pointer: &[&str], diagnostics: &mut Diagnostics) -> Option<String> {
+    match java {
+        Value::String(item) if item.starts_with('#') => {
+            unsupported(diagnostics, json_pointer(pointer), format!("tag ingredient {} isn't converted", item));
+            None
+        }
+        Value::String(item) => Some(item.clone()),
+        Value::Object(map) => {
+            if let Some(item) = map.get("item").and_then(Value::as_str) {
+                return Some(item.to_string());
+            }
+            let tag = map.get("tag").and_then(Value::as_str).unwrap_or_default();
+            unsupported(diagnostics, json_pointer(pointer), format!("tag ingredient #{} isn't converted", tag));
+            None
+        }
+        Value::Array(alternatives) => {
+            if alternatives.len() > 1 {
+                unsupported(diagnostics, json_pointer(pointer), "only the first of several alternative ingredients is kept".to_string());
+            }
+            ingredient(alternatives.first()?, &[pointer, &["0"]].concat(), diagnostics)
+        }
+        _ => None
+    }
+}
+
+/// The item and count of a Java recipe result, which is an id or `{ "item"/"id", "count" }`.
+fn result(java: &Value) -> Option<(String, u32)> {
+    match java {
+        Value::String(item) => Some((item.clone(), 1)),
+        Value::Object(map) => {
+            let item = map.get("id").or_else(|| map.get("item"))?.as_str()?;
+            let count = map.get("count").and_then(Value::as_u64).unwrap_or(1);
+            Some((item.to_string(), count as u32))
+        }
+        _ => None
+    }
+}
+
+/// Converts a Java Edition crafting, cooking or stonecutting recipe into a Bedrock recipe named
+/// `identifier`. Recipe types without a Bedrock equivalent, like smithing and the special
+/// crafting recipes, yield `None`; either way the diagnostics say what was lost.
+pub fn convert_java_recipe(identifier: &str, java: &Value) -> (Option<Value>, Diagnostics) {
+    let mut diagnostics = Diagnostics::new();
+    let kind = java.get("type").and_then(Value::as_str).unwrap_or_default().trim_start_matches("minecraft:");
+    let Some((output, count)) = java.get("result").and_then(result) else {
+        unsupported(&mut diagnostics, json_pointer(&["type"]), format!("recipes of type \"{}\" aren't converted", kind));
+        return (None, diagnostics);
+    };
+
+    let builder = match kind {
+        "crafting_shaped" => {
+            let pattern: Vec<&str> = java.get("pattern").and_then(Value::as_array).into_iter().flatten().filter_map(Value::as_str).collect();
+            let mut builder = RecipeBuilder::shaped(identifier, &pattern, &output);
+            for (symbol, java) in java.get("key").and_then(Value::as_object).into_iter().flatten() {
+                let (Some(symbol), Some(item)) = (symbol.chars().next(), ingredient(java, &["key", symbol], &mut diagnostics)) else {
+                    continue;
+                };
+                builder = builder.key(symbol, &item);
+            }
+            builder.result_count(count)
+        }
+        "crafting_shapeless" | "stonecutting" => {
+            let sources: Vec<&Value> = match kind {
+                "stonecutting" => java.get("ingredient").into_iter().collect(),
+                _ => java.get("ingredients").and_then(Value::as_array).into_iter().flatten().collect()
+            };
+            let field = if kind == "stonecutting" { "ingredient" } else { "ingredients" };
+            let ingredients: Vec<String> = sources.into_iter().enumerate()
+                .filter_map(|(index, java)| ingredient(java, &[field, &index.to_string()], &mut diagnostics))
+                .collect();
+            let ingredients: Vec<&str> = ingredients.iter().map(String::as_str).collect();
+            let builder = RecipeBuilder::shapeless(identifier, &ingredients, &output).result_count(count);
+            if kind == "stonecutting" { builder.tags(&["stonecutter"]) } else { builder }
+        }
+        "smelting" | "blasting" | "smoking" | "campfire_cooking" => {
+            let Some(input) = java.get("ingredient").and_then(|java| ingredient(java, &["ingredient"], &mut diagnostics)) else {
+                return (None, diagnostics);
+            };
+            for key in ["experience", "cookingtime"].into_iter().filter(|key| java.get(key).is_some()) {
+                unsupported(&mut diagnostics, json_pointer(&[key]), format!("\"{}\" has no Bedrock equivalent", key));
+            }
+            let tags: &[&str] = match kind {
+                "smelting" => &["furnace"],
+                "blasting" => &["blast_furnace"],
+                "smoking" => &["smoker"],
+                _ => &["campfire", "soul_campfire"]
+            };
+            RecipeBuilder::furnace(identifier, &input, &output).tags(tags)
+        }
+        _ => {
+            unsupported(&mut diagnostics, json_pointer(&["type"]), format!("recipes of type \"{}\" aren't converted", kind));
+            return (None, diagnostics);
+        }
+    };
+    (Some(builder.build()), diagnostics)
+}
@@ -0,0 +1,266 @@
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::path::{Path, PathBuf};
+use once_cell::sync::Lazy;
+use serde_json::{json, Map, Value};
+use uuid::Uuid;
+use crate::error::{read_to_string, AddonError, AddonResult};
+use crate::files::pack_files;
+use crate::generics::manifest::{serialize_manifest_to_string, Manifest, ManifestHeader, ManifestModule};
+use crate::java::ConversionReport;
+use crate::utils::{to_canonical_json_string, SemVer};
+
+/// Java texture folders and the Bedrock folders under `textures/` they map to. Folders missing
+/// here, like `gui` and `font`, have no Bedrock equivalent with the same layout.
+pub static TEXTURE_FOLDERS: Lazy<HashMap<&str, &str>> = Lazy::new(||
+    HashMap::from(
+        [
+            ("block", "blocks"),
+            ("blocks", "blocks"),
+            ("item", "items"),
+            ("items", "items"),
+            ("entity", "entity"),
+            ("environment", "environment"),
+            ("painting", "painting"),
+            ("particle", "particle"),
+            ("misc", "misc"),
+            ("colormap", "colormap"),
+            ("models", "models"),
+        ]
+    )
+);
+
+/// Vanilla textures whose Bedrock name differs, by Java path.
+pub static TEXTURE_NAMES: Lazy<HashMap<&str, &str>> = Lazy::new(||
+    HashMap::from(
+        [
+            ("block/grass_block_top", "blocks/grass_top"),
+            ("block/grass_block_side", "blocks/grass_side_carried"),
+            ("block/oak_planks", "blocks/planks_oak"),
+            ("block/spruce_planks", "blocks/planks_spruce"),
+            ("block/birch_planks", "blocks/planks_birch"),
+            ("block/oak_log", "blocks/log_oak"),
+            ("block/oak_log_top", "blocks/log_oak_top"),
+            ("block/stone_bricks", "blocks/stonebrick"),
+            ("block/mossy_cobblestone", "blocks/cobblestone_mossy"),
+            ("block/dirt_path_top", "blocks/grass_path_top"),
+            ("block/water_still", "blocks/water_still_grey"),
+            ("block/lava_still", "blocks/lava_still"),
+            ("item/golden_apple", "items/apple_golden"),
+            ("item/cooked_beef", "items/beef_cooked"),
+            ("item/wooden_sword", "items/wood_sword"),
+            ("item/golden_sword", "items/gold_sword"),
+        ]
+    )
+);
+
+/// Splits a Java resource location like `ruby:item/ruby` into namespace and path.
+fn resource_location(location: &str) -> (&str, &str) {
+    location.split_once(':').unwrap_or(("minecraft", location))
+}
+
+/// The Bedrock texture path, without extension, of the Java texture `path` in `namespace`.
+/// Textures of other namespaces get a folder of their own so they can't clash with vanilla.
+fn texture_path(namespace: &str, path: &str) -> Option<String> {
+    if namespace == "minecraft" {
+        if let Some(renamed) = TEXTURE_NAMES.get(path) {
+            return Some(format!("textures/{}", renamed));
+        }
+    }
+    let (folder, rest) = path.split_once('/')?;
+    let folder = TEXTURE_FOLDERS.get(folder)?;
+    if namespace == "minecraft" {
+        Some(format!("textures/{}/{}", folder, rest))
+    } else {
+        Some(format!("textures/{}/{}/{}", folder, namespace, rest))
+    }
+}
+
+/// The short name a texture gets in `item_texture.json` and `terrain_texture.json`.
+fn short_name(namespace: &str, name: &str) -> String {
+    if namespace == "minecraft" {
+        name.to_string()
+    } else {
+        format!("{}_{}", namespace, name)
+    }
+}
+
+/// Java language codes are lowercase, Bedrock's have an uppercase region, e.g. `en_US`.
+fn language_code(code: &str) -> String {
+    match code.split_once('_') {
+        Some((language, region)) => format!("{}_{}", language.to_lowercase(), region.to_uppercase()),
+        None => code.to_string()
+    }
+}
+
+/// The atlases and files converted so far.
+#[derive(Default)]
+struct Converted {
+    item_textures: Map<String, Value>,
+    terrain_textures: Map<String, Value>,
+    blocks: Map<String, Value>,
+    languages: BTreeMap<String, Vec<String>>
+}
+
+impl Converted {
+    /// Converts an `item/generated`, `item/handheld`, `block/cube_all` or `block/cube_column`
+    /// model, or says why it couldn't.
+    fn model(&mut self, namespace: &str, kind: &str, name: &str, model: &Value) -> Result<(), String> {
+        let parent = model.get("parent").and_then(Value::as_str).map(|parent| resource_location(parent).1).unwrap_or_default();
+        let texture = |key: &str| -> Result<String, String> {
+            let location = model.pointer(&format!("/textures/{}", key)).and_then(Value::as_str)
+                .ok_or_else(|| format!("the model has no \"{}\" texture", key))?;
+            if location.starts_with('#') {
+                return Err(format!("texture variable {} isn't resolved", location));
+            }
+            let (namespace, path) = resource_location(location);
+            texture_path(namespace, path).ok_or_else(|| format!("texture {} has no Bedrock folder", location))
+        };
+
+        match (kind, parent) {
+            ("item", "item/generated" | "item/handheld") => {
+                let texture = texture("layer0")?;
+                self.item_textures.insert(short_name(namespace, name), json!({ "textures": texture }));
+            }
+            ("block", "block/cube_all") => {
+                let texture = texture("all")?;
+                let short = short_name(namespace, name);
+                self.terrain_textures.insert(short.clone(), json!({ "textures": texture }));
+                self.blocks.insert(format!("{}:{}", namespace, name), json!({ "textures": short }));
+            }
+            ("block", "block/cube_column") => {
+                let (end, side) = (texture("end")?, texture("side")?);
+                let short = short_name(namespace, name);
+                self.terrain_textures.insert(format!("{}_end", short), json!({ "textures": end }));
+                self.terrain_textures.insert(format!("{}_side", short), json!({ "textures": side }));
+                let textures = json!({ "up": format!("{}_end", short), "down": format!("{}_end", short), "side": format!("{}_side", short) });
+                self.blocks.insert(format!("{}:{}", namespace, name), json!({ "textures": textures }));
+            }
+            _ => return Err(format!("models with parent \"{}\" aren't converted", parent))
+        }
+        Ok(())
+    }
+}
+
+fn write_file(output: &Path, path: &str, contents: &[u8], report: &mut ConversionReport) -> AddonResult<()> {
+    let target = output.join(path);
+    if let Some(parent) = target.parent() {
+        fs::create_dir_all(parent).map_err(|error| AddonError::io(parent, error))?;
+    }
+    fs::write(&target, contents).map_err(|error| AddonError::io(&target, error))?;
+    report.converted.push(PathBuf::from(path));
+    Ok(())
+}
+
+/// Converts the Java Edition resource pack at `java` into a Bedrock resource pack at `output`:
+/// textures move to their Bedrock paths, JSON lang files become `.lang` files, and simple item and
+/// block models become `item_texture.json`, `terrain_texture.json` and `blocks.json` entries. The
+/// report lists the files written and every Java file left behind with the reason.
+pub fn convert_java_resource_pack(java: &Path, output: &Path, uuid: Uuid, module_uuid: Uuid) -> AddonResult<ConversionReport> {
+    let mut report = ConversionReport::default();
+    let mut converted = Converted::default();
+
+    let mcmeta_path = java.join("pack.mcmeta");
+    let mcmeta: Value = serde_json::from_str(&read_to_string(&mcmeta_path)?).map_err(|error| AddonError::json(&mcmeta_path, error))?;
+    let description = match mcmeta.pointer("/pack/description") {
+        Some(Value::String(description)) => description.clone(),
+        Some(component) => component.get("text").and_then(Value::as_str).unwrap_or_default().to_string(),
+        None => String::new()
+    };
+    let name = java.file_name().map(|name| name.to_string_lossy().to_string()).unwrap_or_default();
+    let manifest = Manifest {
+        header: ManifestHeader {
+            uuid, name: name.clone(), description, min_engine_version: SemVer::new(1, 20, 0), version: SemVer::new(1, 0, 0)
+        },
+        modules: vec![ManifestModule::Resources(module_uuid, SemVer::new(1, 0, 0))],
+        dependencies: vec![], subpacks: vec![], capabilities: vec![], raw: Value::Null
+    };
+    write_file(output, "manifest.json", serialize_manifest_to_string(&manifest).as_bytes(), &mut report)?;
+
+    for path in pack_files(java, "") {
+        let relative = path.strip_prefix(java).unwrap_or(&path).to_path_buf();
+        let name = relative.to_string_lossy().replace('\\', "/");
+
+        if name == "pack.mcmeta" {
+            continue;
+        }
+        if name == "pack.png" {
+            let bytes = fs::read(&path).map_err(|error| AddonError::io(&path, error))?;
+            write_file(output, "pack_icon.png", &bytes, &mut report)?;
+            continue;
+        }
+        let parts: Vec<&str> = name.splitn(4, '/').collect();
+        let (namespace, folder, rest) = match parts[..] {
+            ["assets", namespace, folder, rest] => (namespace, folder, rest),
+            ["assets", _, "sounds.json"] => {
+                report.skip(&relative, "sounds aren't converted");
+                continue;
+            }
+            _ => {
+                report.skip(&relative, "only files under assets/ are converted");
+                continue;
+            }
+        };
+
+        match folder {
+            "textures" if rest.ends_with(".png") => {
+                let Some(target) = texture_path(namespace, rest.trim_end_matches(".png")) else {
+                    report.skip(&relative, "this texture folder has no Bedrock equivalent");
+                    continue;
+                };
+                let bytes = fs::read(&path).map_err(|error| AddonError::io(&path, error))?;
+                write_file(output, &format!("{}.png", target), &bytes, &mut report)?;
+            }
+            "textures" if rest.ends_with(".mcmeta") => report.skip(&relative, "texture animations aren't converted"),
+            "lang" if rest.ends_with(".json") => {
+                let src = read_to_string(&path)?;
+                let translations: BTreeMap<String, String> = serde_json::from_str(&src).map_err(|error| AddonError::json(&path, error))?;
+                let lines = converted.languages.entry(language_code(rest.trim_end_matches(".json"))).or_default();
+                lines.extend(translations.into_iter().map(|(key, value)| format!("{}={}", key, value.replace('\n', "~LINEBREAK~"))));
+            }
+            "lang" if rest.ends_with(".lang") => {
+                let lines = converted.languages.entry(language_code(rest.trim_end_matches(".lang"))).or_default();
+                lines.extend(read_to_string(&path)?.lines().filter(|line| !line.trim().is_empty()).map(str::to_string));
+            }
+            "models" => {
+                let Some((kind, model_name)) = rest.strip_suffix(".json").and_then(|rest| rest.split_once('/')) else {
+                    report.skip(&relative, "not an item or block model");
+                    continue;
+                };
+                let model: Value = serde_json::from_str(&read_to_string(&path)?).map_err(|error| AddonError::json(&path, error))?;
+                if let Err(reason) = converted.model(namespace, kind, model_name, &model) {
+                    report.skip(&relative, &reason);
+                }
+            }
+            "blockstates" => report.skip(&relative, "block states have no resource pack equivalent"),
+            "sounds" => report.skip(&relative, "sounds aren't converted"),
+            _ => report.skip(&relative, "this asset type isn't converted")
+        }
+    }
+
+    if !converted.item_textures.is_empty() {
+        let atlas = json!({ "resource_pack_name": name, "texture_name": "atlas.items", "texture_data": converted.item_textures });
+        write_file(output, "textures/item_texture.json", to_canonical_json_string(&atlas).as_bytes(), &mut report)?;
+    }
+    if !converted.terrain_textures.is_empty() {
+        let atlas = json!({
+            "resource_pack_name": name, "texture_name": "atlas.terrain", "padding": 8, "num_mip_levels": 4,
+            "texture_data": converted.terrain_textures
+        });
+        write_file(output, "textures/terrain_texture.json", to_canonical_json_string(&atlas).as_bytes(), &mut report)?;
+    }
+    if !converted.blocks.is_empty() {
+        let mut blocks = converted.blocks;
+        blocks.insert("format_version".to_string(), json!([1, 1, 0]));
+        write_file(output, "blocks.json", to_canonical_json_string(&Value::Object(blocks)).as_bytes(), &mut report)?;
+    }
+    if !converted.languages.is_empty() {
+        let codes: Vec<&String> = converted.languages.keys().collect();
+        write_file(output, "texts/languages.json", to_canonical_json_string(&json!(codes)).as_bytes(), &mut report)?;
+        for (code, lines) in &converted.languages {
+            write_file(output, &format!("texts/{}.lang", code), format!("{}\n", lines.join("\n")).as_bytes(), &mut report)?;
+        }
+    }
+
+    Ok(report)
+}
@@ -1,18 +1,2625 @@
+pub mod addon;
+#[cfg(feature = "authoring")]
+pub mod authoring;
+#[cfg(feature = "build")]
+pub mod build;
+#[cfg(feature = "content")]
+pub mod content;
+pub mod diagnostics;
+pub mod error;
+#[cfg(feature = "ffi")]
+pub mod ffi;
+pub mod files;
 pub mod generics;
+#[cfg(feature = "java")]
+pub mod java;
+#[cfg(feature = "behavior")]
+pub mod merge;
+#[cfg(feature = "migrate")]
+pub mod migrate;
+#[cfg(feature = "molang")]
+pub mod molang;
+#[cfg(feature = "nbt")]
+pub mod nbt;
+#[cfg(any(feature = "scaffold", feature = "textures"))]
+mod png;
+pub mod prelude;
+pub mod progress;
+#[cfg(feature = "behavior")]
+pub mod query;
+#[cfg(feature = "scaffold")]
+pub mod scaffold;
+pub mod serde_helpers;
+#[cfg(feature = "snapshot")]
+pub mod snapshot;
+#[cfg(feature = "textures")]
+pub mod textures;
+#[cfg(feature = "behavior")]
+pub mod typescript;
 pub mod utils;
+pub mod vanilla;
+#[cfg(feature = "validation")]
+pub mod validation;
+
+/// The `uuid` version the crate's types use, so dependents don't have to match it themselves.
+pub use uuid::{self, Uuid};
+#[cfg(feature = "derive")]
+pub use bedrockrs_addon_derive::{Block, Entity, Item};
+
+// Lets the derive macros' `::bedrockrs_addon` paths resolve inside the crate itself.
+#[cfg(feature = "derive")]
+extern crate self as bedrockrs_addon;
 
 #[cfg(test)]
 mod tests {
+    // Imports are gated by the subsystem they come from, tests by everything they use, so
+    // builds with only some features leave a few imports and helpers unused.
+    #![cfg_attr(not(feature = "full"), allow(unused_imports, dead_code))]
+
+    use std::borrow::Cow;
+    use std::collections::BTreeMap;
     use std::fs;
-    use crate::generics::manifest::{deserialize_manifest_from_str, Manifest};
-    use super::*;
+    use std::path::{Path, PathBuf};
+    use crate::Uuid;
+    use crate::addon::{remap_world_pack_lists, Addon, Occurrence, PackKind, Visit};
+    #[cfg(feature = "build")]
+    use crate::build::{build_pack, BuildProfile};
+    #[cfg(feature = "authoring")]
+    use crate::authoring::{export_authored_pack, parse_toml_from_str, TomlError};
+    #[cfg(feature = "content")]
+    use crate::content::{AddonProject, Block, Entity, Item, ItemDefinition};
+    #[cfg(feature = "textures")]
+    use crate::textures::{split_subpacks, AtlasBuilder, AtlasKind, AtlasTile, Flipbook, SubpackRule, SubpackSplit};
+    #[cfg(feature = "resource")]
+    use crate::generics::sound_definitions::{scan_sound_files, write_sound_definitions, SoundNaming, SoundScanOptions};
+    #[cfg(feature = "resource")]
+    use crate::generics::textures_list::{list_textures, write_textures_list};
+    #[cfg(feature = "resource")]
+    use crate::generics::lang::{content_lang_keys, parse_lang_from_str, title_case, write_missing_lang_keys};
+    #[cfg(feature = "migrate")]
+    use crate::migrate::{block_format, item_format, migrate_experimental_block, migrate_experimental_content, migrate_legacy_blocks, migrate_legacy_entities, migrate_legacy_items, BlockFormat, ItemFormat, MIGRATED_FORMAT_VERSION};
+    #[cfg(feature = "behavior")]
+    use crate::merge::{merge_behavior_packs, ConflictStrategy, MergeConflict};
+    use crate::diagnostics::{format_json, format_plain, Diagnostic, Diagnostics, Severity};
+    use crate::error::AddonError;
+    #[cfg(feature = "ffi")]
+    use crate::ffi::{bedrockrs_last_error, bedrockrs_manifest_parse, bedrockrs_mcpack_extract, bedrockrs_mcpack_extract_with_limits, bedrockrs_mcpack_write, bedrockrs_string_free, bedrockrs_validate_addon, catch_panic};
+    #[cfg(all(feature = "molang", feature = "resource"))]
+    use crate::generics::animation::{deserialize_animation_from_value, deserialize_animations_from_path, deserialize_channel_from_value, Interpolation, LoopMode};
+    #[cfg(feature = "behavior")]
+    use crate::generics::entity::{deserialize_behavior_entity_from_path, deserialize_behavior_entity_from_value, entity_layout, serialize_behavior_entity_to_value, BehaviorEntity, EntityLayout, ENTITY_FORMAT_VERSION};
+    #[cfg(feature = "behavior")]
+    use crate::generics::builders::{BlockBuilder, EntityBuilder, ItemBuilder, LootPoolBuilder, LootTableBuilder, RecipeBuilder};
+    #[cfg(feature = "resource")]
+    use crate::generics::client_entity::{deserialize_effect_bindings_from_value, serialize_effect_bindings_into};
+    #[cfg(all(feature = "molang", feature = "resource"))]
+    use crate::generics::client_entity::{deserialize_client_scripts_from_value, serialize_client_scripts_into};
+    #[cfg(feature = "manifest")]
+    use crate::generics::dependencies::{resolve_pack_dependencies, DependencyIssue};
+    #[cfg(feature = "resource")]
+    use crate::generics::geometry::{box_uv_faces, deserialize_geometry_from_path, deserialize_geometry_from_str, per_face_box_uv, serialize_geometry_to_string, CubeUv, FaceUv, PolyList, PolyMeshIssue, Polys, TextureMesh};
+    #[cfg(feature = "nbt")]
+    use crate::generics::level::{deserialize_level_dat_from_bytes, deserialize_level_dat_from_path, serialize_level_dat_to_bytes, write_level_dat_to_path, Experiment, GameRule, LevelDat};
+    #[cfg(feature = "behavior")]
+    use crate::generics::loot_table::{analyze_loot_table, deserialize_loot_table_from_str, LootRange, LootTable};
+    #[cfg(feature = "manifest")]
+    use crate::generics::manifest::{deserialize_manifest_borrowed_from_str, deserialize_manifest_from_bytes, deserialize_manifest_from_path, deserialize_manifest_from_reader, deserialize_manifest_from_path_with_options, deserialize_manifest_from_str, serialize_manifest_to_string, serialize_manifest_to_string_with_style, Manifest, ManifestCapability, ManifestDependency, ManifestModuleType, ManifestVersionRef, ScriptManifestDependency};
+    #[cfg(feature = "archive")]
+    use crate::generics::mcpack::{extract_mcpack, deserialize_mcpack_from_bytes, deserialize_mcpack_from_bytes_with_limits, deserialize_mcpack_from_path, deserialize_mcpack_from_reader, open_mcpack, ArchiveLimits, write_mcpack, write_mcpack_with_progress, write_mcpack_with_style};
+    #[cfg(all(feature = "archive", feature = "manifest"))]
+    use crate::generics::pack_info::{build_pack_info, sha256};
+    #[cfg(feature = "manifest")]
+    use crate::generics::parse::ParseOptions;
+    #[cfg(all(feature = "molang", feature = "resource"))]
+    use crate::generics::particle::{deserialize_particle_effect_from_path, deserialize_particle_effect_from_value, CurveNodes, CurveType, FiredEffect};
+    #[cfg(feature = "manifest")]
+    use crate::generics::server_config::{deserialize_server_pack_config_from_path, provision_server_pack_config, ServerPackConfig};
+    #[cfg(feature = "nbt")]
+    use crate::generics::structure::{deserialize_structure_from_bytes, deserialize_structure_from_path, serialize_structure_to_bytes, write_structure_to_path, BlockState, Structure};
+    #[cfg(all(feature = "archive", feature = "manifest", feature = "nbt"))]
+    use crate::generics::world_template::{deserialize_education_settings_from_world, write_mctemplate, AllowlistEntry, EducationSettings, WorldTemplate};
+    #[cfg(feature = "java")]
+    use crate::java::loot_table::convert_java_loot_table;
+    #[cfg(feature = "java")]
+    use crate::java::recipe::convert_java_recipe;
+    #[cfg(feature = "java")]
+    use crate::java::resource_pack::convert_java_resource_pack;
+    #[cfg(feature = "molang")]
+    use crate::molang::diagnostic::check_molang_fields;
+    #[cfg(feature = "molang")]
+    use crate::molang::eval::MolangContext;
+    #[cfg(feature = "molang")]
+    use crate::molang::optimizer::optimize;
+    #[cfg(feature = "molang")]
+    use crate::molang::parser::{parse_molang_for_version, parse_molang_from_str};
+    #[cfg(feature = "molang")]
+    use crate::molang::queries::{query_available, QueryContext};
+    #[cfg(feature = "molang")]
+    use crate::molang::version::{versioned_differences, VersionedChange};
+    #[cfg(feature = "molang")]
+    use crate::molang::Molang;
+    #[cfg(feature = "nbt")]
+    use crate::nbt::{read_nbt, write_nbt, NbtError, Tag};
+    use crate::progress::Task;
+    #[cfg(feature = "behavior")]
+    use crate::query::DefinitionMatch;
+    #[cfg(feature = "scaffold")]
+    use crate::scaffold::{new_addon, scaffold_entity, EntityScaffold, ScaffoldOptions};
+    #[cfg(feature = "snapshot")]
+    use crate::snapshot::{deserialize_snapshot_from_bytes, deserialize_snapshot_from_path, serialize_snapshot_to_bytes, write_snapshot_to_path, AddonSnapshot, PackSnapshot, SnapshotError};
+    #[cfg(feature = "behavior")]
+    use crate::typescript::{PropertyType, ScriptConstants};
+    use crate::utils::{json_pointer, to_canonical_json_string, JsonStyle, Level, SemVer};
+    #[cfg(feature = "jsonc")]
+    use crate::utils::strip_jsonc;
+    #[cfg(feature = "validation")]
+    use crate::validation::animations::validate_animation_references;
+    #[cfg(feature = "validation")]
+    use crate::validation::capabilities::{add_missing_capabilities, chemistry_references, infer_capabilities, missing_capabilities, validate_education_features};
+    #[cfg(feature = "validation")]
+    use crate::validation::deprecations::{lint_deprecated_components, lint_deprecated_components_for_version, DEFAULT_DEPRECATIONS};
+    #[cfg(feature = "validation")]
+    use crate::validation::duplicates::find_duplicate_identifiers;
+    #[cfg(feature = "validation")]
+    use crate::validation::effects::validate_effect_references;
+    #[cfg(feature = "validation")]
+    use crate::validation::entity_events::{analyze_entity_events, EntityEventIssueKind, EventGraph};
+    #[cfg(feature = "validation")]
+    use crate::validation::format_versions::{validate_format_versions, FormatVersionIssueKind};
+    #[cfg(feature = "validation")]
+    use crate::validation::gametest::{find_registered_tests, structure_identifier, structure_path, validate_gametests, GameTestIssueKind};
+    #[cfg(feature = "validation")]
+    use crate::validation::geometry::validate_geometry_references;
+    #[cfg(feature = "validation")]
+    use crate::validation::installation::{find_uuid_collisions, UuidCollisionKind};
+    #[cfg(feature = "validation")]
+    use crate::validation::localization::{check_localization, LanguageReport};
+    #[cfg(feature = "validation")]
+    use crate::validation::namespaces::{lint_identifier_namespaces, NamespaceIssueKind};
+    #[cfg(feature = "validation")]
+    use crate::validation::queries::validate_query_contexts;
+    #[cfg(feature = "validation")]
+    use crate::validation::recipes::{find_recipe_conflicts, RecipeConflictKind};
+    #[cfg(feature = "validation")]
+    use crate::validation::render_controllers::{validate_render_controllers, RenderControllerIssueKind};
+    use crate::vanilla::VanillaPacks;
+    #[cfg(feature = "validation")]
+    use crate::validation::runner::{validate_addon, validate_addon_with_progress, AddonPaths};
+    #[cfg(feature = "validation")]
+    use crate::validation::schema::{validate_pack_against_schemas, SchemaSet};
+    #[cfg(feature = "validation")]
+    use crate::validation::scripts::{check_script_dependencies, ScriptDependencyIssueKind};
+    #[cfg(feature = "validation")]
+    use crate::validation::sounds::validate_sound_references;
+    #[cfg(feature = "validation")]
+    use crate::validation::subpacks::{validate_subpacks, SubpackIssueKind};
+    #[cfg(feature = "validation")]
+    use crate::validation::texture_constraints::{image_dimensions, validate_texture_constraints, TextureIssueKind};
+    #[cfg(feature = "validation")]
+    use crate::validation::textures::{validate_texture_references, validate_textures_list};
+    #[cfg(feature = "validation")]
+    use crate::validation::{read_json_files, JsonFile, ReferenceKind, UnresolvedReference};
 
+    /// Copies the tree at `from` to `to`, so tests can edit a copy of the input packs.
+    fn copy_dir(from: &Path, to: &Path) {
+        fs::create_dir_all(to).unwrap();
+        for entry in fs::read_dir(from).unwrap() {
+            let entry = entry.unwrap();
+            if entry.path().is_dir() {
+                copy_dir(&entry.path(), &to.join(entry.file_name()));
+            } else {
+                fs::copy(entry.path(), to.join(entry.file_name())).unwrap();
+            }
+        }
+    }
+
+    #[cfg(feature = "manifest")]
     #[test]
     fn test() {
         let deserialized: Manifest = deserialize_manifest_from_str(
             &fs::read_to_string("./inputs/manifest.json").unwrap()
-        );
+        ).unwrap();
 
         dbg!(deserialized);
     }
+
+    #[cfg(feature = "molang")]
+    #[test]
+    fn molang_constant_folding() {
+        let program = parse_molang_from_str("math.sin(90) * 2 + q.anim_time * 1 + (1 > 2 ? q.foo : 3)").unwrap();
+        assert_eq!(optimize(&program).to_string(), "2+q.anim_time+3");
+
+        let program = parse_molang_from_str("v.x = 1 && 0 ? 5 : q.life_time; return v.x; v.y = 2;").unwrap();
+        assert_eq!(optimize(&program).to_string(), "v.x=q.life_time;return v.x;");
+
+        let program = parse_molang_from_str("q.is_baby ? 0.5 : 0.5").unwrap();
+        assert_eq!(optimize(&program).to_string(), "0.5");
+    }
+
+    #[cfg(feature = "molang")]
+    #[test]
+    fn molang_version_semantics() {
+        let old = SemVer::new(1, 18, 0);
+        let folded = |version: &SemVer| optimize(&parse_molang_for_version("1 || 0 && 0", version).unwrap()).to_string();
+        assert_eq!(folded(&old), "0");
+        assert_eq!(folded(&SemVer::new(1, 20, 0)), "1");
+
+        assert_eq!(
+            versioned_differences("q.a ? 1 : q.b ? 2 : 3", &old).unwrap(),
+            vec![VersionedChange::ConditionalAssociativity]
+        );
+        assert!(versioned_differences("q.a ? 1 : q.b ? 2 : 3", &SemVer::new(1, 18, 10)).unwrap().is_empty());
+    }
+
+    #[cfg(feature = "molang")]
+    #[test]
+    fn molang_newtype_serde() {
+        let values: Vec<Molang> = serde_json::from_str(r#"["q.anim_time * 2", 1.5, true]"#).unwrap();
+        assert_eq!(values, vec![Molang::from("q.anim_time * 2"), Molang::from("1.5"), Molang::from("1")]);
+        assert!(values[0].parse().is_ok());
+        assert_eq!(serde_json::to_string(&values).unwrap(), r#"["q.anim_time * 2",1.5,1]"#);
+        let literals = r#"[1,2.0,-3,"1e3"," 4"]"#;
+        assert_eq!(serde_json::to_string(&serde_json::from_str::<Vec<Molang>>(literals).unwrap()).unwrap(), literals);
+    }
+
+    #[cfg(feature = "molang")]
+    #[test]
+    fn molang_diagnostics() {
+        let document: serde_json::Value = serde_json::from_str(
+            r#"{"animations": {"walk": {"anim_time_update": "'éé' + ü * 2"}}}"#
+        ).unwrap();
+        let pointer = json_pointer(&["animations", "walk", "anim_time_update"]);
+        let diagnostics = check_molang_fields(&document, &[&pointer]);
+
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!((diagnostics[0].offset, diagnostics[0].column, diagnostics[0].length), (9, 8, 2));
+        assert_eq!(diagnostics[0].to_string(), "/animations/walk/anim_time_update:8: unexpected character 'ü'");
+    }
+
+    #[cfg(feature = "validation")]
+    #[test]
+    fn texture_references() {
+        let unresolved = validate_texture_references(Path::new("./inputs/resource_pack"), None);
+        let references: Vec<(&str, &str)> = unresolved.iter()
+            .map(|r| (r.pointer.as_str(), r.reference.as_str()))
+            .collect();
+
+        assert_eq!(references, vec![
+            ("/minecraft:client_entity/description/textures/saddled", "textures/entity/pig_saddle"),
+            ("/minecraft:attachable/description/textures/enchanted", "textures/misc/enchanted_item_glint"),
+            ("/particle_effect/description/basic_render_parameters/texture", "textures/particle/spark"),
+            ("/texture_data/ruby_block/textures/0/path", "textures/blocks/ruby_block"),
+            ("/texture_data/ruby/textures", "textures/items/ruby"),
+            ("/test:ruby_block/textures/side", "ruby_side"),
+        ]);
+    }
+
+    #[cfg(feature = "validation")]
+    #[test]
+    fn geometry_references() {
+        let unresolved = validate_geometry_references(
+            Path::new("./inputs/resource_pack"), Some(Path::new("./inputs/behavior_pack")), None
+        );
+        let references: Vec<(&str, &str)> = unresolved.iter()
+            .map(|r| (r.pointer.as_str(), r.reference.as_str()))
+            .collect();
+
+        assert_eq!(references, vec![
+            ("/minecraft:client_entity/description/geometry/baby", "geometry.pig.baby"),
+            ("/minecraft:attachable/description/geometry/default", "geometry.humanoid.armor.helmet"),
+            ("/minecraft:block/permutations/0/components/minecraft:geometry/identifier", "geometry.ruby_block.cut"),
+            ("/render_controllers/controller.render.pig/part_visibility/2/saddle", "saddle"),
+        ]);
+    }
+
+    #[cfg(feature = "validation")]
+    #[test]
+    fn animation_references() {
+        let unresolved = validate_animation_references(Path::new("./inputs/resource_pack"), None);
+        let references: Vec<(&str, &str)> = unresolved.iter()
+            .map(|r| (r.pointer.as_str(), r.reference.as_str()))
+            .collect();
+
+        assert_eq!(references, vec![
+            ("/minecraft:client_entity/description/animations/look_at_target", "animation.common.look_at_target"),
+            ("/minecraft:client_entity/description/scripts/animate/2", "baby_transform"),
+        ]);
+    }
+
+    #[cfg(feature = "validation")]
+    #[test]
+    fn sound_references() {
+        let unresolved = validate_sound_references(
+            Path::new("./inputs/resource_pack"), Some(Path::new("./inputs/behavior_pack")), None
+        );
+        let references: Vec<(&str, &str)> = unresolved.iter()
+            .map(|r| (r.pointer.as_str(), r.reference.as_str()))
+            .collect();
+
+        assert_eq!(references, vec![
+            ("/minecraft:client_entity/description/sound_effects/squeal/effect", "mob.pig.squeal"),
+            ("/block_sounds/ruby/events/break", "dig.ruby"),
+            ("/entity_sounds/entities/test:pig/events/hurt/sound", "mob.pig.hurt"),
+            ("/minecraft:entity/component_groups/test:angry/minecraft:ambient_sound_interval/event_name", "growl"),
+            ("/minecraft:npc_dialogue/scenes/0/buttons/0/commands/0", "random.pop"),
+        ]);
+    }
+
+    #[cfg(feature = "validation")]
+    #[test]
+    fn identifier_namespaces() {
+        let packs = [Path::new("./inputs/behavior_pack"), Path::new("./inputs/resource_pack")];
+        let issues: Vec<(String, NamespaceIssueKind)> = lint_identifier_namespaces(&packs, None).into_iter()
+            .map(|issue| (issue.identifier, issue.kind))
+            .collect();
+
+        assert_eq!(issues, vec![
+            ("gems:Sapphire".to_string(), NamespaceIssueKind::InvalidCharacters),
+            ("gems:Sapphire".to_string(), NamespaceIssueKind::Inconsistent { majority: "test".to_string() }),
+            ("ruby_block_from_rubies".to_string(), NamespaceIssueKind::Missing),
+        ]);
+
+        let issues = lint_identifier_namespaces(&packs, Some("gems"));
+        assert!(issues.iter().any(|issue| issue.identifier == "test:pig"
+            && issue.kind == NamespaceIssueKind::Unexpected { expected: "gems".to_string() }));
+    }
+
+    #[cfg(feature = "validation")]
+    #[test]
+    fn deprecated_components() {
+        let pack = Path::new("./inputs/behavior_pack");
+        let findings: Vec<(String, Option<String>)> = lint_deprecated_components(pack, &DEFAULT_DEPRECATIONS).into_iter()
+            .map(|finding| (finding.pointer, finding.replacement))
+            .collect();
+
+        assert_eq!(findings, vec![
+            ("/minecraft:block/components/minecraft:destroy_time".to_string(), Some("minecraft:destructible_by_mining".to_string())),
+            ("/minecraft:block/components/minecraft:block_light_emission".to_string(), Some("minecraft:light_emission".to_string())),
+        ]);
+
+        let findings = lint_deprecated_components_for_version(pack, &SemVer::new(1, 21, 0), &DEFAULT_DEPRECATIONS);
+        assert!(findings.iter().any(|finding| finding.component == "minecraft:foil"));
+    }
+
+    #[cfg(feature = "validation")]
+    #[test]
+    fn format_versions() {
+        let issues = validate_format_versions(Path::new("./inputs/behavior_pack"));
+
+        assert_eq!(issues.len(), 1);
+        assert_eq!(issues[0].file, Path::new("blocks/ruby_block.json"));
+        assert_eq!(issues[0].kind, FormatVersionIssueKind::NewerThanEngine { min_engine_version: SemVer::new(1, 20, 0) });
+        assert!(validate_format_versions(Path::new("./inputs/resource_pack")).is_empty());
+    }
+
+    #[cfg(feature = "validation")]
+    #[test]
+    fn duplicate_identifiers() {
+        let packs = [Path::new("./inputs/behavior_pack_patch"), Path::new("./inputs/behavior_pack"), Path::new("./inputs/resource_pack")];
+        let duplicates = find_duplicate_identifiers(&packs);
+        let summary: Vec<(&str, &str, Vec<usize>, Option<usize>)> = duplicates.iter()
+            .map(|d| (d.kind.as_str(), d.identifier.as_str(), d.definitions.iter().map(|l| l.pack).collect(), d.winner))
+            .collect();
+
+        assert_eq!(summary, vec![
+            ("minecraft:entity", "test:pig", vec![0, 1], Some(0)),
+            ("minecraft:item", "test:ruby", vec![1, 1], None),
+        ]);
+
+        let mut empty = duplicates[0].clone();
+        empty.definitions.clear();
+        let diagnostic = Diagnostic::from(empty);
+        assert_eq!((diagnostic.code, diagnostic.file), ("duplicate-identifier", None));
+    }
+
+    #[cfg(feature = "validation")]
+    #[test]
+    fn schema_validation() {
+        let mut schemas = SchemaSet::new();
+        schemas.load("items", Path::new("./inputs/schemas/item.schema.json")).unwrap();
+
+        let violations = validate_pack_against_schemas(Path::new("./inputs/behavior_pack"), &schemas);
+        let violations: Vec<(&str, &str)> = violations.iter().map(|v| (v.pointer.as_str(), v.message.as_str())).collect();
+
+        assert_eq!(violations, vec![
+            ("/minecraft:item", "missing required property 'components'"),
+            ("/minecraft:item/component", "unknown property 'component'"),
+        ]);
+    }
+
+    #[cfg(feature = "validation")]
+    #[test]
+    fn diagnostics() {
+        let mut diagnostics = Diagnostics::new();
+        diagnostics.extend(validate_format_versions(Path::new("./inputs/behavior_pack")));
+        diagnostics.extend(lint_deprecated_components(Path::new("./inputs/behavior_pack"), &DEFAULT_DEPRECATIONS));
+        diagnostics.extend(lint_identifier_namespaces(&[Path::new("./inputs/behavior_pack")], None));
+
+        assert!(diagnostics.has_errors());
+        let errors = diagnostics.clone().min_severity(Severity::Error).sorted().into_vec();
+        assert_eq!(format_plain(&errors[..1]), "blocks/ruby_block.json#/format_version: error[format-version-too-new]: format_version 1.20.10 is newer than min_engine_version 1.20.0\n");
+
+        let codes: Vec<&str> = diagnostics.without_codes(&["namespace-missing"]).sorted().iter().map(|d| d.code).collect();
+        assert!(!codes.contains(&"namespace-missing"));
+        assert!(codes.contains(&"deprecated-component"));
+    }
+
+    #[cfg(feature = "behavior")]
+    #[test]
+    fn loot_table_analysis() {
+        let pack = Path::new("./inputs/behavior_pack");
+        let drops = analyze_loot_table(pack, Path::new("loot_tables/entities/pig.json")).unwrap();
+        let drops: Vec<(&str, f64, f64)> = drops.iter().map(|d| (d.item.as_str(), d.probability, d.expected_count)).collect();
+
+        assert_eq!(drops, vec![
+            ("gems:sapphire", 0.109375, 0.125),
+            ("minecraft:porkchop", 1.0, 2.0),
+            ("test:ruby", 0.234375, 0.375),
+        ]);
+        let error = analyze_loot_table(pack, Path::new("loot_tables/missing.json")).unwrap_err();
+        assert!(matches!(error, AddonError::Io { .. }));
+        assert_eq!(error.path(), Path::new("loot_tables/missing.json"));
+
+        let root = std::env::temp_dir().join("bedrockrs_addon_loot_ranges");
+        let _ = fs::remove_dir_all(&root);
+        fs::create_dir_all(root.join("loot_tables")).unwrap();
+        fs::write(root.join("loot_tables/huge.json"), r#"{ "pools": [{
+            "rolls": { "min": 0, "max": 1000000000 },
+            "entries": [{ "type": "item", "name": "test:ruby", "weight": 4294967295 }, { "type": "empty", "weight": 4294967295 }]
+        }] }"#).unwrap();
+        let drops = analyze_loot_table(&root, Path::new("loot_tables/huge.json")).unwrap();
+        assert!((drops[0].probability - 1.0).abs() < 1e-6);
+        assert_eq!(drops[0].expected_count, 250_000_000.0);
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[cfg(feature = "manifest")]
+    #[test]
+    fn addon_errors() {
+        let error = deserialize_manifest_from_path(Path::new("./inputs/missing/manifest.json")).unwrap_err();
+        assert!(matches!(error, AddonError::Io { .. }));
+
+        let error = deserialize_manifest_from_path(Path::new("./inputs/schemas/item.schema.json")).unwrap_err();
+        assert!(matches!(error, AddonError::Json { .. }));
+        assert!(error.to_string().starts_with("./inputs/schemas/item.schema.json: invalid JSON: "));
+
+        let manifest = deserialize_manifest_from_path(Path::new("./inputs/manifest.json")).unwrap();
+        assert_eq!(manifest.raw, deserialize_manifest_from_str(&fs::read_to_string("./inputs/manifest.json").unwrap()).unwrap().raw);
+    }
+
+    #[cfg(feature = "manifest")]
+    #[test]
+    fn parse_modes() {
+        let path = Path::new("./inputs/community_manifest.json");
+        let error = deserialize_manifest_from_path(path).unwrap_err();
+        assert_eq!(error.to_string(), "./inputs/community_manifest.json: unknown module type \"behaviour\"");
+
+        let (manifest, diagnostics) = deserialize_manifest_from_path_with_options(path, &ParseOptions::lenient()).unwrap();
+        assert_eq!(manifest.modules.len(), 1);
+        assert_eq!(manifest.dependencies.len(), 1);
+        let found: Vec<(&str, &str)> = diagnostics.iter().map(|d| (d.code, d.pointer.as_deref().unwrap())).collect();
+        assert_eq!(found, vec![
+            ("invalid-manifest-module", "/modules/1"),
+            ("invalid-manifest-dependency", "/dependencies/0"),
+        ]);
+    }
+
+    #[cfg(feature = "manifest")]
+    #[test]
+    fn prelude() {
+        use crate::prelude::*;
+
+        let manifest: Manifest = deserialize_manifest_from_path(Path::new("./inputs/manifest.json")).unwrap();
+        let uuid: Uuid = manifest.header.uuid;
+        assert_eq!(manifest.header.version, SemVer::new(0, 0, 1));
+        assert!(!uuid.is_nil());
+    }
+
+    #[cfg(feature = "validation")]
+    #[test]
+    fn addon_walk() {
+        #[derive(Default)]
+        struct Counter {
+            manifests: Vec<PackKind>,
+            loot_tables: Vec<String>,
+            languages: usize,
+            json: usize,
+            files: usize
+        }
+
+        impl Visit for Counter {
+            fn visit_manifest(&mut self, pack: PackKind, _: &Manifest) {
+                self.manifests.push(pack);
+            }
+
+            fn visit_loot_table(&mut self, file: &Path, _: &LootTable) {
+                self.loot_tables.push(file.to_string_lossy().replace('\\', "/"));
+            }
+
+            fn visit_lang(&mut self, _: PackKind, _: &Path, _: &BTreeMap<String, String>) {
+                self.languages += 1;
+            }
+
+            fn visit_json(&mut self, _: PackKind, _: &JsonFile) {
+                self.json += 1;
+            }
+
+            fn visit_file(&mut self, _: PackKind, _: &Path) {
+                self.files += 1;
+            }
+        }
+
+        let addon = Addon::new(Some("./inputs/behavior_pack".into()), Some("./inputs/resource_pack".into()));
+        let mut counter = Counter::default();
+        addon.walk(&mut counter);
+
+        assert_eq!(counter.manifests, vec![PackKind::Behavior, PackKind::Resource]);
+        assert_eq!(counter.loot_tables, vec!["loot_tables/chests/lab.json", "loot_tables/entities/pig.json", "loot_tables/gems.json"]);
+        assert_eq!(counter.languages, 2);
+        assert!(counter.files > counter.json && counter.json > 10);
+    }
+
+    #[test]
+    fn rename_identifier() {
+        let root = std::env::temp_dir().join("bedrockrs_addon_rename_identifier");
+        let _ = fs::remove_dir_all(&root);
+        copy_dir(Path::new("./inputs/behavior_pack"), &root.join("bp"));
+        copy_dir(Path::new("./inputs/resource_pack"), &root.join("rp"));
+        let addon = Addon::new(Some(root.join("bp")), Some(root.join("rp")));
+
+        let touched = addon.rename_identifier("test:ruby", "test:red_gem").unwrap();
+        let touched: Vec<String> = touched.iter().map(|path| path.strip_prefix(&root).unwrap().to_string_lossy().replace('\\', "/")).collect();
+        assert_eq!(touched, vec![
+            "bp/functions/ruby_kit.mcfunction",
+            "bp/items/ruby.json",
+            "bp/items/ruby_copy.json",
+            "bp/loot_tables/chests/lab.json",
+            "bp/loot_tables/gems.json",
+            "bp/recipes/ruby_block.json",
+            "bp/recipes/ruby_hoe.json",
+            "bp/recipes/ruby_hoe_mirrored.json",
+            "bp/recipes/ruby_smelting.json",
+            "rp/texts/en_US.lang",
+        ]);
+
+        let function = fs::read_to_string(root.join("bp/functions/ruby_kit.mcfunction")).unwrap();
+        assert_eq!(function, "# Hands out the ruby kit\ngive @s test:red_gem 4\ngive @s test:ruby_hoe\n");
+        let lang = fs::read_to_string(root.join("rp/texts/en_US.lang")).unwrap();
+        assert!(lang.contains("item.test:red_gem.name=Ruby\t## shown in the inventory"));
+        assert!(lang.contains("tile.test:ruby_ore.name=Ruby Ore"));
+        assert!(addon.rename_identifier("test:ruby", "test:red_gem").unwrap().is_empty());
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn renamespace() {
+        let root = std::env::temp_dir().join("bedrockrs_addon_renamespace");
+        let _ = fs::remove_dir_all(&root);
+        copy_dir(Path::new("./inputs/behavior_pack"), &root.join("bp"));
+        copy_dir(Path::new("./inputs/resource_pack"), &root.join("rp"));
+        fs::write(root.join("bp/scripts/ids.js"), "// ids of the test: namespace\nexport const RUBY = \"test:ruby\";\nexport const LABEL = 'test:';\n").unwrap();
+        fs::create_dir_all(root.join("rp/textures/test")).unwrap();
+        fs::copy("./inputs/resource_pack/pack_icon.png", root.join("rp/textures/test/gem.png")).unwrap();
+        let addon = Addon::new(Some(root.join("bp")), Some(root.join("rp")));
+        let mut atlas = addon.edit(PackKind::Resource, "textures/item_texture.json").unwrap();
+        atlas.value["texture_data"]["test_gem"] = serde_json::json!({ "textures": "textures/test/gem" });
+        atlas.save().unwrap();
+
+        let report = addon.renamespace("test", "gems").unwrap();
+        let relative = |path: &Path| path.strip_prefix(&root).unwrap().to_string_lossy().replace('\\', "/");
+        assert_eq!(report.moved.iter().map(|(from, to)| (relative(from), relative(to))).collect::<Vec<_>>(), [
+            ("rp/textures/test".to_string(), "rp/textures/gems".to_string())
+        ]);
+        let changed: Vec<String> = report.changed.iter().map(|path| relative(path)).collect();
+        assert_eq!(changed.len(), 22);
+        assert!(changed.contains(&"bp/scripts/ids.js".to_string()) && changed.contains(&"rp/texts/en_US.lang".to_string()));
+        let untouched: Vec<(String, usize)> = report.untouched.iter().map(|Occurrence { file, line, .. }| (relative(file), *line)).collect();
+        assert_eq!(untouched, [
+            ("bp/package.json".to_string(), 2), ("bp/scripts/ids.js".to_string(), 1), ("bp/scripts/ids.js".to_string(), 3),
+            ("rp/textures/item_texture.json".to_string(), 2), ("rp/textures/item_texture.json".to_string(), 7),
+            ("rp/textures/terrain_texture.json".to_string(), 2)
+        ]);
+
+        assert_eq!(fs::read_to_string(root.join("bp/functions/ruby_kit.mcfunction")).unwrap(), "# Hands out the ruby kit\ngive @s gems:ruby 4\ngive @s gems:ruby_hoe\n");
+        assert_eq!(
+            fs::read_to_string(root.join("bp/scripts/ids.js")).unwrap(),
+            "// ids of the test: namespace\nexport const RUBY = \"gems:ruby\";\nexport const LABEL = 'test:';\n"
+        );
+        let lang = fs::read_to_string(root.join("rp/texts/en_US.lang")).unwrap();
+        assert!(lang.contains("item.gems:ruby.name=Ruby\t## shown in the inventory") && !lang.contains("test:"));
+        let atlas = addon.edit(PackKind::Resource, "textures/item_texture.json").unwrap();
+        assert_eq!(atlas.value["texture_data"]["test_gem"]["textures"], "textures/gems/gem");
+        assert!(root.join("rp/textures/gems/gem.png").exists());
+        assert!(addon.renamespace("test", "gems").unwrap().changed.is_empty());
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[cfg(feature = "behavior")]
+    #[test]
+    fn content_queries() {
+        let addon = Addon::new(Some("./inputs/behavior_pack".into()), None);
+        let found = |matches: Vec<DefinitionMatch>| -> Vec<(String, String)> {
+            matches.into_iter().map(|m| (m.identifier, m.file.to_string_lossy().replace('\\', "/"))).collect()
+        };
+
+        assert_eq!(found(addon.find_entities_with_component("minecraft:health")), vec![("test:pig".to_string(), "entities/pig.json".to_string())]);
+        assert_eq!(found(addon.find_entities_with_component("minecraft:ambient_sound_interval")).len(), 1);
+        assert!(addon.find_entities_with_component("minecraft:rideable").is_empty());
+        assert_eq!(found(addon.find_items_in_creative_category("items")).len(), 4);
+        assert!(addon.find_items_in_creative_category("equipment").is_empty());
+        assert_eq!(found(addon.find_recipes_producing("test:ruby")), vec![("test:ruby_hoe".to_string(), "recipes/ruby_smelting.json".to_string())]);
+    }
+
+    #[cfg(feature = "molang")]
+    #[test]
+    fn serde_helpers() {
+        #[derive(serde::Serialize, serde::Deserialize, Debug, PartialEq)]
+        struct Pinned {
+            #[serde(with = "crate::serde_helpers::uuid_string")]
+            uuid: uuid::Uuid,
+            #[serde(with = "crate::serde_helpers::semver_array")]
+            version: SemVer,
+            #[serde(with = "crate::serde_helpers::semver_string")]
+            module: SemVer,
+            #[serde(with = "crate::serde_helpers::molang")]
+            condition: String
+        }
+
+        let pinned: Pinned = serde_json::from_str(r#"{
+            "uuid": "9a4c7d2e-1b3f-4e5a-8c6d-7e8f9a0b1c2d",
+            "version": "1.2.3",
+            "module": [1, 11, 0],
+            "condition": 0.5
+        }"#).unwrap();
+        assert_eq!(pinned.version, SemVer::new(1, 2, 3));
+        assert_eq!(pinned.condition, "0.5");
+        assert_eq!(
+            serde_json::to_string(&pinned).unwrap(),
+            r#"{"uuid":"9a4c7d2e-1b3f-4e5a-8c6d-7e8f9a0b1c2d","version":[1,2,3],"module":"1.11.0","condition":0.5}"#
+        );
+        assert!(serde_json::from_str::<Pinned>(r#"{"uuid": "nope", "version": [1], "module": "1.0.0", "condition": "1"}"#).is_err());
+    }
+
+    #[cfg(feature = "manifest")]
+    #[test]
+    fn canonical_serialization() {
+        let value: serde_json::Value = serde_json::from_str(r#"{"b": [3, 1], "a": {"d": 1, "c": 2}}"#).unwrap();
+        assert_eq!(to_canonical_json_string(&value), "{\n  \"a\": {\n    \"c\": 2,\n    \"d\": 1\n  },\n  \"b\": [\n    3,\n    1\n  ]\n}\n");
+
+        let manifest = deserialize_manifest_from_str(&fs::read_to_string("./inputs/manifest.json").unwrap()).unwrap();
+        let serialized = serialize_manifest_to_string(&manifest);
+        let reparsed = deserialize_manifest_from_str(&serialized).unwrap();
+        assert_eq!(serialize_manifest_to_string(&reparsed), serialized);
+        assert_eq!(reparsed.raw, manifest.to_value());
+        assert_eq!(reparsed.header.uuid, manifest.header.uuid);
+        assert_eq!(reparsed.modules.len(), manifest.modules.len());
+        assert!(serialized.find("\"capabilities\"").unwrap() < serialized.find("\"header\"").unwrap());
+    }
+
+    #[cfg(feature = "behavior")]
+    #[test]
+    fn definition_builders() {
+        let item = ItemBuilder::new("test:topaz").menu_category("items").component("minecraft:max_stack_size", 16.into()).build();
+        assert_eq!(item.pointer("/minecraft:item/description/identifier"), Some(&"test:topaz".into()));
+        assert_eq!(item.pointer("/minecraft:item/components/minecraft:max_stack_size"), Some(&16.into()));
+
+        let entity = EntityBuilder::new("test:golem").spawnable(true).event("test:grow", serde_json::json!({})).build();
+        assert_eq!(entity.pointer("/minecraft:entity/description/is_spawnable"), Some(&true.into()));
+        assert!(entity.pointer("/minecraft:entity/component_groups").is_none());
+
+        let block = BlockBuilder::new("test:lamp").state("test:lit", serde_json::json!([false, true]))
+            .permutation("q.block_state('test:lit')", serde_json::json!({ "minecraft:light_emission": 15 }))
+            .build();
+        assert_eq!(block.pointer("/minecraft:block/description/states/test:lit/1"), Some(&true.into()));
+        assert_eq!(block.pointer("/minecraft:block/permutations/0/condition"), Some(&"q.block_state('test:lit')".into()));
+
+        let recipe = RecipeBuilder::shaped("test:topaz_block", &["##", "##"], "test:topaz_block").key('#', "test:topaz").build();
+        assert_eq!(recipe.pointer("/minecraft:recipe_shaped/key/#/item"), Some(&"test:topaz".into()));
+
+        let table = LootTableBuilder::new()
+            .pool(LootPoolBuilder::new(LootRange::Exact(1.0)).item("test:topaz", 1, LootRange::Range { min: 1.0, max: 3.0 }).empty(1))
+            .build();
+        assert_eq!(deserialize_loot_table_from_str(&table.raw.to_string()).unwrap(), table);
+    }
+
+    #[test]
+    fn editable_json() {
+        assert_eq!(JsonStyle::detect("{\"a\":1}"), JsonStyle::minified());
+        assert_eq!(JsonStyle::detect("{\n    \"a\": 1\n}\n"), JsonStyle::pretty(4));
+        assert_eq!(JsonStyle::minified().write(&serde_json::json!({"b": 1, "a": [true]})), "{\"a\":[true],\"b\":1}");
+
+        let root = std::env::temp_dir().join("bedrockrs_addon_editable_json");
+        let _ = fs::remove_dir_all(&root);
+        fs::create_dir_all(root.join("items")).unwrap();
+        fs::write(root.join("items/topaz.json"), "{\n\t\"minecraft:item\": {\n\t\t\"description\": {\n\t\t\t\"identifier\": \"test:topaz\"\n\t\t}\n\t}\n}").unwrap();
+
+        let addon = Addon::new(Some(root.clone()), None);
+        let mut item = addon.edit(PackKind::Behavior, "items/topaz.json").unwrap();
+        item.value["minecraft:item"]["description"]["identifier"] = "test:gem".into();
+        item.save().unwrap();
+        assert_eq!(
+            fs::read_to_string(root.join("items/topaz.json")).unwrap(),
+            "{\n\t\"minecraft:item\": {\n\t\t\"description\": {\n\t\t\t\"identifier\": \"test:gem\"\n\t\t}\n\t}\n}"
+        );
+        assert!(addon.edit(PackKind::Resource, "items/topaz.json").is_err());
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[cfg(feature = "manifest")]
+    #[test]
+    fn enum_string_conversions() {
+        assert_eq!("editorExtension".parse::<ManifestCapability>().unwrap(), ManifestCapability::EditorExtension);
+        assert_eq!("rastra_cap".parse::<ManifestCapability>().unwrap(), ManifestCapability::Custom("rastra_cap".to_string()));
+        assert_eq!(ManifestCapability::ScriptEval.to_string(), "script_eval");
+        assert_eq!(serde_json::to_string(&ScriptManifestDependency::MinecraftServerUi).unwrap(), "\"@minecraft/server-ui\"");
+        assert_eq!(serde_json::from_str::<ScriptManifestDependency>("\"@minecraft/server-net\"").unwrap(), ScriptManifestDependency::MinecraftServerNet);
+
+        assert_eq!("world_template".parse::<ManifestModuleType>(), Ok(ManifestModuleType::WorldTemplate));
+        assert_eq!("behaviour".parse::<ManifestModuleType>().unwrap_err().to_string(), "unknown module type \"behaviour\"");
+        for module_type in ManifestModuleType::ALL {
+            assert_eq!(serde_json::from_value::<ManifestModuleType>(serde_json::to_value(module_type).unwrap()).unwrap(), module_type);
+        }
+    }
+
+    #[cfg(feature = "validation")]
+    #[test]
+    fn recipe_conflicts() {
+        let conflicts = find_recipe_conflicts(Path::new("./inputs/behavior_pack"), None);
+        let summary: Vec<(RecipeConflictKind, Vec<&str>, Option<usize>)> = conflicts.iter()
+            .map(|c| (c.kind.clone(), c.recipes.iter().map(|r| r.file.to_str().unwrap()).collect(), c.winner))
+            .collect();
+
+        assert_eq!(summary, vec![
+            (RecipeConflictKind::Identifier, vec!["recipes/ruby_hoe.json", "recipes/ruby_smelting.json"], None),
+            (
+                RecipeConflictKind::Inputs { tags: vec!["crafting_table".to_string()] },
+                vec!["recipes/ruby_hoe.json", "recipes/ruby_hoe_mirrored.json"],
+                Some(0)
+            ),
+        ]);
+    }
+
+    #[cfg(feature = "manifest")]
+    #[test]
+    fn pack_dependencies() {
+        let mut manifests: Vec<Manifest> = ["./inputs/manifest.json", "./inputs/behavior_pack/manifest.json", "./inputs/resource_pack/manifest.json", "./inputs/behavior_pack_patch/manifest.json"]
+            .iter()
+            .map(|path| deserialize_manifest_from_str(&fs::read_to_string(path).unwrap()).unwrap())
+            .collect();
+
+        let resolution = resolve_pack_dependencies(&manifests);
+        assert_eq!(resolution.dependencies, vec![vec![], vec![2], vec![], vec![]]);
+        assert!(matches!(resolution.issues.as_slice(), [DependencyIssue::Missing { pack: 0, .. }]));
+        assert_eq!(resolution.activation_order, vec![2, 1, 3]);
+
+        let bp_uuid = manifests[1].header.uuid;
+        manifests[2].dependencies.push(ManifestDependency::UuidDependency(bp_uuid, SemVer::new(1, 0, 0)));
+        manifests[3].dependencies.push(ManifestDependency::UuidDependency(bp_uuid, SemVer::new(2, 0, 0)));
+        let resolution = resolve_pack_dependencies(&manifests);
+        assert_eq!(&resolution.issues[1..], &[
+            DependencyIssue::VersionMismatch { pack: 3, uuid: bp_uuid, required: SemVer::new(2, 0, 0), available: vec![SemVer::new(1, 0, 0)] },
+            DependencyIssue::Cycle(vec![1, 2]),
+        ]);
+        assert!(resolution.activation_order.is_empty());
+    }
+
+    #[cfg(feature = "validation")]
+    #[test]
+    fn script_dependencies() {
+        let issues: Vec<(String, ScriptDependencyIssueKind)> = check_script_dependencies(Path::new("./inputs/behavior_pack")).into_iter()
+            .map(|issue| (issue.module, issue.kind))
+            .collect();
+
+        assert_eq!(issues, vec![
+            ("@minecraft/server".to_string(), ScriptDependencyIssueKind::VersionMismatch { manifest: SemVer::new(1, 11, 0), package: "^1.12.0".to_string() }),
+            ("@minecraft/server-gametest".to_string(), ScriptDependencyIssueKind::MissingFromManifest { package: "1.0.0-beta.1.21.20-stable".to_string() }),
+        ]);
+        assert!(check_script_dependencies(Path::new("./inputs/resource_pack")).is_empty());
+    }
+
+    #[cfg(feature = "validation")]
+    #[test]
+    fn localization() {
+        let report = check_localization(Path::new("./inputs/resource_pack"), &[Path::new("./inputs/behavior_pack")]);
+
+        assert_eq!(report.missing_files, vec!["fr_FR".to_string()]);
+        assert_eq!(report.languages, vec![LanguageReport {
+            language: "de_DE".to_string(),
+            missing: vec!["action.interact.pet".to_string(), "item.test:ruby.name".to_string()],
+            extra: vec!["item.test:sapphire.name".to_string()]
+        }]);
+        let unresolved: Vec<(&str, &str)> = report.unresolved.iter().map(|m| (m.pointer.as_str(), m.key.as_str())).collect();
+        assert_eq!(unresolved, vec![("/minecraft:npc_dialogue/scenes/0/text/rawtext/0/translate", "dialogue.pig.intro")]);
+    }
+
+    #[cfg(feature = "validation")]
+    #[test]
+    fn texture_constraints() {
+        assert_eq!(image_dimensions(Path::new("./inputs/resource_pack/pack_icon.png")), Some((64, 64)));
+
+        let issues = validate_texture_constraints(Path::new("./inputs/resource_pack"));
+        let issues: Vec<(&str, &str, TextureIssueKind)> = issues.iter()
+            .map(|issue| (issue.pointer.as_str(), issue.texture.as_str(), issue.kind.clone()))
+            .collect();
+        assert_eq!(issues, vec![
+            ("/texture_data/ruby_bricks/textures", "textures/blocks/ruby_bricks", TextureIssueKind::NotPowerOfTwo),
+            ("/texture_data/ruby_bricks/textures", "textures/blocks/ruby_bricks", TextureIssueKind::InconsistentTileSize { expected: (16, 16) }),
+            ("/0/frames/3", "textures/blocks/ruby_lamp", TextureIssueKind::FlipbookFrameOutOfRange { frame: 3, frames: 3 }),
+        ]);
+    }
+
+    #[cfg(feature = "validation")]
+    #[test]
+    fn subpacks() {
+        let issues: Vec<(String, String, SubpackIssueKind)> = validate_subpacks(Path::new("./inputs/resource_pack")).into_iter()
+            .map(|issue| (issue.file.to_string_lossy().replace('\\', "/"), issue.pointer, issue.kind))
+            .collect();
+
+        assert_eq!(issues, vec![
+            ("subpacks/low/pack_icon.png".to_string(), "".to_string(), SubpackIssueKind::NotOverridable),
+            ("subpacks/low/textures/entity/cow.png".to_string(), "".to_string(), SubpackIssueKind::NoBaseFile),
+            ("manifest.json".to_string(), "/subpacks/1/memory_tier".to_string(), SubpackIssueKind::UnorderedMemoryTier { memory_tier: 0, previous: 1 }),
+            ("manifest.json".to_string(), "/subpacks/2/folder_name".to_string(), SubpackIssueKind::MissingFolder),
+            ("subpacks/extra".to_string(), "".to_string(), SubpackIssueKind::UndeclaredFolder),
+        ]);
+    }
+
+    #[cfg(feature = "validation")]
+    #[test]
+    fn uuid_collisions() {
+        let collisions: Vec<(UuidCollisionKind, Vec<String>)> = find_uuid_collisions(Path::new("./inputs/com.mojang")).into_iter()
+            .map(|c| (c.kind, c.packs.iter().map(|p| p.path.to_string_lossy().replace('\\', "/")).collect()))
+            .collect();
+
+        assert_eq!(collisions, vec![
+            (UuidCollisionKind::DifferentVersions, vec!["behavior_packs/gems_v1".to_string(), "behavior_packs/gems_v2".to_string(), "development_behavior_packs/gems_dev".to_string()]),
+            (UuidCollisionKind::IdenticalCopies, vec!["behavior_packs/gems_v2".to_string(), "development_behavior_packs/gems_dev".to_string()]),
+            (UuidCollisionKind::ConflictingContent, vec!["resource_packs/ruby".to_string(), "development_resource_packs/ruby_wip".to_string()]),
+        ]);
+    }
+
+    #[cfg(feature = "validation")]
+    #[test]
+    fn capability_inference() {
+        let inferred: Vec<(ManifestCapability, String)> = infer_capabilities(Path::new("./inputs/behavior_pack")).into_iter()
+            .map(|e| (e.capability, e.file.to_string_lossy().replace('\\', "/")))
+            .collect();
+        assert_eq!(inferred, vec![
+            (ManifestCapability::Chemistry, "loot_tables/chests/lab.json".to_string()),
+            (ManifestCapability::ScriptEval, "scripts/main.js".to_string()),
+        ]);
+
+        let pack = std::env::temp_dir().join("bedrockrs_addon_capability_inference");
+        let _ = fs::remove_dir_all(&pack);
+        fs::create_dir_all(pack.join("textures/blocks")).unwrap();
+        fs::copy("./inputs/resource_pack/manifest.json", pack.join("manifest.json")).unwrap();
+        fs::copy("./inputs/resource_pack/textures/blocks/ruby_ore.texture_set.json", pack.join("textures/blocks/ruby_ore.texture_set.json")).unwrap();
+
+        assert_eq!(add_missing_capabilities(&pack).unwrap(), vec![ManifestCapability::PBR]);
+        assert!(missing_capabilities(&pack).is_empty());
+        let manifest = deserialize_manifest_from_str(&fs::read_to_string(pack.join("manifest.json")).unwrap()).unwrap();
+        assert_eq!(manifest.capabilities, vec![ManifestCapability::PBR]);
+        fs::remove_dir_all(&pack).unwrap();
+    }
+
+    #[cfg(feature = "manifest")]
+    #[test]
+    fn borrowed_manifest() {
+        let src = fs::read_to_string("./inputs/manifest.json").unwrap();
+        let manifest = deserialize_manifest_borrowed_from_str(&src).unwrap();
+
+        assert!(matches!(manifest.header.name, Cow::Borrowed("JDH Suit")));
+        assert_eq!(manifest.header.uuid(), Some(deserialize_manifest_from_str(&src).unwrap().header.uuid));
+        assert_eq!(manifest.modules[1].entry.as_deref(), Some("scripts/main.js"));
+        assert_eq!(manifest.dependencies[1].module_name.as_deref(), Some("@minecraft/server"));
+        assert!(matches!(manifest.dependencies[1].version, ManifestVersionRef::Str(Cow::Borrowed("1.14.0-beta"))));
+        assert_eq!(manifest.dependencies[1].version.to_semver(), Some(SemVer { major: 1, minor: 14, patch: 0, beta: true }));
+        assert_eq!(ManifestVersionRef::Str(Cow::Borrowed("1.0")).to_semver(), None);
+        assert_eq!(manifest.capabilities, vec!["raytraced", "rastra_cap"]);
+        assert!(deserialize_manifest_borrowed_from_str("// pack\n{}").is_err());
+    }
+
+    #[cfg(feature = "archive")]
+    #[test]
+    fn mcpack_writer() {
+        let archive = write_mcpack(Path::new("./inputs/behavior_pack_patch"), vec![]).unwrap();
+
+        let end = &archive[archive.len() - 22..];
+        assert_eq!(&end[..4], &[0x50, 0x4b, 0x05, 0x06]);
+        assert_eq!(u16::from_le_bytes([end[10], end[11]]), 2);
+
+        let manifest = fs::read("./inputs/behavior_pack_patch/manifest.json").unwrap();
+        let name_length = u16::from_le_bytes([archive[26], archive[27]]) as usize;
+        assert_eq!(&archive[30..30 + name_length], b"entities/pig.json");
+        assert!(archive.windows(manifest.len()).any(|window| window == manifest.as_slice()));
+    }
+
+    #[cfg(feature = "validation")]
+    #[test]
+    fn addon_validation() {
+        let addon = AddonPaths {
+            resource_pack: Some(Path::new("./inputs/resource_pack")),
+            behavior_pack: Some(Path::new("./inputs/behavior_pack")),
+            ..AddonPaths::default()
+        };
+        let parallel = validate_addon(&addon, 4);
+        let sequential = validate_addon(&addon, 1);
+
+        assert_eq!(parallel.diagnostics.clone().into_vec(), sequential.diagnostics.into_vec());
+        assert!(parallel.timings.iter().any(|timing| timing.rule == "recipe-conflicts" && timing.diagnostics == 2));
+        assert_eq!(parallel.timings.iter().map(|timing| timing.diagnostics).sum::<usize>(), parallel.diagnostics.len());
+    }
+
+    #[cfg(all(feature = "archive", feature = "validation"))]
+    #[test]
+    fn progress_reporting() {
+        let reports = std::sync::Mutex::new(vec![]);
+        let progress = |task: Task, done: usize, total: usize| reports.lock().unwrap().push((task, done, total));
+
+        let addon = AddonPaths { behavior_pack: Some(Path::new("./inputs/behavior_pack")), ..AddonPaths::default() };
+        let run = validate_addon_with_progress(&addon, 2, &progress);
+        write_mcpack_with_progress(Path::new("./inputs/behavior_pack_patch"), vec![], &progress).unwrap();
+
+        let reports = reports.into_inner().unwrap();
+        let validating: Vec<usize> = reports.iter().filter(|r| r.0 == Task::Validating).map(|r| r.1).collect();
+        assert_eq!(validating.len(), run.timings.len());
+        assert_eq!(validating.iter().max(), Some(&run.timings.len()));
+        let archiving: Vec<(usize, usize)> = reports.iter().filter(|r| r.0 == Task::Archiving).map(|r| (r.1, r.2)).collect();
+        assert_eq!(archiving.last().map(|(done, total)| done == total), Some(true));
+    }
+
+    #[cfg(feature = "nbt")]
+    #[test]
+    fn mcstructure_parsing() {
+        let path = Path::new("./inputs/behavior_pack/structures/ruby_hut.mcstructure");
+        let structure = deserialize_structure_from_path(path).unwrap();
+
+        assert_eq!((structure.size, structure.origin), ([2, 2, 1], [10, 64, -5]));
+        let names: Vec<&str> = structure.palette.iter().map(|block| block.name.as_str()).collect();
+        assert_eq!(names, vec!["minecraft:stone", "minecraft:chest", "minecraft:water"]);
+        let chest = structure.block(0, 1, 0).unwrap();
+        assert_eq!(chest.states.get("facing_direction"), Some(&Tag::Int(2)));
+        assert_eq!(structure.block(1, 1, 0), None);
+        assert_eq!(structure.block(2, 0, 0), None);
+        assert_eq!(structure.extra_blocks[structure.index(1, 0, 0).unwrap()], Some(2));
+        assert_eq!(structure.block_entity(0, 1, 0).and_then(|data| data.get("id")), Some(&Tag::String("Chest".to_string())));
+        assert_eq!(structure.entities[0].get("identifier"), Some(&Tag::String("minecraft:pig".to_string())));
+
+        let bytes = fs::read(path).unwrap();
+        let (name, root) = read_nbt(&bytes).unwrap();
+        assert_eq!(read_nbt(&write_nbt(&name, &root).unwrap()).unwrap(), (name, root));
+        assert_eq!(read_nbt(&bytes[..bytes.len() - 1]), Err(NbtError::UnexpectedEnd));
+        assert_eq!(write_nbt("", &Tag::String("é".repeat(40_000))), Err(NbtError::StringTooLong(80_000)));
+        let empty = write_nbt("", &Tag::Compound(Default::default())).unwrap();
+        assert!(matches!(deserialize_structure_from_bytes(&empty), Err(NbtError::MissingTag(tag)) if tag == "size"));
+    }
+
+    #[cfg(feature = "nbt")]
+    #[test]
+    fn structure_editing() {
+        let mut structure = Structure::new([3, 2, 1]).unwrap();
+        let log = BlockState::new("minecraft:oak_log").state("pillar_axis", Tag::String("y".to_string()));
+        assert!(structure.set_block(0, 0, 0, Some(log.clone())));
+        assert!(structure.set_block(0, 1, 0, Some(log)));
+        assert!(structure.set_block(2, 0, 0, Some(BlockState::new("minecraft:chest"))));
+        assert!(structure.set_block_entity(2, 0, 0, [("id".to_string(), Tag::String("Chest".to_string()))].into()));
+        assert!(!structure.set_block(3, 0, 0, None));
+        assert_eq!(structure.palette.len(), 2);
+
+        let bytes = serialize_structure_to_bytes(&structure).unwrap();
+        assert_eq!(deserialize_structure_from_bytes(&bytes).unwrap(), structure);
+
+        structure.origin = [100, 64, 100];
+        let pig = [("Pos".to_string(), Tag::List(vec![Tag::Float(100.5), Tag::Float(64.0), Tag::Float(100.5)]))].into();
+        structure.add_entity(pig);
+        structure.resize([1, 0, 0], [2, 3, 1]).unwrap();
+        assert_eq!((structure.size, structure.origin, structure.entities.len()), ([2, 3, 1], [101, 64, 100], 0));
+        assert_eq!(structure.block(1, 0, 0).map(|block| block.name.as_str()), Some("minecraft:chest"));
+        assert!(structure.block_entity(1, 0, 0).is_some());
+        assert_eq!(structure.block(0, 2, 0), None);
+
+        assert_eq!(structure.resize([0; 3], [-1, 3, 1]), Err(NbtError::InvalidSize([-1, 3, 1])));
+        assert_eq!(structure.resize([i32::MAX, 0, 0], [2, 3, 1]), Err(NbtError::InvalidOffset([i32::MAX, 0, 0])));
+        assert_eq!(structure.resize([0, i32::MAX - 3, 0], [2, 3, 1]), Err(NbtError::InvalidOffset([0, i32::MAX - 3, 0])));
+        assert_eq!(structure.volume(), Some(6));
+        assert_eq!(Structure::new([i32::MAX; 3]), Err(NbtError::InvalidSize([i32::MAX; 3])));
+        let crafted = |size: [i32; 3]| {
+            let Tag::Compound(mut root) = Structure::new([0; 3]).unwrap().to_nbt() else { unreachable!() };
+            root.insert("size".to_string(), Tag::List(size.iter().map(|axis| Tag::Int(*axis)).collect()));
+            let Some(Tag::Compound(body)) = root.get_mut("structure") else { unreachable!() };
+            body.insert("block_indices".to_string(), Tag::List(vec![]));
+            Structure::from_nbt(&Tag::Compound(root))
+        };
+        assert_eq!(crafted([i32::MAX; 3]), Err(NbtError::InvalidSize([i32::MAX; 3])));
+        assert_eq!(crafted([1024, 1024, 1024]), Err(NbtError::MissingTag("structure/block_indices".to_string())));
+        assert!(crafted([0, 5, 5]).is_ok());
+
+        let path = std::env::temp_dir().join("bedrockrs_addon_structure_editing.mcstructure");
+        write_structure_to_path(&structure, &path).unwrap();
+        assert_eq!(deserialize_structure_from_path(&path).unwrap(), structure);
+        fs::remove_file(path).unwrap();
+    }
+
+    #[cfg(feature = "nbt")]
+    #[test]
+    fn level_dat() {
+        let mut level = LevelDat::new("Ruby Valley");
+        level.set_spawn([0, 70, 16]);
+        level.set_flag("commandsEnabled", true);
+        level.set_game_rule("keepInventory", GameRule::Bool(true));
+        level.set_game_rule("randomtickspeed", GameRule::Int(3));
+        level.set_experiment("gametest", true);
+
+        let bytes = serialize_level_dat_to_bytes(&level).unwrap();
+        assert_eq!(bytes[..4], 10i32.to_le_bytes());
+        assert_eq!(bytes[4..8], ((bytes.len() - 8) as u32).to_le_bytes());
+        assert_eq!(deserialize_level_dat_from_bytes(&bytes[..bytes.len() - 1]), Err(NbtError::UnexpectedEnd));
+
+        let path = std::env::temp_dir().join("bedrockrs_addon_level.dat");
+        write_level_dat_to_path(&level, &path).unwrap();
+        let level = deserialize_level_dat_from_path(&path).unwrap();
+        fs::remove_file(path).unwrap();
+
+        assert_eq!((level.level_name(), level.spawn(), level.flag("commandsEnabled")), (Some("Ruby Valley"), Some([0, 70, 16]), Some(true)));
+        let rules: Vec<(String, GameRule)> = level.game_rules().into_iter().collect();
+        assert_eq!(rules, vec![("keepinventory".to_string(), GameRule::Bool(true)), ("randomtickspeed".to_string(), GameRule::Int(3))]);
+        assert_eq!((level.experiment("gametest"), level.experiment("experiments_ever_used"), level.experiment("data_driven_biomes")), (Some(true), Some(true), None));
+    }
+
+    #[cfg(feature = "nbt")]
+    #[test]
+    fn experiment_toggles() {
+        let mut level = LevelDat::new("Ruby Valley");
+        assert_eq!(level.experiments(), vec![]);
+        level.enable_experiment(&Experiment::BetaApis);
+        level.enable_experiment(&Experiment::HolidayCreatorFeatures);
+        level.enable_experiment(&Experiment::from_name("y_2025_drop_2"));
+        level.disable_experiment(&Experiment::HolidayCreatorFeatures);
+
+        let level = deserialize_level_dat_from_bytes(&serialize_level_dat_to_bytes(&level).unwrap()).unwrap();
+        assert_eq!(level.experiments(), vec![Experiment::BetaApis, Experiment::Custom("y_2025_drop_2".to_string())]);
+        assert_eq!(level.experiment("data_driven_items"), Some(false));
+        assert_eq!(Experiment::CustomBiomes.to_string(), "data_driven_biomes");
+    }
+
+    #[cfg(all(feature = "archive", feature = "manifest", feature = "nbt"))]
+    #[test]
+    fn world_template() {
+        let uuid = Uuid::parse_str("5e1b0b6c-1f0e-4c57-9d5a-5b0c8e1f2a3d").unwrap();
+        let module_uuid = Uuid::parse_str("0c8f3a4e-6b2d-4e1f-8a9c-7d6e5f4a3b2c").unwrap();
+        let mut template = WorldTemplate::new("Ruby Valley", uuid, module_uuid);
+        template.behavior_packs.push("./inputs/behavior_pack".into());
+        template.icon = Some(vec![0xFF, 0xD8, 0xFF, 0xD9]);
+        assert_eq!(template.manifest()["modules"][0]["type"], "world_template");
+
+        let archive = String::from_utf8_lossy(&write_mctemplate(&template, vec![]).unwrap()).to_string();
+        for name in ["manifest.json", "level.dat", "levelname.txt", "world_icon.jpeg", "world_resource_packs.json", "behavior_packs/behavior_pack/manifest.json"] {
+            assert!(archive.contains(name), "{}", name);
+        }
+        let manifest = deserialize_manifest_from_path(Path::new("./inputs/behavior_pack/manifest.json")).unwrap();
+        assert!(archive.contains(&format!("\"pack_id\": \"{}\"", manifest.header.uuid)));
+
+        template.resource_packs.push("./inputs/missing_pack".into());
+        assert!(matches!(write_mctemplate(&template, vec![]), Err(AddonError::Io { .. })));
+    }
+
+    #[cfg(all(feature = "archive", feature = "manifest"))]
+    #[test]
+    fn pack_info() {
+        let hex = |digest: [u8; 32]| digest.iter().map(|byte| format!("{:02x}", byte)).collect::<String>();
+        assert_eq!(hex(sha256(b"abc")), "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad");
+        assert_eq!(hex(sha256(&[b'a'; 1000])), "41edece42d63e8d9bf515a9ba6932e1c20cbc9f5a5d134645adb5db1b9737ea3");
+
+        let (info, archive) = build_pack_info(Path::new("./inputs/behavior_pack")).unwrap();
+        let manifest = deserialize_manifest_from_path(Path::new("./inputs/behavior_pack/manifest.json")).unwrap();
+        assert_eq!((info.uuid, info.version_string(), info.size), (manifest.header.uuid, manifest.header.version.to_string(), archive.len() as u64));
+        assert_eq!((info.has_scripts, info.raytracing_capable, info.hash), (false, false, sha256(&archive)));
+    }
+
+    #[cfg(feature = "java")]
+    #[test]
+    fn java_resource_pack_conversion() {
+        let output = std::env::temp_dir().join("bedrockrs_addon_java_resource_pack");
+        let _ = fs::remove_dir_all(&output);
+        let report = convert_java_resource_pack(Path::new("./inputs/java_resource_pack"), &output, Uuid::nil(), Uuid::max()).unwrap();
+
+        let converted: Vec<String> = report.converted.iter().map(|path| path.to_string_lossy().replace('\\', "/")).collect();
+        assert_eq!(converted, vec![
+            "manifest.json", "textures/blocks/planks_oak.png", "textures/blocks/water_still_grey.png", "textures/items/diamond.png",
+            "textures/blocks/ruby/ruby_block.png", "textures/items/ruby/ruby.png", "pack_icon.png", "textures/item_texture.json",
+            "textures/terrain_texture.json", "blocks.json", "texts/languages.json", "texts/en_US.lang"
+        ]);
+        let unsupported: Vec<(String, &str)> = report.unsupported.iter().map(|(path, reason)| (path.to_string_lossy().replace('\\', "/"), reason.as_str())).collect();
+        assert_eq!(unsupported, vec![
+            ("assets/minecraft/textures/block/water_still.png.mcmeta".to_string(), "texture animations aren't converted"),
+            ("assets/ruby/blockstates/ruby_block.json".to_string(), "block states have no resource pack equivalent"),
+            ("assets/ruby/models/block/ruby_stairs.json".to_string(), "models with parent \"block/stairs\" aren't converted")
+        ]);
+
+        let blocks: serde_json::Value = serde_json::from_str(&fs::read_to_string(output.join("blocks.json")).unwrap()).unwrap();
+        assert_eq!(blocks["ruby:ruby_block"]["textures"], "ruby_ruby_block");
+        let items: serde_json::Value = serde_json::from_str(&fs::read_to_string(output.join("textures/item_texture.json")).unwrap()).unwrap();
+        assert_eq!(items["texture_data"]["ruby_ruby"]["textures"], "textures/items/ruby/ruby");
+        assert_eq!(fs::read_to_string(output.join("texts/en_US.lang")).unwrap(), "block.ruby.ruby_block=Block of Ruby\nitem.ruby.ruby=Ruby\n");
+        deserialize_manifest_from_path(&output.join("manifest.json")).unwrap();
+        fs::remove_dir_all(output).unwrap();
+    }
+
+    #[cfg(feature = "java")]
+    #[test]
+    fn java_data_conversion() {
+        let java = serde_json::json!({
+            "type": "minecraft:chest",
+            "pools": [{
+                "rolls": { "type": "minecraft:uniform", "min": 1, "max": 3 },
+                "entries": [
+                    { "type": "minecraft:item", "name": "minecraft:diamond", "weight": 2,
+                      "functions": [{ "function": "minecraft:set_count", "count": { "min": 1, "max": 2 } }, { "function": "minecraft:set_nbt", "tag": "{}" }] },
+                    { "type": "minecraft:loot_table", "name": "minecraft:chests/lab" },
+                    { "type": "minecraft:tag", "name": "minecraft:wool" }
+                ],
+                "conditions": [{ "condition": "minecraft:random_chance", "chance": 0.5 }, { "condition": "minecraft:survives_explosion" }]
+            }]
+        });
+        let (table, diagnostics) = convert_java_loot_table(&java);
+        assert_eq!(table.pools[0].rolls, LootRange::Range { min: 1.0, max: 3.0 });
+        let entries: Vec<Option<&str>> = table.pools[0].entries.iter().map(|entry| entry.name.as_deref()).collect();
+        assert_eq!(entries, vec![Some("minecraft:diamond"), Some("loot_tables/chests/lab.json")]);
+        assert_eq!(table.pools[0].entries[0].functions[0].count, Some(LootRange::Range { min: 1.0, max: 2.0 }));
+        assert_eq!(table.pools[0].conditions.len(), 1);
+        let pointers: Vec<Option<&str>> = diagnostics.iter().map(|diagnostic| diagnostic.pointer.as_deref()).collect();
+        assert_eq!(pointers, vec![Some("/pools/0/entries/0/functions/1"), Some("/pools/0/entries/2"), Some("/pools/0/conditions/1")]);
+
+        let shaped = serde_json::json!({
+            "type": "minecraft:crafting_shaped",
+            "pattern": ["##", "#/"],
+            "key": { "#": { "item": "ruby:ruby" }, "/": { "tag": "minecraft:planks" } },
+            "result": { "id": "ruby:ruby_pickaxe", "count": 1 }
+        });
+        let (recipe, diagnostics) = convert_java_recipe("ruby:ruby_pickaxe", &shaped);
+        let recipe = recipe.unwrap();
+        assert_eq!(recipe["minecraft:recipe_shaped"]["key"], serde_json::json!({ "#": { "item": "ruby:ruby" } }));
+        assert_eq!(diagnostics.iter().map(|diagnostic| diagnostic.pointer.clone()).collect::<Vec<_>>(), vec![Some("/key/~1".to_string())]);
+
+        let blasting = serde_json::json!({ "type": "minecraft:blasting", "ingredient": { "item": "ruby:ruby_ore" }, "result": "ruby:ruby", "experience": 0.7 });
+        let (recipe, diagnostics) = convert_java_recipe("ruby:ruby_from_blasting", &blasting);
+        assert_eq!(recipe.unwrap()["minecraft:recipe_furnace"]["tags"], serde_json::json!(["blast_furnace"]));
+        assert_eq!(diagnostics.len(), 1);
+        let (recipe, _) = convert_java_recipe("ruby:trim", &serde_json::json!({ "type": "minecraft:smithing_trim" }));
+        assert_eq!(recipe, None);
+    }
+
+    #[cfg(feature = "behavior")]
+    #[test]
+    fn typescript_stubs() {
+        let root = std::env::temp_dir().join("bedrockrs_addon_typescript_stubs");
+        let _ = fs::remove_dir_all(&root);
+        fs::create_dir_all(root.join("entities")).unwrap();
+        let golem = serde_json::json!({
+            "format_version": "1.20.0",
+            "minecraft:entity": {
+                "description": {
+                    "identifier": "test:golem",
+                    "properties": {
+                        "test:size": { "type": "int", "range": [1, 3], "default": 1 },
+                        "test:mood": { "type": "enum", "values": ["calm", "angry"], "default": "calm" }
+                    }
+                },
+                "components": {},
+                "events": { "test:grow": {}, "test:calm_down": {} }
+            }
+        });
+        fs::write(root.join("entities/golem.json"), golem.to_string()).unwrap();
+        fs::copy("./inputs/behavior_pack/entities/pig.json", root.join("entities/pig.json")).unwrap();
+
+        let constants = ScriptConstants::collect(&Addon::new(Some(root.clone()), None));
+        assert_eq!(constants.entities["test:golem"].properties["test:mood"], PropertyType::Enum(vec!["calm".to_string(), "angry".to_string()]));
+        assert_eq!(serde_json::to_value(&constants).unwrap()["entities"]["test:golem"]["properties"]["test:size"], serde_json::json!({ "type": "int" }));
+        assert_eq!(constants.to_typescript(), [
+            "// Generated from the behavior pack; changes will be overwritten.",
+            "",
+            "export type EntityId = \"test:golem\" | \"test:pig\";",
+            "export type ItemId = never;",
+            "export type BlockId = never;",
+            "",
+            "export interface EntityEvents {",
+            "  \"test:golem\": \"test:calm_down\" | \"test:grow\";",
+            "  \"test:pig\": \"test:become_angry\";",
+            "}",
+            "",
+            "export interface EntityProperties {",
+            "  \"test:golem\": { \"test:mood\": \"calm\" | \"angry\"; \"test:size\": number };",
+            "  \"test:pig\": {};",
+            "}",
+            ""
+        ].join("\n"));
+        fs::remove_dir_all(root).unwrap();
+
+        let constants = ScriptConstants::collect(&Addon::new(Some("./inputs/behavior_pack".into()), None));
+        assert_eq!(constants.items.iter().collect::<Vec<_>>(), vec!["gems:Sapphire", "test:emerald_shard", "test:ruby"]);
+        assert_eq!(constants.blocks.iter().collect::<Vec<_>>(), vec!["test:ruby_block", "test:ruby_ore"]);
+    }
+
+    #[cfg(all(feature = "behavior", feature = "jsonc", feature = "manifest"))]
+    #[test]
+    fn jsonc_tolerance() {
+        let src = "{\n  // the pack's name\n  \"url\": \"https://example.com/*x*/\", /* inline */\n  \"list\": [1, 2,],\n  \"quote\": \"a\\\"//b\",\n}\n";
+        let stripped = strip_jsonc(src);
+        assert_eq!(stripped.len(), src.len());
+        assert_eq!(stripped.lines().count(), src.lines().count());
+        let value: serde_json::Value = serde_json::from_str(&stripped).unwrap();
+        assert_eq!(value, serde_json::json!({ "url": "https://example.com/*x*/", "list": [1, 2], "quote": "a\"//b" }));
+        assert!(matches!(strip_jsonc("{\"a\": 1}"), Cow::Borrowed(_)));
+
+        let manifest = fs::read_to_string("./inputs/manifest.json").unwrap();
+        let commented = manifest.replacen("{", "{\n  // generated\n", 1).replacen("]", ",]", 1);
+        assert_eq!(deserialize_manifest_from_str(&commented).unwrap().header.uuid, deserialize_manifest_from_str(&manifest).unwrap().header.uuid);
+        let table = deserialize_loot_table_from_str("{ \"pools\": [ /* none yet */ ], }").unwrap();
+        assert!(table.pools.is_empty());
+    }
+
+    #[cfg(feature = "authoring")]
+    #[test]
+    fn toml_authoring() {
+        let out = std::env::temp_dir().join("bedrockrs_addon_authoring");
+        let _ = fs::remove_dir_all(&out);
+        let mut written = export_authored_pack(Path::new("./inputs/authored_pack"), &out).unwrap();
+        written.sort();
+        let written: Vec<String> = written.iter().map(|path| path.to_string_lossy().replace('\\', "/")).collect();
+        assert_eq!(written, ["items/ruby.json", "texts/en_US.lang"]);
+
+        let item: serde_json::Value = serde_json::from_str(&fs::read_to_string(out.join("items/ruby.json")).unwrap()).unwrap();
+        assert_eq!(item, serde_json::json!({
+            "format_version": "1.20.50",
+            "minecraft:item": {
+                "description": { "identifier": "ruby:ruby", "menu_category": { "category": "items" } },
+                "components": {
+                    "minecraft:max_stack_size": 64,
+                    "minecraft:glint": true,
+                    "minecraft:display_name": { "value": "item.ruby:ruby.name" },
+                    "minecraft:fuel": { "duration": 1000.5 },
+                    "minecraft:tags": { "tags": [{ "name": "ruby:gem" }, { "name": "ruby:shiny" }] }
+                }
+            }
+        }));
+        assert_eq!(fs::read_to_string(out.join("texts/en_US.lang")).unwrap(), "item.ruby:ruby.name=Ruby\n");
+        fs::remove_dir_all(&out).unwrap();
+
+        assert_eq!(parse_toml_from_str("a = [1, -2.5e1, 'x',\n]\nb = 1979-05-27").unwrap(), serde_json::json!({ "a": [1, -25.0, "x"], "b": "1979-05-27" }));
+        assert_eq!(parse_toml_from_str("a = 1\na = 2"), Err(TomlError { line: 2, message: "`a` is defined twice".to_string() }));
+        assert_eq!(parse_toml_from_str("a = \"open").unwrap_err().line, 1);
+    }
+
+    #[cfg(feature = "snapshot")]
+    #[test]
+    fn addon_snapshot() {
+        let addon = Addon::new(Some("./inputs/behavior_pack".into()), Some("./inputs/resource_pack".into()));
+        let snapshot = AddonSnapshot::capture(&addon);
+        assert_eq!(snapshot.packs.iter().map(|pack| pack.kind).collect::<Vec<_>>(), [PackKind::Behavior, PackKind::Resource]);
+        assert_eq!(snapshot.json(PackKind::Behavior, "manifest.json").unwrap()["format_version"], 2);
+        let behavior_pack = snapshot.pack(PackKind::Behavior).unwrap();
+        assert_eq!(behavior_pack.entities().map(|(identifier, entity)| (identifier, entity["components"].is_object())).collect::<Vec<_>>(), [("test:pig", true)]);
+        assert_eq!(behavior_pack.items().map(|(identifier, _)| identifier).collect::<Vec<_>>(), ["test:emerald_shard", "test:ruby", "test:ruby", "gems:Sapphire"]);
+        assert_eq!(behavior_pack.blocks().count(), 2);
+        assert_eq!(behavior_pack.recipes().filter(|(identifier, _)| *identifier == "test:ruby_hoe").count(), 2);
+
+        let bytes = serialize_snapshot_to_bytes(&snapshot);
+        assert_eq!(&bytes[..5], b"BRAS\x01");
+        assert_eq!(deserialize_snapshot_from_bytes(&bytes).unwrap(), snapshot);
+
+        let path = std::env::temp_dir().join("bedrockrs_addon_snapshot.bin");
+        write_snapshot_to_path(&snapshot, &path).unwrap();
+        assert_eq!(deserialize_snapshot_from_path(&path).unwrap(), snapshot);
+        fs::remove_file(&path).unwrap();
+
+        assert_eq!(deserialize_snapshot_from_bytes(b"{}"), Err(SnapshotError::InvalidMagic));
+        assert_eq!(deserialize_snapshot_from_bytes(b"BRAS\x09"), Err(SnapshotError::UnsupportedVersion(9)));
+        assert_eq!(deserialize_snapshot_from_bytes(&bytes[..bytes.len() - 1]), Err(SnapshotError::UnexpectedEnd));
+    }
+
+    #[cfg(all(feature = "archive", feature = "manifest", feature = "snapshot"))]
+    #[test]
+    fn in_memory_packs() {
+        let archive = write_mcpack(Path::new("./inputs/behavior_pack"), vec![]).unwrap();
+        let files = deserialize_mcpack_from_reader(archive.as_slice()).unwrap();
+        assert_eq!(files["manifest.json"], fs::read("./inputs/behavior_pack/manifest.json").unwrap());
+        assert_eq!(files["structures/ruby_hut.mcstructure"], fs::read("./inputs/behavior_pack/structures/ruby_hut.mcstructure").unwrap());
+
+        let manifest = deserialize_manifest_from_bytes(&files["manifest.json"]).unwrap();
+        assert_eq!(manifest.header.uuid, deserialize_manifest_from_path(Path::new("./inputs/behavior_pack/manifest.json")).unwrap().header.uuid);
+        assert!(deserialize_manifest_from_reader(&b"{"[..]).is_err());
+
+        let pack = PackSnapshot::from_files(PackKind::Behavior, &files);
+        let captured = AddonSnapshot::capture(&Addon::new(Some("./inputs/behavior_pack".into()), None));
+        assert_eq!(pack.json.len(), captured.packs[0].json.len());
+        assert_eq!(pack.files.len(), captured.packs[0].files.len());
+
+        // Written by another zip tool: deflated entries with fixed and dynamic codes, a stored one
+        // and a directory entry.
+        let files = deserialize_mcpack_from_path("./inputs/deflated.mcpack").unwrap();
+        assert_eq!(files.keys().collect::<Vec<_>>(), ["manifest.json", "pack_icon.png", "small.txt", "texts/en_US.lang"]);
+        assert_eq!(files["manifest.json"], fs::read("./inputs/manifest.json").unwrap());
+        assert_eq!(files["small.txt"], b"hi");
+        assert_eq!(files["pack_icon.png"].len(), 768);
+        let lang = String::from_utf8(files["texts/en_US.lang"].clone()).unwrap();
+        assert_eq!((lang.lines().count(), lang.lines().last()), (41, Some("tile.ruby:ore.name=Ruby Ore")));
+
+        let bytes = fs::read("./inputs/deflated.mcpack").unwrap();
+        assert!(matches!(deserialize_mcpack_from_bytes(&bytes[..100]), Err(AddonError::Archive { .. })));
+        let mut corrupt = bytes.clone();
+        corrupt[60] ^= 0xff;
+        assert!(matches!(deserialize_mcpack_from_bytes(&corrupt), Err(AddonError::Archive { path, .. }) if path == Path::new("manifest.json")));
+
+        // A central directory claiming a 4 GiB entry fails before anything is inflated.
+        let record = bytes.windows(4).position(|window| window == [0x50, 0x4b, 0x01, 0x02]).unwrap();
+        let mut bomb = bytes.clone();
+        bomb[record + 24..record + 28].copy_from_slice(&u32::MAX.to_le_bytes());
+        assert!(matches!(deserialize_mcpack_from_bytes(&bomb), Err(AddonError::Archive { message, .. }) if message.contains("limit")));
+        let limited = |limits: ArchiveLimits| deserialize_mcpack_from_bytes_with_limits(&bytes, &limits);
+        assert!(limited(ArchiveLimits { max_entry_size: 512, ..ArchiveLimits::default() }).is_err());
+        assert!(limited(ArchiveLimits { max_total_size: 1024, ..ArchiveLimits::default() }).is_err());
+        assert!(limited(ArchiveLimits { max_entries: 2, ..ArchiveLimits::default() }).is_err());
+        assert_eq!(limited(ArchiveLimits::unlimited()).unwrap(), files);
+
+        let mut reader = open_mcpack("./inputs/deflated.mcpack", &ArchiveLimits::default()).unwrap();
+        assert_eq!(reader.entries().iter().filter(|entry| entry.is_directory()).count(), 1);
+        assert_eq!(reader.entry("pack_icon.png").map(|entry| entry.size), Some(768));
+        assert_eq!(reader.read("small.txt").unwrap().as_deref(), Some(&b"hi"[..]));
+        assert_eq!(reader.read("texts/").unwrap(), None);
+        let mut reader = open_mcpack("./inputs/deflated.mcpack", &ArchiveLimits { max_entry_size: 512, ..ArchiveLimits::default() }).unwrap();
+        assert_eq!(reader.read("small.txt").unwrap().as_deref(), Some(&b"hi"[..]));
+        assert!(reader.read("pack_icon.png").is_err());
+    }
+
+    #[cfg(feature = "ffi")]
+    #[test]
+    fn c_abi() {
+        use std::ffi::{CStr, CString};
+
+        let take = |string: *mut std::ffi::c_char| unsafe {
+            let owned = CStr::from_ptr(string).to_str().unwrap().to_string();
+            bedrockrs_string_free(string);
+            owned
+        };
+        let manifest = CString::new(fs::read_to_string("./inputs/manifest.json").unwrap()).unwrap();
+        let parsed = take(unsafe { bedrockrs_manifest_parse(manifest.as_ptr()) });
+        assert_eq!(parsed, serialize_manifest_to_string(&deserialize_manifest_from_str(manifest.to_str().unwrap()).unwrap()));
+
+        let invalid = CString::new("{").unwrap();
+        assert!(unsafe { bedrockrs_manifest_parse(invalid.as_ptr()) }.is_null());
+        let error = unsafe { CStr::from_ptr(bedrockrs_last_error()) }.to_str().unwrap();
+        assert!(error.starts_with("manifest.json: invalid JSON"));
+        assert!(unsafe { bedrockrs_manifest_parse(std::ptr::null()) }.is_null());
+        assert_eq!(catch_panic(|| -> i32 { panic!("broken invariant") }), None);
+        assert_eq!(unsafe { CStr::from_ptr(bedrockrs_last_error()) }.to_str().unwrap(), "panic: broken invariant");
+
+        let behavior = CString::new("./inputs/behavior_pack").unwrap();
+        let diagnostics: serde_json::Value = serde_json::from_str(&take(unsafe { bedrockrs_validate_addon(behavior.as_ptr(), std::ptr::null()) })).unwrap();
+        assert!(diagnostics.is_array());
+
+        let dir = std::env::temp_dir().join("bedrockrs_addon_ffi");
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        let archive = CString::new(dir.join("pack.mcpack").to_string_lossy().as_ref()).unwrap();
+        let output = CString::new(dir.join("out").to_string_lossy().as_ref()).unwrap();
+        assert_eq!(unsafe { bedrockrs_mcpack_write(behavior.as_ptr(), archive.as_ptr()) }, 0);
+        assert_eq!(unsafe { bedrockrs_mcpack_extract(archive.as_ptr(), output.as_ptr()) }, 0);
+        assert_eq!(fs::read(dir.join("out/manifest.json")).unwrap(), fs::read("./inputs/behavior_pack/manifest.json").unwrap());
+        assert_eq!(unsafe { bedrockrs_mcpack_extract(output.as_ptr(), archive.as_ptr()) }, -1);
+        let limited = CString::new(dir.join("limited").to_string_lossy().as_ref()).unwrap();
+        assert_eq!(unsafe { bedrockrs_mcpack_extract_with_limits(archive.as_ptr(), limited.as_ptr(), 0, 0, 3) }, -1);
+        assert!(unsafe { CStr::from_ptr(bedrockrs_last_error()) }.to_str().unwrap().contains("limit of 3"));
+        assert!(!dir.join("limited").exists());
+        assert_eq!(unsafe { bedrockrs_mcpack_extract_with_limits(archive.as_ptr(), limited.as_ptr(), 0, 0, 0) }, 0);
+
+        let mut evil = crate::generics::mcpack::McpackWriter::new(vec![]);
+        evil.add_file("../escaped.txt", &mut &b"x"[..]).unwrap();
+        let long = "a".repeat(u16::MAX as usize + 1);
+        assert_eq!(evil.add_file(&long, &mut &b"x"[..]).map_err(|error| error.kind()), Err(std::io::ErrorKind::InvalidInput));
+        fs::write(dir.join("evil.mcpack"), evil.finish().unwrap()).unwrap();
+        assert!(matches!(extract_mcpack(dir.join("evil.mcpack"), dir.join("evil")), Err(AddonError::Archive { .. })));
+        assert!(!dir.join("escaped.txt").exists());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[cfg(all(feature = "scaffold", feature = "validation"))]
+    #[test]
+    fn scaffolding() {
+        let root = std::env::temp_dir().join("bedrockrs_addon_scaffold");
+        let _ = fs::remove_dir_all(&root);
+        let options = ScaffoldOptions { example_entity: true, example_item: true, ..ScaffoldOptions::new(&root, "Gems", "gems") };
+        let addon = new_addon(&options).unwrap();
+
+        let (bp, rp) = (addon.behavior_pack.clone().unwrap(), addon.resource_pack.clone().unwrap());
+        let bp_manifest = deserialize_manifest_from_path(&bp.join("manifest.json")).unwrap();
+        let rp_manifest = deserialize_manifest_from_path(&rp.join("manifest.json")).unwrap();
+        let uuid_dependency = |manifest: &Manifest| manifest.dependencies.iter().find_map(|dependency| match dependency {
+            ManifestDependency::UuidDependency(uuid, _) => Some(*uuid),
+            _ => None
+        });
+        assert_eq!(uuid_dependency(&bp_manifest), Some(rp_manifest.header.uuid));
+        assert_eq!(uuid_dependency(&rp_manifest), Some(bp_manifest.header.uuid));
+        assert_ne!(bp_manifest.header.uuid, rp_manifest.header.uuid);
+        assert_eq!(bp_manifest.modules.len(), 2);
+        assert!(fs::read_to_string(bp.join("scripts/main.js")).unwrap().contains("\"Gems is running\""));
+        assert_eq!(fs::read_to_string(bp.join("texts/en_US.lang")).unwrap(), "pack.name=Gems\npack.description=\n");
+
+        let run = validate_addon(&AddonPaths { behavior_pack: Some(&bp), resource_pack: Some(&rp), ..AddonPaths::default() }, 1);
+        assert_eq!(format_plain(&run.diagnostics.into_vec()), "");
+
+        assert!(matches!(new_addon(&options), Err(AddonError::Semantic { .. })));
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[cfg(feature = "validation")]
+    #[test]
+    fn vanilla_packs() {
+        let vanilla = VanillaPacks::locate(Path::new("./inputs/bedrock_samples")).unwrap();
+        assert_eq!(vanilla.behavior_pack.as_deref(), Some(Path::new("./inputs/bedrock_samples/behavior_pack")));
+        assert_eq!(vanilla.resource_pack.as_deref(), Some(Path::new("./inputs/bedrock_samples/resource_pack")));
+        assert_eq!(vanilla.version, Some(SemVer::new(1, 21, 50)));
+        assert_eq!(vanilla.addon().packs().len(), 2);
+
+        let paths = AddonPaths { behavior_pack: Some(Path::new("./inputs/behavior_pack")), ..AddonPaths::default() }.with_vanilla(&vanilla);
+        assert_eq!(paths.vanilla_behavior_pack, vanilla.behavior_pack.as_deref());
+        assert_eq!(paths.vanilla_resource_pack, vanilla.resource_pack.as_deref());
+
+        // The extracted vanilla pack archives: any folder with a pack manifest.
+        let root = std::env::temp_dir().join("bedrockrs_addon_vanilla");
+        let _ = fs::remove_dir_all(&root);
+        fs::create_dir_all(root.join("Vanilla_Resource_Pack_1.21.50")).unwrap();
+        fs::copy("./inputs/bedrock_samples/resource_pack/manifest.json", root.join("Vanilla_Resource_Pack_1.21.50/manifest.json")).unwrap();
+        let vanilla = VanillaPacks::locate(&root).unwrap();
+        assert_eq!((vanilla.behavior_pack, vanilla.resource_pack), (None, Some(root.join("Vanilla_Resource_Pack_1.21.50"))));
+        assert!(matches!(VanillaPacks::locate(&root.join("Vanilla_Resource_Pack_1.21.50/missing")), Err(AddonError::Semantic { .. })));
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[cfg(feature = "derive")]
+    #[test]
+    fn content_in_rust() {
+        #[derive(serde::Serialize)]
+        struct Fuel {
+            duration: f64
+        }
+
+        #[derive(crate::Item)]
+        #[item(identifier = "gems:ruby", name = "Ruby", texture = "./inputs/resource_pack/pack_icon.png", category = "items")]
+        struct Ruby {
+            max_stack_size: u32,
+            #[component("minecraft:fuel")]
+            fuel: Fuel
+        }
+
+        #[derive(crate::Block)]
+        #[block(identifier = "gems:ruby_ore", name = "Ruby Ore", texture = "./inputs/resource_pack/pack_icon.png")]
+        struct RubyOre;
+
+        #[derive(crate::Entity)]
+        #[entity(identifier = "gems:golem", spawnable = true)]
+        struct Golem {
+            health: serde_json::Value
+        }
+
+        let ruby = Ruby { max_stack_size: 16, fuel: Fuel { duration: 2.5 } };
+        let definition = ruby.definition();
+        assert_eq!(definition, ItemDefinition {
+            identifier: "gems:ruby".to_string(), name: Some("Ruby".to_string()), texture: Some("./inputs/resource_pack/pack_icon.png".into()),
+            category: Some("items".to_string()),
+            components: serde_json::from_value(serde_json::json!({ "minecraft:max_stack_size": 16, "minecraft:fuel": { "duration": 2.5 } })).unwrap()
+        });
+        assert_eq!(RubyOre.definition().name.as_deref(), Some("Ruby Ore"));
+        assert!(Golem { health: serde_json::json!({ "value": 20 }) }.definition().spawnable);
+
+        let root = std::env::temp_dir().join("bedrockrs_addon_content");
+        let _ = fs::remove_dir_all(&root);
+        let (bp, rp) = (root.join("bp"), root.join("rp"));
+        fs::create_dir_all(rp.join("texts")).unwrap();
+        fs::write(rp.join("texts/en_US.lang"), "pack.name=Gems\nitem.gems:ruby.name=Old Ruby\n").unwrap();
+
+        let project = AddonProject::new().item(&ruby).block(&RubyOre).entity(&Golem { health: serde_json::json!({ "value": 20 }) })
+            .recipe(RecipeBuilder::shapeless("gems:ruby_from_ore", &["gems:ruby_ore"], "gems:ruby").build());
+        let mut written: Vec<String> = project.write(&bp, &rp).unwrap().iter()
+            .map(|path| path.strip_prefix(&root).unwrap().to_string_lossy().replace('\\', "/")).collect();
+        written.sort();
+        assert_eq!(written, [
+            "bp/blocks/ruby_ore.json", "bp/entities/golem.json", "bp/items/ruby.json", "bp/recipes/ruby_from_ore.json",
+            "rp/texts/en_US.lang", "rp/texts/languages.json", "rp/textures/blocks/ruby_ore.png", "rp/textures/item_texture.json",
+            "rp/textures/items/ruby.png", "rp/textures/terrain_texture.json"
+        ]);
+
+        let item: serde_json::Value = serde_json::from_str(&fs::read_to_string(bp.join("items/ruby.json")).unwrap()).unwrap();
+        assert_eq!(item["minecraft:item"]["components"]["minecraft:icon"], serde_json::json!({ "texture": "gems_ruby" }));
+        assert_eq!(item["minecraft:item"]["components"]["minecraft:max_stack_size"], 16);
+        let atlas: serde_json::Value = serde_json::from_str(&fs::read_to_string(rp.join("textures/item_texture.json")).unwrap()).unwrap();
+        assert_eq!(atlas["texture_data"]["gems_ruby"]["textures"], "textures/items/ruby");
+        assert_eq!(fs::read_to_string(rp.join("texts/en_US.lang")).unwrap(), "pack.name=Gems\nitem.gems:ruby.name=Ruby\ntile.gems:ruby_ore.name=Ruby Ore\n");
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[cfg(all(feature = "archive", feature = "content", feature = "manifest"))]
+    #[test]
+    fn export_styles() {
+        let manifest = deserialize_manifest_from_path(Path::new("./inputs/manifest.json")).unwrap();
+        let minified = serialize_manifest_to_string_with_style(&manifest, &JsonStyle::minified());
+        assert!(!minified.contains('\n') && minified.starts_with("{\"capabilities\":"));
+        let pretty = serialize_manifest_to_string_with_style(&manifest, &JsonStyle::pretty(4));
+        assert!(pretty.contains("\n    \"header\": {\n        \""));
+        assert_eq!(serialize_manifest_to_string_with_style(&manifest, &JsonStyle::default()), serialize_manifest_to_string(&manifest));
+
+        let archive = write_mcpack_with_style(Path::new("./inputs/behavior_pack_patch"), vec![], &JsonStyle::minified()).unwrap();
+        let files = deserialize_mcpack_from_bytes(&archive).unwrap();
+        let plain = deserialize_mcpack_from_bytes(&write_mcpack(Path::new("./inputs/behavior_pack_patch"), vec![]).unwrap()).unwrap();
+        assert_eq!(files.keys().collect::<Vec<_>>(), plain.keys().collect::<Vec<_>>());
+        for (name, contents) in &files {
+            if name.ends_with(".json") {
+                assert!(!contents.contains(&b'\n'), "{} isn't minified", name);
+                let value: serde_json::Value = serde_json::from_slice(contents).unwrap();
+                assert_eq!(value, crate::utils::from_json_str::<serde_json::Value>(std::str::from_utf8(&plain[name]).unwrap()).unwrap());
+            } else {
+                assert_eq!(contents, &plain[name]);
+            }
+        }
+
+        let root = std::env::temp_dir().join("bedrockrs_addon_export_styles");
+        let _ = fs::remove_dir_all(&root);
+        let project = AddonProject::new().recipe(RecipeBuilder::shapeless("gems:ruby_from_ore", &["gems:ruby_ore"], "gems:ruby").build());
+        project.write_with_style(&root.join("bp"), &root.join("rp"), &JsonStyle::pretty(4)).unwrap();
+        let recipe = fs::read_to_string(root.join("bp/recipes/ruby_from_ore.json")).unwrap();
+        assert_eq!(JsonStyle::detect(&recipe), JsonStyle::pretty(4));
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[cfg(feature = "textures")]
+    #[test]
+    fn texture_atlas() {
+        let atlas = AtlasBuilder::new().directory(Path::new("./inputs/textures/icons"), "gems_").build().unwrap();
+        assert_eq!((atlas.width, atlas.height, atlas.columns), (32, 32, 2));
+        assert_eq!(atlas.tiles, [
+            AtlasTile { name: "gems_emerald".to_string(), index: 0, x: 0, y: 0, width: 8, height: 8 },
+            AtlasTile { name: "gems_ruby".to_string(), index: 1, x: 16, y: 0, width: 16, height: 16 },
+            AtlasTile { name: "gems_sapphire".to_string(), index: 2, x: 0, y: 16, width: 16, height: 16 }
+        ]);
+        let pixel = |rgba: &[u8], x: usize, y: usize| rgba[(y * 32 + x) * 4..(y * 32 + x) * 4 + 4].to_vec();
+        // An 8-bit gray image, an RGBA image with every filter type and a 4-bit palette image.
+        assert_eq!(pixel(&atlas.rgba, 3, 0), [96, 96, 96, 255]);
+        assert_eq!(pixel(&atlas.rgba, 10, 0), [0, 0, 0, 0]);
+        assert_eq!(pixel(&atlas.rgba, 21, 2), [80, 32, 40, 255]);
+        assert_eq!(pixel(&atlas.rgba, 20, 4), [64, 64, 40, 0]);
+        assert_eq!(pixel(&atlas.rgba, 1, 18), [90, 120, 250, 255]);
+        assert_eq!(pixel(&atlas.rgba, 0, 16), [0, 0, 0, 0]);
+        assert_eq!(crate::png::decode_png(&atlas.png()).unwrap(), (32, 32, atlas.rgba.clone()));
+        let (width, height, _) = crate::png::decode_png(&fs::read("./inputs/resource_pack/textures/blocks/ruby_lamp.png").unwrap()).unwrap();
+        assert_eq!((width, height), (16, 48));
+
+        let root = std::env::temp_dir().join("bedrockrs_addon_texture_atlas");
+        let _ = fs::remove_dir_all(&root);
+        fs::create_dir_all(root.join("textures")).unwrap();
+        fs::copy("./inputs/resource_pack/textures/item_texture.json", root.join("textures/item_texture.json")).unwrap();
+        let written = atlas.write(&root, AtlasKind::Items, "textures/atlas/gems").unwrap();
+        assert_eq!(written, [root.join("textures/atlas/gems.png"), root.join("textures/item_texture.json")]);
+        let items: serde_json::Value = serde_json::from_str(&fs::read_to_string(root.join("textures/item_texture.json")).unwrap()).unwrap();
+        assert_eq!(items["texture_data"]["gems_sapphire"], serde_json::json!({ "textures": { "path": "textures/atlas/gems", "tile_index": 2 } }));
+        assert!(items["texture_data"].as_object().unwrap().len() > 3);
+        fs::write(root.join("textures/terrain_texture.json"), "[]").unwrap();
+        let error = atlas.write(&root, AtlasKind::Terrain, "textures/atlas/blocks").unwrap_err();
+        assert_eq!(error.to_string(), format!("{}: texture atlas is not a JSON object", root.join("textures/terrain_texture.json").display()));
+        assert!(!root.join("textures/atlas/blocks.png").exists());
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[cfg(feature = "textures")]
+    #[test]
+    fn flipbook_frames() {
+        let flipbook = Flipbook::from_directory(Path::new("./inputs/textures/frames")).unwrap();
+        assert_eq!((flipbook.size, flipbook.frames), (4, 3));
+        // frame_1, frame_2 and then frame_10, red, green and blue.
+        let colors: Vec<&[u8]> = flipbook.rgba.chunks(4 * 4 * 4).map(|frame| &frame[..4]).collect();
+        assert_eq!(colors, [[255, 0, 0, 255], [0, 255, 0, 255], [0, 0, 255, 255]]);
+        assert_eq!(crate::png::decode_png(&flipbook.png()).unwrap(), (4, 12, flipbook.rgba.clone()));
+        let error = Flipbook::from_frames(&["./inputs/textures/frames/frame_1.png".into(), "./inputs/textures/icons/emerald.png".into()]).unwrap_err();
+        assert!(error.to_string().contains("unlike the 4x4 frames before it"), "{}", error);
+
+        let root = std::env::temp_dir().join("bedrockrs_addon_flipbook_frames");
+        let _ = fs::remove_dir_all(&root);
+        fs::create_dir_all(root.join("textures")).unwrap();
+        fs::copy("./inputs/resource_pack/textures/flipbook_textures.json", root.join("textures/flipbook_textures.json")).unwrap();
+        flipbook.write(&root, "textures/blocks/ruby_lamp", "ruby_lamp", 2).unwrap();
+        flipbook.write(&root, "textures/blocks/ruby_core", "ruby_core", 5).unwrap();
+        let entries: serde_json::Value = serde_json::from_str(&fs::read_to_string(root.join("textures/flipbook_textures.json")).unwrap()).unwrap();
+        assert_eq!(entries, serde_json::json!([
+            { "flipbook_texture": "textures/blocks/ruby_lamp", "atlas_tile": "ruby_lamp", "ticks_per_frame": 2, "frames": [0, 1, 2] },
+            { "flipbook_texture": "textures/blocks/ruby_core", "atlas_tile": "ruby_core", "ticks_per_frame": 5, "frames": [0, 1, 2] }
+        ]));
+        assert!(root.join("textures/blocks/ruby_core.png").exists());
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[cfg(feature = "resource")]
+    #[test]
+    fn generated_sound_definitions() {
+        let root = std::env::temp_dir().join("bedrockrs_addon_sound_definitions");
+        let _ = fs::remove_dir_all(&root);
+        for file in ["mob/pig/say1.ogg", "mob/pig/say2.ogg", "gems/chime1.ogg", "gems/chime_2.ogg", "gems/break.wav", "readme.txt"] {
+            let path = root.join("sounds").join(file);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(path, []).unwrap();
+        }
+        fs::copy("./inputs/resource_pack/sounds/sound_definitions.json", root.join("sounds/sound_definitions.json")).unwrap();
+
+        let folders = scan_sound_files(&root, &SoundScanOptions { naming: SoundNaming::Folder, ..SoundScanOptions::default() });
+        assert_eq!(folders.keys().collect::<Vec<_>>(), ["gems", "mob.pig"]);
+        assert_eq!(folders["gems"], ["sounds/gems/break", "sounds/gems/chime1", "sounds/gems/chime_2"]);
+
+        let added = write_sound_definitions(&root, &SoundScanOptions { category: "block".to_string(), ..SoundScanOptions::default() }).unwrap();
+        assert_eq!(added, ["gems.break", "gems.chime"]);
+        let file: serde_json::Value = serde_json::from_str(&fs::read_to_string(root.join("sounds/sound_definitions.json")).unwrap()).unwrap();
+        assert_eq!(file["sound_definitions"]["gems.chime"], serde_json::json!({ "category": "block", "sounds": ["sounds/gems/chime1", "sounds/gems/chime_2"] }));
+        // The hand-written event keeps its single file.
+        assert_eq!(file["sound_definitions"]["mob.pig.say"], serde_json::json!({ "category": "neutral", "sounds": ["sounds/mob/pig/say1"] }));
+        assert!(write_sound_definitions(&root, &SoundScanOptions::default()).unwrap().is_empty());
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[cfg(feature = "validation")]
+    #[test]
+    fn textures_list() {
+        assert_eq!(list_textures(Path::new("./inputs/resource_pack")), [
+            "textures/blocks/ruby_bricks", "textures/blocks/ruby_lamp", "textures/blocks/ruby_ore", "textures/entity/pig"
+        ]);
+
+        let root = std::env::temp_dir().join("bedrockrs_addon_textures_list");
+        let _ = fs::remove_dir_all(&root);
+        fs::create_dir_all(root.join("textures/items")).unwrap();
+        fs::copy("./inputs/textures/icons/ruby.png", root.join("textures/items/ruby.png")).unwrap();
+        fs::copy("./inputs/textures/icons/emerald.png", root.join("textures/items/emerald.png")).unwrap();
+        assert_eq!(write_textures_list(&root).unwrap(), ["textures/items/emerald", "textures/items/ruby"]);
+        assert_eq!(fs::read_to_string(root.join("textures/textures_list.json")).unwrap(), "[\n  \"textures/items/emerald\",\n  \"textures/items/ruby\"\n]\n");
+        assert!(validate_textures_list(&root).is_empty());
+
+        fs::remove_file(root.join("textures/items/emerald.png")).unwrap();
+        let stale = validate_textures_list(&root);
+        assert_eq!(stale, [UnresolvedReference {
+            kind: ReferenceKind::TexturesListEntry, file: "textures/textures_list.json".into(), pointer: "/0".to_string(), reference: "textures/items/emerald".to_string()
+        }]);
+        let diagnostic = Diagnostic::from(stale[0].clone());
+        assert_eq!((diagnostic.severity, diagnostic.code), (Severity::Warning, "stale-textures-list"));
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[cfg(all(feature = "behavior", feature = "resource"))]
+    #[test]
+    fn generated_lang_keys() {
+        assert_eq!(title_case("gems:ruby_ore"), "Ruby Ore");
+        assert_eq!(title_case("gems:tools.ruby-pickaxe"), "Tools Ruby Pickaxe");
+
+        let root = std::env::temp_dir().join("bedrockrs_addon_lang_keys");
+        let _ = fs::remove_dir_all(&root);
+        fs::create_dir_all(root.join("rp/texts")).unwrap();
+        fs::copy("./inputs/resource_pack/texts/en_US.lang", root.join("rp/texts/en_US.lang")).unwrap();
+        let added = write_missing_lang_keys(Path::new("./inputs/behavior_pack"), &root.join("rp")).unwrap();
+        assert_eq!(added.into_iter().collect::<Vec<_>>(), [
+            ("item.gems:Sapphire.name".to_string(), "Sapphire".to_string()),
+            ("item.spawn_egg.entity.test:pig.name".to_string(), "Spawn Pig".to_string()),
+            ("item.test:emerald_shard.name".to_string(), "Emerald Shard".to_string()),
+            ("tile.test:ruby_block.name".to_string(), "Ruby Block".to_string())
+        ]);
+        let lang = fs::read_to_string(root.join("rp/texts/en_US.lang")).unwrap();
+        assert!(lang.contains("item.test:ruby.name=Ruby\t## shown in the inventory\n") && lang.ends_with("tile.test:ruby_block.name=Ruby Block\n"));
+        assert!(root.join("rp/texts/languages.json").exists());
+        assert!(write_missing_lang_keys(Path::new("./inputs/behavior_pack"), &root.join("rp")).unwrap().is_empty());
+
+        fs::create_dir_all(root.join("bp/entities")).unwrap();
+        let strider = EntityBuilder::new("gems:crystal_strider").component("minecraft:rideable", serde_json::json!({ "seat_count": 1 })).build();
+        fs::write(root.join("bp/entities/strider.json"), strider.to_string()).unwrap();
+        let keys = content_lang_keys(&root.join("bp"));
+        assert_eq!(keys["entity.gems:crystal_strider.name"], "Crystal Strider");
+        assert_eq!(keys["action.hint.exit.gems:crystal_strider"], "Tap :_input_key.sneak: to dismount");
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[cfg(feature = "scaffold")]
+    #[test]
+    fn regenerated_uuids() {
+        let root = std::env::temp_dir().join("bedrockrs_addon_regenerated_uuids");
+        let _ = fs::remove_dir_all(&root);
+        let addon = new_addon(&ScaffoldOptions::new(&root, "Gems", "gems")).unwrap();
+        let bp_path = root.join("behavior_pack/manifest.json");
+        let rp_path = root.join("resource_pack/manifest.json");
+        let old_bp = deserialize_manifest_from_path(&bp_path).unwrap();
+        fs::create_dir_all(root.join("world")).unwrap();
+        let list = serde_json::json!([{ "pack_id": old_bp.header.uuid.to_string(), "version": [1, 0, 0] }]);
+        fs::write(root.join("world/world_behavior_packs.json"), list.to_string()).unwrap();
+
+        let uuids = addon.regenerate_uuids().unwrap();
+        // Both headers, the data and resources modules and the script module.
+        assert_eq!(uuids.len(), 5);
+        let (bp, rp) = (deserialize_manifest_from_path(&bp_path).unwrap(), deserialize_manifest_from_path(&rp_path).unwrap());
+        assert_eq!(uuids[&old_bp.header.uuid], bp.header.uuid);
+        assert_ne!(bp.header.uuid, old_bp.header.uuid);
+        assert!(bp.dependencies.iter().any(|dependency| matches!(dependency, ManifestDependency::UuidDependency(uuid, _) if *uuid == rp.header.uuid)));
+        assert!(rp.dependencies.iter().any(|dependency| matches!(dependency, ManifestDependency::UuidDependency(uuid, _) if *uuid == bp.header.uuid)));
+
+        assert_eq!(remap_world_pack_lists(&root.join("world"), &uuids).unwrap(), [root.join("world/world_behavior_packs.json")]);
+        let list: serde_json::Value = serde_json::from_str(&fs::read_to_string(root.join("world/world_behavior_packs.json")).unwrap()).unwrap();
+        assert_eq!(list[0]["pack_id"], bp.header.uuid.to_string());
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[cfg(feature = "scaffold")]
+    #[test]
+    fn version_bump() {
+        assert_eq!(SemVer { beta: true, ..SemVer::new(1, 2, 3) }.bump(Level::Patch), SemVer::new(1, 2, 4));
+        assert_eq!(SemVer::new(1, 2, 3).bump(Level::Minor), SemVer::new(1, 3, 0));
+        assert_eq!(SemVer::new(1, 2, 3).bump(Level::Major), SemVer::new(2, 0, 0));
+
+        let root = std::env::temp_dir().join("bedrockrs_addon_version_bump");
+        let _ = fs::remove_dir_all(&root);
+        let addon = new_addon(&ScaffoldOptions::new(&root, "Gems", "gems")).unwrap();
+        assert_eq!(addon.bump_version(Level::Minor).unwrap(), SemVer::new(1, 1, 0));
+        assert_eq!(addon.bump_version(Level::Patch).unwrap(), SemVer::new(1, 1, 1));
+
+        let bp = deserialize_manifest_from_path(&root.join("behavior_pack/manifest.json")).unwrap();
+        let rp = deserialize_manifest_from_path(&root.join("resource_pack/manifest.json")).unwrap();
+        for manifest in [&bp, &rp] {
+            assert_eq!(manifest.header.version, SemVer::new(1, 1, 1));
+            assert!(manifest.to_value()["modules"].as_array().unwrap().iter().all(|module| module["version"] == serde_json::json!([1, 1, 1])));
+        }
+        let versions: Vec<String> = bp.dependencies.iter().map(|dependency| match dependency {
+            ManifestDependency::UuidDependency(_, version) | ManifestDependency::ScriptDependency(_, version) => version.to_string()
+        }).collect();
+        // The script API version is a pin on the game, not on the other pack.
+        assert_eq!(versions, ["1.1.1", "1.11.0"]);
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[cfg(feature = "behavior")]
+    #[test]
+    fn merged_behavior_packs() {
+        let root = std::env::temp_dir().join("bedrockrs_addon_merged_behavior_packs");
+        let _ = fs::remove_dir_all(&root);
+        let write = |path: &str, contents: String| {
+            let path = root.join(path);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(path, contents).unwrap();
+        };
+        for pack in ["left", "right"] {
+            fs::create_dir_all(root.join(pack)).unwrap();
+            fs::copy("./inputs/behavior_pack/manifest.json", root.join(pack).join("manifest.json")).unwrap();
+            write(&format!("{}/items/ruby.json", pack), ItemBuilder::new("gems:ruby").build().to_string());
+            write(&format!("{}/functions/tick.json", pack), serde_json::json!({ "values": [format!("{}/tick", pack)] }).to_string());
+        }
+        write("right/items/sapphire.json", ItemBuilder::new("gems:sapphire").build().to_string());
+        write("right/recipes/ruby.json", RecipeBuilder::shapeless("gems:ruby_from_sapphire", &["gems:sapphire"], "gems:ruby").build().to_string());
+        write("right/functions/right/tick.mcfunction", "give @s gems:ruby\n".to_string());
+        let (left, right) = (root.join("left"), root.join("right"));
+
+        let error = merge_behavior_packs(&left, &right, &root.join("error"), ConflictStrategy::Error).unwrap_err();
+        assert!(error.to_string().contains("minecraft:item gems:ruby is also defined in"), "{}", error);
+        assert!(!root.join("error").exists());
+
+        let report = merge_behavior_packs(&left, &right, &root.join("left_wins"), ConflictStrategy::PreferLeft).unwrap();
+        assert_eq!(report.conflicts, [MergeConflict {
+            kind: "minecraft:item".to_string(), identifier: "gems:ruby".to_string(), left: "items/ruby.json".into(), right: "items/ruby.json".into(), renamed_to: None
+        }]);
+        assert_eq!(report.skipped, [Path::new("items/ruby.json"), Path::new("manifest.json")]);
+
+        let output = root.join("renamed");
+        let report = merge_behavior_packs(&left, &right, &output, ConflictStrategy::Rename).unwrap();
+        assert_eq!(report.conflicts[0].renamed_to.as_deref(), Some("gems:ruby_2"));
+        let written: Vec<String> = report.written.iter().map(|path| path.to_string_lossy().replace('\\', "/")).collect();
+        assert_eq!(written, [
+            "functions/right/tick.mcfunction", "functions/tick.json", "items/ruby.json", "items/ruby_2.json", "items/sapphire.json", "manifest.json", "recipes/ruby.json"
+        ]);
+        let read = |path: &str| -> serde_json::Value { serde_json::from_str(&fs::read_to_string(output.join(path)).unwrap()).unwrap() };
+        assert_eq!(read("functions/tick.json"), serde_json::json!({ "values": ["left/tick", "right/tick"] }));
+        assert_eq!(read("items/ruby.json")["minecraft:item"]["description"]["identifier"], "gems:ruby");
+        assert_eq!(read("items/ruby_2.json")["minecraft:item"]["description"]["identifier"], "gems:ruby_2");
+        assert_eq!(read("recipes/ruby.json")["minecraft:recipe_shapeless"]["result"]["item"], "gems:ruby_2");
+        assert_eq!(fs::read_to_string(output.join("functions/right/tick.mcfunction")).unwrap(), "give @s gems:ruby_2\n");
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[cfg(feature = "textures")]
+    #[test]
+    fn split_texture_subpacks() {
+        let root = std::env::temp_dir().join("bedrockrs_addon_split_subpacks");
+        let _ = fs::remove_dir_all(&root);
+        fs::create_dir_all(root.join("textures/blocks")).unwrap();
+        fs::create_dir_all(root.join("textures/items")).unwrap();
+        fs::write(root.join("manifest.json"), r#"{"format_version":2,"subpacks":[{"folder_name":"hd","name":"HD","memory_tier":1}]}"#).unwrap();
+        fs::copy("./inputs/textures/icons/ruby.png", root.join("pack_icon.png")).unwrap();
+        fs::copy("./inputs/textures/icons/emerald.png", root.join("textures/blocks/ruby.png")).unwrap();
+        fs::copy("./inputs/textures/icons/ruby.png", root.join("textures/blocks/ruby_hd.png")).unwrap();
+        fs::copy("./inputs/textures/icons/sapphire.png", root.join("textures/items/sapphire.png")).unwrap();
+
+        let splits = [
+            SubpackSplit {
+                folder_name: "hd".to_string(),
+                name: "HD".to_string(),
+                memory_tier: 1,
+                rule: SubpackRule::Paths(vec!["textures/**/*_hd.png".to_string()]),
+                variant_suffix: Some("_hd".to_string())
+            },
+            SubpackSplit { folder_name: "large".to_string(), name: "Large".to_string(), memory_tier: 2, rule: SubpackRule::MinWidth(16), variant_suffix: None }
+        ];
+        let mut report = split_subpacks(&root, &splits).unwrap();
+        report.moved.sort();
+        assert_eq!(report.moved, [
+            (PathBuf::from("textures/blocks/ruby_hd.png"), PathBuf::from("subpacks/hd/textures/blocks/ruby.png")),
+            (PathBuf::from("textures/items/sapphire.png"), PathBuf::from("subpacks/large/textures/items/sapphire.png"))
+        ]);
+        assert_eq!(report.without_base, [PathBuf::from("subpacks/large/textures/items/sapphire.png")]);
+        assert_eq!(report.declared, ["large"]);
+        assert!(root.join("pack_icon.png").is_file() && root.join("textures/blocks/ruby.png").is_file());
+        assert!(root.join("subpacks/hd/textures/blocks/ruby.png").is_file() && !root.join("textures/blocks/ruby_hd.png").exists());
+        let manifest: serde_json::Value = serde_json::from_str(&fs::read_to_string(root.join("manifest.json")).unwrap()).unwrap();
+        assert_eq!(manifest["subpacks"][1], serde_json::json!({ "folder_name": "large", "name": "Large", "memory_tier": 2 }));
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[cfg(feature = "build")]
+    #[test]
+    fn release_build() {
+        let root = std::env::temp_dir().join("bedrockrs_addon_release_build");
+        let _ = fs::remove_dir_all(&root);
+        let pack = root.join("rp");
+        copy_dir(Path::new("./inputs/resource_pack"), &pack);
+        fs::create_dir_all(pack.join(".git")).unwrap();
+        fs::write(pack.join(".git/HEAD"), "ref: refs/heads/main\n").unwrap();
+        fs::write(pack.join("README.md"), "# Test RP\n").unwrap();
+        fs::write(pack.join("animations/fold.animation.json"), r#"{ "format_version": "1.10", "animations": { "animation.test.fold": { "anim_time_update": "math.sin(90) * 2 + q.anim_time", "loop": "query.is_baby" } } }"#).unwrap();
+        let lang = fs::read_to_string(pack.join("texts/en_US.lang")).unwrap();
+        fs::write(pack.join("texts/en_US.lang"), format!("{}debug.marker.name=Marker\n", lang)).unwrap();
+
+        let report = build_pack(&pack, &BuildProfile::release(), &root.join("rp.mcpack")).unwrap();
+        assert_eq!(report.excluded, [".git/HEAD", "README.md"]);
+        assert_eq!(report.dropped_lang_keys, ["debug.marker.name"]);
+        assert!(report.folded_molang >= 1);
+        assert!(report.files.contains(&"contents.json".to_string()) && report.files.contains(&"textures/textures_list.json".to_string()));
+
+        let files = deserialize_mcpack_from_path(root.join("rp.mcpack")).unwrap();
+        assert_eq!(String::from_utf8_lossy(&files["animations/fold.animation.json"]), r#"{"animations":{"animation.test.fold":{"anim_time_update":"2+q.anim_time","loop":"query.is_baby"}},"format_version":"1.10"}"#);
+        assert_eq!(String::from_utf8_lossy(&files["texts/en_US.lang"]), lang);
+        let contents: serde_json::Value = serde_json::from_slice(&files["contents.json"]).unwrap();
+        assert_eq!(contents["content"].as_array().unwrap().len(), files.len() - 1);
+        let textures: Vec<String> = serde_json::from_slice(&files["textures/textures_list.json"]).unwrap();
+        assert_eq!(textures, ["textures/blocks/ruby_bricks", "textures/blocks/ruby_lamp", "textures/blocks/ruby_ore", "textures/entity/pig"]);
+
+        build_pack(&pack, &BuildProfile::release(), &root.join("again.mcpack")).unwrap();
+        assert_eq!(fs::read(root.join("rp.mcpack")).unwrap(), fs::read(root.join("again.mcpack")).unwrap());
+        let report = build_pack(&pack, &BuildProfile::development(), &root.join("dev.mcpack")).unwrap();
+        assert_eq!(report.excluded, [".git/HEAD"]);
+        assert_eq!(report.folded_molang, 0);
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[cfg(feature = "scaffold")]
+    #[test]
+    fn scaffolded_entity() {
+        let root = std::env::temp_dir().join("bedrockrs_addon_scaffolded_entity");
+        let _ = fs::remove_dir_all(&root);
+        let addon = new_addon(&ScaffoldOptions::new(&root, "Gems", "gems")).unwrap();
+        let bp = root.join("behavior_pack");
+        let rp = root.join("resource_pack");
+
+        let boar = EntityScaffold::new("gems:ruby_boar");
+        assert_eq!(boar.name, "Ruby Boar");
+        assert_eq!(boar.base_color, EntityScaffold::new("gems:ruby_boar").base_color);
+        assert_ne!(boar.base_color, EntityScaffold::new("gems:crystal_crab").base_color);
+        let written = scaffold_entity(&addon, &boar).unwrap();
+        assert_eq!(written, [
+            (PackKind::Behavior, "entities/ruby_boar.json".to_string()),
+            (PackKind::Behavior, "spawn_rules/ruby_boar.json".to_string()),
+            (PackKind::Resource, "entity/ruby_boar.entity.json".to_string()),
+            (PackKind::Resource, "models/entity/ruby_boar.geo.json".to_string()),
+            (PackKind::Resource, "textures/entity/ruby_boar.png".to_string()),
+            (PackKind::Resource, "texts/en_US.lang".to_string())
+        ]);
+        let read = |path: PathBuf| serde_json::from_str::<serde_json::Value>(&fs::read_to_string(path).unwrap()).unwrap();
+        assert_eq!(read(bp.join("entities/ruby_boar.json"))["minecraft:entity"]["description"]["is_spawnable"], true);
+        assert_eq!(read(bp.join("spawn_rules/ruby_boar.json"))["minecraft:spawn_rules"]["description"]["identifier"], "gems:ruby_boar");
+        let client = read(rp.join("entity/ruby_boar.entity.json"));
+        assert_eq!(client["minecraft:client_entity"]["description"]["spawn_egg"]["base_color"], boar.base_color.as_str());
+        assert_eq!(client["minecraft:client_entity"]["description"]["geometry"]["default"], "geometry.gems.ruby_boar");
+        let lang = parse_lang_from_str(&fs::read_to_string(rp.join("texts/en_US.lang")).unwrap());
+        assert_eq!(lang["item.spawn_egg.entity.gems:ruby_boar.name"], "Spawn Ruby Boar");
+        assert!(matches!(scaffold_entity(&addon, &boar), Err(AddonError::Semantic { .. })));
+
+        // An existing entity is kept and only made spawnable.
+        let crab = EntityBuilder::new("gems:crystal_crab").build();
+        fs::write(bp.join("entities/crab.json"), serde_json::to_string(&crab).unwrap()).unwrap();
+        let written = scaffold_entity(&addon, &EntityScaffold::new("gems:crystal_crab")).unwrap();
+        assert_eq!(written[..2], [(PackKind::Behavior, "entities/crab.json".to_string()), (PackKind::Behavior, "spawn_rules/crystal_crab.json".to_string())]);
+        assert_eq!(read(bp.join("entities/crab.json"))["minecraft:entity"]["description"]["is_spawnable"], true);
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[cfg(feature = "migrate")]
+    #[test]
+    fn holiday_creator_features_migration() {
+        let mut block: serde_json::Value = serde_json::from_str(&fs::read_to_string("./inputs/hcf_behavior_pack/blocks/ruby_lamp.json").unwrap()).unwrap();
+        let notes = migrate_experimental_block(&mut block);
+        assert_eq!(block["format_version"], MIGRATED_FORMAT_VERSION);
+        let lamp = &block["minecraft:block"];
+        assert_eq!(lamp["description"]["states"]["gems:lit"], serde_json::json!([false, true]));
+        assert_eq!(lamp["description"]["menu_category"], serde_json::json!({ "category": "construction", "group": "itemGroup.name.glass" }));
+        assert_eq!(lamp["components"]["minecraft:destructible_by_mining"], serde_json::json!({ "seconds_to_destroy": 0.3 }));
+        assert_eq!(lamp["components"]["minecraft:geometry"], "minecraft:geometry.full_block");
+        assert_eq!(lamp["permutations"][0]["components"]["minecraft:light_emission"], 15);
+        assert_eq!(lamp["permutations"][0]["condition"], "q.block_state('gems:lit') == true");
+        let warnings: Vec<&str> = notes.iter().filter(|note| note.severity == Severity::Warning).map(|note| note.pointer.as_deref().unwrap()).collect();
+        assert_eq!(warnings, ["/minecraft:block/components/minecraft:on_interact", "/minecraft:block/events"]);
+        assert!(lamp["components"].get("minecraft:on_interact").is_some());
+
+        let root = std::env::temp_dir().join("bedrockrs_addon_hcf_migration");
+        let _ = fs::remove_dir_all(&root);
+        for path in ["blocks/ruby_lamp.json", "items/ruby_wand.json"] {
+            fs::create_dir_all(root.join(path).parent().unwrap()).unwrap();
+            fs::copy(Path::new("./inputs/hcf_behavior_pack").join(path), root.join(path)).unwrap();
+        }
+        let report = migrate_experimental_content(&root).unwrap();
+        assert_eq!(report.changed, [PathBuf::from("blocks/ruby_lamp.json"), PathBuf::from("items/ruby_wand.json")]);
+        let wand: serde_json::Value = serde_json::from_str(&fs::read_to_string(root.join("items/ruby_wand.json")).unwrap()).unwrap();
+        assert_eq!(wand["minecraft:item"]["description"]["menu_category"], serde_json::json!({ "category": "equipment" }));
+        assert_eq!(wand["minecraft:item"]["components"]["minecraft:glint"], true);
+        assert_eq!(wand["minecraft:item"]["components"]["minecraft:durability"], serde_json::json!({ "max_durability": 64 }));
+        let manual = report.diagnostics.iter().filter(|note| note.code == "manual-migration" && note.file == Some(PathBuf::from("items/ruby_wand.json"))).count();
+        assert_eq!(manual, 2);
+        assert!(fs::read_to_string(root.join("items/ruby_wand.json")).unwrap().contains("\n  \"minecraft:item\""));
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[cfg(feature = "migrate")]
+    #[test]
+    fn legacy_items() {
+        let read = |path: &Path| serde_json::from_str::<serde_json::Value>(&fs::read_to_string(path).unwrap()).unwrap();
+        let seeds = Path::new("./inputs/legacy_addon/behavior_pack/items/ruby_seeds.json");
+        assert_eq!(item_format(&read(seeds)), ItemFormat::V1_10);
+        assert_eq!(item_format(&read(Path::new("./inputs/hcf_behavior_pack/items/ruby_wand.json"))), ItemFormat::V1_16);
+
+        let root = std::env::temp_dir().join("bedrockrs_addon_legacy_items");
+        let _ = fs::remove_dir_all(&root);
+        fs::create_dir_all(root.join("items")).unwrap();
+        fs::copy(seeds, root.join("items/ruby_seeds.json")).unwrap();
+        let report = migrate_legacy_items(&root, Some(Path::new("./inputs/legacy_addon/resource_pack"))).unwrap();
+        assert_eq!(report.changed, [PathBuf::from("items/ruby_seeds.json")]);
+        let warnings: Vec<&str> = report.diagnostics.iter().filter(|note| note.severity == Severity::Warning).map(|note| note.pointer.as_deref().unwrap()).collect();
+        assert_eq!(warnings, ["/minecraft:item/components/minecraft:camera"]);
+
+        let item = read(&root.join("items/ruby_seeds.json"));
+        assert_eq!(item["format_version"], MIGRATED_FORMAT_VERSION);
+        assert_eq!(item_format(&item), ItemFormat::Current);
+        assert_eq!(item["minecraft:item"]["description"]["menu_category"], serde_json::json!({ "category": "nature" }));
+        let components = &item["minecraft:item"]["components"];
+        assert_eq!(components["minecraft:hand_equipped"], serde_json::json!({ "value": false }));
+        assert_eq!(components["minecraft:max_stack_size"], serde_json::json!({ "value": 64 }));
+        assert_eq!(components["minecraft:use_modifiers"], serde_json::json!({ "use_duration": 1.6 }));
+        assert_eq!(components["minecraft:glint"], true);
+        assert_eq!(components["minecraft:block_placer"], serde_json::json!({ "block": "gems:ruby_crop", "use_on": ["minecraft:farmland"] }));
+        assert_eq!(components["minecraft:icon"], serde_json::json!({ "texture": "gems_ruby_seeds" }));
+        assert_eq!(components["minecraft:render_offsets"], "tools");
+
+        // Already current, so nothing changes the second time.
+        assert!(migrate_legacy_items(&root, None).unwrap().changed.is_empty());
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[cfg(feature = "migrate")]
+    #[test]
+    fn legacy_blocks() {
+        let read = |path: &Path| serde_json::from_str::<serde_json::Value>(&fs::read_to_string(path).unwrap()).unwrap();
+        let ruby_block = Path::new("./inputs/legacy_addon/behavior_pack/blocks/ruby_block.json");
+        assert_eq!(block_format(&read(ruby_block)), BlockFormat::V1_10);
+        assert_eq!(block_format(&read(Path::new("./inputs/hcf_behavior_pack/blocks/ruby_lamp.json"))), BlockFormat::V1_16);
+
+        let root = std::env::temp_dir().join("bedrockrs_addon_legacy_blocks");
+        let _ = fs::remove_dir_all(&root);
+        fs::create_dir_all(root.join("blocks")).unwrap();
+        fs::copy(ruby_block, root.join("blocks/ruby_block.json")).unwrap();
+        let report = migrate_legacy_blocks(&root, Some(Path::new("./inputs/legacy_addon/resource_pack"))).unwrap();
+        assert_eq!(report.changed, [PathBuf::from("blocks/ruby_block.json")]);
+        let warnings: Vec<&str> = report.diagnostics.iter().filter(|note| note.severity == Severity::Warning).map(|note| note.pointer.as_deref().unwrap()).collect();
+        assert_eq!(warnings, ["/minecraft:block/components/minecraft:unwalkable"]);
+
+        let block = read(&root.join("blocks/ruby_block.json"));
+        assert_eq!(block_format(&block), BlockFormat::Current);
+        assert_eq!(block["minecraft:block"]["description"], serde_json::json!({ "identifier": "gems:ruby_block", "menu_category": { "category": "construction" } }));
+        let components = &block["minecraft:block"]["components"];
+        assert_eq!(components["minecraft:friction"], 0.4);
+        assert_eq!(components["minecraft:flammable"], serde_json::json!({ "catch_chance_modifier": 5, "destroy_chance_modifier": 20 }));
+        assert_eq!(components["minecraft:light_dampening"], 15);
+        assert_eq!(components["minecraft:destructible_by_mining"], serde_json::json!({ "seconds_to_destroy": 3.0 }));
+        assert_eq!(components["minecraft:geometry"], "minecraft:geometry.full_block");
+        assert_eq!(components["minecraft:material_instances"], serde_json::json!({
+            "*": { "texture": "ruby_block_side" }, "up": { "texture": "ruby_block_top" }, "down": { "texture": "ruby_block_top" }
+        }));
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[cfg(feature = "migrate")]
+    #[test]
+    fn entity_layouts() {
+        let golem = deserialize_behavior_entity_from_path("./inputs/legacy_addon/behavior_pack/entities/ruby_golem.json").unwrap();
+        assert_eq!(golem.layout, EntityLayout::V1_8);
+        assert_eq!((golem.identifier.as_str(), golem.is_spawnable, golem.is_summonable), ("gems:ruby_golem", true, true));
+        assert!(golem.description.is_empty());
+        assert_eq!(golem.components["minecraft:damage_sensor"], serde_json::json!({ "triggers": [{
+            "on_damage": { "filters": { "test": "is_family", "subject": "other", "value": "player" }, "event": "gems:become_angry" },
+            "deals_damage": true
+        }] }));
+        assert_eq!(golem.components["minecraft:interact"]["interactions"][0]["interact_text"], "action.interact.feed");
+        assert_eq!(golem.component_groups["gems:angry"]["minecraft:environment_sensor"]["triggers"][0]["event"], "gems:calm_down");
+        assert_eq!((golem.events["gems:become_angry"].add.as_slice(), golem.events["gems:become_angry"].remove.as_slice()), (["gems:angry".to_string()].as_slice(), ["gems:calm".to_string()].as_slice()));
+        let randomize = &golem.events["gems:calm_down"].sequence[1].randomize;
+        assert_eq!((randomize[0].weight, randomize[1].triggered_event()), (Some(3.0), Some("gems:become_angry")));
+
+        // Writing the current layout and reading it back gives the same model.
+        let modern = serialize_behavior_entity_to_value(&golem);
+        assert_eq!(modern["format_version"], ENTITY_FORMAT_VERSION);
+        assert_eq!(entity_layout(&modern), EntityLayout::Current);
+        let reread = deserialize_behavior_entity_from_value(&modern).unwrap();
+        assert_eq!(reread, BehaviorEntity { layout: EntityLayout::Current, ..golem });
+        assert_eq!(serialize_behavior_entity_to_value(&reread), modern);
+
+        let pig: serde_json::Value = serde_json::from_str(&fs::read_to_string("./inputs/behavior_pack/entities/pig.json").unwrap()).unwrap();
+        assert!(deserialize_behavior_entity_from_value(&pig).is_some());
+        assert!(deserialize_behavior_entity_from_value(&serde_json::json!({ "minecraft:item": {} })).is_none());
+
+        let root = std::env::temp_dir().join("bedrockrs_addon_entity_layouts");
+        let _ = fs::remove_dir_all(&root);
+        fs::create_dir_all(root.join("entities")).unwrap();
+        fs::copy("./inputs/legacy_addon/behavior_pack/entities/ruby_golem.json", root.join("entities/ruby_golem.json")).unwrap();
+        assert_eq!(migrate_legacy_entities(&root).unwrap().changed, [PathBuf::from("entities/ruby_golem.json")]);
+        let migrated: serde_json::Value = serde_json::from_str(&fs::read_to_string(root.join("entities/ruby_golem.json")).unwrap()).unwrap();
+        assert_eq!(migrated, modern);
+        assert!(migrate_legacy_entities(&root).unwrap().changed.is_empty());
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[cfg(all(feature = "molang", feature = "resource"))]
+    #[test]
+    fn animation_keyframes() {
+        let mut context = MolangContext::new().variable("swing", 2.0);
+        assert_eq!(Molang::from("1 +").evaluate(&mut context), None);
+        assert_eq!(Molang::from("t.x = 0; loop(5, { t.x = t.x + v.swing; }); return t.x > 8 ? t.x : 0;").evaluate(&mut context), Some(10.0));
+        assert_eq!(Molang::from("v.swing * 3 + (v.missing ?? 1) + 10 / 0").evaluate(&mut context), Some(7.0));
+        assert_eq!(Molang::from("'a' == 'a' && q.anim_time == 0").evaluate(&mut context), Some(1.0));
+
+        let animation = deserialize_animation_from_value(&serde_json::json!({
+            "loop": true,
+            "bones": {
+                "arm": {
+                    "rotation": {
+                        "0.0": [0, 0, 0],
+                        "1.0": { "pre": [10, 0, 0], "post": [20, "v.swing", 0] },
+                        "2.0": [40, 0, 0]
+                    },
+                    "position": { "0": 0, "1": { "post": [0, 4, 0], "lerp_mode": "catmullrom" }, "2": 0, "3": 0 },
+                    "scale": "1 + q.anim_time"
+                }
+            }
+        }));
+        assert_eq!(animation.loop_mode, LoopMode::Loop);
+        assert_eq!(animation.length(), 3.0);
+        let arm = &animation.bones["arm"];
+        let rotation = arm.rotation.as_ref().unwrap();
+        assert_eq!(rotation.value_at(-1.0, &mut context), [0.0, 0.0, 0.0]);
+        assert_eq!(rotation.value_at(0.5, &mut context), [5.0, 0.0, 0.0]);
+        // The step at 1.0: arriving at `pre`, leaving from `post`.
+        assert_eq!(rotation.value_at(1.0, &mut context), [20.0, 2.0, 0.0]);
+        assert_eq!(rotation.value_at(1.5, &mut context), [30.0, 1.0, 0.0]);
+        assert_eq!(rotation.value_at(5.0, &mut context), [40.0, 0.0, 0.0]);
+
+        let position = arm.position.as_ref().unwrap();
+        assert_eq!(position.keyframes[1].interpolation, Interpolation::CatmullRom);
+        assert_eq!(position.value_at(1.0, &mut context), [0.0, 4.0, 0.0]);
+        // Catmull-Rom overshoots where a linear blend would give 2.
+        assert_eq!(position.value_at(1.5, &mut context), [0.0, 2.25, 0.0]);
+        assert_eq!(position.value_at(0.5, &mut context), [0.0, 2.25, 0.0]);
+        assert_eq!(arm.scale.as_ref().unwrap().value_at(0.5, &mut context), [1.5, 1.5, 1.5]);
+
+        // Looping wraps the time around the length.
+        assert_eq!(animation.pose_at(3.5, &mut context)["arm"].rotation, [5.0, 0.0, 0.0]);
+        let frames = animation.bake(2.0, &mut context);
+        assert_eq!(frames.len(), 7);
+        assert_eq!(frames[3].0, 1.5);
+        assert_eq!(frames[3].1["arm"].scale, [2.5, 2.5, 2.5]);
+
+        assert_eq!(deserialize_channel_from_value(&serde_json::json!({ "pre": [1, 2, 3] })).unwrap().value_at(7.0, &mut context), [1.0, 2.0, 3.0]);
+        assert_eq!(deserialize_channel_from_value(&serde_json::json!([true, false, "2"])).unwrap().value_at(0.0, &mut context), [1.0, 0.0, 2.0]);
+        let pig = deserialize_animations_from_path("./inputs/resource_pack/animations/pig.animation.json").unwrap();
+        let leg = pig["animation.pig.walk"].bones["leg0"].rotation.as_ref().unwrap();
+        assert!((leg.value_at(0.0, &mut context)[0] - 80.0).abs() < 1e-4);
+    }
+
+    #[cfg(all(feature = "molang", feature = "resource"))]
+    #[test]
+    fn particle_curves_and_events() {
+        let effect = deserialize_particle_effect_from_value(&serde_json::json!({
+            "format_version": "1.10.0",
+            "particle_effect": {
+                "description": { "identifier": "test:ember" },
+                "curves": {
+                    "variable.fade": { "type": "linear", "input": "v.particle_age", "horizontal_range": "v.particle_lifetime", "nodes": [0, 10, 20] },
+                    "variable.ease": { "type": "bezier", "input": "v.particle_age", "nodes": [0, 0, 1, 1] },
+                    "variable.pulse": { "type": "catmull_rom", "input": "v.particle_age", "nodes": [0, 0, 10, 10, 0] },
+                    "variable.step": {
+                        "type": "bezier_chain", "input": "v.particle_age",
+                        "nodes": { "0": { "value": 0 }, "0.5": { "left_value": 1, "right_value": 3 }, "1": { "value": 3 } }
+                    },
+                    "variable.broken": { "type": "sine", "nodes": [] }
+                },
+                "events": {
+                    "hit": {
+                        "sequence": [
+                            { "particle_effect": { "effect": "test:spark", "type": "particle", "pre_effect_expression": "v.spark_size = 2;" } },
+                            { "sound_effect": { "event_name": "random.pop" }, "expression": "v.hits = (v.hits ?? 0) + 1;" }
+                        ],
+                        "randomize": [{ "weight": 0, "log": "never" }, { "weight": 1, "log": "always" }]
+                    }
+                }
+            }
+        })).unwrap();
+        assert_eq!(effect.identifier, "test:ember");
+        assert_eq!(effect.curves.keys().collect::<Vec<_>>(), ["variable.ease", "variable.fade", "variable.pulse", "variable.step"]);
+        assert_eq!(effect.curves["variable.step"].curve_type, CurveType::BezierChain);
+        assert!(matches!(&effect.curves["variable.step"].nodes, CurveNodes::Chain(nodes) if nodes.len() == 3));
+
+        let mut context = MolangContext::new().variable("particle_age", 0.25).variable("particle_lifetime", 2.0);
+        effect.update_curves(&mut context);
+        assert_eq!(context.variables["fade"], 2.5);
+        assert_eq!(context.variables["ease"], 0.15625);
+        assert_eq!(context.variables["pulse"], 5.0);
+        assert_eq!(context.variables["step"], 0.5);
+        context.variables.insert("particle_age".to_string(), 0.75);
+        effect.update_curves(&mut context);
+        assert_eq!(context.variables["fade"], 7.5);
+        assert_eq!(context.variables["pulse"], 11.25);
+        assert_eq!(context.variables["step"], 3.0);
+
+        let fired = effect.fire_event("hit", &mut context).unwrap();
+        assert_eq!(fired, vec![
+            FiredEffect::Particle { effect: "test:spark".to_string(), effect_type: "particle".to_string() },
+            FiredEffect::Sound("random.pop".to_string()),
+            FiredEffect::Log("always".to_string())
+        ]);
+        assert_eq!((context.variables["spark_size"], context.variables["hits"]), (2.0, 1.0));
+        effect.fire_event("hit", &mut context).unwrap();
+        assert_eq!(context.variables["hits"], 2.0);
+        assert_eq!(effect.fire_event("missing", &mut context), None);
+
+        let spark = deserialize_particle_effect_from_path("./inputs/resource_pack/particles/spark.particle.json").unwrap();
+        assert_eq!(spark.texture.as_deref(), Some("textures/particle/spark"));
+        assert!(spark.curves.is_empty() && spark.events.is_empty());
+    }
+
+    #[cfg(feature = "validation")]
+    #[test]
+    fn render_controller_arrays() {
+        assert!(validate_render_controllers(Path::new("./inputs/resource_pack"), None).is_empty());
+
+        let pack = std::env::temp_dir().join("bedrockrs_addon_render_controller_arrays");
+        let _ = fs::remove_dir_all(&pack);
+        fs::create_dir_all(pack.join("entity")).unwrap();
+        fs::create_dir_all(pack.join("render_controllers")).unwrap();
+        fs::write(pack.join("entity/sheep.entity.json"), r#"{ "format_version": "1.10.0", "minecraft:client_entity": { "description": {
+            "identifier": "test:sheep",
+            "textures": { "default": "textures/entity/sheep", "sheared": "textures/entity/sheep_sheared" },
+            "geometry": { "default": "geometry.sheep" },
+            "materials": { "default": "sheep" },
+            "render_controllers": [{ "controller.render.sheep": "1" }]
+        } } }"#).unwrap();
+        fs::write(pack.join("render_controllers/sheep.render_controllers.json"), r#"{ "format_version": "1.8.0", "render_controllers": { "controller.render.sheep": {
+            "arrays": {
+                "textures": { "Array.Skins": ["Texture.default", "Texture.sheared", "Texture.missing"] },
+                "geometries": { "Array.geos": ["Geometry.default"] }
+            },
+            "geometry": "Array.geos[1 + 1]",
+            "textures": ["Array.skins[q.variant]", "Array.nothing[0]"],
+            "materials": [{ "*": "Material.glow" }]
+        } } }"#).unwrap();
+
+        let issues = validate_render_controllers(&pack, None);
+        let kinds: Vec<(&str, &RenderControllerIssueKind)> = issues.iter().map(|issue| (issue.pointer.as_str(), &issue.kind)).collect();
+        assert_eq!(kinds, vec![
+            ("/render_controllers/controller.render.sheep/arrays/textures/Array.Skins/2", &RenderControllerIssueKind::UndefinedResource { resource: "texture.missing".to_string() }),
+            ("/render_controllers/controller.render.sheep/textures/1", &RenderControllerIssueKind::UndefinedArray { array: "array.nothing".to_string() }),
+            ("/render_controllers/controller.render.sheep/geometry", &RenderControllerIssueKind::IndexOutOfRange { array: "array.geos".to_string(), index: 2, len: 1 }),
+            ("/render_controllers/controller.render.sheep/materials/0/*", &RenderControllerIssueKind::UndefinedResource { resource: "material.glow".to_string() })
+        ]);
+        assert!(issues.iter().all(|issue| issue.entity == "test:sheep" && issue.file == Path::new("render_controllers/sheep.render_controllers.json")));
+        let diagnostic = Diagnostic::from(issues[2].clone());
+        assert_eq!(diagnostic.code, "render-controller-index");
+        assert_eq!(diagnostic.message, "index 2 is out of range, array.geos has 1 entries");
+        fs::remove_dir_all(&pack).unwrap();
+    }
+
+    #[cfg(feature = "resource")]
+    #[test]
+    fn poly_mesh_geometry() {
+        let file = deserialize_geometry_from_path("./inputs/geometry/crystal.geo.json").unwrap();
+        let crystal = file.geometry("geometry.crystal").unwrap();
+        assert_eq!(crystal.description.texture_width, Some(32.0));
+        assert_eq!(crystal.description.other["visible_bounds_offset"], serde_json::json!([0, 0.75, 0]));
+        assert_eq!(crystal.bones[0].cubes[0].size, [8.0, 2.0, 8.0]);
+        assert!(crystal.bones[0].other.contains_key("locators"));
+
+        let gem = crystal.bones[2].poly_mesh.as_ref().unwrap();
+        assert!(gem.normalized_uvs);
+        assert_eq!(gem.polygons()[1], vec![[1, 1, 1], [3, 1, 3], [2, 1, 2]]);
+        assert!(gem.validate().is_empty());
+
+        let shard = file.geometry("geometry.crystal.shard").unwrap().bones[0].poly_mesh.as_ref().unwrap();
+        assert_eq!(shard.polys, Polys::List(PolyList::TriList));
+        assert_eq!(shard.polygons(), vec![vec![[0, 0, 0], [1, 1, 1], [2, 2, 2]]]);
+        assert!(shard.validate().is_empty());
+
+        let mut broken = gem.clone();
+        broken.polys = Polys::Indexed(vec![vec![[0, 0, 0], [4, 2, 0]]]);
+        assert_eq!(broken.validate(), vec![
+            PolyMeshIssue::VertexCount { poly: 0, count: 2 },
+            PolyMeshIssue::IndexOutOfRange { poly: 0, list: "positions", index: 4 },
+            PolyMeshIssue::IndexOutOfRange { poly: 0, list: "normals", index: 2 }
+        ]);
+        broken.polys = Polys::List(PolyList::QuadList);
+        assert_eq!(broken.validate(), vec![PolyMeshIssue::UnevenList]);
+
+        // Nothing is lost writing the file back.
+        let written = serialize_geometry_to_string(&file);
+        assert_eq!(deserialize_geometry_from_str(&written).unwrap(), file);
+        let written: serde_json::Value = serde_json::from_str(&written).unwrap();
+        assert_eq!(written.pointer("/minecraft:geometry/1/bones/0/poly_mesh/polys"), Some(&serde_json::json!("tri_list")));
+        assert_eq!(written.pointer("/minecraft:geometry/0/bones/0/locators/glow/1"), Some(&serde_json::json!(8)));
+    }
+
+    #[cfg(feature = "resource")]
+    #[test]
+    fn per_face_uv() {
+        let file = deserialize_geometry_from_path("./inputs/geometry/crystal.geo.json").unwrap();
+        let mut base = file.geometries[0].bones[0].clone();
+        assert_eq!(base.cubes[0].uv, Some(CubeUv::Box([0.0, 0.0])));
+        let Some(CubeUv::PerFace(faces)) = &base.cubes[1].uv else {
+            panic!("expected per-face UVs");
+        };
+        assert_eq!(faces.up.as_ref().unwrap().material_instance.as_deref(), Some("glow"));
+        assert!(faces.south.is_none());
+        assert_eq!(per_face_box_uv(faces, base.cubes[1].size, false), None);
+
+        // An 8x2x8 cube at [0, 0]: the sides in a row at y = 8, top and bottom above them.
+        base.convert_uv_to_per_face();
+        let Some(CubeUv::PerFace(faces)) = base.cubes[0].uv.clone() else {
+            panic!("expected per-face UVs");
+        };
+        let face = |uv, uv_size| Some(FaceUv { uv, uv_size: Some(uv_size), material_instance: None, other: serde_json::Map::new() });
+        assert_eq!(faces.east, face([0.0, 8.0], [8.0, 2.0]));
+        assert_eq!(faces.north, face([8.0, 8.0], [8.0, 2.0]));
+        assert_eq!(faces.west, face([16.0, 8.0], [8.0, 2.0]));
+        assert_eq!(faces.south, face([24.0, 8.0], [8.0, 2.0]));
+        assert_eq!(faces.up, face([16.0, 8.0], [-8.0, -8.0]));
+        assert_eq!(faces.down, face([24.0, 0.0], [-8.0, 8.0]));
+        assert_eq!(base.convert_uv_to_box(), 1);
+        assert_eq!(base.cubes[0].uv, Some(CubeUv::Box([0.0, 0.0])));
+
+        let mirrored = box_uv_faces([4.0, 2.0], [2.0, 3.0, 1.0], true);
+        assert_eq!(mirrored.east, face([8.0, 3.0], [-1.0, 3.0]));
+        assert_eq!(mirrored.north, face([7.0, 3.0], [-2.0, 3.0]));
+        assert_eq!(per_face_box_uv(&mirrored, [2.0, 3.0, 1.0], true), Some([4.0, 2.0]));
+        assert_eq!(per_face_box_uv(&mirrored, [2.0, 3.0, 1.0], false), None);
+
+        let written = serialize_geometry_to_string(&file);
+        assert_eq!(deserialize_geometry_from_str(&written).unwrap(), file);
+    }
+
+    #[cfg(feature = "resource")]
+    #[test]
+    fn texture_meshes() {
+        let file = deserialize_geometry_from_path("./inputs/geometry/crystal.geo.json").unwrap();
+        let glint = &file.geometries[0].bones[1];
+        assert_eq!(glint.name, "glint");
+        assert!(glint.cubes.is_empty() && glint.poly_mesh.is_none());
+        assert_eq!(glint.texture_meshes[0].texture, "glint");
+        assert_eq!(glint.texture_meshes[0].local_pivot, Some([8.0, 0.5, 8.0]));
+        assert_eq!(glint.texture_meshes[0].scale, Some([0.5, 0.5, 1.0]));
+        assert_eq!(glint.texture_meshes[1], TextureMesh {
+            texture: "default".to_string(), position: Some([0.0, 0.0, -4.0]), local_pivot: None, rotation: None, scale: None, other: serde_json::Map::new()
+        });
+        assert!(!glint.other.contains_key("texture_meshes"));
+
+        let written = serialize_geometry_to_string(&file);
+        assert_eq!(deserialize_geometry_from_str(&written).unwrap(), file);
+        let written: serde_json::Value = serde_json::from_str(&written).unwrap();
+        let meshes = written.pointer("/minecraft:geometry/0/bones/1/texture_meshes").unwrap();
+        assert_eq!(meshes[1].as_object().unwrap().keys().collect::<Vec<_>>(), ["position", "texture"]);
+        assert_eq!(meshes[0]["rotation"], serde_json::json!([0.0, 45.0, 0.0]));
+    }
+
+    #[cfg(all(feature = "molang", feature = "resource"))]
+    #[test]
+    fn client_scripts() {
+        let pig = fs::read_to_string("./inputs/resource_pack/entity/pig.entity.json").unwrap();
+        let pig: serde_json::Value = serde_json::from_str(&pig).unwrap();
+        let description = &pig["minecraft:client_entity"]["description"];
+        let scripts = deserialize_client_scripts_from_value(description);
+        let animate: Vec<(&str, Option<&str>)> = scripts.animate.iter().map(|entry| (entry.name.as_str(), entry.condition.as_ref().map(Molang::as_str))).collect();
+        assert_eq!(animate, [("move", None), ("look_at_target", Some("!q.is_baby")), ("baby_transform", None)]);
+        let mut written = description.as_object().unwrap().clone();
+        serialize_client_scripts_into(&scripts, &mut written);
+        assert_eq!(&serde_json::Value::Object(written), description);
+
+        let description = serde_json::json!({ "scripts": { "initialize": ["v.size = 1;", 2], "scale": 0.5, "scalex": "v.size", "animate": [] } });
+        let scripts = deserialize_client_scripts_from_value(&description);
+        assert_eq!(scripts.initialize, [Molang::from("v.size = 1;"), Molang::from("2")]);
+        assert_eq!(scripts.scale, Some(Molang::from("0.5")));
+        assert_eq!(scripts.other.keys().collect::<Vec<_>>(), ["scalex"]);
+        let mut written = serde_json::Map::new();
+        serialize_client_scripts_into(&scripts, &mut written);
+        assert_eq!(written["scripts"], serde_json::json!({ "initialize": ["v.size = 1;", 2], "scale": 0.5, "scalex": "v.size" }));
+    }
+
+    #[cfg(feature = "validation")]
+    #[test]
+    fn effect_shortnames() {
+        let pig = fs::read_to_string("./inputs/resource_pack/entity/pig.entity.json").unwrap();
+        let pig: serde_json::Value = serde_json::from_str(&pig).unwrap();
+        let description = &pig["minecraft:client_entity"]["description"];
+        let bindings = deserialize_effect_bindings_from_value(description);
+        assert_eq!(bindings.sound("squeal"), Some("mob.pig.squeal"));
+        assert_eq!(bindings.particle("squeal"), None);
+        let mut written = description.as_object().unwrap().clone();
+        serialize_effect_bindings_into(&bindings, &mut written);
+        assert_eq!(&serde_json::Value::Object(written), description);
+        assert!(validate_effect_references(Path::new("./inputs/resource_pack")).is_empty());
+
+        let pack = std::env::temp_dir().join("bedrockrs_addon_effect_shortnames");
+        let _ = fs::remove_dir_all(&pack);
+        for dir in ["entity", "animations", "animation_controllers"] {
+            fs::create_dir_all(pack.join(dir)).unwrap();
+        }
+        fs::write(pack.join("entity/wisp.entity.json"), r#"{ "format_version": "1.10.0", "minecraft:client_entity": { "description": {
+            "identifier": "test:wisp",
+            "particle_effects": { "smoke": "test:smoke" },
+            "particle_emitters": { "trail": "test:trail" },
+            "sound_effects": { "hum": { "effect": "mob.wisp.hum" } },
+            "animations": { "float": "animation.wisp.float", "glow": "controller.animation.wisp.glow" }
+        } } }"#).unwrap();
+        fs::write(pack.join("animations/wisp.animation.json"), r#"{ "format_version": "1.10.0", "animations": { "animation.wisp.float": {
+            "particle_effects": { "0.0": { "effect": "smoke", "locator": "head" }, "0.5": [{ "effect": "trail" }, { "effect": "sparkle" }] },
+            "sound_effects": { "1.0": { "effect": "hum" } }
+        } } }"#).unwrap();
+        fs::write(pack.join("animation_controllers/wisp.animation_controllers.json"), r#"{ "format_version": "1.10.0", "animation_controllers": { "controller.animation.wisp.glow": {
+            "states": { "default": { "particle_effects": [{ "effect": "smoke" }], "sound_effects": [{ "effect": "chime" }] } }
+        } } }"#).unwrap();
+
+        let unresolved = validate_effect_references(&pack);
+        assert_eq!(unresolved, vec![
+            UnresolvedReference {
+                kind: ReferenceKind::ParticleShortname,
+                file: PathBuf::from("animations/wisp.animation.json"),
+                pointer: "/animations/animation.wisp.float/particle_effects/0.5/1/effect".to_string(),
+                reference: "sparkle".to_string()
+            },
+            UnresolvedReference {
+                kind: ReferenceKind::SoundShortname,
+                file: PathBuf::from("animation_controllers/wisp.animation_controllers.json"),
+                pointer: "/animation_controllers/controller.animation.wisp.glow/states/default/sound_effects/0/effect".to_string(),
+                reference: "chime".to_string()
+            }
+        ]);
+        assert_eq!(Diagnostic::from(unresolved[1].clone()).code, "undeclared-sound");
+        fs::remove_dir_all(&pack).unwrap();
+    }
+
+    #[cfg(feature = "validation")]
+    #[test]
+    fn gametest_structures() {
+        assert_eq!(structure_identifier(Path::new("ruby/hut.mcstructure")).as_deref(), Some("ruby:hut"));
+        assert_eq!(structure_identifier(Path::new("shared.mcstructure")).as_deref(), Some("mystructure:shared"));
+        assert_eq!(structure_path("ruby:hut"), Path::new("structures/ruby/hut.mcstructure"));
+        assert_eq!(structure_path("mystructure:shared"), Path::new("structures/shared.mcstructure"));
+
+        let pack = std::env::temp_dir().join("bedrockrs_addon_gametest_structures");
+        let _ = fs::remove_dir_all(&pack);
+        fs::create_dir_all(pack.join("scripts")).unwrap();
+        fs::create_dir_all(pack.join("structures/ruby")).unwrap();
+        for structure in ["ruby/hut", "ruby/old", "shared"] {
+            fs::copy("./inputs/behavior_pack/structures/ruby_hut.mcstructure", pack.join(format!("structures/{}.mcstructure", structure))).unwrap();
+        }
+        let script = r#"import * as gt from "@minecraft/server-gametest";
+// gt.register("Ruby", "commented", () => {});
+gt.register("Ruby", "hut", (test) => { test.succeed("done)"); }).maxTicks(20);
+gt.registerAsync("Ruby", "missing", async (test) => {
+    /* "register("Ruby", "nested")" */
+    test.succeed();
+});
+gt.register("Ruby:shared", (test) => {}).tag("slow").structureName("shared");
+gt.register("Ruby", "hut", () => {});
+"#;
+        fs::write(pack.join("scripts/tests.js"), script).unwrap();
+
+        let tests = find_registered_tests(script, Path::new("scripts/tests.js"));
+        let found: Vec<(&str, &str, usize)> = tests.iter().map(|test| (test.name.as_str(), test.structure.as_str(), test.line)).collect();
+        assert_eq!(found, [("Ruby:hut", "Ruby:hut", 3), ("Ruby:missing", "Ruby:missing", 4), ("Ruby:shared", "mystructure:shared", 8), ("Ruby:hut", "Ruby:hut", 9)]);
+
+        let issues = validate_gametests(&pack);
+        let kinds: Vec<(&str, &GameTestIssueKind)> = issues.iter().map(|issue| (issue.structure.as_str(), &issue.kind)).collect();
+        assert_eq!(kinds, [
+            ("Ruby:missing", &GameTestIssueKind::MissingStructure),
+            ("Ruby:hut", &GameTestIssueKind::DuplicateTest { first: PathBuf::from("scripts/tests.js"), line: 3 }),
+            ("ruby:old", &GameTestIssueKind::UnusedStructure)
+        ]);
+        assert_eq!(issues[2].file, Path::new("structures/ruby/old.mcstructure"));
+        assert_eq!(Diagnostic::from(issues[0].clone()).to_string(), "scripts/tests.js: error[gametest-structure]: test 'Ruby:missing' on line 4 runs in structure 'Ruby:missing', which does not exist");
+        fs::remove_dir_all(&pack).unwrap();
+    }
+
+    #[cfg(all(feature = "archive", feature = "nbt", feature = "validation"))]
+    #[test]
+    fn education_features() {
+        let references = chemistry_references(Path::new("./inputs/behavior_pack"));
+        assert_eq!(references.len(), 1);
+        assert_eq!(references[0].identifier, "minecraft:element_1");
+        assert_eq!(references[0].pointer, "/pools/0/entries/0/name");
+        let diagnostics: Vec<Diagnostic> = validate_education_features(Path::new("./inputs/behavior_pack")).into_iter().map(Into::into).collect();
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].code, "chemistry-capability");
+
+        let dir = std::env::temp_dir().join("bedrockrs_addon_education");
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        let mut level = LevelDat::new("Lab");
+        level.set_flag("educationFeaturesEnabled", true);
+        write_level_dat_to_path(&level, dir.join("level.dat")).unwrap();
+        fs::write(dir.join("allowlist.json"), r#"[{ "name": "Teacher", "ignoresPlayerLimit": true }, { "name": "Student", "xuid": "2535" }]"#).unwrap();
+        let settings = deserialize_education_settings_from_world(&dir).unwrap();
+        assert!(settings.features_enabled && !settings.code_builder);
+        assert_eq!(settings.allowlist[1], AllowlistEntry { name: "Student".to_string(), xuid: Some("2535".to_string()), ignores_player_limit: false });
+        assert!(settings.allowlist[0].ignores_player_limit);
+
+        let mut template = WorldTemplate::new("Lab", Uuid::nil(), Uuid::nil());
+        template.education = Some(EducationSettings { code_builder: true, ..settings });
+        let archive = String::from_utf8_lossy(&write_mctemplate(&template, vec![]).unwrap()).to_string();
+        assert!(archive.contains("allowlist.json"));
+        template.education = Some(EducationSettings::default());
+        assert!(!String::from_utf8_lossy(&write_mctemplate(&template, vec![]).unwrap()).contains("allowlist.json"));
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[cfg(feature = "manifest")]
+    #[test]
+    fn server_pack_config() {
+        let manifest = deserialize_manifest_from_path(Path::new("./inputs/behavior_pack/manifest.json")).unwrap();
+        let config = ServerPackConfig::from_manifest(&manifest);
+        assert_eq!(config.permissions.allowed_modules, vec!["@minecraft/server", "@minecraft/server-ui"]);
+        assert_eq!(config.directory(), Path::new("config").join(manifest.header.uuid.to_string()));
+
+        let server = std::env::temp_dir().join("bedrockrs_addon_server_config");
+        let _ = fs::remove_dir_all(&server);
+        let dir = server.join(config.directory());
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("permissions.json"), r#"{ "allowed_modules": ["@minecraft/server-net"] }"#).unwrap();
+        fs::write(dir.join("secrets.json"), r#"{ "api_key": "hunter2" }"#).unwrap();
+
+        let provisioned = provision_server_pack_config(&manifest, &server).unwrap();
+        assert_eq!(provisioned.permissions.allowed_modules, vec!["@minecraft/server", "@minecraft/server-ui", "@minecraft/server-net"]);
+        assert!(!dir.join("variables.json").exists());
+        let read = deserialize_server_pack_config_from_path(&server, manifest.header.uuid).unwrap();
+        assert_eq!(read, provisioned);
+        assert_eq!(read.secrets["api_key"], "hunter2");
+        fs::remove_dir_all(&server).unwrap();
+    }
+
+    #[cfg(feature = "validation")]
+    #[test]
+    fn entity_event_graph() {
+        let pack = std::env::temp_dir().join("bedrockrs_addon_entity_events");
+        let _ = fs::remove_dir_all(&pack);
+        for dir in ["entities", "functions", "scripts"] {
+            fs::create_dir_all(pack.join(dir)).unwrap();
+        }
+        fs::write(pack.join("entities/golem.json"), r#"{
+            "format_version": "1.21.0",
+            "minecraft:entity": {
+                "description": { "identifier": "lab:golem" },
+                "components": { "minecraft:timer": { "time": 5, "time_down_event": { "event": "lab:tick", "target": "self" } } },
+                "component_groups": {
+                    "lab:awake": { "minecraft:interact": { "interactions": [{ "on_interact": { "event": "lab:calm", "target": "other" } }] } },
+                    "lab:charged": {},
+                    "lab:scripted": {},
+                    "lab:orphan": {}
+                },
+                "events": {
+                    "lab:tick": { "sequence": [{ "add": { "component_groups": ["lab:awake"] } }, { "trigger": "lab:charge" }] },
+                    "lab:charge": { "randomize": [{ "weight": 1, "add": { "component_groups": ["lab:charged"] } }] },
+                    "lab:from_function": { "add": { "component_groups": ["lab:scripted"] } },
+                    "lab:from_script": {},
+                    "lab:unused": { "add": { "component_groups": ["lab:orphan"] } },
+                    "lab:flip": { "remove": { "component_groups": ["lab:awake"] }, "trigger": "lab:flop" },
+                    "lab:flop": { "add": { "component_groups": ["lab:awake"] }, "trigger": { "event": "lab:flip", "target": "self" } }
+                }
+            }
+        }"#).unwrap();
+        fs::write(pack.join("functions/wake.mcfunction"), "# event entity @s lab:unused\nevent entity @e[type=lab:golem, r=5] lab:from_function\n").unwrap();
+        fs::write(pack.join("scripts/main.js"), "golem.triggerEvent('lab:from_script');\n").unwrap();
+
+        let entity = deserialize_behavior_entity_from_path(pack.join("entities/golem.json")).unwrap();
+        let graph = EventGraph::from_entity(&entity);
+        assert!(graph.external_events.contains("lab:calm"));
+        assert_eq!(graph.trigger_cycles(), vec![vec!["lab:flip".to_string(), "lab:flop".to_string()]]);
+
+        let issues: Vec<(String, EntityEventIssueKind)> = analyze_entity_events(&pack).into_iter().map(|issue| (issue.name, issue.kind)).collect();
+        assert_eq!(issues, vec![
+            ("lab:orphan".to_string(), EntityEventIssueKind::UnreachableGroup),
+            ("lab:flip".to_string(), EntityEventIssueKind::UntriggeredEvent),
+            ("lab:flop".to_string(), EntityEventIssueKind::UntriggeredEvent),
+            ("lab:unused".to_string(), EntityEventIssueKind::UntriggeredEvent),
+            ("lab:flip".to_string(), EntityEventIssueKind::TriggerCycle { events: vec!["lab:flip".to_string(), "lab:flop".to_string()] })
+        ]);
+        let diagnostic: Diagnostic = analyze_entity_events(&pack).remove(0).into();
+        assert_eq!(diagnostic.pointer.as_deref(), Some("/minecraft:entity/component_groups/lab:orphan"));
+        fs::remove_dir_all(&pack).unwrap();
+    }
+
+    #[cfg(feature = "validation")]
+    #[test]
+    fn query_contexts() {
+        assert_eq!(QueryContext::from_path("particles\\spark.json"), Some(QueryContext::Particle));
+        assert_eq!(QueryContext::from_path("entities/pig.json"), Some(QueryContext::Entity));
+        assert_eq!(QueryContext::from_path("textures/terrain_texture.json"), None);
+        assert!(query_available("Is_Baby", QueryContext::Animation));
+        assert!(!query_available("is_baby", QueryContext::Particle));
+        assert!(query_available("time_of_day", QueryContext::Block));
+
+        let pack = std::env::temp_dir().join("bedrockrs_addon_query_contexts");
+        let _ = fs::remove_dir_all(&pack);
+        for dir in ["particles", "animations", "blocks", "entity"] {
+            fs::create_dir_all(pack.join(dir)).unwrap();
+        }
+        fs::write(pack.join("particles/spark.json"), r#"{ "particle_effect": { "components": {
+            "minecraft:emitter_rate_instant": { "num_particles": "q.is_baby ? 1 : q.time_of_day" },
+            "minecraft:particle_lifetime_expression": { "max_lifetime": "v.particle_age + query.health" }
+        } } }"#).unwrap();
+        fs::write(pack.join("animations/wave.json"), r#"{ "animations": { "animation.wave": {
+            "bones": { "arm": { "rotation": ["q.anim_time * q.block_state('lit')", 0, "q.is_baby"] } }
+        } } }"#).unwrap();
+        fs::write(pack.join("blocks/lamp.json"), r#"{ "minecraft:block": { "permutations": [{ "condition": "q.block_state('lit') == 1" }] } }"#).unwrap();
+        fs::write(pack.join("entity/lamp.json"), r#"{ "minecraft:client_entity": { "description": { "identifier": "lab:lamp", "scripts": { "pre_animation": ["v.t = q.anim_time;"] } } } }"#).unwrap();
+
+        let issues: Vec<(String, String, String)> = validate_query_contexts(&pack).into_iter()
+            .map(|issue| (issue.file.to_string_lossy().replace('\\', "/"), issue.pointer, issue.query))
+            .collect();
+        assert_eq!(issues, vec![
+            ("animations/wave.json".to_string(), "/animations/animation.wave/bones/arm/rotation/0".to_string(), "block_state".to_string()),
+            ("entity/lamp.json".to_string(), "/minecraft:client_entity/description/scripts/pre_animation/0".to_string(), "anim_time".to_string()),
+            ("particles/spark.json".to_string(), "/particle_effect/components/minecraft:emitter_rate_instant/num_particles".to_string(), "is_baby".to_string()),
+            ("particles/spark.json".to_string(), "/particle_effect/components/minecraft:particle_lifetime_expression/max_lifetime".to_string(), "health".to_string())
+        ]);
+        let diagnostic: Diagnostic = validate_query_contexts(&pack).remove(0).into();
+        assert_eq!(diagnostic.message, "query.block_state is not available in an animation");
+        fs::remove_dir_all(&pack).unwrap();
+    }
+
+    #[cfg(all(unix, feature = "manifest"))]
+    #[test]
+    fn fallible_formatting() {
+        use std::ffi::OsStr;
+        use std::os::unix::ffi::OsStrExt;
+
+        assert!(deserialize_manifest_from_str("{ \"format_version\": 2").is_err());
+        let file = Path::new(OsStr::from_bytes(b"entities/bad\xff.json")).to_path_buf();
+        let diagnostic = Diagnostic::new(Severity::Error, "test", "broken".to_string()).at(file, String::new());
+        assert!(format_json(&[diagnostic]).contains("\"file\": \"entities/bad\u{FFFD}.json\""));
+    }
+
+    #[cfg(feature = "validation")]
+    #[test]
+    fn raw_documents() {
+        let manifest = deserialize_manifest_from_str(&fs::read_to_string("./inputs/manifest.json").unwrap()).unwrap();
+        assert_eq!(manifest.raw["format_version"], 2);
+
+        let documents = read_json_files(Path::new("./inputs/behavior_pack"), "dialogue");
+        assert_eq!(documents.len(), 1);
+        assert_eq!(documents[0].path, Path::new("dialogue/pig.dialogue.json"));
+        assert_eq!(documents[0].value["minecraft:npc_dialogue"]["scenes"][0]["scene_tag"], "pig_intro");
+    }
 }
@@ -0,0 +1,229 @@
+//! Combining two behavior packs into one.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use serde_json::Value;
+use crate::addon::{rename_in_value, replace_identifier};
+use crate::error::{read_to_string, AddonError, AddonResult};
+use crate::files::pack_files;
+use crate::utils::{from_json_str, JsonStyle};
+
+/// What [`merge_behavior_packs`] does when both packs define the same identifier.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum ConflictStrategy {
+    /// Fail without writing anything.
+    Error,
+    /// Keep the left definition and leave out the file of the right one.
+    PreferLeft,
+    /// Keep both, giving the right definition a free identifier (`gems:ruby` becomes `gems:ruby_2`)
+    /// and updating the right pack's references to it.
+    Rename
+}
+
+/// An identifier both packs define.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct MergeConflict {
+    /// The definition's top-level key, e.g. `minecraft:item`.
+    pub kind: String,
+    pub identifier: String,
+    /// The defining files, relative to their pack.
+    pub left: PathBuf,
+    pub right: PathBuf,
+    /// The identifier the right definition got with [`ConflictStrategy::Rename`].
+    pub renamed_to: Option<String>
+}
+
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct MergeReport {
+    /// Files written, relative to the output pack.
+    pub written: Vec<PathBuf>,
+    pub conflicts: Vec<MergeConflict>,
+    /// Files of the right pack left out, relative to it: its manifest, files of definitions that
+    /// lost a conflict, and other files at a path the left pack already has.
+    pub skipped: Vec<PathBuf>
+}
+
+/// The `(kind, identifier)` of every definition in a pack file.
+fn definitions(value: &Value) -> Vec<(String, String)> {
+    let Value::Object(map) = value else {
+        return vec![];
+    };
+    map.iter()
+        .filter_map(|(kind, body)| Some((kind.clone(), body.pointer("/description/identifier")?.as_str()?.to_string())))
+        .collect()
+}
+
+/// A file of a pack, relative to it, with its JSON and the style it's written in if it is JSON.
+type PackFile = (PathBuf, Option<(Value, JsonStyle)>);
+
+/// Every file of a pack.
+fn read_pack(root: &Path) -> AddonResult<Vec<PackFile>> {
+    let mut files = vec![];
+    for path in pack_files(root, "") {
+        let relative = path.strip_prefix(root).unwrap_or(&path).to_path_buf();
+        let json = if path.extension().is_some_and(|ext| ext == "json") {
+            let src = read_to_string(&path)?;
+            from_json_str::<Value>(&src).ok().map(|value| (value, JsonStyle::detect(&src)))
+        } else {
+            None
+        };
+        files.push((relative, json));
+    }
+    Ok(files)
+}
+
+/// Adds the entries of a registry file of `right` to the same file of `left`: the `values` of
+/// `functions/tick.json`, or the `texture_data` of an atlas-style file. `left` wins for keys both
+/// have. Returns `false` if the files aren't registries.
+fn merge_registry(left: &mut Value, right: &Value) -> bool {
+    if let (Some(Value::Array(left)), Some(Value::Array(right))) = (left.get_mut("values"), right.get("values")) {
+        for value in right {
+            if !left.contains(value) {
+                left.push(value.clone());
+            }
+        }
+        return true;
+    }
+    if let (Some(Value::Object(left)), Some(Value::Object(right))) = (left.get_mut("texture_data"), right.get("texture_data")) {
+        for (key, value) in right {
+            left.entry(key.clone()).or_insert_with(|| value.clone());
+        }
+        return true;
+    }
+    false
+}
+
+/// A path next to `path` that isn't in `taken`: `items/ruby.json` becomes `items/ruby_2.json`.
+fn free_path(path: &Path, taken: &BTreeSet<PathBuf>) -> PathBuf {
+    let stem = path.file_stem().unwrap_or_default().to_string_lossy().to_string();
+    let extension = path.extension().map(|ext| format!(".{}", ext.to_string_lossy())).unwrap_or_default();
+    (2..).map(|n| path.with_file_name(format!("{}_{}{}", stem, n, extension))).find(|path| !taken.contains(path)).unwrap_or_default()
+}
+
+/// Merges the behavior packs `left` and `right` into a new pack at `output`. Every definition of
+/// both packs ends up in it, with identifiers both define resolved by `strategy`. Registry files
+/// both packs have, like `functions/tick.json`, get the entries of both. The manifest and any other
+/// file at a path both packs use come from `left`, and right definition files whose path is taken
+/// are written next to it under a free name. Fails if `output` already exists.
+pub fn merge_behavior_packs(left: &Path, right: &Path, output: &Path, strategy: ConflictStrategy) -> AddonResult<MergeReport> {
+    if output.exists() {
+        return Err(AddonError::semantic(output, "already exists"));
+    }
+    let left_files = read_pack(left)?;
+    let right_files = read_pack(right)?;
+
+    let mut left_definitions: BTreeMap<(String, String), PathBuf> = BTreeMap::new();
+    for (path, json) in &left_files {
+        for definition in json.iter().flat_map(|(value, _)| definitions(value)) {
+            left_definitions.entry(definition).or_insert_with(|| path.clone());
+        }
+    }
+    let mut identifiers: BTreeSet<String> = left_definitions.keys().map(|(_, identifier)| identifier.clone()).collect();
+    for (_, json) in &right_files {
+        identifiers.extend(json.iter().flat_map(|(value, _)| definitions(value)).map(|(_, identifier)| identifier));
+    }
+
+    let mut report = MergeReport::default();
+    let mut renames: Vec<(String, String)> = vec![];
+    let mut losing_files = BTreeSet::new();
+    for (path, json) in &right_files {
+        for (kind, identifier) in json.iter().flat_map(|(value, _)| definitions(value)) {
+            let Some(left_path) = left_definitions.get(&(kind.clone(), identifier.clone())) else {
+                continue;
+            };
+            let renamed_to = match strategy {
+                ConflictStrategy::Error => {
+                    return Err(AddonError::semantic(right.join(path), format!("{} {} is also defined in {}", kind, identifier, left.join(left_path).display())));
+                }
+                ConflictStrategy::PreferLeft => {
+                    losing_files.insert(path.clone());
+                    None
+                }
+                ConflictStrategy::Rename => {
+                    let renamed = (2..).map(|n| format!("{}_{}", identifier, n)).find(|renamed| !identifiers.contains(renamed)).unwrap_or_default();
+                    identifiers.insert(renamed.clone());
+                    renames.push((identifier.clone(), renamed.clone()));
+                    Some(renamed)
+                }
+            };
+            report.conflicts.push(MergeConflict { kind, identifier, left: left_path.clone(), right: path.clone(), renamed_to });
+        }
+    }
+    let rename = |text: &str| {
+        let mut renamed: Option<String> = None;
+        for (old, new) in &renames {
+            if let Some(result) = replace_identifier(renamed.as_deref().unwrap_or(text), old, new) {
+                renamed = Some(result);
+            }
+        }
+        renamed
+    };
+
+    let mut outputs: BTreeMap<PathBuf, Vec<u8>> = BTreeMap::new();
+    let mut merged: BTreeMap<PathBuf, (Value, JsonStyle)> = BTreeMap::new();
+    for (path, json) in &left_files {
+        match json {
+            Some(json) => {
+                merged.insert(path.clone(), json.clone());
+            }
+            None => {
+                let source = left.join(path);
+                outputs.insert(path.clone(), fs::read(&source).map_err(|error| AddonError::io(&source, error))?);
+            }
+        }
+    }
+    let mut taken: BTreeSet<PathBuf> = left_files.iter().map(|(path, _)| path.clone()).collect();
+
+    for (path, json) in &right_files {
+        if path == Path::new("manifest.json") || losing_files.contains(path) {
+            report.skipped.push(path.clone());
+            continue;
+        }
+        match json {
+            Some((value, style)) => {
+                let mut value = value.clone();
+                rename_in_value(&mut value, &rename);
+                let is_definition = !definitions(&value).is_empty();
+                if let Some((existing, _)) = merged.get_mut(path) {
+                    if merge_registry(existing, &value) {
+                        continue;
+                    }
+                    if !is_definition {
+                        report.skipped.push(path.clone());
+                        continue;
+                    }
+                    let free = free_path(path, &taken);
+                    taken.insert(free.clone());
+                    merged.insert(free, (value, style.clone()));
+                } else {
+                    taken.insert(path.clone());
+                    merged.insert(path.clone(), (value, style.clone()));
+                }
+            }
+            None if taken.contains(path) => report.skipped.push(path.clone()),
+            None => {
+                let source = right.join(path);
+                let mut bytes = fs::read(&source).map_err(|error| AddonError::io(&source, error))?;
+                if path.extension().is_some_and(|ext| ext == "mcfunction") {
+                    if let Some(renamed) = String::from_utf8(bytes.clone()).ok().and_then(|src| rename(&src)) {
+                        bytes = renamed.into_bytes();
+                    }
+                }
+                taken.insert(path.clone());
+                outputs.insert(path.clone(), bytes);
+            }
+        }
+    }
+
+    outputs.extend(merged.into_iter().map(|(path, (value, style))| (path, style.write(&value).into_bytes())));
+    for (path, contents) in outputs {
+        let target = output.join(&path);
+        if let Some(parent) = target.parent() {
+            fs::create_dir_all(parent).map_err(|error| AddonError::io(parent, error))?;
+        }
+        fs::write(&target, contents).map_err(|error| AddonError::io(&target, error))?;
+        report.written.push(path);
+    }
+    Ok(report)
+}
@@ -0,0 +1,504 @@
+//! Upgrading definitions written in deprecated formats to the current ones.
+
+use std::path::{Path, PathBuf};
+use serde_json::{json, Map, Value};
+use crate::diagnostics::{Diagnostic, Severity};
+use crate::error::AddonResult;
+use crate::files::{pack_files, read_json_file, read_json_files, EditableJson};
+use crate::generics::entity::{deserialize_behavior_entity_from_value, serialize_behavior_entity_to_value, EntityLayout};
+use crate::utils::{json_pointer, parse_format_version_from_str, SemVer};
+
+/// The format version migrated definitions are written with.
+pub const MIGRATED_FORMAT_VERSION: &str = "1.21.0";
+
+/// Holiday Creator Features components that trigger events. The stable formats replace them with
+/// custom components implemented in scripts, which can't be derived from the JSON.
+const BLOCK_TRIGGERS: [&str; 10] = [
+    "minecraft:on_interact", "minecraft:on_step_on", "minecraft:on_step_off", "minecraft:on_fall_on", "minecraft:on_placed",
+    "minecraft:on_player_placing", "minecraft:on_player_destroyed", "minecraft:random_ticking", "minecraft:queued_ticking", "minecraft:ticking"
+];
+const ITEM_TRIGGERS: [&str; 2] = ["minecraft:on_use", "minecraft:on_use_on"];
+/// Components without a stable equivalent.
+const REMOVED_COMPONENTS: [&str; 5] = ["minecraft:breathability", "minecraft:block_friction", "minecraft:part_visibility", "minecraft:dye_powder", "minecraft:mining_speed"];
+
+fn note(severity: Severity, pointer: String, message: String) -> Diagnostic {
+    let code = if severity == Severity::Info { "migrated" } else { "manual-migration" };
+    Diagnostic { pointer: Some(pointer), ..Diagnostic::new(severity, code, message) }
+}
+
+/// The stable replacement of an experimental block or item component, or `None` if `name` is
+/// current or has none.
+fn stable_component(name: &str, value: &Value) -> Option<(&'static str, Value)> {
+    let converted = match name {
+        // The experimental emission was a fraction of the brightest light.
+        "minecraft:block_light_emission" => ("minecraft:light_emission", json!((value.as_f64()?.clamp(0.0, 1.0) * 15.0).round() as i64)),
+        "minecraft:block_light_filter" | "minecraft:block_light_absorption" => ("minecraft:light_dampening", json!(value.as_f64()?.clamp(0.0, 15.0).round() as i64)),
+        "minecraft:destroy_time" => ("minecraft:destructible_by_mining", json!({ "seconds_to_destroy": value })),
+        "minecraft:explosion_resistance" => ("minecraft:destructible_by_explosion", json!({ "explosion_resistance": value })),
+        "minecraft:aim_collision" | "minecraft:pick_collision" => ("minecraft:selection_box", value.clone()),
+        "minecraft:entity_collision" => ("minecraft:collision_box", value.clone()),
+        "minecraft:unit_cube" => ("minecraft:geometry", json!("minecraft:geometry.full_block")),
+        "minecraft:foil" => ("minecraft:glint", value.clone()),
+        "minecraft:max_damage" => ("minecraft:durability", json!({ "max_durability": value })),
+        "minecraft:crafting_table" if value.get("custom_description").is_some() || value.get("grid_size").is_some() => {
+            let mut table = value.as_object()?.clone();
+            if let Some(description) = table.remove("custom_description") {
+                table.insert("table_name".to_string(), description);
+            }
+            // Only 3x3 grids were ever supported.
+            table.remove("grid_size");
+            ("minecraft:crafting_table", Value::Object(table))
+        }
+        _ => return None
+    };
+    Some(converted)
+}
+
+/// Migrates one component object, moving a `minecraft:creative_category` component into
+/// `menu_category`.
+fn migrate_components(components: &mut Map<String, Value>, pointer: &str, triggers: &[&str], menu_category: &mut Option<Value>, notes: &mut Vec<Diagnostic>) {
+    let names: Vec<String> = components.keys().cloned().collect();
+    for name in names {
+        let at = format!("{}{}", pointer, json_pointer(&[&name]));
+        if name == "minecraft:creative_category" {
+            if let Some(category) = components.remove(&name) {
+                let mut category = category.as_object().cloned().unwrap_or_default();
+                category.retain(|key, _| key == "category" || key == "group");
+                *menu_category = Some(Value::Object(category));
+                notes.push(note(Severity::Info, at, "moved minecraft:creative_category to menu_category in the description".to_string()));
+            }
+            continue;
+        }
+        if triggers.contains(&name.as_str()) {
+            notes.push(note(Severity::Warning, at, format!("{} triggers events, which need a custom component implemented in a script", name)));
+            continue;
+        }
+        if REMOVED_COMPONENTS.contains(&name.as_str()) {
+            notes.push(note(Severity::Warning, at, format!("{} has no stable equivalent", name)));
+            continue;
+        }
+        if let Some(on) = components[&name].as_object().and_then(|component| component.keys().find(|key| key.starts_with("on_")).cloned()) {
+            notes.push(note(Severity::Warning, format!("{}{}", at, json_pointer(&[&on])), format!("{} of {} triggers an event, which needs a script", on, name)));
+        }
+        let Some((stable, value)) = stable_component(&name, &components[&name]) else {
+            continue;
+        };
+        if stable != name && components.contains_key(stable) {
+            notes.push(note(Severity::Warning, at, format!("{} and its replacement {} are both present", name, stable)));
+            continue;
+        }
+        components.remove(&name);
+        components.insert(stable.to_string(), value);
+        let message = if stable == name { format!("updated {} to its stable fields", name) } else { format!("replaced {} with {}", name, stable) };
+        notes.push(note(Severity::Info, at, message));
+    }
+}
+
+/// Replaces block property queries in every string below `value` with block state queries.
+fn migrate_property_queries(value: &mut Value) -> bool {
+    match value {
+        Value::String(text) => {
+            let migrated = text.replace("query.block_property(", "q.block_state(").replace("q.block_property(", "q.block_state(");
+            let changed = migrated != *text;
+            *text = migrated;
+            changed
+        }
+        Value::Array(values) => values.iter_mut().map(migrate_property_queries).filter(|changed| *changed).count() > 0,
+        Value::Object(map) => map.values_mut().map(migrate_property_queries).filter(|changed| *changed).count() > 0,
+        _ => false
+    }
+}
+
+/// Migrates the definition below `key` of a block or item document to the stable format, shared by
+/// [`migrate_experimental_block`] and [`migrate_experimental_item`].
+fn migrate_definition(document: &mut Value, key: &str, triggers: &[&str]) -> Vec<Diagnostic> {
+    let mut notes = vec![];
+    let base = json_pointer(&[key]);
+    let Some(Value::Object(definition)) = document.get_mut(key) else {
+        return notes;
+    };
+
+    let mut menu_category = None;
+    if let Some(Value::Object(components)) = definition.get_mut("components") {
+        migrate_components(components, &format!("{}/components", base), triggers, &mut menu_category, &mut notes);
+    }
+    if let Some(Value::Array(permutations)) = definition.get_mut("permutations") {
+        for (i, permutation) in permutations.iter_mut().enumerate() {
+            if let Some(Value::Object(components)) = permutation.get_mut("components") {
+                migrate_components(components, &format!("{}/permutations/{}/components", base, i), triggers, &mut menu_category, &mut notes);
+            }
+            if permutation.get_mut("condition").is_some_and(migrate_property_queries) {
+                notes.push(note(Severity::Info, format!("{}/permutations/{}/condition", base, i), "replaced block property queries with block state queries".to_string()));
+            }
+        }
+    }
+    if definition.contains_key("events") {
+        notes.push(note(Severity::Warning, format!("{}/events", base), "events need to be rewritten as custom components implemented in a script".to_string()));
+    }
+
+    if let Some(Value::Object(description)) = definition.get_mut("description") {
+        if let Some(properties) = description.remove("properties") {
+            description.insert("states".to_string(), properties);
+            notes.push(note(Severity::Info, format!("{}/description/properties", base), "renamed properties to states".to_string()));
+        }
+        // Items of the 1.16 format had their category directly in the description.
+        if let Some(category) = description.remove("category") {
+            menu_category.get_or_insert_with(|| json!({ "category": category.as_str().unwrap_or("items").to_lowercase() }));
+            notes.push(note(Severity::Info, format!("{}/description/category", base), "moved category to menu_category".to_string()));
+        }
+        if let Some(menu_category) = menu_category {
+            description.entry("menu_category").or_insert(menu_category);
+        }
+    }
+
+    if notes.iter().any(|note| note.severity == Severity::Info) {
+        document["format_version"] = json!(MIGRATED_FORMAT_VERSION);
+    }
+    notes
+}
+
+/// Migrates a block document written with the Holiday Creator Features experiment to the stable
+/// block format: renamed and reshaped components, `minecraft:creative_category` and `properties`
+/// moved into the description, and `query.block_property` replaced by `q.block_state`. Event
+/// triggers and components without a stable equivalent are left in place and reported as
+/// warnings. The format version is raised if anything was migrated. Pointers are relative to the
+/// document.
+pub fn migrate_experimental_block(document: &mut Value) -> Vec<Diagnostic> {
+    migrate_definition(document, "minecraft:block", &BLOCK_TRIGGERS)
+}
+
+/// Like [`migrate_experimental_block`], for item documents.
+pub fn migrate_experimental_item(document: &mut Value) -> Vec<Diagnostic> {
+    migrate_definition(document, "minecraft:item", &ITEM_TRIGGERS)
+}
+
+/// The layout of an item document, told apart by its format version.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum ItemFormat {
+    /// Before 1.16.100: simple components, with the icon in a separate resource pack item file.
+    V1_10,
+    /// 1.16.100 until the stable format: the Holiday Creator Features components and events.
+    V1_16,
+    /// 1.20.10 and later.
+    Current
+}
+
+/// The [`ItemFormat`] of an item document. A missing or unreadable format version counts as the
+/// oldest format.
+pub fn item_format(document: &Value) -> ItemFormat {
+    let version = document.get("format_version").and_then(Value::as_str).and_then(parse_format_version_from_str);
+    match version {
+        Some(version) if version >= SemVer::new(1, 20, 10) => ItemFormat::Current,
+        Some(version) if version >= SemVer::new(1, 16, 100) => ItemFormat::V1_16,
+        _ => ItemFormat::V1_10
+    }
+}
+
+/// The current form of a component of the 1.10 item format, or `None` if it didn't change.
+fn current_item_component(name: &str, value: &Value) -> Option<(&'static str, Value)> {
+    let converted = match name {
+        "minecraft:hand_equipped" if value.is_boolean() => ("minecraft:hand_equipped", json!({ "value": value })),
+        "minecraft:stacked_by_data" if value.is_boolean() => ("minecraft:stacked_by_data", json!({ "value": value })),
+        "minecraft:max_stack_size" if value.is_number() => ("minecraft:max_stack_size", json!({ "value": value })),
+        // Ticks became seconds.
+        "minecraft:use_duration" => ("minecraft:use_modifiers", json!({ "use_duration": value.as_f64()? / 20.0 })),
+        "minecraft:block" => ("minecraft:block_placer", json!({ "block": value })),
+        "minecraft:seed" => {
+            let mut placer = json!({ "block": value.get("crop_result")? });
+            if let Some(plant_at) = value.get("plant_at") {
+                placer["use_on"] = if plant_at.is_array() { plant_at.clone() } else { json!([plant_at]) };
+            }
+            ("minecraft:block_placer", placer)
+        }
+        _ => return None
+    };
+    Some(converted)
+}
+
+/// Components of the 1.10 item format without a current equivalent.
+const REMOVED_ITEM_COMPONENTS: [&str; 2] = ["minecraft:camera", "minecraft:explodable"];
+
+/// Upgrades an item document of any [`ItemFormat`] to the current one. Components of the 1.10
+/// format get their current form, e.g. `minecraft:hand_equipped: true` becomes
+/// `{ "value": true }` and `minecraft:seed` becomes `minecraft:block_placer`, and the icon and
+/// render offsets of the matching resource pack item file, if given, move into the components.
+/// Everything of the 1.16 format is then [migrated](migrate_experimental_item) like experimental
+/// content. Items already in the current format are left alone.
+pub fn migrate_legacy_item(document: &mut Value, resource_item: Option<&Value>) -> Vec<Diagnostic> {
+    let format = item_format(document);
+    if format == ItemFormat::Current {
+        return vec![];
+    }
+    let mut notes = vec![];
+    if format == ItemFormat::V1_10 {
+        if let Some(Value::Object(components)) = document.pointer_mut("/minecraft:item/components") {
+            let names: Vec<String> = components.keys().cloned().collect();
+            for name in names {
+                let at = format!("/minecraft:item/components{}", json_pointer(&[&name]));
+                if REMOVED_ITEM_COMPONENTS.contains(&name.as_str()) {
+                    notes.push(note(Severity::Warning, at, format!("{} has no current equivalent", name)));
+                    continue;
+                }
+                let Some((current, value)) = current_item_component(&name, &components[&name]) else {
+                    continue;
+                };
+                if current != name && components.contains_key(current) {
+                    notes.push(note(Severity::Warning, at, format!("{} and its replacement {} are both present", name, current)));
+                    continue;
+                }
+                components.remove(&name);
+                components.insert(current.to_string(), value);
+                let message = if current == name { format!("updated {} to its current form", name) } else { format!("replaced {} with {}", name, current) };
+                notes.push(note(Severity::Info, at, message));
+            }
+            let resource_components = resource_item.and_then(|item| item.pointer("/minecraft:item/components")).and_then(Value::as_object);
+            for (name, value) in resource_components.into_iter().flatten() {
+                if components.contains_key(name) {
+                    continue;
+                }
+                let value = match (name.as_str(), value) {
+                    ("minecraft:icon", Value::String(texture)) => json!({ "texture": texture }),
+                    _ => value.clone()
+                };
+                components.insert(name.clone(), value);
+                notes.push(note(Severity::Info, format!("/minecraft:item/components{}", json_pointer(&[name])), format!("moved {} from the resource pack item", name)));
+            }
+        }
+    }
+    let legacy_changes = notes.iter().any(|note| note.severity == Severity::Info);
+    notes.extend(migrate_experimental_item(document));
+    if legacy_changes {
+        document["format_version"] = json!(MIGRATED_FORMAT_VERSION);
+    }
+    notes
+}
+
+/// The layout of a block document, told apart by its format version.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum BlockFormat {
+    /// Before 1.16.100: flat components without states or permutations, with the textures and
+    /// sounds in the resource pack's `blocks.json`.
+    V1_10,
+    /// 1.16.100 until the stable format: the Holiday Creator Features components, properties and
+    /// events.
+    V1_16,
+    /// 1.20.0 and later.
+    Current
+}
+
+/// The [`BlockFormat`] of a block document. A missing or unreadable format version counts as the
+/// oldest format.
+pub fn block_format(document: &Value) -> BlockFormat {
+    let version = document.get("format_version").and_then(Value::as_str).and_then(parse_format_version_from_str);
+    match version {
+        Some(version) if version >= SemVer::new(1, 20, 0) => BlockFormat::Current,
+        Some(version) if version >= SemVer::new(1, 16, 100) => BlockFormat::V1_16,
+        _ => BlockFormat::V1_10
+    }
+}
+
+/// The current form of a component of the 1.10 block format, or `None` if it didn't change.
+fn current_block_component(name: &str, value: &Value) -> Option<(&'static str, Value)> {
+    let converted = match name {
+        // The old value was how slippery the block is, the current one how much it slows entities.
+        "minecraft:friction" => ("minecraft:friction", json!(((1.0 - value.as_f64()?.clamp(0.0, 1.0)) * 1000.0).round() / 1000.0)),
+        "minecraft:flammable" if value.get("flame_odds").is_some() || value.get("burn_odds").is_some() => {
+            let mut flammable = json!({});
+            if let Some(odds) = value.get("flame_odds") {
+                flammable["catch_chance_modifier"] = odds.clone();
+            }
+            if let Some(odds) = value.get("burn_odds") {
+                flammable["destroy_chance_modifier"] = odds.clone();
+            }
+            ("minecraft:flammable", flammable)
+        }
+        // Blockbench exported rotations before blocks could be transformed.
+        "minecraft:rotation" => ("minecraft:transformation", json!({ "rotation": value })),
+        _ => return None
+    };
+    Some(converted)
+}
+
+/// Components of the 1.10 and early 1.16 block formats without a current equivalent.
+const REMOVED_BLOCK_COMPONENTS: [&str; 5] = ["minecraft:breakonpush", "minecraft:immovable", "minecraft:onlypistonpush", "minecraft:preventsjumping", "minecraft:unwalkable"];
+
+/// The material instances of a `blocks.json` entry: one texture for every face, or one per face
+/// named like `up`, `down` and `side`.
+fn material_instances(entry: &Value) -> Option<Value> {
+    match entry.get("textures")? {
+        Value::String(texture) => Some(json!({ "*": { "texture": texture } })),
+        Value::Object(faces) => {
+            let mut instances = serde_json::Map::new();
+            for (face, texture) in faces {
+                let face = if face == "side" { "*" } else { face.as_str() };
+                instances.insert(face.to_string(), json!({ "texture": texture }));
+            }
+            Some(Value::Object(instances))
+        }
+        _ => None
+    }
+}
+
+/// Upgrades a block document of any [`BlockFormat`] to the current one. Components of the 1.10
+/// format get their current form, e.g. the slipperiness of `minecraft:friction` becomes the
+/// friction it stands for, the description's `register_to_creative_menu` becomes a
+/// `menu_category`, and the textures of the block's `blocks.json` entry, if given, become
+/// `minecraft:material_instances` on a full block geometry. Everything of the 1.16 format is then
+/// [migrated](migrate_experimental_block) like experimental content, including properties to
+/// states. Components that no longer exist are reported as warnings. Blocks already in the current
+/// format are left alone.
+pub fn migrate_legacy_block(document: &mut Value, blocks_entry: Option<&Value>) -> Vec<Diagnostic> {
+    let format = block_format(document);
+    if format == BlockFormat::Current {
+        return vec![];
+    }
+    let mut notes = vec![];
+    if let Some(Value::Object(components)) = document.pointer_mut("/minecraft:block/components") {
+        let names: Vec<String> = components.keys().cloned().collect();
+        for name in names {
+            let at = format!("/minecraft:block/components{}", json_pointer(&[&name]));
+            if REMOVED_BLOCK_COMPONENTS.contains(&name.as_str()) {
+                notes.push(note(Severity::Warning, at, format!("{} has no current equivalent", name)));
+                continue;
+            }
+            if format != BlockFormat::V1_10 {
+                continue;
+            }
+            let Some((current, value)) = current_block_component(&name, &components[&name]) else {
+                continue;
+            };
+            components.remove(&name);
+            components.insert(current.to_string(), value);
+            let message = if current == name { format!("updated {} to its current form", name) } else { format!("replaced {} with {}", name, current) };
+            notes.push(note(Severity::Info, at, message));
+        }
+        if format == BlockFormat::V1_10 && !components.contains_key("minecraft:material_instances") {
+            if let Some(instances) = blocks_entry.and_then(material_instances) {
+                components.insert("minecraft:material_instances".to_string(), instances);
+                components.entry("minecraft:geometry").or_insert_with(|| json!("minecraft:geometry.full_block"));
+                notes.push(note(Severity::Info, "/minecraft:block/components/minecraft:material_instances".to_string(), "moved the textures of blocks.json into material instances".to_string()));
+            }
+        }
+    }
+    if format == BlockFormat::V1_10 {
+        if let Some(Value::Object(description)) = document.pointer_mut("/minecraft:block/description") {
+            if description.remove("is_experimental").is_some() {
+                notes.push(note(Severity::Info, "/minecraft:block/description/is_experimental".to_string(), "removed is_experimental".to_string()));
+            }
+            if let Some(register) = description.remove("register_to_creative_menu") {
+                if register == json!(true) {
+                    description.entry("menu_category").or_insert_with(|| json!({ "category": "construction" }));
+                }
+                notes.push(note(Severity::Info, "/minecraft:block/description/register_to_creative_menu".to_string(), "replaced register_to_creative_menu with menu_category".to_string()));
+            }
+        }
+    }
+    let legacy_changes = notes.iter().any(|note| note.severity == Severity::Info);
+    notes.extend(migrate_experimental_block(document));
+    if legacy_changes {
+        document["format_version"] = json!(MIGRATED_FORMAT_VERSION);
+    }
+    notes
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct MigrationReport {
+    /// Files rewritten, relative to the pack.
+    pub changed: Vec<PathBuf>,
+    /// Every change made as an info, and everything left for the author as a warning.
+    pub diagnostics: Vec<Diagnostic>
+}
+
+/// Migrates every block in `blocks/` and item in `items/` of `behavior_pack` with
+/// [`migrate_experimental_block`] and [`migrate_experimental_item`], keeping each file's style.
+pub fn migrate_experimental_content(behavior_pack: &Path) -> AddonResult<MigrationReport> {
+    let mut report = MigrationReport::default();
+    for (dir, migrate) in [("blocks", migrate_experimental_block as fn(&mut Value) -> Vec<Diagnostic>), ("items", migrate_experimental_item)] {
+        for path in pack_files(behavior_pack, dir) {
+            if path.extension().is_none_or(|ext| ext != "json") {
+                continue;
+            }
+            let relative = path.strip_prefix(behavior_pack).unwrap_or(&path).to_path_buf();
+            let mut file = EditableJson::open(&path)?;
+            let notes = migrate(&mut file.value);
+            if notes.iter().any(|note| note.severity == Severity::Info) {
+                file.save()?;
+                report.changed.push(relative.clone());
+            }
+            report.diagnostics.extend(notes.into_iter().map(|note| Diagnostic { file: Some(relative.clone()), ..note }));
+        }
+    }
+    Ok(report)
+}
+
+/// Upgrades every item in `items/` of `behavior_pack` with [`migrate_legacy_item`], pairing each
+/// with the item of the same identifier in `items/` of `resource_pack`. The resource pack files
+/// are left for the author to delete.
+pub fn migrate_legacy_items(behavior_pack: &Path, resource_pack: Option<&Path>) -> AddonResult<MigrationReport> {
+    let resource_items: Vec<Value> = resource_pack.map(|rp| read_json_files(rp, "items")).unwrap_or_default().into_iter().map(|file| file.value).collect();
+    let mut report = MigrationReport::default();
+    for path in pack_files(behavior_pack, "items") {
+        if path.extension().is_none_or(|ext| ext != "json") {
+            continue;
+        }
+        let relative = path.strip_prefix(behavior_pack).unwrap_or(&path).to_path_buf();
+        let mut file = EditableJson::open(&path)?;
+        let identifier = file.value.pointer("/minecraft:item/description/identifier").cloned();
+        let resource_item = resource_items.iter().find(|item| identifier.is_some() && item.pointer("/minecraft:item/description/identifier") == identifier.as_ref());
+        let notes = migrate_legacy_item(&mut file.value, resource_item);
+        if notes.iter().any(|note| note.severity == Severity::Info) {
+            file.save()?;
+            report.changed.push(relative.clone());
+        }
+        report.diagnostics.extend(notes.into_iter().map(|note| Diagnostic { file: Some(relative.clone()), ..note }));
+    }
+    Ok(report)
+}
+
+/// Upgrades every block in `blocks/` of `behavior_pack` with [`migrate_legacy_block`], passing each
+/// its entry of `blocks.json` in `resource_pack`. The `blocks.json` entries are left in place; the
+/// game still reads their sounds.
+pub fn migrate_legacy_blocks(behavior_pack: &Path, resource_pack: Option<&Path>) -> AddonResult<MigrationReport> {
+    let blocks_json = resource_pack.and_then(|rp| read_json_file(rp, "blocks.json")).map(|file| file.value).unwrap_or(Value::Null);
+    let mut report = MigrationReport::default();
+    for path in pack_files(behavior_pack, "blocks") {
+        if path.extension().is_none_or(|ext| ext != "json") {
+            continue;
+        }
+        let relative = path.strip_prefix(behavior_pack).unwrap_or(&path).to_path_buf();
+        let mut file = EditableJson::open(&path)?;
+        let identifier = file.value.pointer("/minecraft:block/description/identifier").and_then(Value::as_str).unwrap_or_default().to_string();
+        let notes = migrate_legacy_block(&mut file.value, blocks_json.get(&identifier));
+        if notes.iter().any(|note| note.severity == Severity::Info) {
+            file.save()?;
+            report.changed.push(relative.clone());
+        }
+        report.diagnostics.extend(notes.into_iter().map(|note| Diagnostic { file: Some(relative.clone()), ..note }));
+    }
+    Ok(report)
+}
+
+/// Rewrites every entity in `entities/` of `behavior_pack` written in an older [`EntityLayout`]
+/// in the current one, keeping each file's style.
+pub fn migrate_legacy_entities(behavior_pack: &Path) -> AddonResult<MigrationReport> {
+    let mut report = MigrationReport::default();
+    for path in pack_files(behavior_pack, "entities") {
+        if path.extension().is_none_or(|ext| ext != "json") {
+            continue;
+        }
+        let relative = path.strip_prefix(behavior_pack).unwrap_or(&path).to_path_buf();
+        let mut file = EditableJson::open(&path)?;
+        let Some(entity) = deserialize_behavior_entity_from_value(&file.value) else {
+            continue;
+        };
+        if entity.layout == EntityLayout::Current {
+            continue;
+        }
+        file.value = serialize_behavior_entity_to_value(&entity);
+        file.save()?;
+        let message = format!("rewrote the {:?} layout in the current one", entity.layout);
+        report.diagnostics.push(Diagnostic { file: Some(relative.clone()), ..note(Severity::Info, "/minecraft:entity".to_string(), message) });
+        report.changed.push(relative);
+    }
+    Ok(report)
+}
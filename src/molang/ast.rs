@@ -0,0 +1,338 @@
+use std::collections::HashMap;
+use std::fmt::{Display, Formatter};
+use once_cell::sync::Lazy;
+
+/// A parsed Molang source: either a single simple expression or a list of `;`-terminated statements.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Program {
+    pub statements: Vec<Expr>,
+    pub complex: bool
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Expr {
+    Number(f32),
+    String(String),
+    Name(Name),
+    Call(Name, Vec<Expr>),
+    This,
+    Unary(UnaryOp, Box<Expr>),
+    Binary(BinaryOp, Box<Expr>, Box<Expr>),
+    Conditional(Box<Expr>, Box<Expr>, Option<Box<Expr>>),
+    Index(Box<Expr>, Box<Expr>),
+    Arrow(Box<Expr>, Box<Expr>),
+    Assign(Box<Expr>, Box<Expr>),
+    Block(Vec<Expr>),
+    Loop(Box<Expr>, Box<Expr>),
+    ForEach(Box<Expr>, Box<Expr>, Box<Expr>),
+    Return(Box<Expr>),
+    Break,
+    Continue
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum UnaryOp {
+    Not,
+    Negate
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum BinaryOp {
+    Coalesce,
+    Or,
+    And,
+    Equal,
+    NotEqual,
+    Less,
+    LessEqual,
+    Greater,
+    GreaterEqual,
+    Add,
+    Subtract,
+    Multiply,
+    Divide
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+pub enum Namespace {
+    Query,
+    Math,
+    Variable,
+    Temp,
+    Context,
+    Geometry,
+    Material,
+    Texture,
+    Array
+}
+
+/// A dotted identifier such as `q.is_baby` or `v.location.x`; the namespace alias is normalized.
+#[derive(Clone, Debug, Eq, PartialEq, Hash)]
+pub struct Name {
+    pub namespace: Namespace,
+    pub path: Vec<String>
+}
+
+pub(crate) static NAMESPACES: Lazy<HashMap<&str, Namespace>> = Lazy::new(|| HashMap::from(
+    [
+        ("q", Namespace::Query),
+        ("query", Namespace::Query),
+        ("math", Namespace::Math),
+        ("v", Namespace::Variable),
+        ("variable", Namespace::Variable),
+        ("t", Namespace::Temp),
+        ("temp", Namespace::Temp),
+        ("c", Namespace::Context),
+        ("context", Namespace::Context),
+        ("geometry", Namespace::Geometry),
+        ("material", Namespace::Material),
+        ("texture", Namespace::Texture),
+        ("array", Namespace::Array),
+    ]
+));
+
+impl Namespace {
+    /// The shortest spelling the game accepts for this namespace.
+    pub fn short_name(&self) -> &'static str {
+        match self {
+            Namespace::Query => "q",
+            Namespace::Math => "math",
+            Namespace::Variable => "v",
+            Namespace::Temp => "t",
+            Namespace::Context => "c",
+            Namespace::Geometry => "geometry",
+            Namespace::Material => "material",
+            Namespace::Texture => "texture",
+            Namespace::Array => "array"
+        }
+    }
+}
+
+impl BinaryOp {
+    pub fn symbol(&self) -> &'static str {
+        match self {
+            BinaryOp::Coalesce => "??",
+            BinaryOp::Or => "||",
+            BinaryOp::And => "&&",
+            BinaryOp::Equal => "==",
+            BinaryOp::NotEqual => "!=",
+            BinaryOp::Less => "<",
+            BinaryOp::LessEqual => "<=",
+            BinaryOp::Greater => ">",
+            BinaryOp::GreaterEqual => ">=",
+            BinaryOp::Add => "+",
+            BinaryOp::Subtract => "-",
+            BinaryOp::Multiply => "*",
+            BinaryOp::Divide => "/"
+        }
+    }
+
+    pub(crate) fn precedence(&self) -> u8 {
+        match self {
+            BinaryOp::Coalesce => PREC_COALESCE,
+            BinaryOp::Or => PREC_OR,
+            BinaryOp::And => PREC_AND,
+            BinaryOp::Equal | BinaryOp::NotEqual => PREC_EQUALITY,
+            BinaryOp::Less | BinaryOp::LessEqual | BinaryOp::Greater | BinaryOp::GreaterEqual => PREC_RELATIONAL,
+            BinaryOp::Add | BinaryOp::Subtract => PREC_ADDITIVE,
+            BinaryOp::Multiply | BinaryOp::Divide => PREC_MULTIPLICATIVE
+        }
+    }
+}
+
+pub(crate) const PREC_STATEMENT: u8 = 0;
+pub(crate) const PREC_ASSIGN: u8 = 1;
+pub(crate) const PREC_CONDITIONAL: u8 = 2;
+pub(crate) const PREC_COALESCE: u8 = 3;
+pub(crate) const PREC_OR: u8 = 4;
+pub(crate) const PREC_AND: u8 = 5;
+pub(crate) const PREC_EQUALITY: u8 = 6;
+pub(crate) const PREC_RELATIONAL: u8 = 7;
+pub(crate) const PREC_ADDITIVE: u8 = 8;
+pub(crate) const PREC_MULTIPLICATIVE: u8 = 9;
+pub(crate) const PREC_UNARY: u8 = 10;
+pub(crate) const PREC_POSTFIX: u8 = 11;
+pub(crate) const PREC_PRIMARY: u8 = 12;
+
+impl Expr {
+    pub(crate) fn precedence(&self) -> u8 {
+        match self {
+            Expr::Return(_) | Expr::Break | Expr::Continue => PREC_STATEMENT,
+            Expr::Assign(_, _) => PREC_ASSIGN,
+            Expr::Conditional(_, _, _) => PREC_CONDITIONAL,
+            Expr::Binary(op, _, _) => op.precedence(),
+            Expr::Unary(_, _) => PREC_UNARY,
+            Expr::Number(n) if *n < 0.0 => PREC_UNARY,
+            Expr::Index(_, _) | Expr::Arrow(_, _) => PREC_POSTFIX,
+            _ => PREC_PRIMARY
+        }
+    }
+
+    /// The direct subexpressions of this expression, in source order.
+    pub fn children(&self) -> Vec<&Expr> {
+        match self {
+            Expr::Number(_) | Expr::String(_) | Expr::Name(_) | Expr::This | Expr::Break | Expr::Continue => vec![],
+            Expr::Call(_, args) => args.iter().collect(),
+            Expr::Block(statements) => statements.iter().collect(),
+            Expr::Unary(_, e) | Expr::Return(e) => vec![e],
+            Expr::Binary(_, l, r) | Expr::Index(l, r) | Expr::Arrow(l, r) | Expr::Assign(l, r) | Expr::Loop(l, r) => vec![l, r],
+            Expr::Conditional(c, t, e) => [Some(c), Some(t), e.as_ref()].into_iter().flatten().map(|e| e.as_ref()).collect(),
+            Expr::ForEach(variable, array, body) => vec![variable, array, body]
+        }
+    }
+
+    /// Whether evaluating this expression can have an observable effect besides producing its value.
+    pub fn has_side_effects(&self) -> bool {
+        match self {
+            Expr::Assign(_, _) | Expr::Block(_) | Expr::Loop(_, _) | Expr::ForEach(_, _, _)
+            | Expr::Return(_) | Expr::Break | Expr::Continue => true,
+            Expr::Number(_) | Expr::String(_) | Expr::Name(_) | Expr::This => false,
+            Expr::Call(_, args) => args.iter().any(Expr::has_side_effects),
+            Expr::Unary(_, e) => e.has_side_effects(),
+            Expr::Binary(_, l, r) | Expr::Index(l, r) | Expr::Arrow(l, r) => l.has_side_effects() || r.has_side_effects(),
+            Expr::Conditional(c, t, e) => {
+                c.has_side_effects() || t.has_side_effects() || e.as_ref().is_some_and(|e| e.has_side_effects())
+            }
+        }
+    }
+}
+
+impl Display for Name {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}.{}", self.namespace.short_name(), self.path.join("."))
+    }
+}
+
+impl Display for Program {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        if self.complex {
+            for statement in &self.statements {
+                write_expr(f, statement, PREC_STATEMENT)?;
+                write!(f, ";")?;
+            }
+            Ok(())
+        } else {
+            match self.statements.first() {
+                Some(expr) => write_expr(f, expr, PREC_STATEMENT),
+                None => Ok(())
+            }
+        }
+    }
+}
+
+impl Display for Expr {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write_expr(f, self, PREC_STATEMENT)
+    }
+}
+
+fn is_mixed_logic(op: BinaryOp, child: &Expr) -> bool {
+    matches!(
+        (op, child),
+        (BinaryOp::Or, Expr::Binary(BinaryOp::And, _, _)) | (BinaryOp::And, Expr::Binary(BinaryOp::Or, _, _))
+    )
+}
+
+fn write_block(f: &mut Formatter<'_>, statements: &[Expr]) -> std::fmt::Result {
+    write!(f, "{{")?;
+    for statement in statements {
+        write_expr(f, statement, PREC_STATEMENT)?;
+        write!(f, ";")?;
+    }
+    write!(f, "}}")
+}
+
+fn write_expr(f: &mut Formatter<'_>, expr: &Expr, min_prec: u8) -> std::fmt::Result {
+    let wrap = expr.precedence() < min_prec;
+    if wrap {
+        write!(f, "(")?;
+    }
+
+    match expr {
+        Expr::Number(n) => write!(f, "{}", n)?,
+        Expr::String(s) => write!(f, "'{}'", s)?,
+        Expr::Name(name) => write!(f, "{}", name)?,
+        Expr::Call(name, args) => {
+            write!(f, "{}(", name)?;
+            for (i, arg) in args.iter().enumerate() {
+                if i > 0 {
+                    write!(f, ",")?;
+                }
+                write_expr(f, arg, PREC_ASSIGN)?;
+            }
+            write!(f, ")")?;
+        }
+        Expr::This => write!(f, "this")?,
+        Expr::Unary(op, operand) => {
+            write!(f, "{}", if *op == UnaryOp::Not { "!" } else { "-" })?;
+            // `--x` and `-(-1)` must not collapse into a single token sequence the game may misread.
+            let min = if matches!(**operand, Expr::Unary(_, _) | Expr::Number(_)) { PREC_POSTFIX } else { PREC_UNARY };
+            write_expr(f, operand, min)?;
+        }
+        Expr::Binary(op, left, right) => {
+            let prec = op.precedence();
+            let (left_min, right_min) = if *op == BinaryOp::Coalesce { (prec + 1, prec) } else { (prec, prec + 1) };
+            // `&&` and `||` are always parenthesized against each other since their relative
+            // precedence differs between engine versions.
+            let left_min = if is_mixed_logic(*op, left) { PREC_PRIMARY } else { left_min };
+            let right_min = if is_mixed_logic(*op, right) || right.precedence() == PREC_UNARY { PREC_POSTFIX } else { right_min };
+            write_expr(f, left, left_min)?;
+            write!(f, "{}", op.symbol())?;
+            write_expr(f, right, right_min)?;
+        }
+        Expr::Conditional(cond, then, otherwise) => {
+            write_expr(f, cond, PREC_COALESCE)?;
+            write!(f, "?")?;
+            write_expr(f, then, PREC_CONDITIONAL)?;
+            if let Some(otherwise) = otherwise {
+                write!(f, ":")?;
+                // Nested conditionals are parenthesized since their associativity is version dependent.
+                write_expr(f, otherwise, PREC_COALESCE)?;
+            }
+        }
+        Expr::Index(array, index) => {
+            write_expr(f, array, PREC_POSTFIX)?;
+            write!(f, "[")?;
+            write_expr(f, index, PREC_ASSIGN)?;
+            write!(f, "]")?;
+        }
+        Expr::Arrow(target, body) => {
+            write_expr(f, target, PREC_POSTFIX)?;
+            write!(f, "->")?;
+            write_expr(f, body, PREC_PRIMARY)?;
+        }
+        Expr::Assign(target, value) => {
+            write_expr(f, target, PREC_POSTFIX)?;
+            write!(f, "=")?;
+            write_expr(f, value, PREC_ASSIGN)?;
+        }
+        Expr::Block(statements) => write_block(f, statements)?,
+        Expr::Loop(count, body) => {
+            write!(f, "loop(")?;
+            write_expr(f, count, PREC_ASSIGN)?;
+            write!(f, ",")?;
+            write_expr(f, body, PREC_ASSIGN)?;
+            write!(f, ")")?;
+        }
+        Expr::ForEach(variable, array, body) => {
+            write!(f, "for_each(")?;
+            write_expr(f, variable, PREC_ASSIGN)?;
+            write!(f, ",")?;
+            write_expr(f, array, PREC_ASSIGN)?;
+            write!(f, ",")?;
+            write_expr(f, body, PREC_ASSIGN)?;
+            write!(f, ")")?;
+        }
+        Expr::Return(value) => {
+            write!(f, "return ")?;
+            write_expr(f, value, PREC_ASSIGN)?;
+        }
+        Expr::Break => write!(f, "break")?,
+        Expr::Continue => write!(f, "continue")?
+    }
+
+    if wrap {
+        write!(f, ")")?;
+    }
+    Ok(())
+}
@@ -0,0 +1,57 @@
+use std::fmt::{Display, Formatter};
+use serde_json::Value;
+use crate::molang::parser::{parse_molang_from_str, MolangError};
+use crate::molang::Molang;
+
+/// A Molang parse failure located both within the expression and within the containing JSON document.
+#[derive(Clone, Debug, PartialEq)]
+pub struct MolangDiagnostic {
+    /// RFC 6901 pointer of the JSON field holding the expression.
+    pub pointer: String,
+    /// Byte offset of the error within the expression.
+    pub offset: usize,
+    /// 1-based character column of the error within the expression.
+    pub column: usize,
+    /// Length in bytes of the span to underline.
+    pub length: usize,
+    pub message: String
+}
+
+impl MolangDiagnostic {
+    pub fn from_error(src: &str, pointer: &str, error: &MolangError) -> MolangDiagnostic {
+        MolangDiagnostic {
+            pointer: pointer.to_string(),
+            offset: error.offset,
+            column: error.column(src),
+            length: error.length,
+            message: error.message.clone()
+        }
+    }
+}
+
+impl Display for MolangDiagnostic {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}:{}: {}", self.pointer, self.column, self.message)
+    }
+}
+
+impl Molang {
+    /// Parses the expression, attributing any failure to the field at `pointer`.
+    pub fn check(&self, pointer: &str) -> Result<(), MolangDiagnostic> {
+        self.parse().map(|_| ()).map_err(|error| MolangDiagnostic::from_error(self.as_str(), pointer, &error))
+    }
+}
+
+/// Parses the string fields at `pointers` in `document` as Molang and returns a diagnostic for
+/// each one that fails. Missing and non-string fields are skipped.
+pub fn check_molang_fields(document: &Value, pointers: &[&str]) -> Vec<MolangDiagnostic> {
+    let mut diagnostics = vec![];
+    for pointer in pointers {
+        if let Some(Value::String(src)) = document.pointer(pointer) {
+            if let Err(error) = parse_molang_from_str(src) {
+                diagnostics.push(MolangDiagnostic::from_error(src, pointer, &error));
+            }
+        }
+    }
+    diagnostics
+}
@@ -0,0 +1,224 @@
+use std::collections::HashMap;
+use crate::molang::ast::{BinaryOp, Expr, Name, Namespace, Program, UnaryOp};
+use crate::molang::optimizer::math_function;
+
+/// What evaluated expressions read and write: query results and the values of `v.`, `t.` and `c.`
+/// names, keyed by their path without namespace, e.g. `anim_time` for `q.anim_time` and
+/// `location.x` for `v.location.x`. Unknown names read as 0, like in game.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct MolangContext {
+    pub queries: HashMap<String, f32>,
+    pub variables: HashMap<String, f32>,
+    pub temps: HashMap<String, f32>,
+    pub context: HashMap<String, f32>,
+    /// The state of `math.random` and friends, so evaluations are repeatable.
+    pub seed: u64
+}
+
+impl MolangContext {
+    pub fn new() -> MolangContext {
+        MolangContext::default()
+    }
+
+    /// Sets the result of the query `name`, e.g. `anim_time`.
+    pub fn query(mut self, name: &str, value: f32) -> MolangContext {
+        self.queries.insert(name.to_string(), value);
+        self
+    }
+
+    /// Sets the variable `name`, e.g. `is_angry` for `v.is_angry`.
+    pub fn variable(mut self, name: &str, value: f32) -> MolangContext {
+        self.variables.insert(name.to_string(), value);
+        self
+    }
+
+    fn values(&mut self, namespace: Namespace) -> Option<&mut HashMap<String, f32>> {
+        match namespace {
+            Namespace::Query => Some(&mut self.queries),
+            Namespace::Variable => Some(&mut self.variables),
+            Namespace::Temp => Some(&mut self.temps),
+            Namespace::Context => Some(&mut self.context),
+            _ => None
+        }
+    }
+
+    fn get(&mut self, name: &Name) -> Option<f32> {
+        let key = name.path.join(".");
+        self.values(name.namespace)?.get(&key).copied()
+    }
+
+    /// A number in `[0, 1)`.
+    pub(crate) fn random(&mut self) -> f32 {
+        self.seed = self.seed.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+        (self.seed >> 40) as f32 / (1u64 << 24) as f32
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum Val {
+    Number(f32),
+    String(String)
+}
+
+impl Val {
+    fn number(&self) -> f32 {
+        match self {
+            Val::Number(n) => *n,
+            Val::String(_) => 0.0
+        }
+    }
+}
+
+/// How a statement list ended.
+enum Flow {
+    Normal,
+    Return(Val),
+    Break,
+    Continue
+}
+
+fn truthy(value: f32) -> f32 {
+    if value != 0.0 { 1.0 } else { 0.0 }
+}
+
+fn call(name: &Name, args: &[Expr], context: &mut MolangContext) -> Val {
+    let values: Vec<f32> = args.iter().map(|arg| eval(arg, context).number()).collect();
+    if name.namespace != Namespace::Math || name.path.len() != 1 {
+        // Queries with arguments answer like the query without them.
+        return Val::Number(context.get(name).unwrap_or(0.0));
+    }
+    let value = match (name.path[0].as_str(), values.as_slice()) {
+        ("random", [low, high]) => low + (high - low) * context.random(),
+        ("random_integer", [low, high]) => (low.round() + ((high.round() - low.round() + 1.0) * context.random()).floor()).min(high.round()),
+        ("die_roll", [count, low, high]) => (0..count.max(0.0) as u32).map(|_| low + (high - low) * context.random()).sum(),
+        ("die_roll_integer", [count, low, high]) => {
+            (0..count.max(0.0) as u32).map(|_| (low.round() + ((high.round() - low.round() + 1.0) * context.random()).floor()).min(high.round())).sum()
+        }
+        (function, values) => math_function(function, values).unwrap_or(0.0)
+    };
+    Val::Number(value)
+}
+
+fn binary(op: BinaryOp, left: &Expr, right: &Expr, context: &mut MolangContext) -> Val {
+    match op {
+        BinaryOp::Coalesce => {
+            if let Expr::Name(name) = left {
+                if let Some(value) = context.get(name) {
+                    return Val::Number(value);
+                }
+                return eval(right, context);
+            }
+            return eval(left, context);
+        }
+        BinaryOp::And => {
+            let value = truthy(eval(left, context).number()) != 0.0 && truthy(eval(right, context).number()) != 0.0;
+            return Val::Number(value as i32 as f32);
+        }
+        BinaryOp::Or => {
+            let value = truthy(eval(left, context).number()) != 0.0 || truthy(eval(right, context).number()) != 0.0;
+            return Val::Number(value as i32 as f32);
+        }
+        _ => {}
+    }
+    let (left, right) = (eval(left, context), eval(right, context));
+    if let (Val::String(l), Val::String(r)) = (&left, &right) {
+        return Val::Number(match op {
+            BinaryOp::Equal => (l == r) as i32 as f32,
+            BinaryOp::NotEqual => (l != r) as i32 as f32,
+            _ => 0.0
+        });
+    }
+    let (l, r) = (left.number(), right.number());
+    Val::Number(match op {
+        BinaryOp::Equal => (l == r) as i32 as f32,
+        BinaryOp::NotEqual => (l != r) as i32 as f32,
+        BinaryOp::Less => (l < r) as i32 as f32,
+        BinaryOp::LessEqual => (l <= r) as i32 as f32,
+        BinaryOp::Greater => (l > r) as i32 as f32,
+        BinaryOp::GreaterEqual => (l >= r) as i32 as f32,
+        BinaryOp::Add => l + r,
+        BinaryOp::Subtract => l - r,
+        BinaryOp::Multiply => l * r,
+        // Dividing by zero gives zero rather than infinity in game.
+        BinaryOp::Divide => if r == 0.0 { 0.0 } else { l / r },
+        BinaryOp::Coalesce | BinaryOp::And | BinaryOp::Or => unreachable!()
+    })
+}
+
+fn eval(expr: &Expr, context: &mut MolangContext) -> Val {
+    match expr {
+        Expr::Number(n) => Val::Number(*n),
+        Expr::String(s) => Val::String(s.clone()),
+        Expr::Name(name) if name.namespace == Namespace::Math && name.path == ["pi"] => Val::Number(std::f32::consts::PI),
+        Expr::Name(name) => Val::Number(context.get(name).unwrap_or(0.0)),
+        Expr::Call(name, args) => call(name, args, context),
+        Expr::Unary(UnaryOp::Not, operand) => Val::Number(1.0 - truthy(eval(operand, context).number())),
+        Expr::Unary(UnaryOp::Negate, operand) => Val::Number(-eval(operand, context).number()),
+        Expr::Binary(op, left, right) => binary(*op, left, right, context),
+        Expr::Conditional(condition, then, otherwise) => {
+            if eval(condition, context).number() != 0.0 {
+                eval(then, context)
+            } else {
+                otherwise.as_ref().map_or(Val::Number(0.0), |otherwise| eval(otherwise, context))
+            }
+        }
+        Expr::Assign(target, value) => {
+            let value = eval(value, context).number();
+            if let Expr::Name(name) = target.as_ref() {
+                let key = name.path.join(".");
+                if let Some(values) = context.values(name.namespace) {
+                    values.insert(key, value);
+                }
+            }
+            Val::Number(0.0)
+        }
+        Expr::Block(statements) => match run(statements, context) {
+            Flow::Return(value) => value,
+            _ => Val::Number(0.0)
+        },
+        Expr::Loop(count, body) => {
+            // The game caps loops at 1024 iterations.
+            let count = eval(count, context).number().clamp(0.0, 1024.0) as u32;
+            for _ in 0..count {
+                let statements = match body.as_ref() {
+                    Expr::Block(statements) => statements.as_slice(),
+                    body => std::slice::from_ref(body)
+                };
+                if matches!(run(statements, context), Flow::Break | Flow::Return(_)) {
+                    break;
+                }
+            }
+            Val::Number(0.0)
+        }
+        // Arrays of entities and `this` have no value outside the game.
+        Expr::This | Expr::Index(_, _) | Expr::Arrow(_, _) | Expr::ForEach(_, _, _) => Val::Number(0.0),
+        Expr::Return(value) => eval(value, context),
+        Expr::Break | Expr::Continue => Val::Number(0.0)
+    }
+}
+
+fn run(statements: &[Expr], context: &mut MolangContext) -> Flow {
+    for statement in statements {
+        match statement {
+            Expr::Return(value) => return Flow::Return(eval(value, context)),
+            Expr::Break => return Flow::Break,
+            Expr::Continue => return Flow::Continue,
+            statement => {
+                eval(statement, context);
+            }
+        }
+    }
+    Flow::Normal
+}
+
+/// Evaluates `program` to a number, applying its assignments to `context`. A simple expression is
+/// its own value, a complex one the value it returns or 0. Strings count as 0.
+pub fn evaluate(program: &Program, context: &mut MolangContext) -> f32 {
+    if !program.complex {
+        return program.statements.first().map_or(0.0, |statement| eval(statement, context).number());
+    }
+    match run(&program.statements, context) {
+        Flow::Return(value) => value.number(),
+        _ => 0.0
+    }
+}
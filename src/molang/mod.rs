@@ -0,0 +1,94 @@
+use std::fmt::{Display, Formatter};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use serde_json::{Number, Value};
+use crate::molang::ast::Program;
+use crate::molang::eval::{evaluate, MolangContext};
+use crate::molang::parser::{parse_molang_for_version, parse_molang_from_str, MolangError};
+use crate::utils::SemVer;
+
+pub mod ast;
+pub mod diagnostic;
+pub mod eval;
+pub mod optimizer;
+pub mod parser;
+pub mod queries;
+pub mod version;
+
+/// A Molang expression as written in a definition file.
+///
+/// Expression fields accept numbers and booleans as well as strings; all of them are kept as
+/// source text and only parsed on demand.
+#[derive(Clone, Debug, Eq, PartialEq, Hash)]
+pub struct Molang(pub String);
+
+impl Molang {
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    pub fn parse(&self) -> Result<Program, MolangError> {
+        parse_molang_from_str(&self.0)
+    }
+
+    pub fn parse_for_version(&self, engine_version: &SemVer) -> Result<Program, MolangError> {
+        parse_molang_for_version(&self.0, engine_version)
+    }
+
+    /// Evaluates the expression in `context`, or `None` if it doesn't parse.
+    pub fn evaluate(&self, context: &mut MolangContext) -> Option<f32> {
+        Some(evaluate(&self.parse().ok()?, context))
+    }
+}
+
+impl Display for Molang {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl From<&str> for Molang {
+    fn from(value: &str) -> Self {
+        Molang(value.to_string())
+    }
+}
+
+impl From<String> for Molang {
+    fn from(value: String) -> Self {
+        Molang(value)
+    }
+}
+
+impl From<Program> for Molang {
+    fn from(value: Program) -> Self {
+        Molang(value.to_string())
+    }
+}
+
+impl Serialize for Molang {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        // Plain numeric literals are written back as JSON numbers, matching how packs usually spell
+        // them. Only literals a number writes back unchanged, so `1` stays `1` rather than `1.0`.
+        match serde_json::from_str::<Number>(&self.0) {
+            Ok(number) if number.to_string() == self.0 => number.serialize(serializer),
+            _ => serializer.serialize_str(&self.0)
+        }
+    }
+}
+
+/// The expression a JSON field holds, written as a string, a number or a boolean, or `None` for
+/// any other value.
+pub(crate) fn molang_from_value(value: &Value) -> Option<Molang> {
+    match value {
+        Value::String(s) => Some(Molang(s.clone())),
+        Value::Number(n) => Some(Molang(n.to_string())),
+        Value::Bool(b) => Some(Molang(if *b { "1" } else { "0" }.to_string())),
+        _ => None
+    }
+}
+
+impl<'de> Deserialize<'de> for Molang {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let value = Value::deserialize(deserializer)?;
+        molang_from_value(&value).ok_or_else(|| serde::de::Error::custom(format!("expected a Molang expression, found {}", value)))
+    }
+}
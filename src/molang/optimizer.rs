@@ -0,0 +1,161 @@
+use crate::molang::ast::{BinaryOp, Expr, Name, Namespace, Program, UnaryOp};
+
+/// Folds constant subexpressions and drops redundant branches, returning an equivalent program.
+///
+/// Only transformations that preserve the game's evaluation semantics are applied: side effects
+/// are never removed unless they are unreachable, and operations whose result isn't finite
+/// (e.g. division by zero) are left for the game to evaluate.
+pub fn optimize(program: &Program) -> Program {
+    Program {
+        statements: optimize_statements(&program.statements),
+        complex: program.complex
+    }
+}
+
+fn optimize_statements(statements: &[Expr]) -> Vec<Expr> {
+    let mut optimized = vec![];
+    for statement in statements {
+        let statement = fold(statement);
+        let terminal = matches!(statement, Expr::Return(_) | Expr::Break | Expr::Continue);
+        optimized.push(statement);
+        if terminal {
+            break;
+        }
+    }
+    optimized
+}
+
+fn truthy(value: f32) -> f32 {
+    if value != 0.0 { 1.0 } else { 0.0 }
+}
+
+fn finite(value: f32) -> Option<Expr> {
+    value.is_finite().then_some(Expr::Number(value))
+}
+
+fn fold(expr: &Expr) -> Expr {
+    match expr {
+        Expr::Name(name) if name.namespace == Namespace::Math && name.path == ["pi"] => Expr::Number(std::f32::consts::PI),
+        Expr::Call(name, args) => {
+            let args: Vec<Expr> = args.iter().map(fold).collect();
+            fold_math(name, &args).unwrap_or(Expr::Call(name.clone(), args))
+        }
+        Expr::Unary(op, operand) => {
+            let operand = fold(operand);
+            match (op, &operand) {
+                (UnaryOp::Not, Expr::Number(n)) => Expr::Number(1.0 - truthy(*n)),
+                (UnaryOp::Negate, Expr::Number(n)) => Expr::Number(-n),
+                (UnaryOp::Negate, Expr::Unary(UnaryOp::Negate, inner)) => *inner.clone(),
+                _ => Expr::Unary(*op, Box::new(operand))
+            }
+        }
+        Expr::Binary(op, left, right) => fold_binary(*op, fold(left), fold(right)),
+        Expr::Conditional(cond, then, otherwise) => {
+            let cond = fold(cond);
+            let then = fold(then);
+            let otherwise = otherwise.as_ref().map(|e| fold(e));
+            match (&cond, otherwise) {
+                (Expr::Number(n), otherwise) => {
+                    if *n != 0.0 { then } else { otherwise.unwrap_or(Expr::Number(0.0)) }
+                }
+                (_, Some(otherwise)) if otherwise == then && !cond.has_side_effects() => then,
+                (_, otherwise) => Expr::Conditional(Box::new(cond), Box::new(then), otherwise.map(Box::new))
+            }
+        }
+        Expr::Index(array, index) => Expr::Index(Box::new(fold(array)), Box::new(fold(index))),
+        Expr::Arrow(target, body) => Expr::Arrow(Box::new(fold(target)), Box::new(fold(body))),
+        Expr::Assign(target, value) => Expr::Assign(target.clone(), Box::new(fold(value))),
+        Expr::Block(statements) => Expr::Block(optimize_statements(statements)),
+        Expr::Loop(count, body) => Expr::Loop(Box::new(fold(count)), Box::new(fold(body))),
+        Expr::ForEach(variable, array, body) => {
+            Expr::ForEach(variable.clone(), Box::new(fold(array)), Box::new(fold(body)))
+        }
+        Expr::Return(value) => Expr::Return(Box::new(fold(value))),
+        _ => expr.clone()
+    }
+}
+
+fn fold_binary(op: BinaryOp, left: Expr, right: Expr) -> Expr {
+    match (&left, &right) {
+        (Expr::Number(l), Expr::Number(r)) => {
+            let (l, r) = (*l, *r);
+            let folded = match op {
+                BinaryOp::Coalesce => Some(left.clone()),
+                BinaryOp::Or => finite(truthy(truthy(l) + truthy(r))),
+                BinaryOp::And => finite(truthy(l) * truthy(r)),
+                BinaryOp::Equal => finite((l == r) as i32 as f32),
+                BinaryOp::NotEqual => finite((l != r) as i32 as f32),
+                BinaryOp::Less => finite((l < r) as i32 as f32),
+                BinaryOp::LessEqual => finite((l <= r) as i32 as f32),
+                BinaryOp::Greater => finite((l > r) as i32 as f32),
+                BinaryOp::GreaterEqual => finite((l >= r) as i32 as f32),
+                BinaryOp::Add => finite(l + r),
+                BinaryOp::Subtract => finite(l - r),
+                BinaryOp::Multiply => finite(l * r),
+                BinaryOp::Divide => if r == 0.0 { None } else { finite(l / r) }
+            };
+            if let Some(folded) = folded {
+                return folded;
+            }
+        }
+        (Expr::String(l), Expr::String(r)) if op == BinaryOp::Equal || op == BinaryOp::NotEqual => {
+            return Expr::Number(((l == r) == (op == BinaryOp::Equal)) as i32 as f32);
+        }
+        // Short-circuiting operators skip their right side entirely when the left side decides the result.
+        (Expr::Number(l), _) if op == BinaryOp::And && *l == 0.0 => return Expr::Number(0.0),
+        (Expr::Number(l), _) if op == BinaryOp::Or && *l != 0.0 => return Expr::Number(1.0),
+        (_, Expr::Number(r)) if *r == 0.0 && (op == BinaryOp::Add || op == BinaryOp::Subtract) => return left,
+        (_, Expr::Number(r)) if *r == 1.0 && (op == BinaryOp::Multiply || op == BinaryOp::Divide) => return left,
+        (Expr::Number(l), _) if *l == 0.0 && op == BinaryOp::Add => return right,
+        (Expr::Number(l), _) if *l == 1.0 && op == BinaryOp::Multiply => return right,
+        _ => {}
+    }
+    Expr::Binary(op, Box::new(left), Box::new(right))
+}
+
+fn fold_math(name: &Name, args: &[Expr]) -> Option<Expr> {
+    if name.namespace != Namespace::Math || name.path.len() != 1 {
+        return None;
+    }
+
+    let mut values = vec![];
+    for arg in args {
+        match arg {
+            Expr::Number(n) => values.push(*n),
+            _ => return None
+        }
+    }
+
+    finite(math_function(&name.path[0], &values)?)
+}
+
+/// The value of the deterministic `math.` function `function`, or `None` if it's unknown, random
+/// or given the wrong number of arguments.
+pub(crate) fn math_function(function: &str, values: &[f32]) -> Option<f32> {
+    // Molang trigonometry works in degrees.
+    let value = match (function, values) {
+        ("abs", [x]) => x.abs(),
+        ("ceil", [x]) => x.ceil(),
+        ("floor", [x]) => x.floor(),
+        ("round", [x]) => x.round(),
+        ("trunc", [x]) => x.trunc(),
+        ("sqrt", [x]) => x.sqrt(),
+        ("exp", [x]) => x.exp(),
+        ("ln", [x]) => x.ln(),
+        ("sin", [x]) => x.to_radians().sin(),
+        ("cos", [x]) => x.to_radians().cos(),
+        ("asin", [x]) => x.asin().to_degrees(),
+        ("acos", [x]) => x.acos().to_degrees(),
+        ("atan", [x]) => x.atan().to_degrees(),
+        ("atan2", [y, x]) => y.atan2(*x).to_degrees(),
+        ("min", [a, b]) => a.min(*b),
+        ("max", [a, b]) => a.max(*b),
+        ("pow", [base, exponent]) => base.powf(*exponent),
+        ("mod", [value, denominator]) if *denominator != 0.0 => value % denominator,
+        ("clamp", [value, min, max]) => value.max(*min).min(*max),
+        ("lerp", [start, end, t]) => start + (end - start) * t,
+        ("hermite_blend", [t]) => 3.0 * t * t - 2.0 * t * t * t,
+        _ => return None
+    };
+    Some(value)
+}
@@ -0,0 +1,369 @@
+use std::error::Error;
+use std::fmt::{Display, Formatter};
+use crate::molang::ast::{BinaryOp, Expr, Name, Program, UnaryOp, NAMESPACES};
+use crate::molang::version::{missing_changes, VersionedChange};
+use crate::utils::SemVer;
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct MolangError {
+    pub message: String,
+    /// Byte offset into the source where the error was detected.
+    pub offset: usize,
+    /// Length in bytes of the offending token, zero at the end of input.
+    pub length: usize
+}
+
+impl MolangError {
+    /// The 1-based character column of the error within `src`, for editors that don't count bytes.
+    pub fn column(&self, src: &str) -> usize {
+        src[..self.offset.min(src.len())].chars().count() + 1
+    }
+}
+
+impl Display for MolangError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} at offset {}", self.message, self.offset)
+    }
+}
+
+impl Error for MolangError {}
+
+#[derive(Clone, Debug, PartialEq)]
+enum Token {
+    Number(f32),
+    String(String),
+    Ident(String),
+    Symbol(&'static str),
+    Eof
+}
+
+#[derive(Clone, Debug)]
+struct Spanned {
+    token: Token,
+    start: usize,
+    end: usize
+}
+
+const SYMBOLS: [&str; 27] = [
+    "??", "->", "==", "!=", "<=", ">=", "&&", "||",
+    "<", ">", "+", "-", "*", "/", "!", "?", ":", "=", "(", ")", "{", "}", "[", "]", ",", ";", "."
+];
+
+fn tokenize(src: &str) -> Result<Vec<Spanned>, MolangError> {
+    let bytes = src.as_bytes();
+    let mut tokens = vec![];
+    let mut i = 0;
+
+    while i < bytes.len() {
+        let c = bytes[i];
+        if c.is_ascii_whitespace() {
+            i += 1;
+            continue;
+        }
+
+        let start = i;
+        if c.is_ascii_digit() {
+            while i < bytes.len() && (bytes[i].is_ascii_digit() || bytes[i] == b'.') {
+                i += 1;
+            }
+            let value = src[start..i].parse::<f32>().map_err(|_| MolangError {
+                message: format!("invalid number '{}'", &src[start..i]), offset: start, length: i - start
+            })?;
+            // Some packs write float literals with a C-style suffix, which the game tolerates.
+            if i < bytes.len() && (bytes[i] == b'f' || bytes[i] == b'F') {
+                i += 1;
+            }
+            tokens.push(Spanned { token: Token::Number(value), start, end: i });
+        } else if c.is_ascii_alphabetic() || c == b'_' {
+            while i < bytes.len() && (bytes[i].is_ascii_alphanumeric() || bytes[i] == b'_') {
+                i += 1;
+            }
+            tokens.push(Spanned { token: Token::Ident(src[start..i].to_ascii_lowercase()), start, end: i });
+        } else if c == b'\'' {
+            let end = src[start + 1..].find('\'').ok_or(MolangError {
+                message: "unterminated string".to_string(), offset: start, length: src.len() - start
+            })?;
+            i = start + end + 2;
+            tokens.push(Spanned { token: Token::String(src[start + 1..start + 1 + end].to_string()), start, end: i });
+        } else {
+            let symbol = SYMBOLS.iter().find(|s| src[start..].starts_with(**s)).ok_or_else(|| {
+                let c = src[start..].chars().next().unwrap();
+                MolangError { message: format!("unexpected character '{}'", c), offset: start, length: c.len_utf8() }
+            })?;
+            i += symbol.len();
+            tokens.push(Spanned { token: Token::Symbol(symbol), start, end: i });
+        }
+    }
+
+    tokens.push(Spanned { token: Token::Eof, start: src.len(), end: src.len() });
+    Ok(tokens)
+}
+
+struct Parser {
+    tokens: Vec<Spanned>,
+    pos: usize,
+    left_associative_conditional: bool,
+    or_binds_tighter: bool
+}
+
+impl Parser {
+    fn peek(&self) -> &Token {
+        &self.tokens[self.pos].token
+    }
+
+    fn offset(&self) -> usize {
+        self.tokens[self.pos].start
+    }
+
+    fn length(&self) -> usize {
+        self.tokens[self.pos].end - self.tokens[self.pos].start
+    }
+
+    fn advance(&mut self) -> Token {
+        let token = self.tokens[self.pos].token.clone();
+        if self.pos < self.tokens.len() - 1 {
+            self.pos += 1;
+        }
+        token
+    }
+
+    fn is_symbol(&self, symbol: &str) -> bool {
+        matches!(self.peek(), Token::Symbol(s) if *s == symbol)
+    }
+
+    fn eat(&mut self, symbol: &str) -> bool {
+        if self.is_symbol(symbol) {
+            self.advance();
+            true
+        } else {
+            false
+        }
+    }
+
+    fn error(&self, message: String) -> MolangError {
+        MolangError { message, offset: self.offset(), length: self.length() }
+    }
+
+    fn expect(&mut self, symbol: &str) -> Result<(), MolangError> {
+        if self.eat(symbol) {
+            Ok(())
+        } else {
+            Err(self.error(format!("expected '{}'", symbol)))
+        }
+    }
+
+    /// Parses `;`-separated statements until `end` (`}` or end of input) without consuming it.
+    fn statements(&mut self, end: &Token) -> Result<(Vec<Expr>, bool), MolangError> {
+        let mut statements = vec![];
+        let mut terminated = false;
+
+        while self.peek() != end {
+            statements.push(self.expr()?);
+            if self.eat(";") {
+                terminated = true;
+                while self.eat(";") {}
+            } else if self.peek() != end {
+                return Err(self.error("expected ';'".to_string()));
+            }
+        }
+
+        Ok((statements, terminated))
+    }
+
+    fn expr(&mut self) -> Result<Expr, MolangError> {
+        let target = self.conditional()?;
+        if self.eat("=") {
+            if !matches!(target, Expr::Name(_) | Expr::Index(_, _) | Expr::Arrow(_, _)) {
+                return Err(self.error("invalid assignment target".to_string()));
+            }
+            let value = self.expr()?;
+            return Ok(Expr::Assign(Box::new(target), Box::new(value)));
+        }
+        Ok(target)
+    }
+
+    fn conditional(&mut self) -> Result<Expr, MolangError> {
+        let mut expr = self.binary(0)?;
+        while self.eat("?") {
+            let then = self.conditional()?;
+            let otherwise = if !self.eat(":") {
+                None
+            } else if self.left_associative_conditional {
+                Some(Box::new(self.binary(0)?))
+            } else {
+                Some(Box::new(self.conditional()?))
+            };
+            expr = Expr::Conditional(Box::new(expr), Box::new(then), otherwise);
+
+            if !self.left_associative_conditional {
+                break;
+            }
+        }
+        Ok(expr)
+    }
+
+    fn binary(&mut self, level: usize) -> Result<Expr, MolangError> {
+        const LEVELS: [&[(&str, BinaryOp)]; 7] = [
+            &[("??", BinaryOp::Coalesce)],
+            &[("||", BinaryOp::Or)],
+            &[("&&", BinaryOp::And)],
+            &[("==", BinaryOp::Equal), ("!=", BinaryOp::NotEqual)],
+            &[("<=", BinaryOp::LessEqual), (">=", BinaryOp::GreaterEqual), ("<", BinaryOp::Less), (">", BinaryOp::Greater)],
+            &[("+", BinaryOp::Add), ("-", BinaryOp::Subtract)],
+            &[("*", BinaryOp::Multiply), ("/", BinaryOp::Divide)],
+        ];
+
+        if level == LEVELS.len() {
+            return self.unary();
+        }
+
+        let ops = match level {
+            1 if self.or_binds_tighter => LEVELS[2],
+            2 if self.or_binds_tighter => LEVELS[1],
+            _ => LEVELS[level]
+        };
+
+        let mut left = self.binary(level + 1)?;
+        while let Some((_, op)) = ops.iter().find(|(symbol, _)| self.is_symbol(symbol)) {
+            self.advance();
+            // `??` is right associative, everything else binds to the left.
+            let right = if *op == BinaryOp::Coalesce { self.binary(level)? } else { self.binary(level + 1)? };
+            left = Expr::Binary(*op, Box::new(left), Box::new(right));
+        }
+        Ok(left)
+    }
+
+    fn unary(&mut self) -> Result<Expr, MolangError> {
+        if self.eat("!") {
+            return Ok(Expr::Unary(UnaryOp::Not, Box::new(self.unary()?)));
+        }
+        if self.eat("-") {
+            return Ok(Expr::Unary(UnaryOp::Negate, Box::new(self.unary()?)));
+        }
+        self.postfix()
+    }
+
+    fn postfix(&mut self) -> Result<Expr, MolangError> {
+        let mut expr = self.primary()?;
+        loop {
+            if self.eat("[") {
+                let index = self.expr()?;
+                self.expect("]")?;
+                expr = Expr::Index(Box::new(expr), Box::new(index));
+            } else if self.eat("->") {
+                let body = self.primary()?;
+                expr = Expr::Arrow(Box::new(expr), Box::new(body));
+            } else {
+                return Ok(expr);
+            }
+        }
+    }
+
+    fn arguments(&mut self) -> Result<Vec<Expr>, MolangError> {
+        let mut args = vec![];
+        if self.eat(")") {
+            return Ok(args);
+        }
+        loop {
+            args.push(self.expr()?);
+            if self.eat(")") {
+                return Ok(args);
+            }
+            self.expect(",")?;
+        }
+    }
+
+    fn primary(&mut self) -> Result<Expr, MolangError> {
+        let (offset, length) = (self.offset(), self.length());
+        match self.advance() {
+            Token::Number(n) => Ok(Expr::Number(n)),
+            Token::String(s) => Ok(Expr::String(s)),
+            Token::Symbol("(") => {
+                let expr = self.expr()?;
+                self.expect(")")?;
+                Ok(expr)
+            }
+            Token::Symbol("{") => {
+                let (statements, _) = self.statements(&Token::Symbol("}"))?;
+                self.expect("}")?;
+                Ok(Expr::Block(statements))
+            }
+            Token::Ident(ident) => self.ident(ident, offset),
+            Token::Eof => Err(MolangError { message: "unexpected end of expression".to_string(), offset, length }),
+            Token::Symbol(s) => Err(MolangError { message: format!("unexpected '{}'", s), offset, length })
+        }
+    }
+
+    fn ident(&mut self, ident: String, offset: usize) -> Result<Expr, MolangError> {
+        match ident.as_str() {
+            "true" => return Ok(Expr::Number(1.0)),
+            "false" => return Ok(Expr::Number(0.0)),
+            "this" => return Ok(Expr::This),
+            "break" => return Ok(Expr::Break),
+            "continue" => return Ok(Expr::Continue),
+            "return" => return Ok(Expr::Return(Box::new(self.expr()?))),
+            "loop" => {
+                self.expect("(")?;
+                let count = self.expr()?;
+                self.expect(",")?;
+                let body = self.expr()?;
+                self.expect(")")?;
+                return Ok(Expr::Loop(Box::new(count), Box::new(body)));
+            }
+            "for_each" => {
+                self.expect("(")?;
+                let variable = self.expr()?;
+                self.expect(",")?;
+                let array = self.expr()?;
+                self.expect(",")?;
+                let body = self.expr()?;
+                self.expect(")")?;
+                return Ok(Expr::ForEach(Box::new(variable), Box::new(array), Box::new(body)));
+            }
+            _ => {}
+        }
+
+        let namespace = *NAMESPACES.get(ident.as_str()).ok_or(MolangError {
+            message: format!("unknown namespace '{}'", ident), offset, length: ident.len()
+        })?;
+
+        let mut path = vec![];
+        while self.eat(".") {
+            match self.advance() {
+                Token::Ident(part) => path.push(part),
+                _ => return Err(self.error("expected identifier after '.'".to_string()))
+            }
+        }
+        if path.is_empty() {
+            return Err(self.error(format!("expected '.' after '{}'", ident)));
+        }
+
+        let name = Name { namespace, path };
+        if self.eat("(") {
+            Ok(Expr::Call(name, self.arguments()?))
+        } else {
+            Ok(Expr::Name(name))
+        }
+    }
+}
+
+pub(crate) fn parse_without_changes(src: &str, missing: &[VersionedChange]) -> Result<Program, MolangError> {
+    let mut parser = Parser {
+        tokens: tokenize(src)?,
+        pos: 0,
+        left_associative_conditional: missing.contains(&VersionedChange::ConditionalAssociativity),
+        or_binds_tighter: missing.contains(&VersionedChange::LogicalPrecedence)
+    };
+    let (statements, complex) = parser.statements(&Token::Eof)?;
+    Ok(Program { statements, complex })
+}
+
+/// Parses `src` with the semantics of the latest engine version.
+pub fn parse_molang_from_str(src: &str) -> Result<Program, MolangError> {
+    parse_without_changes(src, &[])
+}
+
+/// Parses `src` the way a client running `engine_version` (usually the pack's `min_engine_version`) does.
+pub fn parse_molang_for_version(src: &str, engine_version: &SemVer) -> Result<Program, MolangError> {
+    parse_without_changes(src, &missing_changes(engine_version))
+}
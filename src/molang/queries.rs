@@ -0,0 +1,119 @@
+use crate::molang::ast::{Expr, Name, Namespace, Program};
+
+/// The kind of asset a Molang expression runs in, which decides what its queries can read.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+pub enum QueryContext {
+    /// Entity and attachable definitions and render controllers, run for an entity.
+    Entity,
+    /// Animations and animation controllers, run for an entity while they play.
+    Animation,
+    /// Particle effects, which have no entity of their own.
+    Particle,
+    /// Block permutation conditions and other block fields, which only see the block.
+    Block
+}
+
+impl QueryContext {
+    /// The context of a pack file from its path relative to the pack root, or `None` for files
+    /// whose expressions aren't checked. Resource and behavior pack folders share the mapping.
+    pub fn from_path(path: &str) -> Option<QueryContext> {
+        let path = path.replace('\\', "/");
+        match path.split('/').next()? {
+            "entity" | "entities" | "attachables" | "render_controllers" => Some(QueryContext::Entity),
+            "animations" | "animation_controllers" => Some(QueryContext::Animation),
+            "particles" => Some(QueryContext::Particle),
+            "blocks" => Some(QueryContext::Block),
+            _ => None
+        }
+    }
+
+    /// The context as messages name it, e.g. `an animation`.
+    pub fn description(&self) -> &'static str {
+        match self {
+            QueryContext::Entity => "an entity",
+            QueryContext::Animation => "an animation",
+            QueryContext::Particle => "a particle effect",
+            QueryContext::Block => "a block"
+        }
+    }
+}
+
+const ENTITY: &[QueryContext] = &[QueryContext::Entity, QueryContext::Animation];
+const ANIMATION: &[QueryContext] = &[QueryContext::Animation];
+const BLOCK: &[QueryContext] = &[QueryContext::Block];
+
+/// The contexts queries that need something particular are available in, sorted by lowercase
+/// name. Queries missing here, such as `time_of_day`, read the world and work everywhere.
+pub const QUERY_CONTEXTS: [(&str, &[QueryContext]); 40] = [
+    ("all_animations_finished", ANIMATION),
+    ("anim_time", ANIMATION),
+    ("any_animation_finished", ANIMATION),
+    ("block_property", BLOCK),
+    ("block_state", BLOCK),
+    ("body_x_rotation", ENTITY),
+    ("body_y_rotation", ENTITY),
+    ("can_climb", ENTITY),
+    ("can_fly", ENTITY),
+    ("ground_speed", ENTITY),
+    ("has_block_property", BLOCK),
+    ("has_block_state", BLOCK),
+    ("has_property", ENTITY),
+    ("head_x_rotation", ENTITY),
+    ("head_y_rotation", ENTITY),
+    ("health", ENTITY),
+    ("is_alive", ENTITY),
+    ("is_baby", ENTITY),
+    ("is_in_water", ENTITY),
+    ("is_on_fire", ENTITY),
+    ("is_on_ground", ENTITY),
+    ("is_riding", ENTITY),
+    ("is_sleeping", ENTITY),
+    ("is_sneaking", ENTITY),
+    ("is_sprinting", ENTITY),
+    ("is_swimming", ENTITY),
+    ("is_using_item", ENTITY),
+    ("life_time", ENTITY),
+    ("mark_variant", ENTITY),
+    ("max_health", ENTITY),
+    ("modified_distance_moved", ENTITY),
+    ("modified_move_speed", ENTITY),
+    ("property", ENTITY),
+    ("skin_id", ENTITY),
+    ("target_x_rotation", ENTITY),
+    ("target_y_rotation", ENTITY),
+    ("variant", ENTITY),
+    ("vertical_speed", ENTITY),
+    ("walk_distance", ENTITY),
+    ("yaw_speed", ENTITY)
+];
+
+/// Whether the query `name`, without its namespace, reads anything in `context`. Unavailable
+/// queries evaluate to 0 or fail to load with the asset.
+pub fn query_available(name: &str, context: QueryContext) -> bool {
+    let name = name.to_ascii_lowercase();
+    match QUERY_CONTEXTS.binary_search_by(|(query, _)| (*query).cmp(name.as_str())) {
+        Ok(index) => QUERY_CONTEXTS[index].1.contains(&context),
+        Err(_) => true
+    }
+}
+
+fn collect_unavailable(expr: &Expr, context: QueryContext, queries: &mut Vec<String>) {
+    if let Expr::Name(Name { namespace: Namespace::Query, path }) | Expr::Call(Name { namespace: Namespace::Query, path }, _) = expr {
+        let name = path.join(".");
+        if !query_available(&name, context) && !queries.contains(&name) {
+            queries.push(name);
+        }
+    }
+    for child in expr.children() {
+        collect_unavailable(child, context, queries);
+    }
+}
+
+/// The queries `program` uses that aren't available in `context`, each once, in order.
+pub fn unavailable_queries(program: &Program, context: QueryContext) -> Vec<String> {
+    let mut queries = vec![];
+    for statement in &program.statements {
+        collect_unavailable(statement, context, &mut queries);
+    }
+    queries
+}
@@ -0,0 +1,49 @@
+use crate::molang::parser::{parse_molang_from_str, parse_without_changes, MolangError};
+use crate::utils::SemVer;
+
+/// A Molang behavior change the game gates on the pack's engine version.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum VersionedChange {
+    /// `a ? b : c ? d : e` evaluated as `(a ? b : c) ? d : e` before this version.
+    ConditionalAssociativity,
+    /// `||` bound tighter than `&&` before this version.
+    LogicalPrecedence
+}
+
+pub const VERSIONED_CHANGES: [VersionedChange; 2] = [
+    VersionedChange::ConditionalAssociativity,
+    VersionedChange::LogicalPrecedence
+];
+
+impl VersionedChange {
+    /// The first engine version with the current behavior.
+    pub fn version(&self) -> SemVer {
+        match self {
+            VersionedChange::ConditionalAssociativity => SemVer::new(1, 18, 10),
+            VersionedChange::LogicalPrecedence => SemVer::new(1, 18, 20)
+        }
+    }
+
+    pub fn applies_to(&self, engine_version: &SemVer) -> bool {
+        *engine_version >= self.version()
+    }
+}
+
+/// The changes a client running `engine_version` doesn't have yet.
+pub fn missing_changes(engine_version: &SemVer) -> Vec<VersionedChange> {
+    VERSIONED_CHANGES.into_iter().filter(|change| !change.applies_to(engine_version)).collect()
+}
+
+/// Returns the versioned changes for which `src` evaluates differently on `engine_version` than on
+/// current clients, so authors targeting old versions can be warned about the discrepancy.
+pub fn versioned_differences(src: &str, engine_version: &SemVer) -> Result<Vec<VersionedChange>, MolangError> {
+    let current = parse_molang_from_str(src)?;
+    let mut differences = vec![];
+
+    for change in missing_changes(engine_version) {
+        if parse_without_changes(src, &[change])? != current {
+            differences.push(change);
+        }
+    }
+    Ok(differences)
+}
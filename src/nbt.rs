@@ -0,0 +1,311 @@
+use std::collections::BTreeMap;
+use std::error::Error;
+use std::fmt::{Display, Formatter};
+
+/// The named tags of a compound, by name. Written back in name order rather than the file's order,
+/// which Bedrock doesn't care about.
+pub type Compound = BTreeMap<String, Tag>;
+
+/// A tag of Bedrock's little-endian NBT, as used by `.mcstructure` files and `level.dat`.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Tag {
+    Byte(i8),
+    Short(i16),
+    Int(i32),
+    Long(i64),
+    Float(f32),
+    Double(f64),
+    ByteArray(Vec<i8>),
+    String(String),
+    /// Elements all have the same type. Empty lists are written with the end tag as element type.
+    List(Vec<Tag>),
+    Compound(Compound),
+    IntArray(Vec<i32>),
+    LongArray(Vec<i64>)
+}
+
+impl Tag {
+    pub fn id(&self) -> u8 {
+        match self {
+            Tag::Byte(_) => 1,
+            Tag::Short(_) => 2,
+            Tag::Int(_) => 3,
+            Tag::Long(_) => 4,
+            Tag::Float(_) => 5,
+            Tag::Double(_) => 6,
+            Tag::ByteArray(_) => 7,
+            Tag::String(_) => 8,
+            Tag::List(_) => 9,
+            Tag::Compound(_) => 10,
+            Tag::IntArray(_) => 11,
+            Tag::LongArray(_) => 12
+        }
+    }
+
+    /// The value of any integer tag. Bytes are how NBT spells booleans.
+    pub fn as_i64(&self) -> Option<i64> {
+        match *self {
+            Tag::Byte(value) => Some(value as i64),
+            Tag::Short(value) => Some(value as i64),
+            Tag::Int(value) => Some(value as i64),
+            Tag::Long(value) => Some(value),
+            _ => None
+        }
+    }
+
+    pub fn as_i32(&self) -> Option<i32> {
+        self.as_i64().and_then(|value| i32::try_from(value).ok())
+    }
+
+    pub fn as_f64(&self) -> Option<f64> {
+        match *self {
+            Tag::Float(value) => Some(value as f64),
+            Tag::Double(value) => Some(value),
+            _ => self.as_i64().map(|value| value as f64)
+        }
+    }
+
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Tag::String(value) => Some(value),
+            _ => None
+        }
+    }
+
+    pub fn as_list(&self) -> Option<&Vec<Tag>> {
+        match self {
+            Tag::List(values) => Some(values),
+            _ => None
+        }
+    }
+
+    pub fn as_compound(&self) -> Option<&Compound> {
+        match self {
+            Tag::Compound(compound) => Some(compound),
+            _ => None
+        }
+    }
+
+    pub fn as_compound_mut(&mut self) -> Option<&mut Compound> {
+        match self {
+            Tag::Compound(compound) => Some(compound),
+            _ => None
+        }
+    }
+
+    /// The tag named `key` if this is a compound.
+    pub fn get(&self, key: &str) -> Option<&Tag> {
+        self.as_compound()?.get(key)
+    }
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum NbtError {
+    UnexpectedEnd,
+    InvalidTagType(u8),
+    InvalidString,
+    NegativeLength(i32),
+    /// A list whose elements don't all have the declared type.
+    MixedList,
+    /// Nesting deeper than [`MAX_DEPTH`], which real files never need.
+    TooDeep,
+    /// A tag a format built on NBT requires is missing or has the wrong type, e.g.
+    /// `structure/block_indices`.
+    MissingTag(String),
+    /// A structure size with a negative axis, or more blocks than an NBT int can index.
+    InvalidSize([i32; 3]),
+    /// A structure offset that moves its origin or corner past what an NBT int can hold.
+    InvalidOffset([i32; 3]),
+    /// A string longer than the 65535 bytes its length prefix can hold, with its length.
+    StringTooLong(usize),
+    /// An array or list with more elements than its `i32` length prefix can hold.
+    LengthTooLarge(usize)
+}
+
+impl Display for NbtError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            NbtError::UnexpectedEnd => write!(f, "unexpected end of NBT data"),
+            NbtError::InvalidTagType(id) => write!(f, "invalid NBT tag type {}", id),
+            NbtError::InvalidString => write!(f, "NBT string is not valid UTF-8"),
+            NbtError::NegativeLength(length) => write!(f, "negative NBT length {}", length),
+            NbtError::MixedList => write!(f, "NBT list elements have different types"),
+            NbtError::TooDeep => write!(f, "NBT nested more than {} levels deep", MAX_DEPTH),
+            NbtError::MissingTag(path) => write!(f, "missing or mistyped NBT tag \"{}\"", path),
+            NbtError::InvalidSize(size) => write!(f, "invalid structure size {}x{}x{}", size[0], size[1], size[2]),
+            NbtError::InvalidOffset(offset) => write!(f, "structure offset {}, {}, {} is out of range", offset[0], offset[1], offset[2]),
+            NbtError::StringTooLong(length) => write!(f, "NBT string of {} bytes is longer than {} bytes", length, u16::MAX),
+            NbtError::LengthTooLarge(length) => write!(f, "NBT array of {} elements is longer than {} elements", length, i32::MAX)
+        }
+    }
+}
+
+impl Error for NbtError {}
+
+pub const MAX_DEPTH: usize = 512;
+
+struct Reader<'a> {
+    bytes: &'a [u8],
+    position: usize
+}
+
+impl Reader<'_> {
+    fn take(&mut self, count: usize) -> Result<&[u8], NbtError> {
+        let end = self.position.checked_add(count).filter(|end| *end <= self.bytes.len()).ok_or(NbtError::UnexpectedEnd)?;
+        let bytes = &self.bytes[self.position..end];
+        self.position = end;
+        Ok(bytes)
+    }
+
+    fn array<const N: usize>(&mut self) -> Result<[u8; N], NbtError> {
+        Ok(self.take(N)?.try_into().unwrap_or([0; N]))
+    }
+
+    fn u8(&mut self) -> Result<u8, NbtError> {
+        Ok(self.array::<1>()?[0])
+    }
+
+    fn length(&mut self) -> Result<usize, NbtError> {
+        let length = i32::from_le_bytes(self.array()?);
+        usize::try_from(length).map_err(|_| NbtError::NegativeLength(length))
+    }
+
+    fn string(&mut self) -> Result<String, NbtError> {
+        let length = u16::from_le_bytes(self.array()?) as usize;
+        String::from_utf8(self.take(length)?.to_vec()).map_err(|_| NbtError::InvalidString)
+    }
+
+    fn payload(&mut self, id: u8, depth: usize) -> Result<Tag, NbtError> {
+        if depth > MAX_DEPTH {
+            return Err(NbtError::TooDeep);
+        }
+        Ok(match id {
+            1 => Tag::Byte(i8::from_le_bytes(self.array()?)),
+            2 => Tag::Short(i16::from_le_bytes(self.array()?)),
+            3 => Tag::Int(i32::from_le_bytes(self.array()?)),
+            4 => Tag::Long(i64::from_le_bytes(self.array()?)),
+            5 => Tag::Float(f32::from_le_bytes(self.array()?)),
+            6 => Tag::Double(f64::from_le_bytes(self.array()?)),
+            7 => {
+                let length = self.length()?;
+                Tag::ByteArray(self.take(length)?.iter().map(|byte| *byte as i8).collect())
+            }
+            8 => Tag::String(self.string()?),
+            9 => {
+                let element = self.u8()?;
+                let length = self.length()?;
+                if element == 0 {
+                    // Some writers give empty lists a length anyway; there's nothing to read either way.
+                    Tag::List(vec![])
+                } else {
+                    // Each element is at least a byte, so a bogus length fails at the end of data rather than
+                    // allocating for it up front.
+                    let mut values = Vec::with_capacity(length.min(self.bytes.len() - self.position));
+                    for _ in 0..length {
+                        values.push(self.payload(element, depth + 1)?);
+                    }
+                    Tag::List(values)
+                }
+            }
+            10 => {
+                let mut compound = Compound::new();
+                loop {
+                    let id = self.u8()?;
+                    if id == 0 {
+                        break;
+                    }
+                    let name = self.string()?;
+                    compound.insert(name, self.payload(id, depth + 1)?);
+                }
+                Tag::Compound(compound)
+            }
+            11 => {
+                let length = self.length()?;
+                let bytes = self.take(length.checked_mul(4).ok_or(NbtError::UnexpectedEnd)?)?;
+                Tag::IntArray(bytes.chunks_exact(4).map(|chunk| i32::from_le_bytes([chunk[0], chunk[1], chunk[2], chunk[3]])).collect())
+            }
+            12 => {
+                let length = self.length()?;
+                let bytes = self.take(length.checked_mul(8).ok_or(NbtError::UnexpectedEnd)?)?;
+                Tag::LongArray(bytes.chunks_exact(8).map(|chunk| {
+                    i64::from_le_bytes([chunk[0], chunk[1], chunk[2], chunk[3], chunk[4], chunk[5], chunk[6], chunk[7]])
+                }).collect())
+            }
+            id => return Err(NbtError::InvalidTagType(id))
+        })
+    }
+}
+
+/// Reads a named root tag, returning its name and value. Trailing bytes are ignored.
+pub fn read_nbt(bytes: &[u8]) -> Result<(String, Tag), NbtError> {
+    let mut reader = Reader { bytes, position: 0 };
+    let id = reader.u8()?;
+    let name = reader.string()?;
+    let tag = reader.payload(id, 0)?;
+    Ok((name, tag))
+}
+
+fn write_string(out: &mut Vec<u8>, string: &str) -> Result<(), NbtError> {
+    let length = u16::try_from(string.len()).map_err(|_| NbtError::StringTooLong(string.len()))?;
+    out.extend(length.to_le_bytes());
+    out.extend(string.as_bytes());
+    Ok(())
+}
+
+fn write_length(out: &mut Vec<u8>, length: usize) -> Result<(), NbtError> {
+    out.extend(i32::try_from(length).map_err(|_| NbtError::LengthTooLarge(length))?.to_le_bytes());
+    Ok(())
+}
+
+fn write_payload(out: &mut Vec<u8>, tag: &Tag) -> Result<(), NbtError> {
+    match tag {
+        Tag::Byte(value) => out.extend(value.to_le_bytes()),
+        Tag::Short(value) => out.extend(value.to_le_bytes()),
+        Tag::Int(value) => out.extend(value.to_le_bytes()),
+        Tag::Long(value) => out.extend(value.to_le_bytes()),
+        Tag::Float(value) => out.extend(value.to_le_bytes()),
+        Tag::Double(value) => out.extend(value.to_le_bytes()),
+        Tag::ByteArray(values) => {
+            write_length(out, values.len())?;
+            out.extend(values.iter().map(|value| *value as u8));
+        }
+        Tag::String(value) => write_string(out, value)?,
+        Tag::List(values) => {
+            let element = values.first().map(Tag::id).unwrap_or(0);
+            if values.iter().any(|value| value.id() != element) {
+                return Err(NbtError::MixedList);
+            }
+            out.push(element);
+            write_length(out, values.len())?;
+            for value in values {
+                write_payload(out, value)?;
+            }
+        }
+        Tag::Compound(compound) => {
+            for (name, value) in compound {
+                out.push(value.id());
+                write_string(out, name)?;
+                write_payload(out, value)?;
+            }
+            out.push(0);
+        }
+        Tag::IntArray(values) => {
+            write_length(out, values.len())?;
+            values.iter().for_each(|value| out.extend(value.to_le_bytes()));
+        }
+        Tag::LongArray(values) => {
+            write_length(out, values.len())?;
+            values.iter().for_each(|value| out.extend(value.to_le_bytes()));
+        }
+    }
+    Ok(())
+}
+
+/// Writes `tag` as a named root tag. Fails for lists with mixed element types, and for strings,
+/// arrays and lists too long for their length prefix rather than cutting them short.
+pub fn write_nbt(name: &str, tag: &Tag) -> Result<Vec<u8>, NbtError> {
+    let mut out = vec![tag.id()];
+    write_string(&mut out, name)?;
+    write_payload(&mut out, tag)?;
+    Ok(out)
+}
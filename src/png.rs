@@ -0,0 +1,197 @@
+//! Minimal PNG reading and writing for generated and combined textures.
+
+#[cfg(feature = "textures")]
+use crate::generics::inflate::inflate;
+use crate::utils::crc32_update;
+
+fn chunk(out: &mut Vec<u8>, kind: &[u8; 4], data: &[u8]) {
+    out.extend((data.len() as u32).to_be_bytes());
+    let start = out.len();
+    out.extend(kind);
+    out.extend(data);
+    let crc = !crc32_update(!0, &out[start..]);
+    out.extend(crc.to_be_bytes());
+}
+
+/// Wraps `data` in a zlib stream of stored (uncompressed) deflate blocks, which every decoder
+/// reads and which needs no compressor.
+fn zlib_stored(data: &[u8]) -> Vec<u8> {
+    let mut out = vec![0x78, 0x01];
+    let mut blocks = data.chunks(u16::MAX as usize).peekable();
+    if blocks.peek().is_none() {
+        out.extend([1, 0, 0, 0xFF, 0xFF]);
+    }
+    while let Some(block) = blocks.next() {
+        out.push(u8::from(blocks.peek().is_none()));
+        out.extend((block.len() as u16).to_le_bytes());
+        out.extend((!(block.len() as u16)).to_le_bytes());
+        out.extend(block);
+    }
+    let (a, b) = data.iter().fold((1u32, 0u32), |(a, b), byte| {
+        let a = (a + *byte as u32) % 65521;
+        (a, (b + a) % 65521)
+    });
+    out.extend(((b << 16) | a).to_be_bytes());
+    out
+}
+
+/// Encodes 8-bit RGBA pixels, row by row, as a PNG.
+pub(crate) fn encode_png(width: u32, height: u32, rgba: &[u8]) -> Vec<u8> {
+    let mut out = b"\x89PNG\r\n\x1a\n".to_vec();
+
+    let mut header = vec![];
+    header.extend(width.to_be_bytes());
+    header.extend(height.to_be_bytes());
+    // 8 bits per channel, RGBA, default compression, filtering and no interlacing.
+    header.extend([8, 6, 0, 0, 0]);
+    chunk(&mut out, b"IHDR", &header);
+
+    let mut scanlines = vec![];
+    for row in rgba.chunks(width as usize * 4).take(height as usize) {
+        // Filter type 0: the row as is.
+        scanlines.push(0);
+        scanlines.extend(row);
+    }
+    chunk(&mut out, b"IDAT", &zlib_stored(&scanlines));
+    chunk(&mut out, b"IEND", &[]);
+    out
+}
+
+#[cfg(feature = "textures")]
+fn paeth(a: u8, b: u8, c: u8) -> u8 {
+    let p = a as i16 + b as i16 - c as i16;
+    let (pa, pb, pc) = ((p - a as i16).abs(), (p - b as i16).abs(), (p - c as i16).abs());
+    if pa <= pb && pa <= pc {
+        a
+    } else if pb <= pc {
+        b
+    } else {
+        c
+    }
+}
+
+/// Decodes a PNG into its width, height and 8-bit RGBA pixels, row by row. Every color type and bit
+/// depth is supported; 16-bit channels keep their high byte. Interlaced images aren't supported.
+#[cfg(feature = "textures")]
+pub(crate) fn decode_png(bytes: &[u8]) -> Result<(u32, u32, Vec<u8>), &'static str> {
+    let mut chunks = bytes.strip_prefix(b"\x89PNG\r\n\x1a\n").ok_or("not a PNG")?;
+    let mut header = None;
+    let mut palette: &[u8] = &[];
+    let mut transparency: &[u8] = &[];
+    let mut compressed = vec![];
+    while chunks.len() >= 12 {
+        let length = u32::from_be_bytes(chunks[..4].try_into().unwrap_or_default()) as usize;
+        let kind = &chunks[4..8];
+        let data = chunks.get(8..8 + length).ok_or("truncated chunk")?;
+        match kind {
+            b"IHDR" => header = Some(data),
+            b"PLTE" => palette = data,
+            b"tRNS" => transparency = data,
+            b"IDAT" => compressed.extend(data),
+            b"IEND" => break,
+            _ => {}
+        }
+        chunks = &chunks[(12 + length).min(chunks.len())..];
+    }
+
+    let header = header.filter(|header| header.len() == 13).ok_or("missing IHDR chunk")?;
+    let width = u32::from_be_bytes(header[..4].try_into().unwrap_or_default());
+    let height = u32::from_be_bytes(header[4..8].try_into().unwrap_or_default());
+    let (depth, color_type) = (header[8] as usize, header[9]);
+    if header[12] != 0 {
+        return Err("interlaced PNGs aren't supported");
+    }
+    let channels = match (color_type, depth) {
+        (0, 1 | 2 | 4 | 8 | 16) | (3, 1 | 2 | 4 | 8) => 1,
+        (4, 8 | 16) => 2,
+        (2, 8 | 16) => 3,
+        (6, 8 | 16) => 4,
+        _ => return Err("invalid color type or bit depth")
+    };
+    let bits_per_pixel = channels * depth;
+    let stride = (width as usize * bits_per_pixel).div_ceil(8);
+    let pixel_bytes = bits_per_pixel.div_ceil(8);
+    let size = (stride + 1).checked_mul(height as usize).ok_or("image too large")?;
+    // Skip the two byte zlib header; the Adler-32 after the deflate stream isn't checked.
+    let data = inflate(compressed.get(2..).ok_or("missing image data")?, size)?;
+    if data.len() != size {
+        return Err("image data doesn't match the image size");
+    }
+
+    let mut raw = vec![0u8; stride * height as usize];
+    for row in 0..height as usize {
+        let filter = data[row * (stride + 1)];
+        let line = &data[row * (stride + 1) + 1..(row + 1) * (stride + 1)];
+        let (previous, current) = raw.split_at_mut(row * stride);
+        let previous = if row == 0 { None } else { Some(&previous[(row - 1) * stride..]) };
+        let current = &mut current[..stride];
+        for i in 0..stride {
+            let a = if i >= pixel_bytes { current[i - pixel_bytes] } else { 0 };
+            let b = previous.map_or(0, |previous| previous[i]);
+            let c = if i >= pixel_bytes { previous.map_or(0, |previous| previous[i - pixel_bytes]) } else { 0 };
+            current[i] = line[i].wrapping_add(match filter {
+                0 => 0,
+                1 => a,
+                2 => b,
+                3 => ((a as u16 + b as u16) / 2) as u8,
+                4 => paeth(a, b, c),
+                _ => return Err("invalid filter type")
+            });
+        }
+    }
+
+    // The value of channel sample `index` of row `row`, scaled to 8 bits.
+    let sample = |row: &[u8], index: usize| -> (u8, u16) {
+        match depth {
+            16 => (row[index * 2], u16::from_be_bytes([row[index * 2], row[index * 2 + 1]])),
+            8 => (row[index], row[index] as u16),
+            _ => {
+                let bit = index * depth;
+                let value = (row[bit / 8] >> (8 - depth - bit % 8)) & ((1 << depth) - 1);
+                let scaled = if color_type == 3 { value } else { (value as u16 * 255 / ((1 << depth) - 1)) as u8 };
+                (scaled, value as u16)
+            }
+        }
+    };
+    let key = |index: usize| transparency.get(index * 2..index * 2 + 2).map(|key| u16::from_be_bytes([key[0], key[1]]));
+    let mut rgba = Vec::with_capacity(width as usize * height as usize * 4);
+    for row in raw.chunks(stride.max(1)).take(height as usize) {
+        for x in 0..width as usize {
+            let base = x * channels;
+            match color_type {
+                0 => {
+                    let (gray, raw) = sample(row, base);
+                    rgba.extend([gray, gray, gray, if key(0) == Some(raw) { 0 } else { 255 }]);
+                }
+                2 => {
+                    let (r, raw_r) = sample(row, base);
+                    let (g, raw_g) = sample(row, base + 1);
+                    let (b, raw_b) = sample(row, base + 2);
+                    let transparent = key(0) == Some(raw_r) && key(1) == Some(raw_g) && key(2) == Some(raw_b);
+                    rgba.extend([r, g, b, if transparent { 0 } else { 255 }]);
+                }
+                3 => {
+                    let (index, _) = sample(row, base);
+                    let color = palette.get(index as usize * 3..index as usize * 3 + 3).ok_or("palette index out of range")?;
+                    rgba.extend(color);
+                    rgba.push(transparency.get(index as usize).copied().unwrap_or(255));
+                }
+                4 => {
+                    let (gray, _) = sample(row, base);
+                    rgba.extend([gray, gray, gray, sample(row, base + 1).0]);
+                }
+                _ => rgba.extend((0..4).map(|channel| sample(row, base + channel).0))
+            }
+        }
+    }
+    Ok((width, height, rgba))
+}
+
+/// The width and height in the header of a PNG, without decoding it.
+#[cfg(feature = "textures")]
+pub(crate) fn png_size(bytes: &[u8]) -> Option<(u32, u32)> {
+    if !bytes.starts_with(b"\x89PNG\r\n\x1a\n") || bytes.get(12..16)? != b"IHDR" {
+        return None;
+    }
+    Some((u32::from_be_bytes(bytes.get(16..20)?.try_into().ok()?), u32::from_be_bytes(bytes.get(20..24)?.try_into().ok()?)))
+}
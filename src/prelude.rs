@@ -0,0 +1,25 @@
+//! The types most addon tooling needs, for a single glob import:
+//! `use bedrockrs_addon::prelude::*;`
+
+pub use crate::Uuid;
+pub use crate::addon::{Addon, PackKind, Visit};
+pub use crate::diagnostics::{Diagnostic, Diagnostics, Severity};
+pub use crate::error::{AddonError, AddonResult};
+pub use crate::files::{read_json_file, read_json_files, JsonFile};
+#[cfg(feature = "resource")]
+pub use crate::generics::lang::parse_lang_from_str;
+#[cfg(feature = "behavior")]
+pub use crate::generics::loot_table::{analyze_loot_table, deserialize_loot_table_from_str, LootTable};
+#[cfg(feature = "manifest")]
+pub use crate::generics::manifest::{
+    deserialize_manifest_from_path, deserialize_manifest_from_path_with_options, deserialize_manifest_from_str, Manifest,
+    ManifestCapability, ManifestDependency, ManifestHeader, ManifestModule, ManifestSubpack, ScriptManifestDependency,
+    ScriptManifestModule
+};
+#[cfg(feature = "manifest")]
+pub use crate::generics::parse::{ParseMode, ParseOptions};
+#[cfg(feature = "molang")]
+pub use crate::molang::Molang;
+pub use crate::utils::SemVer;
+#[cfg(feature = "validation")]
+pub use crate::validation::runner::{validate_addon, validate_addon_parallel, AddonPaths};
@@ -0,0 +1,22 @@
+/// A long running operation that reports [progress](Progress).
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+pub enum Task {
+    /// Visiting the files of an addon, counted in files.
+    Walking,
+    /// Writing a pack archive, counted in files.
+    Archiving,
+    /// Running validators, counted in rules.
+    Validating
+}
+
+/// Receives how far a long operation is, e.g. to drive a progress bar. Called after every step with
+/// the steps done so far and the total, possibly from several threads at once.
+pub trait Progress: Sync {
+    fn progress(&self, task: Task, done: usize, total: usize);
+}
+
+impl<F: Fn(Task, usize, usize) + Sync> Progress for F {
+    fn progress(&self, task: Task, done: usize, total: usize) {
+        self(task, done, total)
+    }
+}
@@ -0,0 +1,87 @@
+use std::path::PathBuf;
+use serde_json::Value;
+use crate::addon::Addon;
+use crate::files::{read_json_files, JsonFile};
+
+/// A definition found by a query.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct DefinitionMatch {
+    pub identifier: String,
+    /// Relative to the behavior pack root.
+    pub file: PathBuf
+}
+
+/// The definition object of each file under `folder` whose top-level key satisfies `kind`.
+pub(crate) fn definitions(addon: &Addon, folder: &str, kind: impl Fn(&str) -> bool) -> Vec<(JsonFile, Value)> {
+    let Some(bp) = &addon.behavior_pack else {
+        return vec![];
+    };
+    read_json_files(bp, folder).into_iter()
+        .filter_map(|file| {
+            let definition = file.value.as_object()?.iter().find(|(key, _)| kind(key))?.1.clone();
+            Some((file, definition))
+        })
+        .collect()
+}
+
+fn definition_match(file: JsonFile, definition: &Value) -> Option<DefinitionMatch> {
+    let identifier = definition.pointer("/description/identifier")?.as_str()?.to_string();
+    Some(DefinitionMatch { identifier, file: file.path })
+}
+
+/// The items a recipe's `result` or `output` produces.
+fn recipe_results(definition: &Value) -> Vec<&str> {
+    fn items<'a>(value: &'a Value, found: &mut Vec<&'a str>) {
+        match value {
+            Value::String(item) => found.push(item),
+            Value::Object(map) => {
+                if let Some(Value::String(item)) = map.get("item") {
+                    found.push(item);
+                }
+            }
+            Value::Array(values) => values.iter().for_each(|value| items(value, found)),
+            _ => {}
+        }
+    }
+
+    let mut found = vec![];
+    for key in ["result", "output"] {
+        if let Some(value) = definition.get(key) {
+            items(value, &mut found);
+        }
+    }
+    found
+}
+
+impl Addon {
+    /// Behavior entities with `component` in their components or any component group.
+    pub fn find_entities_with_component(&self, component: &str) -> Vec<DefinitionMatch> {
+        definitions(self, "entities", |key| key == "minecraft:entity").into_iter()
+            .filter(|(_, entity)| {
+                entity.pointer("/components").and_then(|components| components.get(component)).is_some()
+                    || entity.pointer("/component_groups").and_then(Value::as_object)
+                        .is_some_and(|groups| groups.values().any(|group| group.get(component).is_some()))
+            })
+            .filter_map(|(file, entity)| definition_match(file, &entity))
+            .collect()
+    }
+
+    /// Items whose `menu_category` (or pre-1.20 `category`) is `category`, e.g. `equipment`.
+    pub fn find_items_in_creative_category(&self, category: &str) -> Vec<DefinitionMatch> {
+        definitions(self, "items", |key| key == "minecraft:item").into_iter()
+            .filter(|(_, item)| {
+                let found = item.pointer("/description/menu_category/category").or_else(|| item.pointer("/description/category"));
+                found.and_then(Value::as_str) == Some(category)
+            })
+            .filter_map(|(file, item)| definition_match(file, &item))
+            .collect()
+    }
+
+    /// Recipes whose result is `item`.
+    pub fn find_recipes_producing(&self, item: &str) -> Vec<DefinitionMatch> {
+        definitions(self, "recipes", |key| key.starts_with("minecraft:recipe_")).into_iter()
+            .filter(|(_, recipe)| recipe_results(recipe).contains(&item))
+            .filter_map(|(file, recipe)| definition_match(file, &recipe))
+            .collect()
+    }
+}
@@ -0,0 +1,271 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use serde_json::{json, Value};
+use crate::addon::{Addon, PackKind};
+use crate::error::{AddonError, AddonResult};
+use crate::files::{read_json_files, EditableJson};
+use crate::generics::builders::{EntityBuilder, ItemBuilder};
+use crate::generics::lang::{add_lang_keys, title_case};
+use crate::generics::manifest::{serialize_manifest_to_string_with_style, Manifest, ManifestDependency, ManifestHeader, ManifestModule, ScriptManifestDependency, ScriptManifestModule};
+use crate::png::encode_png;
+use crate::utils::{crc32_update, new_uuid, JsonStyle, SemVer};
+
+/// What [`new_addon`] creates.
+#[derive(Clone, Debug)]
+pub struct ScaffoldOptions {
+    /// The packs go in `behavior_pack` and `resource_pack` below this folder.
+    pub root: PathBuf,
+    pub name: String,
+    pub description: String,
+    /// The namespace of the example content, e.g. `ruby` for `ruby:example_item`.
+    pub namespace: String,
+    pub min_engine_version: SemVer,
+    /// The `@minecraft/server` version the script entry depends on, or `None` for a pack without
+    /// scripts.
+    pub script_api: Option<SemVer>,
+    pub example_entity: bool,
+    pub example_item: bool,
+    /// How the JSON files are laid out.
+    pub json_style: JsonStyle
+}
+
+impl ScaffoldOptions {
+    /// A scripted addon targeting 1.21, without example content.
+    pub fn new(root: impl Into<PathBuf>, name: &str, namespace: &str) -> ScaffoldOptions {
+        ScaffoldOptions {
+            root: root.into(), name: name.to_string(), description: String::new(), namespace: namespace.to_string(),
+            min_engine_version: SemVer::new(1, 21, 0), script_api: Some(SemVer::new(1, 11, 0)), example_entity: false, example_item: false,
+            json_style: JsonStyle::default()
+        }
+    }
+}
+
+/// Writes `contents` to `path` below `root`, refusing to overwrite anything.
+fn write(root: &Path, path: &str, contents: &[u8]) -> AddonResult<()> {
+    let target = root.join(path);
+    if target.exists() {
+        return Err(AddonError::semantic(&target, "already exists"));
+    }
+    if let Some(parent) = target.parent() {
+        fs::create_dir_all(parent).map_err(|error| AddonError::io(parent, error))?;
+    }
+    fs::write(&target, contents).map_err(|error| AddonError::io(&target, error))
+}
+
+fn write_json(style: &JsonStyle, root: &Path, path: &str, value: &Value) -> AddonResult<()> {
+    write(root, path, style.write(value).as_bytes())
+}
+
+/// A square image of a single color.
+fn solid_png(size: u32, color: [u8; 4]) -> Vec<u8> {
+    encode_png(size, size, &color.repeat((size * size) as usize))
+}
+
+fn manifest(options: &ScaffoldOptions, module: ManifestModule, dependencies: Vec<ManifestDependency>) -> Manifest {
+    Manifest {
+        header: ManifestHeader {
+            uuid: new_uuid(), name: "pack.name".to_string(), description: "pack.description".to_string(),
+            min_engine_version: options.min_engine_version.clone(), version: SemVer::new(1, 0, 0)
+        },
+        modules: vec![module],
+        dependencies, subpacks: vec![], capabilities: vec![], raw: Value::Null
+    }
+}
+
+/// Writes a client entity for `identifier` with a single cube geometry and a texture of one
+/// color as placeholders, named after the identifier without its namespace. Returns the files
+/// written, relative to `resource_pack`.
+fn write_client_entity(style: &JsonStyle, resource_pack: &Path, identifier: &str, egg_colors: (&str, &str), color: [u8; 4]) -> AddonResult<Vec<String>> {
+    let (namespace, name) = identifier.split_once(':').unwrap_or(("", identifier));
+    let geometry = format!("geometry.{}.{}", namespace, name);
+    let files = [format!("entity/{}.entity.json", name), format!("models/entity/{}.geo.json", name), format!("textures/entity/{}.png", name)];
+    write_json(style, resource_pack, &files[0], &json!({
+        "format_version": "1.10.0",
+        "minecraft:client_entity": {
+            "description": {
+                "identifier": identifier,
+                "materials": { "default": "entity_alphatest" },
+                "textures": { "default": format!("textures/entity/{}", name) },
+                "geometry": { "default": geometry },
+                "render_controllers": ["controller.render.default"],
+                "spawn_egg": { "base_color": egg_colors.0, "overlay_color": egg_colors.1 }
+            }
+        }
+    }))?;
+    write_json(style, resource_pack, &files[1], &json!({
+        "format_version": "1.12.0",
+        "minecraft:geometry": [{
+            "description": { "identifier": geometry, "texture_width": 32, "texture_height": 16 },
+            "bones": [{ "name": "body", "pivot": [0, 0, 0], "cubes": [{ "origin": [-4, 0, -4], "size": [8, 8, 8], "uv": [0, 0] }] }]
+        }]
+    }))?;
+    write(resource_pack, &files[2], &encode_png(32, 16, &color.repeat(32 * 16)))?;
+    Ok(files.to_vec())
+}
+
+/// Creates a ready-to-edit addon: a behavior and a resource pack that depend on each other, with
+/// pack icons, `texts/` files whose `pack.name` and `pack.description` the manifests use, a script
+/// entry if scripts are enabled, and the example content asked for. Fails without overwriting if
+/// any of the files already exists.
+pub fn new_addon(options: &ScaffoldOptions) -> AddonResult<Addon> {
+    let bp = options.root.join("behavior_pack");
+    let rp = options.root.join("resource_pack");
+    let version = SemVer::new(1, 0, 0);
+
+    let mut rp_manifest = manifest(options, ManifestModule::Resources(new_uuid(), version.clone()), vec![]);
+    let mut bp_manifest = manifest(options, ManifestModule::Data(new_uuid(), version.clone()), vec![
+        ManifestDependency::UuidDependency(rp_manifest.header.uuid, version.clone())
+    ]);
+    rp_manifest.dependencies.push(ManifestDependency::UuidDependency(bp_manifest.header.uuid, version.clone()));
+    if let Some(script_api) = &options.script_api {
+        bp_manifest.modules.push(ManifestModule::Script(new_uuid(), version.clone(), ScriptManifestModule { entry: "scripts/main.js".to_string() }));
+        bp_manifest.dependencies.push(ManifestDependency::ScriptDependency(ScriptManifestDependency::MinecraftServer, script_api.clone()));
+    }
+
+    let pack_lang = format!("pack.name={}\npack.description={}\n", options.name, options.description);
+    let mut rp_lang = pack_lang.clone();
+    for (root, manifest, color) in [(&bp, &bp_manifest, [67, 160, 71, 255]), (&rp, &rp_manifest, [30, 136, 229, 255])] {
+        write(root, "manifest.json", serialize_manifest_to_string_with_style(manifest, &options.json_style).as_bytes())?;
+        write(root, "pack_icon.png", &solid_png(64, color))?;
+        write_json(&options.json_style, root, "texts/languages.json", &json!(["en_US"]))?;
+    }
+    write(&bp, "texts/en_US.lang", pack_lang.as_bytes())?;
+    if options.script_api.is_some() {
+        let script = format!(
+            "import {{ world }} from \"@minecraft/server\";\n\nworld.afterEvents.playerSpawn.subscribe(({{ player, initialSpawn }}) => {{\n    if (initialSpawn) {{\n        player.sendMessage({});\n    }}\n}});\n",
+            Value::String(format!("{} is running", options.name))
+        );
+        write(&bp, "scripts/main.js", script.as_bytes())?;
+    }
+
+    let namespace = &options.namespace;
+    if options.example_item {
+        let identifier = format!("{}:example_item", namespace);
+        let texture = format!("{}_example_item", namespace);
+        let item = ItemBuilder::new(&identifier).format_version("1.20.50").menu_category("items")
+            .component("minecraft:icon", json!({ "texture": texture }))
+            .build();
+        write_json(&options.json_style, &bp, "items/example_item.json", &item)?;
+        write_json(&options.json_style, &rp, "textures/item_texture.json", &json!({
+            "resource_pack_name": options.name, "texture_name": "atlas.items",
+            "texture_data": { texture: { "textures": "textures/items/example_item" } }
+        }))?;
+        write(&rp, "textures/items/example_item.png", &solid_png(16, [229, 57, 53, 255]))?;
+        rp_lang.push_str(&format!("item.{}.name=Example Item\n", identifier));
+    }
+    if options.example_entity {
+        let identifier = format!("{}:example_entity", namespace);
+        let entity = EntityBuilder::new(&identifier).spawnable(true)
+            .component("minecraft:health", json!({ "value": 10, "max": 10 }))
+            .component("minecraft:collision_box", json!({ "width": 0.5, "height": 0.5 }))
+            .component("minecraft:physics", json!({}))
+            .component("minecraft:pushable", json!({ "is_pushable": true, "is_pushable_by_piston": true }))
+            .build();
+        write_json(&options.json_style, &bp, "entities/example_entity.json", &entity)?;
+        write_client_entity(&options.json_style, &rp, &identifier, ("#43a047", "#1e88e5"), [142, 36, 170, 255])?;
+        rp_lang.push_str(&format!("entity.{}.name=Example Entity\nitem.spawn_egg.entity.{}.name=Spawn Example Entity\n", identifier, identifier));
+    }
+    write(&rp, "texts/en_US.lang", rp_lang.as_bytes())?;
+
+    Ok(Addon::new(Some(bp), Some(rp)))
+}
+
+/// What [`scaffold_entity`] creates.
+#[derive(Clone, Debug)]
+pub struct EntityScaffold {
+    pub identifier: String,
+    /// The name shown in game.
+    pub name: String,
+    /// The spawn egg's colors as `#rrggbb`.
+    pub base_color: String,
+    pub overlay_color: String,
+    /// The population the spawn rules count the entity towards, which is also the biome tag it
+    /// spawns in, e.g. `animal` or `monster`.
+    pub population: String,
+    pub json_style: JsonStyle
+}
+
+impl EntityScaffold {
+    /// A spawnable animal named after the [title-cased](title_case) identifier, with egg colors
+    /// derived from the identifier so different entities get different eggs.
+    pub fn new(identifier: &str) -> EntityScaffold {
+        let hash = !crc32_update(!0, identifier.as_bytes());
+        EntityScaffold {
+            identifier: identifier.to_string(), name: title_case(identifier), base_color: format!("#{:06x}", hash & 0xff_ffff),
+            overlay_color: format!("#{:06x}", (hash >> 8) ^ 0xff_ffff), population: "animal".to_string(), json_style: JsonStyle::default()
+        }
+    }
+}
+
+/// Makes the behavior entity `entity.identifier` of `addon` spawnable in game: creates the entity
+/// if no behavior entity has the identifier yet (or marks the existing one spawnable), spawn rules,
+/// a client entity with the spawn egg colors and placeholder geometry and texture, and the name and
+/// spawn egg translations. Returns the files written, relative to their pack. Fails without
+/// overwriting if any of the new files already exists.
+pub fn scaffold_entity(addon: &Addon, entity: &EntityScaffold) -> AddonResult<Vec<(PackKind, String)>> {
+    let (Some(bp), Some(rp)) = (&addon.behavior_pack, &addon.resource_pack) else {
+        return Err(AddonError::semantic(&entity.identifier, "the addon needs a behavior and a resource pack"));
+    };
+    let identifier = entity.identifier.as_str();
+    let name = identifier.split_once(':').map_or(identifier, |(_, name)| name);
+    let style = &entity.json_style;
+    let mut written = vec![];
+
+    let existing = read_json_files(bp, "entities").into_iter()
+        .find(|file| file.value.pointer("/minecraft:entity/description/identifier").and_then(Value::as_str) == Some(identifier));
+    match existing {
+        Some(file) => {
+            let mut editable = EditableJson::open(&bp.join(&file.path))?;
+            if editable.value.pointer("/minecraft:entity/description/is_spawnable") != Some(&json!(true)) {
+                editable.value["minecraft:entity"]["description"]["is_spawnable"] = json!(true);
+                editable.save()?;
+                written.push((PackKind::Behavior, file.path.to_string_lossy().replace('\\', "/")));
+            }
+        }
+        None => {
+            let path = format!("entities/{}.json", name);
+            let value = EntityBuilder::new(identifier).spawnable(true)
+                .component("minecraft:health", json!({ "value": 10, "max": 10 }))
+                .component("minecraft:collision_box", json!({ "width": 0.8, "height": 0.8 }))
+                .component("minecraft:physics", json!({}))
+                .component("minecraft:pushable", json!({ "is_pushable": true, "is_pushable_by_piston": true }))
+                .component("minecraft:movement", json!({ "value": 0.25 }))
+                .component("minecraft:movement.basic", json!({}))
+                .component("minecraft:navigation.walk", json!({ "avoid_water": true }))
+                .component("minecraft:behavior.random_stroll", json!({ "priority": 6, "speed_multiplier": 1.0 }))
+                .build();
+            write_json(style, bp, &path, &value)?;
+            written.push((PackKind::Behavior, path));
+        }
+    }
+
+    let path = format!("spawn_rules/{}.json", name);
+    write_json(style, bp, &path, &json!({
+        "format_version": "1.8.0",
+        "minecraft:spawn_rules": {
+            "description": { "identifier": identifier, "population_control": entity.population },
+            "conditions": [{
+                "minecraft:spawns_on_surface": {},
+                "minecraft:brightness_filter": { "min": 7, "max": 15, "adjust_for_weather": false },
+                "minecraft:weight": { "default": 8 },
+                "minecraft:herd": { "min_size": 2, "max_size": 4 },
+                "minecraft:biome_filter": { "test": "has_biome_tag", "value": entity.population }
+            }]
+        }
+    }))?;
+    written.push((PackKind::Behavior, path));
+
+    let color = u32::from_str_radix(entity.base_color.trim_start_matches('#'), 16).unwrap_or(0x80_8080).to_be_bytes();
+    let files = write_client_entity(style, rp, identifier, (&entity.base_color, &entity.overlay_color), [color[1], color[2], color[3], 255])?;
+    written.extend(files.into_iter().map(|path| (PackKind::Resource, path)));
+
+    let keys = BTreeMap::from([
+        (format!("entity.{}.name", identifier), entity.name.clone()),
+        (format!("item.spawn_egg.entity.{}.name", identifier), format!("Spawn {}", entity.name))
+    ]);
+    if !add_lang_keys(rp, keys)?.is_empty() {
+        written.push((PackKind::Resource, "texts/en_US.lang".to_string()));
+    }
+    Ok(written)
+}
@@ -0,0 +1,86 @@
+//! Modules for `#[serde(with = "...")]`, so crates building on this one can embed the same
+//! representations in their own types, e.g.
+//! `#[serde(with = "bedrockrs_addon::serde_helpers::uuid_string")]`.
+
+use serde::de::Error;
+use serde::{Deserialize, Deserializer};
+use serde_json::Value;
+use crate::utils::{try_parse_semver_from_str, SemVer};
+
+fn deserialize_semver<'de, D: Deserializer<'de>>(deserializer: D) -> Result<SemVer, D::Error> {
+    let value = Value::deserialize(deserializer)?;
+    let parsed = match &value {
+        Value::String(version) => try_parse_semver_from_str(version),
+        Value::Array(parts) => match parts.iter().map(|part| part.as_i64().and_then(|part| i32::try_from(part).ok())).collect::<Option<Vec<i32>>>().as_deref() {
+            Some([major, minor, patch]) => Some(SemVer::new(*major, *minor, *patch)),
+            _ => None
+        },
+        _ => None
+    };
+    parsed.ok_or_else(|| D::Error::custom(format!("expected a version, found {}", value)))
+}
+
+/// A [`SemVer`] written as `"1.2.3"`, or `"1.2.3-beta"` for betas. Reads the array form too.
+pub mod semver_string {
+    use serde::{Deserializer, Serializer};
+    use crate::utils::SemVer;
+
+    pub fn serialize<S: Serializer>(version: &SemVer, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(version)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<SemVer, D::Error> {
+        super::deserialize_semver(deserializer)
+    }
+}
+
+/// A [`SemVer`] written as `[1, 2, 3]`, as in manifest headers and modules. The array form can't
+/// express betas, so they're rejected when serializing. Reads the string form too.
+pub mod semver_array {
+    use serde::ser::Error;
+    use serde::{Deserializer, Serialize, Serializer};
+    use crate::utils::SemVer;
+
+    pub fn serialize<S: Serializer>(version: &SemVer, serializer: S) -> Result<S::Ok, S::Error> {
+        if version.beta {
+            return Err(S::Error::custom(format!("{} can't be written as an array", version)));
+        }
+        [version.major, version.minor, version.patch].serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<SemVer, D::Error> {
+        super::deserialize_semver(deserializer)
+    }
+}
+
+/// A [`Uuid`](uuid::Uuid) written in its hyphenated string form.
+pub mod uuid_string {
+    use serde::de::Error;
+    use serde::{Deserialize, Deserializer, Serializer};
+    use uuid::Uuid;
+
+    pub fn serialize<S: Serializer>(uuid: &Uuid, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(&uuid.hyphenated())
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Uuid, D::Error> {
+        let src = String::deserialize(deserializer)?;
+        Uuid::parse_str(&src).map_err(|error| D::Error::custom(format!("invalid uuid {:?}: {}", src, error)))
+    }
+}
+
+#[cfg(feature = "molang")]
+/// A Molang expression kept as a `String`, read and written like [`Molang`](crate::molang::Molang):
+/// numbers and booleans are accepted, and numeric literals are written back as numbers.
+pub mod molang {
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+    use crate::molang::Molang;
+
+    pub fn serialize<S: Serializer>(expression: &str, serializer: S) -> Result<S::Ok, S::Error> {
+        Molang::from(expression).serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<String, D::Error> {
+        Ok(Molang::deserialize(deserializer)?.0)
+    }
+}
@@ -0,0 +1,397 @@
+use std::collections::BTreeMap;
+use std::error::Error;
+use std::fmt::{Display, Formatter};
+use std::fs;
+use std::path::{Path, PathBuf};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Number, Value};
+use crate::addon::{Addon, PackKind, Visit};
+use crate::error::{AddonError, AddonResult};
+use crate::files::JsonFile;
+#[cfg(feature = "resource")]
+use crate::generics::lang::parse_lang_from_str;
+use crate::utils::from_json_str;
+
+/// The first bytes of every snapshot, followed by [`SNAPSHOT_VERSION`].
+pub const SNAPSHOT_MAGIC: [u8; 4] = *b"BRAS";
+/// Bumped whenever the layout changes; older snapshots fail to load and should be rebuilt.
+pub const SNAPSHOT_VERSION: u8 = 1;
+pub const MAX_DEPTH: usize = 512;
+
+/// The parsed contents of one pack.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct PackSnapshot {
+    pub kind: PackKind,
+    /// Every readable JSON file, in the order [`Addon::walk`] visits them.
+    pub json: Vec<JsonFile>,
+    /// The translations of every `.lang` file, by path relative to the pack root. Only collected
+    /// with the `resource` feature.
+    pub lang: BTreeMap<PathBuf, BTreeMap<String, String>>,
+    /// Every other file, relative to the pack root.
+    pub files: Vec<PathBuf>
+}
+
+/// A parsed addon that can be saved and reloaded without reading or parsing the packs again.
+/// Loading the binary form from [`serialize_snapshot_to_bytes`] skips JSON parsing entirely; the
+/// serde impls are there for tools with a format of their own.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+pub struct AddonSnapshot {
+    pub packs: Vec<PackSnapshot>
+}
+
+impl AddonSnapshot {
+    pub fn capture(addon: &Addon) -> AddonSnapshot {
+        let mut snapshot = AddonSnapshot::default();
+        addon.walk(&mut snapshot);
+        snapshot
+    }
+
+    pub fn pack(&self, kind: PackKind) -> Option<&PackSnapshot> {
+        self.packs.iter().find(|pack| pack.kind == kind)
+    }
+
+    /// The JSON document at `path`, relative to the root of the `kind` pack.
+    pub fn json(&self, kind: PackKind, path: &str) -> Option<&Value> {
+        self.pack(kind)?.json.iter().find(|file| file.path == Path::new(path)).map(|file| &file.value)
+    }
+
+    fn pack_mut(&mut self, kind: PackKind) -> &mut PackSnapshot {
+        if self.pack(kind).is_none() {
+            self.packs.push(PackSnapshot { kind, json: vec![], lang: BTreeMap::new(), files: vec![] });
+        }
+        self.packs.iter_mut().find(|pack| pack.kind == kind).unwrap()
+    }
+}
+
+impl PackSnapshot {
+    /// Parses a pack held in memory, e.g. the files of an archive from
+    /// [`deserialize_mcpack_from_bytes`](crate::generics::mcpack::deserialize_mcpack_from_bytes),
+    /// by `/`-separated path relative to the pack root. Like [`Addon::walk`], it skips JSON and
+    /// `.lang` files that fail to parse.
+    pub fn from_files(kind: PackKind, files: &BTreeMap<String, Vec<u8>>) -> PackSnapshot {
+        let mut pack = PackSnapshot { kind, json: vec![], lang: BTreeMap::new(), files: vec![] };
+        for (path, bytes) in files {
+            let src = std::str::from_utf8(bytes).ok();
+            if path.ends_with(".json") {
+                if let Some(value) = src.and_then(|src| from_json_str(src).ok()) {
+                    pack.json.push(JsonFile { path: PathBuf::from(path), value });
+                }
+            } else if path.ends_with(".lang") {
+                #[cfg(feature = "resource")]
+                if let Some(src) = src {
+                    pack.lang.insert(PathBuf::from(path), parse_lang_from_str(src));
+                }
+            } else {
+                pack.files.push(PathBuf::from(path));
+            }
+        }
+        pack
+    }
+
+    /// The identifier and definition object of each file under `folder` whose top-level key
+    /// satisfies `kind`, skipping files without a `description.identifier`.
+    fn definitions<'a>(&'a self, folder: &'a str, kind: fn(&str) -> bool) -> impl Iterator<Item = (&'a str, &'a Value)> + 'a {
+        self.json.iter()
+            .filter(move |file| file.path.starts_with(folder))
+            .filter_map(move |file| {
+                let definition = file.value.as_object()?.iter().find(|(key, _)| kind(key))?.1;
+                Some((definition.pointer("/description/identifier")?.as_str()?, definition))
+            })
+    }
+
+    /// The `minecraft:entity` definitions of a behavior pack by identifier, in file order.
+    pub fn entities(&self) -> impl Iterator<Item = (&str, &Value)> {
+        self.definitions("entities", |key| key == "minecraft:entity")
+    }
+
+    pub fn items(&self) -> impl Iterator<Item = (&str, &Value)> {
+        self.definitions("items", |key| key == "minecraft:item")
+    }
+
+    pub fn blocks(&self) -> impl Iterator<Item = (&str, &Value)> {
+        self.definitions("blocks", |key| key == "minecraft:block")
+    }
+
+    /// Every kind of recipe, e.g. `minecraft:recipe_shaped` and `minecraft:recipe_furnace`.
+    pub fn recipes(&self) -> impl Iterator<Item = (&str, &Value)> {
+        self.definitions("recipes", |key| key.starts_with("minecraft:recipe_"))
+    }
+}
+
+impl Visit for AddonSnapshot {
+    #[cfg(feature = "resource")]
+    fn visit_lang(&mut self, pack: PackKind, file: &Path, translations: &BTreeMap<String, String>) {
+        self.pack_mut(pack).lang.insert(file.to_path_buf(), translations.clone());
+    }
+
+    fn visit_json(&mut self, pack: PackKind, file: &JsonFile) {
+        self.pack_mut(pack).json.push(file.clone());
+    }
+
+    fn visit_file(&mut self, pack: PackKind, file: &Path) {
+        let pack = self.pack_mut(pack);
+        if !file.extension().is_some_and(|ext| ext == "json" || ext == "lang") {
+            pack.files.push(file.to_path_buf());
+        }
+    }
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum SnapshotError {
+    /// Not a snapshot at all.
+    InvalidMagic,
+    /// A snapshot written by another version of the crate.
+    UnsupportedVersion(u8),
+    UnexpectedEnd,
+    InvalidTag(u8),
+    InvalidString,
+    InvalidNumber,
+    /// Nesting deeper than [`MAX_DEPTH`].
+    TooDeep
+}
+
+impl Display for SnapshotError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SnapshotError::InvalidMagic => write!(f, "not an addon snapshot"),
+            SnapshotError::UnsupportedVersion(version) => write!(f, "unsupported snapshot version {}, expected {}", version, SNAPSHOT_VERSION),
+            SnapshotError::UnexpectedEnd => write!(f, "unexpected end of snapshot data"),
+            SnapshotError::InvalidTag(tag) => write!(f, "invalid snapshot tag {}", tag),
+            SnapshotError::InvalidString => write!(f, "snapshot string is not valid UTF-8"),
+            SnapshotError::InvalidNumber => write!(f, "snapshot number is not finite"),
+            SnapshotError::TooDeep => write!(f, "snapshot nested more than {} levels deep", MAX_DEPTH)
+        }
+    }
+}
+
+impl Error for SnapshotError {}
+
+const NULL: u8 = 0;
+const FALSE: u8 = 1;
+const TRUE: u8 = 2;
+const UNSIGNED: u8 = 3;
+const SIGNED: u8 = 4;
+const FLOAT: u8 = 5;
+const STRING: u8 = 6;
+const ARRAY: u8 = 7;
+const OBJECT: u8 = 8;
+
+fn write_length(out: &mut Vec<u8>, mut value: u64) {
+    // LEB128: seven bits per byte, high bit set on all but the last.
+    while value >= 0x80 {
+        out.push(value as u8 | 0x80);
+        value >>= 7;
+    }
+    out.push(value as u8);
+}
+
+fn write_string(out: &mut Vec<u8>, string: &str) {
+    write_length(out, string.len() as u64);
+    out.extend_from_slice(string.as_bytes());
+}
+
+fn write_value(out: &mut Vec<u8>, value: &Value) {
+    match value {
+        Value::Null => out.push(NULL),
+        Value::Bool(false) => out.push(FALSE),
+        Value::Bool(true) => out.push(TRUE),
+        Value::Number(number) => {
+            if let Some(number) = number.as_u64() {
+                out.push(UNSIGNED);
+                write_length(out, number);
+            } else if let Some(number) = number.as_i64() {
+                out.push(SIGNED);
+                out.extend_from_slice(&number.to_le_bytes());
+            } else {
+                out.push(FLOAT);
+                out.extend_from_slice(&number.as_f64().unwrap_or_default().to_le_bytes());
+            }
+        }
+        Value::String(string) => {
+            out.push(STRING);
+            write_string(out, string);
+        }
+        Value::Array(values) => {
+            out.push(ARRAY);
+            write_length(out, values.len() as u64);
+            for value in values {
+                write_value(out, value);
+            }
+        }
+        Value::Object(map) => {
+            out.push(OBJECT);
+            write_length(out, map.len() as u64);
+            for (key, value) in map {
+                write_string(out, key);
+                write_value(out, value);
+            }
+        }
+    }
+}
+
+fn write_path(out: &mut Vec<u8>, path: &Path) {
+    write_string(out, &path.to_string_lossy().replace('\\', "/"));
+}
+
+struct Reader<'a> {
+    bytes: &'a [u8],
+    position: usize
+}
+
+impl Reader<'_> {
+    fn take(&mut self, count: usize) -> Result<&[u8], SnapshotError> {
+        let end = self.position.checked_add(count).filter(|end| *end <= self.bytes.len()).ok_or(SnapshotError::UnexpectedEnd)?;
+        let bytes = &self.bytes[self.position..end];
+        self.position = end;
+        Ok(bytes)
+    }
+
+    fn u8(&mut self) -> Result<u8, SnapshotError> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn array<const N: usize>(&mut self) -> Result<[u8; N], SnapshotError> {
+        Ok(self.take(N)?.try_into().unwrap_or([0; N]))
+    }
+
+    fn u64(&mut self) -> Result<u64, SnapshotError> {
+        let mut value = 0u64;
+        for shift in (0..64).step_by(7) {
+            let byte = self.u8()?;
+            value |= u64::from(byte & 0x7f) << shift;
+            if byte & 0x80 == 0 {
+                return Ok(value);
+            }
+        }
+        Err(SnapshotError::InvalidNumber)
+    }
+
+    /// A count of items, each at least a byte long, so a bogus count fails at the end of the
+    /// data instead of allocating for it.
+    fn length(&mut self) -> Result<usize, SnapshotError> {
+        usize::try_from(self.u64()?).ok().filter(|length| *length <= self.bytes.len() - self.position).ok_or(SnapshotError::UnexpectedEnd)
+    }
+
+    fn string(&mut self) -> Result<String, SnapshotError> {
+        let length = self.length()?;
+        String::from_utf8(self.take(length)?.to_vec()).map_err(|_| SnapshotError::InvalidString)
+    }
+
+    fn value(&mut self, depth: usize) -> Result<Value, SnapshotError> {
+        if depth > MAX_DEPTH {
+            return Err(SnapshotError::TooDeep);
+        }
+        Ok(match self.u8()? {
+            NULL => Value::Null,
+            FALSE => Value::Bool(false),
+            TRUE => Value::Bool(true),
+            UNSIGNED => Value::Number(self.u64()?.into()),
+            SIGNED => Value::Number(i64::from_le_bytes(self.array()?).into()),
+            FLOAT => Value::Number(Number::from_f64(f64::from_le_bytes(self.array()?)).ok_or(SnapshotError::InvalidNumber)?),
+            STRING => Value::String(self.string()?),
+            ARRAY => {
+                let length = self.length()?;
+                let mut values = Vec::with_capacity(length);
+                for _ in 0..length {
+                    values.push(self.value(depth + 1)?);
+                }
+                Value::Array(values)
+            }
+            OBJECT => {
+                let length = self.length()?;
+                let mut map = Map::new();
+                for _ in 0..length {
+                    let key = self.string()?;
+                    map.insert(key, self.value(depth + 1)?);
+                }
+                Value::Object(map)
+            }
+            tag => return Err(SnapshotError::InvalidTag(tag))
+        })
+    }
+
+    fn path(&mut self) -> Result<PathBuf, SnapshotError> {
+        Ok(PathBuf::from(self.string()?))
+    }
+}
+
+pub fn serialize_snapshot_to_bytes(snapshot: &AddonSnapshot) -> Vec<u8> {
+    let mut out = SNAPSHOT_MAGIC.to_vec();
+    out.push(SNAPSHOT_VERSION);
+    write_length(&mut out, snapshot.packs.len() as u64);
+    for pack in &snapshot.packs {
+        out.push(match pack.kind {
+            PackKind::Behavior => 0,
+            PackKind::Resource => 1
+        });
+        write_length(&mut out, pack.json.len() as u64);
+        for file in &pack.json {
+            write_path(&mut out, &file.path);
+            write_value(&mut out, &file.value);
+        }
+        write_length(&mut out, pack.lang.len() as u64);
+        for (path, translations) in &pack.lang {
+            write_path(&mut out, path);
+            write_length(&mut out, translations.len() as u64);
+            for (key, value) in translations {
+                write_string(&mut out, key);
+                write_string(&mut out, value);
+            }
+        }
+        write_length(&mut out, pack.files.len() as u64);
+        for path in &pack.files {
+            write_path(&mut out, path);
+        }
+    }
+    out
+}
+
+pub fn deserialize_snapshot_from_bytes(bytes: &[u8]) -> Result<AddonSnapshot, SnapshotError> {
+    let mut reader = Reader { bytes, position: 0 };
+    if reader.take(4).map_err(|_| SnapshotError::InvalidMagic)? != SNAPSHOT_MAGIC {
+        return Err(SnapshotError::InvalidMagic);
+    }
+    let version = reader.u8()?;
+    if version != SNAPSHOT_VERSION {
+        return Err(SnapshotError::UnsupportedVersion(version));
+    }
+
+    let mut snapshot = AddonSnapshot::default();
+    for _ in 0..reader.length()? {
+        let kind = match reader.u8()? {
+            0 => PackKind::Behavior,
+            1 => PackKind::Resource,
+            tag => return Err(SnapshotError::InvalidTag(tag))
+        };
+        let mut pack = PackSnapshot { kind, json: vec![], lang: BTreeMap::new(), files: vec![] };
+        for _ in 0..reader.length()? {
+            let path = reader.path()?;
+            pack.json.push(JsonFile { path, value: reader.value(0)? });
+        }
+        for _ in 0..reader.length()? {
+            let path = reader.path()?;
+            let mut translations = BTreeMap::new();
+            for _ in 0..reader.length()? {
+                let key = reader.string()?;
+                translations.insert(key, reader.string()?);
+            }
+            pack.lang.insert(path, translations);
+        }
+        for _ in 0..reader.length()? {
+            pack.files.push(reader.path()?);
+        }
+        snapshot.packs.push(pack);
+    }
+    Ok(snapshot)
+}
+
+pub fn deserialize_snapshot_from_path(path: impl AsRef<Path>) -> AddonResult<AddonSnapshot> {
+    let path = path.as_ref();
+    let bytes = fs::read(path).map_err(|error| AddonError::io(path, error))?;
+    deserialize_snapshot_from_bytes(&bytes).map_err(|error| AddonError::semantic(path, error.to_string()))
+}
+
+pub fn write_snapshot_to_path(snapshot: &AddonSnapshot, path: impl AsRef<Path>) -> AddonResult<()> {
+    let path = path.as_ref();
+    fs::write(path, serialize_snapshot_to_bytes(snapshot)).map_err(|error| AddonError::io(path, error))
+}
@@ -0,0 +1,344 @@
+//! Tools for resource pack textures: combining many small textures into one atlas image,
+//! animation frames into a flipbook strip, and texture variants into subpacks.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use serde_json::{json, Map, Value};
+use crate::error::{AddonError, AddonResult};
+use crate::files::{glob_match, merge_atlas, pack_files, read_json_file, EditableJson};
+use crate::png::{decode_png, encode_png, png_size};
+use crate::utils::to_canonical_json_string;
+
+/// The texture atlas file a [`TextureAtlas`] registers its tiles in.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum AtlasKind {
+    /// `textures/item_texture.json`
+    Items,
+    /// `textures/terrain_texture.json`
+    Terrain
+}
+
+impl AtlasKind {
+    pub fn file(self) -> &'static str {
+        match self {
+            AtlasKind::Items => "textures/item_texture.json",
+            AtlasKind::Terrain => "textures/terrain_texture.json"
+        }
+    }
+
+    fn texture_name(self) -> &'static str {
+        match self {
+            AtlasKind::Items => "atlas.items",
+            AtlasKind::Terrain => "atlas.terrain"
+        }
+    }
+}
+
+/// Where a texture ended up in a [`TextureAtlas`].
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct AtlasTile {
+    /// The short name the texture is registered under, e.g. `ruby`.
+    pub name: String,
+    /// The cell of the grid, counting left to right and then top to bottom.
+    pub index: usize,
+    pub x: u32,
+    pub y: u32,
+    pub width: u32,
+    pub height: u32
+}
+
+/// Textures laid out on a grid of equal cells, each as large as the largest texture. Smaller
+/// textures sit in the top-left corner of their cell.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct TextureAtlas {
+    pub width: u32,
+    pub height: u32,
+    pub columns: u32,
+    /// 8-bit RGBA pixels, row by row.
+    pub rgba: Vec<u8>,
+    pub tiles: Vec<AtlasTile>
+}
+
+impl TextureAtlas {
+    pub fn png(&self) -> Vec<u8> {
+        encode_png(self.width, self.height, &self.rgba)
+    }
+
+    /// The `texture_data` entries of the tiles, pointing each short name at the atlas image at
+    /// `path` (relative to the pack and without extension, e.g. `textures/atlas/items`) with its
+    /// tile index.
+    pub fn texture_data(&self, path: &str) -> Map<String, Value> {
+        self.tiles.iter()
+            .map(|tile| (tile.name.clone(), json!({ "textures": { "path": path, "tile_index": tile.index } })))
+            .collect()
+    }
+
+    /// Writes the atlas image to `path` (as for [`texture_data`](TextureAtlas::texture_data)) in
+    /// `resource_pack` and adds its tiles to the pack's atlas file of `kind`, keeping the entries
+    /// already there. Returns the files written.
+    pub fn write(&self, resource_pack: &Path, kind: AtlasKind, path: &str) -> AddonResult<Vec<PathBuf>> {
+        let atlas = merge_atlas(resource_pack, kind.file(), kind.texture_name(), self.texture_data(path))?;
+        let image = resource_pack.join(format!("{}.png", path));
+        if let Some(parent) = image.parent() {
+            fs::create_dir_all(parent).map_err(|error| AddonError::io(parent, error))?;
+        }
+        fs::write(&image, self.png()).map_err(|error| AddonError::io(&image, error))?;
+
+        let atlas_path = resource_pack.join(kind.file());
+        if let Some(parent) = atlas_path.parent() {
+            fs::create_dir_all(parent).map_err(|error| AddonError::io(parent, error))?;
+        }
+        fs::write(&atlas_path, to_canonical_json_string(&atlas)).map_err(|error| AddonError::io(&atlas_path, error))?;
+        Ok(vec![image, atlas_path])
+    }
+}
+
+/// Collects PNG textures to combine into a [`TextureAtlas`].
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct AtlasBuilder {
+    textures: Vec<(String, PathBuf)>
+}
+
+impl AtlasBuilder {
+    pub fn new() -> AtlasBuilder {
+        AtlasBuilder::default()
+    }
+
+    /// Adds the PNG at `path` under the short name `name`.
+    pub fn texture(mut self, name: &str, path: impl Into<PathBuf>) -> AtlasBuilder {
+        self.textures.push((name.to_string(), path.into()));
+        self
+    }
+
+    /// Adds every PNG below `dir`, named `prefix` followed by its file name without extension.
+    pub fn directory(mut self, dir: &Path, prefix: &str) -> AtlasBuilder {
+        for path in pack_files(dir, "") {
+            if path.extension().is_some_and(|ext| ext == "png") {
+                let stem = path.file_stem().unwrap_or_default().to_string_lossy();
+                self.textures.push((format!("{}{}", prefix, stem), path));
+            }
+        }
+        self
+    }
+
+    /// Reads the textures and lays them out in the order they were added, on a grid as close to
+    /// square as fits them.
+    pub fn build(&self) -> AddonResult<TextureAtlas> {
+        if self.textures.is_empty() {
+            return Err(AddonError::semantic("atlas", "no textures to combine"));
+        }
+        let mut images = vec![];
+        for (name, path) in &self.textures {
+            let bytes = fs::read(path).map_err(|error| AddonError::io(path, error))?;
+            let image = decode_png(&bytes).map_err(|error| AddonError::semantic(path, error))?;
+            images.push((name, image));
+        }
+
+        let cell_width = images.iter().map(|(_, (width, _, _))| *width).max().unwrap_or_default();
+        let cell_height = images.iter().map(|(_, (_, height, _))| *height).max().unwrap_or_default();
+        let columns = (images.len() as f64).sqrt().ceil() as u32;
+        let rows = (images.len() as u32).div_ceil(columns);
+        let (width, height) = (cell_width * columns, cell_height * rows);
+
+        let mut rgba = vec![0u8; width as usize * height as usize * 4];
+        let mut tiles = vec![];
+        for (index, (name, (tile_width, tile_height, pixels))) in images.into_iter().enumerate() {
+            let x = index as u32 % columns * cell_width;
+            let y = index as u32 / columns * cell_height;
+            let row_bytes = tile_width as usize * 4;
+            for (row, line) in pixels.chunks(row_bytes).enumerate() {
+                let start = ((y as usize + row) * width as usize + x as usize) * 4;
+                rgba[start..start + row_bytes].copy_from_slice(line);
+            }
+            tiles.push(AtlasTile { name: name.clone(), index, x, y, width: tile_width, height: tile_height });
+        }
+        Ok(TextureAtlas { width, height, columns, rgba, tiles })
+    }
+}
+
+/// The number a frame file name ends in, e.g. 10 for `frame_10.png`, so frames sort numerically.
+fn frame_number(path: &Path) -> Option<u64> {
+    let stem = path.file_stem()?.to_str()?;
+    let digits = stem.len() - stem.trim_end_matches(|c: char| c.is_ascii_digit()).len();
+    stem[stem.len() - digits..].parse().ok()
+}
+
+/// Animation frames stacked top to bottom into one strip, the layout `flipbook_textures.json`
+/// expects.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Flipbook {
+    /// The width and height of one frame.
+    pub size: u32,
+    pub frames: u32,
+    /// 8-bit RGBA pixels of the whole strip, row by row.
+    pub rgba: Vec<u8>
+}
+
+impl Flipbook {
+    /// Stacks the PNGs at `paths` in the order given. Every frame has to be square and the same size.
+    pub fn from_frames(paths: &[PathBuf]) -> AddonResult<Flipbook> {
+        let mut flipbook = Flipbook { size: 0, frames: 0, rgba: vec![] };
+        for path in paths {
+            let bytes = fs::read(path).map_err(|error| AddonError::io(path, error))?;
+            let (width, height, rgba) = decode_png(&bytes).map_err(|error| AddonError::semantic(path, error))?;
+            if width != height {
+                return Err(AddonError::semantic(path, format!("frame is {}x{}, not square", width, height)));
+            }
+            if flipbook.frames > 0 && width != flipbook.size {
+                return Err(AddonError::semantic(path, format!("frame is {0}x{0}, unlike the {1}x{1} frames before it", width, flipbook.size)));
+            }
+            flipbook.size = width;
+            flipbook.frames += 1;
+            flipbook.rgba.extend(rgba);
+        }
+        if flipbook.frames == 0 {
+            return Err(AddonError::semantic("flipbook", "no frames to stack"));
+        }
+        Ok(flipbook)
+    }
+
+    /// Stacks every PNG in `dir`, ordered by the number their names end in (`frame_2.png` before
+    /// `frame_10.png`) and then by name.
+    pub fn from_directory(dir: &Path) -> AddonResult<Flipbook> {
+        let mut paths: Vec<PathBuf> = pack_files(dir, "").into_iter().filter(|path| path.extension().is_some_and(|ext| ext == "png")).collect();
+        paths.sort_by(|a, b| frame_number(a).cmp(&frame_number(b)).then_with(|| a.cmp(b)));
+        Flipbook::from_frames(&paths)
+    }
+
+    pub fn png(&self) -> Vec<u8> {
+        encode_png(self.size, self.size * self.frames, &self.rgba)
+    }
+
+    /// The `flipbook_textures.json` entry animating the atlas tile `atlas_tile` with the strip at
+    /// `path` (relative to the pack and without extension), showing every frame in order.
+    pub fn entry(&self, path: &str, atlas_tile: &str, ticks_per_frame: u32) -> Value {
+        json!({
+            "flipbook_texture": path,
+            "atlas_tile": atlas_tile,
+            "ticks_per_frame": ticks_per_frame,
+            "frames": (0..self.frames).collect::<Vec<_>>()
+        })
+    }
+
+    /// Writes the strip to `path` in `resource_pack` and its [entry](Flipbook::entry) to the pack's
+    /// `flipbook_textures.json`, replacing an entry for the same texture and keeping the others.
+    /// Returns the files written.
+    pub fn write(&self, resource_pack: &Path, path: &str, atlas_tile: &str, ticks_per_frame: u32) -> AddonResult<Vec<PathBuf>> {
+        let image = resource_pack.join(format!("{}.png", path));
+        if let Some(parent) = image.parent() {
+            fs::create_dir_all(parent).map_err(|error| AddonError::io(parent, error))?;
+        }
+        fs::write(&image, self.png()).map_err(|error| AddonError::io(&image, error))?;
+
+        let mut entries = match read_json_file(resource_pack, "textures/flipbook_textures.json").map(|file| file.value) {
+            Some(Value::Array(entries)) => entries,
+            _ => vec![]
+        };
+        entries.retain(|entry| entry.get("flipbook_texture").and_then(Value::as_str) != Some(path));
+        entries.push(self.entry(path, atlas_tile, ticks_per_frame));
+        let file = resource_pack.join("textures/flipbook_textures.json");
+        fs::write(&file, to_canonical_json_string(&Value::Array(entries))).map_err(|error| AddonError::io(&file, error))?;
+        Ok(vec![image, file])
+    }
+}
+
+/// Which files of a pack go into a subpack.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum SubpackRule {
+    /// Files whose path relative to the pack matches one of the globs, e.g. `textures/**/*_128x.png`.
+    Paths(Vec<String>),
+    /// PNG textures at least this many pixels wide.
+    MinWidth(u32)
+}
+
+/// A subpack for [`split_subpacks`] to create.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct SubpackSplit {
+    pub folder_name: String,
+    /// The name shown in the pack settings.
+    pub name: String,
+    pub memory_tier: i32,
+    pub rule: SubpackRule,
+    /// A marker removed from the end of the names of moved files, so `ruby_ore_128x.png` moves
+    /// to the subpack as `ruby_ore.png` and overrides that texture of the base pack.
+    pub variant_suffix: Option<String>
+}
+
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct SubpackSplitReport {
+    /// Each file moved, from and to, relative to the pack.
+    pub moved: Vec<(PathBuf, PathBuf)>,
+    /// Moved files the base pack has no file to fall back to at the same path, which the game
+    /// only shows with their subpack selected.
+    pub without_base: Vec<PathBuf>,
+    /// The subpacks added to the manifest. Subpacks it already declares aren't added again.
+    pub declared: Vec<String>
+}
+
+/// Moves the files of `pack` each split's rule selects into `subpacks/<folder_name>/`, the first
+/// matching split winning, and declares the subpacks in the manifest. The manifest, pack icon and
+/// existing subpacks are never moved.
+pub fn split_subpacks(pack: &Path, splits: &[SubpackSplit]) -> AddonResult<SubpackSplitReport> {
+    let mut manifest = EditableJson::open(&pack.join("manifest.json"))?;
+    let mut report = SubpackSplitReport::default();
+
+    for path in pack_files(pack, "") {
+        let Ok(relative) = path.strip_prefix(pack).map(Path::to_path_buf) else {
+            continue;
+        };
+        let name = relative.to_string_lossy().replace('\\', "/");
+        if name == "manifest.json" || name == "pack_icon.png" || name.starts_with("subpacks/") {
+            continue;
+        }
+        let split = splits.iter().find(|split| match &split.rule {
+            SubpackRule::Paths(globs) => globs.iter().any(|glob| glob_match(glob, &name)),
+            SubpackRule::MinWidth(width) => path.extension().is_some_and(|ext| ext == "png")
+                && fs::read(&path).ok().and_then(|bytes| png_size(&bytes)).is_some_and(|size| size.0 >= *width)
+        });
+        let Some(split) = split else {
+            continue;
+        };
+
+        let mut target = relative.clone();
+        if let Some(suffix) = &split.variant_suffix {
+            let stem = relative.file_stem().unwrap_or_default().to_string_lossy();
+            if let Some(base) = stem.strip_suffix(suffix.as_str()).filter(|base| !base.is_empty()) {
+                let extension = relative.extension().map(|ext| format!(".{}", ext.to_string_lossy())).unwrap_or_default();
+                target = relative.with_file_name(format!("{}{}", base, extension));
+            }
+        }
+        let destination = Path::new("subpacks").join(&split.folder_name).join(&target);
+        let absolute = pack.join(&destination);
+        if absolute.exists() {
+            return Err(AddonError::semantic(&absolute, "already exists"));
+        }
+        if let Some(parent) = absolute.parent() {
+            fs::create_dir_all(parent).map_err(|error| AddonError::io(parent, error))?;
+        }
+        fs::rename(&path, &absolute).map_err(|error| AddonError::io(&path, error))?;
+        if !pack.join(&target).is_file() {
+            report.without_base.push(destination.clone());
+        }
+        report.moved.push((relative, destination));
+    }
+    // A moved file's base may be moved after it was checked.
+    report.without_base.retain(|destination| {
+        let target: PathBuf = destination.components().skip(2).collect();
+        !pack.join(target).is_file()
+    });
+
+    if !manifest.value.get("subpacks").is_some_and(Value::is_array) {
+        manifest.value["subpacks"] = json!([]);
+    }
+    if let Some(Value::Array(subpacks)) = manifest.value.get_mut("subpacks") {
+        for split in splits {
+            if subpacks.iter().any(|subpack| subpack.get("folder_name").and_then(Value::as_str) == Some(split.folder_name.as_str())) {
+                continue;
+            }
+            subpacks.push(json!({ "folder_name": split.folder_name, "name": split.name, "memory_tier": split.memory_tier }));
+            report.declared.push(split.folder_name.clone());
+        }
+    }
+    manifest.save()?;
+    Ok(report)
+}
@@ -0,0 +1,119 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt::Write;
+use serde::Serialize;
+use serde_json::Value;
+use crate::addon::Addon;
+use crate::query::definitions;
+
+/// The TypeScript type of an entity property.
+#[derive(Serialize, Clone, Debug, Eq, PartialEq)]
+#[serde(tag = "type", content = "values", rename_all = "lowercase")]
+pub enum PropertyType {
+    Int,
+    Float,
+    Bool,
+    Enum(Vec<String>)
+}
+
+impl PropertyType {
+    fn typescript(&self) -> String {
+        match self {
+            PropertyType::Int | PropertyType::Float => "number".to_string(),
+            PropertyType::Bool => "boolean".to_string(),
+            PropertyType::Enum(values) => union(values.iter())
+        }
+    }
+}
+
+#[derive(Serialize, Clone, Debug, Default, Eq, PartialEq)]
+pub struct EntityConstants {
+    pub events: BTreeSet<String>,
+    pub properties: BTreeMap<String, PropertyType>
+}
+
+/// The identifiers a behavior pack defines, for scripts to refer to with autocomplete. Serializes
+/// to a JSON bridge file; [`to_typescript`](ScriptConstants::to_typescript) renders `.d.ts` stubs.
+#[derive(Serialize, Clone, Debug, Default, Eq, PartialEq)]
+pub struct ScriptConstants {
+    pub entities: BTreeMap<String, EntityConstants>,
+    pub items: BTreeSet<String>,
+    pub blocks: BTreeSet<String>
+}
+
+fn union<'a>(values: impl Iterator<Item = &'a String>) -> String {
+    let values: Vec<String> = values.map(|value| Value::String(value.clone()).to_string()).collect();
+    if values.is_empty() {
+        "never".to_string()
+    } else {
+        values.join(" | ")
+    }
+}
+
+fn identifiers(addon: &Addon, folder: &str, key: &str) -> BTreeSet<String> {
+    definitions(addon, folder, |found| found == key).into_iter()
+        .filter_map(|(_, definition)| Some(definition.pointer("/description/identifier")?.as_str()?.to_string()))
+        .collect()
+}
+
+fn property_type(property: &Value) -> Option<PropertyType> {
+    match property.get("type")?.as_str()? {
+        "int" => Some(PropertyType::Int),
+        "float" => Some(PropertyType::Float),
+        "bool" => Some(PropertyType::Bool),
+        "enum" => {
+            let values = property.get("values")?.as_array()?.iter().filter_map(Value::as_str).map(str::to_string).collect();
+            Some(PropertyType::Enum(values))
+        }
+        _ => None
+    }
+}
+
+impl ScriptConstants {
+    /// Collects entity identifiers with their events and properties, and item and block
+    /// identifiers, from the behavior pack.
+    pub fn collect(addon: &Addon) -> ScriptConstants {
+        let mut entities = BTreeMap::new();
+        for (_, entity) in definitions(addon, "entities", |key| key == "minecraft:entity") {
+            let Some(identifier) = entity.pointer("/description/identifier").and_then(Value::as_str) else {
+                continue;
+            };
+            let constants: &mut EntityConstants = entities.entry(identifier.to_string()).or_default();
+            constants.events.extend(entity.get("events").and_then(Value::as_object).into_iter().flatten().map(|(name, _)| name.clone()));
+            for (name, property) in entity.pointer("/description/properties").and_then(Value::as_object).into_iter().flatten() {
+                if let Some(property) = property_type(property) {
+                    constants.properties.insert(name.clone(), property);
+                }
+            }
+        }
+
+        ScriptConstants {
+            entities,
+            items: identifiers(addon, "items", "minecraft:item"),
+            blocks: identifiers(addon, "blocks", "minecraft:block")
+        }
+    }
+
+    /// Renders the constants as string literal types, with an interface per lookup from entity
+    /// to its events and properties.
+    pub fn to_typescript(&self) -> String {
+        let mut out = String::from("// Generated from the behavior pack; changes will be overwritten.\n\n");
+        let _ = writeln!(out, "export type EntityId = {};", union(self.entities.keys()));
+        let _ = writeln!(out, "export type ItemId = {};", union(self.items.iter()));
+        let _ = writeln!(out, "export type BlockId = {};", union(self.blocks.iter()));
+
+        out.push_str("\nexport interface EntityEvents {\n");
+        for (identifier, entity) in &self.entities {
+            let _ = writeln!(out, "  {}: {};", Value::String(identifier.clone()), union(entity.events.iter()));
+        }
+        out.push_str("}\n\nexport interface EntityProperties {\n");
+        for (identifier, entity) in &self.entities {
+            let properties: Vec<String> = entity.properties.iter()
+                .map(|(name, property)| format!("{}: {}", Value::String(name.clone()), property.typescript()))
+                .collect();
+            let properties = if properties.is_empty() { "{}".to_string() } else { format!("{{ {} }}", properties.join("; ")) };
+            let _ = writeln!(out, "  {}: {};", Value::String(identifier.clone()), properties);
+        }
+        out.push_str("}\n");
+        out
+    }
+}
@@ -1,6 +1,17 @@
-use serde::{Deserialize, Deserializer};
+#[cfg(feature = "jsonc")]
+use std::borrow::Cow;
+use std::cmp::Ordering;
+use std::collections::hash_map::RandomState;
+use std::fmt::{Display, Formatter};
+use std::hash::{BuildHasher, Hasher};
+use std::sync::atomic::{AtomicU64, Ordering as AtomicOrdering};
+use std::time::{SystemTime, UNIX_EPOCH};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use serde_json::Value;
+use uuid::{Builder, Uuid};
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Eq, PartialEq, Hash)]
 pub struct SemVer {
     pub major: i32,
     pub minor: i32,
@@ -8,24 +19,287 @@ pub struct SemVer {
     pub beta: bool
 }
 
+impl SemVer {
+    pub fn new(major: i32, minor: i32, patch: i32) -> SemVer {
+        SemVer {
+            major, minor, patch, beta: false
+        }
+    }
+
+    /// The next version at `level`, with the parts below it reset: 1.2.3 bumped at
+    /// [`Level::Minor`] is 1.3.0. The result is never a beta.
+    pub fn bump(&self, level: Level) -> SemVer {
+        match level {
+            Level::Major => SemVer::new(self.major + 1, 0, 0),
+            Level::Minor => SemVer::new(self.major, self.minor + 1, 0),
+            Level::Patch => SemVer::new(self.major, self.minor, self.patch + 1)
+        }
+    }
+}
+
+/// Which part of a [`SemVer`] a release increments.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+pub enum Level {
+    Major,
+    Minor,
+    Patch
+}
+
+impl Display for SemVer {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}.{}.{}", self.major, self.minor, self.patch)?;
+        if self.beta {
+            write!(f, "-beta")?;
+        }
+        Ok(())
+    }
+}
+
+impl Ord for SemVer {
+    fn cmp(&self, other: &Self) -> Ordering {
+        (self.major, self.minor, self.patch).cmp(&(other.major, other.minor, other.patch))
+            // A beta sorts before the release with the same number.
+            .then(other.beta.cmp(&self.beta))
+    }
+}
+
+impl PartialOrd for SemVer {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
 pub fn parse_semver_from_str(src: &str) -> SemVer {
-    let mut beta = src.contains("-beta");
-    let mut new_src = src.replace("-beta", "");
+    try_parse_semver_from_str(src).expect("Couldn't parse SemVer")
+}
+
+/// Like [`parse_semver_from_str`], but returns `None` instead of panicking.
+pub fn try_parse_semver_from_str(src: &str) -> Option<SemVer> {
+    let beta = src.contains("-beta");
+    let new_src = src.replace("-beta", "");
     let split_str: Vec<&str> = new_src.split(".").collect();
-    let mut major = split_str[0].parse::<i32>().expect("Couldn't parse SemVer");
-    let mut minor = split_str[1].parse::<i32>().expect("Couldn't parse SemVer");
-    let mut patch = split_str[2].parse::<i32>().expect("Couldn't parse SemVer");
+    let major = split_str.first()?.parse::<i32>().ok()?;
+    let minor = split_str.get(1)?.parse::<i32>().ok()?;
+    let patch = split_str.get(2)?.parse::<i32>().ok()?;
 
-    SemVer {
+    Some(SemVer {
         major, minor, patch, beta
+    })
+}
+
+/// Parses a definition file `format_version` such as `1.20.10`, `1.8` or `1.16.100.1`, which
+/// unlike pack versions don't always have exactly three components. Extra components are ignored.
+pub fn parse_format_version_from_str(src: &str) -> Option<SemVer> {
+    let parts = src.trim().split('.').map(|part| part.parse::<i32>().ok()).collect::<Option<Vec<i32>>>()?;
+    match parts.as_slice() {
+        [major, minor] => Some(SemVer::new(*major, *minor, 0)),
+        [major, minor, patch, ..] => Some(SemVer::new(*major, *minor, *patch)),
+        _ => None
     }
 }
 
 pub fn parse_semver_from_vec(src: Vec<i32>) -> SemVer {
-    SemVer {
-        major: src[0],
-        minor: src[1],
-        patch: src[2],
-        beta: false
+    try_parse_semver_from_vec(&src).expect("Couldn't parse SemVer")
+}
+
+/// Like [`parse_semver_from_vec`], but returns `None` instead of panicking.
+pub fn try_parse_semver_from_vec(src: &[i32]) -> Option<SemVer> {
+    match src {
+        [major, minor, patch, ..] => Some(SemVer::new(*major, *minor, *patch)),
+        _ => None
+    }
+}
+
+#[cfg(any(feature = "archive", feature = "scaffold", feature = "textures"))]
+const CRC32_TABLE: [u32; 256] = {
+    let mut table = [0u32; 256];
+    let mut i = 0;
+    while i < 256 {
+        let mut crc = i as u32;
+        let mut bit = 0;
+        while bit < 8 {
+            crc = if crc & 1 != 0 { (crc >> 1) ^ 0xEDB8_8320 } else { crc >> 1 };
+            bit += 1;
+        }
+        table[i] = crc;
+        i += 1;
+    }
+    table
+};
+
+/// Continues a CRC-32 (as used by zip and PNG) over `bytes`. Start from `!0` and invert the
+/// result.
+#[cfg(any(feature = "archive", feature = "scaffold", feature = "textures"))]
+pub(crate) fn crc32_update(crc: u32, bytes: &[u8]) -> u32 {
+    bytes.iter().fold(crc, |crc, byte| CRC32_TABLE[((crc ^ *byte as u32) & 0xFF) as usize] ^ (crc >> 8))
+}
+
+/// A random (version 4) uuid, for new packs and modules. The randomness comes from the standard
+/// library's hash seeds: plenty to keep pack uuids apart, but not cryptographically secure.
+pub fn new_uuid() -> Uuid {
+    static COUNTER: AtomicU64 = AtomicU64::new(0);
+    let time = SystemTime::now().duration_since(UNIX_EPOCH).map(|time| time.as_nanos()).unwrap_or_default();
+    let mut bytes = [0u8; 16];
+    for chunk in bytes.chunks_mut(8) {
+        let mut hasher = RandomState::new().build_hasher();
+        hasher.write_u64(COUNTER.fetch_add(1, AtomicOrdering::Relaxed));
+        hasher.write_u128(time);
+        chunk.copy_from_slice(&hasher.finish().to_le_bytes());
+    }
+    Builder::from_random_bytes(bytes).into_uuid()
+}
+
+/// Builds a JSON pointer from unescaped path segments, e.g. `["animations", "a/b"]` -> `/animations/a~1b`.
+pub fn json_pointer(segments: &[&str]) -> String {
+    segments.iter()
+        .map(|segment| format!("/{}", segment.replace('~', "~0").replace('/', "~1")))
+        .collect()
+}
+
+/// Copies `value` with the keys of every object in sorted order, even when serde_json is built with
+/// `preserve_order`.
+fn sort_keys(value: &Value) -> Value {
+    match value {
+        Value::Array(values) => Value::Array(values.iter().map(sort_keys).collect()),
+        Value::Object(map) => {
+            let mut entries: Vec<(&String, &Value)> = map.iter().collect();
+            entries.sort_by(|a, b| a.0.cmp(b.0));
+            Value::Object(entries.into_iter().map(|(key, value)| (key.clone(), sort_keys(value))).collect())
+        }
+        _ => value.clone()
+    }
+}
+
+/// How a JSON file is laid out. Object keys are always written sorted.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct JsonStyle {
+    /// The indentation of one nesting level, or `None` for everything on one line.
+    pub indent: Option<String>,
+    pub trailing_newline: bool
+}
+
+impl Default for JsonStyle {
+    fn default() -> JsonStyle {
+        JsonStyle::pretty(2)
+    }
+}
+
+impl JsonStyle {
+    pub fn minified() -> JsonStyle {
+        JsonStyle { indent: None, trailing_newline: false }
+    }
+
+    /// Indented by `spaces` spaces per level.
+    pub fn pretty(spaces: usize) -> JsonStyle {
+        JsonStyle { indent: Some(" ".repeat(spaces)), trailing_newline: true }
+    }
+
+    /// The style `src` is written in, judged by its first indented line.
+    pub fn detect(src: &str) -> JsonStyle {
+        let trailing_newline = src.ends_with('\n');
+        let indent = src.trim().lines().skip(1)
+            .map(|line| &line[..line.len() - line.trim_start().len()])
+            .find(|indent| !indent.is_empty());
+        match indent {
+            Some(indent) => JsonStyle { indent: Some(indent.to_string()), trailing_newline },
+            None if src.trim().contains('\n') => JsonStyle { indent: Some(String::new()), trailing_newline },
+            None => JsonStyle { indent: None, trailing_newline }
+        }
+    }
+
+    pub fn write(&self, value: &Value) -> String {
+        let value = sort_keys(value);
+        let mut out = match &self.indent {
+            None => serde_json::to_string(&value).unwrap_or_default(),
+            Some(indent) => {
+                let mut bytes = vec![];
+                let formatter = serde_json::ser::PrettyFormatter::with_indent(indent.as_bytes());
+                let mut serializer = serde_json::Serializer::with_formatter(&mut bytes, formatter);
+                let _ = value.serialize(&mut serializer);
+                String::from_utf8(bytes).unwrap_or_default()
+            }
+        };
+        if self.trailing_newline {
+            out.push('\n');
+        }
+        out
+    }
+}
+
+/// Pretty-prints `value` the way every file the crate writes is formatted: two-space indentation,
+/// object keys sorted, arrays in their given order and a trailing newline. The same document
+/// always produces the same bytes, so generated packs diff and hash cleanly.
+pub fn to_canonical_json_string(value: &Value) -> String {
+    JsonStyle::default().write(value)
+}
+
+/// Blanks out `//` and `/* */` comments and trailing commas outside strings, the JSONC Bedrock
+/// accepts in pack files. Everything removed becomes spaces, keeping line breaks, so errors still
+/// point at the right line and column of the source.
+#[cfg(feature = "jsonc")]
+pub fn strip_jsonc(src: &str) -> Cow<'_, str> {
+    if !src.contains('/') && !src.contains(',') {
+        return Cow::Borrowed(src);
     }
-}
\ No newline at end of file
+
+    let mut out: Vec<u8> = src.as_bytes().to_vec();
+    let mut changed = false;
+    let mut i = 0;
+    // Where the last comma outside a string is, until something other than whitespace follows it.
+    let mut pending_comma: Option<usize> = None;
+    while i < out.len() {
+        match out[i] {
+            b'"' => {
+                pending_comma = None;
+                i += 1;
+                while i < out.len() && out[i] != b'"' {
+                    i += if out[i] == b'\\' { 2 } else { 1 };
+                }
+            }
+            b'/' if out.get(i + 1) == Some(&b'/') => {
+                while i < out.len() && out[i] != b'\n' {
+                    out[i] = b' ';
+                    i += 1;
+                }
+                changed = true;
+                continue;
+            }
+            b'/' if out.get(i + 1) == Some(&b'*') => {
+                let end = src[i + 2..].find("*/").map(|end| i + 2 + end + 2).unwrap_or(out.len());
+                for byte in &mut out[i..end] {
+                    if *byte != b'\n' {
+                        *byte = b' ';
+                    }
+                }
+                changed = true;
+                i = end;
+                continue;
+            }
+            b',' => pending_comma = Some(i),
+            b'}' | b']' => {
+                if let Some(comma) = pending_comma.take() {
+                    out[comma] = b' ';
+                    changed = true;
+                }
+            }
+            byte if byte.is_ascii_whitespace() => {}
+            _ => pending_comma = None
+        }
+        i += 1;
+    }
+
+    if !changed {
+        return Cow::Borrowed(src);
+    }
+    // Only ASCII bytes outside strings were replaced, by ASCII spaces, so the result is still UTF-8.
+    Cow::Owned(String::from_utf8(out).unwrap_or_else(|_| src.to_string()))
+}
+
+/// Parses pack JSON. With the `jsonc` feature, comments and trailing commas are accepted like the
+/// game does; without it, parsing is strict.
+pub fn from_json_str<T: DeserializeOwned>(src: &str) -> serde_json::Result<T> {
+    #[cfg(feature = "jsonc")]
+    return serde_json::from_str(&strip_jsonc(src));
+    #[cfg(not(feature = "jsonc"))]
+    serde_json::from_str(src)
+}
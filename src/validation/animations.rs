@@ -0,0 +1,77 @@
+use std::collections::HashSet;
+use std::path::Path;
+use serde_json::Value;
+use crate::utils::json_pointer;
+use crate::validation::{read_json_files, string_entries, JsonFile, ReferenceKind, UnresolvedReference};
+
+/// Every animation and animation controller identifier defined by the given resource packs.
+pub fn animation_identifiers(resource_packs: &[&Path]) -> HashSet<String> {
+    let mut identifiers = HashSet::new();
+    for root in resource_packs {
+        for (dir, key) in [("animations", "animations"), ("animation_controllers", "animation_controllers")] {
+            for file in read_json_files(root, dir) {
+                if let Some(Value::Object(definitions)) = file.value.get(key) {
+                    identifiers.extend(definitions.keys().cloned());
+                }
+            }
+        }
+    }
+    identifiers
+}
+
+fn check_description(file: &JsonFile, root_key: &str, identifiers: &HashSet<String>, unresolved: &mut Vec<UnresolvedReference>) {
+    let pointer = json_pointer(&[root_key, "description", "animations"]);
+    let shortnames = string_entries(&file.value, &pointer);
+
+    for (shortname, identifier) in &shortnames {
+        if !identifiers.contains(*identifier) {
+            unresolved.push(UnresolvedReference {
+                kind: ReferenceKind::Animation,
+                file: file.path.clone(),
+                pointer: format!("{}{}", pointer, json_pointer(&[shortname])),
+                reference: identifier.to_string()
+            });
+        }
+    }
+
+    let animate_pointer = json_pointer(&[root_key, "description", "scripts", "animate"]);
+    let Some(Value::Array(animate)) = file.value.pointer(&animate_pointer) else {
+        return;
+    };
+
+    for (i, entry) in animate.iter().enumerate() {
+        // Entries are either plain shortnames or `{ "shortname": "condition" }` objects.
+        let (pointer, name) = match entry {
+            Value::String(name) => (format!("{}/{}", animate_pointer, i), name),
+            Value::Object(map) => match map.keys().next() {
+                Some(name) => (format!("{}/{}{}", animate_pointer, i, json_pointer(&[name])), name),
+                None => continue
+            },
+            _ => continue
+        };
+
+        if !shortnames.iter().any(|(shortname, _)| shortname == name) {
+            unresolved.push(UnresolvedReference { kind: ReferenceKind::AnimationShortname, file: file.path.clone(), pointer, reference: name.clone() });
+        }
+    }
+}
+
+/// Checks that the animation and controller identifiers client entities and attachables map in
+/// `description.animations` are defined, and that every name in `scripts.animate` is declared
+/// there. Either mistake leaves the model in its rest pose in game.
+pub fn validate_animation_references(resource_pack: &Path, vanilla_resource_pack: Option<&Path>) -> Vec<UnresolvedReference> {
+    let mut roots = vec![resource_pack];
+    roots.extend(vanilla_resource_pack);
+
+    let identifiers = animation_identifiers(&roots);
+    let mut unresolved = vec![];
+
+    for file in read_json_files(resource_pack, "entity") {
+        check_description(&file, "minecraft:client_entity", &identifiers, &mut unresolved);
+    }
+    for file in read_json_files(resource_pack, "attachables") {
+        check_description(&file, "minecraft:attachable", &identifiers, &mut unresolved);
+    }
+
+    unresolved
+}
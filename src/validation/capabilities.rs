@@ -0,0 +1,143 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use serde_json::Value;
+use crate::error::{AddonError, AddonResult};
+use crate::files::EditableJson;
+use crate::generics::manifest::{ManifestCapability, ManifestDependency, ScriptManifestDependency};
+use crate::utils::json_pointer;
+use crate::validation::{pack_files, read_json_files, read_manifest};
+
+/// Prefixes of the Education Edition chemistry items and blocks.
+const CHEMISTRY_ITEMS: [&str; 9] = [
+    "minecraft:element_", "minecraft:compound", "minecraft:chemistry_table", "minecraft:lab_table",
+    "minecraft:material_reducer", "minecraft:element_constructor", "minecraft:compound_creator",
+    "minecraft:chemical_heat", "minecraft:underwater_torch"
+];
+
+/// A capability the pack's content needs, with the first file that needs it.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct CapabilityEvidence {
+    pub capability: ManifestCapability,
+    /// Relative to the pack root.
+    pub file: PathBuf
+}
+
+/// A chemistry item or block the content of a pack refers to.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ChemistryReference {
+    pub identifier: String,
+    /// Relative to the pack root.
+    pub file: PathBuf,
+    pub pointer: String
+}
+
+fn find_chemistry(value: &Value, pointer: String, found: &mut Vec<(String, String)>) {
+    match value {
+        Value::String(string) if CHEMISTRY_ITEMS.iter().any(|prefix| string.starts_with(prefix)) => found.push((pointer, string.clone())),
+        Value::Array(values) => {
+            for (i, value) in values.iter().enumerate() {
+                find_chemistry(value, format!("{}/{}", pointer, i), found);
+            }
+        }
+        Value::Object(map) => {
+            for (key, value) in map {
+                find_chemistry(value, format!("{}{}", pointer, json_pointer(&[key])), found);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Every string value in the JSON files of `pack`, besides its manifest, naming an Education
+/// Edition chemistry item or block, in file order.
+pub fn chemistry_references(pack: &Path) -> Vec<ChemistryReference> {
+    let mut references = vec![];
+    for file in read_json_files(pack, "") {
+        if file.path == Path::new("manifest.json") {
+            continue;
+        }
+        let mut found = vec![];
+        find_chemistry(&file.value, String::new(), &mut found);
+        references.extend(found.into_iter().map(|(pointer, identifier)| ChemistryReference { identifier, file: file.path.clone(), pointer }));
+    }
+    references
+}
+
+/// The chemistry references of `pack` when its manifest doesn't declare the `chemistry`
+/// capability. Without it the game doesn't enable chemistry for the pack, so recipes, loot and
+/// spawn rules naming these items silently do nothing outside worlds with education features on.
+pub fn validate_education_features(pack: &Path) -> Vec<ChemistryReference> {
+    let declared = read_manifest(pack).is_some_and(|manifest| manifest.capabilities.contains(&ManifestCapability::Chemistry));
+    if declared {
+        return vec![];
+    }
+    chemistry_references(pack)
+}
+
+fn relative(pack: &Path, path: &Path) -> PathBuf {
+    path.strip_prefix(pack).unwrap_or(path).to_path_buf()
+}
+
+/// Infers the manifest capabilities the content of `pack` relies on: texture sets need `pbr`,
+/// the editor script module `editorExtension`, chemistry items `chemistry` and `eval` in scripts
+/// `script_eval`.
+pub fn infer_capabilities(pack: &Path) -> Vec<CapabilityEvidence> {
+    let mut evidence = vec![];
+
+    let texture_set = pack_files(pack, "textures").into_iter()
+        .find(|path| path.to_string_lossy().ends_with(".texture_set.json"));
+    if let Some(path) = texture_set {
+        evidence.push(CapabilityEvidence { capability: ManifestCapability::PBR, file: relative(pack, &path) });
+    }
+
+    let editor = read_manifest(pack).is_some_and(|manifest| manifest.dependencies.iter().any(|dependency| {
+        matches!(dependency, ManifestDependency::ScriptDependency(ScriptManifestDependency::MinecraftServerEditor, _))
+    }));
+    if editor {
+        evidence.push(CapabilityEvidence { capability: ManifestCapability::EditorExtension, file: PathBuf::from("manifest.json") });
+    }
+
+    if let Some(reference) = chemistry_references(pack).into_iter().next() {
+        evidence.push(CapabilityEvidence { capability: ManifestCapability::Chemistry, file: reference.file });
+    }
+
+    let eval = pack_files(pack, "scripts").into_iter()
+        .filter(|path| path.extension().is_some_and(|ext| ext == "js"))
+        .find(|path| fs::read_to_string(path).is_ok_and(|src| src.contains("eval(") || src.contains("new Function(")));
+    if let Some(path) = eval {
+        evidence.push(CapabilityEvidence { capability: ManifestCapability::ScriptEval, file: relative(pack, &path) });
+    }
+
+    evidence
+}
+
+/// The inferred capabilities the manifest doesn't declare yet.
+pub fn missing_capabilities(pack: &Path) -> Vec<CapabilityEvidence> {
+    let declared = read_manifest(pack).map(|manifest| manifest.capabilities).unwrap_or_default();
+    infer_capabilities(pack).into_iter()
+        .filter(|evidence| !declared.contains(&evidence.capability))
+        .collect()
+}
+
+/// Adds the [missing capabilities](missing_capabilities) to the pack's `manifest.json` and returns
+/// them. The manifest keeps its indentation, but its keys are sorted.
+pub fn add_missing_capabilities(pack: &Path) -> AddonResult<Vec<ManifestCapability>> {
+    let missing: Vec<ManifestCapability> = missing_capabilities(pack).into_iter().map(|evidence| evidence.capability).collect();
+    if missing.is_empty() {
+        return Ok(missing);
+    }
+
+    let path = pack.join("manifest.json");
+    let mut manifest = EditableJson::open(&path)?;
+    let Some(object) = manifest.value.as_object_mut() else {
+        return Err(AddonError::semantic(&path, "manifest is not an object"));
+    };
+    let capabilities = object.entry("capabilities").or_insert_with(|| Value::Array(vec![]));
+    let Some(capabilities) = capabilities.as_array_mut() else {
+        return Err(AddonError::semantic(&path, "capabilities is not an array"));
+    };
+    capabilities.extend(missing.iter().map(|capability| Value::String(capability.name().to_string())));
+
+    manifest.save()?;
+    Ok(missing)
+}
@@ -0,0 +1,101 @@
+use std::path::{Path, PathBuf};
+use serde_json::Value;
+use crate::utils::{json_pointer, SemVer};
+use crate::validation::{read_json_files, read_manifest};
+
+/// A component that stopped being supported by definitions of `definition` as of `since`.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct DeprecationRule {
+    /// Top-level key of the definition, e.g. `minecraft:block`.
+    pub definition: &'static str,
+    pub component: &'static str,
+    pub since: (i32, i32, i32),
+    pub replacement: Option<&'static str>
+}
+
+/// Component renames from the stabilization of block and item components. Not exhaustive;
+/// callers can extend it with their own rules.
+pub const DEFAULT_DEPRECATIONS: [DeprecationRule; 9] = [
+    DeprecationRule { definition: "minecraft:block", component: "minecraft:destroy_time", since: (1, 19, 20), replacement: Some("minecraft:destructible_by_mining") },
+    DeprecationRule { definition: "minecraft:block", component: "minecraft:explosion_resistance", since: (1, 19, 20), replacement: Some("minecraft:destructible_by_explosion") },
+    DeprecationRule { definition: "minecraft:block", component: "minecraft:block_light_emission", since: (1, 19, 40), replacement: Some("minecraft:light_emission") },
+    DeprecationRule { definition: "minecraft:block", component: "minecraft:block_light_filter", since: (1, 19, 40), replacement: Some("minecraft:light_dampening") },
+    DeprecationRule { definition: "minecraft:block", component: "minecraft:block_light_absorption", since: (1, 19, 40), replacement: Some("minecraft:light_dampening") },
+    DeprecationRule { definition: "minecraft:block", component: "minecraft:aim_collision", since: (1, 19, 50), replacement: Some("minecraft:selection_box") },
+    DeprecationRule { definition: "minecraft:block", component: "minecraft:pick_collision", since: (1, 19, 50), replacement: Some("minecraft:selection_box") },
+    DeprecationRule { definition: "minecraft:block", component: "minecraft:entity_collision", since: (1, 19, 50), replacement: Some("minecraft:collision_box") },
+    DeprecationRule { definition: "minecraft:item", component: "minecraft:foil", since: (1, 20, 30), replacement: Some("minecraft:glint") },
+];
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct DeprecatedComponent {
+    /// The defining file, relative to the pack root.
+    pub file: PathBuf,
+    pub pointer: String,
+    pub component: String,
+    pub since: SemVer,
+    pub replacement: Option<String>
+}
+
+/// The component objects of a definition: its `components`, every component group and every
+/// block permutation, with their JSON pointers.
+fn component_sets<'a>(definition_key: &str, definition: &'a Value) -> Vec<(String, &'a Value)> {
+    let base = json_pointer(&[definition_key]);
+    let mut sets = vec![];
+
+    if let Some(components) = definition.get("components") {
+        sets.push((format!("{}/components", base), components));
+    }
+    if let Some(Value::Object(groups)) = definition.get("component_groups") {
+        for (name, group) in groups {
+            sets.push((format!("{}/component_groups{}", base, json_pointer(&[name])), group));
+        }
+    }
+    if let Some(Value::Array(permutations)) = definition.get("permutations") {
+        for (i, permutation) in permutations.iter().enumerate() {
+            if let Some(components) = permutation.get("components") {
+                sets.push((format!("{}/permutations/{}/components", base, i), components));
+            }
+        }
+    }
+    sets
+}
+
+/// Flags components in the behavior pack at `behavior_pack` that `rules` mark as removed at or
+/// before `engine_version`.
+pub fn lint_deprecated_components_for_version(behavior_pack: &Path, engine_version: &SemVer, rules: &[DeprecationRule]) -> Vec<DeprecatedComponent> {
+    let mut findings = vec![];
+
+    for file in read_json_files(behavior_pack, "") {
+        let Value::Object(map) = &file.value else {
+            continue;
+        };
+
+        for (definition_key, definition) in map {
+            for (pointer, components) in component_sets(definition_key, definition) {
+                for rule in rules.iter().filter(|rule| rule.definition == definition_key) {
+                    let since = SemVer::new(rule.since.0, rule.since.1, rule.since.2);
+                    if components.get(rule.component).is_some() && *engine_version >= since {
+                        findings.push(DeprecatedComponent {
+                            file: file.path.clone(),
+                            pointer: format!("{}{}", pointer, json_pointer(&[rule.component])),
+                            component: rule.component.to_string(),
+                            since,
+                            replacement: rule.replacement.map(str::to_string)
+                        });
+                    }
+                }
+            }
+        }
+    }
+
+    findings
+}
+
+/// Like [`lint_deprecated_components_for_version`], using the pack manifest's `min_engine_version`.
+pub fn lint_deprecated_components(behavior_pack: &Path, rules: &[DeprecationRule]) -> Vec<DeprecatedComponent> {
+    let Some(manifest) = read_manifest(behavior_pack) else {
+        return vec![];
+    };
+    lint_deprecated_components_for_version(behavior_pack, &manifest.header.min_engine_version, rules)
+}
@@ -0,0 +1,75 @@
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use serde_json::Value;
+use crate::generics::manifest::ManifestModule;
+use crate::utils::json_pointer;
+use crate::validation::{read_json_files, read_manifest};
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct DefinitionLocation {
+    /// Index of the defining pack in the stack passed to [`find_duplicate_identifiers`].
+    pub pack: usize,
+    /// Relative to the pack root.
+    pub file: PathBuf,
+    pub pointer: String
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct DuplicateIdentifier {
+    /// The definition's top-level key (`minecraft:entity`, `minecraft:recipe_shaped`, ...), or
+    /// `animations`/`animation_controllers`.
+    pub kind: String,
+    pub identifier: String,
+    /// Every definition, highest stack priority first.
+    pub definitions: Vec<DefinitionLocation>,
+    /// Index into `definitions` of the copy the game uses, or `None` when the highest-priority
+    /// pack defines it more than once and the result depends on load order.
+    pub winner: Option<usize>
+}
+
+fn is_resource_pack(root: &Path) -> bool {
+    read_manifest(root).is_some_and(|manifest| manifest.modules.iter().any(|module| matches!(module, ManifestModule::Resources(_, _))))
+}
+
+/// Reports identifiers defined more than once across `packs`, which are ordered like a world's
+/// pack stack: the first pack has the highest priority and overrides the ones after it.
+///
+/// Definitions are compared per kind, so a behavior entity and its client entity don't clash,
+/// and behavior and resource pack animations are kept apart.
+pub fn find_duplicate_identifiers(packs: &[&Path]) -> Vec<DuplicateIdentifier> {
+    let mut definitions: BTreeMap<(bool, String, String), Vec<DefinitionLocation>> = BTreeMap::new();
+
+    for (pack, root) in packs.iter().enumerate() {
+        let resource = is_resource_pack(root);
+        for file in read_json_files(root, "") {
+            let Value::Object(map) = &file.value else {
+                continue;
+            };
+
+            for (key, definition) in map {
+                let mut found = vec![];
+                if key == "animations" || key == "animation_controllers" {
+                    if let Value::Object(identifiers) = definition {
+                        found.extend(identifiers.keys().map(|identifier| (identifier.clone(), json_pointer(&[key, identifier]))));
+                    }
+                } else if let Some(Value::String(identifier)) = definition.pointer("/description/identifier") {
+                    found.push((identifier.clone(), json_pointer(&[key, "description", "identifier"])));
+                }
+
+                for (identifier, pointer) in found {
+                    definitions.entry((resource, key.clone(), identifier)).or_default()
+                        .push(DefinitionLocation { pack, file: file.path.clone(), pointer });
+                }
+            }
+        }
+    }
+
+    definitions.into_iter()
+        .filter(|(_, locations)| locations.len() > 1)
+        .map(|((_, kind, identifier), locations)| {
+            let top = locations[0].pack;
+            let winner = if locations.iter().filter(|location| location.pack == top).count() == 1 { Some(0) } else { None };
+            DuplicateIdentifier { kind, identifier, definitions: locations, winner }
+        })
+        .collect()
+}
@@ -0,0 +1,115 @@
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+use serde_json::Value;
+use crate::generics::client_entity::{deserialize_effect_bindings_from_value, EffectBindings};
+use crate::utils::json_pointer;
+use crate::validation::{read_json_files, string_entries, JsonFile, ReferenceKind, UnresolvedReference};
+
+/// Collects `(pointer, shortname)` for every effect in `value`, which holds one effect object or
+/// a list of them.
+fn effect_names(value: &Value, pointer: String, names: &mut Vec<(String, String)>) {
+    match value {
+        Value::Array(effects) => {
+            for (i, effect) in effects.iter().enumerate() {
+                effect_names(effect, format!("{}/{}", pointer, i), names);
+            }
+        }
+        Value::Object(effect) => {
+            if let Some(Value::String(name)) = effect.get("effect") {
+                names.push((format!("{}/effect", pointer), name.clone()));
+            }
+        }
+        _ => {}
+    }
+}
+
+/// The particle and sound effect shortnames an animation or animation controller uses, with their
+/// pointers. Animations key their effects by time; controller states list them directly.
+fn used_effects(pointer: &str, definition: &Value) -> Vec<(ReferenceKind, String, String)> {
+    let mut used = vec![];
+    let mut sections = vec![];
+    match definition.get("states") {
+        Some(Value::Object(states)) => {
+            for (state, body) in states {
+                sections.push((format!("{}{}", pointer, json_pointer(&["states", state])), body, false));
+            }
+        }
+        _ => sections.push((pointer.to_string(), definition, true))
+    }
+
+    for (pointer, body, timeline) in sections {
+        for (key, kind) in [("particle_effects", ReferenceKind::ParticleShortname), ("sound_effects", ReferenceKind::SoundShortname)] {
+            let Some(effects) = body.get(key) else {
+                continue;
+            };
+            let pointer = format!("{}{}", pointer, json_pointer(&[key]));
+            let mut names = vec![];
+            match effects {
+                Value::Object(times) if timeline => {
+                    for (time, effects) in times {
+                        effect_names(effects, format!("{}{}", pointer, json_pointer(&[time])), &mut names);
+                    }
+                }
+                effects => effect_names(effects, pointer, &mut names)
+            }
+            used.extend(names.into_iter().map(|(pointer, name)| (kind, pointer, name)));
+        }
+    }
+    used
+}
+
+fn is_declared(bindings: &EffectBindings, kind: ReferenceKind, name: &str) -> bool {
+    match kind {
+        ReferenceKind::ParticleShortname => bindings.particle(name).is_some(),
+        _ => bindings.sound(name).is_some()
+    }
+}
+
+/// Checks that every particle and sound effect shortname the animations and animation
+/// controllers of `resource_pack` use is declared in `particle_effects`, `particle_emitters` or
+/// `sound_effects` of each client entity and attachable that maps them. An undeclared shortname
+/// plays nothing in game.
+///
+/// References are reported in the animation files, once per field even if several entities miss
+/// the declaration.
+pub fn validate_effect_references(resource_pack: &Path) -> Vec<UnresolvedReference> {
+    let mut definitions: Vec<JsonFile> = vec![];
+    let mut by_identifier: HashMap<String, (usize, String)> = HashMap::new();
+    for (dir, key) in [("animations", "animations"), ("animation_controllers", "animation_controllers")] {
+        for file in read_json_files(resource_pack, dir) {
+            if let Some(Value::Object(map)) = file.value.get(key) {
+                for identifier in map.keys() {
+                    by_identifier.insert(identifier.clone(), (definitions.len(), json_pointer(&[key, identifier])));
+                }
+            }
+            definitions.push(file);
+        }
+    }
+
+    let mut unresolved = vec![];
+    let mut reported = HashSet::new();
+    let owners: Vec<(JsonFile, &str)> = read_json_files(resource_pack, "entity").into_iter().map(|file| (file, "minecraft:client_entity"))
+        .chain(read_json_files(resource_pack, "attachables").into_iter().map(|file| (file, "minecraft:attachable")))
+        .collect();
+    for (file, root_key) in &owners {
+        let Some(description) = file.value.get(*root_key).and_then(|owner| owner.get("description")) else {
+            continue;
+        };
+        let bindings = deserialize_effect_bindings_from_value(description);
+        for (_, identifier) in string_entries(description, "/animations") {
+            let Some((index, pointer)) = by_identifier.get(identifier) else {
+                continue;
+            };
+            let definition_file = &definitions[*index];
+            let Some(definition) = definition_file.value.pointer(pointer) else {
+                continue;
+            };
+            for (kind, pointer, name) in used_effects(pointer, definition) {
+                if !is_declared(&bindings, kind, &name) && reported.insert((definition_file.path.clone(), pointer.clone())) {
+                    unresolved.push(UnresolvedReference { kind, file: definition_file.path.clone(), pointer, reference: name });
+                }
+            }
+        }
+    }
+    unresolved
+}
@@ -0,0 +1,273 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use serde_json::{Map, Value};
+use crate::generics::entity::{deserialize_behavior_entity_from_value, BehaviorEntity, EntityEvent};
+use crate::utils::json_pointer;
+use crate::validation::{pack_files, read_json_files};
+
+/// Events the game fires on its own: when the entity spawns, is bred, converts from another
+/// entity or is primed to explode.
+pub const BUILTIN_EVENTS: [&str; 4] = ["minecraft:entity_spawned", "minecraft:entity_born", "minecraft:entity_transformed", "minecraft:on_prime"];
+
+/// What an event may do, over all of its `sequence` and `randomize` steps.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct EventEffects {
+    pub add: BTreeSet<String>,
+    pub remove: BTreeSet<String>,
+    /// Events of the same entity fired by `trigger`.
+    pub triggers: BTreeSet<String>
+}
+
+/// How the events of an entity change its component groups and fire each other.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct EventGraph {
+    pub events: BTreeMap<String, EventEffects>,
+    /// The events the components of each group fire on the entity itself while it is active.
+    pub group_events: BTreeMap<String, BTreeSet<String>>,
+    /// The events the always active components fire on the entity itself.
+    pub component_events: BTreeSet<String>,
+    /// Events fired on other entities, e.g. with `"target": "other"`.
+    pub external_events: BTreeSet<String>
+}
+
+fn is_self(target: Option<&Value>) -> bool {
+    target.and_then(Value::as_str).is_none_or(|target| target == "self")
+}
+
+/// Collects the events a component tree fires, as `event` and `spawn_event` fields, split by
+/// whether they target the entity itself.
+fn fired_events(value: &Value, own: &mut BTreeSet<String>, external: &mut BTreeSet<String>) {
+    match value {
+        Value::Object(map) => {
+            for (key, value) in map {
+                match (key.as_str(), value) {
+                    ("event", Value::String(event)) if is_self(map.get("target")) => {
+                        own.insert(event.clone());
+                    }
+                    ("event", Value::String(event)) => {
+                        external.insert(event.clone());
+                    }
+                    // The spawned entity receives the event.
+                    ("spawn_event", Value::String(event)) => {
+                        external.insert(event.clone());
+                    }
+                    _ => fired_events(value, own, external)
+                }
+            }
+        }
+        Value::Array(values) => {
+            for value in values {
+                fired_events(value, own, external);
+            }
+        }
+        _ => {}
+    }
+}
+
+fn collect_effects(event: &EntityEvent, effects: &mut EventEffects, external: &mut BTreeSet<String>) {
+    effects.add.extend(event.add.iter().cloned());
+    effects.remove.extend(event.remove.iter().cloned());
+    if let Some(triggered) = event.triggered_event() {
+        let target = event.trigger.as_ref().and_then(|trigger| trigger.get("target"));
+        if is_self(target) {
+            effects.triggers.insert(triggered.to_string());
+        } else {
+            external.insert(triggered.to_string());
+        }
+    }
+    for step in event.sequence.iter().chain(&event.randomize) {
+        collect_effects(step, effects, external);
+    }
+}
+
+fn components_events(components: &Map<String, Value>, external: &mut BTreeSet<String>) -> BTreeSet<String> {
+    let mut own = BTreeSet::new();
+    fired_events(&Value::Object(components.clone()), &mut own, external);
+    own
+}
+
+impl EventGraph {
+    pub fn from_entity(entity: &BehaviorEntity) -> EventGraph {
+        let mut graph = EventGraph::default();
+        graph.component_events = components_events(&entity.components, &mut graph.external_events);
+        for (name, group) in &entity.component_groups {
+            let events = components_events(group, &mut graph.external_events);
+            graph.group_events.insert(name.clone(), events);
+        }
+        for (name, event) in &entity.events {
+            let mut effects = EventEffects::default();
+            collect_effects(event, &mut effects, &mut graph.external_events);
+            graph.events.insert(name.clone(), effects);
+        }
+        graph
+    }
+
+    /// The events that can fire and the groups that can become active, starting from the built-in
+    /// events, the always active components and the events in `fired` that something outside the
+    /// entity fires on it. Filters are ignored, so every step of an event counts.
+    pub fn reachable(&self, fired: &BTreeSet<String>) -> (BTreeSet<String>, BTreeSet<String>) {
+        let mut events = BTreeSet::new();
+        let mut groups = BTreeSet::new();
+        let mut pending: Vec<String> = BUILTIN_EVENTS.iter().map(|event| event.to_string())
+            .chain(self.component_events.iter().cloned())
+            .chain(fired.iter().cloned())
+            .collect();
+        while let Some(event) = pending.pop() {
+            if !events.insert(event.clone()) {
+                continue;
+            }
+            let Some(effects) = self.events.get(&event) else {
+                continue;
+            };
+            pending.extend(effects.triggers.iter().cloned());
+            for group in &effects.add {
+                if groups.insert(group.clone()) {
+                    pending.extend(self.group_events.get(group).into_iter().flatten().cloned());
+                }
+            }
+        }
+        (events, groups)
+    }
+
+    /// The loops of events triggering each other through `trigger`, each from its
+    /// alphabetically first event. The game runs them until it gives up, adding and removing the
+    /// same groups over and over within a tick.
+    pub fn trigger_cycles(&self) -> Vec<Vec<String>> {
+        let mut cycles = vec![];
+        for start in self.events.keys() {
+            // Depth-first search over events after `start`, so each cycle is found once.
+            let mut stack: Vec<(String, Vec<String>)> = vec![(start.clone(), vec![start.clone()])];
+            while let Some((event, path)) = stack.pop() {
+                for next in self.events.get(&event).into_iter().flat_map(|effects| &effects.triggers) {
+                    if next == start {
+                        cycles.push(path.clone());
+                    } else if next > start && !path.contains(next) && self.events.contains_key(next) {
+                        let mut path = path.clone();
+                        path.push(next.clone());
+                        stack.push((next.clone(), path));
+                    }
+                }
+            }
+        }
+        cycles
+    }
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum EntityEventIssueKind {
+    /// No event that can fire adds the group, so its components never apply.
+    UnreachableGroup,
+    /// Nothing in the pack fires the event. Commands run from outside the pack still can.
+    UntriggeredEvent,
+    /// The event triggers itself through the listed events.
+    TriggerCycle { events: Vec<String> }
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct EntityEventIssue {
+    pub entity: String,
+    /// The group or event.
+    pub name: String,
+    /// Relative to the pack root.
+    pub file: PathBuf,
+    pub pointer: String,
+    pub kind: EntityEventIssueKind
+}
+
+/// The event named by an `event entity <selector> <event>` command, or the `@s <event>`
+/// shorthand of behavior animations.
+fn command_event(command: &str) -> Option<String> {
+    let command = command.trim().trim_start_matches('/');
+    let rest = match command.strip_prefix("@s ") {
+        Some(event) => return event.split_whitespace().next().map(str::to_string),
+        None => command.split_once("event entity ")?.1.trim_start()
+    };
+    let rest = match rest.find(char::is_whitespace) {
+        Some(end) if !rest[..end].contains('[') || rest[..end].contains(']') => &rest[end..],
+        _ => rest.split_once(']')?.1
+    };
+    rest.split_whitespace().next().map(str::to_string)
+}
+
+fn string_events(value: &Value, events: &mut BTreeSet<String>) {
+    match value {
+        Value::String(command) => events.extend(command_event(command)),
+        Value::Array(values) => values.iter().for_each(|value| string_events(value, events)),
+        Value::Object(map) => map.values().for_each(|value| string_events(value, events)),
+        _ => {}
+    }
+}
+
+/// The events fired from outside the entity definitions: commands in behavior animations,
+/// animation controllers and functions, and `triggerEvent` calls in scripts. Events are matched
+/// by name only, whichever entity a command selects.
+pub fn externally_fired_events(pack: &Path) -> BTreeSet<String> {
+    let mut events = BTreeSet::new();
+    for dir in ["animations", "animation_controllers"] {
+        for file in read_json_files(pack, dir) {
+            string_events(&file.value, &mut events);
+        }
+    }
+    for file in read_json_files(pack, "spawn_rules") {
+        fired_events(&file.value, &mut BTreeSet::new(), &mut events);
+    }
+    for path in pack_files(pack, "functions") {
+        if path.extension().is_some_and(|ext| ext == "mcfunction") {
+            let src = fs::read_to_string(&path).unwrap_or_default();
+            events.extend(src.lines().filter(|line| !line.trim_start().starts_with('#')).filter_map(command_event));
+        }
+    }
+    for path in pack_files(pack, "scripts") {
+        if !path.extension().is_some_and(|ext| ext == "js" || ext == "ts") {
+            continue;
+        }
+        let src = fs::read_to_string(&path).unwrap_or_default();
+        for part in src.split("triggerEvent(").skip(1) {
+            let part = part.trim_start();
+            let Some(quote) = part.chars().next().filter(|c| matches!(c, '"' | '\'' | '`')) else {
+                continue;
+            };
+            if let Some(end) = part[1..].find(quote) {
+                events.insert(part[1..end + 1].to_string());
+            }
+        }
+    }
+    events
+}
+
+/// Analyses the event graph of every entity in `behavior_pack`: component groups no firing event
+/// adds, events nothing in the pack fires and events triggering themselves in a loop. Entities
+/// and the rest of the pack firing events on other entities count for every entity with an event
+/// of that name.
+pub fn analyze_entity_events(behavior_pack: &Path) -> Vec<EntityEventIssue> {
+    let entities: Vec<(PathBuf, BehaviorEntity)> = read_json_files(behavior_pack, "entities").into_iter()
+        .filter_map(|file| Some((file.path, deserialize_behavior_entity_from_value(&file.value)?)))
+        .collect();
+    let graphs: Vec<EventGraph> = entities.iter().map(|(_, entity)| EventGraph::from_entity(entity)).collect();
+    let mut fired = externally_fired_events(behavior_pack);
+    fired.extend(graphs.iter().flat_map(|graph| graph.external_events.iter().cloned()));
+
+    let mut issues = vec![];
+    for ((file, entity), graph) in entities.iter().zip(&graphs) {
+        let issue = |name: &str, section: &str, kind| EntityEventIssue {
+            entity: entity.identifier.clone(),
+            name: name.to_string(),
+            file: file.clone(),
+            pointer: json_pointer(&["minecraft:entity", section, name]),
+            kind
+        };
+        let (events, groups) = graph.reachable(&fired);
+        for group in entity.component_groups.keys().filter(|group| !groups.contains(*group)) {
+            issues.push(issue(group, "component_groups", EntityEventIssueKind::UnreachableGroup));
+        }
+        for event in entity.events.keys().filter(|event| !events.contains(*event)) {
+            issues.push(issue(event, "events", EntityEventIssueKind::UntriggeredEvent));
+        }
+        for cycle in graph.trigger_cycles() {
+            let name = cycle[0].clone();
+            issues.push(issue(&name, "events", EntityEventIssueKind::TriggerCycle { events: cycle }));
+        }
+    }
+    issues
+}
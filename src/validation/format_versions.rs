@@ -0,0 +1,65 @@
+use std::path::{Path, PathBuf};
+use serde_json::Value;
+use crate::utils::{parse_format_version_from_str, SemVer};
+use crate::validation::{read_json_files, read_manifest};
+
+/// The newest game release this crate knows the definition formats of.
+pub const LATEST_KNOWN_RELEASE: (i32, i32, i32) = (1, 21, 100);
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum FormatVersionIssueKind {
+    /// The file needs a newer client than the pack's `min_engine_version` allows, so older
+    /// clients silently drop it.
+    NewerThanEngine { min_engine_version: SemVer },
+    /// The file claims a format no released game version has.
+    NewerThanLatest { latest: SemVer },
+    /// `format_version` isn't a version string.
+    Invalid
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct FormatVersionIssue {
+    /// Relative to the pack root.
+    pub file: PathBuf,
+    pub format_version: String,
+    pub kind: FormatVersionIssueKind
+}
+
+/// Checks each definition file's `format_version` against `min_engine_version` and `latest`.
+pub fn validate_format_versions_for_version(pack: &Path, min_engine_version: &SemVer, latest: &SemVer) -> Vec<FormatVersionIssue> {
+    let mut issues = vec![];
+
+    for file in read_json_files(pack, "") {
+        // The manifest's own `format_version` is the manifest schema version, not a game version.
+        if file.path == Path::new("manifest.json") {
+            continue;
+        }
+        let format_version = match file.value.get("format_version") {
+            Some(Value::String(version)) => version.clone(),
+            Some(Value::Number(version)) => version.to_string(),
+            _ => continue
+        };
+
+        let kind = match parse_format_version_from_str(&format_version) {
+            None => FormatVersionIssueKind::Invalid,
+            Some(version) if version > *latest => FormatVersionIssueKind::NewerThanLatest { latest: latest.clone() },
+            Some(version) if version > *min_engine_version => {
+                FormatVersionIssueKind::NewerThanEngine { min_engine_version: min_engine_version.clone() }
+            }
+            Some(_) => continue
+        };
+        issues.push(FormatVersionIssue { file: file.path, format_version, kind });
+    }
+
+    issues
+}
+
+/// Like [`validate_format_versions_for_version`], using the pack manifest's `min_engine_version`
+/// and [`LATEST_KNOWN_RELEASE`].
+pub fn validate_format_versions(pack: &Path) -> Vec<FormatVersionIssue> {
+    let Some(manifest) = read_manifest(pack) else {
+        return vec![];
+    };
+    let (major, minor, patch) = LATEST_KNOWN_RELEASE;
+    validate_format_versions_for_version(pack, &manifest.header.min_engine_version, &SemVer::new(major, minor, patch))
+}
@@ -0,0 +1,289 @@
+use std::collections::{BTreeMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use crate::validation::pack_files;
+
+/// The namespace of structures saved directly in `structures/`.
+const DEFAULT_NAMESPACE: &str = "mystructure";
+
+/// The identifier of the structure file at `path`, relative to the pack's `structures` folder:
+/// `ns/name.mcstructure` is `ns:name`, deeper folders are kept in the name and files directly in
+/// the folder are in the `mystructure` namespace.
+pub fn structure_identifier(path: &Path) -> Option<String> {
+    let relative = path.with_extension("").to_string_lossy().replace('\\', "/");
+    match relative.split_once('/') {
+        Some((namespace, name)) => Some(format!("{}:{}", namespace, name)),
+        None if !relative.is_empty() => Some(format!("{}:{}", DEFAULT_NAMESPACE, relative)),
+        None => None
+    }
+}
+
+/// Where the structure `identifier` is saved in a pack, the inverse of [`structure_identifier`].
+pub fn structure_path(identifier: &str) -> PathBuf {
+    let (namespace, name) = identifier.split_once(':').unwrap_or((DEFAULT_NAMESPACE, identifier));
+    let relative = if namespace == DEFAULT_NAMESPACE { format!("{}.mcstructure", name) } else { format!("{}/{}.mcstructure", namespace, name) };
+    Path::new("structures").join(relative)
+}
+
+/// A test registered with `register` or `registerAsync` of `@minecraft/server-gametest`.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct GameTest {
+    /// `class:name`, as `/gametest run` takes it.
+    pub name: String,
+    /// The structure the test runs in: the one given to `structureName`, or else the test's own
+    /// name. Without a namespace it is in the `mystructure` one.
+    pub structure: String,
+    /// Relative to the pack root.
+    pub file: PathBuf,
+    /// 1-based line of the `register` call.
+    pub line: usize
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum GameTestIssueKind {
+    /// The test's structure isn't in the pack, so the test fails to start.
+    MissingStructure,
+    /// Another test registered the same name first.
+    DuplicateTest { first: PathBuf, line: usize },
+    /// A structure in the folder of a test class that no test runs in.
+    UnusedStructure
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct GameTestIssue {
+    /// The test, empty for unused structures.
+    pub test: String,
+    pub structure: String,
+    /// The script, or the structure file for unused structures; relative to the pack root.
+    pub file: PathBuf,
+    /// 1-based, 0 for structure files.
+    pub line: usize,
+    pub kind: GameTestIssueKind
+}
+
+/// A cursor over script source without comments that reads string literals.
+struct Scanner<'a> {
+    src: &'a [u8],
+    pos: usize
+}
+
+impl<'a> Scanner<'a> {
+    fn skip_trivia(&mut self) {
+        while self.pos < self.src.len() && self.src[self.pos].is_ascii_whitespace() {
+            self.pos += 1;
+        }
+    }
+
+    fn eat(&mut self, byte: u8) -> bool {
+        self.skip_trivia();
+        let matched = self.src.get(self.pos) == Some(&byte);
+        if matched {
+            self.pos += 1;
+        }
+        matched
+    }
+
+    fn identifier(&mut self) -> &'a str {
+        self.skip_trivia();
+        let start = self.pos;
+        while self.pos < self.src.len() && (self.src[self.pos].is_ascii_alphanumeric() || matches!(self.src[self.pos], b'_' | b'$')) {
+            self.pos += 1;
+        }
+        std::str::from_utf8(&self.src[start..self.pos]).unwrap_or_default()
+    }
+
+    /// Reads a string literal without escapes or interpolation at the cursor.
+    fn string(&mut self) -> Option<String> {
+        self.skip_trivia();
+        let quote = *self.src.get(self.pos)?;
+        if !matches!(quote, b'"' | b'\'' | b'`') {
+            return None;
+        }
+        let end = self.src[self.pos + 1..].iter().position(|c| *c == quote || *c == b'\\' || (quote == b'`' && *c == b'$'))? + self.pos + 1;
+        if self.src[end] != quote {
+            return None;
+        }
+        let value = String::from_utf8_lossy(&self.src[self.pos + 1..end]).into_owned();
+        self.pos = end + 1;
+        Some(value)
+    }
+
+    /// Moves past the closing parenthesis of the call whose arguments start at the cursor.
+    fn skip_arguments(&mut self) {
+        let mut depth = 1;
+        while self.pos < self.src.len() && depth > 0 {
+            self.skip_trivia();
+            match self.src.get(self.pos) {
+                Some(b'"' | b'\'' | b'`') => {
+                    let quote = self.src[self.pos];
+                    self.pos += 1;
+                    while self.pos < self.src.len() && self.src[self.pos] != quote {
+                        self.pos += if self.src[self.pos] == b'\\' { 2 } else { 1 };
+                    }
+                }
+                Some(b'(' | b'[' | b'{') => depth += 1,
+                Some(b')' | b']' | b'}') => depth -= 1,
+                _ => {}
+            }
+            self.pos += 1;
+        }
+    }
+}
+
+/// `src` without its comments, keeping the line breaks in them so lines still count the same.
+fn strip_comments(src: &str) -> String {
+    let mut stripped = String::with_capacity(src.len());
+    let mut chars = src.chars().peekable();
+    let mut quote = None;
+    while let Some(c) = chars.next() {
+        match (quote, c) {
+            (Some(q), _) => {
+                stripped.push(c);
+                if c == '\\' {
+                    stripped.extend(chars.next());
+                } else if c == q {
+                    quote = None;
+                }
+            }
+            (None, '"' | '\'' | '`') => {
+                quote = Some(c);
+                stripped.push(c);
+            }
+            (None, '/') if chars.peek() == Some(&'/') => {
+                while chars.peek().is_some_and(|c| *c != '\n') {
+                    chars.next();
+                }
+            }
+            (None, '/') if chars.peek() == Some(&'*') => {
+                chars.next();
+                let mut previous = ' ';
+                for c in chars.by_ref() {
+                    if c == '\n' {
+                        stripped.push('\n');
+                    }
+                    if previous == '*' && c == '/' {
+                        break;
+                    }
+                    previous = c;
+                }
+            }
+            _ => stripped.push(c)
+        }
+    }
+    stripped
+}
+
+/// The tests registered in one script. Only calls naming the test with string literals are
+/// found; `register("Class:name", ...)` is read like `register("Class", "name", ...)`.
+pub fn find_registered_tests(src: &str, file: &Path) -> Vec<GameTest> {
+    let src = &strip_comments(src);
+    let bytes = src.as_bytes();
+    let mut tests = vec![];
+    let mut start = 0;
+    while let Some(found) = src[start..].find("register") {
+        let at = start + found;
+        start = at + "register".len();
+        if at > 0 && (bytes[at - 1].is_ascii_alphanumeric() || bytes[at - 1] == b'_') {
+            continue;
+        }
+
+        let mut scanner = Scanner { src: bytes, pos: start };
+        let method = scanner.identifier();
+        if (!method.is_empty() && method != "Async") || !scanner.eat(b'(') {
+            continue;
+        }
+        let Some(first) = scanner.string() else {
+            continue;
+        };
+        let name = match first.contains(':') {
+            true => first,
+            false if scanner.eat(b',') => match scanner.string() {
+                Some(test) => format!("{}:{}", first, test),
+                None => continue
+            },
+            false => continue
+        };
+        scanner.skip_arguments();
+
+        // Options are chained onto the call: `.maxTicks(100).structureName("ns:name")`.
+        let mut structure = name.clone();
+        while scanner.eat(b'.') {
+            let option = scanner.identifier().to_string();
+            if !scanner.eat(b'(') {
+                break;
+            }
+            if option == "structureName" {
+                if let Some(name) = scanner.string() {
+                    structure = if name.contains(':') { name } else { format!("{}:{}", DEFAULT_NAMESPACE, name) };
+                }
+            }
+            scanner.skip_arguments();
+        }
+
+        let line = src[..at].matches('\n').count() + 1;
+        tests.push(GameTest { name, structure, file: file.to_path_buf(), line });
+        start = scanner.pos.clamp(start, src.len());
+    }
+    tests
+}
+
+/// Every test the scripts of `pack` register, in file order.
+pub fn registered_tests(pack: &Path) -> Vec<GameTest> {
+    let mut tests = vec![];
+    for path in pack_files(pack, "scripts") {
+        if !path.extension().is_some_and(|ext| ext == "js" || ext == "ts") {
+            continue;
+        }
+        let Ok(src) = fs::read_to_string(&path) else {
+            continue;
+        };
+        let relative = path.strip_prefix(pack).unwrap_or(&path);
+        tests.extend(find_registered_tests(&src, relative));
+    }
+    tests
+}
+
+/// Cross-references the tests the scripts of `pack` register with the `.mcstructure` files in its
+/// `structures` folder: every test needs its structure, test names must be unique, and a structure
+/// in the folder of a test class that no test uses is probably left over from a renamed test.
+/// Structure identifiers are compared ignoring case, like the game looks them up.
+pub fn validate_gametests(pack: &Path) -> Vec<GameTestIssue> {
+    let structures: BTreeMap<String, String> = pack_files(pack, "structures").into_iter()
+        .filter(|path| path.extension().is_some_and(|ext| ext == "mcstructure"))
+        .filter_map(|path| {
+            let identifier = structure_identifier(path.strip_prefix(pack.join("structures")).ok()?)?;
+            Some((identifier.to_ascii_lowercase(), identifier))
+        })
+        .collect();
+
+    let tests = registered_tests(pack);
+    let mut issues = vec![];
+    let mut seen: BTreeMap<String, &GameTest> = BTreeMap::new();
+    for test in &tests {
+        let issue = |kind| GameTestIssue { test: test.name.clone(), structure: test.structure.clone(), file: test.file.clone(), line: test.line, kind };
+        if let Some(first) = seen.get(&test.name) {
+            issues.push(issue(GameTestIssueKind::DuplicateTest { first: first.file.clone(), line: first.line }));
+            continue;
+        }
+        seen.insert(test.name.clone(), test);
+        if !structures.contains_key(&test.structure.to_ascii_lowercase()) {
+            issues.push(issue(GameTestIssueKind::MissingStructure));
+        }
+    }
+
+    let used: HashSet<String> = tests.iter().map(|test| test.structure.to_ascii_lowercase()).collect();
+    let classes: HashSet<String> = tests.iter().filter_map(|test| Some(test.name.split_once(':')?.0.to_ascii_lowercase())).collect();
+    for (key, identifier) in &structures {
+        let class = key.split_once(':').map(|(class, _)| class).unwrap_or_default();
+        if classes.contains(class) && !used.contains(key) {
+            issues.push(GameTestIssue {
+                test: String::new(),
+                structure: identifier.clone(),
+                file: structure_path(identifier),
+                line: 0,
+                kind: GameTestIssueKind::UnusedStructure
+            });
+        }
+    }
+    issues
+}
@@ -0,0 +1,180 @@
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+use serde_json::Value;
+use crate::utils::json_pointer;
+use crate::validation::{read_json_files, string_entries, wildcard_match, JsonFile, ReferenceKind, UnresolvedReference};
+
+fn bone_names(bones: Option<&Value>) -> HashSet<String> {
+    bones.and_then(Value::as_array)
+        .map(|bones| bones.iter().filter_map(|bone| Some(bone.get("name")?.as_str()?.to_string())).collect())
+        .unwrap_or_default()
+}
+
+/// Every geometry identifier defined under `models/` of the given packs, with its bone names.
+///
+/// Both the `minecraft:geometry` list format and the legacy `"geometry.a:geometry.b"` keyed
+/// format are read; legacy geometries inherit the bones of their parent.
+pub fn geometry_bones(resource_packs: &[&Path]) -> HashMap<String, HashSet<String>> {
+    let mut geometries: HashMap<String, HashSet<String>> = HashMap::new();
+    let mut parents = vec![];
+
+    for root in resource_packs {
+        for file in read_json_files(root, "models") {
+            let Value::Object(map) = &file.value else {
+                continue;
+            };
+
+            if let Some(Value::Array(models)) = map.get("minecraft:geometry") {
+                for model in models {
+                    if let Some(identifier) = model.pointer("/description/identifier").and_then(Value::as_str) {
+                        geometries.entry(identifier.to_string()).or_default().extend(bone_names(model.get("bones")));
+                    }
+                }
+            }
+
+            for (key, model) in map.iter().filter(|(key, _)| key.starts_with("geometry.")) {
+                let (identifier, parent) = match key.split_once(':') {
+                    Some((identifier, parent)) => (identifier, Some(parent)),
+                    None => (key.as_str(), None)
+                };
+                geometries.entry(identifier.to_string()).or_default().extend(bone_names(model.get("bones")));
+                if let Some(parent) = parent {
+                    parents.push((identifier.to_string(), parent.to_string()));
+                }
+            }
+        }
+    }
+
+    for (identifier, parent) in parents {
+        let inherited = geometries.get(&parent).cloned().unwrap_or_default();
+        geometries.entry(identifier).or_default().extend(inherited);
+    }
+
+    geometries
+}
+
+fn check_description(file: &JsonFile, root_key: &str, geometries: &HashMap<String, HashSet<String>>, unresolved: &mut Vec<UnresolvedReference>) {
+    let pointer = json_pointer(&[root_key, "description", "geometry"]);
+    for (key, identifier) in string_entries(&file.value, &pointer) {
+        if !geometries.contains_key(identifier) {
+            unresolved.push(UnresolvedReference {
+                kind: ReferenceKind::Geometry,
+                file: file.path.clone(),
+                pointer: format!("{}{}", pointer, json_pointer(&[key])),
+                reference: identifier.to_string()
+            });
+        }
+    }
+}
+
+/// The geometry identifier a block's `minecraft:geometry` component points at, with the pointer
+/// of the field holding it relative to the components object.
+fn block_geometry(components: &Value) -> Option<(&'static str, &str)> {
+    match components.get("minecraft:geometry")? {
+        Value::String(identifier) => Some(("/minecraft:geometry", identifier)),
+        Value::Object(geometry) => Some(("/minecraft:geometry/identifier", geometry.get("identifier")?.as_str()?)),
+        _ => None
+    }
+}
+
+/// Checks that `geometry.*` identifiers used by client entities, attachables and behavior pack
+/// blocks are defined in `models/`, and that the bones named in the `part_visibility` of the
+/// render controllers each client entity uses exist in the geometry the controller selects.
+///
+/// Block references are reported relative to `behavior_pack`; everything else relative to `resource_pack`.
+pub fn validate_geometry_references(resource_pack: &Path, behavior_pack: Option<&Path>, vanilla_resource_pack: Option<&Path>) -> Vec<UnresolvedReference> {
+    let mut roots = vec![resource_pack];
+    roots.extend(vanilla_resource_pack);
+
+    let geometries = geometry_bones(&roots);
+    let mut unresolved = vec![];
+
+    let entities = read_json_files(resource_pack, "entity");
+    for file in &entities {
+        check_description(file, "minecraft:client_entity", &geometries, &mut unresolved);
+    }
+
+    for file in read_json_files(resource_pack, "attachables") {
+        check_description(&file, "minecraft:attachable", &geometries, &mut unresolved);
+    }
+
+    if let Some(behavior_pack) = behavior_pack {
+        for file in read_json_files(behavior_pack, "blocks") {
+            let mut components = vec![("/minecraft:block/components".to_string(), file.value.pointer("/minecraft:block/components"))];
+            if let Some(Value::Array(permutations)) = file.value.pointer("/minecraft:block/permutations") {
+                for (i, permutation) in permutations.iter().enumerate() {
+                    components.push((format!("/minecraft:block/permutations/{}/components", i), permutation.get("components")));
+                }
+            }
+
+            for (pointer, components) in components {
+                let Some((field, identifier)) = components.and_then(block_geometry) else {
+                    continue;
+                };
+                // `minecraft:geometry.full_block` and friends are built into the game.
+                if !identifier.starts_with("minecraft:") && !geometries.contains_key(identifier) {
+                    unresolved.push(UnresolvedReference {
+                        kind: ReferenceKind::Geometry,
+                        file: file.path.clone(),
+                        pointer: format!("{}{}", pointer, field),
+                        reference: identifier.to_string()
+                    });
+                }
+            }
+        }
+    }
+
+    let controllers: Vec<JsonFile> = roots.iter().flat_map(|root| read_json_files(root, "render_controllers")).collect();
+    let mut reported = HashSet::new();
+
+    for entity in &entities {
+        let entity_geometries = string_entries(&entity.value, "/minecraft:client_entity/description/geometry");
+        let Some(Value::Array(used)) = entity.value.pointer("/minecraft:client_entity/description/render_controllers") else {
+            continue;
+        };
+
+        for controller_id in used {
+            // Entries are either plain ids or `{ "id": "condition" }` objects.
+            let Some(controller_id) = controller_id.as_str().or_else(|| controller_id.as_object()?.keys().next().map(String::as_str)) else {
+                continue;
+            };
+
+            for file in &controllers {
+                let Some(controller) = file.value.pointer(&json_pointer(&["render_controllers", controller_id])) else {
+                    continue;
+                };
+
+                // Only direct `Geometry.<name>` selections can be resolved without evaluating Molang.
+                let Some(selected) = controller.get("geometry").and_then(Value::as_str)
+                    .and_then(|geometry| geometry.to_ascii_lowercase().strip_prefix("geometry.").map(str::to_string)) else {
+                    continue;
+                };
+                let Some(bones) = entity_geometries.iter()
+                    .find(|(key, _)| key.eq_ignore_ascii_case(&selected))
+                    .and_then(|(_, identifier)| geometries.get(*identifier)) else {
+                    continue;
+                };
+
+                let Some(Value::Array(part_visibility)) = controller.get("part_visibility") else {
+                    continue;
+                };
+                for (i, entry) in part_visibility.iter().enumerate() {
+                    let Value::Object(entry) = entry else {
+                        continue;
+                    };
+                    for pattern in entry.keys() {
+                        if bones.iter().any(|bone| wildcard_match(pattern, bone)) {
+                            continue;
+                        }
+                        let pointer = format!("{}{}", json_pointer(&["render_controllers", controller_id, "part_visibility"]), json_pointer(&[&i.to_string(), pattern]));
+                        if reported.insert((file.path.clone(), pointer.clone())) {
+                            unresolved.push(UnresolvedReference { kind: ReferenceKind::Bone, file: file.path.clone(), pointer, reference: pattern.clone() });
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    unresolved
+}
@@ -0,0 +1,110 @@
+use std::collections::BTreeMap;
+use std::collections::hash_map::DefaultHasher;
+use std::fs;
+use std::hash::{Hash, Hasher};
+use std::path::{Path, PathBuf};
+use serde_json::Value;
+use uuid::Uuid;
+use crate::utils::{parse_format_version_from_str, SemVer};
+use crate::validation::{pack_files, read_json_file};
+
+/// The folders packs are installed to, in both `com.mojang` and a dedicated server.
+pub const PACK_FOLDERS: [&str; 4] = ["behavior_packs", "development_behavior_packs", "resource_packs", "development_resource_packs"];
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct InstalledPack {
+    /// Relative to the installation root.
+    pub path: PathBuf,
+    pub uuid: Uuid,
+    pub version: SemVer
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum UuidCollisionKind {
+    /// Several versions are installed side by side, and worlds pick whichever their pack list
+    /// names, or none if it names another.
+    DifferentVersions,
+    /// Copies of the same version with different content; which one a world loads is undefined.
+    ConflictingContent,
+    /// Copies of the same version with the same content.
+    IdenticalCopies
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct UuidCollision {
+    pub uuid: Uuid,
+    pub packs: Vec<InstalledPack>,
+    pub kind: UuidCollisionKind
+}
+
+/// Reads the header uuid and version leniently, as an installation can hold broken packs.
+fn installed_pack(root: &Path, path: PathBuf) -> Option<InstalledPack> {
+    let manifest = read_json_file(&root.join(&path), "manifest.json")?.value;
+    let uuid = Uuid::parse_str(manifest.pointer("/header/uuid")?.as_str()?).ok()?;
+    let version = match manifest.pointer("/header/version")? {
+        Value::Array(parts) => match parts.iter().map(|part| part.as_i64().map(|part| part as i32)).collect::<Option<Vec<i32>>>()?.as_slice() {
+            [major, minor, patch] => SemVer::new(*major, *minor, *patch),
+            _ => return None
+        },
+        Value::String(version) => parse_format_version_from_str(version)?,
+        _ => return None
+    };
+    Some(InstalledPack { path, uuid, version })
+}
+
+/// Every pack with a readable manifest in the pack folders of an installation.
+pub fn scan_installed_packs(root: &Path) -> Vec<InstalledPack> {
+    let mut packs = vec![];
+    for folder in PACK_FOLDERS {
+        let Ok(entries) = fs::read_dir(root.join(folder)) else {
+            continue;
+        };
+        let mut names: Vec<PathBuf> = entries.filter_map(|entry| entry.ok())
+            .filter(|entry| entry.path().is_dir())
+            .map(|entry| Path::new(folder).join(entry.file_name()))
+            .collect();
+        names.sort();
+        packs.extend(names.into_iter().filter_map(|path| installed_pack(root, path)));
+    }
+    packs
+}
+
+/// A fingerprint of every file's path and content.
+fn content_hash(pack: &Path) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    for file in pack_files(pack, "") {
+        file.strip_prefix(pack).unwrap_or(&file).hash(&mut hasher);
+        fs::read(&file).unwrap_or_default().hash(&mut hasher);
+    }
+    hasher.finish()
+}
+
+/// Reports packs of an installation, e.g. a `com.mojang` folder, that share a header uuid.
+pub fn find_uuid_collisions(root: &Path) -> Vec<UuidCollision> {
+    let mut by_uuid: BTreeMap<Uuid, Vec<InstalledPack>> = BTreeMap::new();
+    for pack in scan_installed_packs(root) {
+        by_uuid.entry(pack.uuid).or_default().push(pack);
+    }
+
+    let mut collisions = vec![];
+    for (uuid, packs) in by_uuid.into_iter().filter(|(_, packs)| packs.len() > 1) {
+        let mut by_version: BTreeMap<SemVer, Vec<InstalledPack>> = BTreeMap::new();
+        for pack in &packs {
+            by_version.entry(pack.version.clone()).or_default().push(pack.clone());
+        }
+
+        if by_version.len() > 1 {
+            collisions.push(UuidCollision { uuid, packs: packs.clone(), kind: UuidCollisionKind::DifferentVersions });
+        }
+        for copies in by_version.into_values().filter(|copies| copies.len() > 1) {
+            let first = content_hash(&root.join(&copies[0].path));
+            let kind = if copies[1..].iter().all(|copy| content_hash(&root.join(&copy.path)) == first) {
+                UuidCollisionKind::IdenticalCopies
+            } else {
+                UuidCollisionKind::ConflictingContent
+            };
+            collisions.push(UuidCollision { uuid, packs: copies, kind });
+        }
+    }
+    collisions
+}
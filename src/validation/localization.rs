@@ -0,0 +1,149 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use serde_json::Value;
+use crate::generics::lang::parse_lang_from_str;
+use crate::utils::json_pointer;
+use crate::validation::{pack_files, read_json_file, read_json_files};
+
+/// The language every other language is compared against.
+pub const BASE_LANGUAGE: &str = "en_US";
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct LanguageReport {
+    pub language: String,
+    /// Keys of the base language this language doesn't define.
+    pub missing: Vec<String>,
+    /// Keys this language defines that the base language doesn't.
+    pub extra: Vec<String>
+}
+
+/// A localization key used by a definition that no language defines.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct MissingTranslation {
+    /// The referencing file, relative to its pack root.
+    pub file: PathBuf,
+    pub pointer: String,
+    pub key: String
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct LocalizationReport {
+    /// Languages listed in `languages.json` without a `.lang` file.
+    pub missing_files: Vec<String>,
+    /// Every other language with a `.lang` file, compared against [`BASE_LANGUAGE`].
+    pub languages: Vec<LanguageReport>,
+    pub unresolved: Vec<MissingTranslation>
+}
+
+/// Whether a display name is meant as a localization key rather than literal text. The game
+/// shows unknown keys verbatim, so only values shaped like keys are checked.
+fn looks_like_key(value: &str) -> bool {
+    value.contains('.') && !value.contains(char::is_whitespace)
+}
+
+/// Collects the key of every `rawtext` `translate` below `value`.
+fn translate_keys(value: &Value, path: &mut Vec<String>, keys: &mut Vec<(String, String)>) {
+    match value {
+        Value::Object(map) => {
+            for (key, child) in map {
+                path.push(key.clone());
+                match child {
+                    Value::String(translate) if key == "translate" => {
+                        let segments: Vec<&str> = path.iter().map(String::as_str).collect();
+                        keys.push((json_pointer(&segments), translate.clone()));
+                    }
+                    _ => translate_keys(child, path, keys)
+                }
+                path.pop();
+            }
+        }
+        Value::Array(array) => {
+            for (index, child) in array.iter().enumerate() {
+                path.push(index.to_string());
+                translate_keys(child, path, keys);
+                path.pop();
+            }
+        }
+        _ => {}
+    }
+}
+
+/// The localization keys each definition in `pack` relies on, with the JSON pointer of the field
+/// that implies them.
+fn referenced_keys(pack: &Path) -> Vec<(PathBuf, String, String)> {
+    let mut references = vec![];
+
+    for file in read_json_files(pack, "") {
+        let mut keys = vec![];
+
+        if let Some(Value::String(identifier)) = file.value.pointer("/minecraft:entity/description/identifier") {
+            keys.push((json_pointer(&["minecraft:entity", "description", "identifier"]), format!("entity.{}.name", identifier)));
+        }
+        for definition in ["minecraft:item", "minecraft:block"] {
+            let pointer = json_pointer(&[definition, "components", "minecraft:display_name"]);
+            let (pointer, name) = match file.value.pointer(&pointer) {
+                Some(Value::String(name)) => (pointer, name),
+                Some(Value::Object(map)) => match map.get("value") {
+                    Some(Value::String(name)) => (pointer + "/value", name),
+                    _ => continue
+                },
+                _ => continue
+            };
+            if looks_like_key(name) {
+                keys.push((pointer, name.clone()));
+            }
+        }
+        translate_keys(&file.value, &mut vec![], &mut keys);
+
+        references.extend(keys.into_iter().map(|(pointer, key)| (file.path.clone(), pointer, key)));
+    }
+
+    references
+}
+
+/// Checks the `.lang` files in `pack/texts` for completeness against [`BASE_LANGUAGE`], and that
+/// the localization keys used by definitions in `referencing` packs are defined in some language.
+pub fn check_localization(pack: &Path, referencing: &[&Path]) -> LocalizationReport {
+    let mut languages: Vec<String> = match read_json_file(pack, "texts/languages.json") {
+        Some(file) => file.value.as_array()
+            .map(|languages| languages.iter().filter_map(|language| language.as_str().map(str::to_string)).collect())
+            .unwrap_or_default(),
+        None => pack_files(pack, "texts").iter()
+            .filter(|path| path.extension().is_some_and(|ext| ext == "lang"))
+            .filter_map(|path| Some(path.file_stem()?.to_string_lossy().to_string()))
+            .collect()
+    };
+    if !languages.iter().any(|language| language == BASE_LANGUAGE) {
+        languages.insert(0, BASE_LANGUAGE.to_string());
+    }
+
+    let mut missing_files = vec![];
+    let mut translations: BTreeMap<String, BTreeMap<String, String>> = BTreeMap::new();
+    for language in languages {
+        match fs::read_to_string(pack.join("texts").join(format!("{}.lang", language))) {
+            Ok(src) => {
+                translations.insert(language, parse_lang_from_str(&src));
+            }
+            Err(_) => missing_files.push(language)
+        }
+    }
+
+    let base = translations.get(BASE_LANGUAGE).cloned().unwrap_or_default();
+    let reports = translations.iter()
+        .filter(|(language, _)| *language != BASE_LANGUAGE)
+        .map(|(language, entries)| LanguageReport {
+            language: language.clone(),
+            missing: base.keys().filter(|key| !entries.contains_key(*key)).cloned().collect(),
+            extra: entries.keys().filter(|key| !base.contains_key(*key)).cloned().collect()
+        })
+        .collect();
+
+    let unresolved = referencing.iter()
+        .flat_map(|root| referenced_keys(root))
+        .filter(|(_, _, key)| !translations.values().any(|entries| entries.contains_key(key)))
+        .map(|(file, pointer, key)| MissingTranslation { file, pointer, key })
+        .collect();
+
+    LocalizationReport { missing_files, languages: reports, unresolved }
+}
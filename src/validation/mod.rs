@@ -0,0 +1,91 @@
+use std::path::PathBuf;
+use serde_json::Value;
+
+pub use crate::files::{read_json_file, read_json_files, JsonFile};
+pub(crate) use crate::files::{pack_files, read_manifest};
+
+pub mod animations;
+pub mod capabilities;
+pub mod deprecations;
+pub mod duplicates;
+pub mod effects;
+pub mod entity_events;
+pub mod format_versions;
+pub mod gametest;
+pub mod geometry;
+pub mod installation;
+pub mod localization;
+pub mod namespaces;
+pub mod queries;
+pub mod recipes;
+pub mod render_controllers;
+pub mod runner;
+pub mod schema;
+pub mod scripts;
+pub mod sounds;
+pub mod subpacks;
+pub mod texture_constraints;
+pub mod textures;
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum ReferenceKind {
+    TexturePath,
+    /// A `terrain_texture.json` shortname.
+    TextureShortname,
+    Geometry,
+    Bone,
+    Animation,
+    /// A `scripts.animate` name missing from `description.animations`.
+    AnimationShortname,
+    /// A particle effect an animation plays that the entity using it doesn't declare.
+    ParticleShortname,
+    /// A sound effect an animation plays that the entity using it doesn't declare.
+    SoundShortname,
+    SoundDefinition,
+    /// An entity sound event missing from `sounds.json`.
+    SoundEvent,
+    /// A `blocks.json` sound type missing from `sounds.json`.
+    BlockSoundType,
+    /// A `textures_list.json` entry for a texture the pack no longer has.
+    TexturesListEntry
+}
+
+/// A reference in a pack file that doesn't resolve to anything in the pack or the vanilla pack.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct UnresolvedReference {
+    pub kind: ReferenceKind,
+    /// The referencing file, relative to the pack root.
+    pub file: PathBuf,
+    /// JSON pointer of the referencing field.
+    pub pointer: String,
+    pub reference: String
+}
+
+/// The string values of the object at `pointer`, keyed by their property names.
+pub(crate) fn string_entries<'a>(value: &'a Value, pointer: &str) -> Vec<(&'a str, &'a str)> {
+    match value.pointer(pointer) {
+        Some(Value::Object(map)) => map.iter()
+            .filter_map(|(key, value)| Some((key.as_str(), value.as_str()?)))
+            .collect(),
+        _ => vec![]
+    }
+}
+
+/// Matches `name` against a pattern where `*` stands for any run of characters, as used by
+/// `part_visibility` and similar bone selectors.
+pub(crate) fn wildcard_match(pattern: &str, name: &str) -> bool {
+    match pattern.split_once('*') {
+        None => pattern == name,
+        Some((prefix, rest)) => {
+            let Some(remaining) = name.strip_prefix(prefix) else {
+                return false;
+            };
+            if rest.is_empty() {
+                return true;
+            }
+            (0..=remaining.len())
+                .filter(|i| remaining.is_char_boundary(*i))
+                .any(|i| wildcard_match(rest, &remaining[i..]))
+        }
+    }
+}
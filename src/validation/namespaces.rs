@@ -0,0 +1,100 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use serde_json::Value;
+use crate::utils::json_pointer;
+use crate::validation::read_json_files;
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum NamespaceIssueKind {
+    /// New content placed in the `minecraft` namespace, which belongs to the game.
+    Reserved,
+    Missing,
+    /// Characters other than lowercase ASCII letters, digits, `_`, `-` and `.`.
+    InvalidCharacters,
+    /// A namespace other than the configured one.
+    Unexpected { expected: String },
+    /// A namespace other than the one most of the addon uses, when none is configured.
+    Inconsistent { majority: String }
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct NamespaceIssue {
+    /// The defining file, relative to its pack root.
+    pub file: PathBuf,
+    pub pointer: String,
+    pub identifier: String,
+    pub kind: NamespaceIssueKind
+}
+
+fn valid_identifier_part(part: &str) -> bool {
+    !part.is_empty() && part.chars().all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || matches!(c, '_' | '-' | '.'))
+}
+
+/// The `description.identifier` of every definition in `root`, keyed by the definition's
+/// top-level key (`minecraft:entity`, `minecraft:recipe_shaped`, `particle_effect`, ...).
+fn defined_identifiers(root: &Path) -> Vec<(PathBuf, String, String)> {
+    let mut identifiers = vec![];
+    for file in read_json_files(root, "") {
+        let Value::Object(map) = &file.value else {
+            continue;
+        };
+
+        for (key, definition) in map {
+            if let Some(Value::String(identifier)) = definition.pointer("/description/identifier") {
+                identifiers.push((file.path.clone(), json_pointer(&[key, "description", "identifier"]), identifier.clone()));
+            }
+        }
+    }
+    identifiers
+}
+
+/// Lints the namespaces of all content identifiers defined across `packs`.
+///
+/// When `expected_namespace` is `None`, the namespace used by most custom identifiers is taken
+/// as the expected one and deviations are reported as inconsistent.
+pub fn lint_identifier_namespaces(packs: &[&Path], expected_namespace: Option<&str>) -> Vec<NamespaceIssue> {
+    let identifiers: Vec<(PathBuf, String, String)> = packs.iter().flat_map(|root| defined_identifiers(root)).collect();
+
+    let mut counts: HashMap<&str, usize> = HashMap::new();
+    for (_, _, identifier) in &identifiers {
+        if let Some((namespace, _)) = identifier.split_once(':') {
+            if namespace != "minecraft" {
+                *counts.entry(namespace).or_default() += 1;
+            }
+        }
+    }
+    // Ties are broken alphabetically so the result doesn't depend on hash order.
+    let majority = counts.iter()
+        .max_by(|(a, x), (b, y)| x.cmp(y).then(b.cmp(a)))
+        .map(|(namespace, _)| namespace.to_string());
+
+    let mut issues = vec![];
+    for (file, pointer, identifier) in &identifiers {
+        let mut report = |kind| issues.push(NamespaceIssue {
+            file: file.clone(), pointer: pointer.clone(), identifier: identifier.clone(), kind
+        });
+
+        let Some((namespace, name)) = identifier.split_once(':') else {
+            report(NamespaceIssueKind::Missing);
+            continue;
+        };
+        if !valid_identifier_part(namespace) || !valid_identifier_part(name) {
+            report(NamespaceIssueKind::InvalidCharacters);
+        }
+        if namespace == "minecraft" {
+            report(NamespaceIssueKind::Reserved);
+            continue;
+        }
+
+        match (expected_namespace, &majority) {
+            (Some(expected), _) if namespace != expected => {
+                report(NamespaceIssueKind::Unexpected { expected: expected.to_string() })
+            }
+            (None, Some(majority)) if namespace != majority => {
+                report(NamespaceIssueKind::Inconsistent { majority: majority.clone() })
+            }
+            _ => {}
+        }
+    }
+    issues
+}
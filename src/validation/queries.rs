@@ -0,0 +1,54 @@
+use std::path::{Path, PathBuf};
+use serde_json::Value;
+use crate::molang::parser::parse_molang_from_str;
+use crate::molang::queries::{unavailable_queries, QueryContext};
+use crate::utils::json_pointer;
+use crate::validation::read_json_files;
+
+/// A query used in a file whose kind of asset doesn't provide it.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct QueryContextIssue {
+    /// Without its namespace, e.g. `is_baby`.
+    pub query: String,
+    pub context: QueryContext,
+    /// Relative to the pack root.
+    pub file: PathBuf,
+    pub pointer: String
+}
+
+fn check_strings(value: &Value, pointer: String, context: QueryContext, found: &mut Vec<(String, String)>) {
+    match value {
+        Value::String(src) => {
+            if let Ok(program) = parse_molang_from_str(src) {
+                found.extend(unavailable_queries(&program, context).into_iter().map(|query| (pointer.clone(), query)));
+            }
+        }
+        Value::Array(values) => {
+            for (i, value) in values.iter().enumerate() {
+                check_strings(value, format!("{}/{}", pointer, i), context, found);
+            }
+        }
+        Value::Object(map) => {
+            for (key, value) in map {
+                check_strings(value, format!("{}{}", pointer, json_pointer(&[key])), context, found);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Checks every Molang expression in the entity, animation, particle and block files of `pack`
+/// against the queries the kind of asset provides, e.g. entity queries in a particle effect or
+/// `block_state` in an animation. Strings that don't parse as Molang are skipped.
+pub fn validate_query_contexts(pack: &Path) -> Vec<QueryContextIssue> {
+    let mut issues = vec![];
+    for file in read_json_files(pack, "") {
+        let Some(context) = QueryContext::from_path(&file.path.to_string_lossy()) else {
+            continue;
+        };
+        let mut found = vec![];
+        check_strings(&file.value, String::new(), context, &mut found);
+        issues.extend(found.into_iter().map(|(pointer, query)| QueryContextIssue { query, context, file: file.path.clone(), pointer }));
+    }
+    issues
+}
@@ -0,0 +1,221 @@
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use serde_json::Value;
+use crate::validation::read_json_files;
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct RecipeDefinition {
+    /// Relative to the pack root.
+    pub file: PathBuf,
+    /// The recipe's top-level key, e.g. `minecraft:recipe_shaped`.
+    pub kind: String,
+    pub identifier: String,
+    pub priority: i64,
+    pub vanilla: bool
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum RecipeConflictKind {
+    /// Recipes of different kinds sharing an identifier. Same-kind duplicates are reported by
+    /// [`find_duplicate_identifiers`](crate::validation::duplicates::find_duplicate_identifiers).
+    Identifier,
+    /// Recipes that accept the same inputs at the same crafting stations.
+    Inputs { tags: Vec<String> }
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct RecipeConflict {
+    pub kind: RecipeConflictKind,
+    pub recipes: Vec<RecipeDefinition>,
+    /// Index into `recipes` of the recipe that shadows the others, or `None` when the game's
+    /// choice is undefined.
+    pub winner: Option<usize>
+}
+
+/// An item, `item:data` or `#tag`.
+fn ingredient(value: &Value) -> Option<String> {
+    match value {
+        Value::String(item) => Some(item.clone()),
+        Value::Object(map) => {
+            if let Some(Value::String(tag)) = map.get("tag") {
+                return Some(format!("#{}", tag));
+            }
+            let item = map.get("item")?.as_str()?;
+            match map.get("data").and_then(Value::as_i64) {
+                Some(data) if data != 0 => Some(format!("{}:{}", item, data)),
+                _ => Some(item.to_string())
+            }
+        }
+        _ => None
+    }
+}
+
+/// The shaped grid without empty outer rows and columns, as the game matches it anywhere in the
+/// crafting grid.
+fn shaped_grid(definition: &Value) -> Option<Vec<Vec<String>>> {
+    let key = definition.get("key")?.as_object()?;
+    let rows: Vec<Vec<String>> = definition.get("pattern")?.as_array()?.iter()
+        .map(|row| Some(row.as_str()?.chars().map(|c| {
+            key.get(&c.to_string()).and_then(ingredient).unwrap_or_default()
+        }).collect()))
+        .collect::<Option<_>>()?;
+
+    let width = rows.iter().map(Vec::len).max().unwrap_or(0);
+    let mut grid: Vec<Vec<String>> = rows.into_iter().map(|mut row| {
+        row.resize(width, String::new());
+        row
+    }).collect();
+
+    let empty_row = |row: &Vec<String>| row.iter().all(String::is_empty);
+    while grid.last().is_some_and(empty_row) {
+        grid.pop();
+    }
+    while grid.first().is_some_and(empty_row) {
+        grid.remove(0);
+    }
+    while !grid.is_empty() && grid.iter().all(|row| row.last().is_some_and(String::is_empty)) {
+        grid.iter_mut().for_each(|row| { row.pop(); });
+    }
+    while !grid.is_empty() && grid.iter().all(|row| row.first().is_some_and(String::is_empty)) {
+        grid.iter_mut().for_each(|row| { row.remove(0); });
+    }
+    Some(grid)
+}
+
+/// A canonical description of what a recipe consumes, equal for recipes the game can't tell apart.
+fn recipe_inputs(kind: &str, definition: &Value) -> Option<String> {
+    let field = |name: &str| definition.get(name).and_then(ingredient);
+    match kind {
+        "minecraft:recipe_shaped" => {
+            let grid = shaped_grid(definition)?;
+            let print = |grid: &Vec<Vec<String>>| grid.iter().map(|row| row.join(",")).collect::<Vec<_>>().join("/");
+            // Symmetric recipes also match their mirror image, so both describe the same recipe.
+            if definition.get("assume_symmetry").and_then(Value::as_bool).unwrap_or(true) {
+                let mirrored: Vec<Vec<String>> = grid.iter().map(|row| row.iter().rev().cloned().collect()).collect();
+                Some(print(&grid).min(print(&mirrored)))
+            } else {
+                Some(print(&grid))
+            }
+        }
+        "minecraft:recipe_shapeless" => {
+            let mut items = vec![];
+            for value in definition.get("ingredients")?.as_array()? {
+                let count = value.get("count").and_then(Value::as_u64).unwrap_or(1);
+                items.extend(std::iter::repeat_n(ingredient(value)?, count as usize));
+            }
+            items.sort();
+            Some(items.join(","))
+        }
+        "minecraft:recipe_furnace" => field("input"),
+        "minecraft:recipe_brewing_mix" | "minecraft:recipe_brewing_container" => {
+            Some(format!("{}+{}", field("input")?, field("reagent")?))
+        }
+        "minecraft:recipe_smithing_transform" | "minecraft:recipe_smithing_trim" => {
+            Some(format!("{}+{}+{}", field("template")?, field("base")?, field("addition")?))
+        }
+        _ => None
+    }
+}
+
+struct Recipe {
+    definition: RecipeDefinition,
+    tags: Vec<String>,
+    inputs: Option<String>
+}
+
+fn pack_recipes(root: &Path, vanilla: bool) -> Vec<Recipe> {
+    let mut recipes = vec![];
+    for file in read_json_files(root, "recipes") {
+        let Value::Object(map) = &file.value else {
+            continue;
+        };
+
+        for (kind, definition) in map.iter().filter(|(key, _)| key.starts_with("minecraft:recipe_")) {
+            let Some(Value::String(identifier)) = definition.pointer("/description/identifier") else {
+                continue;
+            };
+            let tags = definition.get("tags").and_then(Value::as_array)
+                .map(|tags| tags.iter().filter_map(|tag| tag.as_str().map(str::to_string)).collect())
+                .unwrap_or_default();
+            recipes.push(Recipe {
+                definition: RecipeDefinition {
+                    file: file.path.clone(),
+                    kind: kind.clone(),
+                    identifier: identifier.clone(),
+                    priority: definition.get("priority").and_then(Value::as_i64).unwrap_or(0),
+                    vanilla
+                },
+                tags,
+                inputs: recipe_inputs(kind, definition)
+            });
+        }
+    }
+    recipes
+}
+
+/// The recipe with the lowest priority value, which the game prefers, if there's exactly one.
+fn preferred(recipes: &[RecipeDefinition]) -> Option<usize> {
+    let lowest = recipes.iter().map(|recipe| recipe.priority).min()?;
+    let mut candidates = recipes.iter().enumerate().filter(|(_, recipe)| recipe.priority == lowest);
+    let (winner, _) = candidates.next()?;
+    candidates.next().is_none().then_some(winner)
+}
+
+/// Reports recipes in the behavior pack at `bp` that the game can't tell apart from each other or
+/// from the vanilla recipes in `vanilla`, if given.
+pub fn find_recipe_conflicts(bp: &Path, vanilla: Option<&Path>) -> Vec<RecipeConflict> {
+    let mut recipes = pack_recipes(bp, false);
+    if let Some(vanilla) = vanilla {
+        recipes.extend(pack_recipes(vanilla, true));
+    }
+
+    let mut conflicts = vec![];
+
+    let mut by_identifier: BTreeMap<&str, Vec<&Recipe>> = BTreeMap::new();
+    for recipe in &recipes {
+        by_identifier.entry(&recipe.definition.identifier).or_default().push(recipe);
+    }
+    for group in by_identifier.into_values() {
+        let mut kinds: Vec<&str> = group.iter().map(|recipe| recipe.definition.kind.as_str()).collect();
+        kinds.sort();
+        kinds.dedup();
+        if kinds.len() < 2 || group.iter().all(|recipe| recipe.definition.vanilla) {
+            continue;
+        }
+        let definitions: Vec<RecipeDefinition> = group.iter().map(|recipe| recipe.definition.clone()).collect();
+        // Addon recipes replace vanilla ones with the same identifier.
+        let addon: Vec<usize> = (0..definitions.len()).filter(|i| !definitions[*i].vanilla).collect();
+        let winner = (addon.len() == 1).then(|| addon[0]);
+        conflicts.push(RecipeConflict { kind: RecipeConflictKind::Identifier, recipes: definitions, winner });
+    }
+
+    // Recipes only compete at a crafting station both are registered for.
+    let mut by_inputs: BTreeMap<(&str, &str), BTreeMap<&str, Vec<&Recipe>>> = BTreeMap::new();
+    for recipe in &recipes {
+        let Some(inputs) = &recipe.inputs else {
+            continue;
+        };
+        for tag in &recipe.tags {
+            by_inputs.entry((&recipe.definition.kind, inputs)).or_default().entry(tag).or_default().push(recipe);
+        }
+    }
+    for stations in by_inputs.into_values() {
+        let mut groups: Vec<(Vec<String>, Vec<&Recipe>)> = vec![];
+        for (tag, group) in stations {
+            if group.len() < 2 || group.iter().all(|recipe| recipe.definition.vanilla) {
+                continue;
+            }
+            match groups.iter_mut().find(|(_, existing)| existing.iter().map(|r| &r.definition).eq(group.iter().map(|r| &r.definition))) {
+                Some((tags, _)) => tags.push(tag.to_string()),
+                None => groups.push((vec![tag.to_string()], group))
+            }
+        }
+        for (tags, group) in groups {
+            let definitions: Vec<RecipeDefinition> = group.iter().map(|recipe| recipe.definition.clone()).collect();
+            let winner = preferred(&definitions);
+            conflicts.push(RecipeConflict { kind: RecipeConflictKind::Inputs { tags }, recipes: definitions, winner });
+        }
+    }
+
+    conflicts
+}
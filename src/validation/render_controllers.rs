@@ -0,0 +1,191 @@
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use serde_json::{Map, Value};
+use crate::molang::ast::{Expr, Namespace};
+use crate::molang::optimizer::optimize;
+use crate::molang::{molang_from_value, Molang};
+use crate::utils::json_pointer;
+use crate::validation::{read_json_files, string_entries, JsonFile};
+
+/// The array categories of a render controller, with the namespace of the resources they hold
+/// and the client entity description field declaring those.
+const CATEGORIES: [(&str, Namespace, &str); 3] = [
+    ("textures", Namespace::Texture, "textures"),
+    ("geometries", Namespace::Geometry, "geometry"),
+    ("materials", Namespace::Material, "materials")
+];
+
+#[derive(Clone, Debug, Eq, PartialEq, Hash)]
+pub enum RenderControllerIssueKind {
+    /// A `Texture.`, `Geometry.` or `Material.` name the client entity doesn't declare, in an
+    /// array or used directly.
+    UndefinedResource { resource: String },
+    /// An `Array.` the controller doesn't define for the field using it.
+    UndefinedArray { array: String },
+    /// A constant index outside its array; the game clamps it to the last or first entry.
+    IndexOutOfRange { array: String, index: i64, len: usize }
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct RenderControllerIssue {
+    /// The client entity the controller was resolved for.
+    pub entity: String,
+    pub controller: String,
+    /// The render controller file, relative to the pack root.
+    pub file: PathBuf,
+    pub pointer: String,
+    pub kind: RenderControllerIssueKind
+}
+
+/// The field expressions of a controller that select from the arrays of `category`, with their
+/// pointers relative to the controller.
+fn field_expressions(controller: &Value, category: &str) -> Vec<(String, Molang)> {
+    match category {
+        "geometries" => controller.get("geometry").and_then(molang_from_value).map(|geometry| ("/geometry".to_string(), geometry)).into_iter().collect(),
+        "textures" => controller.get("textures").and_then(Value::as_array).into_iter().flatten().enumerate()
+            .filter_map(|(i, texture)| Some((json_pointer(&["textures", &i.to_string()]), molang_from_value(texture)?)))
+            .collect(),
+        _ => controller.get("materials").and_then(Value::as_array).into_iter().flatten().enumerate()
+            .filter_map(|(i, entry)| entry.as_object().map(|entry| (i, entry)))
+            .flat_map(|(i, entry)| entry.iter().filter_map(move |(bone, material)| Some((json_pointer(&["materials", &i.to_string(), bone]), molang_from_value(material)?))))
+            .collect()
+    }
+}
+
+/// Calls `visit` on `expr` and every expression below it.
+fn walk<'a>(expr: &'a Expr, visit: &mut impl FnMut(&'a Expr)) {
+    visit(expr);
+    for child in expr.children() {
+        walk(child, visit);
+    }
+}
+
+struct Resolver<'a> {
+    entity: &'a str,
+    /// The declared resource names of the entity, lowercase, by namespace.
+    resources: Vec<(Namespace, HashSet<String>)>,
+    file: &'a JsonFile,
+    controller_id: &'a str,
+    issues: Vec<RenderControllerIssue>
+}
+
+impl Resolver<'_> {
+    fn push(&mut self, pointer: &str, kind: RenderControllerIssueKind) {
+        self.issues.push(RenderControllerIssue {
+            entity: self.entity.to_string(),
+            controller: self.controller_id.to_string(),
+            file: self.file.path.clone(),
+            pointer: format!("{}{}", json_pointer(&["render_controllers", self.controller_id]), pointer),
+            kind
+        });
+    }
+
+    fn is_declared(&self, namespace: Namespace, name: &str) -> bool {
+        self.resources.iter().any(|(declared, names)| *declared == namespace && names.contains(name))
+    }
+
+    /// Checks every resource name in `expression`, and every array index if `arrays` gives the
+    /// arrays the expression can select from.
+    fn check_expression(&mut self, pointer: &str, expression: &Molang, arrays: Option<&Map<String, Value>>) {
+        let Ok(program) = expression.parse() else {
+            return;
+        };
+        let program = optimize(&program);
+        let mut found = vec![];
+        for statement in &program.statements {
+            walk(statement, &mut |expr| found.push(expr));
+        }
+
+        for expr in found {
+            match expr {
+                Expr::Name(name) if matches!(name.namespace, Namespace::Texture | Namespace::Geometry | Namespace::Material) => {
+                    let resource = name.path.join(".");
+                    if !self.is_declared(name.namespace, &resource) {
+                        self.push(pointer, RenderControllerIssueKind::UndefinedResource { resource: format!("{}.{}", name.namespace.short_name(), resource) });
+                    }
+                }
+                Expr::Index(array, index) => {
+                    let (Expr::Name(array), Some(arrays)) = (array.as_ref(), arrays) else {
+                        continue;
+                    };
+                    if array.namespace != Namespace::Array {
+                        continue;
+                    }
+                    let name = format!("array.{}", array.path.join("."));
+                    let Some(entries) = arrays.iter().find(|(key, _)| key.eq_ignore_ascii_case(&name)).and_then(|(_, entries)| entries.as_array()) else {
+                        self.push(pointer, RenderControllerIssueKind::UndefinedArray { array: name });
+                        continue;
+                    };
+                    // Only constant indices can be checked without running the game.
+                    if let Expr::Number(index) = index.as_ref() {
+                        let index = index.floor() as i64;
+                        if index < 0 || index >= entries.len() as i64 {
+                            self.push(pointer, RenderControllerIssueKind::IndexOutOfRange { array: name, index, len: entries.len() });
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+}
+
+/// Resolves the arrays of the render controllers each client entity uses against the textures,
+/// geometries and materials the entity declares: every array entry and every resource a field
+/// selects directly must be declared, every `Array.` a field indexes must be defined in the
+/// controller's arrays of the field's kind, and constant indices must be within their array.
+///
+/// Controllers are looked up in `resource_pack` and then `vanilla_resource_pack`; issues the
+/// entity doesn't affect are reported once.
+pub fn validate_render_controllers(resource_pack: &Path, vanilla_resource_pack: Option<&Path>) -> Vec<RenderControllerIssue> {
+    let mut roots = vec![resource_pack];
+    roots.extend(vanilla_resource_pack);
+    let controllers: Vec<JsonFile> = roots.iter().flat_map(|root| read_json_files(root, "render_controllers")).collect();
+
+    let mut issues = vec![];
+    let mut reported = HashSet::new();
+    for entity in read_json_files(resource_pack, "entity") {
+        let Some(description) = entity.value.pointer("/minecraft:client_entity/description") else {
+            continue;
+        };
+        let identifier = description.get("identifier").and_then(Value::as_str).unwrap_or_default();
+        let resources: Vec<(Namespace, HashSet<String>)> = CATEGORIES.iter()
+            .map(|(_, namespace, field)| {
+                let names = string_entries(description, &json_pointer(&[field])).into_iter().map(|(name, _)| name.to_ascii_lowercase()).collect();
+                (*namespace, names)
+            })
+            .collect();
+
+        let used = description.get("render_controllers").and_then(Value::as_array).into_iter().flatten()
+            .filter_map(|controller| controller.as_str().or_else(|| controller.as_object()?.keys().next().map(String::as_str)));
+        for controller_id in used {
+            let Some((file, controller)) = controllers.iter()
+                .find_map(|file| Some((file, file.value.pointer(&json_pointer(&["render_controllers", controller_id]))?))) else {
+                continue;
+            };
+            let mut resolver = Resolver { entity: identifier, resources: resources.clone(), file, controller_id, issues: vec![] };
+
+            for (category, _, _) in CATEGORIES {
+                let arrays = controller.get("arrays").and_then(|arrays| arrays.get(category)).and_then(Value::as_object);
+                for (array, entries) in arrays.into_iter().flatten() {
+                    for (i, entry) in entries.as_array().into_iter().flatten().enumerate() {
+                        if let Some(entry) = molang_from_value(entry) {
+                            resolver.check_expression(&json_pointer(&["arrays", category, array, &i.to_string()]), &entry, None);
+                        }
+                    }
+                }
+                for (pointer, expression) in field_expressions(controller, category) {
+                    resolver.check_expression(&pointer, &expression, Some(arrays.unwrap_or(&Map::new())));
+                }
+            }
+
+            for issue in resolver.issues {
+                let shared = !matches!(issue.kind, RenderControllerIssueKind::UndefinedResource { .. });
+                if !shared || reported.insert((issue.file.clone(), issue.pointer.clone(), issue.kind.clone())) {
+                    issues.push(issue);
+                }
+            }
+        }
+    }
+    issues
+}
@@ -0,0 +1,144 @@
+use std::path::Path;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::thread;
+use std::time::{Duration, Instant};
+use crate::diagnostics::{Diagnostic, Diagnostics};
+use crate::progress::{Progress, Task};
+use crate::validation::animations::validate_animation_references;
+use crate::validation::capabilities::validate_education_features;
+use crate::validation::deprecations::{lint_deprecated_components, DEFAULT_DEPRECATIONS};
+use crate::validation::duplicates::find_duplicate_identifiers;
+use crate::validation::effects::validate_effect_references;
+use crate::validation::entity_events::analyze_entity_events;
+use crate::validation::format_versions::validate_format_versions;
+use crate::validation::gametest::validate_gametests;
+use crate::validation::geometry::validate_geometry_references;
+use crate::validation::localization::check_localization;
+use crate::validation::namespaces::lint_identifier_namespaces;
+use crate::validation::queries::validate_query_contexts;
+use crate::validation::recipes::find_recipe_conflicts;
+use crate::validation::render_controllers::validate_render_controllers;
+use crate::validation::schema::{validate_pack_against_schemas, SchemaSet};
+use crate::validation::scripts::check_script_dependencies;
+use crate::validation::sounds::validate_sound_references;
+use crate::validation::subpacks::validate_subpacks;
+use crate::validation::texture_constraints::validate_texture_constraints;
+use crate::validation::textures::{validate_texture_references, validate_textures_list};
+
+/// The packs of an addon to validate together, and what to validate them against.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct AddonPaths<'a> {
+    pub resource_pack: Option<&'a Path>,
+    pub behavior_pack: Option<&'a Path>,
+    pub vanilla_resource_pack: Option<&'a Path>,
+    pub vanilla_behavior_pack: Option<&'a Path>,
+    pub schemas: Option<&'a SchemaSet>
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct RuleTiming {
+    pub rule: &'static str,
+    pub duration: Duration,
+    pub diagnostics: usize
+}
+
+#[derive(Clone, Debug)]
+pub struct ValidationRun {
+    /// In rule order, regardless of which rule finished first.
+    pub diagnostics: Diagnostics,
+    pub timings: Vec<RuleTiming>
+}
+
+type Rule<'a> = (&'static str, Box<dyn Fn() -> Vec<Diagnostic> + Send + Sync + 'a>);
+type RuleResult = (Vec<Diagnostic>, Duration);
+
+fn into_diagnostics<T: Into<Diagnostic>>(items: Vec<T>) -> Vec<Diagnostic> {
+    items.into_iter().map(Into::into).collect()
+}
+
+fn rules<'a>(addon: &AddonPaths<'a>) -> Vec<Rule<'a>> {
+    let AddonPaths { resource_pack, behavior_pack, vanilla_resource_pack, vanilla_behavior_pack, schemas } = *addon;
+    let packs: Vec<&Path> = behavior_pack.into_iter().chain(resource_pack).collect();
+    let mut rules: Vec<Rule> = vec![];
+
+    if let Some(rp) = resource_pack {
+        rules.push(("texture-references", Box::new(move || into_diagnostics(validate_texture_references(rp, vanilla_resource_pack)))));
+        rules.push(("geometry-references", Box::new(move || into_diagnostics(validate_geometry_references(rp, behavior_pack, vanilla_resource_pack)))));
+        rules.push(("animation-references", Box::new(move || into_diagnostics(validate_animation_references(rp, vanilla_resource_pack)))));
+        rules.push(("effect-references", Box::new(move || into_diagnostics(validate_effect_references(rp)))));
+        rules.push(("render-controllers", Box::new(move || into_diagnostics(validate_render_controllers(rp, vanilla_resource_pack)))));
+        rules.push(("sound-references", Box::new(move || into_diagnostics(validate_sound_references(rp, behavior_pack, vanilla_resource_pack)))));
+        rules.push(("texture-constraints", Box::new(move || into_diagnostics(validate_texture_constraints(rp)))));
+        rules.push(("textures-list", Box::new(move || into_diagnostics(validate_textures_list(rp)))));
+        rules.push(("localization", Box::new(move || {
+            into_diagnostics(check_localization(rp, &behavior_pack.into_iter().collect::<Vec<_>>()).unresolved)
+        })));
+    }
+    if let Some(bp) = behavior_pack {
+        rules.push(("deprecated-components", Box::new(move || into_diagnostics(lint_deprecated_components(bp, &DEFAULT_DEPRECATIONS)))));
+        rules.push(("recipe-conflicts", Box::new(move || into_diagnostics(find_recipe_conflicts(bp, vanilla_behavior_pack)))));
+        rules.push(("script-dependencies", Box::new(move || into_diagnostics(check_script_dependencies(bp)))));
+        rules.push(("entity-events", Box::new(move || into_diagnostics(analyze_entity_events(bp)))));
+        rules.push(("gametests", Box::new(move || into_diagnostics(validate_gametests(bp)))));
+    }
+    for pack in packs.clone() {
+        rules.push(("format-versions", Box::new(move || into_diagnostics(validate_format_versions(pack)))));
+        rules.push(("subpacks", Box::new(move || into_diagnostics(validate_subpacks(pack)))));
+        rules.push(("query-contexts", Box::new(move || into_diagnostics(validate_query_contexts(pack)))));
+        rules.push(("education-features", Box::new(move || into_diagnostics(validate_education_features(pack)))));
+        if let Some(schemas) = schemas {
+            rules.push(("schema", Box::new(move || into_diagnostics(validate_pack_against_schemas(pack, schemas)))));
+        }
+    }
+    if !packs.is_empty() {
+        let namespace_packs = packs.clone();
+        rules.push(("namespaces", Box::new(move || into_diagnostics(lint_identifier_namespaces(&namespace_packs, None)))));
+        rules.push(("duplicate-identifiers", Box::new(move || into_diagnostics(find_duplicate_identifiers(&packs)))));
+    }
+
+    rules
+}
+
+/// Runs every validator that applies to `addon` on up to `threads` threads (at least one), and
+/// collects their findings with the time each one took.
+pub fn validate_addon(addon: &AddonPaths, threads: usize) -> ValidationRun {
+    validate_addon_with_progress(addon, threads, &|_, _, _| {})
+}
+
+/// Like [`validate_addon`], reporting each rule as it finishes.
+pub fn validate_addon_with_progress(addon: &AddonPaths, threads: usize, progress: &dyn Progress) -> ValidationRun {
+    let rules = rules(addon);
+    let next = AtomicUsize::new(0);
+    let done = AtomicUsize::new(0);
+    let results: Mutex<Vec<Option<RuleResult>>> = Mutex::new(vec![None; rules.len()]);
+
+    thread::scope(|scope| {
+        for _ in 0..threads.clamp(1, rules.len().max(1)) {
+            scope.spawn(|| loop {
+                let index = next.fetch_add(1, Ordering::Relaxed);
+                let Some((_, rule)) = rules.get(index) else {
+                    break;
+                };
+                let start = Instant::now();
+                let diagnostics = rule();
+                results.lock().unwrap()[index] = Some((diagnostics, start.elapsed()));
+                progress.progress(Task::Validating, done.fetch_add(1, Ordering::Relaxed) + 1, rules.len());
+            });
+        }
+    });
+
+    let mut diagnostics = Diagnostics::new();
+    let mut timings = vec![];
+    for ((rule, _), result) in rules.iter().zip(results.into_inner().unwrap()) {
+        let (found, duration) = result.unwrap_or_default();
+        timings.push(RuleTiming { rule, duration, diagnostics: found.len() });
+        diagnostics.extend(found);
+    }
+    ValidationRun { diagnostics, timings }
+}
+
+/// Like [`validate_addon`], with one thread per available core.
+pub fn validate_addon_parallel(addon: &AddonPaths) -> ValidationRun {
+    validate_addon(addon, thread::available_parallelism().map(|n| n.get()).unwrap_or(1))
+}
@@ -0,0 +1,227 @@
+use std::path::{Path, PathBuf};
+use serde_json::Value;
+use crate::error::{read_to_string, AddonError, AddonResult};
+use crate::utils::from_json_str;
+use crate::validation::read_json_files;
+
+/// A JSON document that doesn't match its schema.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct SchemaViolation {
+    /// Relative to the pack root.
+    pub file: PathBuf,
+    /// JSON pointer of the offending value.
+    pub pointer: String,
+    pub message: String
+}
+
+/// JSON schemas keyed by the pack folder whose files they describe (e.g. `items`).
+#[derive(Clone, Debug, Default)]
+pub struct SchemaSet {
+    schemas: Vec<(String, Value)>
+}
+
+impl SchemaSet {
+    pub fn new() -> SchemaSet {
+        SchemaSet::default()
+    }
+
+    pub fn insert(&mut self, folder: &str, schema: Value) {
+        self.schemas.push((folder.trim_matches('/').to_string(), schema));
+    }
+
+    /// Loads the schema at `path` for files under `folder`.
+    pub fn load(&mut self, folder: &str, path: &Path) -> AddonResult<()> {
+        let schema = from_json_str(&read_to_string(path)?).map_err(|error| AddonError::json(path, error))?;
+        self.insert(folder, schema);
+        Ok(())
+    }
+}
+
+/// Validates every JSON file of `pack` that lives under a folder with a registered schema.
+pub fn validate_pack_against_schemas(pack: &Path, schemas: &SchemaSet) -> Vec<SchemaViolation> {
+    let mut violations = vec![];
+    for (folder, schema) in &schemas.schemas {
+        for file in read_json_files(pack, folder) {
+            for (pointer, message) in validate_against_schema(&file.value, schema) {
+                violations.push(SchemaViolation { file: file.path.clone(), pointer, message });
+            }
+        }
+    }
+    violations
+}
+
+/// Validates `document` against a JSON schema, returning `(pointer, message)` per violation.
+///
+/// Supports the structural subset of draft-07 used by the community Bedrock schemas: `type`,
+/// `enum`, `const`, `properties`, `required`, `additionalProperties`, `items`, `minItems`,
+/// `maxItems`, `minimum`, `maximum`, `exclusiveMinimum`, `exclusiveMaximum`, `minLength`,
+/// `maxLength`, `allOf`, `anyOf`, `oneOf`, `not` and local `$ref`s. Other keywords are ignored.
+pub fn validate_against_schema(document: &Value, schema: &Value) -> Vec<(String, String)> {
+    let mut violations = vec![];
+    validate(document, schema, schema, String::new(), &mut violations);
+    violations
+}
+
+fn type_name(value: &Value) -> &'static str {
+    match value {
+        Value::Null => "null",
+        Value::Bool(_) => "boolean",
+        Value::Number(n) if n.is_i64() || n.is_u64() => "integer",
+        Value::Number(_) => "number",
+        Value::String(_) => "string",
+        Value::Array(_) => "array",
+        Value::Object(_) => "object"
+    }
+}
+
+fn matches_type(value: &Value, expected: &str) -> bool {
+    let actual = type_name(value);
+    actual == expected || (expected == "number" && actual == "integer")
+}
+
+fn is_valid(document: &Value, schema: &Value, root: &Value) -> bool {
+    let mut violations = vec![];
+    validate(document, schema, root, String::new(), &mut violations);
+    violations.is_empty()
+}
+
+fn validate(document: &Value, schema: &Value, root: &Value, pointer: String, violations: &mut Vec<(String, String)>) {
+    let schema = match schema {
+        Value::Bool(true) => return,
+        Value::Bool(false) => {
+            violations.push((pointer, "no value is allowed here".to_string()));
+            return;
+        }
+        Value::Object(schema) => schema,
+        _ => return
+    };
+
+    if let Some(Value::String(reference)) = schema.get("$ref") {
+        match reference.strip_prefix('#').and_then(|target| root.pointer(target)) {
+            Some(target) => validate(document, target, root, pointer.clone(), violations),
+            None => violations.push((pointer.clone(), format!("unresolvable schema reference '{}'", reference)))
+        }
+    }
+
+    match schema.get("type") {
+        Some(Value::String(expected)) if !matches_type(document, expected) => {
+            violations.push((pointer, format!("expected {}, found {}", expected, type_name(document))));
+            return;
+        }
+        Some(Value::Array(expected)) if !expected.iter().filter_map(Value::as_str).any(|t| matches_type(document, t)) => {
+            let names: Vec<&str> = expected.iter().filter_map(Value::as_str).collect();
+            violations.push((pointer, format!("expected one of {}, found {}", names.join(", "), type_name(document))));
+            return;
+        }
+        _ => {}
+    }
+
+    if let Some(Value::Array(allowed)) = schema.get("enum") {
+        if !allowed.contains(document) {
+            let names: Vec<String> = allowed.iter().map(Value::to_string).collect();
+            violations.push((pointer.clone(), format!("expected one of {}, found {}", names.join(", "), document)));
+        }
+    }
+    if let Some(expected) = schema.get("const") {
+        if expected != document {
+            violations.push((pointer.clone(), format!("expected {}, found {}", expected, document)));
+        }
+    }
+
+    if let Some(Value::Array(all)) = schema.get("allOf") {
+        for sub in all {
+            validate(document, sub, root, pointer.clone(), violations);
+        }
+    }
+    if let Some(Value::Array(any)) = schema.get("anyOf") {
+        if !any.iter().any(|sub| is_valid(document, sub, root)) {
+            violations.push((pointer.clone(), "doesn't match any of the allowed schemas".to_string()));
+        }
+    }
+    if let Some(Value::Array(one)) = schema.get("oneOf") {
+        let matching = one.iter().filter(|sub| is_valid(document, sub, root)).count();
+        if matching != 1 {
+            violations.push((pointer.clone(), format!("must match exactly one schema, matches {}", matching)));
+        }
+    }
+    if let Some(not) = schema.get("not") {
+        if is_valid(document, not, root) {
+            violations.push((pointer.clone(), "matches a disallowed schema".to_string()));
+        }
+    }
+
+    match document {
+        Value::Object(map) => {
+            let properties = schema.get("properties").and_then(Value::as_object);
+            if let Some(Value::Array(required)) = schema.get("required") {
+                for key in required.iter().filter_map(Value::as_str) {
+                    if !map.contains_key(key) {
+                        violations.push((pointer.clone(), format!("missing required property '{}'", key)));
+                    }
+                }
+            }
+            for (key, value) in map {
+                let child = format!("{}/{}", pointer, key.replace('~', "~0").replace('/', "~1"));
+                match (properties.and_then(|properties| properties.get(key)), schema.get("additionalProperties")) {
+                    (Some(sub), _) => validate(value, sub, root, child, violations),
+                    (None, Some(Value::Bool(false))) => violations.push((child, format!("unknown property '{}'", key))),
+                    (None, Some(sub)) => validate(value, sub, root, child, violations),
+                    (None, None) => {}
+                }
+            }
+        }
+        Value::Array(values) => {
+            if let Some(min) = schema.get("minItems").and_then(Value::as_u64) {
+                if (values.len() as u64) < min {
+                    violations.push((pointer.clone(), format!("expected at least {} items, found {}", min, values.len())));
+                }
+            }
+            if let Some(max) = schema.get("maxItems").and_then(Value::as_u64) {
+                if values.len() as u64 > max {
+                    violations.push((pointer.clone(), format!("expected at most {} items, found {}", max, values.len())));
+                }
+            }
+            match schema.get("items") {
+                Some(Value::Array(tuple)) => {
+                    for (i, (value, sub)) in values.iter().zip(tuple).enumerate() {
+                        validate(value, sub, root, format!("{}/{}", pointer, i), violations);
+                    }
+                }
+                Some(sub) => {
+                    for (i, value) in values.iter().enumerate() {
+                        validate(value, sub, root, format!("{}/{}", pointer, i), violations);
+                    }
+                }
+                None => {}
+            }
+        }
+        Value::Number(number) => {
+            let n = number.as_f64().unwrap_or_default();
+            let bounds = [
+                ("minimum", n < schema.get("minimum").and_then(Value::as_f64).unwrap_or(f64::MIN)),
+                ("maximum", n > schema.get("maximum").and_then(Value::as_f64).unwrap_or(f64::MAX)),
+                ("exclusiveMinimum", n <= schema.get("exclusiveMinimum").and_then(Value::as_f64).unwrap_or(f64::MIN)),
+                ("exclusiveMaximum", n >= schema.get("exclusiveMaximum").and_then(Value::as_f64).unwrap_or(f64::MAX))
+            ];
+            for (keyword, violated) in bounds {
+                if violated {
+                    violations.push((pointer.clone(), format!("{} violates {} {}", number, keyword, schema[keyword])));
+                }
+            }
+        }
+        Value::String(s) => {
+            let length = s.chars().count() as u64;
+            if let Some(min) = schema.get("minLength").and_then(Value::as_u64) {
+                if length < min {
+                    violations.push((pointer.clone(), format!("expected at least {} characters, found {}", min, length)));
+                }
+            }
+            if let Some(max) = schema.get("maxLength").and_then(Value::as_u64) {
+                if length > max {
+                    violations.push((pointer.clone(), format!("expected at most {} characters, found {}", max, length)));
+                }
+            }
+        }
+        _ => {}
+    }
+}
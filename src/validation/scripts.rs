@@ -0,0 +1,116 @@
+use std::path::{Path, PathBuf};
+use serde_json::Value;
+use crate::generics::manifest::{ManifestDependency, SCRIPT_MANIFEST_DEPENDENCIES};
+use crate::utils::{json_pointer, SemVer};
+use crate::validation::{read_json_file, read_manifest};
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum ScriptDependencyIssueKind {
+    /// The manifest requests a version the npm dependency doesn't allow, so the scripts are
+    /// written against a different API than the game loads.
+    VersionMismatch { manifest: SemVer, package: String },
+    /// The scripts depend on a game module the manifest doesn't request, so importing it fails
+    /// at runtime.
+    MissingFromManifest { package: String }
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ScriptDependencyIssue {
+    pub module: String,
+    /// Relative to the pack root.
+    pub file: PathBuf,
+    /// JSON pointer of the npm dependency.
+    pub pointer: String,
+    pub kind: ScriptDependencyIssueKind
+}
+
+enum NpmRange {
+    Exact(SemVer),
+    /// `^x.y.z`
+    Caret(SemVer),
+    /// `~x.y.z`
+    Tilde(SemVer),
+    AtLeast(SemVer)
+}
+
+/// Parses the common single-version npm ranges. Beta releases of game modules look like
+/// `1.14.0-beta.1.21.20-stable`, which pins the same version the manifest spells `1.14.0-beta`.
+fn parse_npm_range(src: &str) -> Option<NpmRange> {
+    let src = src.trim();
+    let (constructor, rest): (fn(SemVer) -> NpmRange, &str) = if let Some(rest) = src.strip_prefix('^') {
+        (NpmRange::Caret, rest)
+    } else if let Some(rest) = src.strip_prefix('~') {
+        (NpmRange::Tilde, rest)
+    } else if let Some(rest) = src.strip_prefix(">=") {
+        (NpmRange::AtLeast, rest)
+    } else {
+        (NpmRange::Exact, src.strip_prefix('=').unwrap_or(src))
+    };
+
+    let rest = rest.trim().trim_start_matches('v');
+    let (version, prerelease) = rest.split_once('-').unwrap_or((rest, ""));
+    let parts = version.split('.').map(|part| part.parse::<i32>().ok()).collect::<Option<Vec<i32>>>()?;
+    let [major, minor, patch] = parts.as_slice() else {
+        return None;
+    };
+    Some(constructor(SemVer { major: *major, minor: *minor, patch: *patch, beta: prerelease.starts_with("beta") }))
+}
+
+impl NpmRange {
+    fn allows(&self, version: &SemVer) -> bool {
+        match self {
+            // Beta modules change between versions, so they only ever match exactly.
+            _ if version.beta => matches!(self, NpmRange::Exact(exact) | NpmRange::Caret(exact) | NpmRange::Tilde(exact) if exact == version),
+            NpmRange::Exact(exact) => exact == version,
+            NpmRange::Caret(base) => version >= base && version.major == base.major,
+            NpmRange::Tilde(base) => version >= base && (version.major, version.minor) == (base.major, base.minor),
+            NpmRange::AtLeast(base) => version >= base
+        }
+    }
+}
+
+/// Compares the `@minecraft/*` game modules in the pack's `package.json`, if it has one, with the
+/// script dependencies in its manifest.
+pub fn check_script_dependencies(pack: &Path) -> Vec<ScriptDependencyIssue> {
+    let (Some(package), Some(manifest)) = (read_json_file(pack, "package.json"), read_manifest(pack)) else {
+        return vec![];
+    };
+
+    let mut issues = vec![];
+    for section in ["dependencies", "devDependencies", "peerDependencies"] {
+        let Some(Value::Object(dependencies)) = package.value.get(section) else {
+            continue;
+        };
+
+        for (module, range) in dependencies {
+            let requested = manifest.dependencies.iter().find_map(|dependency| match dependency {
+                ManifestDependency::ScriptDependency(script, version) if script.module_name() == module => Some(version),
+                _ => None
+            });
+            // Libraries such as `@minecraft/math` are bundled with the scripts, not provided by the game.
+            if requested.is_none() && !SCRIPT_MANIFEST_DEPENDENCIES.contains_key(module.as_str()) {
+                continue;
+            }
+            let Some(range_str) = range.as_str() else {
+                continue;
+            };
+
+            let kind = match requested {
+                None => ScriptDependencyIssueKind::MissingFromManifest { package: range_str.to_string() },
+                Some(version) => match parse_npm_range(range_str) {
+                    Some(range) if !range.allows(version) => {
+                        ScriptDependencyIssueKind::VersionMismatch { manifest: version.clone(), package: range_str.to_string() }
+                    }
+                    _ => continue
+                }
+            };
+            issues.push(ScriptDependencyIssue {
+                module: module.clone(),
+                file: package.path.clone(),
+                pointer: json_pointer(&[section, module]),
+                kind
+            });
+        }
+    }
+    issues
+}
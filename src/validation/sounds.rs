@@ -0,0 +1,224 @@
+use std::collections::HashSet;
+use std::path::Path;
+use serde_json::Value;
+use crate::utils::json_pointer;
+use crate::validation::{read_json_file, read_json_files, ReferenceKind, UnresolvedReference};
+
+/// Every sound event name defined in `sounds/sound_definitions.json` of the given packs, in
+/// either the versioned (`sound_definitions` wrapper) or legacy flat layout.
+pub fn sound_definitions(resource_packs: &[&Path]) -> HashSet<String> {
+    let mut names = HashSet::new();
+    for root in resource_packs {
+        let Some(file) = read_json_file(root, "sounds/sound_definitions.json") else {
+            continue;
+        };
+        let definitions = file.value.get("sound_definitions").unwrap_or(&file.value);
+        if let Value::Object(definitions) = definitions {
+            names.extend(definitions.keys().filter(|key| *key != "format_version").cloned());
+        }
+    }
+    names
+}
+
+/// Collects `(pointer, sound)` for every entry of every `events` object in `sounds.json`. Entries
+/// are either a sound name or an object with a `sound` field.
+fn sounds_json_events(value: &Value, pointer: String, events: &mut Vec<(String, String)>) {
+    let Value::Object(map) = value else {
+        return;
+    };
+
+    for (key, child) in map {
+        let child_pointer = format!("{}{}", pointer, json_pointer(&[key]));
+        if key != "events" {
+            sounds_json_events(child, child_pointer, events);
+            continue;
+        }
+
+        let Value::Object(entries) = child else {
+            continue;
+        };
+        for (event, entry) in entries {
+            let event_pointer = format!("{}{}", child_pointer, json_pointer(&[event]));
+            match entry {
+                Value::String(sound) => events.push((event_pointer, sound.clone())),
+                Value::Object(entry) => {
+                    if let Some(Value::String(sound)) = entry.get("sound") {
+                        events.push((format!("{}/sound", event_pointer), sound.clone()));
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+}
+
+/// The entity sound events `sounds.json` declares for `identifier`, including the defaults.
+fn entity_events(sounds_json: &[Value], identifier: &str) -> HashSet<String> {
+    let mut events = HashSet::new();
+    for value in sounds_json {
+        for pointer in ["/entity_sounds/defaults/events".to_string(), format!("/entity_sounds/entities{}/events", json_pointer(&[identifier]))] {
+            if let Some(Value::Object(map)) = value.pointer(&pointer) {
+                events.extend(map.keys().cloned());
+            }
+        }
+    }
+    events
+}
+
+/// The sound name of a `playsound` command, with or without the leading slash.
+fn playsound_target(command: &str) -> Option<&str> {
+    let mut parts = command.trim().trim_start_matches('/').split_whitespace();
+    if parts.next()?.eq_ignore_ascii_case("playsound") {
+        parts.next()
+    } else {
+        None
+    }
+}
+
+/// Checks sound event names used by client entity `sound_effects`, `sounds.json`, behavior pack
+/// entities' `minecraft:ambient_sound_interval` and `playsound` commands in NPC dialogue.
+///
+/// Sound effects, `sounds.json` entries and dialogue commands must name a sound definition, while
+/// ambient sound events must be declared for the entity in `sounds.json`. The `sound` types used by
+/// `blocks.json` are only checked when a vanilla pack is given, as nearly all of them come from it.
+/// Behavior pack findings are reported relative to `behavior_pack`.
+pub fn validate_sound_references(resource_pack: &Path, behavior_pack: Option<&Path>, vanilla_resource_pack: Option<&Path>) -> Vec<UnresolvedReference> {
+    let mut roots = vec![resource_pack];
+    roots.extend(vanilla_resource_pack);
+
+    let definitions = sound_definitions(&roots);
+    let sounds_json: Vec<Value> = roots.iter().filter_map(|root| read_json_file(root, "sounds.json")).map(|file| file.value).collect();
+    let mut unresolved = vec![];
+
+    for file in read_json_files(resource_pack, "entity") {
+        let Some(Value::Object(effects)) = file.value.pointer("/minecraft:client_entity/description/sound_effects") else {
+            continue;
+        };
+        for (shortname, effect) in effects {
+            if let Some(Value::String(sound)) = effect.get("effect") {
+                if !definitions.contains(sound) {
+                    unresolved.push(UnresolvedReference {
+                        kind: ReferenceKind::SoundDefinition,
+                        file: file.path.clone(),
+                        pointer: format!("/minecraft:client_entity/description/sound_effects{}/effect", json_pointer(&[shortname])),
+                        reference: sound.clone()
+                    });
+                }
+            }
+        }
+    }
+
+    if let Some(file) = read_json_file(resource_pack, "sounds.json") {
+        let mut events = vec![];
+        sounds_json_events(&file.value, String::new(), &mut events);
+        for (pointer, sound) in events {
+            // An empty sound name deliberately silences the event.
+            if !sound.is_empty() && !definitions.contains(&sound) {
+                unresolved.push(UnresolvedReference { kind: ReferenceKind::SoundDefinition, file: file.path.clone(), pointer, reference: sound });
+            }
+        }
+    }
+
+    if vanilla_resource_pack.is_some() {
+        if let Some(file) = read_json_file(resource_pack, "blocks.json") {
+            let block_sounds: HashSet<&String> = sounds_json.iter()
+                .filter_map(|value| value.get("block_sounds")?.as_object())
+                .flat_map(|map| map.keys())
+                .collect();
+            if let Value::Object(blocks) = &file.value {
+                for (block, definition) in blocks {
+                    if let Some(Value::String(sound)) = definition.get("sound") {
+                        if !block_sounds.contains(sound) {
+                            unresolved.push(UnresolvedReference {
+                                kind: ReferenceKind::BlockSoundType, file: file.path.clone(), pointer: json_pointer(&[block, "sound"]), reference: sound.clone()
+                            });
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    let Some(behavior_pack) = behavior_pack else {
+        return unresolved;
+    };
+
+    for file in read_json_files(behavior_pack, "entities") {
+        let Some(identifier) = file.value.pointer("/minecraft:entity/description/identifier").and_then(Value::as_str) else {
+            continue;
+        };
+        let declared = entity_events(&sounds_json, identifier);
+
+        let mut components = vec![("/minecraft:entity/components".to_string(), file.value.pointer("/minecraft:entity/components"))];
+        if let Some(Value::Object(groups)) = file.value.pointer("/minecraft:entity/component_groups") {
+            for (name, group) in groups {
+                components.push((format!("/minecraft:entity/component_groups{}", json_pointer(&[name])), Some(group)));
+            }
+        }
+
+        for (pointer, components) in components {
+            let Some(interval) = components.and_then(|components| components.get("minecraft:ambient_sound_interval")) else {
+                continue;
+            };
+            let pointer = format!("{}/minecraft:ambient_sound_interval", pointer);
+
+            let mut names = vec![];
+            match interval.get("event_name") {
+                Some(Value::String(name)) => names.push((format!("{}/event_name", pointer), name.as_str())),
+                // The component plays `ambient` unless told otherwise.
+                None => names.push((pointer.clone(), "ambient")),
+                _ => {}
+            }
+            if let Some(Value::Array(choices)) = interval.get("event_names") {
+                for (i, choice) in choices.iter().enumerate() {
+                    if let Some(Value::String(name)) = choice.get("event_name") {
+                        names.push((format!("{}/event_names/{}/event_name", pointer, i), name));
+                    }
+                }
+            }
+
+            for (pointer, name) in names {
+                if !declared.contains(name) {
+                    unresolved.push(UnresolvedReference { kind: ReferenceKind::SoundEvent, file: file.path.clone(), pointer, reference: name.to_string() });
+                }
+            }
+        }
+    }
+
+    for file in read_json_files(behavior_pack, "dialogue") {
+        let Some(Value::Array(scenes)) = file.value.pointer("/minecraft:npc_dialogue/scenes") else {
+            continue;
+        };
+
+        for (i, scene) in scenes.iter().enumerate() {
+            let scene_pointer = format!("/minecraft:npc_dialogue/scenes/{}", i);
+            let mut command_lists = vec![];
+            for key in ["on_open_commands", "on_close_commands"] {
+                command_lists.push((format!("{}/{}", scene_pointer, key), scene.get(key)));
+            }
+            if let Some(Value::Array(buttons)) = scene.get("buttons") {
+                for (j, button) in buttons.iter().enumerate() {
+                    command_lists.push((format!("{}/buttons/{}/commands", scene_pointer, j), button.get("commands")));
+                }
+            }
+
+            for (pointer, commands) in command_lists {
+                let Some(Value::Array(commands)) = commands else {
+                    continue;
+                };
+                for (k, command) in commands.iter().enumerate() {
+                    let Some(sound) = command.as_str().and_then(playsound_target) else {
+                        continue;
+                    };
+                    if !definitions.contains(sound) {
+                        unresolved.push(UnresolvedReference {
+                            kind: ReferenceKind::SoundDefinition, file: file.path.clone(), pointer: format!("{}/{}", pointer, k), reference: sound.to_string()
+                        });
+                    }
+                }
+            }
+        }
+    }
+
+    unresolved
+}
@@ -0,0 +1,113 @@
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+use crate::utils::json_pointer;
+use crate::validation::{pack_files, read_manifest};
+
+/// Top-level entries of a subpack folder the game never reads from it.
+const NOT_OVERRIDABLE: [&str; 3] = ["manifest.json", "pack_icon.png", "subpacks"];
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum SubpackIssueKind {
+    /// The manifest declares a subpack whose folder doesn't exist.
+    MissingFolder,
+    /// A folder in `subpacks` no subpack declares.
+    UndeclaredFolder,
+    /// Two subpacks declare the same folder.
+    DuplicateFolder,
+    /// A file a subpack can't override, such as its own manifest.
+    NotOverridable,
+    /// A subpack file with no base pack file of the same path to replace.
+    NoBaseFile,
+    NegativeMemoryTier { memory_tier: i32 },
+    /// A subpack requiring less memory than the one before it, which the settings slider shows
+    /// in manifest order.
+    UnorderedMemoryTier { memory_tier: i32, previous: i32 }
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct SubpackIssue {
+    pub folder_name: String,
+    /// Relative to the pack root: `manifest.json` or a file in the subpack.
+    pub file: PathBuf,
+    /// JSON pointer into the manifest, empty for subpack files.
+    pub pointer: String,
+    pub kind: SubpackIssueKind
+}
+
+/// Checks the subpacks a pack's manifest declares against the contents of its `subpacks` folder.
+pub fn validate_subpacks(pack: &Path) -> Vec<SubpackIssue> {
+    let Some(manifest) = read_manifest(pack) else {
+        return vec![];
+    };
+
+    let mut issues = vec![];
+    let mut declared = HashSet::new();
+    let mut previous_tier: Option<i32> = None;
+
+    for (i, subpack) in manifest.subpacks.iter().enumerate() {
+        let issue = |field: &str, kind| SubpackIssue {
+            folder_name: subpack.folder_name.clone(),
+            file: PathBuf::from("manifest.json"),
+            pointer: json_pointer(&["subpacks", &i.to_string(), field]),
+            kind
+        };
+
+        if !declared.insert(subpack.folder_name.as_str()) {
+            issues.push(issue("folder_name", SubpackIssueKind::DuplicateFolder));
+            continue;
+        }
+
+        if subpack.memory_tier < 0 {
+            issues.push(issue("memory_tier", SubpackIssueKind::NegativeMemoryTier { memory_tier: subpack.memory_tier }));
+        } else {
+            if let Some(previous) = previous_tier.filter(|previous| *previous > subpack.memory_tier) {
+                issues.push(issue("memory_tier", SubpackIssueKind::UnorderedMemoryTier { memory_tier: subpack.memory_tier, previous }));
+            }
+            previous_tier = Some(subpack.memory_tier);
+        }
+
+        let folder = format!("subpacks/{}", subpack.folder_name);
+        if !pack.join(&folder).is_dir() {
+            issues.push(issue("folder_name", SubpackIssueKind::MissingFolder));
+            continue;
+        }
+
+        for path in pack_files(pack, &folder) {
+            let Ok(relative) = path.strip_prefix(pack.join(&folder)) else {
+                continue;
+            };
+            let top = relative.components().next().map(|component| component.as_os_str().to_string_lossy().to_string()).unwrap_or_default();
+            let kind = if NOT_OVERRIDABLE.contains(&top.as_str()) {
+                SubpackIssueKind::NotOverridable
+            } else if !pack.join(relative).is_file() {
+                SubpackIssueKind::NoBaseFile
+            } else {
+                continue;
+            };
+            issues.push(SubpackIssue {
+                folder_name: subpack.folder_name.clone(),
+                file: Path::new(&folder).join(relative),
+                pointer: String::new(),
+                kind
+            });
+        }
+    }
+
+    if let Ok(entries) = fs::read_dir(pack.join("subpacks")) {
+        let mut folders: Vec<String> = entries.filter_map(|entry| entry.ok())
+            .filter(|entry| entry.path().is_dir())
+            .map(|entry| entry.file_name().to_string_lossy().to_string())
+            .filter(|folder| !declared.contains(folder.as_str()))
+            .collect();
+        folders.sort();
+        issues.extend(folders.into_iter().map(|folder| SubpackIssue {
+            file: PathBuf::from("subpacks").join(&folder),
+            folder_name: folder,
+            pointer: String::new(),
+            kind: SubpackIssueKind::UndeclaredFolder
+        }));
+    }
+
+    issues
+}
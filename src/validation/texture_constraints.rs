@@ -0,0 +1,168 @@
+use std::collections::{BTreeMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use serde_json::Value;
+use crate::utils::json_pointer;
+use crate::validation::read_json_file;
+use crate::validation::textures::{atlas_paths, texture_file, ATLASES};
+
+/// The largest pack icon worth shipping; the game never shows it bigger.
+pub const PACK_ICON_MAX_SIZE: u32 = 256;
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum TextureIssueKind {
+    /// An atlas tile with a different size than most tiles of the same atlas.
+    InconsistentTileSize { expected: (u32, u32) },
+    /// An atlas tile whose width or height isn't a power of two, which breaks mipmapping.
+    NotPowerOfTwo,
+    /// A flipbook whose height isn't a whole number of square frames.
+    FlipbookHeight,
+    /// A flipbook `frames` index past the frames the image has.
+    FlipbookFrameOutOfRange { frame: u64, frames: u32 },
+    PackIconNotSquare,
+    PackIconTooLarge { max: u32 }
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct TextureIssue {
+    /// The file referencing the texture, relative to the pack root.
+    pub file: PathBuf,
+    pub pointer: String,
+    pub texture: String,
+    pub size: (u32, u32),
+    pub kind: TextureIssueKind
+}
+
+fn be_u16(bytes: &[u8], at: usize) -> Option<u32> {
+    Some(u16::from_be_bytes(bytes.get(at..at + 2)?.try_into().ok()?) as u32)
+}
+
+/// Reads the width and height from a PNG, JPEG or TGA header without decoding the image.
+pub fn image_dimensions(path: &Path) -> Option<(u32, u32)> {
+    let bytes = fs::read(path).ok()?;
+
+    if bytes.starts_with(b"\x89PNG\r\n\x1a\n") && bytes.get(12..16)? == b"IHDR" {
+        let width = u32::from_be_bytes(bytes.get(16..20)?.try_into().ok()?);
+        let height = u32::from_be_bytes(bytes.get(20..24)?.try_into().ok()?);
+        return Some((width, height));
+    }
+
+    if bytes.starts_with(&[0xFF, 0xD8]) {
+        let mut at = 2;
+        while bytes.get(at)? == &0xFF {
+            let marker = *bytes.get(at + 1)?;
+            // Start-of-frame markers, leaving out DHT, JPG and DAC which share the range.
+            if (0xC0..=0xCF).contains(&marker) && ![0xC4, 0xC8, 0xCC].contains(&marker) {
+                return Some((be_u16(&bytes, at + 7)?, be_u16(&bytes, at + 5)?));
+            }
+            at += 2 + be_u16(&bytes, at + 2)? as usize;
+        }
+        return None;
+    }
+
+    // TGA has no signature, so go by the extension.
+    if path.extension().is_some_and(|ext| ext.eq_ignore_ascii_case("tga")) {
+        let width = u16::from_le_bytes(bytes.get(12..14)?.try_into().ok()?) as u32;
+        let height = u16::from_le_bytes(bytes.get(14..16)?.try_into().ok()?) as u32;
+        return Some((width, height));
+    }
+
+    None
+}
+
+/// The flipbook textures of `flipbook_textures.json`, with their JSON pointers.
+fn flipbooks(pack: &Path) -> Vec<(usize, String, Value)> {
+    let Some(file) = read_json_file(pack, "textures/flipbook_textures.json") else {
+        return vec![];
+    };
+    let Value::Array(entries) = file.value else {
+        return vec![];
+    };
+    entries.into_iter().enumerate()
+        .filter_map(|(i, entry)| Some((i, entry.get("flipbook_texture")?.as_str()?.to_string(), entry)))
+        .collect()
+}
+
+/// Checks the sizes of atlas tiles, flipbooks and the pack icon of a resource pack.
+pub fn validate_texture_constraints(resource_pack: &Path) -> Vec<TextureIssue> {
+    let mut issues = vec![];
+    let flipbooks = flipbooks(resource_pack);
+    let animated: HashSet<&str> = flipbooks.iter().map(|(_, path, _)| path.as_str()).collect();
+
+    for atlas in ATLASES {
+        let Some(file) = read_json_file(resource_pack, atlas) else {
+            continue;
+        };
+        let mut paths = vec![];
+        if let Some(Value::Object(data)) = file.value.get("texture_data") {
+            for (shortname, entry) in data {
+                if let Some(textures) = entry.get("textures") {
+                    atlas_paths(textures, json_pointer(&["texture_data", shortname, "textures"]), &mut paths);
+                }
+            }
+        }
+
+        let mut tiles = vec![];
+        for (pointer, path) in paths {
+            let Some(size) = texture_file(&[resource_pack], &path).and_then(|file| image_dimensions(&file)) else {
+                continue;
+            };
+            // A flipbook's frames are stacked vertically; one frame takes up one tile.
+            let tile = if animated.contains(path.as_str()) { (size.0, size.0) } else { size };
+            if !tile.0.is_power_of_two() || !tile.1.is_power_of_two() {
+                issues.push(TextureIssue { file: file.path.clone(), pointer: pointer.clone(), texture: path.clone(), size, kind: TextureIssueKind::NotPowerOfTwo });
+            }
+            tiles.push((pointer, path, size, tile));
+        }
+
+        let mut counts: BTreeMap<(u32, u32), usize> = BTreeMap::new();
+        for (_, _, _, tile) in &tiles {
+            *counts.entry(*tile).or_default() += 1;
+        }
+        // Ties go to the smaller size, as the map iterates in ascending order.
+        let Some(expected) = counts.iter().rev().max_by_key(|(_, count)| **count).map(|(tile, _)| *tile) else {
+            continue;
+        };
+        for (pointer, texture, size, tile) in tiles {
+            if tile != expected {
+                issues.push(TextureIssue { file: file.path.clone(), pointer, texture, size, kind: TextureIssueKind::InconsistentTileSize { expected } });
+            }
+        }
+    }
+
+    let flipbook_file = PathBuf::from("textures/flipbook_textures.json");
+    for (i, path, entry) in &flipbooks {
+        let Some(size) = texture_file(&[resource_pack], path).and_then(|file| image_dimensions(&file)) else {
+            continue;
+        };
+        let issue = |pointer: String, kind| TextureIssue { file: flipbook_file.clone(), pointer, texture: path.clone(), size, kind };
+        if size.0 == 0 || size.1 % size.0 != 0 {
+            issues.push(issue(json_pointer(&[&i.to_string(), "flipbook_texture"]), TextureIssueKind::FlipbookHeight));
+            continue;
+        }
+        let frames = size.1 / size.0;
+        if let Some(Value::Array(indices)) = entry.get("frames") {
+            for (j, frame) in indices.iter().enumerate() {
+                match frame.as_u64() {
+                    Some(frame) if frame >= frames as u64 => issues.push(issue(
+                        json_pointer(&[&i.to_string(), "frames", &j.to_string()]),
+                        TextureIssueKind::FlipbookFrameOutOfRange { frame, frames }
+                    )),
+                    _ => {}
+                }
+            }
+        }
+    }
+
+    if let Some(size) = image_dimensions(&resource_pack.join("pack_icon.png")) {
+        let issue = |kind| TextureIssue { file: PathBuf::from("pack_icon.png"), pointer: String::new(), texture: "pack_icon.png".to_string(), size, kind };
+        if size.0 != size.1 {
+            issues.push(issue(TextureIssueKind::PackIconNotSquare));
+        }
+        if size.0.max(size.1) > PACK_ICON_MAX_SIZE {
+            issues.push(issue(TextureIssueKind::PackIconTooLarge { max: PACK_ICON_MAX_SIZE }));
+        }
+    }
+
+    issues
+}
@@ -0,0 +1,150 @@
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use serde_json::Value;
+use crate::utils::json_pointer;
+use crate::validation::{read_json_file, read_json_files, string_entries, JsonFile, ReferenceKind, UnresolvedReference};
+
+pub const TEXTURE_EXTENSIONS: [&str; 4] = ["png", "tga", "jpg", "jpeg"];
+
+pub(crate) const ATLASES: [&str; 2] = ["textures/terrain_texture.json", "textures/item_texture.json"];
+
+/// The image file a texture path refers to, which usually omits the extension.
+pub(crate) fn texture_file(roots: &[&Path], path: &str) -> Option<PathBuf> {
+    roots.iter().find_map(|root| {
+        std::iter::once(root.join(path))
+            .chain(TEXTURE_EXTENSIONS.iter().map(|ext| root.join(format!("{}.{}", path, ext))))
+            .find(|file| file.is_file())
+    })
+}
+
+fn texture_exists(roots: &[&Path], path: &str) -> bool {
+    texture_file(roots, path).is_some()
+}
+
+/// Collects the texture paths of a `texture_data` entry, which may be a path, an object with a
+/// `path`, a list of either, or an object with `variations`.
+pub(crate) fn atlas_paths(value: &Value, pointer: String, paths: &mut Vec<(String, String)>) {
+    match value {
+        Value::String(path) => paths.push((pointer, path.clone())),
+        Value::Array(values) => {
+            for (i, value) in values.iter().enumerate() {
+                atlas_paths(value, format!("{}/{}", pointer, i), paths);
+            }
+        }
+        Value::Object(map) => {
+            if let Some(Value::String(path)) = map.get("path") {
+                paths.push((format!("{}/path", pointer), path.clone()));
+            }
+            if let Some(variations) = map.get("variations") {
+                atlas_paths(variations, format!("{}/variations", pointer), paths);
+            }
+        }
+        _ => {}
+    }
+}
+
+fn atlas_shortnames(roots: &[&Path]) -> HashSet<String> {
+    roots.iter()
+        .filter_map(|root| read_json_file(root, ATLASES[0]))
+        .filter_map(|file| file.value.get("texture_data")?.as_object().map(|data| data.keys().cloned().collect::<Vec<_>>()))
+        .flatten()
+        .collect()
+}
+
+fn check_paths(file: &JsonFile, paths: Vec<(String, String)>, roots: &[&Path], unresolved: &mut Vec<UnresolvedReference>) {
+    for (pointer, path) in paths {
+        // `atlas.terrain`/`atlas.items` name the generated atlases rather than files.
+        if !path.starts_with("atlas.") && !texture_exists(roots, &path) {
+            unresolved.push(UnresolvedReference { kind: ReferenceKind::TexturePath, file: file.path.clone(), pointer, reference: path });
+        }
+    }
+}
+
+fn description_textures(file: &JsonFile, root_key: &str) -> Vec<(String, String)> {
+    let pointer = json_pointer(&[root_key, "description", "textures"]);
+    string_entries(&file.value, &pointer).into_iter()
+        .map(|(key, path)| (format!("{}{}", pointer, json_pointer(&[key])), path.to_string()))
+        .collect()
+}
+
+/// Checks the texture paths and atlas shortnames referenced by client entities, attachables,
+/// particles, `blocks.json`, `item_texture.json` and `terrain_texture.json`.
+///
+/// References are resolved against the pack and, when given, an extracted vanilla resource pack.
+pub fn validate_texture_references(resource_pack: &Path, vanilla_resource_pack: Option<&Path>) -> Vec<UnresolvedReference> {
+    let mut roots = vec![resource_pack];
+    roots.extend(vanilla_resource_pack);
+
+    let mut unresolved = vec![];
+
+    for file in read_json_files(resource_pack, "entity") {
+        check_paths(&file, description_textures(&file, "minecraft:client_entity"), &roots, &mut unresolved);
+    }
+
+    for file in read_json_files(resource_pack, "attachables") {
+        check_paths(&file, description_textures(&file, "minecraft:attachable"), &roots, &mut unresolved);
+    }
+
+    for file in read_json_files(resource_pack, "particles") {
+        let pointer = "/particle_effect/description/basic_render_parameters/texture";
+        if let Some(Value::String(path)) = file.value.pointer(pointer) {
+            check_paths(&file, vec![(pointer.to_string(), path.clone())], &roots, &mut unresolved);
+        }
+    }
+
+    for atlas in ATLASES {
+        if let Some(file) = read_json_file(resource_pack, atlas) {
+            let mut paths = vec![];
+            if let Some(Value::Object(data)) = file.value.get("texture_data") {
+                for (shortname, entry) in data {
+                    if let Some(textures) = entry.get("textures") {
+                        atlas_paths(textures, json_pointer(&["texture_data", shortname, "textures"]), &mut paths);
+                    }
+                }
+            }
+            check_paths(&file, paths, &roots, &mut unresolved);
+        }
+    }
+
+    if let Some(file) = read_json_file(resource_pack, "blocks.json") {
+        let shortnames = atlas_shortnames(&roots);
+        let Value::Object(blocks) = &file.value else {
+            return unresolved;
+        };
+
+        for (block, definition) in blocks {
+            for key in ["textures", "carried_textures"] {
+                let references: Vec<(String, &str)> = match definition.get(key) {
+                    Some(Value::String(shortname)) => vec![(json_pointer(&[block, key]), shortname)],
+                    Some(Value::Object(faces)) => faces.iter()
+                        .filter_map(|(face, shortname)| Some((json_pointer(&[block, key, face]), shortname.as_str()?)))
+                        .collect(),
+                    _ => vec![]
+                };
+
+                for (pointer, shortname) in references {
+                    if !shortnames.contains(shortname) {
+                        unresolved.push(UnresolvedReference { kind: ReferenceKind::TextureShortname, file: file.path.clone(), pointer, reference: shortname.to_string() });
+                    }
+                }
+            }
+        }
+    }
+
+    unresolved
+}
+
+/// Checks that every entry of `textures/textures_list.json` is a texture of the pack.
+pub fn validate_textures_list(resource_pack: &Path) -> Vec<UnresolvedReference> {
+    let Some(file) = read_json_file(resource_pack, "textures/textures_list.json") else {
+        return vec![];
+    };
+    let Value::Array(entries) = &file.value else {
+        return vec![];
+    };
+    entries.iter().enumerate()
+        .filter_map(|(i, entry)| Some((i, entry.as_str()?)))
+        .filter(|(_, path)| !texture_exists(&[resource_pack], path))
+        .map(|(i, path)| UnresolvedReference { kind: ReferenceKind::TexturesListEntry, file: file.path.clone(), pointer: format!("/{}", i), reference: path.to_string() })
+        .collect()
+}
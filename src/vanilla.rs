@@ -0,0 +1,84 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use serde_json::Value;
+use crate::addon::{Addon, PackKind};
+use crate::error::{AddonError, AddonResult};
+use crate::files::read_json_file;
+use crate::utils::{try_parse_semver_from_vec, SemVer};
+#[cfg(feature = "validation")]
+use crate::validation::runner::AddonPaths;
+
+/// The vanilla packs Mojang publishes, e.g. in a checkout of `bedrock-samples` or from the
+/// extracted vanilla pack archives, to resolve references against real vanilla content.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct VanillaPacks {
+    pub behavior_pack: Option<PathBuf>,
+    pub resource_pack: Option<PathBuf>,
+    /// The game version the packs are from, per the header of their manifest.
+    pub version: Option<SemVer>
+}
+
+/// Whether the folder holds a behavior or resource pack, going by the module types in its
+/// manifest. Vanilla manifests lack fields the typed [`Manifest`](crate::generics::manifest::Manifest)
+/// requires, so this reads the raw JSON.
+fn pack_kind(root: &Path) -> Option<(PackKind, Option<SemVer>)> {
+    let manifest = read_json_file(root, "manifest.json")?.value;
+    let types: Vec<&str> = manifest.get("modules")?.as_array()?.iter().filter_map(|module| module.get("type")?.as_str()).collect();
+    let kind = if types.contains(&"data") {
+        PackKind::Behavior
+    } else if types.contains(&"resources") {
+        PackKind::Resource
+    } else {
+        return None;
+    };
+    let version: Option<Vec<i32>> = manifest.pointer("/header/version").and_then(Value::as_array)
+        .map(|parts| parts.iter().filter_map(|part| part.as_i64().map(|part| part as i32)).collect());
+    Some((kind, version.and_then(|version| try_parse_semver_from_vec(&version))))
+}
+
+impl VanillaPacks {
+    /// Finds the vanilla packs below `root`: the `behavior_pack` and `resource_pack` folders of a
+    /// `bedrock-samples` checkout, or any folder directly below `root` (or `root` itself) whose
+    /// manifest has a `data` or `resources` module, like the extracted `Vanilla_Behavior_Pack`
+    /// archives. Fails if it finds neither pack.
+    pub fn locate(root: &Path) -> AddonResult<VanillaPacks> {
+        let mut candidates = vec![root.join("behavior_pack"), root.join("resource_pack"), root.to_path_buf()];
+        if let Ok(entries) = fs::read_dir(root) {
+            let mut folders: Vec<PathBuf> = entries.filter_map(|entry| Some(entry.ok()?.path())).filter(|path| path.is_dir()).collect();
+            folders.sort();
+            candidates.extend(folders);
+        }
+
+        let mut packs = VanillaPacks::default();
+        for candidate in candidates {
+            let Some((kind, version)) = pack_kind(&candidate) else {
+                continue;
+            };
+            let slot = match kind {
+                PackKind::Behavior => &mut packs.behavior_pack,
+                PackKind::Resource => &mut packs.resource_pack
+            };
+            if slot.is_none() {
+                *slot = Some(candidate);
+                packs.version = packs.version.max(version);
+            }
+        }
+        if packs.behavior_pack.is_none() && packs.resource_pack.is_none() {
+            return Err(AddonError::semantic(root, "no vanilla behavior or resource pack found"));
+        }
+        Ok(packs)
+    }
+
+    /// The packs as an addon, to walk or query like any other.
+    pub fn addon(&self) -> Addon {
+        Addon::new(self.behavior_pack.clone(), self.resource_pack.clone())
+    }
+}
+
+#[cfg(feature = "validation")]
+impl<'a> AddonPaths<'a> {
+    /// Resolves references against `vanilla` in the validators that support it.
+    pub fn with_vanilla(self, vanilla: &'a VanillaPacks) -> AddonPaths<'a> {
+        AddonPaths { vanilla_behavior_pack: vanilla.behavior_pack.as_deref(), vanilla_resource_pack: vanilla.resource_pack.as_deref(), ..self }
+    }
+}